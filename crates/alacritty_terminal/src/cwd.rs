@@ -0,0 +1,134 @@
+//! Tracking of the working directory reported by the shell through OSC 7.
+
+/// Longest OSC 7 payload which is accepted.
+const MAX_PAYLOAD_LEN: usize = 4096;
+
+const OSC7_PREFIX: &[u8] = b"7;";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+/// Scanner extracting `OSC 7 ; file://host/path ST` sequences from the PTY output.
+///
+/// The sequence is not handled by the ANSI parser, so the raw bytes are scanned
+/// alongside it. Sequences split across reads are supported.
+#[derive(Debug, Default)]
+pub(crate) struct Osc7Scanner {
+    state: State,
+    payload: Vec<u8>,
+}
+
+impl Osc7Scanner {
+    /// Feed bytes read from the PTY, returns the last directory they reported.
+    pub(crate) fn advance(&mut self, bytes: &[u8]) -> Option<String> {
+        let mut directory = None;
+        for &byte in bytes {
+            self.state = match (self.state, byte) {
+                (State::Ground, 0x1b) | (State::Escape, 0x1b) => State::Escape,
+                (State::Escape, b']') | (State::OscEscape, b']') => {
+                    self.payload.clear();
+                    State::Osc
+                }
+                (State::Osc, 0x07) | (State::OscEscape, b'\\') => {
+                    directory = self.finish().or(directory);
+                    State::Ground
+                }
+                (State::Osc, 0x1b) => State::OscEscape,
+                (State::Osc, _) => {
+                    let len = self.payload.len();
+                    // Bail out early on any other OSC.
+                    let is_osc7 = len >= OSC7_PREFIX.len() || OSC7_PREFIX[len] == byte;
+                    if is_osc7 && len < MAX_PAYLOAD_LEN {
+                        self.payload.push(byte);
+                        State::Osc
+                    } else {
+                        State::Ground
+                    }
+                }
+                _ => State::Ground,
+            };
+        }
+        directory
+    }
+
+    fn finish(&mut self) -> Option<String> {
+        let url = self.payload.strip_prefix(OSC7_PREFIX)?;
+        let url = String::from_utf8_lossy(url);
+        parse_file_url(&url)
+    }
+}
+
+/// Extract the path of a `file://host/path` url, a bare absolute path is accepted too.
+fn parse_file_url(url: &str) -> Option<String> {
+    let path = match url.strip_prefix("file://") {
+        Some(rest) => &rest[rest.find('/')?..],
+        None if url.starts_with('/') => url,
+        None => return None,
+    };
+    Some(percent_decode(path))
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok());
+        match (
+            bytes[i],
+            hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+        ) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc7_with_bel_and_st() {
+        let mut scanner = Osc7Scanner::default();
+        assert_eq!(
+            scanner.advance(b"$ \x1b]7;file://host/home/user\x07"),
+            Some("/home/user".into())
+        );
+        assert_eq!(
+            scanner.advance(b"\x1b]7;file://host/tmp\x1b\\ls\r\n"),
+            Some("/tmp".into())
+        );
+    }
+
+    #[test]
+    fn osc7_split_across_reads() {
+        let mut scanner = Osc7Scanner::default();
+        assert_eq!(scanner.advance(b"\x1b]7;file://ho"), None);
+        assert_eq!(scanner.advance(b"st/var/my%20dir"), None);
+        assert_eq!(scanner.advance(b"\x07"), Some("/var/my dir".into()));
+    }
+
+    #[test]
+    fn ignore_other_osc() {
+        let mut scanner = Osc7Scanner::default();
+        assert_eq!(scanner.advance(b"\x1b]0;file:///title\x07"), None);
+        assert_eq!(scanner.advance(b"\x1b]77;/x\x07"), None);
+        assert_eq!(scanner.advance(b"\x1b]7;relative\x07"), None);
+    }
+}
@@ -58,6 +58,18 @@ pub enum Event {
 
     /// Child process exited with an error code.
     ChildExit(i32),
+
+    /// Working directory reported by the shell through OSC 7.
+    CurrentDirectory(String),
+
+    /// Command typed at the shell prompt, run once the shell marks it with OSC 133.
+    CommandSubmitted(String),
+
+    /// Command run at the shell prompt finished, with its exit code when the shell tells it.
+    CommandFinished(Option<i32>),
+
+    /// I/O on the PTY failed, the event loop stopped.
+    PtyError(String),
 }
 
 impl Debug for Event {
@@ -76,6 +88,10 @@ impl Debug for Event {
             Event::Bell => write!(f, "Bell"),
             Event::Exit => write!(f, "Exit"),
             Event::ChildExit(code) => write!(f, "ChildExit({code})"),
+            Event::CurrentDirectory(dir) => write!(f, "CurrentDirectory({dir})"),
+            Event::CommandSubmitted(command) => write!(f, "CommandSubmitted({command})"),
+            Event::CommandFinished(code) => write!(f, "CommandFinished({code:?})"),
+            Event::PtyError(err) => write!(f, "PtyError({err})"),
         }
     }
 }
@@ -6,6 +6,7 @@ use std::fmt::{self, Display, Formatter};
 use std::fs::File;
 use std::io::{self, ErrorKind, Read, Write};
 use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
 use std::sync::Arc;
 use std::thread::JoinHandle;
@@ -14,8 +15,10 @@ use std::time::Instant;
 use log::error;
 use polling::{Event as PollingEvent, Events, PollMode};
 
+use crate::cwd::Osc7Scanner;
 use crate::event::{self, Event, EventListener, WindowSize};
 use crate::sync::FairMutex;
+use crate::term::prompt::PromptScanner;
 use crate::term::Term;
 use crate::{thread, tty};
 use vte::ansi;
@@ -37,6 +40,9 @@ pub enum Msg {
 
     /// Instruction to resize the PTY.
     Resize(WindowSize),
+
+    /// Sequence number of a heartbeat, stored in [`EventLoop::heartbeat`] once received.
+    Heartbeat(u64),
 }
 
 /// The main event loop.
@@ -52,6 +58,7 @@ pub struct EventLoop<T: tty::EventedPty, U: EventListener> {
     event_proxy: U,
     drain_on_exit: bool,
     ref_test: bool,
+    heartbeat: Arc<AtomicU64>,
 }
 
 impl<T, U> EventLoop<T, U>
@@ -78,9 +85,16 @@ where
             event_proxy,
             drain_on_exit,
             ref_test,
+            heartbeat: Default::default(),
         })
     }
 
+    /// Last heartbeat received, a heartbeat left unanswered tells that the loop is stuck or
+    /// stopped.
+    pub fn heartbeat(&self) -> Arc<AtomicU64> {
+        self.heartbeat.clone()
+    }
+
     pub fn channel(&self) -> EventLoopSender {
         EventLoopSender {
             sender: self.tx.clone(),
@@ -97,6 +111,7 @@ where
                 Msg::Input(input) => state.write_list.push_back(input),
                 Msg::Resize(window_size) => self.pty.on_resize(window_size),
                 Msg::Shutdown => return false,
+                Msg::Heartbeat(heartbeat) => self.heartbeat.store(heartbeat, Ordering::Relaxed),
             }
         }
 
@@ -153,8 +168,20 @@ where
                 writer.write_all(&buf[..unprocessed]).unwrap();
             }
 
-            // Parse the incoming bytes.
-            state.parser.advance(&mut **terminal, &buf[..unprocessed]);
+            // Parse the incoming bytes, up to each prompt mark for the marks to
+            // apply at the right cursor position.
+            let mut parsed = 0;
+            for (end, mark) in state.prompt.advance(&buf[..unprocessed]) {
+                state.parser.advance(&mut **terminal, &buf[parsed..end]);
+                terminal.mark_prompt(mark);
+                parsed = end;
+            }
+            state
+                .parser
+                .advance(&mut **terminal, &buf[parsed..unprocessed]);
+            if let Some(dir) = state.cwd.advance(&buf[..unprocessed]) {
+                self.event_proxy.send_event(Event::CurrentDirectory(dir));
+            }
 
             processed += unprocessed;
             unprocessed = 0;
@@ -240,6 +267,8 @@ where
                         ErrorKind::Interrupted => continue,
                         _ => {
                             error!("Event loop polling error: {err}");
+                            self.event_proxy
+                                .send_event(Event::PtyError(err.to_string()));
                             break 'event_loop;
                         }
                     }
@@ -294,6 +323,8 @@ where
                                     }
 
                                     error!("Error reading from PTY in event loop: {err}");
+                                    self.event_proxy
+                                        .send_event(Event::PtyError(err.to_string()));
                                     break 'event_loop;
                                 }
                             }
@@ -301,6 +332,8 @@ where
                             if event.writable {
                                 if let Err(err) = self.pty_write(&mut state) {
                                     error!("Error writing to PTY in event loop: {err}");
+                                    self.event_proxy
+                                        .send_event(Event::PtyError(err.to_string()));
                                     break 'event_loop;
                                 }
                             }
@@ -407,6 +440,8 @@ pub struct State {
     write_list: VecDeque<Cow<'static, [u8]>>,
     writing: Option<Writing>,
     parser: ansi::Processor,
+    cwd: Osc7Scanner,
+    prompt: PromptScanner,
 }
 
 impl State {
@@ -160,6 +160,67 @@ impl<T: GridCell + Default + PartialEq> Grid<T> {
         self.max_scroll_limit = history_size;
     }
 
+    /// Take `count` lines starting at `start` out of the grid, the lines above them move down in
+    /// their place.
+    ///
+    /// Without enough history above, the screen is filled with empty lines at the top.
+    pub(crate) fn remove_lines<D>(&mut self, start: Line, count: usize) -> Vec<Row<T>>
+    where
+        T: Clone + ResetDiscriminant<D>,
+        D: PartialEq,
+    {
+        // Move the lines to remove to the top of the history, from where they are dropped.
+        let history_size = self.history_size();
+        let topmost = Line(-(history_size as i32));
+        self.rotate_lines(topmost..start + count, (start - topmost).0 as usize);
+        let rows = (0..count).map(|i| self.raw[topmost + i].clone()).collect();
+
+        for line in (0..count.saturating_sub(history_size) as i32).map(Line::from) {
+            self.raw[line].reset(&self.cursor.template);
+        }
+        self.raw.shrink_lines(min(count, history_size));
+        self.display_offset = min(self.display_offset, self.history_size());
+        rows
+    }
+
+    /// Put `rows` back right above `line`, the lines above it move up. Inverse of
+    /// [`Grid::remove_lines`].
+    ///
+    /// Returns how many of the oldest lines were dropped to stay within the scrollback limit.
+    pub(crate) fn insert_lines(&mut self, line: Line, rows: Vec<Row<T>>) -> usize {
+        let count = rows.len();
+        self.raw.initialize(count, self.columns);
+
+        // The rows enter at the top of the history, then move down above `line`.
+        let topmost = Line(-(self.history_size() as i32));
+        for (i, row) in rows.into_iter().enumerate() {
+            self.raw[topmost + i] = row;
+        }
+        self.rotate_lines(topmost..line, count);
+
+        let excess = self.history_size().saturating_sub(self.max_scroll_limit);
+        self.raw.shrink_lines(excess);
+        self.display_offset = min(self.display_offset, self.history_size());
+        excess
+    }
+
+    /// Rotate the lines in `range` up by `count`, the lines at its top move to its bottom.
+    fn rotate_lines(&mut self, range: Range<Line>, count: usize) {
+        let middle = range.start + count;
+        self.reverse_lines(range.start..middle);
+        self.reverse_lines(middle..range.end);
+        self.reverse_lines(range);
+    }
+
+    fn reverse_lines(&mut self, range: Range<Line>) {
+        let (mut top, mut bottom) = (range.start, range.end - 1i32);
+        while top < bottom {
+            self.raw.swap(top, bottom);
+            top += 1;
+            bottom -= 1;
+        }
+    }
+
     pub fn scroll_display(&mut self, scroll: Scroll) {
         self.display_offset = match scroll {
             Scroll::Delta(count) => min(
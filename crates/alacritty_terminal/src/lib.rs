@@ -4,6 +4,7 @@
 #![deny(clippy::all, clippy::if_not_else, clippy::enum_glob_use)]
 #![cfg_attr(clippy, deny(warnings))]
 
+mod cwd;
 pub mod event;
 pub mod event_loop;
 pub mod grid;
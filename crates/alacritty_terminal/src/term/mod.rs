@@ -14,20 +14,24 @@ use log::{debug, trace};
 use unicode_width::UnicodeWidthChar;
 
 use crate::event::{Event, EventListener};
-use crate::grid::{Dimensions, Grid, GridIterator, Scroll};
+use crate::grid::{Dimensions, Grid, GridIterator, Row, Scroll};
 use crate::index::{self, Boundary, Column, Direction, Line, Point, Side};
 use crate::selection::{Selection, SelectionRange, SelectionType};
 use crate::term::cell::{Cell, Flags, LineLength};
 use crate::term::color::Colors;
+use crate::term::prompt::PromptState;
 use crate::vi_mode::{ViModeCursor, ViMotion};
 use crate::vte::ansi::{
     self, Attr, CharsetIndex, Color, CursorShape, CursorStyle, Handler, Hyperlink, KeyboardModes,
-    KeyboardModesApplyBehavior, NamedColor, NamedMode, NamedPrivateMode, PrivateMode, Rgb,
-    StandardCharset,
+    KeyboardModesApplyBehavior, ModifyOtherKeys, NamedColor, NamedMode, NamedPrivateMode,
+    PrivateMode, Rgb, StandardCharset,
 };
 
 pub mod cell;
 pub mod color;
+pub(crate) mod prompt;
+
+pub use crate::term::prompt::CommandBlock;
 pub mod search;
 
 /// Minimum number of columns.
@@ -77,6 +81,11 @@ bitflags! {
         const REPORT_ALTERNATE_KEYS   = 1 << 20;
         const REPORT_ALL_KEYS_AS_ESC  = 1 << 21;
         const REPORT_ASSOCIATED_TEXT  = 1 << 22;
+        /// xterm's modifyOtherKeys level 1, the keys without a well-defined
+        /// sequence with their modifiers are sent as `CSI 27 ; mods ; code ~`.
+        const MODIFY_OTHER_KEYS       = 1 << 23;
+        /// modifyOtherKeys level 2, every key with modifiers is.
+        const MODIFY_ALL_KEYS         = 1 << 24;
         const MOUSE_MODE              = Self::MOUSE_REPORT_CLICK.bits() | Self::MOUSE_MOTION.bits() | Self::MOUSE_DRAG.bits();
         const KITTY_KEYBOARD_PROTOCOL = Self::DISAMBIGUATE_ESC_CODES.bits()
                                       | Self::REPORT_EVENT_TYPES.bits()
@@ -339,8 +348,14 @@ pub struct Term<T> {
     /// Information about damaged cells.
     damage: TermDamageState,
 
+    /// Bumped whenever the text of the grids changes.
+    generation: u64,
+
     /// Config directly for the terminal.
     config: Config,
+
+    /// Semantic prompt marks of the shell.
+    prompt: PromptState,
 }
 
 /// Configuration options for the [`Term`].
@@ -365,6 +380,9 @@ pub struct Config {
 
     /// OSC52 support mode.
     pub osc52: Osc52,
+
+    /// Width of the ambiguous chars and of the emoji.
+    pub unicode_width: UnicodeWidth,
 }
 
 impl Default for Config {
@@ -376,10 +394,49 @@ impl Default for Config {
             vi_mode_cursor_style: Default::default(),
             kitty_keyboard: Default::default(),
             osc52: Default::default(),
+            unicode_width: Default::default(),
+        }
+    }
+}
+
+/// Width of the chars the terminals disagree on, which must match the one
+/// the applications assume for the cursor to end up where they expect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UnicodeWidth {
+    /// East Asian ambiguous chars, e.g. `·` or `○`, take two cells, as in the
+    /// CJK locales.
+    pub ambiguous_wide: bool,
+    /// Emoji take a single cell, as with older `wcwidth` implementations.
+    pub narrow_emoji: bool,
+}
+
+impl UnicodeWidth {
+    /// Number of cells taken by `c`, `None` for the control chars.
+    pub fn width(self, c: char) -> Option<usize> {
+        let width = match self.ambiguous_wide {
+            true => c.width_cjk()?,
+            false => c.width()?,
+        };
+        if width == 2 && self.narrow_emoji && is_emoji(c) {
+            return Some(1);
         }
+        Some(width)
     }
 }
 
+/// Blocks of the emoji presented wide.
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x231A..=0x23FF
+            | 0x2600..=0x27BF
+            | 0x2B00..=0x2BFF
+            | 0x1F300..=0x1F64F
+            | 0x1F680..=0x1F6FF
+            | 0x1F900..=0x1FAFF
+    )
+}
+
 /// OSC 52 behavior.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[cfg_attr(
@@ -403,6 +460,13 @@ pub enum Osc52 {
 }
 
 impl<T> Term<T> {
+    /// Width of the chars printed from now on, the ones on the screen keep
+    /// theirs.
+    #[inline]
+    pub fn set_unicode_width(&mut self, unicode_width: UnicodeWidth) {
+        self.config.unicode_width = unicode_width;
+    }
+
     #[inline]
     pub fn scroll_display(&mut self, scroll: Scroll)
     where
@@ -445,6 +509,7 @@ impl<T> Term<T> {
             scroll_region,
             event_proxy,
             damage,
+            generation: 0,
             config,
             grid,
             tabs,
@@ -458,6 +523,7 @@ impl<T> Term<T> {
             is_focused: Default::default(),
             selection: Default::default(),
             title: Default::default(),
+            prompt: Default::default(),
             mode: Default::default(),
         }
     }
@@ -508,6 +574,21 @@ impl<T> Term<T> {
         self.damage.reset(self.columns());
     }
 
+    /// Counter of the changes of the text of the grids, e.g. to know when
+    /// something derived from it is stale. Changes made through [`grid_mut`]
+    /// aren't counted.
+    ///
+    /// [`grid_mut`]: Self::grid_mut
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    #[inline]
+    fn content_changed(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
     #[inline]
     fn mark_fully_damaged(&mut self) {
         self.damage.full = true;
@@ -528,9 +609,19 @@ impl<T> Term<T> {
         self.event_proxy.send_event(title_event);
 
         if self.mode.contains(TermMode::ALT_SCREEN) {
+            let dropped = self
+                .inactive_grid
+                .history_size()
+                .saturating_sub(self.config.scrolling_history);
+            self.prompt.drop_history(dropped);
             self.inactive_grid
                 .update_history(self.config.scrolling_history);
         } else {
+            let dropped = self
+                .grid
+                .history_size()
+                .saturating_sub(self.config.scrolling_history);
+            self.prompt.drop_history(dropped);
             self.grid.update_history(self.config.scrolling_history);
         }
 
@@ -549,6 +640,10 @@ impl<T> Term<T> {
         let selection_range = self.selection.as_ref().and_then(|s| s.to_range(self))?;
         let SelectionRange { start, end, .. } = selection_range;
 
+        // The whole history may be selected, its lines are appended to a
+        // single buffer rather than joined. The clipboard takes the text at
+        // once, so it can't be streamed; the buffer grows with the text
+        // rather than being sized for full lines, which most aren't.
         let mut res = String::new();
 
         match self.selection.as_ref() {
@@ -556,25 +651,30 @@ impl<T> Term<T> {
                 ty: SelectionType::Block,
                 ..
             }) => {
-                for line in (start.line.0..end.line.0).map(Line::from) {
-                    res += self
-                        .line_to_string(line, start.column..end.column, start.column.0 != 0)
-                        .trim_end();
-                    res += "\n";
+                for line in (start.line.0..=end.line.0).map(Line::from) {
+                    let line_start = res.len();
+                    let include_wrapped_wide = line == end.line || start.column.0 != 0;
+                    self.push_line(
+                        &mut res,
+                        line,
+                        start.column..end.column,
+                        include_wrapped_wide,
+                    );
+                    res.truncate(line_start + res[line_start..].trim_end().len());
+                    if line != end.line {
+                        res.push('\n');
+                    }
                 }
-
-                res += self
-                    .line_to_string(end.line, start.column..end.column, true)
-                    .trim_end();
             }
             Some(Selection {
                 ty: SelectionType::Lines,
                 ..
             }) => {
-                res = self.bounds_to_string(start, end) + "\n";
+                self.push_bounds(&mut res, start, end);
+                res.push('\n');
             }
             _ => {
-                res = self.bounds_to_string(start, end);
+                self.push_bounds(&mut res, start, end);
             }
         }
 
@@ -584,7 +684,12 @@ impl<T> Term<T> {
     /// Convert range between two points to a String.
     pub fn bounds_to_string(&self, start: Point, end: Point) -> String {
         let mut res = String::new();
+        self.push_bounds(&mut res, start, end);
+        res
+    }
 
+    /// Append the range between two points to `res`.
+    fn push_bounds(&self, res: &mut String, start: Point, end: Point) {
         for line in (start.line.0..=end.line.0).map(Line::from) {
             let start_col = if line == start.line {
                 start.column
@@ -597,21 +702,22 @@ impl<T> Term<T> {
                 self.last_column()
             };
 
-            res += &self.line_to_string(line, start_col..end_col, line == end.line);
+            self.push_line(res, line, start_col..end_col, line == end.line);
         }
 
-        res.strip_suffix('\n').map(str::to_owned).unwrap_or(res)
+        if res.ends_with('\n') {
+            res.pop();
+        }
     }
 
-    /// Convert a single line in the grid to a String.
-    fn line_to_string(
+    /// Append a single line in the grid to `text`.
+    fn push_line(
         &self,
+        text: &mut String,
         line: Line,
         mut cols: Range<Column>,
         include_wrapped_wide: bool,
-    ) -> String {
-        let mut text = String::new();
-
+    ) {
         let grid_line = &self.grid[line];
         let line_length = cmp::min(grid_line.line_length(), cols.end + 1);
 
@@ -673,8 +779,6 @@ impl<T> Term<T> {
         {
             text.push(self.grid[line - 1i32][Column(0)].c);
         }
-
-        text
     }
 
     /// Terminal content required for rendering.
@@ -698,6 +802,7 @@ impl<T> Term<T> {
 
     /// Resize terminal to new dimensions.
     pub fn resize<S: Dimensions>(&mut self, size: S) {
+        self.content_changed();
         let old_cols = self.columns();
         let old_lines = self.screen_lines();
 
@@ -711,6 +816,10 @@ impl<T> Term<T> {
 
         debug!("New num_cols is {num_cols} and num_lines is {num_lines}");
 
+        // The input and the commands are moved by the reflow.
+        self.unfold_commands();
+        self.prompt.reset_lines();
+
         // Move vi mode cursor with the content.
         let history_size = self.history_size();
         let mut delta = num_lines as i32 - old_lines as i32;
@@ -757,6 +866,7 @@ impl<T> Term<T> {
 
     /// Swap primary and alternate screen buffer.
     pub fn swap_alt(&mut self) {
+        self.content_changed();
         if !self.mode.contains(TermMode::ALT_SCREEN) {
             // Set alt screen cursor to the current primary screen cursor.
             self.inactive_grid.cursor = self.grid.cursor.clone();
@@ -792,6 +902,7 @@ impl<T> Term<T> {
     /// Expects origin to be in scroll range.
     #[inline]
     fn scroll_down_relative(&mut self, origin: Line, mut lines: usize) {
+        self.content_changed();
         trace!("Scrolling down relative: origin={origin}, lines={lines}");
 
         lines = cmp::min(
@@ -825,6 +936,7 @@ impl<T> Term<T> {
     /// Expects origin to be in scroll range.
     #[inline]
     fn scroll_up_relative(&mut self, origin: Line, mut lines: usize) {
+        self.content_changed();
         trace!("Scrolling up relative: origin={origin}, lines={lines}");
 
         lines = cmp::min(
@@ -840,7 +952,13 @@ impl<T> Term<T> {
             .take()
             .and_then(|s| s.rotate(self, &region, lines as i32));
 
+        let history_size = self.history_size();
         self.grid.scroll_up(&region, lines);
+        if region.start == 0 && !self.mode.contains(TermMode::ALT_SCREEN) {
+            // Lines past the scrollback limit are dropped.
+            let added = self.history_size() - history_size;
+            self.prompt.drop_history(lines - added);
+        }
 
         // Scroll vi mode cursor.
         let viewport_top = Line(-(self.grid.display_offset() as i32));
@@ -869,6 +987,47 @@ impl<T> Term<T> {
         self.mark_fully_damaged();
     }
 
+    /// Approximate memory taken by the lines of the grids, in bytes.
+    pub fn grid_memory(&self) -> usize {
+        let lines = self.grid.total_lines() + self.inactive_grid.total_lines();
+        lines * (self.columns() * mem::size_of::<Cell>() + mem::size_of::<Row<Cell>>())
+    }
+
+    /// Drop the oldest lines of the history beyond `max_lines`, returns how many were dropped.
+    ///
+    /// The limit of the history stays, it grows up to it again with the output.
+    pub fn trim_history(&mut self, max_lines: usize) -> usize {
+        self.content_changed();
+        let alt_screen = self.mode.contains(TermMode::ALT_SCREEN);
+        let grid = if alt_screen {
+            &mut self.inactive_grid
+        } else {
+            &mut self.grid
+        };
+        let dropped = grid.history_size().saturating_sub(max_lines);
+        if dropped == 0 {
+            return 0;
+        }
+        grid.update_history(max_lines);
+        grid.update_history(self.config.scrolling_history);
+        self.prompt.drop_history(dropped);
+
+        if !alt_screen {
+            self.vi_mode_cursor.point.line = self
+                .vi_mode_cursor
+                .point
+                .line
+                .grid_clamp(self, Boundary::Grid);
+            let topmost_line = self.topmost_line();
+            self.selection = self
+                .selection
+                .take()
+                .filter(|s| !s.intersects_range(..topmost_line));
+        }
+        self.mark_fully_damaged();
+        dropped
+    }
+
     #[inline]
     pub fn exit(&mut self)
     where
@@ -1141,8 +1300,9 @@ impl<T: EventListener> Handler for Term<T> {
     /// A character to be displayed.
     #[inline(never)]
     fn input(&mut self, c: char) {
+        self.content_changed();
         // Number of cells the char will occupy.
-        let width = match c.width() {
+        let width = match self.config.unicode_width.width(c) {
             Some(width) => width,
             None => return,
         };
@@ -1239,6 +1399,7 @@ impl<T: EventListener> Handler for Term<T> {
 
     #[inline]
     fn decaln(&mut self) {
+        self.content_changed();
         trace!("Decalnning");
 
         for line in (0..self.screen_lines()).map(Line::from) {
@@ -1285,6 +1446,7 @@ impl<T: EventListener> Handler for Term<T> {
 
     #[inline]
     fn insert_blank(&mut self, count: usize) {
+        self.content_changed();
         let cursor = &self.grid.cursor;
         let bg = cursor.template.bg;
 
@@ -1631,6 +1793,7 @@ impl<T: EventListener> Handler for Term<T> {
 
     #[inline]
     fn erase_chars(&mut self, count: usize) {
+        self.content_changed();
         let cursor = &self.grid.cursor;
 
         trace!(
@@ -1654,6 +1817,7 @@ impl<T: EventListener> Handler for Term<T> {
 
     #[inline]
     fn delete_chars(&mut self, count: usize) {
+        self.content_changed();
         let columns = self.columns();
         let cursor = &self.grid.cursor;
         let bg = cursor.template.bg;
@@ -1754,6 +1918,7 @@ impl<T: EventListener> Handler for Term<T> {
 
     #[inline]
     fn clear_line(&mut self, mode: ansi::LineClearMode) {
+        self.content_changed();
         trace!("Clearing line: {mode:?}");
 
         let cursor = &self.grid.cursor;
@@ -1871,6 +2036,7 @@ impl<T: EventListener> Handler for Term<T> {
 
     #[inline]
     fn clear_screen(&mut self, mode: ansi::ClearMode) {
+        self.content_changed();
         trace!("Clearing screen: {mode:?}");
         let bg = self.grid.cursor.template.bg;
 
@@ -1926,6 +2092,7 @@ impl<T: EventListener> Handler for Term<T> {
                 self.selection = None;
             }
             ansi::ClearMode::Saved if self.history_size() > 0 => {
+                self.prompt.drop_history(self.history_size());
                 self.grid.clear_history();
 
                 self.vi_mode_cursor.point.line = self
@@ -1962,6 +2129,7 @@ impl<T: EventListener> Handler for Term<T> {
     /// Reset all important fields in the term struct.
     #[inline]
     fn reset_state(&mut self) {
+        self.content_changed();
         if self.mode.contains(TermMode::ALT_SCREEN) {
             mem::swap(&mut self.grid, &mut self.inactive_grid);
         }
@@ -2326,6 +2494,34 @@ impl<T: EventListener> Handler for Term<T> {
         self.mode.remove(TermMode::APP_KEYPAD);
     }
 
+    #[inline]
+    fn set_modify_other_keys(&mut self, mode: ModifyOtherKeys) {
+        trace!("Setting modifyOtherKeys to {mode:?}");
+        self.mode
+            .remove(TermMode::MODIFY_OTHER_KEYS | TermMode::MODIFY_ALL_KEYS);
+        match mode {
+            ModifyOtherKeys::Reset => (),
+            ModifyOtherKeys::EnableExceptWellDefined => {
+                self.mode.insert(TermMode::MODIFY_OTHER_KEYS)
+            }
+            ModifyOtherKeys::EnableAll => self.mode.insert(TermMode::MODIFY_ALL_KEYS),
+        }
+    }
+
+    #[inline]
+    fn report_modify_other_keys(&mut self) {
+        trace!("Reporting modifyOtherKeys");
+        let level = if self.mode.contains(TermMode::MODIFY_ALL_KEYS) {
+            2
+        } else if self.mode.contains(TermMode::MODIFY_OTHER_KEYS) {
+            1
+        } else {
+            0
+        };
+        self.event_proxy
+            .send_event(Event::PtyWrite(format!("\x1b[>4;{level}m")));
+    }
+
     #[inline]
     fn configure_charset(&mut self, index: CharsetIndex, charset: StandardCharset) {
         trace!("Configuring charset {index:?} as {charset:?}");
@@ -2890,6 +3086,30 @@ mod tests {
         assert_eq!(term.selection_to_string(), Some(String::from("\"aa\"a\n")));
     }
 
+    #[test]
+    fn selection_of_the_whole_history() {
+        let size = TermSize::new(8, 3);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        for i in 0..2000 {
+            for c in i.to_string().chars() {
+                term.input(c);
+            }
+            term.carriage_return();
+            term.linefeed();
+        }
+        assert_eq!(term.history_size(), 1998);
+
+        let start = Point::new(term.topmost_line(), Column(0));
+        let end = Point::new(term.bottommost_line(), term.last_column());
+        let mut selection = Selection::new(SelectionType::Lines, start, Side::Left);
+        selection.update(end, Side::Right);
+        term.selection = Some(selection);
+        let text = term.selection_to_string().unwrap();
+        assert!(text.starts_with("0\n1\n2\n"));
+        assert!(text.ends_with("1999\n\n"));
+        assert_eq!(text.lines().count(), 2001);
+    }
+
     #[test]
     fn block_selection_works() {
         let size = TermSize::new(5, 5);
@@ -3056,6 +3276,29 @@ mod tests {
         assert_eq!(term.grid.display_offset(), 0);
     }
 
+    #[test]
+    fn trim_history_keeps_its_limit() {
+        let size = TermSize::new(10, 20);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+
+        // Create 30 lines of scrollback.
+        for _ in 0..49 {
+            term.newline();
+        }
+        let memory = term.grid_memory();
+
+        assert_eq!(term.trim_history(10), 20);
+        assert_eq!(term.history_size(), 10);
+        assert!(term.grid_memory() < memory);
+        assert_eq!(term.trim_history(10), 0);
+
+        // The history grows again.
+        for _ in 0..5 {
+            term.newline();
+        }
+        assert_eq!(term.history_size(), 15);
+    }
+
     #[test]
     fn clearing_scrollback_sets_vi_cursor_into_viewport() {
         let size = TermSize::new(10, 20);
@@ -3712,6 +3955,52 @@ mod tests {
         assert_eq!(term.title, None);
     }
 
+    #[test]
+    fn unicode_width_policy() {
+        let size = TermSize::new(10, 1);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        term.input('○');
+        term.input('😀');
+        assert_eq!(term.grid.cursor.point.column, Column(3));
+
+        term.carriage_return();
+        term.set_unicode_width(UnicodeWidth {
+            ambiguous_wide: true,
+            narrow_emoji: true,
+        });
+        term.input('○');
+        term.input('😀');
+        term.input('中');
+        assert_eq!(term.grid.cursor.point.column, Column(5));
+        assert!(term.grid[Line(0)][Column(0)]
+            .flags
+            .contains(Flags::WIDE_CHAR));
+        assert!(!term.grid[Line(0)][Column(2)]
+            .flags
+            .contains(Flags::WIDE_CHAR));
+    }
+
+    #[test]
+    fn zero_width_joined_cluster() {
+        let size = TermSize::new(10, 1);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        for c in "👨\u{200d}👩\u{200d}👧e\u{301}".chars() {
+            term.input(c);
+        }
+        // every emoji takes its cells, like wcwidth counts them
+        assert_eq!(term.grid.cursor.point.column, Column(7));
+        assert_eq!(
+            term.grid[Line(0)][Column(0)].zerowidth(),
+            Some(&['\u{200d}'][..])
+        );
+        assert_eq!(term.grid[Line(0)][Column(2)].c, '👩');
+        assert_eq!(term.grid[Line(0)][Column(4)].c, '👧');
+        assert_eq!(
+            term.grid[Line(0)][Column(6)].zerowidth(),
+            Some(&['\u{301}'][..])
+        );
+    }
+
     #[test]
     fn parse_cargo_version() {
         assert!(version_number(env!("CARGO_PKG_VERSION")) >= 10_01);
@@ -0,0 +1,568 @@
+//! Semantic prompt marks reported by the shell through OSC 133, e.g. by the
+//! shell integration of WezTerm, iTerm2 or VS Code.
+
+use std::time::{Duration, Instant};
+
+use crate::event::{Event, EventListener};
+use crate::grid::{Dimensions, Row, Scroll};
+use crate::index::{Column, Line, Point};
+use crate::term::cell::{Cell, Flags};
+use crate::term::{Term, TermMode};
+
+/// Longest OSC 133 payload which is accepted, the marks may carry options.
+const MAX_PAYLOAD_LEN: usize = 256;
+
+const OSC133_PREFIX: &[u8] = b"133;";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PromptMark {
+    /// `A`, the prompt is printed.
+    PromptStart,
+    /// `B`, the user types the command.
+    InputStart,
+    /// `C`, the command runs.
+    CommandStart,
+    /// `D`, the command finished, with its exit code when the shell tells it.
+    CommandEnd(Option<i32>),
+}
+
+impl PromptMark {
+    fn parse(payload: &[u8]) -> Option<Self> {
+        let mark = payload.strip_prefix(OSC133_PREFIX)?;
+        let mut fields = mark.split(|byte| *byte == b';');
+        match fields.next()? {
+            b"A" => Some(PromptMark::PromptStart),
+            b"B" => Some(PromptMark::InputStart),
+            b"C" => Some(PromptMark::CommandStart),
+            b"D" => {
+                let exit_code = fields
+                    .next()
+                    .and_then(|code| std::str::from_utf8(code).ok()?.parse().ok());
+                Some(PromptMark::CommandEnd(exit_code))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+/// Scanner extracting `OSC 133 ; mark ST` sequences from the PTY output.
+///
+/// Like OSC 7, the sequence is not handled by the ANSI parser. The cursor
+/// position matters at each mark, so the scanner tells where they end for the
+/// bytes to be parsed up to there first.
+#[derive(Debug, Default)]
+pub(crate) struct PromptScanner {
+    state: State,
+    payload: Vec<u8>,
+}
+
+impl PromptScanner {
+    /// Feed bytes read from the PTY, returns the marks along with the offset
+    /// right after each of them.
+    pub(crate) fn advance(&mut self, bytes: &[u8]) -> Vec<(usize, PromptMark)> {
+        let mut marks = vec![];
+        for (offset, &byte) in bytes.iter().enumerate() {
+            self.state = match (self.state, byte) {
+                (State::Ground, 0x1b) | (State::Escape, 0x1b) => State::Escape,
+                (State::Escape, b']') | (State::OscEscape, b']') => {
+                    self.payload.clear();
+                    State::Osc
+                }
+                (State::Osc, 0x07) | (State::OscEscape, b'\\') => {
+                    marks.extend(PromptMark::parse(&self.payload).map(|mark| (offset + 1, mark)));
+                    State::Ground
+                }
+                (State::Osc, 0x1b) => State::OscEscape,
+                (State::Osc, _) => {
+                    let len = self.payload.len();
+                    // Bail out early on any other OSC.
+                    let is_osc133 = len >= OSC133_PREFIX.len() || OSC133_PREFIX[len] == byte;
+                    if is_osc133 && len < MAX_PAYLOAD_LEN {
+                        self.payload.push(byte);
+                        State::Osc
+                    } else {
+                        State::Ground
+                    }
+                }
+                _ => State::Ground,
+            };
+        }
+        marks
+    }
+}
+
+/// Output of a command run at the shell prompt, between its `C` and `D`
+/// marks.
+#[derive(Debug, Clone)]
+pub struct CommandBlock {
+    /// First line of the output, the command ends on the line above it.
+    output_start: usize,
+    output_lines: usize,
+    exit_code: Option<i32>,
+    duration: Duration,
+    /// Lines of the output taken out of the grid while it is folded.
+    folded: Option<Vec<Row<Cell>>>,
+}
+
+impl CommandBlock {
+    pub fn output_lines(&self) -> usize {
+        self.output_lines
+    }
+
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn is_folded(&self) -> bool {
+        self.folded.is_some()
+    }
+}
+
+/// Where the shell is at, from its last mark.
+///
+/// Lines are counted from the first line which went through the scrollback,
+/// to stay put while the screen scrolls and the oldest lines are dropped.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct PromptState {
+    mark: Option<PromptMark>,
+    /// Start of the command typed at the prompt.
+    input_start: Option<(usize, Column)>,
+    /// Lines dropped from the top of the scrollback so far.
+    dropped_lines: usize,
+    /// First line of the output of the running command, and since when it
+    /// runs.
+    running: Option<(usize, Instant)>,
+    /// Commands which finished, the oldest first.
+    blocks: Vec<CommandBlock>,
+}
+
+impl PromptState {
+    /// Forget the positions of the input and of the commands, e.g. once the
+    /// lines are reflowed.
+    pub(crate) fn reset_lines(&mut self) {
+        self.input_start = None;
+        self.running = None;
+        self.blocks.clear();
+    }
+
+    /// Account for `count` lines dropped from the top of the scrollback, the
+    /// commands which went along are forgotten.
+    pub(crate) fn drop_history(&mut self, count: usize) {
+        self.dropped_lines += count;
+        let dropped_lines = self.dropped_lines;
+        self.blocks
+            .retain(|block| block.output_start > dropped_lines);
+    }
+
+    /// Move what follows the command `index` by `delta` lines, once its output
+    /// is folded or unfolded.
+    fn shift_after(&mut self, index: usize, delta: isize) {
+        for block in &mut self.blocks[index + 1..] {
+            block.output_start = block.output_start.saturating_add_signed(delta);
+        }
+        if let Some((line, _)) = &mut self.running {
+            *line = line.saturating_add_signed(delta);
+        }
+        if let Some((line, _)) = &mut self.input_start {
+            *line = line.saturating_add_signed(delta);
+        }
+    }
+}
+
+impl<T: EventListener> Term<T> {
+    pub(crate) fn mark_prompt(&mut self, mark: PromptMark) {
+        let cursor = self.grid.cursor.point;
+        match mark {
+            PromptMark::InputStart => {
+                self.prompt.input_start = Some((self.counted_line(cursor.line), cursor.column));
+            }
+            PromptMark::CommandStart => {
+                if let Some(command) = self.submitted_command() {
+                    self.event_proxy
+                        .send_event(Event::CommandSubmitted(command));
+                }
+                self.prompt.input_start = None;
+                if !self.mode.contains(TermMode::ALT_SCREEN) {
+                    self.prompt.running = Some((self.counted_line(cursor.line), Instant::now()));
+                }
+            }
+            PromptMark::CommandEnd(exit_code) => {
+                self.prompt.input_start = None;
+                if let Some((output_start, started)) = self.prompt.running.take() {
+                    self.event_proxy
+                        .send_event(Event::CommandFinished(exit_code));
+                    // the output may stop in the middle of a line
+                    let output_end =
+                        self.counted_line(cursor.line) + usize::from(cursor.column > 0);
+                    let in_grid = output_start > self.prompt.dropped_lines;
+                    if in_grid && output_end >= output_start {
+                        self.prompt.blocks.push(CommandBlock {
+                            output_start,
+                            output_lines: output_end - output_start,
+                            exit_code,
+                            duration: started.elapsed(),
+                            folded: None,
+                        });
+                    }
+                }
+            }
+            PromptMark::PromptStart => self.prompt.input_start = None,
+        }
+        self.prompt.mark = Some(mark);
+    }
+
+    fn input_start(&self) -> Option<Point> {
+        let (line, column) = self.prompt.input_start?;
+        let line = self.grid_line(line)?;
+        (column < self.columns()).then_some(Point::new(line, column))
+    }
+
+    /// The command typed at the prompt, up to the end of its wrapped lines.
+    fn submitted_command(&self) -> Option<String> {
+        let start = self.input_start()?;
+        let end = self.line_search_right(start);
+        let command = self.bounds_to_string(start, end);
+        // a leading space keeps the command out of the shell history
+        let command = command.trim_end();
+        (!command.trim_start().is_empty()).then(|| command.to_string())
+    }
+
+    /// Where the command typed at the shell prompt starts.
+    ///
+    /// `None` out of a prompt, e.g. in a full screen application, or without
+    /// the shell integration.
+    pub fn prompt_input_start(&self) -> Option<Point> {
+        if self.prompt.mark != Some(PromptMark::InputStart)
+            || self.mode.contains(TermMode::ALT_SCREEN)
+        {
+            return None;
+        }
+        self.input_start()
+    }
+
+    /// Characters of the command typed at the shell prompt, up to the end of
+    /// its wrapped lines, with their position. The spacers of wide characters
+    /// are left out.
+    pub fn prompt_input_cells(&self) -> Vec<(Point, char)> {
+        let Some(start) = self.prompt_input_start() else {
+            return vec![];
+        };
+        let end = self.line_search_right(start);
+        let mut cells = vec![];
+        for line in (start.line.0..=end.line.0).map(Line) {
+            let first = if line == start.line {
+                start.column.0
+            } else {
+                0
+            };
+            let last = if line == end.line {
+                end.column.0
+            } else {
+                self.last_column().0
+            };
+            for column in (first..=last).map(Column) {
+                let cell = &self.grid[line][column];
+                if !cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                    cells.push((Point::new(line, column), cell.c));
+                }
+            }
+        }
+        cells
+    }
+
+    /// How many characters the cursor of the shell moves by to reach `point`
+    /// in the command typed at the prompt, negative to the left. Past the end
+    /// of the command, it stops there.
+    ///
+    /// `None` when `point` is out of the lines of the command.
+    pub fn prompt_cursor_moves(&self, point: Point) -> Option<isize> {
+        let cells = self.prompt_input_cells();
+        let (start, _) = cells.first()?;
+        let (end, _) = cells.last()?;
+        if point < *start || point.line > end.line {
+            return None;
+        }
+        let index = |point: Point| cells.iter().take_while(|(p, _)| *p < point).count();
+        let cursor = index(self.grid.cursor.point);
+        // the blanks after the command aren't part of it, unless typed
+        let typed = cells
+            .iter()
+            .rposition(|(_, c)| *c != ' ')
+            .map_or(0, |last| last + 1);
+        let target = index(point).min(typed.max(cursor));
+        Some(target as isize - cursor as isize)
+    }
+
+    /// What is typed at the shell prompt, while the cursor is at its end.
+    ///
+    /// `None` out of a prompt, see [`Term::prompt_input_start`].
+    pub fn prompt_input(&self) -> Option<String> {
+        let start = self.prompt_input_start()?;
+        let cursor = self.grid.cursor.point;
+        if cursor < start {
+            return None;
+        }
+        // completions are only appended
+        let end = self.line_search_right(cursor);
+        if !self.bounds_to_string(cursor, end).trim().is_empty() {
+            return None;
+        }
+        if cursor == start {
+            return Some(String::new());
+        }
+        let before_cursor = match cursor.column {
+            Column(0) => Point::new(cursor.line - 1i32, self.last_column()),
+            column => Point::new(cursor.line, column - 1),
+        };
+        Some(self.bounds_to_string(start, before_cursor))
+    }
+}
+
+impl<T> Term<T> {
+    /// `line` of the grid, counted like the lines of [`PromptState`].
+    fn counted_line(&self, line: Line) -> usize {
+        (self.prompt.dropped_lines as i32 + self.grid.history_size() as i32 + line.0) as usize
+    }
+
+    /// Line of the grid at `line`, counted like the lines of [`PromptState`].
+    fn grid_line(&self, line: usize) -> Option<Line> {
+        let line = Line(line as i32 - self.counted_line(Line(0)) as i32);
+        (line >= self.topmost_line() && line <= self.bottommost_line()).then_some(line)
+    }
+
+    /// Commands run at the shell prompt whose last line is in the grid, with
+    /// their index and that line.
+    pub fn command_blocks(&self) -> Vec<(usize, Line, &CommandBlock)> {
+        if self.mode.contains(TermMode::ALT_SCREEN) {
+            return vec![];
+        }
+        self.prompt
+            .blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, block)| {
+                let line = self.grid_line(block.output_start - 1)?;
+                Some((index, line, block))
+            })
+            .collect()
+    }
+
+    /// Fold the output of the command at `index` to the line of the command,
+    /// or unfold it. Returns whether it was done.
+    ///
+    /// The output is taken out of the grid while it is folded, the lines
+    /// above it move down.
+    pub fn toggle_command_fold(&mut self, index: usize) -> bool {
+        if self.mode.contains(TermMode::ALT_SCREEN) {
+            return false;
+        }
+        let Some(block) = self.prompt.blocks.get(index) else {
+            return false;
+        };
+        let Some(start) = self.grid_line(block.output_start) else {
+            return false;
+        };
+        let count = block.output_lines;
+
+        match self.prompt.blocks[index].folded.take() {
+            Some(rows) => {
+                let dropped = self.grid.insert_lines(start, rows);
+                self.prompt.shift_after(index, count as isize);
+                // keep the line of the command where it was on the screen
+                self.grid.scroll_display(Scroll::Delta(count as i32));
+                self.prompt.drop_history(dropped);
+            }
+            None => {
+                if start + count > self.grid.cursor.point.line {
+                    return false;
+                }
+                // the screen is filled with empty lines for what the history lacks
+                let padding = count.saturating_sub(self.history_size());
+                let rows = self.grid.remove_lines(start, count);
+                for block in &mut self.prompt.blocks[..=index] {
+                    block.output_start += padding;
+                }
+                self.prompt.blocks[index].folded = Some(rows);
+                self.prompt
+                    .shift_after(index, padding as isize - count as isize);
+                self.grid.scroll_display(Scroll::Delta(-(count as i32)));
+            }
+        }
+        self.selection = None;
+        self.mark_fully_damaged();
+        true
+    }
+
+    /// Put the output of the folded commands back in the grid.
+    pub(crate) fn unfold_commands(&mut self) {
+        while let Some(index) = self.prompt.blocks.iter().position(CommandBlock::is_folded) {
+            if !self.toggle_command_fold(index) {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::VoidListener;
+    use crate::term::test::TermSize;
+    use crate::term::Config;
+    use crate::vte::ansi::Handler;
+
+    #[test]
+    fn scan_prompt_marks() {
+        let mut scanner = PromptScanner::default();
+        let bytes = b"\x1b]133;A\x07$ \x1b]133;B\x1b\\ls\r\n\x1b]133;C\x07";
+        let marks = scanner.advance(bytes);
+        let kinds: Vec<_> = marks.iter().map(|(_, mark)| *mark).collect();
+        assert_eq!(
+            kinds,
+            [
+                PromptMark::PromptStart,
+                PromptMark::InputStart,
+                PromptMark::CommandStart
+            ]
+        );
+        // right after the terminator
+        assert_eq!(&bytes[marks[1].0..marks[1].0 + 2], b"ls");
+
+        // split across reads, with options
+        assert!(scanner.advance(b"\x1b]133;D;0").is_empty());
+        assert_eq!(
+            scanner.advance(b"\x07"),
+            [(1, PromptMark::CommandEnd(Some(0)))]
+        );
+        assert_eq!(
+            scanner.advance(b"\x1b]133;D\x07"),
+            [(8, PromptMark::CommandEnd(None))]
+        );
+        assert!(scanner
+            .advance(b"\x1b]1337;B\x07\x1b]7;/tmp\x07")
+            .is_empty());
+    }
+
+    #[test]
+    fn read_input_at_prompt() {
+        let size = TermSize::new(20, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        assert_eq!(term.prompt_input(), None);
+
+        for c in "$ ".chars() {
+            term.input(c);
+        }
+        term.mark_prompt(PromptMark::InputStart);
+        assert_eq!(term.prompt_input(), Some(String::new()));
+        for c in "git status".chars() {
+            term.input(c);
+        }
+        assert_eq!(term.prompt_input(), Some("git status".to_string()));
+        assert_eq!(term.submitted_command(), Some("git status".to_string()));
+        assert_eq!(
+            term.prompt_cursor_moves(Point::new(Line(0), Column(2))),
+            Some(-10)
+        );
+        assert_eq!(
+            term.prompt_cursor_moves(Point::new(Line(0), Column(19))),
+            Some(0)
+        );
+        // on the prompt itself
+        assert_eq!(
+            term.prompt_cursor_moves(Point::new(Line(0), Column(1))),
+            None
+        );
+        let cells = term.prompt_input_cells();
+        assert_eq!(cells[0], (Point::new(Line(0), Column(2)), 'g'));
+        assert_eq!(
+            cells.iter().map(|(_, c)| c).collect::<String>().trim_end(),
+            "git status"
+        );
+
+        // not at the end of the input
+        term.goto(0, 4);
+        assert_eq!(term.prompt_input(), None);
+
+        term.mark_prompt(PromptMark::CommandStart);
+        assert_eq!(term.prompt_input(), None);
+
+        // nor in a full screen application
+        term.goto(1, 2);
+        term.mark_prompt(PromptMark::InputStart);
+        assert_eq!(term.prompt_input(), Some(String::new()));
+        term.mode.insert(TermMode::ALT_SCREEN);
+        assert_eq!(term.prompt_input(), None);
+    }
+
+    fn print(term: &mut Term<VoidListener>, text: &str) {
+        for c in text.chars() {
+            if c == '\n' {
+                term.carriage_return();
+                term.linefeed();
+            } else {
+                term.input(c);
+            }
+        }
+    }
+
+    fn line_text(term: &Term<VoidListener>, line: i32) -> String {
+        let row = &term.grid()[Line(line)];
+        let text: String = (0..term.columns())
+            .map(|column| row[Column(column)].c)
+            .collect();
+        text.trim_end().to_string()
+    }
+
+    #[test]
+    fn fold_command_output() {
+        let size = TermSize::new(20, 5);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        print(&mut term, "$ ls\n");
+        term.mark_prompt(PromptMark::CommandStart);
+        for i in 0..8 {
+            print(&mut term, &format!("file{i}\n"));
+        }
+        term.mark_prompt(PromptMark::CommandEnd(Some(1)));
+        print(&mut term, "$ ");
+
+        let blocks = term.command_blocks();
+        assert_eq!(blocks.len(), 1);
+        let (index, line, block) = blocks[0];
+        assert_eq!(
+            (line, block.output_lines(), block.exit_code()),
+            (Line(-5), 8, Some(1))
+        );
+
+        // more lines than the history, empty lines fill the screen
+        assert!(term.toggle_command_fold(index));
+        assert_eq!(term.history_size(), 0);
+        assert_eq!(line_text(&term, 0), "");
+        assert_eq!(line_text(&term, 3), "$ ls");
+        assert_eq!(line_text(&term, 4), "$");
+        let (_, line, block) = term.command_blocks()[0];
+        assert!(block.is_folded());
+        assert_eq!(line, Line(3));
+
+        assert!(term.toggle_command_fold(index));
+        assert_eq!(line_text(&term, -5), "$ ls");
+        assert_eq!(line_text(&term, -4), "file0");
+        assert_eq!(line_text(&term, 3), "file7");
+        assert_eq!(line_text(&term, 4), "$");
+        let (_, line, block) = term.command_blocks()[0];
+        assert!(!block.is_folded());
+        assert_eq!(line, Line(-5));
+    }
+}
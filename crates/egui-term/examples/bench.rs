@@ -0,0 +1,230 @@
+//! Throughput of the terminal on heavy output, in the spirit of
+//! [vtebench](https://github.com/alacritty/vtebench).
+//!
+//! ```sh
+//! cargo run --release --example bench -- [--headless] [CORPUS]...
+//! ```
+//!
+//! The corpora are recorded outputs, e.g. of the vtebench benchmarks. When
+//! none is given, a synthetic one of dense colored cells and scrolling is
+//! generated.
+//!
+//! Each corpus is first fed through the parser alone, then `cat`-ed in a shell
+//! shown by a [`TerminalView`], measuring the bytes processed and the frames
+//! painted per second. `--headless` skips the latter.
+
+use alacritty_terminal::event::VoidListener;
+use alacritty_terminal::term::test::TermSize;
+use alacritty_terminal::term::{Config, Term};
+use alacritty_terminal::vte::ansi::{Processor, StdSyncHandler};
+use copypasta::ClipboardContext;
+use egui::{Id, Vec2};
+use egui_term::{
+    PtyEvent, Terminal, TerminalConfig, TerminalContext, TerminalFont, TerminalOptions,
+    TerminalTheme, TerminalView,
+};
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+
+/// The parser is fed each corpus for this long at least.
+const PARSE_DURATION: Duration = Duration::from_secs(2);
+
+struct Corpus {
+    name: String,
+    path: PathBuf,
+    bytes: Vec<u8>,
+}
+
+/// `bytes` of dense colored cells, scrolling the screen many times over.
+fn synthetic_corpus(bytes: usize) -> Vec<u8> {
+    let mut corpus = Vec::with_capacity(bytes);
+    let mut i = 0usize;
+    while corpus.len() < bytes {
+        for column in 0..120 {
+            let color = (i + column) % 256;
+            let c = b'!' + ((i * 7 + column) % 94) as u8;
+            corpus.extend(format!("\x1b[38;5;{color};48;5;{}m", 255 - color).as_bytes());
+            corpus.push(c);
+        }
+        corpus.extend(b"\x1b[0m\r\n");
+        i += 1;
+    }
+    corpus
+}
+
+fn load_corpora(paths: &[String]) -> std::io::Result<Vec<Corpus>> {
+    if paths.is_empty() {
+        let path = std::env::temp_dir().join("egui-term-bench.vte");
+        std::fs::write(&path, synthetic_corpus(16 * 1024 * 1024))?;
+        let bytes = std::fs::read(&path)?;
+        return Ok(vec![Corpus {
+            name: "synthetic".to_string(),
+            path,
+            bytes,
+        }]);
+    }
+    paths
+        .iter()
+        .map(|path| {
+            let path = PathBuf::from(path);
+            Ok(Corpus {
+                name: path.display().to_string(),
+                bytes: std::fs::read(&path)?,
+                path,
+            })
+        })
+        .collect()
+}
+
+fn mib_per_sec(bytes: usize, elapsed: Duration) -> f64 {
+    bytes as f64 / 1024. / 1024. / elapsed.as_secs_f64()
+}
+
+/// Feed the corpus through the parser into a 120x40 terminal, with its
+/// default history, until [`PARSE_DURATION`] has passed.
+fn bench_parser(corpus: &Corpus) {
+    let size = TermSize::new(120, 40);
+    let mut term = Term::new(Config::default(), &size, VoidListener);
+    let mut parser: Processor<StdSyncHandler> = Processor::new();
+
+    let start = Instant::now();
+    let mut processed = 0;
+    while start.elapsed() < PARSE_DURATION {
+        parser.advance(&mut term, &corpus.bytes);
+        processed += corpus.bytes.len();
+    }
+    println!(
+        "{}: parser {:.1} MiB/s",
+        corpus.name,
+        mib_per_sec(processed, start.elapsed())
+    );
+}
+
+struct App {
+    terminal_backend: Terminal,
+    terminal_font: TerminalFont,
+    terminal_theme: TerminalTheme,
+    multi_exec: bool,
+    active_id: Option<Id>,
+    clipboard: ClipboardContext,
+    pty_proxy_receiver: Receiver<(u64, PtyEvent)>,
+    /// Bytes of the corpora `cat`-ed in the shell.
+    bytes: usize,
+    started: Option<Instant>,
+    frames: usize,
+}
+
+impl App {
+    fn new(ctx: egui::Context, corpora: &[Corpus]) -> Self {
+        let (pty_proxy_sender, pty_proxy_receiver) = std::sync::mpsc::channel();
+        let terminal_backend = Terminal::new_regular(
+            0,
+            ctx,
+            None,
+            TerminalConfig::default(),
+            pty_proxy_sender.clone(),
+        )
+        .unwrap();
+        let mut app = Self {
+            terminal_backend,
+            multi_exec: false,
+            active_id: None,
+            clipboard: ClipboardContext::new().unwrap(),
+            terminal_font: TerminalFont::default(),
+            terminal_theme: TerminalTheme::default(),
+            pty_proxy_receiver,
+            bytes: corpora.iter().map(|corpus| corpus.bytes.len()).sum(),
+            started: None,
+            frames: 0,
+        };
+
+        let paths: Vec<_> = corpora
+            .iter()
+            .map(|corpus| format!("'{}'", corpus.path.display()))
+            .collect();
+        let command = format!("cat {}; exit\n", paths.join(" "));
+        TerminalContext::new(&mut app.terminal_backend, &mut app.clipboard)
+            .write_data(command.into_bytes());
+        app
+    }
+
+    fn report(&self) {
+        let Some(started) = self.started else {
+            return;
+        };
+        let elapsed = started.elapsed();
+        println!(
+            "view: {:.1} MiB/s, {:.1} fps over {:.2}s",
+            mib_per_sec(self.bytes, elapsed),
+            self.frames as f64 / elapsed.as_secs_f64(),
+            elapsed.as_secs_f64()
+        );
+    }
+}
+
+impl eframe::App for App {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if let Ok((_, PtyEvent::Exit)) = self.pty_proxy_receiver.try_recv() {
+            self.report();
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            return;
+        }
+        self.started.get_or_insert_with(Instant::now);
+        self.frames += 1;
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let term_ctx = TerminalContext::new(&mut self.terminal_backend, &mut self.clipboard);
+            let term_opt = TerminalOptions {
+                font: &mut self.terminal_font,
+                multi_exec: &mut self.multi_exec,
+                theme: &mut self.terminal_theme,
+                default_font_size: 14.,
+                active_tab_id: &mut self.active_id,
+            };
+            let terminal = TerminalView::new(ui, term_ctx, term_opt)
+                .set_size(Vec2::new(ui.available_width(), ui.available_height()));
+
+            ui.add(terminal);
+        });
+        // paint as fast as possible, the frame rate is what's measured
+        ctx.request_repaint();
+    }
+}
+
+fn main() -> eframe::Result {
+    let mut headless = false;
+    let mut paths = vec![];
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--headless" => headless = true,
+            _ => paths.push(arg),
+        }
+    }
+    let corpora = match load_corpora(&paths) {
+        Ok(corpora) => corpora,
+        Err(err) => {
+            eprintln!("failed to read the corpora: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    for corpus in &corpora {
+        bench_parser(corpus);
+    }
+    if headless {
+        return Ok(());
+    }
+
+    let native_options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default().with_inner_size([1200.0, 800.0]),
+        vsync: false,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "bench_example",
+        native_options,
+        Box::new(move |cc| Ok(Box::new(App::new(cc.egui_ctx.clone(), &corpora)))),
+    )
+}
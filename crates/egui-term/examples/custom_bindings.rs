@@ -1,17 +1,13 @@
 use copypasta::ClipboardContext;
-use egui::{Id, Key, Modifiers, Vec2};
+use egui::{Key, Modifiers, Vec2};
 use egui_term::{
     generate_bindings, Binding, BindingAction, InputKind, KeyboardBinding, PtyEvent, TermMode,
-    Terminal, TerminalContext, TerminalFont, TerminalOptions, TerminalTheme, TerminalView,
+    Terminal, TerminalConfig, TerminalView,
 };
 use std::sync::mpsc::Receiver;
 
 pub struct App {
     terminal_backend: Terminal,
-    terminal_font: TerminalFont,
-    terminal_theme: TerminalTheme,
-    multi_exec: bool,
-    active_id: Option<Id>,
     clipboard: ClipboardContext,
     pty_proxy_receiver: Receiver<(u64, PtyEvent)>,
     custom_terminal_bindings: Vec<(Binding<InputKind>, BindingAction)>,
@@ -20,7 +16,9 @@ pub struct App {
 impl App {
     pub fn new(ctx: egui::Context) -> Self {
         let (pty_proxy_sender, pty_proxy_receiver) = std::sync::mpsc::channel();
-        let terminal_backend = Terminal::new_regular(0, ctx, None, pty_proxy_sender).unwrap();
+        let terminal_backend =
+            Terminal::new_regular(0, ctx, None, TerminalConfig::default(), pty_proxy_sender)
+                .unwrap();
 
         let mut custom_terminal_bindings = vec![
             (
@@ -64,10 +62,6 @@ impl App {
 
         Self {
             terminal_backend,
-            terminal_theme: TerminalTheme::default(),
-            terminal_font: TerminalFont::default(),
-            multi_exec: false,
-            active_id: None,
             clipboard: ClipboardContext::new().unwrap(),
             pty_proxy_receiver,
             custom_terminal_bindings,
@@ -83,17 +77,11 @@ impl eframe::App for App {
         }
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            let term_ctx = TerminalContext::new(&mut self.terminal_backend, &mut self.clipboard);
-            let term_opt = TerminalOptions {
-                font: &mut self.terminal_font,
-                multi_exec: &mut self.multi_exec,
-                theme: &mut self.terminal_theme,
-                default_font_size: 14.,
-                active_tab_id: &mut self.active_id,
-            };
-            let terminal = TerminalView::new(ui, term_ctx, term_opt)
-                .add_bindings(self.custom_terminal_bindings.clone())
-                .set_size(Vec2::new(ui.available_width(), ui.available_height()));
+            // the font, theme and behavior come from the config of the terminal
+            let terminal =
+                TerminalView::from_terminal(ui, &mut self.terminal_backend, &mut self.clipboard)
+                    .add_bindings(self.custom_terminal_bindings.clone())
+                    .set_size(Vec2::new(ui.available_width(), ui.available_height()));
 
             ui.add(terminal);
         });
@@ -2,7 +2,8 @@ use copypasta::ClipboardContext;
 use eframe::glow;
 use egui::Id;
 use egui_term::{
-    PtyEvent, Terminal, TerminalContext, TerminalFont, TerminalOptions, TerminalTheme, TerminalView,
+    PtyEvent, Terminal, TerminalConfig, TerminalContext, TerminalFont, TerminalOptions,
+    TerminalTheme, TerminalView,
 };
 use std::{
     collections::BTreeMap,
@@ -169,7 +170,9 @@ struct Tab {
 
 impl Tab {
     fn new(ctx: egui::Context, command_sender: Sender<(u64, PtyEvent)>, id: u64) -> Self {
-        let backend = Terminal::new_regular(id, ctx, None, command_sender).unwrap();
+        let backend =
+            Terminal::new_regular(id, ctx, None, TerminalConfig::default(), command_sender)
+                .unwrap();
 
         Self {
             backend,
@@ -1,8 +1,8 @@
 use copypasta::ClipboardContext;
 use egui::{Id, Vec2};
 use egui_term::{
-    ColorPalette, PtyEvent, Terminal, TerminalContext, TerminalFont, TerminalOptions,
-    TerminalTheme, TerminalView,
+    ColorPalette, PtyEvent, Terminal, TerminalConfig, TerminalContext, TerminalFont,
+    TerminalOptions, TerminalTheme, TerminalView,
 };
 use std::sync::mpsc::Receiver;
 
@@ -19,8 +19,14 @@ pub struct App {
 impl App {
     pub fn new(ctx: egui::Context) -> Self {
         let (pty_proxy_sender, pty_proxy_receiver) = std::sync::mpsc::channel();
-        let terminal_backend =
-            Terminal::new_regular(0, ctx, None, pty_proxy_sender.clone()).unwrap();
+        let terminal_backend = Terminal::new_regular(
+            0,
+            ctx,
+            None,
+            TerminalConfig::default(),
+            pty_proxy_sender.clone(),
+        )
+        .unwrap();
 
         Self {
             terminal_backend,
@@ -1,40 +1,98 @@
+use crate::encoding::TermEncoding;
 use crate::errors::TermError;
-use crate::ssh::{Pty, SshOptions};
+use crate::font::TerminalFont;
+use crate::local_echo::LocalEcho;
+use crate::output_log::{LoggedPty, OutputLog};
+use crate::redact::Redaction;
+use crate::search::TerminalSearch;
+use crate::ssh::{
+    ConnectObserver, ConnectStage, HostKeyChange, KeyboardInteractive, Pty, SessionLatency,
+    SshOptions, TunnelStatus,
+};
+use crate::theme::TerminalTheme;
 use crate::types::Size;
+use crate::watchdog::Watchdog;
+use crate::wrap::LineWrap;
 use alacritty_terminal::event::{Event, EventListener, Notify, OnResize, WindowSize};
 use alacritty_terminal::event_loop::{EventLoop, Msg, Notifier};
 use alacritty_terminal::grid::{Dimensions, Scroll};
-use alacritty_terminal::index::{Column, Direction, Line, Point, Side};
+use alacritty_terminal::index::{Boundary, Column, Direction, Line, Point, Side};
 use alacritty_terminal::selection::{Selection, SelectionRange, SelectionType};
 use alacritty_terminal::sync::FairMutex;
 use alacritty_terminal::term::search::{Match, RegexIter, RegexSearch};
-use alacritty_terminal::term::{cell::Cell, viewport_to_point, Config, Term, TermMode};
+use alacritty_terminal::term::{
+    cell::Cell, point_to_viewport, viewport_to_point, Config, Term, TermMode,
+};
 use alacritty_terminal::tty;
 use alacritty_terminal::tty::{EventedPty, Options};
+use alacritty_terminal::vte::ansi::NamedColor;
 use copypasta::ClipboardContext;
-use egui::Modifiers;
-use parking_lot::MutexGuard;
+use egui::{Color32, Modifiers};
+use parking_lot::{Mutex, MutexGuard};
 use std::borrow::Cow;
 use std::cmp::min;
-use std::io::{Error as IoError, ErrorKind};
-use std::ops::Index;
-use std::path::PathBuf;
-use std::sync::mpsc::Sender;
+use std::io::{self, Error as IoError, ErrorKind};
+use std::ops::{Deref, DerefMut, Index};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
 use std::sync::{mpsc, Arc};
-use tracing::debug;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tracing::{debug, error};
 
 pub type PtyEvent = Event;
 
+/// Ctrl+S, the pty stops sending the output until [`XON`].
+pub const XOFF: u8 = 0x13;
+/// Ctrl+Q, the pty sends the output again.
+pub const XON: u8 = 0x11;
+
+/// Whether the output is paused once `input` is written, `None` when it
+/// doesn't change.
+fn flow_control(input: &[u8]) -> Option<bool> {
+    input.iter().rev().find_map(|byte| match *byte {
+        XOFF => Some(true),
+        XON => Some(false),
+        _ => None,
+    })
+}
+
 #[derive(Debug, Clone)]
 pub enum BackendCommand {
     Write(Vec<u8>),
     Scroll(i32),
     Resize(Size, Size),
     SelectAll,
+    /// Grow the selection, or select the word at the point without one.
+    ExpandSelection(Point),
     SelectStart(SelectionType, f32, f32),
     SelectUpdate(f32, f32),
     ProcessLink(LinkAction, Point),
     MouseReport(MouseButton, Modifiers, Point, bool),
+    /// Fold or unfold the output of the command at the index.
+    ToggleFold(usize),
+    /// Open the search bar over the scrollback, searching for the query
+    /// when given, for the last one otherwise.
+    SearchStart(Option<String>),
+    SearchNext,
+    SearchPrev,
+    /// Scroll the command above the top of the view to the top.
+    PreviousPrompt,
+    /// Scroll the command below the top of the view to the top, or to the
+    /// bottom past the last one.
+    NextPrompt,
+}
+
+const BACKEND_STOPPED: &str = "the terminal backend stopped, the input wasn't sent";
+
+/// Command the backend failed to carry out, or the failure which stopped its
+/// event loop, shown over the terminal.
+#[derive(Debug, Clone)]
+pub struct BackendError {
+    pub message: String,
+    /// Sent again when retried.
+    pub command: Option<BackendCommand>,
 }
 
 #[derive(Debug, Clone)]
@@ -66,6 +124,8 @@ pub enum MouseButton {
     NoneMove = 35,
     ScrollUp = 64,
     ScrollDown = 65,
+    BackButton = 128,
+    ForwardButton = 129,
     Other = 99,
 }
 
@@ -97,6 +157,22 @@ impl Default for TerminalSize {
     }
 }
 
+impl TerminalSize {
+    /// Grid of the cells of `font_size` fitting in `layout_size`, `None`
+    /// when not a single cell does.
+    fn fitting(layout_size: Size, font_size: Size) -> Option<Self> {
+        let lines = (layout_size.height / font_size.height.floor()) as u16;
+        let cols = (layout_size.width / font_size.width.floor()) as u16;
+        (lines > 0 && cols > 0).then_some(Self {
+            layout_size,
+            cell_height: font_size.height as u16,
+            cell_width: font_size.width as u16,
+            screen_lines: lines,
+            columns: cols,
+        })
+    }
+}
+
 impl Dimensions for TerminalSize {
     fn total_lines(&self) -> usize {
         self.screen_lines()
@@ -130,10 +206,73 @@ impl From<TerminalSize> for WindowSize {
     }
 }
 
-#[derive(PartialEq)]
+/// Lines kept in the history by default, as many as alacritty keeps.
+pub const DEFAULT_SCROLLBACK_LINES: usize = 10_000;
+
+/// Settings of a terminal, kept by it. The views made with
+/// [`TerminalView::from_terminal`] are drawn with its font, theme and
+/// behavior.
+///
+/// [`TerminalView::from_terminal`]: crate::TerminalView::from_terminal
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalConfig {
+    /// Lines kept in the history, the older ones are dropped. Read when the
+    /// terminal is created.
+    pub scrollback_lines: usize,
+    /// Zoomed in and out by the views.
+    pub font: TerminalFont,
+    /// Size the font is reset to.
+    pub default_font_size: f32,
+    pub theme: TerminalTheme,
+    /// The view takes the keys even when another one has the focus.
+    pub multi_exec: bool,
+    pub smooth_cursor: bool,
+    pub focus_follows_mouse: bool,
+    pub local_echo: bool,
+    pub flow_control: bool,
+    /// Lines scrolled by a wheel notch.
+    pub scroll_multiplier: f32,
+    pub natural_scroll: bool,
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        let font = TerminalFont::default();
+        Self {
+            scrollback_lines: DEFAULT_SCROLLBACK_LINES,
+            default_font_size: font.font_size(),
+            font,
+            theme: TerminalTheme::default(),
+            multi_exec: false,
+            smooth_cursor: false,
+            focus_follows_mouse: true,
+            local_echo: false,
+            flow_control: true,
+            scroll_multiplier: 1.,
+            natural_scroll: false,
+        }
+    }
+}
+
+impl From<&TerminalConfig> for Config {
+    fn from(config: &TerminalConfig) -> Self {
+        Self {
+            scrolling_history: config.scrollback_lines,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
 pub enum TermType {
-    Regular { working_directory: Option<PathBuf> },
-    Ssh { options: SshOptions },
+    Regular {
+        working_directory: Option<PathBuf>,
+    },
+    Ssh {
+        options: Box<SshOptions>,
+        /// Remote directory the shell changes into once started.
+        working_directory: Option<String>,
+    },
 }
 
 pub struct Terminal {
@@ -143,6 +282,106 @@ pub struct Terminal {
     pub size: TerminalSize,
     notifier: Notifier,
     pub hovered_hyperlink: Option<Match>,
+    pub search: TerminalSearch,
+    pub line_wrap: LineWrap,
+    pub config: TerminalConfig,
+    /// Focused view of the terminal, see [`TerminalView::from_terminal`].
+    ///
+    /// [`TerminalView::from_terminal`]: crate::TerminalView::from_terminal
+    focused_view: Option<egui::Id>,
+    local_echo: LocalEcho,
+    /// Working directory last reported by the shell, see [`Event::CurrentDirectory`].
+    current_dir: Arc<Mutex<Option<String>>>,
+    /// Commands run at the shell prompt since they were last taken, see
+    /// [`Event::CommandSubmitted`].
+    submitted_commands: Arc<Mutex<Vec<String>>>,
+    /// Set while the output is logged, see [`Terminal::start_log`].
+    output_log: Arc<Mutex<Option<OutputLog>>>,
+    read_bytes: Arc<AtomicU64>,
+    /// Bytes written to the pty, see [`Terminal::written_bytes`].
+    written_bytes: AtomicU64,
+    /// Set once XOFF is written, until XON, see [`Terminal::output_paused`].
+    output_paused: AtomicBool,
+    /// See [`Terminal::set_encoding`].
+    encoding: Arc<Mutex<TermEncoding>>,
+    /// Forwards of the ssh session, see [`Terminal::tunnels`].
+    tunnels: Arc<Mutex<Vec<TunnelStatus>>>,
+    /// See [`Terminal::latency`].
+    latency: Option<SessionLatency>,
+    /// See [`Terminal::set_link_handler`].
+    link_handler: LinkHandler,
+    /// When the terminal last rang the bell, see [`Event::Bell`].
+    bell_at: Arc<Mutex<Option<Instant>>>,
+    /// See [`Terminal::backend_unresponsive`].
+    watchdog: Watchdog,
+    /// Failures of the backend, taken by the view.
+    errors: Receiver<BackendError>,
+    error_sender: Sender<BackendError>,
+    /// Set once the event loop stopped on a failure, see [`Event::PtyError`].
+    stopped: Arc<AtomicBool>,
+    /// Set once the terminal sent [`Event::Exit`], its event loop stopped.
+    exited: Arc<AtomicBool>,
+    /// See [`Terminal::on_event`].
+    event_hooks: EventHooks,
+    /// Threads of the event loop and of its events, joined on shutdown.
+    threads: Vec<ThreadJoin>,
+}
+
+/// Called with each event of a terminal, see [`Terminal::on_event`].
+pub type EventHook = Box<dyn Fn(&PtyEvent) + Send>;
+
+/// Hooks of a terminal, shared with the thread reading its events.
+#[derive(Clone, Default)]
+struct EventHooks(Arc<Mutex<Vec<EventHook>>>);
+
+impl EventHooks {
+    fn register(&self, hook: EventHook) {
+        self.0.lock().push(hook);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.lock().is_empty()
+    }
+
+    /// Call the hooks with `event` outside of their lock, so that a hook may
+    /// register another one, which is called from the next event on.
+    fn call(&self, event: &PtyEvent) {
+        let mut hooks = std::mem::take(&mut *self.0.lock());
+        for hook in &hooks {
+            hook(event);
+        }
+        let mut registered = self.0.lock();
+        hooks.append(&mut registered);
+        *registered = hooks;
+    }
+}
+
+/// Called with the url of a link clicked in a terminal, see
+/// [`Terminal::set_link_handler`].
+pub type LinkHandler = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Open `url` with the default application of the system, the link handler
+/// of a terminal unless it is replaced.
+pub fn open_link_externally(url: &str) {
+    if let Err(err) = open::that(url) {
+        error!("failed to open {url}: {err}");
+    }
+}
+
+/// Join of a thread, waiting for it until the deadline at most. Returns
+/// whether it finished.
+type ThreadJoin = Box<dyn FnOnce(Instant) -> bool + Send>;
+
+fn thread_join<T: Send + 'static>(handle: JoinHandle<T>) -> ThreadJoin {
+    Box::new(move |deadline| {
+        while !handle.is_finished() {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        handle.join().is_ok()
+    })
 }
 
 impl PartialEq for Terminal {
@@ -157,6 +396,7 @@ impl Terminal {
         app_context: egui::Context,
         term_type: TermType,
         term_size: TerminalSize,
+        config: TerminalConfig,
         pty_event_proxy_sender: Sender<(u64, PtyEvent)>,
     ) -> Result<Self, TermError> {
         match term_type {
@@ -169,15 +409,20 @@ impl Terminal {
                     id,
                     app_context,
                     term_size,
+                    config,
                     tty::new(&opts, term_size.into(), id)?,
                     pty_event_proxy_sender,
                 )
             }
-            TermType::Ssh { options } => Self::new_with_pty(
+            TermType::Ssh {
+                options,
+                working_directory,
+            } => Self::new_with_ssh_pty(
                 id,
                 app_context,
                 term_size,
-                Pty::new(options)?,
+                config,
+                Pty::new(*options, working_directory.as_deref())?,
                 pty_event_proxy_sender,
             ),
         }
@@ -187,6 +432,7 @@ impl Terminal {
         id: u64,
         app_context: egui::Context,
         working_directory: Option<PathBuf>,
+        config: TerminalConfig,
         pty_event_proxy_sender: Sender<(u64, PtyEvent)>,
     ) -> Result<Self, TermError> {
         let typ = TermType::Regular { working_directory };
@@ -195,6 +441,7 @@ impl Terminal {
             app_context,
             typ,
             TerminalSize::default(),
+            config,
             pty_event_proxy_sender,
         )
     }
@@ -203,54 +450,158 @@ impl Terminal {
         id: u64,
         app_context: egui::Context,
         options: SshOptions,
+        working_directory: Option<String>,
+        config: TerminalConfig,
         pty_event_proxy_sender: Sender<(u64, PtyEvent)>,
     ) -> Result<Self, TermError> {
         Self::new(
             id,
             app_context,
-            TermType::Ssh { options },
+            TermType::Ssh {
+                options: Box::new(options),
+                working_directory,
+            },
             TerminalSize::default(),
+            config,
             pty_event_proxy_sender,
         )
     }
 
+    /// Connect to a ssh host on a background thread, the user interface
+    /// keeps running meanwhile. The terminal is created once
+    /// [`PendingTerminal::poll`] gets the connection.
+    pub fn connect_ssh(
+        id: u64,
+        app_context: egui::Context,
+        options: SshOptions,
+        working_directory: Option<String>,
+        config: TerminalConfig,
+        pty_event_proxy_sender: Sender<(u64, PtyEvent)>,
+    ) -> Result<PendingTerminal, TermError> {
+        let (sender, events) = mpsc::channel();
+        let ctx = app_context.clone();
+        std::thread::Builder::new()
+            .name(format!("ssh_connect_{id}"))
+            .spawn(move || {
+                let observer = ConnectSender { sender, ctx };
+                let pty = Pty::connect(options, working_directory.as_deref(), &observer);
+                // the tab may have been closed meanwhile
+                observer.send(ConnectEvent::Connected(pty));
+            })?;
+
+        Ok(PendingTerminal {
+            id,
+            app_context,
+            config,
+            stage: ConnectStage::default(),
+            host_key_change: None,
+            pinned_host_key: None,
+            keyboard_interactive: None,
+            events,
+            pty_event_proxy_sender,
+        })
+    }
+
+    fn new_with_ssh_pty(
+        id: u64,
+        app_context: egui::Context,
+        term_size: TerminalSize,
+        config: TerminalConfig,
+        pty: Pty,
+        pty_event_proxy_sender: Sender<(u64, PtyEvent)>,
+    ) -> Result<Self, TermError> {
+        let tunnels = pty.tunnels.status();
+        let latency = pty.latency.clone();
+        let mut terminal = Self::new_with_pty(
+            id,
+            app_context,
+            term_size,
+            config,
+            pty,
+            pty_event_proxy_sender,
+        )?;
+        terminal.tunnels = tunnels;
+        terminal.latency = Some(latency);
+        Ok(terminal)
+    }
+
     fn new_with_pty<Pty>(
         id: u64,
         app_context: egui::Context,
         term_size: TerminalSize,
+        config: TerminalConfig,
         pty: Pty,
         pty_event_proxy_sender: Sender<(u64, PtyEvent)>,
     ) -> Result<Self, TermError>
     where
         Pty: EventedPty + OnResize + Send + 'static,
     {
-        let config = Config::default();
-
         let (event_sender, event_receiver) = mpsc::channel();
         let event_proxy = EventProxy(event_sender);
-        let term = Term::new(config, &term_size, event_proxy.clone());
+        let term = Term::new(Config::from(&config), &term_size, event_proxy.clone());
         let term = Arc::new(FairMutex::new(term));
 
+        let output_log = Arc::new(Mutex::new(None));
+        let read_bytes = Arc::new(AtomicU64::new(0));
+        let encoding = Arc::new(Mutex::new(TermEncoding::default()));
+        let pty = LoggedPty::new(
+            pty,
+            output_log.clone(),
+            read_bytes.clone(),
+            encoding.clone(),
+        );
         let pty_event_loop = EventLoop::new(term.clone(), event_proxy, pty, false, false)?;
         let notifier = Notifier(pty_event_loop.channel());
+        let watchdog = Watchdog::new(pty_event_loop.heartbeat());
         let pty_notifier = Notifier(pty_event_loop.channel());
 
         let url_regex = r#"(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file://|git://|ssh:|ftp://)[^\u{0000}-\u{001F}\u{007F}-\u{009F}<>"\s{-}\^⟨⟩`]+"#;
         let url_regex =
             RegexSearch::new(url_regex).map_err(|err| IoError::new(ErrorKind::InvalidData, err))?;
-        let _pty_event_loop_thread = pty_event_loop.spawn();
-        let _pty_event_subscription = std::thread::Builder::new()
+        let current_dir = Arc::new(Mutex::new(None));
+        let current_dir_updater = current_dir.clone();
+        let submitted_commands = Arc::new(Mutex::new(vec![]));
+        let submitted_commands_updater = submitted_commands.clone();
+        let exited = Arc::new(AtomicBool::new(false));
+        let exited_updater = exited.clone();
+        let bell_at = Arc::new(Mutex::new(None));
+        let bell_updater = bell_at.clone();
+        let (error_sender, errors) = mpsc::channel();
+        let pty_error_sender = error_sender.clone();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let stopped_updater = stopped.clone();
+        let event_hooks = EventHooks::default();
+        let event_hooks_caller = event_hooks.clone();
+        let pty_event_loop_thread = pty_event_loop.spawn();
+        let pty_event_subscription = std::thread::Builder::new()
             .name(format!("pty_event_subscription_{id}"))
             .spawn(move || while let Ok(event) = event_receiver.recv() {
-                pty_event_proxy_sender
-                    .send((id, event.clone()))
-                    .unwrap_or_else(|err| {
+                // embedders relying on the hooks may drop the receiver
+                if let Err(err) = pty_event_proxy_sender.send((id, event.clone())) {
+                    if event_hooks_caller.is_empty() {
                         panic!("pty_event_subscription_{id}: sending PtyEvent is failed, error: {err}")
-                    });
+                    }
+                }
+                event_hooks_caller.call(&event);
                 app_context.request_repaint();
                 match event {
-                    Event::Exit => break,
+                    Event::Exit => {
+                        exited_updater.store(true, Ordering::Relaxed);
+                        break;
+                    }
                     Event::PtyWrite(s) => pty_notifier.notify(s.into_bytes()),
+                    Event::CurrentDirectory(dir) => *current_dir_updater.lock() = Some(dir),
+                    Event::CommandSubmitted(command) => {
+                        submitted_commands_updater.lock().push(command)
+                    }
+                    Event::Bell => *bell_updater.lock() = Some(Instant::now()),
+                    Event::PtyError(message) => {
+                        stopped_updater.store(true, Ordering::Relaxed);
+                        let _ = pty_error_sender.send(BackendError {
+                            message,
+                            command: None,
+                        });
+                    }
                     _ => {}
                 }
             })?;
@@ -263,8 +614,372 @@ impl Terminal {
             size: term_size,
             notifier,
             hovered_hyperlink: None,
+            search: TerminalSearch::default(),
+            line_wrap: LineWrap::default(),
+            config,
+            focused_view: None,
+            local_echo: LocalEcho::default(),
+            current_dir,
+            submitted_commands,
+            output_log,
+            read_bytes,
+            written_bytes: AtomicU64::new(0),
+            output_paused: AtomicBool::new(false),
+            encoding,
+            tunnels: Arc::new(Mutex::new(vec![])),
+            latency: None,
+            link_handler: Arc::new(open_link_externally),
+            bell_at,
+            watchdog,
+            errors,
+            error_sender,
+            stopped,
+            exited,
+            event_hooks,
+            threads: vec![
+                thread_join(pty_event_loop_thread),
+                thread_join(pty_event_subscription),
+            ],
         })
     }
+
+    /// Stop the event loop and wait for its threads until `deadline`, then
+    /// flush the log. Returns whether the threads finished in time, the
+    /// terminal can't be used anymore either way.
+    pub fn shutdown(&mut self, deadline: Instant) -> bool {
+        let _ = self.notifier.0.send(Msg::Shutdown);
+        let mut threads = std::mem::take(&mut self.threads).into_iter();
+        let event_loop = threads.next().is_none_or(|join| join(deadline));
+        // the events stop with the exit, the terminal keeps their sender
+        self.term.lock().exit();
+        let finished = threads.all(|join| join(deadline)) && event_loop;
+        self.stop_log();
+        finished
+    }
+
+    /// Whether the event loop of the pty missed a heartbeat at `now`, its
+    /// thread is stuck or stopped and the terminal frozen. The heartbeats are
+    /// sent with the input, never once the terminal exited. An event loop
+    /// stopped on a failure isn't unresponsive, the view tells its failure.
+    pub fn backend_unresponsive(&self, now: Instant) -> bool {
+        !self.exited.load(Ordering::Relaxed)
+            && !self.stopped.load(Ordering::Relaxed)
+            && self.watchdog.missed(now)
+    }
+
+    /// How long the event loop may still answer its last heartbeat, to check
+    /// [`Terminal::backend_unresponsive`] again then.
+    pub fn heartbeat_pending(&self, now: Instant) -> Option<Duration> {
+        self.watchdog.pending(now)
+    }
+
+    /// Call `hook` with each event of the terminal from now on, on the thread
+    /// reading them: its title changes, the bell, the clipboard requests, the
+    /// exit of its child, and the directory and the commands reported by the
+    /// shell integration. The other escape sequences aren't events.
+    ///
+    /// The events are still sent to the channel the terminal was created
+    /// with, once a hook is registered its receiver may be dropped.
+    pub fn on_event(&self, hook: impl Fn(&PtyEvent) + Send + 'static) {
+        self.event_hooks.register(Box::new(hook));
+    }
+
+    /// Background color set by the host through OSC 11, e.g. by a light
+    /// theme of the shell, which the theme of the view may clash with.
+    pub fn remote_background(&self) -> Option<Color32> {
+        let rgb = self.term.lock().colors()[NamedColor::Background]?;
+        Some(Color32::from_rgb(rgb.r, rgb.g, rgb.b))
+    }
+
+    /// Working directory reported by the shell through OSC 7, if any.
+    pub fn current_dir(&self) -> Option<String> {
+        self.current_dir.lock().clone()
+    }
+
+    /// Open the links clicked in the terminal with `handler` rather than
+    /// [`open_link_externally`], e.g. to ask first, to log them, or to keep
+    /// them in a sandboxed or kiosk deployment.
+    pub fn set_link_handler(&mut self, handler: LinkHandler) {
+        self.link_handler = handler;
+    }
+
+    /// Round trip of the ssh session, measured on it every now and then.
+    /// `None` for a local shell, or until it is measured.
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency.as_ref().and_then(SessionLatency::latency)
+    }
+
+    /// Ports forwarded through the ssh session and how they go, none for a
+    /// local shell.
+    pub fn tunnels(&self) -> Vec<TunnelStatus> {
+        self.tunnels.lock().clone()
+    }
+
+    /// Commands run at the shell prompt since the last call, the shell marks
+    /// them through OSC 133.
+    pub fn take_submitted_commands(&self) -> Vec<String> {
+        std::mem::take(&mut *self.submitted_commands.lock())
+    }
+
+    /// Append the output to the file at `path` from now on, in place of the
+    /// current log if any. See [`OutputLog`] about `collapse_progress` and
+    /// `redaction`.
+    pub fn start_log(
+        &self,
+        path: &Path,
+        collapse_progress: bool,
+        redaction: Redaction,
+    ) -> io::Result<()> {
+        let log = OutputLog::create(path, collapse_progress, redaction)?;
+        *self.output_log.lock() = Some(log);
+        Ok(())
+    }
+
+    /// Stop logging the output, returns the file of the log.
+    pub fn stop_log(&self) -> Option<PathBuf> {
+        let log = self.output_log.lock().take()?;
+        Some(log.path().to_path_buf())
+    }
+
+    /// File the output is logged to.
+    pub fn log_path(&self) -> Option<PathBuf> {
+        let log = self.output_log.lock();
+        log.as_ref().map(|log| log.path().to_path_buf())
+    }
+
+    /// Bytes read from the pty since the terminal was created, the output of
+    /// the shell before it is parsed.
+    pub fn read_bytes(&self) -> u64 {
+        self.read_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Bytes written to the pty since the terminal was created, the input
+    /// typed or pasted into the shell.
+    pub fn written_bytes(&self) -> u64 {
+        self.written_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Whether the shell was sent XOFF without XON since, the pty doesn't
+    /// send the output then. Full screen apps get Ctrl+S as a key, it doesn't
+    /// pause them.
+    pub fn output_paused(&self) -> bool {
+        self.output_paused.load(Ordering::Relaxed)
+    }
+
+    pub fn encoding(&self) -> TermEncoding {
+        *self.encoding.lock()
+    }
+
+    /// Decode the output of the shell from `encoding` from now on, and encode
+    /// the input into it, e.g. for a legacy host in GBK.
+    pub fn set_encoding(&self, encoding: TermEncoding) {
+        *self.encoding.lock() = encoding;
+    }
+
+    /// Approximate memory taken by the grids, see [`Term::grid_memory`].
+    pub fn grid_memory(&self) -> usize {
+        self.term.lock().grid_memory()
+    }
+
+    /// Drop the oldest lines of the history beyond `max_lines`, see
+    /// [`Term::trim_history`].
+    pub fn trim_history(&mut self, max_lines: usize) -> usize {
+        let dropped = self.term.lock().trim_history(max_lines);
+        if dropped > 0 {
+            // the matches may be in the dropped lines
+            self.search.invalidate();
+            self.hovered_hyperlink = None;
+        }
+        dropped
+    }
+
+    /// What is typed at the shell prompt, see [`Term::prompt_input`].
+    pub fn prompt_input(&self) -> Option<String> {
+        self.term.lock().prompt_input()
+    }
+
+    pub fn grid_status(&self) -> GridStatus {
+        let term = self.term.lock();
+        let grid = term.grid();
+        GridStatus {
+            rows: term.screen_lines(),
+            columns: term.columns(),
+            cursor_line: grid.cursor.point.line.0.max(0) as usize + 1,
+            cursor_column: grid.cursor.point.column.0 + 1,
+            display_offset: grid.display_offset(),
+            history_size: grid.history_size(),
+        }
+    }
+}
+
+enum ConnectEvent {
+    Stage(ConnectStage),
+    HostKeyPinned(String),
+    /// Waits for the answer of the user on the sender.
+    TrustHostKey(HostKeyChange, Sender<bool>),
+    /// Waits for the answers of the user on the sender.
+    KeyboardInteractive(KeyboardInteractive, Sender<Option<Vec<String>>>),
+    Connected(Result<Pty, TermError>),
+}
+
+/// Hands the progress of a connection to its [`PendingTerminal`].
+struct ConnectSender {
+    sender: Sender<ConnectEvent>,
+    ctx: egui::Context,
+}
+
+impl ConnectSender {
+    fn send(&self, event: ConnectEvent) -> bool {
+        let sent = self.sender.send(event).is_ok();
+        if sent {
+            self.ctx.request_repaint();
+        }
+        sent
+    }
+}
+
+impl ConnectObserver for ConnectSender {
+    fn stage(&self, stage: ConnectStage) {
+        self.send(ConnectEvent::Stage(stage));
+    }
+
+    fn host_key_pinned(&self, fingerprint: &str) {
+        self.send(ConnectEvent::HostKeyPinned(fingerprint.to_string()));
+    }
+
+    fn trust_host_key(&self, change: &HostKeyChange) -> bool {
+        let (answer, answer_receiver) = mpsc::channel();
+        let event = ConnectEvent::TrustHostKey(change.clone(), answer);
+        // refused if the tab is closed before the user answers
+        self.send(event) && answer_receiver.recv().unwrap_or(false)
+    }
+
+    fn keyboard_interactive(&self, interactive: &KeyboardInteractive) -> Option<Vec<String>> {
+        let (answers, answers_receiver) = mpsc::channel();
+        let event = ConnectEvent::KeyboardInteractive(interactive.clone(), answers);
+        // cancelled if the tab is closed before the user answers
+        if !self.send(event) {
+            return None;
+        }
+        answers_receiver.recv().ok().flatten()
+    }
+}
+
+/// Ssh terminal whose connection is being established, see
+/// [`Terminal::connect_ssh`].
+pub struct PendingTerminal {
+    id: u64,
+    app_context: egui::Context,
+    config: TerminalConfig,
+    stage: ConnectStage,
+    /// Changed host key waiting for the user, see [`PendingTerminal::answer_host_key`].
+    host_key_change: Option<(HostKeyChange, Sender<bool>)>,
+    pinned_host_key: Option<String>,
+    /// Prompts waiting for the user, see [`PendingTerminal::answer_keyboard_interactive`].
+    keyboard_interactive: Option<(KeyboardInteractive, Sender<Option<Vec<String>>>)>,
+    events: Receiver<ConnectEvent>,
+    pty_event_proxy_sender: Sender<(u64, PtyEvent)>,
+}
+
+impl PartialEq for PendingTerminal {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PendingTerminal {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Last stage reached by the connection.
+    pub fn stage(&self) -> ConnectStage {
+        self.stage
+    }
+
+    /// The host key of a pinned session is new or changed, the connection waits until
+    /// [`PendingTerminal::answer_host_key`] is called.
+    pub fn host_key_change(&self) -> Option<&HostKeyChange> {
+        self.host_key_change.as_ref().map(|(change, _)| change)
+    }
+
+    /// Trust the changed host key and go on connecting, or refuse it.
+    pub fn answer_host_key(&mut self, trust: bool) {
+        if let Some((_, answer)) = self.host_key_change.take() {
+            let _ = answer.send(trust);
+        }
+    }
+
+    /// Prompts of the host the session can't answer, the connection waits
+    /// until [`PendingTerminal::answer_keyboard_interactive`] is called.
+    pub fn keyboard_interactive(&self) -> Option<&KeyboardInteractive> {
+        self.keyboard_interactive
+            .as_ref()
+            .map(|(interactive, _)| interactive)
+    }
+
+    /// Answer the prompts in their order, or cancel the connection with `None`.
+    pub fn answer_keyboard_interactive(&mut self, answers: Option<Vec<String>>) {
+        if let Some((_, sender)) = self.keyboard_interactive.take() {
+            let _ = sender.send(answers);
+        }
+    }
+
+    /// Fingerprint of the host key to save with the session, once known.
+    pub fn take_pinned_host_key(&mut self) -> Option<String> {
+        self.pinned_host_key.take()
+    }
+
+    /// Apply the progress of the connection, the terminal once it is
+    /// established or the error it failed with.
+    pub fn poll(&mut self) -> Option<Result<Terminal, TermError>> {
+        loop {
+            match self.events.try_recv() {
+                Ok(ConnectEvent::Stage(stage)) => self.stage = stage,
+                Ok(ConnectEvent::HostKeyPinned(fingerprint)) => {
+                    self.pinned_host_key = Some(fingerprint);
+                }
+                Ok(ConnectEvent::TrustHostKey(change, answer)) => {
+                    self.host_key_change = Some((change, answer));
+                }
+                Ok(ConnectEvent::KeyboardInteractive(interactive, answers)) => {
+                    self.keyboard_interactive = Some((interactive, answers));
+                }
+                Ok(ConnectEvent::Connected(pty)) => {
+                    let terminal = pty.and_then(|pty| {
+                        Terminal::new_with_ssh_pty(
+                            self.id,
+                            self.app_context.clone(),
+                            TerminalSize::default(),
+                            self.config.clone(),
+                            pty,
+                            self.pty_event_proxy_sender.clone(),
+                        )
+                    });
+                    return Some(terminal);
+                }
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {
+                    let err = IoError::other("the connection thread stopped");
+                    return Some(Err(err.into()));
+                }
+            }
+        }
+    }
+}
+
+/// Dimensions and positions of the grid, e.g. for a status bar.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GridStatus {
+    pub rows: usize,
+    pub columns: usize,
+    /// 1-based line of the cursor in the screen.
+    pub cursor_line: usize,
+    /// 1-based column of the cursor.
+    pub cursor_column: usize,
+    /// Lines scrolled back into the history, 0 at the bottom.
+    pub display_offset: usize,
+    pub history_size: usize,
 }
 
 impl Drop for Terminal {
@@ -273,28 +988,95 @@ impl Drop for Terminal {
     }
 }
 
+/// Lock of the terminal held by a [`TerminalContext`]. The view releases it
+/// once it has handled the input, the frame is painted from a snapshot.
+pub struct TermLock<'a> {
+    guard: Option<MutexGuard<'a, Term<EventProxy>>>,
+}
+
+impl TermLock<'_> {
+    /// Let the event loop parse the output meanwhile, the terminal can't be
+    /// used anymore through this context.
+    pub(crate) fn unlock(&mut self) {
+        self.guard = None;
+    }
+}
+
+impl Deref for TermLock<'_> {
+    type Target = Term<EventProxy>;
+
+    fn deref(&self) -> &Self::Target {
+        self.guard
+            .as_deref()
+            .expect("terminal used after its lock was released")
+    }
+}
+
+impl DerefMut for TermLock<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.guard
+            .as_deref_mut()
+            .expect("terminal used after its lock was released")
+    }
+}
+
 pub struct TerminalContext<'a> {
     pub id: u64,
-    pub terminal: MutexGuard<'a, Term<EventProxy>>,
+    pub terminal: TermLock<'a>,
     pub url_regex: &'a mut RegexSearch,
     pub size: &'a mut TerminalSize,
     pub notifier: &'a mut Notifier,
     pub hovered_hyperlink: &'a mut Option<Match>,
+    pub search: &'a mut TerminalSearch,
+    pub line_wrap: &'a mut LineWrap,
+    pub(crate) local_echo: &'a mut LocalEcho,
+    watchdog: &'a mut Watchdog,
+    errors: &'a Receiver<BackendError>,
+    error_sender: &'a Sender<BackendError>,
+    stopped: &'a AtomicBool,
+    written_bytes: &'a AtomicU64,
+    output_paused: &'a AtomicBool,
+    encoding: TermEncoding,
+    link_handler: &'a LinkHandler,
+    /// See [`Event::Bell`].
+    pub(crate) bell_at: Option<Instant>,
     pub clipboard: &'a mut ClipboardContext,
 }
 
 impl<'a> TerminalContext<'a> {
     pub fn new(terminal: &'a mut Terminal, clipboard: &'a mut ClipboardContext) -> Self {
+        Self::with_config(terminal, clipboard).0
+    }
+
+    /// Context of the terminal along with its config and its focused view.
+    pub(crate) fn with_config(
+        terminal: &'a mut Terminal,
+        clipboard: &'a mut ClipboardContext,
+    ) -> (Self, &'a mut TerminalConfig, &'a mut Option<egui::Id>) {
+        let encoding = terminal.encoding();
         let term = terminal.term.lock();
-        Self {
+        let context = Self {
             id: terminal.id,
-            terminal: term,
+            terminal: TermLock { guard: Some(term) },
             url_regex: &mut terminal.url_regex,
             size: &mut terminal.size,
             notifier: &mut terminal.notifier,
             hovered_hyperlink: &mut terminal.hovered_hyperlink,
+            search: &mut terminal.search,
+            line_wrap: &mut terminal.line_wrap,
+            local_echo: &mut terminal.local_echo,
+            watchdog: &mut terminal.watchdog,
+            errors: &terminal.errors,
+            error_sender: &terminal.error_sender,
+            stopped: &terminal.stopped,
+            written_bytes: &terminal.written_bytes,
+            output_paused: &terminal.output_paused,
+            encoding,
+            link_handler: &terminal.link_handler,
+            bell_at: *terminal.bell_at.lock(),
             clipboard,
-        }
+        };
+        (context, &mut terminal.config, &mut terminal.focused_view)
     }
 
     pub fn term_mode(&self) -> TermMode {
@@ -315,6 +1097,9 @@ impl<'a> TerminalContext<'a> {
             BackendCommand::SelectAll => {
                 self.select_all();
             }
+            BackendCommand::ExpandSelection(point) => {
+                self.expand_selection(point);
+            }
             BackendCommand::SelectStart(selection_type, x, y) => {
                 self.start_selection(selection_type, x, y);
             }
@@ -327,6 +1112,27 @@ impl<'a> TerminalContext<'a> {
             BackendCommand::MouseReport(button, modifiers, point, pressed) => {
                 self.mouse_report(button, modifiers, point, pressed);
             }
+            BackendCommand::ToggleFold(index) => {
+                self.toggle_fold(index);
+            }
+            BackendCommand::SearchStart(query) => {
+                self.search.start(query);
+                self.update_search();
+            }
+            BackendCommand::SearchNext => {
+                self.update_search();
+                self.search_next();
+            }
+            BackendCommand::SearchPrev => {
+                self.update_search();
+                self.search_previous();
+            }
+            BackendCommand::PreviousPrompt => {
+                self.scroll_to_prompt(false);
+            }
+            BackendCommand::NextPrompt => {
+                self.scroll_to_prompt(true);
+            }
         };
     }
 
@@ -354,8 +1160,37 @@ impl<'a> TerminalContext<'a> {
             .is_none_or(Selection::is_empty)
     }
 
+    /// Refresh the search matches against the current grid content.
+    pub fn update_search(&mut self) {
+        self.search.update(&self.terminal);
+    }
+
+    /// Focus the next search match and scroll it into the viewport.
+    pub fn search_next(&mut self) {
+        if let Some(m) = self.search.focus_next() {
+            let point = *m.start();
+            self.terminal.scroll_to_point(point);
+        }
+    }
+
+    /// Focus the previous search match and scroll it into the viewport.
+    pub fn search_previous(&mut self) {
+        if let Some(m) = self.search.focus_previous() {
+            let point = *m.start();
+            self.terminal.scroll_to_point(point);
+        }
+    }
+
+    /// Send XON, the output paused by XOFF is sent again.
+    pub fn resume_output(&mut self) {
+        self.write_data(vec![XON]);
+    }
+
     pub fn write_data<I: Into<Cow<'static, [u8]>>>(&mut self, data: I) {
         self.write(data);
+        if let Some(heartbeat) = self.watchdog.beat(Instant::now()) {
+            let _ = self.notifier.0.send(Msg::Heartbeat(heartbeat));
+        }
         self.terminal.scroll_display(Scroll::Bottom);
         self.terminal.selection = None;
     }
@@ -387,7 +1222,7 @@ impl<'a> TerminalContext<'a> {
                 }
             }
 
-            let _ = open::that(url);
+            (self.link_handler)(&url);
         }
     }
 
@@ -465,6 +1300,28 @@ impl<'a> TerminalContext<'a> {
         self.write(msg);
     }
 
+    fn scroll_to_prompt(&mut self, next: bool) {
+        let top = Line(-(self.terminal.grid().display_offset() as i32));
+        let lines = self
+            .terminal
+            .command_blocks()
+            .into_iter()
+            .map(|(_, line, _)| line);
+        match prompt_line(lines, top, next) {
+            Some(line) => self.terminal.scroll_display(Scroll::Delta(top.0 - line.0)),
+            None if next => self.terminal.scroll_display(Scroll::Bottom),
+            None => {}
+        }
+    }
+
+    fn toggle_fold(&mut self, index: usize) {
+        if self.terminal.toggle_command_fold(index) {
+            // the lines moved under them
+            self.search.invalidate();
+            *self.hovered_hyperlink = None;
+        }
+    }
+
     pub fn select_all(&mut self) {
         let start = Point::new(self.terminal.topmost_line(), Column(0));
         let end = Point::new(
@@ -480,6 +1337,25 @@ impl<'a> TerminalContext<'a> {
         self.terminal.selection = Some(selection);
     }
 
+    /// Select the smallest unit around the selection which is larger than
+    /// it, see [`selection_units`].
+    pub fn expand_selection(&mut self, point: Point) {
+        let current = self.to_range().map(|range| (range.start, range.end));
+        let anchor = current.map_or(point, |(start, _)| start);
+        let unit =
+            selection_units(&self.terminal, anchor)
+                .into_iter()
+                .find(|&unit| match current {
+                    Some((start, end)) => unit.0 <= start && unit.1 >= end && unit != (start, end),
+                    None => true,
+                });
+        if let Some((start, end)) = unit {
+            let mut selection = Selection::new(SelectionType::Simple, start, Side::Left);
+            selection.update(end, Side::Right);
+            self.terminal.selection = Some(selection);
+        }
+    }
+
     fn start_selection(&mut self, selection_type: SelectionType, x: f32, y: f32) {
         let location = selection_point(x, y, self.size, self.terminal.grid().display_offset());
         self.terminal.selection = Some(Selection::new(
@@ -517,24 +1393,51 @@ impl<'a> TerminalContext<'a> {
             return;
         }
 
-        let lines = (layout_size.height / font_size.height.floor()) as u16;
-        let cols = (layout_size.width / font_size.width.floor()) as u16;
-        if lines > 0 && cols > 0 {
-            *self.size = TerminalSize {
-                layout_size,
-                cell_height: font_size.height as u16,
-                cell_width: font_size.width as u16,
-                screen_lines: lines,
-                columns: cols,
-            };
-
-            self.notifier.on_resize((*self.size).into());
+        if let Some(size) = TerminalSize::fitting(layout_size, font_size) {
+            *self.size = size;
+            if let Err(err) = self.notifier.0.send(Msg::Resize((*self.size).into())) {
+                self.report_error(err.to_string(), None);
+            }
             self.terminal.resize(*self.size);
         }
     }
 
     fn write<I: Into<Cow<'static, [u8]>>>(&self, input: I) {
-        self.notifier.notify(input);
+        let input = self.encoding.encode(input.into());
+        self.written_bytes
+            .fetch_add(input.len() as u64, Ordering::Relaxed);
+        if let Some(paused) = flow_control(&input) {
+            let full_screen = self.terminal.mode().contains(TermMode::ALT_SCREEN);
+            self.output_paused
+                .store(paused && !full_screen, Ordering::Relaxed);
+        }
+        // the event loop hangs on empty input
+        if input.is_empty() {
+            return;
+        }
+        if self.stopped.load(Ordering::Relaxed) {
+            let command = BackendCommand::Write(input.into_owned());
+            self.report_error(BACKEND_STOPPED.to_string(), Some(command));
+        } else if let Err(err) = self.notifier.0.send(Msg::Input(input)) {
+            self.report_error(err.to_string(), None);
+        }
+    }
+
+    /// Show a failure of the backend over the terminal, `command` is sent
+    /// again when it is retried.
+    fn report_error(&self, message: String, command: Option<BackendCommand>) {
+        let _ = self.error_sender.send(BackendError { message, command });
+    }
+
+    /// Last failure of the backend since the previous call.
+    pub(crate) fn take_error(&self) -> Option<BackendError> {
+        self.errors.try_iter().last()
+    }
+
+    /// Whether the event loop stopped on a failure, nothing sent to it is
+    /// carried out anymore.
+    pub(crate) fn backend_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
     }
 
     fn scroll(&mut self, delta_value: i32) {
@@ -562,6 +1465,51 @@ impl<'a> TerminalContext<'a> {
     }
 }
 
+/// Ranges around `point` the selection grows through, the smallest first:
+/// the word, the WORD (up to whitespace), the line, then the output of the
+/// command it is in and the command along with its output.
+fn selection_units<T>(term: &Term<T>, point: Point) -> Vec<(Point, Point)> {
+    let mut units = vec![
+        (
+            term.semantic_search_left(point),
+            term.semantic_search_right(point),
+        ),
+        (
+            term.inline_search_left(point, " ")
+                .map_or_else(|p| p, |p| p.add(term, Boundary::Grid, 1)),
+            term.inline_search_right(point, " ")
+                .map_or_else(|p| p, |p| p.sub(term, Boundary::Grid, 1)),
+        ),
+        (term.line_search_left(point), term.line_search_right(point)),
+    ];
+    let last_column = term.last_column();
+    for (_, line, block) in term.command_blocks() {
+        let last = line + block.output_lines();
+        let command = term.line_search_left(Point::new(line, Column(0)));
+        if block.is_folded() || point.line < command.line || point.line > last {
+            continue;
+        }
+        if point.line > line {
+            units.push((
+                Point::new(line + 1, Column(0)),
+                Point::new(last, last_column),
+            ));
+        }
+        units.push((command, Point::new(last, last_column)));
+        break;
+    }
+    units
+}
+
+/// Line of the command closest to the `top` of the view, above it or below
+/// it with `next`.
+fn prompt_line(lines: impl Iterator<Item = Line>, top: Line, next: bool) -> Option<Line> {
+    match next {
+        true => lines.filter(|line| *line > top).min(),
+        false => lines.filter(|line| *line < top).max(),
+    }
+}
+
 pub fn selection_point(x: f32, y: f32, term_size: &TerminalSize, display_offset: usize) -> Point {
     let col = (x as usize) / (term_size.cell_width as usize);
     let col = min(Column(col), Column(term_size.columns as usize - 1));
@@ -572,6 +1520,22 @@ pub fn selection_point(x: f32, y: f32, term_size: &TerminalSize, display_offset:
     viewport_to_point(display_offset, Point::new(line, col))
 }
 
+/// Top left corner of the cell at `point` relative to the grid, `None` when
+/// it is scrolled out of the viewport.
+pub fn cell_position(
+    point: Point,
+    term_size: &TerminalSize,
+    display_offset: usize,
+) -> Option<(f32, f32)> {
+    let point = point_to_viewport(display_offset, point)?;
+    if point.line >= term_size.screen_lines as usize {
+        return None;
+    }
+    let x = point.column.0 as f32 * term_size.cell_width as f32;
+    let y = point.line as f32 * term_size.cell_height as f32;
+    Some((x, y))
+}
+
 fn selection_side(cell_width: u16, x: f32) -> Side {
     let cell_x = x as usize % cell_width as usize;
     let half_cell_width = (cell_width as f32 / 2.0) as usize;
@@ -619,3 +1583,138 @@ impl EventListener for EventProxy {
         let _ = self.0.send(event);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alacritty_terminal::event::VoidListener;
+    use alacritty_terminal::vte::ansi;
+
+    fn term_size(cell_width: u16, cell_height: u16) -> TerminalSize {
+        TerminalSize {
+            cell_width,
+            cell_height,
+            columns: 80,
+            screen_lines: 24,
+            layout_size: Size::new(80. * cell_width as f32, 24. * cell_height as f32),
+        }
+    }
+
+    #[test]
+    fn cells_follow_the_font_size() {
+        let small = term_size(8, 16);
+        let large = term_size(10, 20);
+        let point = Point::new(Line(3), Column(5));
+
+        // the cursor is painted over the resized cell
+        assert_eq!(cell_position(point, &small, 0), Some((40., 48.)));
+        assert_eq!(cell_position(point, &large, 0), Some((50., 60.)));
+        assert_eq!(cell_position(point, &large, 22), None);
+        assert_eq!(
+            cell_position(Point::new(Line(-1), Column(0)), &large, 1),
+            Some((0., 0.))
+        );
+
+        // the pointer stays put, the cell under it changes
+        assert_eq!(selection_point(44., 52., &small, 0), point);
+        let moved = Point::new(Line(2), Column(4));
+        assert_eq!(selection_point(44., 52., &large, 0), moved);
+        assert_eq!(
+            selection_point(44., 52., &large, 5),
+            Point::new(Line(-3), Column(4))
+        );
+        assert_eq!(selection_side(small.cell_width, 44.), Side::Left);
+        assert_eq!(selection_side(large.cell_width, 47.), Side::Right);
+
+        // anchors are cells, painted wherever their cell is now
+        let (x, y) = cell_position(point, &large, 0).unwrap();
+        assert_eq!(selection_point(x, y, &large, 0), point);
+
+        // beyond the grid
+        let last = Point::new(Line(23), Column(79));
+        assert_eq!(selection_point(5000., 5000., &large, 0), last);
+    }
+
+    #[test]
+    fn selection_after_a_font_size_change() {
+        let layout = Size::new(400., 160.);
+        let small = TerminalSize::fitting(layout, Size::new(8., 16.)).unwrap();
+        let mut term = Term::new(Config::default(), &small, VoidListener);
+        let mut parser: ansi::Processor = ansi::Processor::new();
+        parser.advance(&mut term, b"first line\r\nsecond line\r\nthird line");
+
+        let large = TerminalSize::fitting(layout, Size::new(10., 20.)).unwrap();
+        term.resize(large);
+        assert_eq!((term.columns(), term.screen_lines()), (40, 8));
+
+        // a click on the large cells lands on the cell under the pointer
+        let click = selection_point(41., 25., &large, 0);
+        assert_eq!(term.grid()[click].c, 'n');
+        assert_eq!(term.grid()[selection_point(41., 25., &small, 0)].c, 'd');
+
+        // a drag over `second`, the end on the right half of its last cell
+        let start = selection_point(2., 25., &large, 0);
+        let mut selection = Selection::new(
+            SelectionType::Simple,
+            start,
+            selection_side(large.cell_width, 2.),
+        );
+        selection.update(
+            selection_point(58., 25., &large, 0),
+            selection_side(large.cell_width, 58.),
+        );
+        term.selection = Some(selection);
+        assert_eq!(term.selection_to_string().as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn selection_grows_word_then_line() {
+        let term = alacritty_terminal::term::test::mock_term("scp web:/tmp/a.txt .\r\n");
+        let point = |column| Point::new(Line(0), Column(column));
+        let units = selection_units(&term, point(12));
+        assert_eq!(
+            units,
+            [
+                (point(8), point(17)),
+                (point(4), point(17)),
+                (point(0), point(19)),
+            ]
+        );
+    }
+
+    #[test]
+    fn prompt_lines() {
+        let lines = || [Line(-40), Line(-12), Line(3)].into_iter();
+        assert_eq!(prompt_line(lines(), Line(-12), false), Some(Line(-40)));
+        assert_eq!(prompt_line(lines(), Line(-12), true), Some(Line(3)));
+        assert_eq!(prompt_line(lines(), Line(-40), false), None);
+        assert_eq!(prompt_line(lines(), Line(3), true), None);
+    }
+
+    #[test]
+    fn flow_control_chars() {
+        assert_eq!(flow_control(b"ls -l\r"), None);
+        assert_eq!(flow_control(&[XOFF]), Some(true));
+        assert_eq!(flow_control(&[XOFF, b'a', XON]), Some(false));
+        assert_eq!(flow_control(&[XON, XOFF]), Some(true));
+    }
+
+    #[test]
+    fn event_hooks() {
+        let hooks = EventHooks::default();
+        let (sender, receiver) = mpsc::channel();
+        let registering = hooks.clone();
+        hooks.register(Box::new(move |event| {
+            sender.send(matches!(event, Event::Bell)).unwrap();
+            // a hook may register another one without locking up
+            if matches!(event, Event::Bell) {
+                let sender = sender.clone();
+                registering.register(Box::new(move |_| sender.send(true).unwrap()));
+            }
+        }));
+        hooks.call(&Event::Bell);
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), [true]);
+        hooks.call(&Event::Wakeup);
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), [false, true]);
+    }
+}
@@ -6,7 +6,21 @@ pub enum BindingAction {
     Copy,
     Paste,
     SelectAll,
+    /// Grow the selection to the next unit around it: word, WORD, line, then
+    /// the output of the command and the command along with it.
+    ExpandSelection,
+    /// Extend the selection to the clicked cell rather than starting a new
+    /// one.
+    ExtendSelection,
+    /// Drag a rectangle rather than lines.
+    BlockSelection,
     LinkOpen,
+    /// Open the scrollback search bar.
+    SearchForward,
+    /// Scroll to the command above the view, e.g. with the back button.
+    PreviousPrompt,
+    /// Scroll to the command below the top of the view.
+    NextPrompt,
     /// Reset font size to the config value.
     ResetFontSize,
     /// Increase font size.
@@ -21,6 +35,8 @@ pub enum BindingAction {
 pub enum InputKind {
     KeyCode(Key),
     Mouse(PointerButton),
+    /// A click right after another one on the same cell.
+    DoubleClick(PointerButton),
     Char(char),
 }
 
@@ -118,6 +134,21 @@ impl Bindings {
         }
     }
 
+    /// Bind `action` of `input` to `modifiers` only, e.g. the block selection
+    /// to Ctrl+Alt where Alt+drag moves the windows. The action bound to the
+    /// modifiers before is replaced.
+    pub fn rebind(&mut self, input: InputKind, modifiers: Modifiers, action: BindingAction) {
+        self.layout
+            .retain(|(binding, bound)| binding.target != input || bound != &action);
+        let binding = Binding {
+            target: input,
+            modifiers,
+            term_mode_include: TermMode::empty(),
+            term_mode_exclude: TermMode::empty(),
+        };
+        self.add_bindings(vec![(binding, action)]);
+    }
+
     pub fn get_action(
         &self,
         input: InputKind,
@@ -139,6 +170,173 @@ impl Bindings {
     }
 }
 
+/// How Alt, or Option on macOS, modifies the keys typed along with it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MetaKeys {
+    /// Alt+key sends ESC then the key, like most shells and editors expect,
+    /// rather than the key with its eighth bit set.
+    pub esc_prefix: bool,
+    /// AltGr, reported as Ctrl+Alt, is Alt too rather than typing the third
+    /// level of the keyboard layout.
+    pub altgr_as_alt: bool,
+    /// Option is Meta on macOS rather than typing the special chars of the
+    /// keyboard layout.
+    pub option_as_meta: bool,
+}
+
+impl Default for MetaKeys {
+    fn default() -> Self {
+        Self {
+            esc_prefix: true,
+            altgr_as_alt: false,
+            option_as_meta: false,
+        }
+    }
+}
+
+impl MetaKeys {
+    /// Whether the keys typed with `modifiers` are typed with Meta.
+    pub(crate) fn is_meta(&self, modifiers: Modifiers) -> bool {
+        if !modifiers.alt {
+            return false;
+        }
+        if cfg!(target_os = "macos") {
+            return self.option_as_meta;
+        }
+        !modifiers.ctrl || self.altgr_as_alt
+    }
+
+    /// `text` typed with Meta.
+    pub(crate) fn meta_text(&self, text: &str) -> String {
+        text.chars()
+            .map(|c| match (self.esc_prefix, c.is_ascii()) {
+                (true, _) => format!("\x1b{c}"),
+                // like xterm in UTF-8, the eighth bit set on the char
+                (false, true) => char::from(c as u8 | 0x80).to_string(),
+                (false, false) => c.to_string(),
+            })
+            .collect()
+    }
+
+    /// Letter or digit `key` typed with Meta, whatever the layout makes of
+    /// it with Alt, e.g. `∫` for Option+B on macOS.
+    pub(crate) fn meta_key(&self, key: Key, modifiers: Modifiers) -> Option<String> {
+        if !self.is_meta(modifiers) {
+            return None;
+        }
+        let mut name = key.name().chars();
+        let c = name.next().filter(char::is_ascii_alphanumeric)?;
+        // the shifted digits depend on the layout, they are left to the text
+        if name.next().is_some() || (modifiers.shift && c.is_ascii_digit()) {
+            return None;
+        }
+        let c = match (modifiers.ctrl, modifiers.shift) {
+            (true, _) if c.is_ascii_alphabetic() => char::from(c.to_ascii_uppercase() as u8 & 0x1f),
+            (_, true) => c.to_ascii_uppercase(),
+            _ => c.to_ascii_lowercase(),
+        };
+        Some(self.meta_text(&c.to_string()))
+    }
+}
+
+/// xterm's modifier parameter of `modifiers`, `None` without any.
+fn modifier_param(modifiers: Modifiers) -> Option<u8> {
+    let param = 1 + modifiers.shift as u8 + 2 * modifiers.alt as u8 + 4 * modifiers.ctrl as u8;
+    (param > 1).then_some(param)
+}
+
+/// Function and editing keys with the modifiers the bindings lack, e.g.
+/// Shift+F5, as xterm sends them.
+///
+/// The application keypad mode (DECKPAM) isn't supported: egui has no keys
+/// of the numeric keypad, the logical and the physical key of Numpad1 are
+/// both `Key::Num1`, so keypad keys always send what the main ones do.
+pub(crate) fn modified_key(key: Key, modifiers: Modifiers) -> Option<String> {
+    let param = modifier_param(modifiers)?;
+    let tilde = |code: u8| Some(format!("\x1b[{code};{param}~"));
+    let letter = |c: char| Some(format!("\x1b[1;{param}{c}"));
+    match key {
+        Key::F1 => letter('P'),
+        Key::F2 => letter('Q'),
+        Key::F3 => letter('R'),
+        Key::F4 => letter('S'),
+        Key::F5 => tilde(15),
+        Key::F6 => tilde(17),
+        Key::F7 => tilde(18),
+        Key::F8 => tilde(19),
+        Key::F9 => tilde(20),
+        Key::F10 => tilde(21),
+        Key::F11 => tilde(23),
+        Key::F12 => tilde(24),
+        Key::F13 => tilde(25),
+        Key::F14 => tilde(26),
+        Key::F15 => tilde(28),
+        Key::F16 => tilde(29),
+        Key::F17 => tilde(31),
+        Key::F18 => tilde(32),
+        Key::F19 => tilde(33),
+        Key::F20 => tilde(34),
+        Key::Insert => tilde(2),
+        Key::Delete => tilde(3),
+        Key::PageUp => tilde(5),
+        Key::PageDown => tilde(6),
+        Key::ArrowUp => letter('A'),
+        Key::ArrowDown => letter('B'),
+        Key::ArrowRight => letter('C'),
+        Key::ArrowLeft => letter('D'),
+        Key::Home => letter('H'),
+        Key::End => letter('F'),
+        _ => None,
+    }
+}
+
+/// Code of `key` in the modifyOtherKeys encoding, the char it types.
+fn other_key_code(key: Key, shift: bool) -> Option<u32> {
+    let c = match key {
+        Key::Enter => '\r',
+        Key::Tab => '\t',
+        Key::Backspace => '\x7f',
+        Key::Escape => '\x1b',
+        Key::Space => ' ',
+        _ => {
+            let mut symbol = key.symbol_or_name().chars();
+            let c = symbol.next().filter(char::is_ascii_graphic)?;
+            if symbol.next().is_some() {
+                return None;
+            }
+            match shift {
+                true => c.to_ascii_uppercase(),
+                false => c.to_ascii_lowercase(),
+            }
+        }
+    };
+    Some(c as u32)
+}
+
+/// `key` with `modifiers` as `CSI 27 ; mods ; code ~` once the shell enabled
+/// xterm's modifyOtherKeys. At level 1, only the combinations which send
+/// nothing well-defined, the `unbound` ones with Ctrl, e.g. Ctrl+Enter or
+/// Ctrl+Shift+A. At level 2, every combination but the shifted chars.
+pub(crate) fn modify_other_keys(
+    key: Key,
+    modifiers: Modifiers,
+    mode: TermMode,
+    unbound: bool,
+) -> Option<String> {
+    let param = modifier_param(modifiers)?;
+    let code = other_key_code(key, modifiers.shift)?;
+    let encoded = if mode.contains(TermMode::MODIFY_ALL_KEYS) {
+        modifiers.ctrl
+            || modifiers.alt
+            || !char::from_u32(code).is_some_and(|c| c.is_ascii_graphic())
+    } else if mode.contains(TermMode::MODIFY_OTHER_KEYS) {
+        unbound && modifiers.ctrl
+    } else {
+        false
+    };
+    encoded.then(|| format!("\x1b[27;{param};{code}~"))
+}
+
 fn default_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
     generate_bindings!(
         KeyboardBinding;
@@ -195,10 +393,10 @@ fn default_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
         Delete,       Modifiers::CTRL; BindingAction::Esc("\x1b[3;5~".into());
         PageUp,       Modifiers::CTRL; BindingAction::Esc("\x1b[5;5~".into());
         PageDown,     Modifiers::CTRL; BindingAction::Esc("\x1b[6;5~".into());
-        F1,           Modifiers::CTRL; BindingAction::Esc("\x1bO;5P".into());
-        F2,           Modifiers::CTRL; BindingAction::Esc("\x1bO;5Q".into());
-        F3,           Modifiers::CTRL; BindingAction::Esc("\x1bO;5R".into());
-        F4,           Modifiers::CTRL; BindingAction::Esc("\x1bO;5S".into());
+        F1,           Modifiers::CTRL; BindingAction::Esc("\x1b[1;5P".into());
+        F2,           Modifiers::CTRL; BindingAction::Esc("\x1b[1;5Q".into());
+        F3,           Modifiers::CTRL; BindingAction::Esc("\x1b[1;5R".into());
+        F4,           Modifiers::CTRL; BindingAction::Esc("\x1b[1;5S".into());
         F5,           Modifiers::CTRL; BindingAction::Esc("\x1b[15;5~".into());
         F6,           Modifiers::CTRL; BindingAction::Esc("\x1b[17;5~".into());
         F7,           Modifiers::CTRL; BindingAction::Esc("\x1b[18;5~".into());
@@ -227,7 +425,7 @@ fn default_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
         R,            Modifiers::CTRL; BindingAction::Char('\x12');
         S,            Modifiers::CTRL; BindingAction::Char('\x13');
         T,            Modifiers::CTRL; BindingAction::Char('\x14');
-        U,            Modifiers::CTRL; BindingAction::Char('\x51');
+        U,            Modifiers::CTRL; BindingAction::Char('\x15');
         V,            Modifiers::CTRL; BindingAction::Char('\x16');
         W,            Modifiers::CTRL; BindingAction::Char('\x17');
         X,            Modifiers::CTRL; BindingAction::Char('\x18');
@@ -237,6 +435,7 @@ fn default_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
         CloseBracket, Modifiers::CTRL; BindingAction::Char('\x1d');
         Backslash,    Modifiers::CTRL; BindingAction::Char('\x1c');
         Minus,        Modifiers::CTRL; BindingAction::Char('\x1f');
+        Space,        Modifiers::CTRL; BindingAction::Char('\x00');
         // SHIFT
         Enter,      Modifiers::SHIFT; BindingAction::Char('\x0d');
         Backspace,  Modifiers::SHIFT; BindingAction::Char('\x7f');
@@ -253,7 +452,7 @@ fn default_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
         Backspace,  Modifiers::ALT; BindingAction::Esc("\x1b\x7f".into());
         End,        Modifiers::ALT; BindingAction::Esc("\x1b[1;3F".into());
         Home,       Modifiers::ALT; BindingAction::Esc("\x1b[1;3H".into());
-        Insert,     Modifiers::ALT; BindingAction::Esc("\x1b[3;2~".into());
+        Insert,     Modifiers::ALT; BindingAction::Esc("\x1b[2;3~".into());
         Delete,     Modifiers::ALT; BindingAction::Esc("\x1b[3;3~".into());
         PageUp,     Modifiers::ALT; BindingAction::Esc("\x1b[5;3~".into());
         PageDown,   Modifiers::ALT; BindingAction::Esc("\x1b[6;3~".into());
@@ -280,7 +479,7 @@ fn default_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
         // C,        Modifiers::SHIFT | Modifiers::CTRL; BindingAction::Char('\x03');
         D,        Modifiers::SHIFT | Modifiers::CTRL; BindingAction::Char('\x04');
         E,        Modifiers::SHIFT | Modifiers::CTRL; BindingAction::Char('\x05');
-        F,        Modifiers::SHIFT | Modifiers::CTRL; BindingAction::Char('\x06');
+        // F,        Modifiers::SHIFT | Modifiers::CTRL; BindingAction::Char('\x06');
         G,        Modifiers::SHIFT | Modifiers::CTRL; BindingAction::Char('\x07');
         H,        Modifiers::SHIFT | Modifiers::CTRL; BindingAction::Char('\x08');
         I,        Modifiers::SHIFT | Modifiers::CTRL; BindingAction::Char('\x09');
@@ -328,8 +527,10 @@ fn platform_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
     generate_bindings!(
         KeyboardBinding;
         A,      Modifiers::MAC_CMD;     BindingAction::SelectAll;
+        Space,  Modifiers::MAC_CMD | Modifiers::SHIFT; BindingAction::ExpandSelection;
         C,      Modifiers::MAC_CMD;     BindingAction::Copy;
         V,      Modifiers::MAC_CMD;     BindingAction::Paste;
+        F,      Modifiers::MAC_CMD;     BindingAction::SearchForward;
         Num0,   Modifiers::MAC_CMD;     BindingAction::ResetFontSize;
         Equals, Modifiers::MAC_CMD;     BindingAction::IncreaseFontSize;
         Plus,   Modifiers::MAC_CMD;     BindingAction::IncreaseFontSize;
@@ -342,8 +543,10 @@ fn platform_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
     generate_bindings!(
         KeyboardBinding;
         A,      Modifiers::CTRL | Modifiers::SHIFT;  BindingAction::SelectAll;
+        Space,  Modifiers::CTRL | Modifiers::SHIFT;  BindingAction::ExpandSelection;
         C,      Modifiers::SHIFT | Modifiers::COMMAND; BindingAction::Copy;
         V,      Modifiers::SHIFT | Modifiers::COMMAND; BindingAction::Paste;
+        F,      Modifiers::SHIFT | Modifiers::COMMAND; BindingAction::SearchForward;
         Num0,   Modifiers::CTRL;                       BindingAction::ResetFontSize;
         Equals, Modifiers::CTRL;                       BindingAction::IncreaseFontSize;
         Plus,   Modifiers::CTRL;                       BindingAction::IncreaseFontSize;
@@ -351,16 +554,40 @@ fn platform_keyboard_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
     )
 }
 
+/// Modifiers of the block selection, Alt+drag moves the windows on many
+/// Linux desktops.
+#[cfg(target_os = "linux")]
+pub const BLOCK_SELECTION_MODIFIERS: Modifiers = Modifiers::CTRL.plus(Modifiers::ALT);
+#[cfg(not(target_os = "linux"))]
+pub const BLOCK_SELECTION_MODIFIERS: Modifiers = Modifiers::ALT;
+
 fn mouse_default_bindings() -> Vec<(Binding<InputKind>, BindingAction)> {
-    generate_bindings!(
+    let mut bindings = generate_bindings!(
         MouseBinding;
         Primary, Modifiers::COMMAND; BindingAction::LinkOpen;
-    )
+        Primary, Modifiers::SHIFT;   BindingAction::ExtendSelection;
+        Primary, BLOCK_SELECTION_MODIFIERS; BindingAction::BlockSelection;
+        Middle,  ~TermMode::MOUSE_MODE; BindingAction::Paste;
+        Extra1,  ~TermMode::MOUSE_MODE; BindingAction::PreviousPrompt;
+        Extra2,  ~TermMode::MOUSE_MODE; BindingAction::NextPrompt;
+    );
+    // Ctrl+double-click grows the selection rather than selecting the word
+    let expand = Binding {
+        target: InputKind::DoubleClick(PointerButton::Primary),
+        modifiers: Modifiers::COMMAND,
+        term_mode_include: TermMode::empty(),
+        term_mode_exclude: TermMode::empty(),
+    };
+    bindings.push((expand, BindingAction::ExpandSelection));
+    bindings
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{BindingAction, Bindings, InputKind, KeyboardBinding};
+    use super::{
+        modified_key, modify_other_keys, BindingAction, Bindings, InputKind, KeyboardBinding,
+        MetaKeys,
+    };
     use crate::bindings::MouseBinding;
     use alacritty_terminal::term::TermMode;
     use egui::{Key, Modifiers, PointerButton};
@@ -481,6 +708,68 @@ mod tests {
         }
     }
 
+    #[test]
+    fn extend_selection_binding() {
+        let mut layout = Bindings::new();
+        let click = InputKind::Mouse(PointerButton::Primary);
+        let shift_click = layout.get_action(click.clone(), Modifiers::SHIFT, TermMode::NONE);
+        assert_eq!(shift_click, Some(BindingAction::ExtendSelection));
+
+        layout.add_bindings(generate_bindings!(
+            MouseBinding;
+            Primary, Modifiers::SHIFT; BindingAction::Paste;
+            Primary, Modifiers::ALT;   BindingAction::ExtendSelection;
+        ));
+        let alt_click = layout.get_action(click, Modifiers::ALT, TermMode::NONE);
+        assert_eq!(alt_click, Some(BindingAction::ExtendSelection));
+    }
+
+    #[test]
+    fn remap_the_block_selection() {
+        let mut layout = Bindings::new();
+        let click = InputKind::Mouse(PointerButton::Primary);
+        let action = |layout: &Bindings, modifiers| {
+            layout.get_action(click.clone(), modifiers, TermMode::NONE)
+        };
+        assert_eq!(
+            action(&layout, super::BLOCK_SELECTION_MODIFIERS),
+            Some(BindingAction::BlockSelection)
+        );
+
+        let modifiers = Modifiers::ALT | Modifiers::SHIFT;
+        layout.rebind(click.clone(), modifiers, BindingAction::BlockSelection);
+        assert_eq!(
+            action(&layout, modifiers),
+            Some(BindingAction::BlockSelection)
+        );
+        assert_eq!(action(&layout, super::BLOCK_SELECTION_MODIFIERS), None);
+        assert_eq!(
+            action(&layout, Modifiers::SHIFT),
+            Some(BindingAction::ExtendSelection)
+        );
+        let double_click = InputKind::DoubleClick(PointerButton::Primary);
+        assert_eq!(
+            layout.get_action(double_click, Modifiers::COMMAND, TermMode::NONE),
+            Some(BindingAction::ExpandSelection)
+        );
+    }
+
+    #[test]
+    fn extra_button_bindings() {
+        let layout = Bindings::new();
+        let action =
+            |button, mode| layout.get_action(InputKind::Mouse(button), Modifiers::NONE, mode);
+        assert_eq!(
+            action(PointerButton::Middle, TermMode::NONE),
+            Some(BindingAction::Paste)
+        );
+        assert_eq!(
+            action(PointerButton::Extra1, TermMode::NONE),
+            Some(BindingAction::PreviousPrompt)
+        );
+        assert_eq!(action(PointerButton::Extra2, TermMode::SGR_MOUSE), None);
+    }
+
     #[test]
     fn get_action_with_custom_bindings() {
         let mut current_layout = Bindings::default();
@@ -502,4 +791,87 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn meta_keys() {
+        let alt = Modifiers::ALT;
+        let meta = MetaKeys::default();
+        assert_eq!(meta.meta_text("f"), "\x1bf");
+        let eight_bit = MetaKeys {
+            esc_prefix: false,
+            ..meta
+        };
+        assert_eq!(eight_bit.meta_text("f"), "\u{e6}");
+        assert_eq!(eight_bit.meta_text("é"), "é");
+        assert!(!meta.is_meta(Modifiers::SHIFT));
+
+        if cfg!(target_os = "macos") {
+            assert_eq!(meta.meta_key(Key::B, alt), None);
+            let option = MetaKeys {
+                option_as_meta: true,
+                ..meta
+            };
+            assert_eq!(option.meta_key(Key::B, alt).as_deref(), Some("\x1bb"));
+        } else {
+            assert_eq!(meta.meta_key(Key::B, alt).as_deref(), Some("\x1bb"));
+            assert_eq!(
+                meta.meta_key(Key::B, alt | Modifiers::SHIFT).as_deref(),
+                Some("\x1bB")
+            );
+            assert_eq!(meta.meta_key(Key::Num1, alt).as_deref(), Some("\x1b1"));
+            assert_eq!(meta.meta_key(Key::Num1, alt | Modifiers::SHIFT), None);
+            assert_eq!(meta.meta_key(Key::Enter, alt), None);
+            // AltGr
+            let altgr = alt | Modifiers::CTRL;
+            assert_eq!(meta.meta_key(Key::Q, altgr), None);
+            let altgr_as_alt = MetaKeys {
+                altgr_as_alt: true,
+                ..meta
+            };
+            assert_eq!(
+                altgr_as_alt.meta_key(Key::Q, altgr).as_deref(),
+                Some("\x1b\x11")
+            );
+        }
+    }
+
+    #[test]
+    fn modified_function_keys() {
+        let shift = Modifiers::SHIFT;
+        assert_eq!(modified_key(Key::F5, shift).as_deref(), Some("\x1b[15;2~"));
+        assert_eq!(
+            modified_key(Key::F1, Modifiers::CTRL | Modifiers::ALT).as_deref(),
+            Some("\x1b[1;7P")
+        );
+        assert_eq!(modified_key(Key::Home, shift).as_deref(), Some("\x1b[1;2H"));
+        assert_eq!(modified_key(Key::F5, Modifiers::NONE), None);
+        assert_eq!(modified_key(Key::A, shift), None);
+
+        let ctrl = Modifiers::CTRL;
+        let level1 = TermMode::MODIFY_OTHER_KEYS;
+        let level2 = TermMode::MODIFY_ALL_KEYS;
+        let other =
+            |key, modifiers, mode, unbound| modify_other_keys(key, modifiers, mode, unbound);
+        assert_eq!(other(Key::Enter, ctrl, TermMode::NONE, true), None);
+        assert_eq!(
+            other(Key::Enter, ctrl, level1, true).as_deref(),
+            Some("\x1b[27;5;13~")
+        );
+        assert_eq!(
+            other(Key::A, ctrl | Modifiers::SHIFT, level1, true).as_deref(),
+            Some("\x1b[27;6;65~")
+        );
+        // Ctrl+A is well-defined
+        assert_eq!(other(Key::A, ctrl, level1, false), None);
+        assert_eq!(
+            other(Key::A, ctrl, level2, false).as_deref(),
+            Some("\x1b[27;5;97~")
+        );
+        assert_eq!(
+            other(Key::Tab, Modifiers::SHIFT, level2, false).as_deref(),
+            Some("\x1b[27;2;9~")
+        );
+        assert_eq!(other(Key::A, Modifiers::SHIFT, level2, true), None);
+        assert_eq!(other(Key::ArrowUp, ctrl, level2, false), None);
+    }
 }
@@ -0,0 +1,180 @@
+//! Output of a color test script, to try a theme out without connecting
+//! anywhere: the 16 colors of the palette, the 256 color cube and grays, then
+//! truecolor gradients.
+
+use crate::theme::TerminalTheme;
+use alacritty_terminal::event::VoidListener;
+use alacritty_terminal::index::{Column, Line};
+use alacritty_terminal::term::test::TermSize;
+use alacritty_terminal::term::{Config, Term};
+use alacritty_terminal::vte::ansi::{self, Color, NamedColor};
+use egui::{Align2, CornerRadius, Rect, Response, Sense, TextStyle, Vec2};
+use std::fmt::Write;
+
+const COLUMNS: usize = 72;
+const COLOR_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// Cells next to each other on a line with the same colors.
+#[derive(Debug, Clone, PartialEq)]
+struct ColorRun {
+    fg: Color,
+    bg: Color,
+    text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorTest {
+    lines: Vec<Vec<ColorRun>>,
+}
+
+impl Default for ColorTest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ColorTest {
+    /// Run the script through a terminal without a shell, once.
+    pub fn new() -> Self {
+        let script = color_test_script();
+        let screen_lines = script.split("\r\n").count();
+        let size = TermSize::new(COLUMNS, screen_lines);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser: ansi::Processor = ansi::Processor::new();
+        parser.advance(&mut term, script.as_bytes());
+
+        let lines = (0..screen_lines)
+            .map(|line| {
+                let row = &term.grid()[Line(line as i32)];
+                let mut runs: Vec<ColorRun> = vec![];
+                for column in 0..COLUMNS {
+                    let cell = &row[Column(column)];
+                    match runs.last_mut() {
+                        Some(run) if run.fg == cell.fg && run.bg == cell.bg => {
+                            run.text.push(cell.c)
+                        }
+                        _ => runs.push(ColorRun {
+                            fg: cell.fg,
+                            bg: cell.bg,
+                            text: cell.c.to_string(),
+                        }),
+                    }
+                }
+                runs
+            })
+            .collect();
+        Self { lines }
+    }
+
+    /// Paint the output in the colors of `theme`.
+    pub fn show(&self, ui: &mut egui::Ui, theme: &TerminalTheme) -> Response {
+        let font = TextStyle::Monospace.resolve(ui.style());
+        let cell = ui.fonts(|f| Vec2::new(f.glyph_width(&font, 'M'), f.row_height(&font)));
+        let size = Vec2::new(cell.x * COLUMNS as f32, cell.y * self.lines.len() as f32);
+        let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+        let painter = ui.painter_at(rect);
+
+        let background = theme.get_color(Color::Named(NamedColor::Background));
+        painter.rect_filled(rect, CornerRadius::ZERO, background);
+        for (line, runs) in self.lines.iter().enumerate() {
+            let mut column = 0;
+            for run in runs {
+                let len = run.text.chars().count();
+                let min = rect.min + Vec2::new(column as f32 * cell.x, line as f32 * cell.y);
+                let bg = theme.get_color(run.bg);
+                if bg != background {
+                    let run_rect = Rect::from_min_size(min, Vec2::new(len as f32 * cell.x, cell.y));
+                    painter.rect_filled(run_rect, CornerRadius::ZERO, bg);
+                }
+                let fg = theme.get_color(run.fg);
+                painter.text(min, Align2::LEFT_TOP, &run.text, font.clone(), fg);
+                column += len;
+            }
+        }
+        response
+    }
+}
+
+/// Escape codes of the test, a screen line per line.
+fn color_test_script() -> String {
+    let mut lines = vec![];
+    // the 16 colors of the palette, as foregrounds then as backgrounds
+    for base in [30, 90] {
+        let mut line = String::new();
+        for (i, name) in COLOR_NAMES.iter().enumerate() {
+            let _ = write!(line, "\x1b[{}m{name:<9}", base + i);
+        }
+        lines.push(line);
+    }
+    for (base, first) in [(40, 0), (100, 8)] {
+        let mut line = String::new();
+        for i in 0..8 {
+            let _ = write!(line, "\x1b[{}m {:<7}\x1b[0m ", base + i, first + i);
+        }
+        lines.push(line);
+    }
+    lines.push(String::new());
+
+    // the 6x6x6 cube, a green level per line, then the grays
+    for g in 0..6 {
+        let mut line = String::new();
+        for r in 0..6 {
+            for b in 0..6 {
+                let _ = write!(line, "\x1b[48;5;{}m  ", 16 + 36 * r + 6 * g + b);
+            }
+        }
+        lines.push(line);
+    }
+    let mut grays = String::new();
+    for i in 0..24 {
+        let _ = write!(grays, "\x1b[48;5;{}m   ", 232 + i);
+    }
+    lines.push(grays);
+    lines.push(String::new());
+
+    // truecolor, from red through green to blue
+    let gradient = |i: usize| {
+        let x = (i * 255 / (COLUMNS - 1)) as i32;
+        let g = 255 - (2 * x - 255).abs();
+        (255 - x, g, x)
+    };
+    let mut background = String::new();
+    let mut foreground = String::new();
+    let text: Vec<char> = "The quick brown fox jumps over the lazy dog, 24-bit colors"
+        .chars()
+        .collect();
+    for i in 0..COLUMNS {
+        let (r, g, b) = gradient(i);
+        let _ = write!(background, "\x1b[48;2;{r};{g};{b}m ");
+        let c = text.get(i).copied().unwrap_or(' ');
+        let _ = write!(foreground, "\x1b[38;2;{r};{g};{b}m{c}");
+    }
+    lines.push(background);
+    lines.push(foreground);
+
+    lines
+        .into_iter()
+        .map(|line| line + "\x1b[0m")
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_test_output() {
+        let test = ColorTest::new();
+        assert_eq!(test.lines.len(), 15);
+        assert_eq!(test.lines[0][0].fg, Color::Named(NamedColor::Black));
+        assert_eq!(test.lines[0][0].text.trim_end(), "black");
+        assert_eq!(test.lines[5][0].bg, Color::Indexed(16));
+        assert_eq!(test.lines[5].len(), 36);
+        assert_eq!(test.lines[11].len(), 24);
+        assert!(matches!(test.lines[13][0].bg, Color::Spec(_)));
+        assert!(test.lines[14][0].text.starts_with('T'));
+    }
+}
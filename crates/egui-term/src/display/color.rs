@@ -2,3 +2,6 @@ use egui::Color32;
 
 pub const HOVERED_HYPERLINK_COLOR: Color32 = Color32::from_rgb(102, 217, 239);
 pub const STRING_COLOR: Color32 = Color32::from_rgb(230, 219, 116);
+pub const SEARCH_MATCH_COLOR: Color32 = Color32::from_rgb(172, 142, 46);
+pub const SEARCH_FOCUSED_MATCH_COLOR: Color32 = Color32::from_rgb(244, 191, 117);
+pub const SEARCH_MATCH_TEXT_COLOR: Color32 = Color32::from_rgb(24, 24, 24);
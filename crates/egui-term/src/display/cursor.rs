@@ -0,0 +1,77 @@
+//! Cursor gliding from cell to cell with a fading trail, for the eyes to
+//! follow its jumps on large screens.
+
+use egui::epaint::RectShape;
+use egui::{Color32, CornerRadius, Rect, Shape, Stroke};
+
+/// Time for the cursor to cover most of the way to its cell, in seconds.
+const GLIDE_TIME: f32 = 0.04;
+/// Closer to its cell, in pixels, the cursor is snapped onto it.
+const SNAP_DISTANCE: f32 = 0.5;
+const TRAIL_OPACITY: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct CursorAnimation {
+    /// Where the cursor was painted on the last frame.
+    rect: Option<Rect>,
+}
+
+impl CursorAnimation {
+    /// Move the cursor `dt` seconds further toward `target`. Returns the
+    /// shapes of the cursor and of its trail, and whether it still moves.
+    pub(crate) fn step(&mut self, target: Rect, dt: f32, color: Color32) -> (Vec<Shape>, bool) {
+        let from = self.rect.unwrap_or(target);
+        // the closer, the slower, whatever the frame rate
+        let t = 1. - (-dt / GLIDE_TIME).exp();
+        let mut rect = Rect::from_min_max(
+            from.min + (target.min - from.min) * t,
+            from.max + (target.max - from.max) * t,
+        );
+        let moving = (rect.min - target.min).length() > SNAP_DISTANCE
+            || (rect.max - target.max).length() > SNAP_DISTANCE;
+        if !moving {
+            rect = target;
+        }
+        self.rect = Some(rect);
+
+        let mut shapes = vec![];
+        if from.center() != rect.center() {
+            let trail = Stroke::new(rect.height() * 0.5, color.gamma_multiply(TRAIL_OPACITY));
+            shapes.push(Shape::line_segment([from.center(), rect.center()], trail));
+        }
+        shapes.push(Shape::Rect(RectShape::filled(
+            rect,
+            CornerRadius::default(),
+            color,
+        )));
+        (shapes, moving)
+    }
+
+    /// The cursor shows up at once where it is next painted.
+    pub(crate) fn reset(&mut self) {
+        self.rect = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{Pos2, Vec2};
+
+    #[test]
+    fn glide_to_the_cell() {
+        let cell = |x: f32| Rect::from_min_size(Pos2::new(x, 0.), Vec2::new(8., 16.));
+        let mut animation = CursorAnimation::default();
+        let (shapes, moving) = animation.step(cell(0.), 0.016, Color32::WHITE);
+        assert_eq!((shapes.len(), moving), (1, false));
+
+        let (shapes, moving) = animation.step(cell(800.), 0.016, Color32::WHITE);
+        assert_eq!((shapes.len(), moving), (2, true));
+        let mut frames = 1;
+        while animation.step(cell(800.), 0.016, Color32::WHITE).1 {
+            frames += 1;
+        }
+        assert!(frames < 30, "{frames} frames");
+        assert_eq!(animation.rect, Some(cell(800.)));
+    }
+}
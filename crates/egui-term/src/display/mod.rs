@@ -1,64 +1,132 @@
 #![allow(dead_code)]
 mod color;
+mod cursor;
+mod snapshot;
 
-use crate::display::color::HOVERED_HYPERLINK_COLOR;
+pub(crate) use cursor::CursorAnimation;
+pub(crate) use snapshot::RenderSnapshot;
+
+use crate::display::color::{
+    HOVERED_HYPERLINK_COLOR, SEARCH_FOCUSED_MATCH_COLOR, SEARCH_MATCH_COLOR,
+    SEARCH_MATCH_TEXT_COLOR,
+};
+use crate::highlight::{highlight_input, Highlight};
+use crate::redact::REDACTED_CHAR;
+use crate::scroll_bar::ScrollMarker;
 use crate::view::TerminalViewState;
-use crate::TerminalView;
-use alacritty_terminal::grid::GridCell;
+use crate::{TerminalTint, TerminalView};
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Line, Point};
 use alacritty_terminal::term::cell::Flags;
-use alacritty_terminal::term::TermMode;
+use alacritty_terminal::term::{CommandBlock, TermMode};
 use alacritty_terminal::vte::ansi::{Color, NamedColor};
 use egui::epaint::RectShape;
-use egui::{Align2, CornerRadius, CursorIcon, Painter, Pos2, Rect, Response, Vec2};
-use egui::{Shape, Stroke};
+use egui::{
+    Align2, Color32, CornerRadius, CursorIcon, FontId, Painter, Pos2, Rect, Response, Vec2,
+};
+use egui::{Shape, Stroke, StrokeKind};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Shorter outputs aren't worth folding, they get no summary.
+const MIN_FOLDED_LINES: usize = 10;
+/// Width of the gutter showing the exit codes of the commands, in cells.
+pub(crate) const EXIT_CODE_GUTTER_CELLS: f32 = 3.;
+/// Opacity of the background painted over the terminals without the focus.
+const UNFOCUSED_DIM: f32 = 0.35;
+const FOCUS_BORDER_WIDTH: f32 = 2.;
+/// How long the view flashes when the bell rings, and how bright it starts.
+const VISUAL_BELL_DURATION: Duration = Duration::from_millis(150);
+const VISUAL_BELL_OPACITY: f32 = 0.3;
 
 impl TerminalView<'_> {
-    pub fn show(self, state: &mut TerminalViewState, layout: &Response, painter: &Painter) {
+    pub(crate) fn show(
+        self,
+        snapshot: &RenderSnapshot,
+        state: &mut TerminalViewState,
+        layout: &Response,
+        painter: &Painter,
+    ) {
         let layout_min = layout.rect.min;
         let layout_max = layout.rect.max;
         let cell_height = self.term_ctx.size.cell_height as f32;
         let cell_width = self.term_ctx.size.cell_width as f32;
+        let text_offset = self.options.font.text_offset(&layout.ctx);
+
+        let offset = self.term_ctx.line_wrap.offset;
 
         let global_bg = self.theme().get_color(Color::Named(NamedColor::Background));
+        let wrap_marker = Stroke::new(
+            2.,
+            self.theme()
+                .get_color(Color::Named(NamedColor::Foreground))
+                .linear_multiply(0.4),
+        );
+        let painted_bg = match self.tint {
+            Some(tint) => tint.tint_background(global_bg),
+            None => global_bg,
+        };
 
         let mut shapes = vec![Shape::Rect(RectShape::filled(
             Rect::from_min_max(layout_min, layout_max),
             CornerRadius::ZERO,
-            global_bg,
+            painted_bg,
         ))];
 
-        let grid = self.term_ctx.terminal.grid();
+        // painted over the cells
+        let mut wrap_markers = vec![];
 
-        for indexed in grid.display_iter() {
-            let is_wide_char_spacer = indexed.flags().contains(Flags::WIDE_CHAR_SPACER);
-            if is_wide_char_spacer {
-                continue;
-            }
-            let is_app_cursor_mode = self.term_ctx.term_mode().contains(TermMode::APP_CURSOR);
-            let is_inverse = indexed.flags().contains(Flags::INVERSE);
-            let is_dim = indexed.flags().intersects(Flags::DIM | Flags::DIM_BOLD);
-            let is_wide_char = indexed.flags().contains(Flags::WIDE_CHAR);
-            let is_selected = self
-                .term_ctx
-                .to_range()
+        // Only the matches intersecting the viewport are relevant for painting.
+        let is_searching = self.term_ctx.search.is_highlighting();
+        let viewport_top = Line(-(snapshot.display_offset as i32));
+        let viewport_bottom = viewport_top + (snapshot.screen_lines as i32 - 1);
+        let focused_match = self.term_ctx.search.focused_match();
+        let visible_matches: Vec<_> = self
+            .term_ctx
+            .search
+            .matches()
+            .iter()
+            .filter(|m| m.end().line >= viewport_top && m.start().line <= viewport_bottom)
+            .collect();
+
+        let input_colors = self.input_colors(snapshot);
+        let is_app_cursor_mode = snapshot.mode.contains(TermMode::APP_CURSOR);
+        let cursor = snapshot.shown_cursor();
+
+        for indexed in &snapshot.cells {
+            let is_inverse = indexed.flags.contains(Flags::INVERSE);
+            let is_dim = indexed.flags.intersects(Flags::DIM | Flags::DIM_BOLD);
+            let is_bold = indexed.flags.contains(Flags::BOLD);
+            let is_wide_char = indexed.flags.contains(Flags::WIDE_CHAR);
+            let is_selected = snapshot
+                .selection
                 .is_some_and(|r| r.contains(indexed.point));
             let is_hovered_hyperlink = self
                 .term_ctx
                 .hovered_hyperlink
                 .as_ref()
                 .is_some_and(|r| r.contains(&indexed.point) && r.contains(&state.mouse_point));
-            let is_text_cell = indexed.c != ' ' && indexed.c != '\t';
+            let predicted = snapshot.prediction(indexed.point);
+            let c = predicted.unwrap_or(indexed.c);
+            let is_text_cell = c != ' ' && c != '\t';
 
-            let x = layout_min.x + indexed.point.column.saturating_mul(cell_width as usize) as f32;
+            let x = layout_min.x + indexed.point.column.saturating_mul(cell_width as usize) as f32
+                - offset;
             let y = layout_min.y
                 + indexed
                     .point
                     .line
-                    .saturating_add(grid.display_offset() as i32)
+                    .saturating_add(snapshot.display_offset as i32)
                     .saturating_mul(cell_height as i32) as f32;
 
-            let mut fg = self.theme().get_color(indexed.fg);
+            let mut fg = self.theme().get_text_color(indexed.fg, is_bold);
             let mut bg = self.theme().get_color(indexed.bg);
+            // the colors of the shell come first
+            if indexed.fg == Color::Named(NamedColor::Foreground) {
+                if let Some(color) = input_colors.get(&indexed.point) {
+                    fg = *color;
+                }
+            }
 
             let cell_width = if is_wide_char {
                 cell_width * 2.0
@@ -66,6 +134,23 @@ impl TerminalView<'_> {
                 cell_width
             };
 
+            // scrolled out of the view in the no-wrap mode
+            if x + cell_width < layout_min.x || x > layout_max.x {
+                continue;
+            }
+
+            // the line continues on the next row
+            if indexed.flags.contains(Flags::WRAPLINE) {
+                let marker_x = x + cell_width - wrap_marker.width / 2.;
+                wrap_markers.push(Shape::LineSegment {
+                    points: [
+                        Pos2::new(marker_x, y + cell_height * 0.2),
+                        Pos2::new(marker_x, y + cell_height * 0.8),
+                    ],
+                    stroke: wrap_marker,
+                });
+            }
+
             if is_dim {
                 fg = fg.linear_multiply(0.7);
             }
@@ -74,6 +159,20 @@ impl TerminalView<'_> {
                 std::mem::swap(&mut fg, &mut bg);
             }
 
+            if is_searching {
+                match visible_matches.iter().find(|m| m.contains(&indexed.point)) {
+                    Some(m) => {
+                        fg = SEARCH_MATCH_TEXT_COLOR;
+                        bg = if focused_match == Some(*m) {
+                            SEARCH_FOCUSED_MATCH_COLOR
+                        } else {
+                            SEARCH_MATCH_COLOR
+                        };
+                    }
+                    None => fg = fg.linear_multiply(0.4),
+                }
+            }
+
             if is_selected {
                 bg = self.theme().get_selection_color()
             }
@@ -99,9 +198,21 @@ impl TerminalView<'_> {
                 });
             }
 
+            // not echoed by the server yet
+            if predicted.is_some() {
+                let underline_height = y + cell_height;
+                shapes.push(Shape::LineSegment {
+                    points: [
+                        Pos2::new(x, underline_height),
+                        Pos2::new(x + cell_width, underline_height),
+                    ],
+                    stroke: Stroke::new(cell_height * 0.08, fg.linear_multiply(0.5)),
+                });
+            }
+
             // Handle cursor rendering
-            if grid.cursor.point == indexed.point {
-                let cursor_color = self.theme().get_color(self.term_ctx.cursor_cell().fg);
+            if cursor == indexed.point {
+                let cursor_color = self.theme().get_color(snapshot.cursor_fg);
 
                 let cursor_width = if is_text_cell {
                     cell_width
@@ -110,35 +221,416 @@ impl TerminalView<'_> {
                 };
 
                 state.cursor_position = Some(Pos2::new(x, y));
-                shapes.push(Shape::Rect(RectShape::filled(
-                    Rect::from_min_size(Pos2::new(x, y), Vec2::new(cursor_width, cell_height)),
-                    CornerRadius::default(),
-                    cursor_color,
-                )));
+                let cursor_rect =
+                    Rect::from_min_size(Pos2::new(x, y), Vec2::new(cursor_width, cell_height));
+                if self.smooth_cursor {
+                    let dt = layout.ctx.input(|i| i.stable_dt).min(0.1);
+                    let (cursor_shapes, moving) =
+                        state.cursor_animation.step(cursor_rect, dt, cursor_color);
+                    shapes.extend(cursor_shapes);
+                    if moving {
+                        layout.ctx.request_repaint();
+                    }
+                } else {
+                    state.cursor_animation.reset();
+                    shapes.push(Shape::Rect(RectShape::filled(
+                        cursor_rect,
+                        CornerRadius::default(),
+                        cursor_color,
+                    )));
+                }
             }
 
             // Draw text content
-            if is_text_cell {
+            if is_text_cell && self.blurred {
+                shapes.push(Shape::Rect(RectShape::filled(
+                    Rect::from_min_size(
+                        Pos2::new(x + cell_width * 0.1, y + cell_height * 0.3),
+                        Vec2::new(cell_width * 0.8, cell_height * 0.45),
+                    ),
+                    CornerRadius::same(1),
+                    fg.linear_multiply(0.5),
+                )));
+            } else if is_text_cell
+                && (predicted.is_some() || !snapshot.joined_cells.contains(&indexed.point))
+            {
                 if is_hovered_hyperlink {
                     fg = HOVERED_HYPERLINK_COLOR;
-                } else if grid.cursor.point == indexed.point && is_app_cursor_mode {
+                } else if cursor == indexed.point && is_app_cursor_mode {
                     std::mem::swap(&mut fg, &mut bg);
                 }
 
+                let cluster = snapshot
+                    .cluster(indexed.point)
+                    .filter(|_| predicted.is_none());
+                let text = if snapshot.secret_cells.contains(&indexed.point) {
+                    REDACTED_CHAR.to_string()
+                } else if let Some(cluster) = cluster {
+                    cluster.text.clone()
+                } else {
+                    c.to_string()
+                };
+                // a cluster is centered on all its cells
+                let text_width = match cluster {
+                    Some(cluster) => cluster.columns as f32 * self.term_ctx.size.cell_width as f32,
+                    None => cell_width,
+                };
+                let fonts = painter.fonts(|c| c.clone());
+                let pos = Pos2 {
+                    x: x + (text_width / 2.0),
+                    y: y + text_offset,
+                };
+                // the font has no bold face, the glyphs are drawn again a bit
+                // to the right
+                if self.theme().is_bold_color(indexed.fg) {
+                    let offset = Vec2::new((self.options.font.font_size() / 14.).max(1.), 0.);
+                    shapes.push(Shape::text(
+                        &fonts,
+                        pos + offset,
+                        Align2::CENTER_TOP,
+                        &text,
+                        self.options.font.font_type(),
+                        fg,
+                    ));
+                }
                 shapes.push(Shape::text(
-                    &painter.fonts(|c| c.clone()),
-                    Pos2 {
-                        x: x + (cell_width / 2.0),
-                        y,
-                    },
+                    &fonts,
+                    pos,
                     Align2::CENTER_TOP,
-                    indexed.c,
+                    text,
                     self.options.font.font_type(),
                     fg,
                 ));
             }
         }
 
+        shapes.extend(wrap_markers);
+        shapes.extend(self.command_durations(snapshot, layout, painter, painted_bg));
+        shapes.extend(self.output_summaries(snapshot, state, layout, painter, painted_bg));
+
+        // scrolled short of a line, the content is moved by the rest
+        let scroll_offset =
+            -state.scrollbar_state.scroll_pixels / self.options.font.font_size() * cell_height;
+        if scroll_offset != 0. {
+            for shape in &mut shapes[1..] {
+                shape.translate(Vec2::new(0., scroll_offset));
+            }
+        }
+
+        if let Some(stroke) = self.tint.as_ref().and_then(TerminalTint::border) {
+            shapes.push(Shape::Rect(RectShape::stroke(
+                layout.rect,
+                CornerRadius::ZERO,
+                stroke,
+                StrokeKind::Inside,
+            )));
+        }
+
+        // where the keys go, among the terminals shown
+        if self.dim_unfocused {
+            if *self.options.active_tab_id == Some(self.id()) {
+                let color = layout.ctx.style().visuals.selection.stroke.color;
+                shapes.push(Shape::Rect(RectShape::stroke(
+                    layout.rect,
+                    CornerRadius::ZERO,
+                    Stroke::new(FOCUS_BORDER_WIDTH, color),
+                    StrokeKind::Inside,
+                )));
+            } else {
+                shapes.push(Shape::Rect(RectShape::filled(
+                    layout.rect,
+                    CornerRadius::ZERO,
+                    painted_bg.gamma_multiply(UNFOCUSED_DIM),
+                )));
+            }
+        }
+
+        if let Some(flash) = self.bell_flash(layout) {
+            shapes.push(flash);
+        }
+
+        state.shapes = shapes.len();
         painter.extend(shapes);
     }
+
+    /// Foreground painted over the view, fading out, right after the bell
+    /// rang.
+    fn bell_flash(&self, layout: &Response) -> Option<Shape> {
+        if !self.visual_bell {
+            return None;
+        }
+        let elapsed = self.term_ctx.bell_at?.elapsed();
+        if elapsed >= VISUAL_BELL_DURATION {
+            return None;
+        }
+        layout.ctx.request_repaint();
+        let fade = 1. - elapsed.as_secs_f32() / VISUAL_BELL_DURATION.as_secs_f32();
+        let color = self.theme().get_color(Color::Named(NamedColor::Foreground));
+        Some(Shape::Rect(RectShape::filled(
+            layout.rect,
+            CornerRadius::ZERO,
+            color.gamma_multiply(VISUAL_BELL_OPACITY * fade),
+        )))
+    }
+
+    /// Lines marked on the scrollbar: the search matches, the commands run at
+    /// the prompt and the ones which failed.
+    pub(crate) fn scroll_markers(&self) -> Vec<ScrollMarker> {
+        let term = &self.term_ctx.terminal;
+        let cell_height = self.term_ctx.size.cell_height as f32;
+        let line_pos = |line: Line| (line.0 + term.history_size() as i32) as f32 * cell_height;
+        let prompt = self
+            .theme()
+            .get_color(Color::Named(NamedColor::Foreground))
+            .linear_multiply(0.5);
+        let failed = self.theme().get_color(Color::Named(NamedColor::Red));
+
+        let mut markers: Vec<_> = term
+            .command_blocks()
+            .into_iter()
+            .map(|(_, line, block)| ScrollMarker {
+                line_pos: line_pos(line),
+                color: match block.exit_code() {
+                    Some(code) if code != 0 => failed,
+                    _ => prompt,
+                },
+            })
+            .collect();
+        if self.term_ctx.search.is_highlighting() {
+            markers.extend(self.term_ctx.search.matches().iter().map(|m| ScrollMarker {
+                line_pos: line_pos(m.start().line),
+                color: SEARCH_MATCH_COLOR,
+            }));
+        }
+        markers
+    }
+
+    /// Summaries of the command outputs at the right of the line of their
+    /// command, clicked to fold or unfold the outputs.
+    fn output_summaries(
+        &self,
+        snapshot: &RenderSnapshot,
+        state: &mut TerminalViewState,
+        layout: &Response,
+        painter: &Painter,
+        background: Color32,
+    ) -> Vec<Shape> {
+        state.output_summaries.clear();
+        let cell_width = self.term_ctx.size.cell_width as f32;
+        let cell_height = self.term_ctx.size.cell_height as f32;
+        let text_offset = self.options.font.text_offset(&layout.ctx);
+        let foreground = self.theme().get_color(Color::Named(NamedColor::Foreground));
+        let failed = self.theme().get_color(Color::Named(NamedColor::Red));
+
+        let mut shapes = vec![];
+        for (index, line, block) in &snapshot.blocks {
+            if !block.is_folded() && block.output_lines() < MIN_FOLDED_LINES {
+                continue;
+            }
+            let Some(row) = snapshot.screen_row(*line) else {
+                continue;
+            };
+
+            let color = match block.exit_code() {
+                Some(code) if code != 0 => failed,
+                _ => foreground.linear_multiply(0.7),
+            };
+            let galley = painter.layout_no_wrap(
+                self.output_summary(block),
+                self.options.font.font_type(),
+                color,
+            );
+            let size = Vec2::new(galley.size().x + cell_width * 2., cell_height);
+            let min = Pos2::new(
+                layout.rect.max.x - size.x,
+                layout.rect.min.y + row as f32 * cell_height,
+            );
+            let rect = Rect::from_min_size(min, size);
+
+            let hovered = state.mouse_position.is_some_and(|pos| rect.contains(pos));
+            if hovered {
+                layout.ctx.set_cursor_icon(CursorIcon::PointingHand);
+            }
+            let fill = background.lerp_to_gamma(foreground, if hovered { 0.25 } else { 0.12 });
+            shapes.push(Shape::Rect(RectShape::filled(
+                rect,
+                CornerRadius::same(4),
+                fill,
+            )));
+            shapes.push(Shape::galley(
+                min + Vec2::new(cell_width, text_offset),
+                galley,
+                color,
+            ));
+            state.output_summaries.push((rect, *index));
+        }
+        shapes
+    }
+
+    /// How long the slow commands took, at the right of the last line of their
+    /// output, like the `REPORTTIME` of zsh.
+    fn command_durations(
+        &self,
+        snapshot: &RenderSnapshot,
+        layout: &Response,
+        painter: &Painter,
+        background: Color32,
+    ) -> Vec<Shape> {
+        let Some(threshold) = self.duration_threshold else {
+            return vec![];
+        };
+        let cell_width = self.term_ctx.size.cell_width as f32;
+        let cell_height = self.term_ctx.size.cell_height as f32;
+        let text_offset = self.options.font.text_offset(&layout.ctx);
+        let color = self
+            .theme()
+            .get_color(Color::Named(NamedColor::Foreground))
+            .linear_multiply(0.5);
+
+        let mut shapes = vec![];
+        for (_, line, block) in &snapshot.blocks {
+            if block.duration() < threshold {
+                continue;
+            }
+            // the summary of a folded output shows it already
+            if block.is_folded() {
+                continue;
+            }
+            let last_line = *line + block.output_lines() as i32;
+            let Some(row) = snapshot.screen_row(last_line) else {
+                continue;
+            };
+            let galley = painter.layout_no_wrap(
+                format_duration(block.duration()),
+                self.options.font.font_type(),
+                color,
+            );
+            let size = Vec2::new(galley.size().x + cell_width * 2., cell_height);
+            let min = Pos2::new(
+                layout.rect.max.x - size.x,
+                layout.rect.min.y + row as f32 * cell_height,
+            );
+            shapes.push(Shape::Rect(RectShape::filled(
+                Rect::from_min_size(min, size),
+                CornerRadius::ZERO,
+                background,
+            )));
+            shapes.push(Shape::galley(
+                min + Vec2::new(cell_width, text_offset),
+                galley,
+                color,
+            ));
+        }
+        shapes
+    }
+
+    /// Badges of the exit codes in the gutter, at the line of their command: a
+    /// dot for a success, the code otherwise.
+    pub(crate) fn exit_code_badges(
+        &self,
+        snapshot: &RenderSnapshot,
+        gutter: Rect,
+        painter: &Painter,
+    ) {
+        let cell_height = self.term_ctx.size.cell_height as f32;
+        let background = self.theme().get_color(Color::Named(NamedColor::Background));
+        let succeeded = self.theme().get_color(Color::Named(NamedColor::Green));
+        let failed = self.theme().get_color(Color::Named(NamedColor::Red));
+
+        let mut shapes = vec![Shape::Rect(RectShape::filled(
+            gutter,
+            CornerRadius::ZERO,
+            background,
+        ))];
+        for (_, line, block) in &snapshot.blocks {
+            let (Some(code), Some(row)) = (block.exit_code(), snapshot.screen_row(*line)) else {
+                continue;
+            };
+            let center = Pos2::new(
+                gutter.center().x,
+                gutter.min.y + (row as f32 + 0.5) * cell_height,
+            );
+            if code == 0 {
+                shapes.push(Shape::circle_filled(center, cell_height * 0.15, succeeded));
+                continue;
+            }
+            let font = FontId::monospace(cell_height * 0.5);
+            let galley = painter.layout_no_wrap(code.to_string(), font, background);
+            let size = galley.size() + Vec2::new(6., 2.);
+            let rect = Rect::from_center_size(center, size.min(gutter.size()));
+            shapes.push(Shape::Rect(RectShape::filled(
+                rect,
+                CornerRadius::same(3),
+                failed,
+            )));
+            shapes.push(Shape::galley(
+                center - galley.size() / 2.,
+                galley,
+                background,
+            ));
+        }
+        painter.extend(shapes);
+    }
+
+    /// `▾ 120 lines · exit 1 · 2.3s`, the arrow points right once folded.
+    fn output_summary(&self, block: &CommandBlock) -> String {
+        let arrow = if block.is_folded() { '▸' } else { '▾' };
+        let lines = self
+            .labels
+            .output_lines
+            .replace("{count}", &block.output_lines().to_string());
+        let mut parts = vec![format!("{arrow} {lines}")];
+        if let Some(code) = block.exit_code() {
+            parts.push(self.labels.exit_code.replace("{code}", &code.to_string()));
+        }
+        parts.push(format_duration(block.duration()));
+        parts.join(" · ")
+    }
+
+    /// Colors of the highlighted command typed at the prompt, by cell.
+    fn input_colors(&self, snapshot: &RenderSnapshot) -> BTreeMap<Point, Color32> {
+        let Some(known_commands) = self.known_commands else {
+            return BTreeMap::new();
+        };
+        let cells = &snapshot.prompt_input;
+        let chars: Vec<_> = cells.iter().map(|(_, c)| *c).collect();
+        let highlights = highlight_input(&chars, known_commands);
+        cells
+            .iter()
+            .zip(highlights)
+            .filter_map(|((point, _), highlight)| {
+                let color = match highlight? {
+                    Highlight::Command => NamedColor::Green,
+                    Highlight::Flag => NamedColor::Cyan,
+                    Highlight::Quoted => NamedColor::Yellow,
+                    Highlight::Operator => NamedColor::Magenta,
+                };
+                Some((*point, self.theme().get_color(Color::Named(color))))
+            })
+            .collect()
+    }
+}
+
+/// `850ms`, `2.3s`, `4m 05s` or `1h 02m`.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0 => format!("{}ms", duration.as_millis()),
+        1..60 => format!("{:.1}s", duration.as_secs_f32()),
+        60..3600 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs / 60 % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_command_durations() {
+        assert_eq!(format_duration(Duration::from_millis(850)), "850ms");
+        assert_eq!(format_duration(Duration::from_millis(2340)), "2.3s");
+        assert_eq!(format_duration(Duration::from_secs(245)), "4m 05s");
+        assert_eq!(format_duration(Duration::from_secs(3720)), "1h 02m");
+    }
 }
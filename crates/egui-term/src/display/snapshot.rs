@@ -0,0 +1,244 @@
+use crate::redact::Redaction;
+use alacritty_terminal::event::EventListener;
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Line, Point};
+use alacritty_terminal::selection::SelectionRange;
+use alacritty_terminal::term::cell::Flags;
+use alacritty_terminal::term::{CommandBlock, Term, TermMode};
+use alacritty_terminal::vte::ansi::Color;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Cell of the screen, as painted.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RenderCell {
+    pub point: Point,
+    pub c: char,
+    pub fg: Color,
+    pub bg: Color,
+    pub flags: Flags,
+}
+
+/// What a frame paints of a terminal, copied while the terminal is locked. The
+/// lock is released before painting, for the event loop to keep parsing the
+/// output meanwhile.
+#[derive(Debug, Clone)]
+pub(crate) struct RenderSnapshot {
+    /// Cells of the screen, without the spacers of the wide chars.
+    pub cells: Vec<RenderCell>,
+    pub cursor: Point,
+    /// Foreground color of the cell under the cursor.
+    pub cursor_fg: Color,
+    pub display_offset: usize,
+    pub screen_lines: usize,
+    pub mode: TermMode,
+    pub selection: Option<SelectionRange>,
+    /// Command blocks showing on the screen, with their index and the line of
+    /// their command.
+    pub blocks: Vec<(usize, Line, CommandBlock)>,
+    /// Command typed at the prompt, by cell.
+    pub prompt_input: Vec<(Point, char)>,
+    /// Cells masked on the screen, see [`Redaction::secret_cells`].
+    pub secret_cells: BTreeSet<Point>,
+    /// Chars typed but not echoed yet, shown in place of the cells.
+    pub predictions: Vec<(Point, char)>,
+    /// Grapheme clusters starting at a cell, e.g. `e` and its accent, or the
+    /// emoji of a family joined over the next cells, drawn as a single text.
+    pub clusters: BTreeMap<Point, Cluster>,
+    /// Cells drawn by the cluster of a previous cell.
+    pub joined_cells: BTreeSet<Point>,
+}
+
+/// Text drawn over one or more cells.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Cluster {
+    pub text: String,
+    pub columns: usize,
+}
+
+/// Joins the chars of the next cell to the cluster, e.g. the emoji of a
+/// family.
+const ZERO_WIDTH_JOINER: char = '\u{200d}';
+
+/// Chars drawn for `base` and its `zerowidth` chars. egui doesn't shape the
+/// text, the joiners and the variation selectors have no glyph and are left
+/// out.
+fn drawn_chars(base: char, zerowidth: &[char]) -> impl Iterator<Item = char> + '_ {
+    std::iter::once(base).chain(
+        zerowidth
+            .iter()
+            .copied()
+            .filter(|c| *c != ZERO_WIDTH_JOINER && !matches!(c, '\u{fe00}'..='\u{fe0f}')),
+    )
+}
+
+/// Clusters of `cells`, by the cell they start at, and the cells joined to a
+/// previous one. Each cell keeps its columns, a cluster spans the cells of all
+/// its chars.
+fn clusters<'a>(
+    cells: impl Iterator<Item = (Point, char, usize, &'a [char])>,
+) -> (BTreeMap<Point, Cluster>, BTreeSet<Point>) {
+    let mut clusters = BTreeMap::<Point, Cluster>::new();
+    let mut joined_cells = BTreeSet::new();
+    // cluster the next cell belongs to, after a joiner
+    let mut joining: Option<Point> = None;
+    for (point, c, columns, zerowidth) in cells {
+        let start = match joining.filter(|start| start.line == point.line) {
+            Some(start) => {
+                let cluster = clusters.get_mut(&start).expect("joining cluster");
+                cluster.text.extend(drawn_chars(c, zerowidth));
+                cluster.columns += columns;
+                joined_cells.insert(point);
+                start
+            }
+            None => {
+                let text: String = drawn_chars(c, zerowidth).collect();
+                let joins = zerowidth.last() == Some(&ZERO_WIDTH_JOINER);
+                if text.chars().count() > 1 || joins {
+                    clusters.insert(point, Cluster { text, columns });
+                }
+                point
+            }
+        };
+        joining = (zerowidth.last() == Some(&ZERO_WIDTH_JOINER)).then_some(start);
+    }
+    (clusters, joined_cells)
+}
+
+impl RenderSnapshot {
+    /// `redaction` is only given when the secrets are masked on the screen.
+    pub fn new<T: EventListener>(term: &Term<T>, redaction: Option<&Redaction>) -> Self {
+        let grid = term.grid();
+        let display_offset = grid.display_offset();
+        let screen_lines = grid.screen_lines();
+        let top = Line(-(display_offset as i32));
+        let bottom = top + (screen_lines as i32 - 1);
+
+        let (clusters, joined_cells) = clusters(
+            grid.display_iter()
+                .filter(|indexed| !indexed.flags.contains(Flags::WIDE_CHAR_SPACER))
+                .map(|indexed| {
+                    let columns = if indexed.flags.contains(Flags::WIDE_CHAR) {
+                        2
+                    } else {
+                        1
+                    };
+                    (
+                        indexed.point,
+                        indexed.c,
+                        columns,
+                        indexed.zerowidth().unwrap_or_default(),
+                    )
+                }),
+        );
+        let cells = grid
+            .display_iter()
+            .filter(|indexed| !indexed.flags.contains(Flags::WIDE_CHAR_SPACER))
+            .map(|indexed| RenderCell {
+                point: indexed.point,
+                c: indexed.c,
+                fg: indexed.fg,
+                bg: indexed.bg,
+                flags: indexed.flags,
+            })
+            .collect();
+        let blocks = term
+            .command_blocks()
+            .into_iter()
+            .filter(|(_, line, block)| {
+                *line <= bottom && *line + block.output_lines() as i32 >= top
+            })
+            .map(|(index, line, block)| (index, line, block.clone()))
+            .collect();
+        let secret_cells = redaction
+            .map(|redaction| redaction.secret_cells(term.grid(), top, bottom))
+            .unwrap_or_default();
+
+        Self {
+            cells,
+            cursor: grid.cursor.point,
+            cursor_fg: grid[grid.cursor.point].fg,
+            display_offset,
+            screen_lines,
+            mode: *term.mode(),
+            selection: term.selection.as_ref().and_then(|s| s.to_range(term)),
+            blocks,
+            prompt_input: term.prompt_input_cells(),
+            secret_cells,
+            predictions: vec![],
+            clusters,
+            joined_cells,
+        }
+    }
+
+    /// The cursor follows the predicted chars.
+    pub fn shown_cursor(&self) -> Point {
+        match self.predictions.last() {
+            Some((point, _)) => Point::new(point.line, point.column + 1),
+            None => self.cursor,
+        }
+    }
+
+    pub fn prediction(&self, point: Point) -> Option<char> {
+        self.predictions
+            .iter()
+            .find(|(predicted, _)| *predicted == point)
+            .map(|(_, c)| *c)
+    }
+
+    /// Text drawn from the cell at `point`, its char unless it starts a
+    /// cluster.
+    pub fn cluster(&self, point: Point) -> Option<&Cluster> {
+        self.clusters.get(&point)
+    }
+
+    /// Row of the screen showing `line` of the grid, if it is shown.
+    pub fn screen_row(&self, line: Line) -> Option<usize> {
+        let row = line.0 + self.display_offset as i32;
+        (row >= 0 && row < self.screen_lines as i32).then_some(row as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alacritty_terminal::index::Column;
+    use alacritty_terminal::term::test::mock_term;
+
+    #[test]
+    fn snapshot_of_the_screen() {
+        let term = mock_term("ab\ncd");
+        let snapshot = RenderSnapshot::new(&term, None);
+        assert_eq!(snapshot.cells.len(), 4);
+        assert_eq!(snapshot.cells[3].c, 'd');
+        assert_eq!(snapshot.cursor, Point::new(Line(0), Column(0)));
+        assert_eq!(snapshot.screen_row(Line(1)), Some(1));
+        assert_eq!(snapshot.screen_row(Line(2)), None);
+        assert!(snapshot.selection.is_none());
+        assert!(snapshot.secret_cells.is_empty());
+        assert!(snapshot.clusters.is_empty());
+    }
+
+    #[test]
+    fn grapheme_clusters() {
+        let line = |column| Point::new(Line(0), Column(column));
+        let cells: [(Point, char, usize, &[char]); 6] = [
+            (line(0), 'e', 1, &['\u{301}']),
+            (line(1), '❤', 2, &['\u{fe0f}']),
+            (line(3), '👨', 2, &['\u{200d}']),
+            (line(5), '👩', 2, &['\u{200d}']),
+            (line(7), '👧', 2, &[]),
+            (line(9), 'a', 1, &[]),
+        ];
+        let (clusters, joined_cells) = clusters(cells.into_iter());
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[&line(0)].text, "e\u{301}");
+        assert_eq!(
+            clusters[&line(3)],
+            Cluster {
+                text: "👨👩👧".to_string(),
+                columns: 6,
+            }
+        );
+        assert_eq!(joined_cells, BTreeSet::from([line(5), line(7)]));
+    }
+}
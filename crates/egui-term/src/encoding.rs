@@ -0,0 +1,147 @@
+//! Output of legacy hosts in GBK or Latin-1, transcoded to the UTF-8 the
+//! terminal parses, and the input encoded back.
+
+use encoding_rs::{CoderResult, Decoder, Encoding, GBK, WINDOWS_1252};
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::io::{self, Read};
+
+/// Sent in place of the chars the encoding of the host doesn't have.
+const UNMAPPABLE: u8 = b'?';
+
+/// Character encoding of the host.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TermEncoding {
+    #[default]
+    Utf8,
+    Gbk,
+    /// ISO-8859-1, decoded as its superset windows-1252 like the browsers do.
+    Latin1,
+}
+
+impl TermEncoding {
+    pub const ALL: [TermEncoding; 3] = [Self::Utf8, Self::Gbk, Self::Latin1];
+
+    /// `None` for UTF-8, which is left as is.
+    fn encoding(self) -> Option<&'static Encoding> {
+        match self {
+            Self::Utf8 => None,
+            Self::Gbk => Some(GBK),
+            Self::Latin1 => Some(WINDOWS_1252),
+        }
+    }
+
+    /// Input typed in the terminal, in the encoding of the host. Input which
+    /// isn't UTF-8 is sent as is.
+    pub(crate) fn encode<'a>(self, input: Cow<'a, [u8]>) -> Cow<'a, [u8]> {
+        let Some(encoding) = self.encoding() else {
+            return input;
+        };
+        let text = match std::str::from_utf8(&input) {
+            Ok(text) if !text.is_ascii() => text,
+            _ => return input,
+        };
+        let mut encoded = Vec::with_capacity(text.len());
+        let mut buf = [0; 4];
+        for c in text.chars() {
+            let (bytes, _, unmappable) = encoding.encode(c.encode_utf8(&mut buf));
+            match unmappable {
+                true => encoded.push(UNMAPPABLE),
+                false => encoded.extend_from_slice(&bytes),
+            }
+        }
+        Cow::Owned(encoded)
+    }
+}
+
+impl Display for TermEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Utf8 => write!(f, "UTF-8"),
+            Self::Gbk => write!(f, "GBK"),
+            Self::Latin1 => write!(f, "ISO-8859-1"),
+        }
+    }
+}
+
+/// Decodes the output read from the pty into UTF-8. A char split between two
+/// reads is kept by the decoder until the next one.
+#[derive(Default)]
+pub(crate) struct OutputDecoder {
+    encoding: TermEncoding,
+    decoder: Option<Decoder>,
+    raw: Vec<u8>,
+}
+
+impl OutputDecoder {
+    /// Read from `reader` into `buf`, in UTF-8 whatever the `encoding`.
+    pub(crate) fn read(
+        &mut self,
+        encoding: TermEncoding,
+        reader: &mut impl Read,
+        buf: &mut [u8],
+    ) -> io::Result<usize> {
+        if encoding != self.encoding {
+            self.encoding = encoding;
+            self.decoder = encoding
+                .encoding()
+                .map(Encoding::new_decoder_without_bom_handling);
+        }
+        let Some(decoder) = &mut self.decoder else {
+            return reader.read(buf);
+        };
+        // read no more than what fits into `buf` once decoded
+        let mut len = buf.len() / 3;
+        while len > 1 && decoder.max_utf8_buffer_length(len).unwrap_or(usize::MAX) > buf.len() {
+            len /= 2;
+        }
+        self.raw.resize(len.max(1), 0);
+        let read = reader.read(&mut self.raw)?;
+        if read == 0 {
+            return Ok(0);
+        }
+        // the invalid bytes are replaced, e.g. binary output
+        let (result, _, written, _) = decoder.decode_to_utf8(&self.raw[..read], buf, false);
+        debug_assert_eq!(result, CoderResult::InputEmpty);
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(encoding: TermEncoding, chunks: &[&[u8]]) -> String {
+        let mut decoder = OutputDecoder::default();
+        let mut output = vec![];
+        for chunk in chunks {
+            let mut reader = *chunk;
+            let mut buf = [0; 64];
+            let read = decoder.read(encoding, &mut reader, &mut buf).unwrap();
+            output.extend_from_slice(&buf[..read]);
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn transcode_legacy_hosts() {
+        // 中文, its first char split between two reads
+        let gbk: &[u8] = b"\x1b[1m\xd6\xd0\xce\xc4\x1b[0m";
+        assert_eq!(decode(TermEncoding::Gbk, &[gbk]), "\x1b[1m中文\x1b[0m");
+        assert_eq!(
+            decode(TermEncoding::Gbk, &[&gbk[..5], &gbk[5..]]),
+            "\x1b[1m中文\x1b[0m"
+        );
+        assert_eq!(decode(TermEncoding::Latin1, &[b"caf\xe9"]), "café");
+        assert_eq!(decode(TermEncoding::Utf8, &["café".as_bytes()]), "café");
+
+        let encode = |encoding: TermEncoding, input: &str| {
+            encoding
+                .encode(Cow::Borrowed(input.as_bytes()))
+                .into_owned()
+        };
+        assert_eq!(encode(TermEncoding::Gbk, "ls 中文"), b"ls \xd6\xd0\xce\xc4");
+        assert_eq!(encode(TermEncoding::Latin1, "café ✓"), b"caf\xe9 ?");
+        assert_eq!(encode(TermEncoding::Utf8, "café"), "café".as_bytes());
+    }
+}
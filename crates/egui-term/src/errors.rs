@@ -1,3 +1,5 @@
+use crate::ssh::{Authentication, HostKeyChange};
+use std::error::Error;
 use wezterm_ssh::HostVerificationFailed;
 
 #[derive(Debug, thiserror::Error)]
@@ -8,6 +10,146 @@ pub enum TermError {
     Box(#[from] Box<dyn std::error::Error + Send + Sync>),
     #[error(transparent)]
     HostVerification(HostVerificationFailed),
+    #[error("the host key {} of {} wasn't trusted", .0.found, .0.host)]
+    HostKeyRefused(HostKeyChange),
+    #[error(
+        "authentication with the security key {identity} failed, check that it is \
+         plugged in and touched in time: {err}"
+    )]
+    SecurityKey { identity: String, err: String },
+    #[error("the authentication was cancelled")]
+    AuthenticationCancelled,
+    #[error("invalid redaction pattern {pattern}: {err}")]
+    RedactionPattern { pattern: String, err: String },
+    #[error("invalid search pattern {pattern}: {err}")]
+    SearchPattern { pattern: String, err: String },
+    #[error("invalid port forward {0}, e.g. L 8080:localhost:80")]
+    InvalidForward(String),
+    #[error("port forward {0} isn't supported, the ssh backend only has local forwards")]
+    UnsupportedForward(String),
     #[error("{0}")]
     Io(#[from] std::io::Error),
 }
+
+/// What a failed ssh connection ran into, to tell how to fix it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectFailure {
+    /// The name of the host doesn't resolve.
+    Dns,
+    /// A session using the ssh config whose host is neither a `Host` of the
+    /// config nor a name which resolves.
+    NoConfigHost,
+    Timeout,
+    Refused,
+    /// The host rejected the credentials, or they weren't given.
+    AuthRejected,
+    /// The host key isn't the one known or pinned.
+    HostKeyMismatch,
+    Other,
+}
+
+/// Messages of the failures, from the resolver, the sockets and libssh, on
+/// the platforms supported.
+const DNS_MESSAGES: &[&str] = &[
+    "failed to resolve hostname",
+    "failed to lookup address",
+    "name or service not known",
+    "nodename nor servname provided",
+    "no such host is known",
+    "temporary failure in name resolution",
+];
+const TIMEOUT_MESSAGES: &[&str] = &["timed out", "timeout"];
+const REFUSED_MESSAGES: &[&str] = &["connection refused", "actively refused"];
+const AUTH_MESSAGES: &[&str] = &[
+    "authentication failed",
+    "authentication methods failed",
+    "permission denied",
+    "access denied",
+    "unable to authenticate",
+];
+
+impl TermError {
+    /// What the connection of a session authenticated with `auth` ran into.
+    pub fn connect_failure(&self, auth: &Authentication) -> ConnectFailure {
+        match self {
+            Self::HostVerification(_) | Self::HostKeyRefused(_) => {
+                return ConnectFailure::HostKeyMismatch;
+            }
+            Self::SecurityKey { .. } | Self::AuthenticationCancelled => {
+                return ConnectFailure::AuthRejected;
+            }
+            _ => {}
+        }
+        // the failures of the lower layers are only told by their message
+        let mut message = self.to_string();
+        let mut source = self.source();
+        while let Some(err) = source {
+            message.push_str(": ");
+            message.push_str(&err.to_string());
+            source = err.source();
+        }
+        let message = message.to_lowercase();
+        let matches = |messages: &[&str]| messages.iter().any(|m| message.contains(m));
+        if matches(DNS_MESSAGES) {
+            match auth {
+                Authentication::Config => ConnectFailure::NoConfigHost,
+                Authentication::Password(..) | Authentication::PublicKey { .. } => {
+                    ConnectFailure::Dns
+                }
+            }
+        } else if matches(TIMEOUT_MESSAGES) {
+            ConnectFailure::Timeout
+        } else if matches(REFUSED_MESSAGES) {
+            ConnectFailure::Refused
+        } else if matches(AUTH_MESSAGES) {
+            ConnectFailure::AuthRejected
+        } else {
+            ConnectFailure::Other
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connect_failures() {
+        let password = Authentication::Password("root".to_string(), String::new());
+        let failure = |message: &str, auth: &Authentication| {
+            TermError::Any(anyhow::anyhow!(message.to_string())).connect_failure(auth)
+        };
+        assert_eq!(
+            failure(
+                "failed to lookup address information: Name or service not known",
+                &password
+            ),
+            ConnectFailure::Dns
+        );
+        assert_eq!(
+            failure(
+                "Failed to resolve hostname web (nodename nor servname provided, or not known)",
+                &Authentication::Config
+            ),
+            ConnectFailure::NoConfigHost
+        );
+        assert_eq!(
+            failure("Connection timed out (os error 110)", &password),
+            ConnectFailure::Timeout
+        );
+        assert_eq!(
+            failure("Connection refused (os error 111)", &password),
+            ConnectFailure::Refused
+        );
+        let context = anyhow::anyhow!("Authentication failed").context("ssh login");
+        assert_eq!(
+            TermError::Any(context).connect_failure(&password),
+            ConnectFailure::AuthRejected
+        );
+        assert_eq!(
+            TermError::AuthenticationCancelled.connect_failure(&password),
+            ConnectFailure::AuthRejected
+        );
+        assert_eq!(failure("channel closed", &password), ConnectFailure::Other);
+    }
+}
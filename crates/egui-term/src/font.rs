@@ -1,30 +1,39 @@
-use egui::{Context, FontId};
+use egui::{Context, FontFamily, FontId};
 
 use crate::types::Size;
 
+pub const LINE_HEIGHT_RANGE: std::ops::RangeInclusive<f32> = 0.8..=2.0;
+pub const CELL_PADDING_RANGE: std::ops::RangeInclusive<f32> = 0.0..=8.0;
+
 #[derive(Debug, Clone)]
 pub struct FontSettings {
     pub font_type: FontId,
+    /// Height of a cell, relative to the height of a row of the font.
+    pub line_height: f32,
+    /// Extra width of a cell in points, the glyph stays centered.
+    pub cell_padding: f32,
 }
 
 impl Default for FontSettings {
     fn default() -> Self {
         Self {
             font_type: FontId::monospace(14.0),
+            line_height: 1.0,
+            cell_padding: 0.0,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TerminalFont {
     font_type: FontId,
+    line_height: f32,
+    cell_padding: f32,
 }
 
 impl Default for TerminalFont {
     fn default() -> Self {
-        Self {
-            font_type: FontSettings::default().font_type,
-        }
+        Self::new(FontSettings::default())
     }
 }
 
@@ -32,6 +41,12 @@ impl TerminalFont {
     pub fn new(settings: FontSettings) -> Self {
         Self {
             font_type: settings.font_type,
+            line_height: settings
+                .line_height
+                .clamp(*LINE_HEIGHT_RANGE.start(), *LINE_HEIGHT_RANGE.end()),
+            cell_padding: settings
+                .cell_padding
+                .clamp(*CELL_PADDING_RANGE.start(), *CELL_PADDING_RANGE.end()),
         }
     }
 
@@ -43,10 +58,36 @@ impl TerminalFont {
         &mut self.font_type.size
     }
 
+    /// The family has to be registered in egui, e.g. `FontFamily::Name`
+    /// families added with `Context::set_fonts`.
+    pub fn set_font_family(&mut self, family: FontFamily) {
+        self.font_type.family = family;
+    }
+
+    pub fn line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    /// Clamped to [`LINE_HEIGHT_RANGE`].
+    pub fn set_line_height(&mut self, line_height: f32) {
+        self.line_height = line_height.clamp(*LINE_HEIGHT_RANGE.start(), *LINE_HEIGHT_RANGE.end());
+    }
+
+    pub fn cell_padding(&self) -> f32 {
+        self.cell_padding
+    }
+
+    /// Clamped to [`CELL_PADDING_RANGE`].
+    pub fn set_cell_padding(&mut self, cell_padding: f32) {
+        self.cell_padding =
+            cell_padding.clamp(*CELL_PADDING_RANGE.start(), *CELL_PADDING_RANGE.end());
+    }
+
     pub fn font_type(&self) -> FontId {
         self.font_type.clone()
     }
 
+    /// Size of a cell, the glyph with the line height and padding applied.
     pub fn font_measure(&self, ctx: &Context) -> Size {
         let (width, height) = ctx.fonts(|f| {
             (
@@ -55,6 +96,42 @@ impl TerminalFont {
             )
         });
 
-        Size::new(width, height)
+        Size::new(width + self.cell_padding, height * self.line_height)
+    }
+
+    /// Offset of the glyphs from the top of their cell, centering them
+    /// vertically in taller lines.
+    pub fn text_offset(&self, ctx: &Context) -> f32 {
+        let height = ctx.fonts(|f| f.row_height(&self.font_type));
+        height * (self.line_height - 1.) / 2.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spacing_is_applied_to_cells() {
+        let ctx = Context::default();
+        // the fonts are available once a frame has started
+        let _ = ctx.run(Default::default(), |_| {});
+
+        let plain = TerminalFont::default();
+        let spaced = TerminalFont::new(FontSettings {
+            line_height: 1.5,
+            cell_padding: 2.,
+            ..Default::default()
+        });
+        let (plain_size, spaced_size) = (plain.font_measure(&ctx), spaced.font_measure(&ctx));
+        assert_eq!(spaced_size.width, plain_size.width + 2.);
+        assert_eq!(spaced_size.height, plain_size.height * 1.5);
+        assert_eq!(plain.text_offset(&ctx), 0.);
+        assert_eq!(spaced.text_offset(&ctx), plain_size.height * 0.25);
+
+        let mut font = TerminalFont::default();
+        font.set_line_height(5.);
+        font.set_cell_padding(-1.);
+        assert_eq!((font.line_height(), font.cell_padding()), (2., 0.));
     }
 }
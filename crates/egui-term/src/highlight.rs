@@ -0,0 +1,142 @@
+//! Highlighting of the command typed at the shell prompt, like fish does it
+//! but without the help of the shell.
+
+use std::collections::HashSet;
+
+/// Builtins of the common shells, which are commands on every host.
+const SHELL_BUILTINS: &[&str] = &[
+    ".", "alias", "bg", "cd", "command", "echo", "eval", "exec", "exit", "export", "fg", "history",
+    "jobs", "kill", "popd", "printf", "pushd", "pwd", "read", "set", "source", "test", "time",
+    "type", "ulimit", "umask", "unalias", "unset", "wait",
+];
+
+/// Commands running the command that follows them, e.g. `sudo ls`.
+const PREFIX_COMMANDS: &[&str] = &[
+    "command", "doas", "env", "exec", "nice", "nohup", "sudo", "time",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Highlight {
+    /// Command known to exist on the host.
+    Command,
+    /// `-v` or `--verbose`.
+    Flag,
+    /// Quoted string, along with its quotes.
+    Quoted,
+    /// `|`, `&&`, `;` or a redirection.
+    Operator,
+}
+
+fn is_operator(c: char) -> bool {
+    matches!(c, '|' | '&' | ';' | '<' | '>' | '(' | ')')
+}
+
+/// Highlight of each char of `input`. Besides the shell builtins, the
+/// commands are valid when they are among `known_commands`, the others are
+/// left as they are.
+pub(crate) fn highlight_input(
+    input: &[char],
+    known_commands: &HashSet<String>,
+) -> Vec<Option<Highlight>> {
+    let mut highlights = vec![None; input.len()];
+    // whether the next word is a command
+    let mut command_position = true;
+    let mut i = 0;
+    while i < input.len() {
+        let c = input[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if is_operator(c) {
+            highlights[i] = Some(Highlight::Operator);
+            // a file follows a redirection
+            command_position = !matches!(c, '<' | '>');
+            i += 1;
+            continue;
+        }
+
+        // a word, quotes may start in the middle of it
+        let start = i;
+        let mut quote = None;
+        while i < input.len() {
+            let c = input[i];
+            match quote {
+                Some(q) => {
+                    highlights[i] = Some(Highlight::Quoted);
+                    if c == q {
+                        quote = None;
+                    } else if c == '\\' && q == '"' && i + 1 < input.len() {
+                        i += 1;
+                        highlights[i] = Some(Highlight::Quoted);
+                    }
+                }
+                None if c == '\'' || c == '"' => {
+                    quote = Some(c);
+                    highlights[i] = Some(Highlight::Quoted);
+                }
+                // the escaped char is part of the word
+                None if c == '\\' => i += 1,
+                None if c.is_whitespace() || is_operator(c) => break,
+                None => {}
+            }
+            i += 1;
+        }
+        let end = i.min(input.len());
+        let plain = highlights[start..end].iter().all(Option::is_none);
+        let word: String = input[start..end].iter().collect();
+        let highlight = if command_position {
+            // `LANG=C ls`, the command is still to come
+            if word.find('=').is_some_and(|position| position > 0) {
+                continue;
+            }
+            command_position = PREFIX_COMMANDS.contains(&word.as_str());
+            let known = SHELL_BUILTINS.contains(&word.as_str()) || known_commands.contains(&word);
+            (plain && known).then_some(Highlight::Command)
+        } else {
+            (plain && word.starts_with('-')).then_some(Highlight::Flag)
+        };
+        if highlight.is_some() {
+            highlights[start..end].fill(highlight);
+        }
+    }
+    highlights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `input` with the highlights as letters, `.` when there is none.
+    fn marks(input: &str, known_commands: &[&str]) -> String {
+        let chars: Vec<_> = input.chars().collect();
+        let known_commands = known_commands.iter().map(|c| c.to_string()).collect();
+        highlight_input(&chars, &known_commands)
+            .into_iter()
+            .map(|highlight| match highlight {
+                Some(Highlight::Command) => 'c',
+                Some(Highlight::Flag) => 'f',
+                Some(Highlight::Quoted) => 'q',
+                Some(Highlight::Operator) => 'o',
+                None => '.',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn highlight_command_line() {
+        assert_eq!(
+            marks("grep -rn 'a b' src | wc -l", &["grep", "wc"]),
+            "cccc.fff.qqqqq.....o.cc.ff"
+        );
+        // unknown commands are left as they are
+        assert_eq!(marks("foo --bar", &[]), "....fffff");
+        assert_eq!(
+            marks("sudo LANG=C ls > out.txt", &["sudo", "ls"]),
+            "cccc........cc.o........"
+        );
+        assert_eq!(marks("cd \"$HOME\"&&ls", &[]), "cc.qqqqqqqoo..");
+        // not closed yet
+        assert_eq!(marks("echo 'abc", &[]), "cccc.qqqq");
+    }
+}
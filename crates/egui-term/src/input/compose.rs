@@ -0,0 +1,132 @@
+/// Composed input, dead keys (´ + e → é) and IME, reported once.
+///
+/// Depending on the platform and the layout, a composed char comes as an IME
+/// commit, as text, or as both, and the keys composing it may be reported as
+/// text too. The text of a frame which repeats a commit of the same frame, or
+/// the other way around, is dropped, like the text typed while composing.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ComposeState {
+    /// Text the IME is composing, e.g. the ´ of a dead key.
+    preedit: String,
+    /// Committed on this frame and not seen as text yet.
+    committed: Vec<String>,
+    /// Typed on this frame and not seen as a commit yet.
+    typed: Vec<String>,
+}
+
+impl ComposeState {
+    pub(crate) fn begin_frame(&mut self) {
+        self.committed.clear();
+        self.typed.clear();
+    }
+
+    pub(crate) fn preedit(&mut self, text: String) {
+        self.preedit = text;
+    }
+
+    /// Whether the committed `text` is to be sent.
+    pub(crate) fn commit(&mut self, text: &str) -> bool {
+        self.preedit.clear();
+        // Enter ending the composition, sent by its key
+        if text.is_empty() || text == "\n" || text == "\r" {
+            return false;
+        }
+        if take(&mut self.typed, text) {
+            return false;
+        }
+        self.committed.push(text.to_owned());
+        true
+    }
+
+    /// Whether the typed `text` is to be sent.
+    pub(crate) fn text(&mut self, text: &str) -> bool {
+        if !self.preedit.is_empty() {
+            return false;
+        }
+        if take(&mut self.committed, text) {
+            return false;
+        }
+        self.typed.push(text.to_owned());
+        true
+    }
+
+    /// Text still composing once the IME is disabled, which would be lost
+    /// otherwise, e.g. a dead key.
+    pub(crate) fn disable(&mut self) -> Option<String> {
+        Some(std::mem::take(&mut self.preedit)).filter(|text| !text.is_empty())
+    }
+}
+
+fn take(texts: &mut Vec<String>, text: &str) -> bool {
+    match texts.iter().position(|t| t == text) {
+        Some(index) => {
+            texts.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ComposeState;
+    use egui::{Event, ImeEvent};
+
+    /// Text sent for the frames of events.
+    fn typed(frames: &[Vec<Event>]) -> String {
+        let mut compose = ComposeState::default();
+        let mut sent = String::new();
+        for events in frames {
+            compose.begin_frame();
+            for event in events {
+                match event {
+                    Event::Text(text) if compose.text(text) => sent.push_str(text),
+                    Event::Ime(ImeEvent::Preedit(text)) => compose.preedit(text.clone()),
+                    Event::Ime(ImeEvent::Commit(text)) if compose.commit(text) => {
+                        sent.push_str(text)
+                    }
+                    Event::Ime(ImeEvent::Disabled) => {
+                        sent.extend(compose.disable());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        sent
+    }
+
+    fn text(text: &str) -> Event {
+        Event::Text(text.to_owned())
+    }
+
+    fn preedit(text: &str) -> Event {
+        Event::Ime(ImeEvent::Preedit(text.to_owned()))
+    }
+
+    fn commit(text: &str) -> Event {
+        Event::Ime(ImeEvent::Commit(text.to_owned()))
+    }
+
+    #[test]
+    fn dead_keys_and_compose() {
+        // macOS: the dead key is composing until the next key commits
+        let macos = [vec![preedit("´")], vec![preedit(""), commit("é")]];
+        assert_eq!(typed(&macos), "é");
+        // Windows: the commit is reported as text as well
+        let windows = [vec![commit("é"), text("é")], vec![text("a")]];
+        assert_eq!(typed(&windows), "éa");
+        let windows = [vec![text("é"), commit("é")]];
+        assert_eq!(typed(&windows), "é");
+        // Linux with IBus: the key composing is reported as text
+        let linux = [vec![preedit("´")], vec![text("e"), commit("é")]];
+        assert_eq!(typed(&linux), "é");
+        // Linux compose key without an IME, only text
+        assert_eq!(typed(&[vec![text("é")], vec![text("é")]]), "éé");
+        // the same char typed again on a later frame is kept
+        assert_eq!(typed(&[vec![commit("é")], vec![text("é")]]), "éé");
+        // a dead key left composing
+        let disabled = [vec![preedit("´")], vec![Event::Ime(ImeEvent::Disabled)]];
+        assert_eq!(typed(&disabled), "´");
+        assert_eq!(typed(&[vec![preedit("中"), commit("\r")]]), "");
+    }
+}
@@ -1,9 +1,16 @@
+mod compose;
+
+pub(crate) use compose::ComposeState;
+
 use crate::alacritty::{selection_point, BackendCommand, LinkAction, MouseButton};
+use crate::bindings::{modified_key, modify_other_keys};
 use crate::view::TerminalViewState;
 use crate::{BindingAction, InputKind, TerminalView};
 use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::Point;
 use alacritty_terminal::selection::SelectionType;
 use alacritty_terminal::term::TermMode;
+use copypasta::ClipboardProvider;
 use egui::{Key, Modifiers, MouseWheelUnit, PointerButton, Pos2, Rect, Response, Vec2};
 use std::cmp::min;
 
@@ -13,6 +20,43 @@ const MIN_SELECTION_SCROLLING_HEIGHT: f64 = 5.;
 /// Number of pixels for increasing the selection scrolling speed factor by one.
 const SELECTION_SCROLLING_STEP: f64 = 20.;
 
+/// Columns scrolled by a wheel notch in the no-wrap mode.
+const HORIZONTAL_SCROLL_COLUMNS: f32 = 3.;
+
+/// Seconds between the clicks counted in a row, egui's double click delay.
+const MULTI_CLICK_DELAY: f64 = 0.3;
+
+/// Clicks in a row on the same cell, counted when they are pressed so that
+/// the drag which follows extends the selection by their unit: words after a
+/// double click, lines after a triple click.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ClickCount {
+    time: f64,
+    point: Point,
+    count: u8,
+}
+
+impl ClickCount {
+    /// Count the click pressed on `point` at `time`, in seconds. The click
+    /// after a triple click starts over.
+    pub(crate) fn press(&mut self, time: f64, point: Point) -> u8 {
+        let repeated =
+            self.count > 0 && time - self.time <= MULTI_CLICK_DELAY && point == self.point;
+        self.count = if repeated { self.count % 3 + 1 } else { 1 };
+        self.time = time;
+        self.point = point;
+        self.count
+    }
+
+    fn selection_type(self) -> SelectionType {
+        match self.count {
+            2 => SelectionType::Semantic,
+            3 => SelectionType::Lines,
+            _ => SelectionType::Simple,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum InputAction {
     BackendCall(BackendCommand),
@@ -26,6 +70,7 @@ impl TerminalView<'_> {
 
     pub fn keyboard_input(
         &mut self,
+        state: &TerminalViewState,
         key: Key,
         modifiers: Modifiers,
         pressed: bool,
@@ -34,10 +79,13 @@ impl TerminalView<'_> {
             return None;
         }
         let terminal_mode = self.term_ctx.term_mode();
-        match self
-            .bindings_layout
-            .get_action(InputKind::KeyCode(key), modifiers, terminal_mode)
-        {
+        let action =
+            self.bindings_layout
+                .get_action(InputKind::KeyCode(key), modifiers, terminal_mode);
+        if let Some(text) = self.replaced_text(key, modifiers, action.as_ref()) {
+            return Some(self.text_input(&text));
+        }
+        match action {
             Some(BindingAction::Char(c)) => {
                 let mut buf = [0, 0, 0, 0];
                 let str = c.encode_utf8(&mut buf);
@@ -49,7 +97,7 @@ impl TerminalView<'_> {
                 seq.as_bytes().to_vec(),
             ))),
             Some(BindingAction::Copy) => {
-                let content = self.term_ctx.selection_content();
+                let content = self.copied_selection();
                 Some(InputAction::WriteToClipboard(content))
             }
             Some(BindingAction::ResetFontSize) => {
@@ -67,10 +115,55 @@ impl TerminalView<'_> {
             Some(BindingAction::SelectAll) => {
                 Some(InputAction::BackendCall(BackendCommand::SelectAll))
             }
+            Some(BindingAction::ExpandSelection) => Some(InputAction::BackendCall(
+                BackendCommand::ExpandSelection(state.mouse_point),
+            )),
+            Some(BindingAction::SearchForward) => {
+                self.open_search_bar();
+                None
+            }
+            Some(BindingAction::PreviousPrompt) => {
+                Some(InputAction::BackendCall(BackendCommand::PreviousPrompt))
+            }
+            Some(BindingAction::NextPrompt) => {
+                Some(InputAction::BackendCall(BackendCommand::NextPrompt))
+            }
+            None => modified_key(key, modifiers).map(|text| self.text_input(&text)),
             _ => None,
         }
     }
 
+    /// Whether `key` typed with `modifiers` is sent as a sequence of its own,
+    /// the text it types is dropped then.
+    pub(crate) fn replaces_text(&self, key: Key, modifiers: Modifiers) -> bool {
+        let action = self.bindings_layout.get_action(
+            InputKind::KeyCode(key),
+            modifiers,
+            self.term_ctx.term_mode(),
+        );
+        self.replaced_text(key, modifiers, action.as_ref())
+            .is_some()
+    }
+
+    /// Sequence of `key` in place of the text it types, with modifyOtherKeys
+    /// or Meta, the `action` it is bound to otherwise.
+    fn replaced_text(
+        &self,
+        key: Key,
+        modifiers: Modifiers,
+        action: Option<&BindingAction>,
+    ) -> Option<String> {
+        let mode = self.term_ctx.term_mode();
+        match action {
+            None => modify_other_keys(key, modifiers, mode, true)
+                .or_else(|| self.meta_keys.meta_key(key, modifiers)),
+            Some(BindingAction::Char(_) | BindingAction::Esc(_)) => {
+                modify_other_keys(key, modifiers, mode, false)
+            }
+            Some(_) => None,
+        }
+    }
+
     fn reset_font_size(&mut self, default_font_size: f32) {
         *self.options.font.font_size_mut() = default_font_size;
     }
@@ -89,6 +182,27 @@ impl TerminalView<'_> {
         delta: Vec2,
         modifiers: Modifiers,
     ) -> Option<InputAction> {
+        let no_wrap = self
+            .term_ctx
+            .line_wrap
+            .is_applied(self.term_ctx.term_mode());
+        if no_wrap && (delta.x != 0. || modifiers.shift_only()) {
+            let delta = if delta.x != 0. { delta.x } else { delta.y };
+            let cell_width = self.term_ctx.size.cell_width as f32;
+            let pixels = match unit {
+                MouseWheelUnit::Point => delta,
+                MouseWheelUnit::Line | MouseWheelUnit::Page => {
+                    delta * cell_width * HORIZONTAL_SCROLL_COLUMNS
+                }
+            };
+            self.term_ctx.line_wrap.offset -= pixels;
+            state.follow_cursor = false;
+            return None;
+        }
+        let scroll_y = match self.natural_scroll {
+            true => -delta.y * self.scroll_multiplier,
+            false => delta.y * self.scroll_multiplier,
+        };
         match (unit, modifiers.command_only()) {
             (MouseWheelUnit::Line | MouseWheelUnit::Point, true) => {
                 let font_size = self.options.font.font_size() + delta.y;
@@ -98,14 +212,16 @@ impl TerminalView<'_> {
                 None
             }
             (MouseWheelUnit::Line, _) => {
-                let lines = delta.y.signum() * delta.y.abs().ceil();
+                state.scrollbar_state.stop();
+                let lines = scroll_y.signum() * scroll_y.abs().ceil();
                 Some(InputAction::BackendCall(BackendCommand::Scroll(
                     lines as i32,
                 )))
             }
             (MouseWheelUnit::Point, _) => {
                 let font_size = self.options.font.font_size();
-                state.scrollbar_state.scroll_pixels -= delta.y;
+                state.scrollbar_state.flung += scroll_y;
+                state.scrollbar_state.scroll_pixels -= scroll_y;
                 let lines = (state.scrollbar_state.scroll_pixels / font_size).trunc();
                 state.scrollbar_state.scroll_pixels %= font_size;
                 if lines != 0.0 {
@@ -133,6 +249,60 @@ impl TerminalView<'_> {
             PointerButton::Primary => {
                 self.left_button_click(state, layout, position, modifiers, pressed)
             }
+            // the context menu
+            PointerButton::Secondary => None,
+            _ => self.other_button_click(state, button, modifiers, pressed),
+        }
+    }
+
+    /// Middle, back and forward buttons: reported to the applications which
+    /// ask for the mouse, bound otherwise.
+    fn other_button_click(
+        &mut self,
+        state: &TerminalViewState,
+        button: PointerButton,
+        modifiers: &Modifiers,
+        pressed: bool,
+    ) -> Option<InputAction> {
+        let terminal_mode = self.term_ctx.term_mode();
+        if terminal_mode.intersects(TermMode::MOUSE_MODE) {
+            let button = match button {
+                PointerButton::Middle => MouseButton::MiddleButton,
+                PointerButton::Extra1 => MouseButton::BackButton,
+                PointerButton::Extra2 => MouseButton::ForwardButton,
+                _ => return None,
+            };
+            return Some(InputAction::BackendCall(BackendCommand::MouseReport(
+                button,
+                *modifiers,
+                state.mouse_point,
+                pressed,
+            )));
+        }
+        if !pressed {
+            return None;
+        }
+        let action =
+            self.bindings_layout
+                .get_action(InputKind::Mouse(button), *modifiers, terminal_mode);
+        match action? {
+            BindingAction::Paste => {
+                let data = self.term_ctx.clipboard.get_contents().ok()?;
+                Some(InputAction::BackendCall(BackendCommand::Write(
+                    data.into_bytes(),
+                )))
+            }
+            BindingAction::Copy => Some(InputAction::WriteToClipboard(self.copied_selection())),
+            BindingAction::PreviousPrompt => {
+                Some(InputAction::BackendCall(BackendCommand::PreviousPrompt))
+            }
+            BindingAction::NextPrompt => Some(InputAction::BackendCall(BackendCommand::NextPrompt)),
+            BindingAction::SearchForward => {
+                self.open_search_bar();
+                None
+            }
+            BindingAction::Char(c) => Some(self.text_input(&c.to_string())),
+            BindingAction::Esc(seq) => Some(self.text_input(&seq)),
             _ => None,
         }
     }
@@ -148,8 +318,35 @@ impl TerminalView<'_> {
         if layout.context_menu_opened() {
             return None;
         }
+        let summary = state
+            .output_summaries
+            .iter()
+            .find(|(rect, _)| rect.contains(position));
+        if let Some((_, index)) = summary {
+            state.is_dragged = false;
+            return pressed.then_some(InputAction::BackendCall(BackendCommand::ToggleFold(*index)));
+        }
         let terminal_mode = self.term_ctx.terminal.mode();
-        if terminal_mode.intersects(TermMode::MOUSE_MODE) {
+        if pressed && modifiers.alt && !terminal_mode.intersects(TermMode::MOUSE_MODE) {
+            if let Some(moves) = self.prompt_cursor_moves(layout, position) {
+                state.is_dragged = false;
+                return self.move_prompt_cursor(moves);
+            }
+        }
+        let offset = self.term_ctx.line_wrap.offset;
+        let x = position.x - layout.rect.min.x + offset;
+        let y = position.y - layout.rect.min.y;
+        // e.g. Shift+click, even over the applications reporting the mouse
+        let extends = self.bindings_layout.get_action(
+            InputKind::Mouse(PointerButton::Primary),
+            *modifiers,
+            *terminal_mode,
+        ) == Some(BindingAction::ExtendSelection)
+            && !self.term_ctx.selection_is_empty();
+        if extends && pressed && is_in_terminal(position, layout.rect) {
+            state.is_dragged = true;
+            Some(InputAction::BackendCall(BackendCommand::SelectUpdate(x, y)))
+        } else if terminal_mode.intersects(TermMode::MOUSE_MODE) && !extends {
             Some(InputAction::BackendCall(BackendCommand::MouseReport(
                 MouseButton::LeftButton,
                 *modifiers,
@@ -158,37 +355,88 @@ impl TerminalView<'_> {
             )))
         } else if pressed && is_in_terminal(position, layout.rect) {
             state.is_dragged = true;
-            Some(InputAction::BackendCall(start_select_command(
-                layout, position,
+            let display_offset = self.term_ctx.terminal.grid().display_offset();
+            let point = selection_point(x, y, self.term_ctx.size, display_offset);
+            let time = layout.ctx.input(|i| i.time);
+            let count = state.clicks.press(time, point);
+            let action = |input| {
+                self.bindings_layout
+                    .get_action(input, *modifiers, *terminal_mode)
+            };
+            if count > 1
+                && action(InputKind::DoubleClick(PointerButton::Primary))
+                    == Some(BindingAction::ExpandSelection)
+            {
+                state.is_dragged = false;
+                return Some(InputAction::BackendCall(BackendCommand::ExpandSelection(
+                    point,
+                )));
+            }
+            let selection_type = match action(InputKind::Mouse(PointerButton::Primary)) {
+                Some(BindingAction::BlockSelection) if count == 1 => SelectionType::Block,
+                _ => state.clicks.selection_type(),
+            };
+            Some(InputAction::BackendCall(BackendCommand::SelectStart(
+                selection_type,
+                x,
+                y,
             )))
         } else {
-            self.left_button_released(state, layout, position, modifiers)
+            self.left_button_released(state, layout, modifiers)
         }
     }
 
+    /// Characters between the cursor and the clicked cell of the command typed
+    /// at the prompt, see [`Term::prompt_cursor_moves`].
+    ///
+    /// [`Term::prompt_cursor_moves`]: alacritty_terminal::Term::prompt_cursor_moves
+    fn prompt_cursor_moves(&self, layout: &Response, position: Pos2) -> Option<isize> {
+        let x = position.x - layout.rect.min.x + self.term_ctx.line_wrap.offset;
+        let y = position.y - layout.rect.min.y;
+        let display_offset = self.term_ctx.terminal.grid().display_offset();
+        let point = selection_point(x, y, self.term_ctx.size, display_offset);
+        self.term_ctx.terminal.prompt_cursor_moves(point)
+    }
+
+    /// Move the cursor of the shell with the arrow keys, like they are sent in
+    /// the current mode.
+    fn move_prompt_cursor(&self, moves: isize) -> Option<InputAction> {
+        let key = if moves < 0 {
+            Key::ArrowLeft
+        } else {
+            Key::ArrowRight
+        };
+        let mode = self.term_ctx.term_mode();
+        let Some(BindingAction::Esc(arrow)) =
+            self.bindings_layout
+                .get_action(InputKind::KeyCode(key), Modifiers::NONE, mode)
+        else {
+            return None;
+        };
+        let keys = arrow.repeat(moves.unsigned_abs());
+        (moves != 0).then(|| InputAction::BackendCall(BackendCommand::Write(keys.into_bytes())))
+    }
+
     pub fn left_button_released(
         &self,
         state: &mut TerminalViewState,
         layout: &Response,
-        position: Pos2,
         modifiers: &Modifiers,
     ) -> Option<InputAction> {
         state.is_dragged = false;
+        // the selection of a double or triple click started when it was pressed
         if layout.double_clicked() || layout.triple_clicked() {
-            Some(InputAction::BackendCall(start_select_command(
-                layout, position,
-            )))
-        } else {
-            match self.bindings_layout.get_action(
-                InputKind::Mouse(PointerButton::Primary),
-                *modifiers,
-                *self.term_ctx.terminal.mode(),
-            ) {
-                Some(BindingAction::LinkOpen) => Some(InputAction::BackendCall(
-                    BackendCommand::ProcessLink(LinkAction::Open, state.mouse_point),
-                )),
-                _ => None,
-            }
+            return None;
+        }
+        match self.bindings_layout.get_action(
+            InputKind::Mouse(PointerButton::Primary),
+            *modifiers,
+            *self.term_ctx.terminal.mode(),
+        ) {
+            Some(BindingAction::LinkOpen) => Some(InputAction::BackendCall(
+                BackendCommand::ProcessLink(LinkAction::Open, state.mouse_point),
+            )),
+            _ => None,
         }
     }
 
@@ -199,7 +447,7 @@ impl TerminalView<'_> {
         position: Pos2,
         modifiers: &Modifiers,
     ) -> Vec<InputAction> {
-        let mouse_x = position.x - layout.rect.min.x;
+        let mouse_x = position.x - layout.rect.min.x + self.term_ctx.line_wrap.offset;
         let mouse_y = position.y - layout.rect.min.y;
 
         state.mouse_point = selection_point(
@@ -275,22 +523,28 @@ impl TerminalView<'_> {
     }
 }
 
-fn start_select_command(layout: &Response, cursor_position: Pos2) -> BackendCommand {
-    let selection_type = if layout.double_clicked() {
-        SelectionType::Semantic
-    } else if layout.triple_clicked() {
-        SelectionType::Lines
-    } else {
-        SelectionType::Simple
-    };
-
-    BackendCommand::SelectStart(
-        selection_type,
-        cursor_position.x - layout.rect.min.x,
-        cursor_position.y - layout.rect.min.y,
-    )
-}
-
 pub fn is_in_terminal(pos: Pos2, rect: Rect) -> bool {
     pos.x > rect.min.x && pos.x < rect.max.x && pos.y > rect.min.y && pos.y < rect.max.y
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ClickCount;
+    use alacritty_terminal::index::{Column, Line, Point};
+    use alacritty_terminal::selection::SelectionType;
+
+    #[test]
+    fn clicks_in_a_row() {
+        let mut clicks = ClickCount::default();
+        let point = Point::new(Line(2), Column(4));
+        assert_eq!(clicks.press(1.0, point), 1);
+        assert_eq!(clicks.press(1.2, point), 2);
+        assert!(matches!(clicks.selection_type(), SelectionType::Semantic));
+        assert_eq!(clicks.press(1.4, point), 3);
+        assert!(matches!(clicks.selection_type(), SelectionType::Lines));
+        assert_eq!(clicks.press(1.5, point), 1);
+        // too late, or on another cell
+        assert_eq!(clicks.press(2.0, point), 1);
+        assert_eq!(clicks.press(2.1, Point::new(Line(2), Column(5))), 1);
+    }
+}
@@ -0,0 +1,77 @@
+use std::sync::LazyLock;
+
+pub(crate) static DEFAULT_LABELS: LazyLock<TerminalLabels> = LazyLock::new(TerminalLabels::default);
+
+/// Text shown by the terminal widget, in English unless the application
+/// translates it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalLabels {
+    pub copy: String,
+    pub copy_as_html: String,
+    pub copy_as_ansi: String,
+    pub paste: String,
+    pub select_all: String,
+    pub find: String,
+    pub save_scrollback: String,
+    pub wrap_lines: String,
+    pub wrap_lines_hint: String,
+    pub match_case: String,
+    pub use_regex: String,
+    pub previous_match: String,
+    pub next_match: String,
+    pub close_search: String,
+    pub invalid_regex: String,
+    pub no_matches: String,
+    /// `{current}` and `{count}` are replaced by the focused match and the
+    /// number of matches.
+    pub matches: String,
+    /// `{shortcut}` is replaced by the protected key.
+    pub send_key_prompt: String,
+    pub send_key: String,
+    pub cancel_key: String,
+    /// `{count}` is replaced by the number of lines of a command output.
+    pub output_lines: String,
+    /// `{code}` is replaced by the exit code of a command.
+    pub exit_code: String,
+    /// `{error}` is replaced by the failure of the terminal backend.
+    pub backend_error: String,
+    pub retry: String,
+    /// Shown in place of retry once the backend stopped.
+    pub restart: String,
+    pub dismiss: String,
+    pub close_tab: String,
+}
+
+impl Default for TerminalLabels {
+    fn default() -> Self {
+        Self {
+            copy: "Copy".to_string(),
+            copy_as_html: "Copy as HTML".to_string(),
+            copy_as_ansi: "Copy as ANSI".to_string(),
+            paste: "Paste".to_string(),
+            select_all: "Select All".to_string(),
+            find: "Find".to_string(),
+            save_scrollback: "Save Scrollback to File".to_string(),
+            wrap_lines: "Wrap Lines".to_string(),
+            wrap_lines_hint: "Scroll long lines horizontally when off".to_string(),
+            match_case: "Match case".to_string(),
+            use_regex: "Use regular expression".to_string(),
+            previous_match: "Previous match (Shift+Enter)".to_string(),
+            next_match: "Next match (Enter)".to_string(),
+            close_search: "Close (Esc)".to_string(),
+            invalid_regex: "Invalid regex".to_string(),
+            no_matches: "No matches".to_string(),
+            matches: "{current}/{count} matches".to_string(),
+            send_key_prompt: "Send {shortcut} to the terminal?".to_string(),
+            send_key: "Send (Enter)".to_string(),
+            cancel_key: "Cancel (Esc)".to_string(),
+            output_lines: "{count} lines".to_string(),
+            exit_code: "exit {code}".to_string(),
+            backend_error: "The terminal backend failed: {error}".to_string(),
+            retry: "Retry".to_string(),
+            restart: "Restart".to_string(),
+            dismiss: "Dismiss".to_string(),
+            close_tab: "Close".to_string(),
+        }
+    }
+}
@@ -1,21 +1,58 @@
 mod alacritty;
 mod bindings;
+mod color_test;
 mod display;
+mod encoding;
 mod errors;
 mod font;
+mod highlight;
 mod input;
+mod labels;
+mod local_echo;
+mod output_log;
+mod query;
+mod redact;
 mod scroll_bar;
+mod search;
 mod ssh;
+mod styled;
 mod theme;
+mod thumbnail;
 mod types;
 mod ui;
 mod view;
+mod watchdog;
+mod wrap;
 
-pub use alacritty::{PtyEvent, TermType, Terminal, TerminalContext};
-pub use alacritty_terminal::term::TermMode;
-pub use bindings::{Binding, BindingAction, InputKind, KeyboardBinding};
-pub use font::{FontSettings, TerminalFont};
-pub use scroll_bar::{InteractiveScrollbar, ScrollbarState};
-pub use ssh::{Authentication, SshOptions};
-pub use theme::{ColorPalette, TerminalTheme};
-pub use view::{TerminalOptions, TerminalView};
+pub use alacritty::{
+    open_link_externally, BackendCommand, EventHook, GridStatus, LinkHandler, PendingTerminal,
+    PtyEvent, TermType, Terminal, TerminalConfig, TerminalContext, DEFAULT_SCROLLBACK_LINES,
+};
+pub use alacritty_terminal::term::{TermMode, UnicodeWidth};
+pub use bindings::{
+    Binding, BindingAction, InputKind, KeyboardBinding, MetaKeys, BLOCK_SELECTION_MODIFIERS,
+};
+pub use color_test::ColorTest;
+pub use encoding::TermEncoding;
+pub use errors::{ConnectFailure, TermError};
+pub use font::{FontSettings, TerminalFont, CELL_PADDING_RANGE, LINE_HEIGHT_RANGE};
+pub use labels::TerminalLabels;
+pub use output_log::OutputLog;
+pub use query::{GridMatch, GridPoint};
+pub use redact::{Redaction, DEFAULT_REDACTION_PATTERNS, REDACTED_CHAR};
+pub use scroll_bar::{InteractiveScrollbar, ScrollMarker, ScrollbarState};
+pub use search::{SearchOptions, TerminalSearch};
+pub use ssh::{
+    cd_command, connect as connect_ssh, connect_with_observer as connect_ssh_with_observer,
+    Authentication, ConnectObserver, ConnectStage, Forward, HostKeyChange, HostKeyCheck,
+    InteractivePrompt, KeyboardInteractive, RemoteLocale, SessionLatency, SshOptions,
+    TransportOptions, TunnelState, TunnelStatus,
+};
+pub use styled::{HistorySnapshot, TextRun};
+pub use theme::{
+    color_to_hex, ColorDeficiency, ColorFilter, ColorPalette, TerminalTheme, TerminalTint,
+    TrueColor,
+};
+pub use thumbnail::TerminalThumbnail;
+pub use view::{TerminalOptions, TerminalView, TerminalViewState};
+pub use wrap::{LineWrap, NO_WRAP_COLUMNS};
@@ -0,0 +1,194 @@
+//! Predictive local echo, like mosh: the chars typed are shown at the cursor
+//! right away, before the server echoes them back over a slow connection.
+//!
+//! The predictions are conservative, they are only made for printable ascii
+//! typed at the end of a line of the primary screen, and all of them are
+//! dropped as soon as the server disagrees with one. They are only made where
+//! the input is known to be echoed, at a shell prompt marked through OSC 133
+//! or once a char typed there came back, so that the passwords typed at the
+//! prompts which don't echo, e.g. of sudo, aren't shown.
+
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Column, Point};
+use alacritty_terminal::term::{Term, TermMode};
+use std::time::{Duration, Instant};
+
+/// Predictions the server didn't confirm by then are dropped.
+const PREDICTION_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Prediction {
+    point: Point,
+    c: char,
+    at: Instant,
+}
+
+/// Chars typed but not echoed by the server yet.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LocalEcho {
+    predictions: Vec<Prediction>,
+    /// Char typed where the echo isn't known yet, not shown, watched to tell
+    /// whether the server echoes it.
+    probe: Option<Prediction>,
+    /// Cursor after the last char the server echoed, the chars typed there
+    /// are predicted.
+    echoed_at: Option<Point>,
+}
+
+impl LocalEcho {
+    /// Predict the echo of `text` typed at the cursor of `term`.
+    pub(crate) fn predict<T>(&mut self, term: &Term<T>, text: &str) {
+        if term.mode().contains(TermMode::ALT_SCREEN)
+            || !text.chars().all(|c| matches!(c, ' '..='~'))
+        {
+            self.reset();
+            return;
+        }
+        let grid = term.grid();
+        let mut point = match self.predictions.last() {
+            Some(prediction) => Point::new(prediction.point.line, prediction.point.column + 1),
+            None => {
+                let cursor = grid.cursor.point;
+                // typed in the middle of a line, the rest of it would move
+                let row = &grid[cursor.line];
+                let blank_after =
+                    (cursor.column.0..term.columns()).all(|column| row[Column(column)].c == ' ');
+                if !blank_after {
+                    return;
+                }
+                let echoed = term.prompt_input_start().is_some() || self.echoed_at == Some(cursor);
+                if !echoed {
+                    if let Some(c) = text.chars().next() {
+                        self.probe = Some(Prediction {
+                            point: cursor,
+                            c,
+                            at: Instant::now(),
+                        });
+                    }
+                    return;
+                }
+                cursor
+            }
+        };
+        let now = Instant::now();
+        for c in text.chars() {
+            // the cursor after the prediction has to fit on the line
+            if point.column.0 + 1 >= term.columns() {
+                return;
+            }
+            self.predictions.push(Prediction { point, c, at: now });
+            point.column += 1;
+        }
+    }
+
+    /// Forget the predictions, and where the input was echoed, e.g. when a
+    /// key which isn't echoed is typed.
+    pub(crate) fn reset(&mut self) {
+        self.predictions.clear();
+        self.probe = None;
+        self.echoed_at = None;
+    }
+
+    /// Drop the predictions the server echoed. When it shows something else,
+    /// or takes too long, all of them are wrong.
+    pub(crate) fn confirm<T>(&mut self, term: &Term<T>) {
+        if let Some(probe) = self.probe {
+            let after = Point::new(probe.point.line, probe.point.column + 1);
+            let cursor = term.grid().cursor.point;
+            if cursor == after && term.grid()[probe.point].c == probe.c {
+                self.echoed_at = Some(after);
+                self.probe = None;
+            } else if probe.at.elapsed() > PREDICTION_TIMEOUT {
+                self.probe = None;
+            }
+        }
+        if self.predictions.is_empty() {
+            return;
+        }
+        if term.mode().contains(TermMode::ALT_SCREEN)
+            || self.predictions[0].at.elapsed() > PREDICTION_TIMEOUT
+        {
+            self.reset();
+            return;
+        }
+        let grid = term.grid();
+        let mut confirmed = 0;
+        for (i, prediction) in self.predictions.iter().enumerate() {
+            let point = prediction.point;
+            if point.line > term.bottommost_line() || point.column.0 >= term.columns() {
+                self.reset();
+                return;
+            }
+            match grid[point].c {
+                c if c == prediction.c => confirmed = i + 1,
+                ' ' => break,
+                _ => {
+                    self.reset();
+                    return;
+                }
+            }
+        }
+        if let Some(last) = confirmed.checked_sub(1).map(|i| self.predictions[i].point) {
+            self.echoed_at = Some(Point::new(last.line, last.column + 1));
+        }
+        self.predictions.drain(..confirmed);
+    }
+
+    /// Chars predicted and where they are shown.
+    pub(crate) fn predictions(&self) -> Vec<(Point, char)> {
+        self.predictions.iter().map(|p| (p.point, p.c)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alacritty_terminal::index::Line;
+    use alacritty_terminal::term::test::mock_term;
+
+    #[test]
+    fn predictions_until_echoed() {
+        let mut term = mock_term("$           ");
+        term.grid_mut().cursor.point = Point::new(Line(0), Column(2));
+        let mut echo = LocalEcho::default();
+        // without the shell integration, the first char has to come back
+        echo.predict(&term, "l");
+        assert!(echo.predictions().is_empty());
+        term.grid_mut()[Line(0)][Column(2)].c = 'l';
+        term.grid_mut().cursor.point = Point::new(Line(0), Column(3));
+        echo.confirm(&term);
+
+        echo.predict(&term, "s");
+        echo.predict(&term, " -l");
+        assert_eq!(echo.predictions().len(), 4);
+        assert_eq!(echo.predictions()[3], (Point::new(Line(0), Column(6)), 'l'));
+
+        // the server echoed the first two
+        term.grid_mut()[Line(0)][Column(3)].c = 's';
+        echo.confirm(&term);
+        assert_eq!(echo.predictions()[0], (Point::new(Line(0), Column(5)), '-'));
+
+        // and then something else
+        term.grid_mut()[Line(0)][Column(5)].c = 'x';
+        echo.confirm(&term);
+        assert!(echo.predictions().is_empty());
+
+        // the rest of the line would move
+        echo.predict(&term, "a");
+        assert!(echo.predictions().is_empty());
+        echo.predict(&term, "\t");
+        assert!(echo.predictions().is_empty());
+    }
+
+    #[test]
+    fn no_predictions_at_a_password_prompt() {
+        let mut term = mock_term("Password:   ");
+        term.grid_mut().cursor.point = Point::new(Line(0), Column(10));
+        let mut echo = LocalEcho::default();
+        echo.predict(&term, "s");
+        echo.confirm(&term);
+        echo.predict(&term, "ecret");
+        assert!(echo.predictions().is_empty());
+        assert_eq!(echo.echoed_at, None);
+    }
+}
@@ -0,0 +1,308 @@
+//! Output of a terminal saved as plain text, e.g. to keep a trace of a
+//! session.
+
+use crate::encoding::{OutputDecoder, TermEncoding};
+use crate::redact::Redaction;
+use alacritty_terminal::event::{OnResize, WindowSize};
+use alacritty_terminal::tty::{ChildEvent, EventedPty, EventedReadWrite};
+use alacritty_terminal::vte::{Params, Parser, Perform};
+use parking_lot::Mutex;
+use polling::{Event, PollMode, Poller};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::error;
+
+const TAB_WIDTH: usize = 8;
+
+/// Log of the output of a terminal, without its escape sequences.
+///
+/// Progress bars and spinners redraw their line after a carriage return,
+/// hundreds of times. With `collapse_progress`, only the state of the line
+/// once it ends is logged, like the terminal shows it. Otherwise every state
+/// it goes through is logged on its own line.
+///
+/// The secrets matched by its [`Redaction`] are masked.
+pub struct OutputLog {
+    path: PathBuf,
+    parser: Parser,
+    lines: LineWriter<BufWriter<File>>,
+}
+
+impl OutputLog {
+    /// Append the log to the file at `path`.
+    pub fn create(path: &Path, collapse_progress: bool, redaction: Redaction) -> io::Result<Self> {
+        let file = File::options().create(true).append(true).open(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            parser: Parser::new(),
+            lines: LineWriter::new(BufWriter::new(file), collapse_progress, redaction),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn advance(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.parser.advance(&mut self.lines, bytes);
+        match self.lines.error.take() {
+            Some(err) => Err(err),
+            None => self.lines.out.flush(),
+        }
+    }
+}
+
+impl Drop for OutputLog {
+    fn drop(&mut self) {
+        // the line the output stopped at, e.g. the prompt
+        let _ = self.lines.end_line();
+        let _ = self.lines.out.flush();
+    }
+}
+
+/// Plain text of the output, line by line.
+struct LineWriter<W: Write> {
+    out: W,
+    collapse_progress: bool,
+    redaction: Redaction,
+    line: Vec<char>,
+    column: usize,
+    /// Set after a carriage return, until the line is written over or ends.
+    returned: bool,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> LineWriter<W> {
+    fn new(out: W, collapse_progress: bool, redaction: Redaction) -> Self {
+        Self {
+            out,
+            collapse_progress,
+            redaction,
+            line: vec![],
+            column: 0,
+            returned: false,
+            error: None,
+        }
+    }
+
+    fn end_line(&mut self) -> io::Result<()> {
+        let line: String = self.line.drain(..).collect();
+        self.column = 0;
+        self.returned = false;
+        if line.is_empty() {
+            return Ok(());
+        }
+        writeln!(self.out, "{}", self.redaction.redact(line.trim_end()))
+    }
+
+    /// Keep the previous state of a line which is drawn again, unless the
+    /// progress is collapsed.
+    fn redraw(&mut self) {
+        if std::mem::take(&mut self.returned) && !self.collapse_progress {
+            if let Err(err) = self.end_line() {
+                self.error = Some(err);
+            }
+        }
+    }
+
+    fn put(&mut self, c: char) {
+        self.redraw();
+        if self.column < self.line.len() {
+            self.line[self.column] = c;
+        } else {
+            self.line.resize(self.column, ' ');
+            self.line.push(c);
+        }
+        self.column += 1;
+    }
+}
+
+impl<W: Write> Perform for LineWriter<W> {
+    fn print(&mut self, c: char) {
+        self.put(c);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            b'\n' => {
+                if let Err(err) = self.end_line() {
+                    self.error = Some(err);
+                }
+            }
+            b'\r' => {
+                self.column = 0;
+                self.returned = !self.line.is_empty();
+            }
+            // backspace
+            0x08 => self.column = self.column.saturating_sub(1),
+            b'\t' => {
+                let spaces = TAB_WIDTH - self.column % TAB_WIDTH;
+                for _ in 0..spaces {
+                    self.put(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, _intermediates: &[u8], _ignore: bool, c: char) {
+        let param = params
+            .iter()
+            .next()
+            .and_then(|param| param.first().copied())
+            .unwrap_or(0) as usize;
+        match c {
+            // erase the rest of the line, or all of it
+            'K' if param == 0 => {
+                self.redraw();
+                self.line.truncate(self.column);
+            }
+            'K' if param == 2 => {
+                self.redraw();
+                self.line.clear();
+            }
+            // move the cursor within the line
+            'G' => self.column = param.saturating_sub(1),
+            'C' => self.column += param.max(1),
+            'D' => self.column = self.column.saturating_sub(param.max(1)),
+            _ => {}
+        }
+    }
+}
+
+/// PTY whose output is decoded to UTF-8, then copied to the log of the
+/// terminal while there is one.
+pub(crate) struct LoggedPty<P> {
+    pty: P,
+    log: Arc<Mutex<Option<OutputLog>>>,
+    /// Bytes read from the pty, see [`crate::Terminal::read_bytes`].
+    read_bytes: Arc<AtomicU64>,
+    /// See [`crate::Terminal::set_encoding`].
+    encoding: Arc<Mutex<TermEncoding>>,
+    decoder: OutputDecoder,
+}
+
+impl<P> LoggedPty<P> {
+    pub(crate) fn new(
+        pty: P,
+        log: Arc<Mutex<Option<OutputLog>>>,
+        read_bytes: Arc<AtomicU64>,
+        encoding: Arc<Mutex<TermEncoding>>,
+    ) -> Self {
+        Self {
+            pty,
+            log,
+            read_bytes,
+            encoding,
+            decoder: OutputDecoder::default(),
+        }
+    }
+}
+
+impl<P: EventedReadWrite> Read for LoggedPty<P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let encoding = *self.encoding.lock();
+        let read = self.decoder.read(encoding, self.pty.reader(), buf)?;
+        self.read_bytes.fetch_add(read as u64, Ordering::Relaxed);
+        let mut log = self.log.lock();
+        if let Some(output_log) = log.as_mut() {
+            if let Err(err) = output_log.advance(&buf[..read]) {
+                error!("stopped the log {}: {err}", output_log.path().display());
+                *log = None;
+            }
+        }
+        Ok(read)
+    }
+}
+
+impl<P: EventedReadWrite> EventedReadWrite for LoggedPty<P> {
+    type Reader = Self;
+    type Writer = P::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poller: &Arc<Poller>,
+        interest: Event,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.register(poller, interest, mode)
+    }
+
+    fn reregister(
+        &mut self,
+        poller: &Arc<Poller>,
+        interest: Event,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poller, interest, mode)
+    }
+
+    fn deregister(&mut self, poller: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poller)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        self
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl<P: EventedPty> EventedPty for LoggedPty<P> {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl<P: OnResize> OnResize for LoggedPty<P> {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.pty.on_resize(window_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(output: &[u8], collapse_progress: bool) -> String {
+        log_redacted(output, collapse_progress, Redaction::default())
+    }
+
+    fn log_redacted(output: &[u8], collapse_progress: bool, redaction: Redaction) -> String {
+        let mut lines = LineWriter::new(vec![], collapse_progress, redaction);
+        Parser::new().advance(&mut lines, output);
+        lines.end_line().unwrap();
+        String::from_utf8(lines.out).unwrap()
+    }
+
+    #[test]
+    fn collapse_progress_lines() {
+        let output = b"$ make\r\n\x1b[32mbuilding\x1b[0m\r\n 10% [#   ]\r 50% [##  ]\r100% [####]\r\ndone\r\n";
+        assert_eq!(log(output, true), "$ make\nbuilding\n100% [####]\ndone\n");
+        assert_eq!(
+            log(output, false),
+            "$ make\nbuilding\n 10% [#   ]\n 50% [##  ]\n100% [####]\ndone\n"
+        );
+
+        // spinner erasing its line, and the prompt the output stops at
+        let output = b"| working\r\x1b[K/ working\r\x1b[Kok\r\n$ ";
+        assert_eq!(log(output, true), "ok\n$\n");
+        assert_eq!(log(output, false), "| working\n/ working\nok\n$\n");
+        assert_eq!(log(b"a\tb\x08c", true), "a       c\n");
+    }
+
+    #[test]
+    fn redact_logged_secrets() {
+        let redaction = Redaction::new(&[r"token=(\w+)"]).unwrap();
+        let output = b"$ curl -d token=\x1b[1mabc123\x1b[0m\r\nok\r\n";
+        assert_eq!(
+            log_redacted(output, true, redaction),
+            "$ curl -d token=******\nok\n"
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Read-only queries of the grid, for the tests, assistants and automations
+//! driving a terminal without reaching into alacritty.
+
+use crate::errors::TermError;
+use crate::search::{search_pattern, SearchOptions, MAX_SEARCH_MATCHES};
+use crate::Terminal;
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Column, Direction, Point};
+use alacritty_terminal::term::search::{RegexIter, RegexSearch};
+use alacritty_terminal::term::Term;
+
+/// Cell of the grid. Line 0 is the top of the screen when it isn't scrolled,
+/// the lines of the history are negative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GridPoint {
+    pub line: i32,
+    pub column: usize,
+}
+
+impl From<Point> for GridPoint {
+    fn from(point: Point) -> Self {
+        Self {
+            line: point.line.0,
+            column: point.column.0,
+        }
+    }
+}
+
+/// Text found by [`Terminal::find`], between two cells included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridMatch {
+    pub start: GridPoint,
+    pub end: GridPoint,
+    pub text: String,
+}
+
+impl Terminal {
+    /// Cell of the cursor.
+    pub fn cursor_point(&self) -> GridPoint {
+        self.term.lock().grid().cursor.point.into()
+    }
+
+    /// Text of the rows of the screen as scrolled, without their trailing
+    /// blanks.
+    pub fn screen_text(&self) -> Vec<String> {
+        self.styled_screen(&Default::default())
+            .into_iter()
+            .map(|runs| {
+                let row: String = runs.into_iter().map(|run| run.text).collect();
+                row.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    /// Matches of `query` in the history and the screen, from the top. The
+    /// search stops after as many matches as the search bar shows.
+    pub fn find(&self, query: &str, options: &SearchOptions) -> Result<Vec<GridMatch>, TermError> {
+        find(&self.term.lock(), query, options)
+    }
+}
+
+fn find<T>(
+    term: &Term<T>,
+    query: &str,
+    options: &SearchOptions,
+) -> Result<Vec<GridMatch>, TermError> {
+    let pattern = search_pattern(query, options);
+    let mut regex = RegexSearch::new(&pattern).map_err(|err| TermError::SearchPattern {
+        pattern: query.to_string(),
+        err: err.to_string(),
+    })?;
+    let start = Point::new(term.topmost_line(), Column(0));
+    let end = Point::new(term.bottommost_line(), term.last_column());
+    let matches = RegexIter::new(start, end, Direction::Right, term, &mut regex)
+        .take(MAX_SEARCH_MATCHES)
+        .map(|found| GridMatch {
+            start: (*found.start()).into(),
+            end: (*found.end()).into(),
+            text: term.bounds_to_string(*found.start(), *found.end()),
+        })
+        .collect();
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alacritty_terminal::event::VoidListener;
+    use alacritty_terminal::term::test::TermSize;
+    use alacritty_terminal::term::Config;
+    use alacritty_terminal::vte::ansi::Handler;
+
+    #[test]
+    fn find_in_the_history() {
+        let size = TermSize::new(10, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        for line in ["Error one", "ok", "error two"] {
+            for c in line.chars() {
+                term.input(c);
+            }
+            term.carriage_return();
+            term.linefeed();
+        }
+
+        let found = find(&term, "error", &SearchOptions::default()).unwrap();
+        let starts: Vec<_> = found.iter().map(|found| found.start).collect();
+        assert_eq!(
+            starts,
+            [
+                GridPoint {
+                    line: -2,
+                    column: 0
+                },
+                GridPoint { line: 0, column: 0 }
+            ]
+        );
+        assert_eq!(found[0].text, "Error");
+
+        let options = SearchOptions {
+            case_sensitive: true,
+            regex: false,
+        };
+        assert_eq!(find(&term, "Error", &options).unwrap().len(), 1);
+        let regex = SearchOptions {
+            case_sensitive: false,
+            regex: true,
+        };
+        assert!(find(&term, "(", &regex).is_err());
+    }
+}
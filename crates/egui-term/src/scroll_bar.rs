@@ -1,20 +1,77 @@
 use egui::{Color32, NumExt, Pos2, Rect, Sense, Ui, Vec2};
 
+/// Slower flings don't keep scrolling, in pixels per second.
+const MIN_FLING_VELOCITY: f32 = 60.;
+/// The momentum is lost at this rate, by second.
+const FLING_FRICTION: f32 = 4.;
+
 #[derive(Clone)]
 pub struct ScrollbarState {
+    /// Pixels scrolled short of a line, shown as an offset of the content.
     pub scroll_pixels: f32,
+    /// Pixels scrolled by the trackpad on this frame.
+    pub(crate) flung: f32,
+    /// Momentum left by the last fling, in pixels per second.
+    pub(crate) velocity: f32,
 }
 
 impl Default for ScrollbarState {
     fn default() -> Self {
-        Self { scroll_pixels: 0.0 }
+        Self {
+            scroll_pixels: 0.0,
+            flung: 0.0,
+            velocity: 0.0,
+        }
     }
 }
 
+impl ScrollbarState {
+    /// Keep scrolling after a fling, slowing down over the `dt` seconds of
+    /// the frame. Returns the lines of `line_height` scrolled, up is positive.
+    pub(crate) fn momentum(&mut self, dt: f32, line_height: f32) -> i32 {
+        let flung = std::mem::take(&mut self.flung);
+        if flung != 0. {
+            // still scrolled by the fingers, follow how fast
+            self.velocity = flung / dt.max(f32::EPSILON);
+            return 0;
+        }
+        if self.velocity.abs() < MIN_FLING_VELOCITY {
+            // settle on the nearest line
+            self.velocity = 0.;
+            let lines = (self.scroll_pixels / line_height).round();
+            self.scroll_pixels = 0.;
+            return -lines as i32;
+        }
+        self.scroll_pixels -= self.velocity * dt;
+        self.velocity *= (-FLING_FRICTION * dt).exp();
+        let lines = (self.scroll_pixels / line_height).trunc();
+        self.scroll_pixels %= line_height;
+        -lines as i32
+    }
+
+    pub fn is_flinging(&self) -> bool {
+        self.velocity != 0.
+    }
+
+    /// Stop the fling, e.g. on a click.
+    pub fn stop(&mut self) {
+        self.velocity = 0.;
+    }
+}
+
+/// Lines of the history marked on the scrollbar, e.g. the search matches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollMarker {
+    /// Pixels between the top of the history and the line.
+    pub line_pos: f32,
+    pub color: Color32,
+}
+
 pub struct InteractiveScrollbar {
     pub first_row_pos: f32,
     pub new_first_row_pos: Option<f32>,
     pub background: Color32,
+    pub markers: Vec<ScrollMarker>,
 }
 
 impl InteractiveScrollbar {
@@ -23,6 +80,7 @@ impl InteractiveScrollbar {
             first_row_pos: 0.0,
             new_first_row_pos: None,
             background,
+            markers: vec![],
         }
     }
 
@@ -30,8 +88,15 @@ impl InteractiveScrollbar {
         self.first_row_pos = row;
     }
 
+    /// Mark lines on the scrollbar, clicked to show them.
+    pub fn set_markers(&mut self, markers: Vec<ScrollMarker>) {
+        self.markers = markers;
+    }
+
     pub const WIDTH: f32 = 8.0;
     pub const MARGIN: f32 = 0.0;
+    /// Height of a marker, clicks this close to it show its line.
+    pub const MARKER_HEIGHT: f32 = 3.0;
 }
 
 impl InteractiveScrollbar {
@@ -62,6 +127,14 @@ impl InteractiveScrollbar {
             .rect_filled(scrollbar_rect, 0.0, self.background);
         ui.painter()
             .rect_filled(slider_rect, 0.0, Color32::DARK_GRAY);
+        let marker_y = |marker: &ScrollMarker| marker.line_pos * height / total_height;
+        for marker in &self.markers {
+            let marker_rect = Rect::from_min_size(
+                scrollbar_rect.min + Vec2::new(0.0, marker_y(marker)),
+                Vec2::new(scrollbar_width, InteractiveScrollbar::MARKER_HEIGHT),
+            );
+            ui.painter().rect_filled(marker_rect, 0.0, marker.color);
+        }
 
         let response = ui.allocate_rect(slider_rect, Sense::click_and_drag());
         let scrollbar_response = ui.allocate_rect(scrollbar_rect, Sense::click());
@@ -78,11 +151,50 @@ impl InteractiveScrollbar {
         if scrollbar_response.clicked() {
             if let Some(click_pos) = scrollbar_response.interact_pointer_pos() {
                 let click_y = click_pos.y - scrollbar_rect.top();
-                position = click_y.clamp(0.0, height);
-                let new_first_row_pos = max_value - position * max_value / max_scroll_top;
+                let marker = self.markers.iter().find(|marker| {
+                    let y = marker_y(marker);
+                    (y - InteractiveScrollbar::MARKER_HEIGHT
+                        ..=y + 2.0 * InteractiveScrollbar::MARKER_HEIGHT)
+                        .contains(&click_y)
+                });
+                let new_first_row_pos = match marker {
+                    // its line in the middle of the screen
+                    Some(marker) => {
+                        (max_value - marker.line_pos + height / 2.0).clamp(0.0, max_value)
+                    }
+                    None => {
+                        position = click_y.clamp(0.0, height);
+                        max_value - position * max_value / max_scroll_top
+                    }
+                };
                 self.new_first_row_pos = Some(new_first_row_pos);
             }
         }
         ui.ctx().request_repaint();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fling_slows_down() {
+        let mut state = ScrollbarState::default();
+        state.flung = 20.;
+        assert_eq!(state.momentum(0.01, 10.), 0);
+        assert_eq!(state.velocity, 2000.);
+
+        // the fingers are lifted, it goes on up
+        let lines = state.momentum(0.01, 10.);
+        assert_eq!(lines, 2);
+        assert!(state.velocity < 2000.);
+
+        let mut scrolled = lines;
+        while state.is_flinging() {
+            scrolled += state.momentum(0.01, 10.);
+        }
+        assert!(scrolled > 2 && scrolled < 60);
+        assert_eq!(state.scroll_pixels, 0.);
+    }
+}
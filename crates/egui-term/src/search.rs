@@ -0,0 +1,286 @@
+use alacritty_terminal::event::EventListener;
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Column, Direction, Line, Point};
+use alacritty_terminal::term::search::{Match, RegexIter, RegexSearch};
+use alacritty_terminal::term::Term;
+
+/// Upper bound of matches collected for a single query, protects the UI
+/// from patterns that match almost every cell of a long scrollback.
+pub(crate) const MAX_SEARCH_MATCHES: usize = 10_000;
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub case_sensitive: bool,
+    pub regex: bool,
+}
+
+#[derive(Default)]
+pub struct TerminalSearch {
+    /// Whether the search bar is shown.
+    pub active: bool,
+    pub query: String,
+    pub options: SearchOptions,
+    /// Set when the query is not a valid regex.
+    pub error: Option<String>,
+    /// The search input should grab the keyboard focus on next frame.
+    focus_requested: bool,
+    matches: Vec<Match>,
+    focused: Option<usize>,
+    /// Query, options and generation of the grid the matches were computed
+    /// for.
+    cache_key: Option<(String, bool, bool, u64)>,
+}
+
+impl TerminalSearch {
+    pub fn open(&mut self) {
+        self.active = true;
+        self.focus_requested = true;
+    }
+
+    /// Open the search bar on `query`, or on the last query without one.
+    pub fn start(&mut self, query: Option<String>) {
+        if let Some(query) = query {
+            self.query = query;
+        }
+        self.open();
+    }
+
+    pub fn close(&mut self) {
+        self.active = false;
+        self.matches.clear();
+        self.focused = None;
+        self.cache_key = None;
+        self.error = None;
+    }
+
+    pub(crate) fn take_focus_request(&mut self) -> bool {
+        std::mem::take(&mut self.focus_requested)
+    }
+
+    pub fn matches(&self) -> &[Match] {
+        &self.matches
+    }
+
+    pub fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// 1-based index of the focused match, for display purpose.
+    pub fn focused_index(&self) -> Option<usize> {
+        self.focused.map(|i| i + 1)
+    }
+
+    pub fn focused_match(&self) -> Option<&Match> {
+        self.focused.and_then(|i| self.matches.get(i))
+    }
+
+    /// Whether matches should be highlighted and the rest of the grid dimmed.
+    pub fn is_highlighting(&self) -> bool {
+        self.active && !self.query.is_empty()
+    }
+
+    /// Recompute the matches on the next update, e.g. once lines were moved.
+    pub(crate) fn invalidate(&mut self) {
+        self.cache_key = None;
+    }
+
+    /// Recompute matches if the query, the options or the grid content changed.
+    pub(crate) fn update<T: EventListener>(&mut self, term: &Term<T>) {
+        if !self.is_highlighting() {
+            self.matches.clear();
+            self.focused = None;
+            self.cache_key = None;
+            self.error = None;
+            return;
+        }
+
+        let key = (
+            self.query.clone(),
+            self.options.case_sensitive,
+            self.options.regex,
+            term.generation(),
+        );
+        if self.cache_key.as_ref() == Some(&key) {
+            return;
+        }
+        let query_changed =
+            self.cache_key
+                .as_ref()
+                .is_none_or(|(query, case_sensitive, regex, ..)| {
+                    query != &key.0 || *case_sensitive != key.1 || *regex != key.2
+                });
+        self.cache_key = Some(key);
+
+        let mut regex = match RegexSearch::new(&self.pattern()) {
+            Ok(regex) => regex,
+            Err(err) => {
+                self.error = Some(err.to_string());
+                self.matches.clear();
+                self.focused = None;
+                return;
+            }
+        };
+        self.error = None;
+
+        let previous = self.focused_match().map(|m| *m.start());
+        let start = Point::new(term.topmost_line(), Column(0));
+        let end = Point::new(term.bottommost_line(), term.last_column());
+        self.matches = RegexIter::new(start, end, Direction::Right, term, &mut regex)
+            .take(MAX_SEARCH_MATCHES)
+            .collect();
+
+        self.focused = if self.matches.is_empty() {
+            None
+        } else if query_changed {
+            // Jump to the match closest to the bottom of the viewport, like a browser
+            // starting from the current position.
+            let viewport_bottom =
+                Line(term.screen_lines() as i32 - 1 - term.grid().display_offset() as i32);
+            Some(
+                self.matches
+                    .iter()
+                    .rposition(|m| m.start().line <= viewport_bottom)
+                    .unwrap_or(0),
+            )
+        } else {
+            // Keep the focus on the same match while new output arrives.
+            previous
+                .and_then(|p| self.matches.iter().position(|m| *m.start() == p))
+                .or(Some(self.matches.len() - 1))
+        };
+    }
+
+    /// Move the focus to the next match, wrapping around at the end.
+    pub fn focus_next(&mut self) -> Option<&Match> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let next = self.focused.map_or(0, |i| (i + 1) % self.matches.len());
+        self.focused = Some(next);
+        self.matches.get(next)
+    }
+
+    /// Move the focus to the previous match, wrapping around at the start.
+    pub fn focus_previous(&mut self) -> Option<&Match> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let len = self.matches.len();
+        let previous = self.focused.map_or(len - 1, |i| (i + len - 1) % len);
+        self.focused = Some(previous);
+        self.matches.get(previous)
+    }
+
+    fn pattern(&self) -> String {
+        search_pattern(&self.query, &self.options)
+    }
+}
+
+/// Regex of `query` searched with `options`.
+pub(crate) fn search_pattern(query: &str, options: &SearchOptions) -> String {
+    let query = if options.regex {
+        query.to_string()
+    } else {
+        escape(query)
+    };
+    // Inline flags override the smart-case default of `RegexSearch`.
+    if options.case_sensitive {
+        format!("(?-i){query}")
+    } else {
+        format!("(?i){query}")
+    }
+}
+
+/// Escape all regex meta characters, so the query is matched literally.
+fn escape(query: &str) -> String {
+    let mut escaped = String::with_capacity(query.len());
+    for c in query.chars() {
+        if matches!(
+            c,
+            '\\' | '.'
+                | '+'
+                | '*'
+                | '?'
+                | '('
+                | ')'
+                | '|'
+                | '['
+                | ']'
+                | '{'
+                | '}'
+                | '^'
+                | '$'
+                | '#'
+                | '&'
+                | '-'
+                | '~'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, SearchOptions, TerminalSearch};
+    use alacritty_terminal::event::VoidListener;
+    use alacritty_terminal::term::test::TermSize;
+    use alacritty_terminal::term::{Config, Term};
+    use alacritty_terminal::vte::ansi;
+
+    #[test]
+    fn escape_meta_characters() {
+        assert_eq!(escape("a.b*c"), r"a\.b\*c");
+        assert_eq!(escape("[x](y)"), r"\[x\]\(y\)");
+        assert_eq!(escape("plain"), "plain");
+    }
+
+    #[test]
+    fn pattern_respects_options() {
+        let mut search = TerminalSearch {
+            query: "a.b".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(search.pattern(), r"(?i)a\.b");
+
+        search.options = SearchOptions {
+            case_sensitive: true,
+            regex: true,
+        };
+        assert_eq!(search.pattern(), "(?-i)a.b");
+    }
+
+    #[test]
+    fn start_keeps_the_last_query() {
+        let mut search = TerminalSearch::default();
+        search.start(Some("error".to_string()));
+        search.close();
+        search.start(None);
+        assert!(search.is_highlighting());
+        assert_eq!(search.query, "error");
+        assert!(search.take_focus_request());
+    }
+
+    #[test]
+    fn output_invalidates_matches_once_the_history_is_full() {
+        let config = Config {
+            scrolling_history: 10,
+            ..Default::default()
+        };
+        let mut term = Term::new(config, &TermSize::new(20, 3), VoidListener);
+        let mut parser: ansi::Processor = ansi::Processor::new();
+        parser.advance(&mut term, "output\r\n".repeat(30).as_bytes());
+
+        let mut search = TerminalSearch::default();
+        search.start(Some("error".to_string()));
+        search.update(&term);
+        assert_eq!(search.match_count(), 0);
+
+        // the history size and the cursor are the same as before
+        parser.advance(&mut term, b"error\r\n");
+        search.update(&term);
+        assert_eq!(search.match_count(), 1);
+    }
+}
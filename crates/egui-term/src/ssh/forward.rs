@@ -0,0 +1,281 @@
+//! Ports forwarded through the ssh session of a terminal, like the `-L`
+//! option of ssh. The ssh backend opens neither direct-tcpip nor tcpip-forward
+//! channels: a local forward runs a relay command on the host for each
+//! connection, and the `-R` and `-D` forwards are refused.
+
+use crate::errors::TermError;
+use parking_lot::Mutex;
+use std::fmt;
+use std::io::{self, ErrorKind};
+use std::net::{Ipv4Addr, Shutdown, TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error};
+use wezterm_ssh::Session;
+
+/// How often the listeners check whether their terminal was closed.
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Port forwarded through the session, written like the options of ssh, e.g.
+/// `L 8080:localhost:80`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Forward {
+    /// Connections to the local `bind_port` reach `host:port` from the host.
+    Local {
+        bind_port: u16,
+        host: String,
+        port: u16,
+    },
+}
+
+impl Forward {
+    pub fn bind_port(&self) -> u16 {
+        match self {
+            Forward::Local { bind_port, .. } => *bind_port,
+        }
+    }
+}
+
+impl fmt::Display for Forward {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Forward::Local {
+                bind_port,
+                host,
+                port,
+            } if host.contains(':') => write!(f, "L {bind_port}:[{host}]:{port}"),
+            Forward::Local {
+                bind_port,
+                host,
+                port,
+            } => write!(f, "L {bind_port}:{host}:{port}"),
+        }
+    }
+}
+
+impl FromStr for Forward {
+    type Err = TermError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let invalid = || TermError::InvalidForward(spec.trim().to_string());
+        let (kind, rest) = spec
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or_else(invalid)?;
+        let port = |text: &str| {
+            text.trim()
+                .parse()
+                .ok()
+                .filter(|port| *port > 0)
+                .ok_or_else(invalid)
+        };
+        // these need tcpip-forward and direct-tcpip channels
+        if kind.eq_ignore_ascii_case("R") || kind.eq_ignore_ascii_case("D") {
+            return Err(TermError::UnsupportedForward(spec.trim().to_string()));
+        }
+        let (bind_port, target) = rest.trim().split_once(':').ok_or_else(invalid)?;
+        let (host, target_port) = target.rsplit_once(':').ok_or_else(invalid)?;
+        // IPv6 addresses are in brackets, e.g. `[::1]`
+        let host = host
+            .strip_prefix('[')
+            .and_then(|host| host.strip_suffix(']'))
+            .unwrap_or(host);
+        if !is_host(host) {
+            return Err(invalid());
+        }
+        if !kind.eq_ignore_ascii_case("L") {
+            return Err(invalid());
+        }
+        Ok(Forward::Local {
+            bind_port: port(bind_port)?,
+            host: host.to_string(),
+            port: port(target_port)?,
+        })
+    }
+}
+
+/// Whether `host` is a name or an address, safe to pass to the shell of the
+/// host.
+fn is_host(host: &str) -> bool {
+    !host.is_empty()
+        && !host.starts_with('-')
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | ':'))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TunnelState {
+    Listening,
+    /// The port couldn't be listened on, or the listener stopped.
+    Failed(String),
+}
+
+/// Forward of a terminal and how it goes, e.g. for the tooltip of its tab.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TunnelStatus {
+    pub forward: Forward,
+    pub state: TunnelState,
+}
+
+/// Forwards of a ssh terminal, they stop once it is dropped along with its
+/// pty.
+#[derive(Debug)]
+pub struct Tunnels {
+    status: Arc<Mutex<Vec<TunnelStatus>>>,
+    running: Arc<AtomicBool>,
+}
+
+impl Drop for Tunnels {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+impl Tunnels {
+    /// Listen on the local ports of `forwards`, each connection is relayed
+    /// through a channel of `session`.
+    pub(crate) fn open(session: &Session, forwards: &[Forward]) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let status = Arc::new(Mutex::new(vec![]));
+        for forward in forwards {
+            let index = status.lock().len();
+            let state = match bind(forward) {
+                Ok(listener) => {
+                    let session = session.clone();
+                    let forward = forward.clone();
+                    let running = running.clone();
+                    let status = status.clone();
+                    std::thread::Builder::new()
+                        .name(format!("tunnel_{}", forward.bind_port()))
+                        .spawn(move || {
+                            if let Err(err) = accept(listener, &session, &forward, &running) {
+                                error!("stopped forwarding {forward}: {err}");
+                                status.lock()[index].state = TunnelState::Failed(err.to_string());
+                            }
+                        })
+                        .map_or_else(
+                            |err| TunnelState::Failed(err.to_string()),
+                            |_| TunnelState::Listening,
+                        )
+                }
+                Err(err) => {
+                    error!("failed to forward {forward}: {err}");
+                    TunnelState::Failed(err.to_string())
+                }
+            };
+            status.lock().push(TunnelStatus {
+                forward: forward.clone(),
+                state,
+            });
+        }
+        Self { status, running }
+    }
+
+    /// Status of the forwards, updated while they run.
+    pub(crate) fn status(&self) -> Arc<Mutex<Vec<TunnelStatus>>> {
+        self.status.clone()
+    }
+}
+
+fn bind(forward: &Forward) -> io::Result<TcpListener> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, forward.bind_port()))?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+fn accept(
+    listener: TcpListener,
+    session: &Session,
+    forward: &Forward,
+    running: &AtomicBool,
+) -> io::Result<()> {
+    while running.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let session = session.clone();
+                let forward = forward.clone();
+                std::thread::Builder::new()
+                    .name(format!("tunnel_{}_connection", forward.bind_port()))
+                    .spawn(move || {
+                        if let Err(err) = relay(stream, &session, &forward) {
+                            debug!("forwarded connection of {forward} failed: {err}");
+                        }
+                    })?;
+            }
+            Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                std::thread::sleep(ACCEPT_INTERVAL);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+/// Copy the connection to the destination of the forward and back, through
+/// a command run on the host.
+fn relay(mut stream: TcpStream, session: &Session, forward: &Forward) -> io::Result<()> {
+    // inherited from the listener on some platforms
+    stream.set_nonblocking(false)?;
+    let Forward::Local { host, port, .. } = forward;
+    let exec = smol::block_on(session.exec(&relay_command(host, *port), None))
+        .map_err(io::Error::other)?;
+    let (mut stdin, mut stdout) = (exec.stdin, exec.stdout);
+    let mut upload = stream.try_clone()?;
+    let upstream = std::thread::spawn(move || {
+        // the command gets EOF once its input is dropped
+        let _ = io::copy(&mut upload, &mut stdin);
+    });
+    let copied = io::copy(&mut stdout, &mut stream);
+    let _ = stream.shutdown(Shutdown::Both);
+    let _ = upstream.join();
+    copied.map(|_| ())
+}
+
+/// Command connecting its input and output to `host:port`, with nc or, when
+/// the host has none, with the `/dev/tcp` of bash. The backend opens exec
+/// channels only, not direct-tcpip ones, so a host with neither can't be
+/// forwarded through. A single connection is made, a failed nc isn't retried
+/// with bash.
+fn relay_command(host: &str, port: u16) -> String {
+    format!(
+        "if command -v nc >/dev/null 2>&1; then exec nc {host} {port}; else exec bash -c \
+         'exec 3<>/dev/tcp/{host}/{port} || exit 1; cat <&3 & exec cat >&3'; fi"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_forwards() {
+        let local = Forward::Local {
+            bind_port: 8080,
+            host: "localhost".to_string(),
+            port: 80,
+        };
+        assert_eq!("L 8080:localhost:80".parse::<Forward>().unwrap(), local);
+        assert_eq!(local.to_string(), "L 8080:localhost:80");
+        let ipv6: Forward = " l 9000:[::1]:3000 ".parse().unwrap();
+        assert_eq!(ipv6.to_string(), "L 9000:[::1]:3000");
+        for spec in ["R 9000:localhost:3000", "D 1080"] {
+            assert!(matches!(
+                spec.parse::<Forward>(),
+                Err(TermError::UnsupportedForward(_))
+            ));
+        }
+
+        for spec in [
+            "L 8080",
+            "X 1:a:2",
+            "L 0:a:2",
+            "L 1:a b:2",
+            "L 1:$(reboot):2",
+        ] {
+            assert!(spec.parse::<Forward>().is_err(), "{spec}");
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Latency of an open ssh session, measured on the session itself rather than
+//! with new connections to the host, which sshd would log and count.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::debug;
+use wezterm_ssh::Session;
+
+/// The round trip is measured this often.
+const LATENCY_INTERVAL: Duration = Duration::from_secs(30);
+/// The thread checks this often whether the latency is still wanted, so that
+/// it doesn't keep the session open for long once its tab is closed.
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+/// Stored until the first round trip, or while they fail.
+const UNKNOWN: u64 = u64::MAX;
+
+/// Round trip of a command doing nothing on a ssh session, measured in the
+/// background until every clone is dropped.
+#[derive(Debug, Clone)]
+pub struct SessionLatency {
+    /// In microseconds.
+    micros: Arc<AtomicU64>,
+}
+
+impl Default for SessionLatency {
+    fn default() -> Self {
+        Self {
+            micros: Arc::new(AtomicU64::new(UNKNOWN)),
+        }
+    }
+}
+
+impl SessionLatency {
+    /// Measure the round trips of `session` from now on.
+    pub fn watch(&self, session: &Session) {
+        let weak = Arc::downgrade(&self.micros);
+        let session = session.clone();
+        let spawned = std::thread::Builder::new()
+            .name("ssh_latency".to_string())
+            .spawn(move || {
+                while let Some(micros) = weak.upgrade() {
+                    let rtt = match smol::block_on(round_trip(&session)) {
+                        Ok(rtt) => rtt.as_micros() as u64,
+                        Err(err) => {
+                            debug!("failed to measure the latency: {err}");
+                            UNKNOWN
+                        }
+                    };
+                    micros.store(rtt, Ordering::Relaxed);
+                    drop(micros);
+
+                    let measured_at = Instant::now();
+                    while measured_at.elapsed() < LATENCY_INTERVAL {
+                        if weak.strong_count() == 0 {
+                            return;
+                        }
+                        std::thread::sleep(CHECK_INTERVAL);
+                    }
+                }
+            });
+        if let Err(err) = spawned {
+            debug!("failed to spawn the latency thread: {err}");
+        }
+    }
+
+    /// Last round trip, `None` until it is measured or while the session
+    /// doesn't answer.
+    pub fn latency(&self) -> Option<Duration> {
+        match self.micros.load(Ordering::Relaxed) {
+            UNKNOWN => None,
+            micros => Some(Duration::from_micros(micros)),
+        }
+    }
+}
+
+/// Time to run `true` on the host, from opening its channel until it exits.
+async fn round_trip(session: &Session) -> anyhow::Result<Duration> {
+    let start = Instant::now();
+    let mut exec = session.exec("true", None).await?;
+    exec.child.async_wait().await?;
+    Ok(start.elapsed())
+}
@@ -1,15 +1,18 @@
 use crate::errors::TermError;
 use crate::errors::TermError::HostVerification;
+use crate::ssh::forward::Tunnels;
 use alacritty_terminal::event::{OnResize, WindowSize};
 use alacritty_terminal::tty::{ChildEvent, EventedPty, EventedReadWrite};
 use anyhow::Context;
 use polling::{Event, PollMode, Poller};
 use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
 use std::sync::Arc;
 use tracing::{error, trace};
 use wezterm_ssh::{
-    Child, ChildKiller, Config, FileDescriptor, MasterPty, PtySize, Session, SessionEvent,
-    SshChildProcess, SshPty,
+    Child, ChildKiller, Config, ConfigMap, FileDescriptor, MasterPty, PtySize, Session,
+    SessionEvent, SshChildProcess, SshPty,
 };
 
 #[cfg(unix)]
@@ -38,10 +41,25 @@ const PTY_READ_WRITE_TOKEN: usize = 0;
 const PTY_READ_WRITE_TOKEN: usize = 2;
 const PTY_CHILD_EVENT_TOKEN: usize = 1;
 
+/// `LANG` of the shell when the locales of the host can't be listed.
+const FALLBACK_LANG: &str = "en_US.UTF-8";
+/// Languages picked first among the UTF-8 locales of the host, after the one
+/// of the local user.
+const PREFERRED_LANGUAGES: [&str; 2] = ["en_US", "C"];
+
+mod forward;
+mod latency;
+
+pub use forward::{Forward, TunnelState, TunnelStatus};
+pub use latency::SessionLatency;
+
 #[derive(Debug)]
 pub struct Pty {
     pub pty: SshPty,
     pub child: SshChildProcess,
+    /// Forwards of the session, stopped with the pty.
+    pub tunnels: Tunnels,
+    pub latency: SessionLatency,
     #[cfg(unix)]
     pub signals: UnixStream,
     #[cfg(unix)]
@@ -194,70 +212,74 @@ impl OnResize for Pty {
     }
 }
 
-impl Pty {
-    pub fn new(opts: SshOptions) -> Result<Self, TermError> {
-        let mut config = Config::new();
+/// Steps of a ssh connection, reported while it is established.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConnectStage {
+    /// Resolving the host and opening the transport.
+    #[default]
+    Resolving,
+    Authenticating,
+    /// Authenticating with a security key, which waits for a touch.
+    TouchSecurityKey,
+    OpeningPty,
+}
 
-        let (mut auth_data, config) = match opts.auth {
-            Authentication::Password(user, password) => {
-                let port = opts.port.unwrap_or(22);
-                let mut config = config.for_host(opts.host);
+/// Follows a ssh connection while it is established, on the connecting thread.
+pub trait ConnectObserver {
+    fn stage(&self, _stage: ConnectStage) {}
 
-                config.insert("port".to_string(), port.to_string());
-                config.insert("user".to_string(), user);
-                (Some(password), config)
-            }
-            Authentication::Config => {
-                config.add_default_config_files();
-                let config = config.for_host(opts.host);
+    /// The host key of a pinned session was trusted, for the first time or
+    /// after it changed: its fingerprint is to be saved with the session.
+    fn host_key_pinned(&self, _fingerprint: &str) {}
 
-                (None, config)
-            }
-        };
-        smol::block_on(async move {
-            let (session, events) = Session::connect(config)?;
+    /// The host key of a pinned session is seen for the first time, or isn't
+    /// the expected one. Blocks until the user decides whether to trust it,
+    /// the connection is refused if not.
+    fn trust_host_key(&self, _change: &HostKeyChange) -> bool {
+        false
+    }
 
-            while let Ok(event) = events.recv().await {
-                match event {
-                    SessionEvent::Banner(banner) => {
-                        if let Some(banner) = banner {
-                            trace!("{}", banner);
-                        }
-                    }
-                    SessionEvent::HostVerify(verify) => {
-                        verify.answer(true).await.context("send verify response")?;
-                    }
-                    SessionEvent::Authenticate(auth) => {
-                        let mut answers = vec![];
-                        for prompt in auth.prompts.iter() {
-                            if prompt.prompt.contains("Password") {
-                                let answer = auth_data.take();
-                                answers.push(answer.unwrap_or_default());
-                            }
-                        }
+    /// The host asks for answers the session doesn't have, e.g. a verification
+    /// code. Blocks until the user answers, `None` cancels the connection.
+    fn keyboard_interactive(&self, interactive: &KeyboardInteractive) -> Option<Vec<String>> {
+        Some(interactive.answers())
+    }
+}
 
-                        auth.answer(answers).await?;
-                    }
-                    SessionEvent::HostVerificationFailed(failed) => {
-                        error!("host verification failed: {failed}");
-                        return Err(HostVerification(failed));
-                    }
-                    SessionEvent::Error(err) => {
-                        error!("ssh login error: {err}");
-                        return Err(TermError::Box(err.into()));
-                    }
-                    SessionEvent::Authenticated => break,
-                }
-            }
+impl ConnectObserver for () {}
+
+impl Pty {
+    pub fn new(opts: SshOptions, working_directory: Option<&str>) -> Result<Self, TermError> {
+        Self::connect(opts, working_directory, &())
+    }
 
-            // FIXME: set in settings
+    /// Like [`Pty::new`], telling `observer` how the connection goes.
+    pub fn connect(
+        opts: SshOptions,
+        working_directory: Option<&str>,
+        observer: &dyn ConnectObserver,
+    ) -> Result<Self, TermError> {
+        let locale = opts.locale.clone();
+        let forwards = opts.forwards.clone();
+        let session = connect_with_observer(opts, observer)?;
+        observer.stage(ConnectStage::OpeningPty);
+        let tunnels = Tunnels::open(&session, &forwards);
+        let latency = SessionLatency::default();
+        latency.watch(&session);
+        smol::block_on(async move {
             let mut env = HashMap::new();
-            env.insert("LANG".to_string(), "en_US.UTF-8".to_string());
+            if let Some(lang) = shell_lang(&session, locale).await {
+                env.insert("LANG".to_string(), lang);
+            }
             env.insert("LC_COLLATE".to_string(), "C".to_string());
 
-            let (pty, child) = session
+            let (mut pty, child) = session
                 .request_pty("xterm-256color", PtySize::default(), None, Some(env))
                 .await?;
+            if let Some(dir) = working_directory {
+                // Typed ahead, the shell reads it once started.
+                pty.writer.write_all(cd_command(dir).as_bytes())?;
+            }
 
             #[cfg(unix)]
             {
@@ -274,6 +296,8 @@ impl Pty {
                 Ok(Pty {
                     pty,
                     child,
+                    tunnels,
+                    latency,
                     signals,
                     sig_id,
                 })
@@ -286,6 +310,8 @@ impl Pty {
                 Ok(Pty {
                     pty,
                     child,
+                    tunnels,
+                    latency,
                     signals,
                 })
             }
@@ -293,6 +319,410 @@ impl Pty {
     }
 }
 
+/// `LANG` of the shell, `None` leaves the default of the host, which has no
+/// UTF-8 locale.
+async fn shell_lang(session: &Session, locale: RemoteLocale) -> Option<String> {
+    let locales = match locale {
+        RemoteLocale::Fixed(lang) => return Some(lang),
+        RemoteLocale::Auto => list_locales(session).await,
+    };
+    match locales {
+        Ok(locales) => {
+            let local = std::env::var("LANG").ok();
+            best_locale(&locales, local.as_deref()).map(str::to_string)
+        }
+        Err(err) => {
+            error!("failed to list the locales of the host: {err}");
+            Some(FALLBACK_LANG.to_string())
+        }
+    }
+}
+
+/// Output of `locale -a` on the host, one locale by line.
+async fn list_locales(session: &Session) -> anyhow::Result<String> {
+    let mut exec = session.exec("locale -a", None).await?;
+    let mut locales = String::new();
+    exec.stdout.read_to_string(&mut locales)?;
+    let status = exec.child.async_wait().await?;
+    anyhow::ensure!(
+        status.success(),
+        "locale -a exited with {}",
+        status.exit_code()
+    );
+    Ok(locales)
+}
+
+/// Best of the UTF-8 `locales` of the host: the one of the `local` user, e.g.
+/// `de_DE.UTF-8`, then the preferred ones, then any.
+fn best_locale<'a>(locales: &'a str, local: Option<&str>) -> Option<&'a str> {
+    let utf8: Vec<&str> = locales
+        .lines()
+        .map(str::trim)
+        .filter(|locale| is_utf8(locale))
+        .collect();
+    let language = |locale: &str| {
+        locale
+            .split(['.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    };
+    local
+        .map(language)
+        .into_iter()
+        .chain(PREFERRED_LANGUAGES.map(String::from))
+        .find_map(|preferred| {
+            utf8.iter()
+                .find(|locale| language(locale) == preferred)
+                .copied()
+        })
+        .or(utf8.first().copied())
+}
+
+/// Whether the codeset of `locale` is UTF-8, spelled `UTF-8` or `utf8`.
+fn is_utf8(locale: &str) -> bool {
+    locale.split_once('.').is_some_and(|(_, codeset)| {
+        let codeset = codeset.split('@').next().unwrap_or_default();
+        codeset.eq_ignore_ascii_case("utf-8") || codeset.eq_ignore_ascii_case("utf8")
+    })
+}
+
+/// Shell command changing the working directory to `dir`. The leading space
+/// keeps the command out of the history of most shells.
+pub fn cd_command(dir: &str) -> String {
+    let dir = dir.replace('\'', r"'\''");
+    format!(" cd -- '{dir}'\n")
+}
+
+/// Connect to the remote host and authenticate, blocking until the session is ready.
+///
+/// The returned session can be used to open channels other than the shell, e.g. sftp.
+///
+/// The key of a pinned host must be the expected one, a new or changed key
+/// can't be trusted from here, see [`ConnectObserver::trust_host_key`].
+pub fn connect(opts: SshOptions) -> Result<Session, TermError> {
+    connect_with_observer(opts, &())
+}
+
+/// Like [`connect`], telling `observer` how the connection goes, e.g. to ask
+/// the user to trust the host key.
+pub fn connect_with_observer(
+    opts: SshOptions,
+    observer: &dyn ConnectObserver,
+) -> Result<Session, TermError> {
+    observer.stage(ConnectStage::Resolving);
+    let mut config = Config::new();
+    let host = opts.host.clone();
+    let mut security_key = None;
+    // the prompts the saved secret answers
+    let mut is_secret_prompt: fn(&str) -> bool = is_password_prompt;
+    let uses_ssh_config = opts.auth == Authentication::Config;
+
+    let (mut auth_data, mut config) = match opts.auth {
+        Authentication::Password(user, password) => {
+            let port = opts.port.unwrap_or(22);
+            let mut config = config.for_host(opts.host);
+
+            config.insert("port".to_string(), port.to_string());
+            config.insert("user".to_string(), user);
+            // asked like the other prompts when empty
+            (
+                Some(password).filter(|password| !password.is_empty()),
+                config,
+            )
+        }
+        Authentication::PublicKey {
+            user,
+            path,
+            passphrase,
+        } => {
+            let port = opts.port.unwrap_or(22);
+            let mut config = config.for_host(opts.host);
+
+            config.insert("port".to_string(), port.to_string());
+            config.insert("user".to_string(), user);
+            config.insert("identityfile".to_string(), expand_home(&path));
+            config.insert("identitiesonly".to_string(), "yes".to_string());
+            security_key = is_security_key(&path).then(|| path.clone());
+            is_secret_prompt = is_passphrase_prompt;
+            (
+                Some(passphrase).filter(|passphrase| !passphrase.is_empty()),
+                config,
+            )
+        }
+        Authentication::Config => {
+            config.add_default_config_files();
+            let mut config = config.for_host(opts.host);
+            // the default port is the one of the ssh config, another one was
+            // asked for, e.g. by an imported session
+            if let Some(port) = opts.port.filter(|port| *port != 22) {
+                config.insert("port".to_string(), port.to_string());
+            }
+            security_key = config
+                .get("identityfile")
+                .and_then(|files| security_key_identity(files));
+
+            (None, config)
+        }
+    };
+    // unknown to ssh, the host key is verified on every connection. The known
+    // hosts of the ssh config are kept for the sessions using it, the pin is
+    // only checked for the hosts they don't know.
+    if matches!(opts.host_key, HostKeyCheck::Pinned(_)) && !uses_ssh_config {
+        config.insert("userknownhostsfile".to_string(), NULL_DEVICE.to_string());
+    }
+    opts.transport.apply(&mut config);
+    smol::block_on(async move {
+        let (session, events) = Session::connect(config)?;
+
+        while let Ok(event) = events.recv().await {
+            if let SessionEvent::Banner(_)
+            | SessionEvent::HostVerify(_)
+            | SessionEvent::Authenticate(_) = event
+            {
+                // the host answered
+                let stage = match security_key {
+                    Some(_) => ConnectStage::TouchSecurityKey,
+                    None => ConnectStage::Authenticating,
+                };
+                observer.stage(stage);
+            }
+            match event {
+                SessionEvent::Banner(banner) => {
+                    if let Some(banner) = banner {
+                        trace!("{}", banner);
+                    }
+                }
+                SessionEvent::HostVerify(verify) => {
+                    let trusted = match &opts.host_key {
+                        HostKeyCheck::KnownHosts => true,
+                        HostKeyCheck::Pinned(pinned) => {
+                            let found = fingerprint(&verify.message).ok_or_else(|| {
+                                anyhow::anyhow!("no host key fingerprint in: {}", verify.message)
+                            })?;
+                            if let Some(change) = check_pinned(&host, pinned.as_deref(), found) {
+                                // new or changed, the user compares it to the
+                                // one of the host
+                                if !observer.trust_host_key(&change) {
+                                    let _ = verify.answer(false).await;
+                                    return Err(TermError::HostKeyRefused(change));
+                                }
+                                observer.host_key_pinned(&change.found);
+                            }
+                            true
+                        }
+                    };
+                    verify
+                        .answer(trusted)
+                        .await
+                        .context("send verify response")?;
+                }
+                SessionEvent::Authenticate(auth) => {
+                    let interactive = KeyboardInteractive {
+                        host: host.clone(),
+                        username: auth.username.clone(),
+                        instructions: auth.instructions.clone(),
+                        prompts: auth
+                            .prompts
+                            .iter()
+                            .map(|prompt| InteractivePrompt {
+                                label: prompt.prompt.clone(),
+                                echo: prompt.echo,
+                                // the saved secret is only tried once
+                                answer: is_secret_prompt(&prompt.prompt)
+                                    .then(|| auth_data.take())
+                                    .flatten(),
+                            })
+                            .collect(),
+                    };
+                    let answers = if interactive.is_answered() {
+                        interactive.answers()
+                    } else {
+                        observer
+                            .keyboard_interactive(&interactive)
+                            .ok_or(TermError::AuthenticationCancelled)?
+                    };
+
+                    auth.answer(answers).await?;
+                }
+                SessionEvent::HostVerificationFailed(failed) => {
+                    error!("host verification failed: {failed}");
+                    return Err(HostVerification(failed));
+                }
+                SessionEvent::Error(err) => {
+                    error!("ssh login error: {err}");
+                    if let Some(identity) = security_key.take() {
+                        return Err(TermError::SecurityKey { identity, err });
+                    }
+                    return Err(TermError::Box(err.into()));
+                }
+                SessionEvent::Authenticated => break,
+            }
+        }
+
+        Ok(session)
+    })
+}
+
+/// First of the identity files, as listed by the ssh config, that holds a
+/// FIDO2 security key, e.g. `sk-ssh-ed25519@openssh.com`.
+fn security_key_identity(identity_files: &str) -> Option<String> {
+    identity_files
+        .split_whitespace()
+        .find(|file| is_security_key(file))
+        .map(String::from)
+}
+
+/// Whether the identity file at `path` holds a FIDO2 security key.
+fn is_security_key(path: &str) -> bool {
+    let path = expand_home(path);
+    // the private key is only readable with its passphrase, its public key
+    // tells the type
+    match std::fs::read_to_string(format!("{path}.pub")) {
+        Ok(public_key) => is_security_key_type(&public_key),
+        // named by `ssh-keygen -t ed25519-sk` by default
+        Err(_) => Path::new(&path).exists() && path.ends_with("_sk"),
+    }
+}
+
+fn is_security_key_type(public_key: &str) -> bool {
+    public_key
+        .split_whitespace()
+        .next()
+        .is_some_and(|key_type| key_type.starts_with("sk-") && key_type.ends_with("@openssh.com"))
+}
+
+fn expand_home(path: &str) -> String {
+    let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE"));
+    match (path.strip_prefix("~/"), home) {
+        (Some(rest), Ok(home)) => format!("{home}/{rest}"),
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(unix)]
+const NULL_DEVICE: &str = "/dev/null";
+#[cfg(windows)]
+const NULL_DEVICE: &str = "NUL";
+
+/// Host key `found` for a pinned session, which the user has to trust unless
+/// it is the `pinned` one.
+fn check_pinned(host: &str, pinned: Option<&str>, found: &str) -> Option<HostKeyChange> {
+    (pinned != Some(found)).then(|| HostKeyChange {
+        host: host.to_string(),
+        expected: pinned.map(str::to_string),
+        found: found.to_string(),
+    })
+}
+
+/// Fingerprint of the host key of a verification event. wezterm-ssh only
+/// tells it in the message of the event, `<key type> Fingerprint: SHA256:<hash>.`
+/// on a line of its own, anything else isn't taken for a fingerprint and the
+/// connection fails rather than trust an unknown key.
+fn fingerprint(message: &str) -> Option<&str> {
+    message.lines().find_map(|line| {
+        let (key_type, rest) = line.trim().split_once(" Fingerprint: ")?;
+        let fingerprint = rest.strip_suffix('.').unwrap_or(rest);
+        let hash = fingerprint.strip_prefix("SHA256:")?;
+        let is_hash = !hash.is_empty()
+            && hash
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '='));
+        let is_key_type = !key_type.is_empty() && !key_type.contains(char::is_whitespace);
+        (is_hash && is_key_type).then_some(fingerprint)
+    })
+}
+
+/// Prompts of a keyboard-interactive authentication round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyboardInteractive {
+    pub host: String,
+    pub username: String,
+    pub instructions: String,
+    pub prompts: Vec<InteractivePrompt>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InteractivePrompt {
+    pub label: String,
+    /// Whether the answer can be shown while typed.
+    pub echo: bool,
+    /// Answered from the session, e.g. with its password.
+    pub answer: Option<String>,
+}
+
+impl KeyboardInteractive {
+    fn is_answered(&self) -> bool {
+        self.prompts.iter().all(|prompt| prompt.answer.is_some())
+    }
+
+    /// Answers of the session, empty for the prompts it can't answer.
+    pub fn answers(&self) -> Vec<String> {
+        self.prompts
+            .iter()
+            .map(|prompt| prompt.answer.clone().unwrap_or_default())
+            .collect()
+    }
+}
+
+fn is_password_prompt(label: &str) -> bool {
+    label.to_lowercase().contains("password")
+}
+
+/// Prompt for the passphrase of a private key, e.g. `Passphrase for key
+/// "/home/user/.ssh/id_ed25519":`.
+fn is_passphrase_prompt(label: &str) -> bool {
+    label.to_lowercase().contains("passphrase")
+}
+
+/// Host key of a pinned session which isn't the one it expects, or the first
+/// one it sees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostKeyChange {
+    pub host: String,
+    /// Fingerprint saved with the session, `None` on its first connection.
+    pub expected: Option<String>,
+    /// Fingerprint of the key the host presented.
+    pub found: String,
+}
+
+/// How the key of the host is verified.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum HostKeyCheck {
+    /// Against the known hosts files of ssh.
+    #[default]
+    KnownHosts,
+    /// Against the fingerprint saved with the session, `None` until the first
+    /// connection.
+    Pinned(Option<String>),
+}
+
+/// Preferences of the ssh transport, the defaults of ssh when unset.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransportOptions {
+    pub compression: Option<bool>,
+    /// Comma separated ciphers, by preference, e.g. `aes128-ctr,aes256-ctr`.
+    pub ciphers: Option<String>,
+    /// Comma separated key exchange algorithms, by preference.
+    pub kex_algorithms: Option<String>,
+}
+
+impl TransportOptions {
+    /// Override the ssh config, the backend ignores the options it doesn't support.
+    fn apply(&self, config: &mut ConfigMap) {
+        if let Some(compression) = self.compression {
+            let value = if compression { "yes" } else { "no" };
+            config.insert("compression".to_string(), value.to_string());
+        }
+        if let Some(ciphers) = &self.ciphers {
+            config.insert("ciphers".to_string(), ciphers.clone());
+        }
+        if let Some(kex_algorithms) = &self.kex_algorithms {
+            config.insert("kexalgorithms".to_string(), kex_algorithms.clone());
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct SshOptions {
     pub group: String,
@@ -300,10 +730,174 @@ pub struct SshOptions {
     pub host: String,
     pub port: Option<u16>,
     pub auth: Authentication,
+    pub host_key: HostKeyCheck,
+    pub transport: TransportOptions,
+    pub locale: RemoteLocale,
+    /// Ports forwarded once authenticated.
+    pub forwards: Vec<Forward>,
+}
+
+/// `LANG` of the remote shell.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RemoteLocale {
+    /// The best UTF-8 locale of the host, listed with `locale -a` once
+    /// connected.
+    #[default]
+    Auto,
+    /// This one, e.g. `de_DE.UTF-8`.
+    Fixed(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Authentication {
     Password(String, String),
+    /// Only the key of the identity file is offered, its passphrase is asked
+    /// like the other prompts when empty.
+    PublicKey {
+        user: String,
+        path: String,
+        passphrase: String,
+    },
     Config,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_pinned_fingerprint() {
+        let message = "SSH host example.com:22 is not yet trusted.\n\
+                       ssh-ed25519 Fingerprint: SHA256:q1w2e3r4.\n\
+                       Trust and continue connecting?";
+        assert_eq!(fingerprint(message), Some("SHA256:q1w2e3r4"));
+        assert_eq!(fingerprint("Fingerprint: "), None);
+        assert_eq!(fingerprint("ssh-rsa Fingerprint: MD5:ab:cd."), None);
+        assert_eq!(fingerprint("a host Fingerprint: SHA256:q1w2e3r4."), None);
+
+        let check = |pinned| check_pinned("example.com", pinned, "SHA256:q1w2e3r4");
+        assert_eq!(check(Some("SHA256:q1w2e3r4")), None);
+        assert_eq!(
+            check(None),
+            Some(HostKeyChange {
+                host: "example.com".to_string(),
+                expected: None,
+                found: "SHA256:q1w2e3r4".to_string(),
+            })
+        );
+        assert_eq!(
+            check(Some("SHA256:old")).and_then(|change| change.expected),
+            Some("SHA256:old".to_string())
+        );
+    }
+
+    #[test]
+    fn answer_prompts_from_the_session() {
+        let prompt = |label: &str, answer: Option<&str>| InteractivePrompt {
+            label: label.to_string(),
+            echo: false,
+            answer: answer.map(str::to_string),
+        };
+        let mut interactive = KeyboardInteractive {
+            host: "example.com".to_string(),
+            username: "root".to_string(),
+            instructions: String::new(),
+            prompts: vec![prompt("Password: ", Some("secret"))],
+        };
+        assert!(interactive.is_answered());
+        interactive
+            .prompts
+            .push(prompt("Verification code: ", None));
+        assert!(!interactive.is_answered());
+        assert_eq!(interactive.answers(), ["secret", ""]);
+
+        assert!(is_password_prompt("(root@example.com) password:"));
+        assert!(!is_password_prompt("Verification code:"));
+        assert!(is_passphrase_prompt(
+            "Enter passphrase for key '/root/.ssh/id_ed25519':"
+        ));
+        assert!(!is_passphrase_prompt("Password:"));
+    }
+
+    #[test]
+    fn pick_the_best_locale() {
+        let locales = "C\nC.utf8\nPOSIX\nde_DE.utf8\nen_GB.ISO-8859-1\nen_US.utf8\n";
+        assert_eq!(best_locale(locales, None), Some("en_US.utf8"));
+        assert_eq!(
+            best_locale(locales, Some("de_DE.UTF-8")),
+            Some("de_DE.utf8")
+        );
+        assert_eq!(
+            best_locale(locales, Some("fr_FR.UTF-8")),
+            Some("en_US.utf8")
+        );
+        assert_eq!(
+            best_locale("C\nC.UTF-8\nfr_FR.UTF-8\n", None),
+            Some("C.UTF-8")
+        );
+        assert_eq!(
+            best_locale("POSIX\nja_JP.UTF-8@cjk\n", None),
+            Some("ja_JP.UTF-8@cjk")
+        );
+        // left to the host
+        assert_eq!(best_locale("C\nPOSIX\nen_US.ISO-8859-1\n", None), None);
+    }
+
+    #[test]
+    fn transport_overrides_config() {
+        let mut config = ConfigMap::new();
+        config.insert("compression".to_string(), "yes".to_string());
+        config.insert("ciphers".to_string(), "aes256-ctr".to_string());
+        let transport = TransportOptions {
+            compression: Some(false),
+            kex_algorithms: Some("curve25519-sha256".to_string()),
+            ..Default::default()
+        };
+        transport.apply(&mut config);
+        assert_eq!(config["compression"], "no");
+        // left to the config
+        assert_eq!(config["ciphers"], "aes256-ctr");
+        assert_eq!(config["kexalgorithms"], "curve25519-sha256");
+    }
+
+    #[test]
+    fn detect_security_keys() {
+        assert!(is_security_key_type(
+            "sk-ssh-ed25519@openssh.com AAAAGnNrLXNzaC1lZDI1NTE5 user@host"
+        ));
+        assert!(is_security_key_type(
+            "sk-ecdsa-sha2-nistp256@openssh.com AAAAInNrLWVjZHNh"
+        ));
+        assert!(!is_security_key_type(
+            "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5 user@host"
+        ));
+        assert!(!is_security_key_type(""));
+
+        let dir = std::env::temp_dir().join(format!("egui_term_sk_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let plain = dir.join("id_ed25519");
+        let sk = dir.join("id_ed25519_sk");
+        std::fs::write(format!("{}.pub", plain.display()), "ssh-ed25519 AAAA").unwrap();
+        std::fs::write(&sk, "").unwrap();
+        let files = format!(
+            "{} {} {}/missing_sk",
+            plain.display(),
+            sk.display(),
+            dir.display()
+        );
+        assert_eq!(
+            security_key_identity(&files),
+            Some(sk.display().to_string())
+        );
+        assert_eq!(security_key_identity(&plain.display().to_string()), None);
+
+        // the path of a session isn't a list
+        let spaced = dir.join("my keys");
+        std::fs::create_dir_all(&spaced).unwrap();
+        let spaced_sk = spaced.join("id_ed25519_sk");
+        std::fs::write(&spaced_sk, "").unwrap();
+        assert!(is_security_key(&spaced_sk.display().to_string()));
+        assert!(!is_security_key(&plain.display().to_string()));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
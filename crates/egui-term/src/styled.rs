@@ -0,0 +1,397 @@
+//! Selected text along with its colors and attributes, as HTML with inline
+//! styles or as ANSI escape codes, for the snippets pasted into documents or
+//! chats to keep their highlighting. The whole history too, to print it or to
+//! save it as plain text.
+
+use crate::redact::{Redaction, REDACTED_CHAR};
+use crate::theme::TerminalTheme;
+use crate::Terminal;
+use alacritty_terminal::grid::{Dimensions, Grid};
+use alacritty_terminal::index::{Column, Line, Point};
+use alacritty_terminal::selection::SelectionRange;
+use alacritty_terminal::term::cell::{Cell, Flags};
+use alacritty_terminal::term::Term;
+use alacritty_terminal::vte::ansi::{Color, NamedColor};
+use egui::Color32;
+use std::fmt::Write;
+
+/// Attributes written along with the text, the others are left out.
+const STYLE_FLAGS: Flags = Flags::BOLD
+    .union(Flags::DIM)
+    .union(Flags::ITALIC)
+    .union(Flags::ALL_UNDERLINES)
+    .union(Flags::INVERSE)
+    .union(Flags::HIDDEN)
+    .union(Flags::STRIKEOUT);
+
+/// Cells in a row with the same style.
+#[derive(Debug, Clone, PartialEq)]
+struct StyledRun {
+    fg: Color,
+    bg: Color,
+    flags: Flags,
+    text: String,
+}
+
+impl StyledRun {
+    fn has_style(&self, fg: Color, bg: Color, flags: Flags) -> bool {
+        self.fg == fg && self.bg == bg && self.flags == flags
+    }
+}
+
+/// Copy of the history and of the screen of a terminal, to turn them into
+/// text without holding the terminal, see [`Terminal::history_snapshot`].
+pub struct HistorySnapshot(Grid<Cell>);
+
+impl HistorySnapshot {
+    /// Lines without their trailing blanks, the secrets masked when a
+    /// `redaction` is given.
+    pub fn text(&self, redaction: Option<&Redaction>) -> String {
+        history_text(&self.0, redaction)
+    }
+}
+
+/// Text of a line in the same color and weight, see
+/// [`Terminal::styled_history`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextRun {
+    pub text: String,
+    /// `None` in the default foreground color.
+    pub color: Option<Color32>,
+    pub bold: bool,
+}
+
+impl Terminal {
+    /// Lines of the history and of the screen, in the colors of `theme`, e.g.
+    /// to print them. The secrets are masked when a `redaction` is given.
+    pub fn styled_history(
+        &self,
+        theme: &TerminalTheme,
+        redaction: Option<&Redaction>,
+    ) -> Vec<Vec<TextRun>> {
+        let term = self.term.lock();
+        let start = Point::new(term.topmost_line(), Column(0));
+        let end = Point::new(term.bottommost_line(), term.last_column());
+        let range = SelectionRange::new(start, end, false);
+        text_runs(styled_lines(term.grid(), range, redaction), theme)
+    }
+
+    /// Rows of the screen as scrolled, one by row even when a line wraps, in
+    /// the colors of `theme`.
+    pub fn styled_screen(&self, theme: &TerminalTheme) -> Vec<Vec<TextRun>> {
+        let term = self.term.lock();
+        let top = Line(-(term.grid().display_offset() as i32));
+        let start = Point::new(top, Column(0));
+        let end = Point::new(top + (term.screen_lines() - 1), term.last_column());
+        // the rows of a block aren't joined
+        let range = SelectionRange::new(start, end, true);
+        text_runs(styled_lines(term.grid(), range, None), theme)
+    }
+
+    /// Copy of the history and of the screen, e.g. to save them to a file
+    /// on another thread. The terminal is only held while they are copied.
+    pub fn history_snapshot(&self) -> HistorySnapshot {
+        HistorySnapshot(self.term.lock().grid().clone())
+    }
+}
+
+fn text_runs(lines: Vec<Vec<StyledRun>>, theme: &TerminalTheme) -> Vec<Vec<TextRun>> {
+    lines
+        .into_iter()
+        .map(|runs| {
+            runs.into_iter()
+                .map(|run| TextRun {
+                    color: (run.fg != Color::Named(NamedColor::Foreground))
+                        .then(|| theme.get_color(run.fg)),
+                    bold: run.flags.contains(Flags::BOLD),
+                    text: run.text,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Lines of the history and of the screen without their trailing blanks.
+fn history_text(grid: &Grid<Cell>, redaction: Option<&Redaction>) -> String {
+    let start = Point::new(grid.topmost_line(), Column(0));
+    let end = Point::new(grid.bottommost_line(), grid.last_column());
+    let range = SelectionRange::new(start, end, false);
+    let mut text = String::new();
+    for runs in styled_lines(grid, range, redaction) {
+        let line: String = runs.into_iter().map(|run| run.text).collect();
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// Runs of the selected cells, line by line.
+fn selected_lines<T>(term: &Term<T>, redaction: Option<&Redaction>) -> Vec<Vec<StyledRun>> {
+    match term.selection.as_ref().and_then(|s| s.to_range(term)) {
+        Some(range) => styled_lines(term.grid(), range, redaction),
+        None => vec![],
+    }
+}
+
+/// Runs of the cells of `range`, line by line. A line wrapping over the next
+/// row is joined to it, except in a block selection.
+fn styled_lines(
+    grid: &Grid<Cell>,
+    range: SelectionRange,
+    redaction: Option<&Redaction>,
+) -> Vec<Vec<StyledRun>> {
+    let (start, end) = (range.start, range.end);
+    let secret_cells = redaction
+        .map(|redaction| redaction.secret_cells(grid, start.line, end.line))
+        .unwrap_or_default();
+    let last_column = grid.last_column();
+
+    let mut lines = vec![];
+    let mut runs: Vec<StyledRun> = vec![];
+    for line in (start.line.0..=end.line.0).map(Line) {
+        let first = match range.is_block || line == start.line {
+            true => start.column,
+            false => Column(0),
+        };
+        let last = match range.is_block || line == end.line {
+            true => end.column,
+            false => last_column,
+        };
+        let row = &grid[line];
+        for column in (first.0..=last.0.min(last_column.0)).map(Column) {
+            let cell = &row[column];
+            if cell
+                .flags
+                .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+            {
+                continue;
+            }
+            let flags = cell.flags & STYLE_FLAGS;
+            if !runs
+                .last()
+                .is_some_and(|run| run.has_style(cell.fg, cell.bg, flags))
+            {
+                runs.push(StyledRun {
+                    fg: cell.fg,
+                    bg: cell.bg,
+                    flags,
+                    text: String::new(),
+                });
+            }
+            let text = &mut runs.last_mut().expect("run pushed above").text;
+            if secret_cells.contains(&Point::new(line, column)) {
+                text.push(REDACTED_CHAR);
+            } else {
+                text.push(cell.c);
+                text.extend(cell.zerowidth().into_iter().flatten());
+            }
+        }
+        let wraps = row[last_column].flags.contains(Flags::WRAPLINE);
+        if range.is_block || line == end.line || !wraps || last != last_column {
+            trim_blank_end(&mut runs);
+            lines.push(std::mem::take(&mut runs));
+        }
+    }
+    lines
+}
+
+/// Drop the blanks at the end of a line, which have no background.
+fn trim_blank_end(runs: &mut Vec<StyledRun>) {
+    while let Some(run) = runs.last_mut() {
+        if run.bg != Color::Named(NamedColor::Background) {
+            break;
+        }
+        run.text
+            .truncate(run.text.trim_end_matches([' ', '\t']).len());
+        if !run.text.is_empty() {
+            break;
+        }
+        runs.pop();
+    }
+}
+
+/// The selection as HTML, a `<pre>` block with inline styles in the colors
+/// of `theme`. The secrets are masked when a `redaction` is given.
+pub(crate) fn selection_html<T>(
+    term: &Term<T>,
+    theme: &TerminalTheme,
+    redaction: Option<&Redaction>,
+) -> String {
+    let fg = theme.get_color(Color::Named(NamedColor::Foreground));
+    let bg = theme.get_color(Color::Named(NamedColor::Background));
+    let mut html = format!(
+        "<pre style=\"color:{};background-color:{};font-family:monospace\">",
+        css_color(fg),
+        css_color(bg)
+    );
+    for (index, runs) in selected_lines(term, redaction).iter().enumerate() {
+        if index > 0 {
+            html.push('\n');
+        }
+        for run in runs {
+            let mut fg = theme.get_color(run.fg);
+            let mut bg = theme.get_color(run.bg);
+            if run.flags.contains(Flags::INVERSE) {
+                std::mem::swap(&mut fg, &mut bg);
+            }
+            let _ = write!(html, "<span style=\"color:{}", css_color(fg));
+            if run.bg != Color::Named(NamedColor::Background) || run.flags.contains(Flags::INVERSE)
+            {
+                let _ = write!(html, ";background-color:{}", css_color(bg));
+            }
+            if run.flags.contains(Flags::BOLD) {
+                html.push_str(";font-weight:bold");
+            }
+            if run.flags.contains(Flags::DIM) {
+                html.push_str(";opacity:0.7");
+            }
+            if run.flags.contains(Flags::ITALIC) {
+                html.push_str(";font-style:italic");
+            }
+            match (
+                run.flags.intersects(Flags::ALL_UNDERLINES),
+                run.flags.contains(Flags::STRIKEOUT),
+            ) {
+                (true, true) => html.push_str(";text-decoration:underline line-through"),
+                (true, false) => html.push_str(";text-decoration:underline"),
+                (false, true) => html.push_str(";text-decoration:line-through"),
+                (false, false) => {}
+            }
+            if run.flags.contains(Flags::HIDDEN) {
+                html.push_str(";visibility:hidden");
+            }
+            html.push_str("\">");
+            for c in run.text.chars() {
+                match c {
+                    '&' => html.push_str("&amp;"),
+                    '<' => html.push_str("&lt;"),
+                    '>' => html.push_str("&gt;"),
+                    '"' => html.push_str("&quot;"),
+                    c => html.push(c),
+                }
+            }
+            html.push_str("</span>");
+        }
+    }
+    html.push_str("</pre>");
+    html
+}
+
+fn css_color(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+/// The selection with the SGR escape codes of its colors and attributes, as
+/// the applications printed them. The secrets are masked when a `redaction`
+/// is given.
+pub(crate) fn selection_ansi<T>(term: &Term<T>, redaction: Option<&Redaction>) -> String {
+    let mut ansi = String::new();
+    for (index, runs) in selected_lines(term, redaction).iter().enumerate() {
+        if index > 0 {
+            ansi.push('\n');
+        }
+        for run in runs {
+            ansi.push_str("\x1b[0");
+            for (flag, code) in [
+                (Flags::BOLD, 1),
+                (Flags::DIM, 2),
+                (Flags::ITALIC, 3),
+                (Flags::INVERSE, 7),
+                (Flags::HIDDEN, 8),
+                (Flags::STRIKEOUT, 9),
+            ] {
+                if run.flags.contains(flag) {
+                    let _ = write!(ansi, ";{code}");
+                }
+            }
+            if run.flags.intersects(Flags::ALL_UNDERLINES) {
+                ansi.push_str(";4");
+            }
+            sgr_color(&mut ansi, run.fg, 30);
+            sgr_color(&mut ansi, run.bg, 40);
+            ansi.push('m');
+            ansi.push_str(&run.text);
+        }
+        if !runs.is_empty() {
+            ansi.push_str("\x1b[0m");
+        }
+    }
+    ansi
+}
+
+/// Parameters of `color`, `base` is 30 for the foreground and 40 for the
+/// background. The default colors are left out.
+fn sgr_color(ansi: &mut String, color: Color, base: u16) {
+    let _ = match color {
+        Color::Named(named) => match named as u16 {
+            named @ 0..=7 => write!(ansi, ";{}", base + named),
+            named @ 8..=15 => write!(ansi, ";{}", base + 60 + named - 8),
+            _ => Ok(()),
+        },
+        Color::Indexed(index) => write!(ansi, ";{};5;{index}", base + 8),
+        Color::Spec(rgb) => write!(ansi, ";{};2;{};{};{}", base + 8, rgb.r, rgb.g, rgb.b),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alacritty_terminal::event::VoidListener;
+    use alacritty_terminal::index::Side;
+    use alacritty_terminal::selection::{Selection, SelectionType};
+    use alacritty_terminal::term::test::TermSize;
+    use alacritty_terminal::term::Config;
+    use alacritty_terminal::vte::ansi::{Attr, Handler};
+
+    fn styled_term() -> Term<VoidListener> {
+        let size = TermSize::new(12, 2);
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        term.terminal_attribute(Attr::Bold);
+        term.terminal_attribute(Attr::Foreground(Color::Named(NamedColor::Red)));
+        for c in "ok".chars() {
+            term.input(c);
+        }
+        term.terminal_attribute(Attr::Reset);
+        for c in " <a&b>".chars() {
+            term.input(c);
+        }
+        term.carriage_return();
+        term.linefeed();
+        term.terminal_attribute(Attr::Foreground(Color::Indexed(208)));
+        term.input('x');
+
+        let mut selection = Selection::new(
+            SelectionType::Simple,
+            Point::new(Line(0), Column(0)),
+            Side::Left,
+        );
+        selection.update(Point::new(Line(1), Column(11)), Side::Right);
+        term.selection = Some(selection);
+        term
+    }
+
+    #[test]
+    fn history_as_text() {
+        let term = styled_term();
+        assert_eq!(history_text(term.grid(), None), "ok <a&b>\nx\n");
+        assert_eq!(
+            HistorySnapshot(term.grid().clone()).text(None),
+            "ok <a&b>\nx\n"
+        );
+    }
+
+    #[test]
+    fn copy_with_styles() {
+        let term = styled_term();
+        assert_eq!(
+            selection_ansi(&term, None),
+            "\x1b[0;1;31mok\x1b[0m <a&b>\x1b[0m\n\x1b[0;38;5;208mx\x1b[0m"
+        );
+
+        let html = selection_html(&term, &TerminalTheme::default(), None);
+        assert!(html.starts_with("<pre style=\"color:#d8d8d8;"));
+        assert!(html.contains(";font-weight:bold\">ok</span>"));
+        assert!(html.contains("> &lt;a&amp;b&gt;</span>\n<span"));
+        assert!(html.ends_with(">x</span></pre>"));
+    }
+}
@@ -1,5 +1,5 @@
 use alacritty_terminal::vte::ansi::{self, NamedColor};
-use egui::Color32;
+use egui::{Color32, Rgba, Stroke};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -40,7 +40,7 @@ impl Default for ColorPalette {
         Self {
             foreground: String::from("#d8d8d8"),
             background: String::from("#181818"),
-            selection: String::from("#2A70E3"),
+            selection: String::from("#2a70e3"),
             black: String::from("#181818"),
             red: String::from("#ac4242"),
             green: String::from("#90a959"),
@@ -71,10 +71,312 @@ impl Default for ColorPalette {
     }
 }
 
+impl ColorPalette {
+    /// Color `index` of the first 16 of the 256 color palette.
+    fn ansi_color(&self, index: u8) -> Option<&String> {
+        let color = match index {
+            // Normal terminal colors
+            0 => &self.black,
+            1 => &self.red,
+            2 => &self.green,
+            3 => &self.yellow,
+            4 => &self.blue,
+            5 => &self.magenta,
+            6 => &self.cyan,
+            7 => &self.white,
+            // Bright terminal colors
+            8 => &self.bright_black,
+            9 => &self.bright_red,
+            10 => &self.bright_green,
+            11 => &self.bright_yellow,
+            12 => &self.bright_blue,
+            13 => &self.bright_magenta,
+            14 => &self.bright_cyan,
+            15 => &self.bright_white,
+            _ => return None,
+        };
+        Some(color)
+    }
+
+    fn ansi_color_mut(&mut self, index: u8) -> Option<&mut String> {
+        let color = match index {
+            0 => &mut self.black,
+            1 => &mut self.red,
+            2 => &mut self.green,
+            3 => &mut self.yellow,
+            4 => &mut self.blue,
+            5 => &mut self.magenta,
+            6 => &mut self.cyan,
+            7 => &mut self.white,
+            8 => &mut self.bright_black,
+            9 => &mut self.bright_red,
+            10 => &mut self.bright_green,
+            11 => &mut self.bright_yellow,
+            12 => &mut self.bright_blue,
+            13 => &mut self.bright_magenta,
+            14 => &mut self.bright_cyan,
+            15 => &mut self.bright_white,
+            _ => return None,
+        };
+        Some(color)
+    }
+
+    /// Colors told apart with a red or green color vision deficiency, after
+    /// the Okabe-Ito palette: red is vermillion and green is sky blue, e.g.
+    /// for the removed and added lines of the diffs.
+    pub fn color_blind() -> Self {
+        Self {
+            foreground: String::from("#d8d8d8"),
+            background: String::from("#181818"),
+            selection: String::from("#2a70e3"),
+            black: String::from("#181818"),
+            red: String::from("#d55e00"),
+            green: String::from("#56b4e9"),
+            yellow: String::from("#f0e442"),
+            blue: String::from("#0072b2"),
+            magenta: String::from("#cc79a7"),
+            cyan: String::from("#009e73"),
+            white: String::from("#d8d8d8"),
+            bright_black: String::from("#6b6b6b"),
+            bright_red: String::from("#e69f00"),
+            bright_green: String::from("#8fcdf2"),
+            bright_yellow: String::from("#f5ee8a"),
+            bright_blue: String::from("#3d9bd9"),
+            bright_magenta: String::from("#e0a6c6"),
+            bright_cyan: String::from("#33c49c"),
+            bright_white: String::from("#f8f8f8"),
+            bright_foreground: None,
+            dim_foreground: String::from("#828482"),
+            dim_black: String::from("#0f0f0f"),
+            dim_red: String::from("#8a3d00"),
+            dim_green: String::from("#38758f"),
+            dim_yellow: String::from("#9c942b"),
+            dim_blue: String::from("#004a74"),
+            dim_magenta: String::from("#854f6c"),
+            dim_cyan: String::from("#00664b"),
+            dim_white: String::from("#8e8e8e"),
+        }
+    }
+
+    /// [`ColorPalette::color_blind`] on a light background.
+    pub fn color_blind_light() -> Self {
+        Self {
+            foreground: String::from("#383a42"),
+            background: String::from("#fafafa"),
+            selection: String::from("#9ebdf5"),
+            black: String::from("#383a42"),
+            red: String::from("#b34f00"),
+            green: String::from("#0072b2"),
+            yellow: String::from("#8a7d00"),
+            blue: String::from("#004f7c"),
+            magenta: String::from("#a8467c"),
+            cyan: String::from("#007a5a"),
+            white: String::from("#a0a1a7"),
+            bright_black: String::from("#4f525e"),
+            bright_red: String::from("#d55e00"),
+            bright_green: String::from("#2a8fd1"),
+            bright_yellow: String::from("#a89a00"),
+            bright_blue: String::from("#0072b2"),
+            bright_magenta: String::from("#cc79a7"),
+            bright_cyan: String::from("#009e73"),
+            bright_white: String::from("#ffffff"),
+            bright_foreground: None,
+            dim_foreground: String::from("#696c77"),
+            dim_black: String::from("#2a2c33"),
+            dim_red: String::from("#7d3700"),
+            dim_green: String::from("#00507d"),
+            dim_yellow: String::from("#605700"),
+            dim_blue: String::from("#003757"),
+            dim_magenta: String::from("#763157"),
+            dim_cyan: String::from("#00553f"),
+            dim_white: String::from("#7c7d82"),
+        }
+    }
+
+    /// Dark text on a light background, for the hosts whose shell expects
+    /// one.
+    pub fn light() -> Self {
+        Self {
+            foreground: String::from("#383a42"),
+            background: String::from("#fafafa"),
+            selection: String::from("#9ebdf5"),
+            black: String::from("#383a42"),
+            red: String::from("#e45649"),
+            green: String::from("#50a14f"),
+            yellow: String::from("#c18401"),
+            blue: String::from("#0184bc"),
+            magenta: String::from("#a626a4"),
+            cyan: String::from("#0997b3"),
+            white: String::from("#a0a1a7"),
+            bright_black: String::from("#4f525e"),
+            bright_red: String::from("#e06c75"),
+            bright_green: String::from("#5fa85e"),
+            bright_yellow: String::from("#d19a1b"),
+            bright_blue: String::from("#2a8fd1"),
+            bright_magenta: String::from("#b04fae"),
+            bright_cyan: String::from("#1aa5bf"),
+            bright_white: String::from("#ffffff"),
+            bright_foreground: None,
+            dim_foreground: String::from("#696c77"),
+            dim_black: String::from("#2a2c33"),
+            dim_red: String::from("#a8443a"),
+            dim_green: String::from("#3d7a3c"),
+            dim_yellow: String::from("#8f6200"),
+            dim_blue: String::from("#016390"),
+            dim_magenta: String::from("#7d1d7b"),
+            dim_cyan: String::from("#077186"),
+            dim_white: String::from("#7c7d82"),
+        }
+    }
+
+    /// Palette for a light background in place of this one: the light
+    /// variant of a built-in palette, or this one when it is already light or
+    /// was edited.
+    pub fn on_light_background(&self) -> Self {
+        if *self == Self::default() {
+            Self::light()
+        } else if *self == Self::color_blind() {
+            Self::color_blind_light()
+        } else {
+            self.clone()
+        }
+    }
+}
+
+/// Color applied on top of the theme, e.g. to tell production sessions apart.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TerminalTint {
+    /// Blended into the background color.
+    Background(Color32),
+    /// Frame drawn around the terminal.
+    Border(Color32),
+}
+
+impl TerminalTint {
+    /// Share of the tint in a tinted background, subtle enough to keep the
+    /// theme readable.
+    const BACKGROUND_OPACITY: f32 = 0.15;
+    const BORDER_WIDTH: f32 = 2.;
+
+    pub fn tint_background(&self, background: Color32) -> Color32 {
+        match *self {
+            TerminalTint::Background(tint) => {
+                let blend = |base: u8, tint: u8| {
+                    let base = base as f32;
+                    (base + (tint as f32 - base) * Self::BACKGROUND_OPACITY).round() as u8
+                };
+                Color32::from_rgb(
+                    blend(background.r(), tint.r()),
+                    blend(background.g(), tint.g()),
+                    blend(background.b(), tint.b()),
+                )
+            }
+            TerminalTint::Border(_) => background,
+        }
+    }
+
+    pub fn border(&self) -> Option<Stroke> {
+        match *self {
+            TerminalTint::Border(color) => Some(Stroke::new(Self::BORDER_WIDTH, color)),
+            TerminalTint::Background(_) => None,
+        }
+    }
+}
+
+/// How the 24-bit colors of the programs are shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrueColor {
+    #[default]
+    Exact,
+    /// As the nearest color of the 256 color palette, the way a terminal
+    /// without truecolor support shows them.
+    Ansi256,
+}
+
+/// Color vision deficiency a color filter is made for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDeficiency {
+    /// No red cones, red looks dark and close to green.
+    Protanopia,
+    /// No green cones, red and green look alike.
+    Deuteranopia,
+}
+
+impl ColorDeficiency {
+    /// Colors as seen with the deficiency, in linear RGB, after Machado et
+    /// al. 2009.
+    const fn simulation(self) -> [[f32; 3]; 3] {
+        match self {
+            ColorDeficiency::Protanopia => [
+                [0.152_286, 1.052_583, -0.204_868],
+                [0.114_503, 0.786_281, 0.099_216],
+                [-0.003_882, -0.048_116, 1.051_998],
+            ],
+            ColorDeficiency::Deuteranopia => [
+                [0.367_322, 0.860_646, -0.227_968],
+                [0.280_085, 0.672_501, 0.047_413],
+                [-0.011_820, 0.042_940, 0.968_881],
+            ],
+        }
+    }
+}
+
+/// Filter applied to the colors of the terminals, last.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorFilter {
+    #[default]
+    None,
+    /// Show the colors as seen with the deficiency, e.g. to check a theme.
+    Simulate(ColorDeficiency),
+    /// Shift the differences lost with the deficiency to the colors still
+    /// told apart, daltonization.
+    Compensate(ColorDeficiency),
+}
+
+impl ColorFilter {
+    pub const ALL: [ColorFilter; 5] = [
+        ColorFilter::None,
+        ColorFilter::Simulate(ColorDeficiency::Protanopia),
+        ColorFilter::Simulate(ColorDeficiency::Deuteranopia),
+        ColorFilter::Compensate(ColorDeficiency::Protanopia),
+        ColorFilter::Compensate(ColorDeficiency::Deuteranopia),
+    ];
+
+    pub fn apply(self, color: Color32) -> Color32 {
+        let deficiency = match self {
+            ColorFilter::None => return color,
+            ColorFilter::Simulate(deficiency) | ColorFilter::Compensate(deficiency) => deficiency,
+        };
+        let rgba = Rgba::from(color);
+        let rgb = [rgba.r(), rgba.g(), rgba.b()];
+        let matrix = deficiency.simulation();
+        let simulated = matrix.map(|row| row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]);
+        let [r, g, b] = match self {
+            ColorFilter::Compensate(_) => {
+                let error = [0, 1, 2].map(|i| rgb[i] - simulated[i]);
+                [
+                    rgb[0],
+                    rgb[1] + 0.7 * error[0] + error[1],
+                    rgb[2] + 0.7 * error[0] + error[2],
+                ]
+            }
+            _ => simulated,
+        };
+        let rgba = Rgba::from_rgb(r.clamp(0., 1.), g.clamp(0., 1.), b.clamp(0., 1.));
+        Color32::from(rgba)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct TerminalTheme {
     palette: Box<ColorPalette>,
     ansi256_colors: HashMap<u8, Color32>,
+    true_color: TrueColor,
+    color_filter: ColorFilter,
+    /// Bold text in the first 8 colors is shown in the bright ones.
+    bold_as_bright: bool,
+    /// Text in the bright colors is shown in bold.
+    bright_as_bold: bool,
 }
 
 impl Default for TerminalTheme {
@@ -82,6 +384,10 @@ impl Default for TerminalTheme {
         Self {
             palette: Box::<ColorPalette>::default(),
             ansi256_colors: TerminalTheme::get_ansi256_colors(),
+            true_color: TrueColor::default(),
+            color_filter: ColorFilter::default(),
+            bold_as_bright: false,
+            bright_as_bold: false,
         }
     }
 }
@@ -91,63 +397,141 @@ impl TerminalTheme {
         Self {
             palette,
             ansi256_colors: TerminalTheme::get_ansi256_colors(),
+            true_color: TrueColor::default(),
+            color_filter: ColorFilter::default(),
+            bold_as_bright: false,
+            bright_as_bold: false,
         }
     }
 
     fn get_ansi256_colors() -> HashMap<u8, Color32> {
-        let mut ansi256_colors = HashMap::new();
-
-        for r in 0..6 {
-            for g in 0..6 {
-                for b in 0..6 {
-                    // Reserve the first 16 colors for config.
-                    let index = 16 + r * 36 + g * 6 + b;
-                    let color = Color32::from_rgb(
-                        if r == 0 { 0 } else { r * 40 + 55 },
-                        if g == 0 { 0 } else { g * 40 + 55 },
-                        if b == 0 { 0 } else { b * 40 + 55 },
-                    );
-                    ansi256_colors.insert(index, color);
+        // Reserve the first 16 colors for config.
+        (16..=255)
+            .map(|index| (index, xterm_color(index)))
+            .collect()
+    }
+
+    pub fn palette(&self) -> &ColorPalette {
+        &self.palette
+    }
+
+    pub fn palette_mut(&mut self) -> &mut ColorPalette {
+        &mut self.palette
+    }
+
+    pub fn true_color(&self) -> TrueColor {
+        self.true_color
+    }
+
+    pub fn set_true_color(&mut self, true_color: TrueColor) {
+        self.true_color = true_color;
+    }
+
+    pub fn color_filter(&self) -> ColorFilter {
+        self.color_filter
+    }
+
+    pub fn set_color_filter(&mut self, color_filter: ColorFilter) {
+        self.color_filter = color_filter;
+    }
+
+    pub fn bold_as_bright(&self) -> bool {
+        self.bold_as_bright
+    }
+
+    pub fn set_bold_as_bright(&mut self, bold_as_bright: bool) {
+        self.bold_as_bright = bold_as_bright;
+    }
+
+    pub fn bright_as_bold(&self) -> bool {
+        self.bright_as_bold
+    }
+
+    pub fn set_bright_as_bold(&mut self, bright_as_bold: bool) {
+        self.bright_as_bold = bright_as_bold;
+    }
+
+    /// Color of text in `c`, in the bright variant when it is bold and the
+    /// theme shows bold as bright.
+    pub fn get_text_color(&self, c: ansi::Color, bold: bool) -> Color32 {
+        if !(bold && self.bold_as_bright) {
+            return self.get_color(c);
+        }
+        let bright = match c {
+            ansi::Color::Named(named)
+                if (named as usize) < 8 || named == NamedColor::Foreground =>
+            {
+                ansi::Color::Named(named.to_bright())
+            }
+            ansi::Color::Indexed(index) if index < 8 => ansi::Color::Indexed(index + 8),
+            c => c,
+        };
+        self.get_color(bright)
+    }
+
+    /// Whether text in `c` is shown in bold, besides the bold text.
+    pub fn is_bold_color(&self, c: ansi::Color) -> bool {
+        self.bright_as_bold
+            && match c {
+                ansi::Color::Named(named) => {
+                    (8..16).contains(&(named as usize)) || named == NamedColor::BrightForeground
                 }
+                ansi::Color::Indexed(index) => (8..16).contains(&index),
+                ansi::Color::Spec(_) => false,
             }
-        }
+    }
 
-        let index: u8 = 232;
-        for i in 0..24 {
-            let value = i * 10 + 8;
-            ansi256_colors.insert(index + i, Color32::from_rgb(value, value, value));
+    /// Color `index` of the 256 color palette, the first 16 are the ones of
+    /// the palette. The color filter is left out.
+    pub fn indexed_color(&self, index: u8) -> Color32 {
+        self.base_color(ansi::Color::Indexed(index))
+    }
+
+    pub fn set_indexed_color(&mut self, index: u8, color: Color32) {
+        match self.palette.ansi_color_mut(index) {
+            Some(hex) => *hex = color_to_hex(color),
+            None => {
+                self.ansi256_colors.insert(index, color);
+            }
         }
+    }
 
-        ansi256_colors
+    /// Map the colors past the first 16 to the xterm ones again.
+    pub fn reset_ansi256_colors(&mut self) {
+        self.ansi256_colors = TerminalTheme::get_ansi256_colors();
     }
 
+    /// Colors past the first 16 mapped to another color than the xterm one,
+    /// by index.
+    pub fn custom_ansi256_colors(&self) -> Vec<(u8, Color32)> {
+        let mut colors: Vec<(u8, Color32)> = self
+            .ansi256_colors
+            .iter()
+            .filter(|(index, color)| xterm_color(**index) != **color)
+            .map(|(index, color)| (*index, *color))
+            .collect();
+        colors.sort_unstable_by_key(|(index, _)| *index);
+        colors
+    }
+
+    /// Color `c` in the theme, through its color filter.
     pub fn get_color(&self, c: ansi::Color) -> Color32 {
+        self.color_filter.apply(self.base_color(c))
+    }
+
+    fn base_color(&self, c: ansi::Color) -> Color32 {
         match c {
-            ansi::Color::Spec(rgb) => Color32::from_rgb(rgb.r, rgb.g, rgb.b),
+            ansi::Color::Spec(rgb) => {
+                let color = Color32::from_rgb(rgb.r, rgb.g, rgb.b);
+                match self.true_color {
+                    TrueColor::Exact => color,
+                    TrueColor::Ansi256 => {
+                        self.base_color(ansi::Color::Indexed(nearest_ansi256(color)))
+                    }
+                }
+            }
             ansi::Color::Indexed(index) => {
-                if index <= 15 {
-                    let color = match index {
-                        // Normal terminal colors
-                        0 => &self.palette.black,
-                        1 => &self.palette.red,
-                        2 => &self.palette.green,
-                        3 => &self.palette.yellow,
-                        4 => &self.palette.blue,
-                        5 => &self.palette.magenta,
-                        6 => &self.palette.cyan,
-                        7 => &self.palette.white,
-                        // Bright terminal colors
-                        8 => &self.palette.bright_black,
-                        9 => &self.palette.bright_red,
-                        10 => &self.palette.bright_green,
-                        11 => &self.palette.bright_yellow,
-                        12 => &self.palette.bright_blue,
-                        13 => &self.palette.bright_magenta,
-                        14 => &self.palette.bright_cyan,
-                        15 => &self.palette.bright_white,
-                        _ => &self.palette.background,
-                    };
-
+                if let Some(color) = self.palette.ansi_color(index) {
                     return hex_to_color(color).unwrap_or_else(|_| panic!("invalid color {color}"));
                 }
 
@@ -204,10 +588,58 @@ impl TerminalTheme {
     pub fn get_selection_color(&self) -> Color32 {
         let color = hex_to_color(&self.palette.selection)
             .unwrap_or_else(|_| panic!("invalid color {}", &self.palette.selection));
-        color.gamma_multiply(0.5)
+        self.color_filter.apply(color).gamma_multiply(0.5)
+    }
+}
+
+/// Color `index` of the xterm 256 color palette, past the first 16: a 6x6x6
+/// cube then a ramp of 24 grays.
+fn xterm_color(index: u8) -> Color32 {
+    let level = |value: u8| if value == 0 { 0 } else { value * 40 + 55 };
+    match index {
+        16..=231 => {
+            let index = index - 16;
+            Color32::from_rgb(level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        232.. => {
+            let value = (index - 232) * 10 + 8;
+            Color32::from_rgb(value, value, value)
+        }
+        _ => Color32::BLACK,
     }
 }
 
+/// Index of the xterm color nearest to `color`, in the cube or in the grays.
+fn nearest_ansi256(color: Color32) -> u8 {
+    // nearest level of the cube, they are 0, 95, 135, 175, 215 and 255
+    let level = |value: u8| match value {
+        0..=47 => 0,
+        48..=114 => 1,
+        value => (value - 35) / 40,
+    };
+    let cube = 16 + 36 * level(color.r()) + 6 * level(color.g()) + level(color.b());
+    let average = (color.r() as u16 + color.g() as u16 + color.b() as u16) / 3;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    let distance = |index: u8| {
+        let other = xterm_color(index);
+        let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        channel(color.r(), other.r())
+            + channel(color.g(), other.g())
+            + channel(color.b(), other.b())
+    };
+    if distance(gray) < distance(cube) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// `#rrggbb`, the format of the colors of [`ColorPalette`].
+pub fn color_to_hex(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
 fn hex_to_color(hex: &str) -> anyhow::Result<Color32> {
     if hex.len() != 7 {
         return Err(anyhow::format_err!("input string is in non valid format"));
@@ -219,3 +651,116 @@ fn hex_to_color(hex: &str) -> anyhow::Result<Color32> {
 
     Ok(Color32::from_rgb(r, g, b))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subtle_background_tint() {
+        let tint = TerminalTint::Background(Color32::from_rgb(255, 0, 0));
+        assert_eq!(
+            tint.tint_background(Color32::from_rgb(0, 0, 100)),
+            Color32::from_rgb(38, 0, 85)
+        );
+        let border = TerminalTint::Border(Color32::RED);
+        assert_eq!(border.tint_background(Color32::BLACK), Color32::BLACK);
+    }
+
+    #[test]
+    fn ansi256_colors() {
+        assert_eq!(xterm_color(196), Color32::from_rgb(255, 0, 0));
+        assert_eq!(nearest_ansi256(Color32::from_rgb(250, 10, 5)), 196);
+        assert_eq!(nearest_ansi256(Color32::from_rgb(128, 128, 130)), 244);
+
+        let mut theme = TerminalTheme::default();
+        let orange = ansi::Color::Spec(ansi::Rgb {
+            r: 255,
+            g: 135,
+            b: 0,
+        });
+        theme.set_indexed_color(208, Color32::from_rgb(1, 2, 3));
+        assert_eq!(theme.get_color(orange), Color32::from_rgb(255, 135, 0));
+        theme.set_true_color(TrueColor::Ansi256);
+        assert_eq!(theme.get_color(orange), Color32::from_rgb(1, 2, 3));
+        assert_eq!(
+            theme.custom_ansi256_colors(),
+            [(208, Color32::from_rgb(1, 2, 3))]
+        );
+
+        theme.set_indexed_color(1, Color32::from_rgb(0xab, 0xcd, 0xef));
+        assert_eq!(theme.palette().red, "#abcdef");
+        theme.reset_ansi256_colors();
+        assert!(theme.custom_ansi256_colors().is_empty());
+    }
+
+    #[test]
+    fn palettes_on_light_background() {
+        let light = ColorPalette::default().on_light_background();
+        assert_eq!(light, ColorPalette::light());
+        assert_eq!(light.on_light_background(), ColorPalette::light());
+        assert_eq!(
+            ColorPalette::color_blind().on_light_background(),
+            ColorPalette::color_blind_light()
+        );
+        let mut edited = ColorPalette::default();
+        edited.red = String::from("#ff0000");
+        assert_eq!(edited.on_light_background(), edited);
+    }
+
+    #[test]
+    fn color_filters() {
+        let (red, green) = (
+            Color32::from_rgb(200, 40, 40),
+            Color32::from_rgb(40, 160, 40),
+        );
+        assert_eq!(ColorFilter::None.apply(red), red);
+        let distance = |filter: ColorFilter| {
+            let (a, b) = (filter.apply(red), filter.apply(green));
+            let channel = |a: u8, b: u8| (a as i32 - b as i32).abs();
+            channel(a.r(), b.r()) + channel(a.g(), b.g()) + channel(a.b(), b.b())
+        };
+        // red and green look closer with the deficiency, compensating it
+        // sets them apart again
+        for deficiency in [ColorDeficiency::Protanopia, ColorDeficiency::Deuteranopia] {
+            let simulated = distance(ColorFilter::Simulate(deficiency));
+            assert!(simulated < distance(ColorFilter::None));
+            let compensated = ColorFilter::Compensate(deficiency);
+            let seen = |color| ColorFilter::Simulate(deficiency).apply(compensated.apply(color));
+            let (a, b) = (seen(red), seen(green));
+            let channel = |a: u8, b: u8| (a as i32 - b as i32).abs();
+            let seen_distance =
+                channel(a.r(), b.r()) + channel(a.g(), b.g()) + channel(a.b(), b.b());
+            assert!(seen_distance > simulated);
+        }
+
+        let mut theme = TerminalTheme::default();
+        theme.set_color_filter(ColorFilter::Simulate(ColorDeficiency::Protanopia));
+        let red = ansi::Color::Named(NamedColor::Red);
+        assert_ne!(
+            theme.get_color(red),
+            TerminalTheme::default().get_color(red)
+        );
+    }
+
+    #[test]
+    fn bold_and_bright() {
+        let mut theme = TerminalTheme::default();
+        let red = ansi::Color::Named(NamedColor::Red);
+        let bright_red = theme.get_color(ansi::Color::Named(NamedColor::BrightRed));
+        assert_eq!(theme.get_text_color(red, true), theme.get_color(red));
+        assert!(!theme.is_bold_color(ansi::Color::Indexed(9)));
+
+        theme.set_bold_as_bright(true);
+        theme.set_bright_as_bold(true);
+        assert_eq!(theme.get_text_color(red, true), bright_red);
+        assert_eq!(
+            theme.get_text_color(ansi::Color::Indexed(1), true),
+            bright_red
+        );
+        assert_eq!(theme.get_text_color(red, false), theme.get_color(red));
+        assert!(theme.is_bold_color(ansi::Color::Indexed(9)));
+        assert!(theme.is_bold_color(ansi::Color::Named(NamedColor::BrightBlue)));
+        assert!(!theme.is_bold_color(red));
+    }
+}
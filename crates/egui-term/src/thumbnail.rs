@@ -0,0 +1,153 @@
+use crate::alacritty::Terminal;
+use crate::theme::TerminalTheme;
+use alacritty_terminal::grid::{Dimensions, GridCell};
+use alacritty_terminal::term::cell::Flags;
+use alacritty_terminal::vte::ansi::{Color, NamedColor};
+use egui::{Color32, CornerRadius, Painter, Rect, Vec2};
+
+/// Low resolution copy of the screen of a terminal, cheap to paint at a small
+/// size, e.g. in an overview of the tabs. The text is too small to be read,
+/// a block of its color stands for each character.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TerminalThumbnail {
+    columns: usize,
+    lines: usize,
+    background: Color32,
+    runs: Vec<CellRun>,
+    cursor: Option<(usize, usize, Color32)>,
+}
+
+/// Cells of the same color next to each other on a line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CellRun {
+    line: usize,
+    column: usize,
+    len: usize,
+    color: Color32,
+}
+
+impl TerminalThumbnail {
+    fn new(columns: usize, lines: usize, background: Color32) -> Self {
+        Self {
+            columns,
+            lines,
+            background,
+            ..Default::default()
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    fn push_cell(&mut self, line: usize, column: usize, color: Color32) {
+        if color == self.background {
+            return;
+        }
+        if let Some(run) = self.runs.last_mut() {
+            if run.line == line && run.column + run.len == column && run.color == color {
+                run.len += 1;
+                return;
+            }
+        }
+        self.runs.push(CellRun {
+            line,
+            column,
+            len: 1,
+            color,
+        });
+    }
+
+    /// Paint the whole screen into `rect`, stretched to fill it.
+    pub fn paint(&self, painter: &Painter, rect: Rect) {
+        painter.rect_filled(rect, CornerRadius::ZERO, self.background);
+        if self.columns == 0 || self.lines == 0 {
+            return;
+        }
+        let cell = Vec2::new(
+            rect.width() / self.columns as f32,
+            rect.height() / self.lines as f32,
+        );
+        let cell_rect = |line: usize, column: usize, len: usize| {
+            let min = rect.min + Vec2::new(column as f32 * cell.x, line as f32 * cell.y);
+            Rect::from_min_size(min, Vec2::new(len as f32 * cell.x, cell.y))
+        };
+        for run in &self.runs {
+            let run_rect = cell_rect(run.line, run.column, run.len);
+            painter.rect_filled(run_rect, CornerRadius::ZERO, run.color);
+        }
+        if let Some((line, column, color)) = self.cursor {
+            painter.rect_filled(cell_rect(line, column, 1), CornerRadius::ZERO, color);
+        }
+    }
+}
+
+impl Terminal {
+    /// Snapshot of the visible screen, with the colors of `theme`.
+    pub fn thumbnail(&self, theme: &TerminalTheme) -> TerminalThumbnail {
+        let term = self.term.lock();
+        let grid = term.grid();
+        let background = theme.get_color(Color::Named(NamedColor::Background));
+        let mut thumbnail = TerminalThumbnail::new(term.columns(), term.screen_lines(), background);
+        let display_offset = grid.display_offset() as i32;
+
+        for indexed in grid.display_iter() {
+            if indexed.flags().contains(Flags::WIDE_CHAR_SPACER) {
+                continue;
+            }
+            let mut fg = theme.get_color(indexed.fg);
+            let mut bg = theme.get_color(indexed.bg);
+            if indexed.flags().contains(Flags::INVERSE) {
+                std::mem::swap(&mut fg, &mut bg);
+            }
+            let color = if indexed.c != ' ' && indexed.c != '\t' {
+                bg.lerp_to_gamma(fg, 0.6)
+            } else {
+                bg
+            };
+            let line = (indexed.point.line.0 + display_offset) as usize;
+            let column = indexed.point.column.0;
+            thumbnail.push_cell(line, column, color);
+            if indexed.flags().contains(Flags::WIDE_CHAR) {
+                thumbnail.push_cell(line, column + 1, color);
+            }
+        }
+
+        let cursor = grid.cursor.point;
+        let cursor_line = cursor.line.0 + display_offset;
+        if (0..thumbnail.lines as i32).contains(&cursor_line) {
+            let color = theme.get_color(Color::Named(NamedColor::Foreground));
+            thumbnail.cursor = Some((cursor_line as usize, cursor.column.0, color));
+        }
+        thumbnail
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_cells_into_runs() {
+        let mut thumbnail = TerminalThumbnail::new(80, 24, Color32::BLACK);
+        for column in 0..4 {
+            thumbnail.push_cell(0, column, Color32::WHITE);
+        }
+        // the background isn't painted over
+        thumbnail.push_cell(0, 4, Color32::BLACK);
+        thumbnail.push_cell(0, 5, Color32::WHITE);
+        thumbnail.push_cell(0, 6, Color32::RED);
+        thumbnail.push_cell(1, 7, Color32::RED);
+
+        let runs: Vec<_> = thumbnail
+            .runs
+            .iter()
+            .map(|run| (run.line, run.column, run.len))
+            .collect();
+        assert_eq!(runs, [(0, 0, 4), (0, 5, 1), (0, 6, 1), (1, 7, 1)]);
+    }
+}
@@ -0,0 +1,58 @@
+use crate::alacritty::BackendError;
+use crate::view::TerminalViewState;
+use crate::TerminalView;
+use egui::{Align2, Area, Color32, Frame, Order, Response, RichText, Vec2};
+
+impl TerminalView<'_> {
+    /// Keep the last failure of the backend, shown at the top of the terminal
+    /// until it is retried or dismissed. Once the backend stopped, nothing can
+    /// be retried, the terminal is restarted instead.
+    pub(crate) fn error_banner(&mut self, state: &mut TerminalViewState, layout: &Response) {
+        state.close_requested = false;
+        state.restart_requested = false;
+        if let Some(error) = self.term_ctx.take_error() {
+            state.backend_error = Some(error);
+        }
+        let Some(error) = &state.backend_error else {
+            return;
+        };
+
+        let labels = self.labels;
+        let stopped = self.term_ctx.backend_stopped();
+        let mut restart = false;
+        let mut retry = false;
+        let mut dismiss = false;
+        Area::new(self.id().with("error_banner"))
+            .order(Order::Foreground)
+            .pivot(Align2::CENTER_TOP)
+            .fixed_pos(layout.rect.center_top() + Vec2::new(0., 16.))
+            .show(&layout.ctx, |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let text = labels.backend_error.replace("{error}", &error.message);
+                        ui.label(RichText::new(text).color(Color32::LIGHT_RED));
+                        if stopped {
+                            restart = ui.button(&labels.restart).clicked();
+                        } else if error.command.is_some() {
+                            retry = ui.button(&labels.retry).clicked();
+                        } else {
+                            dismiss = ui.button(&labels.dismiss).clicked();
+                        }
+                        state.close_requested = ui.button(&labels.close_tab).clicked();
+                    });
+                });
+            });
+
+        if restart {
+            state.backend_error = None;
+            state.restart_requested = true;
+        } else if retry {
+            let command = state.backend_error.take().and_then(|error| error.command);
+            if let Some(command) = command {
+                self.term_ctx.process_command(command);
+            }
+        } else if dismiss {
+            state.backend_error = None;
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::input::InputAction;
+use crate::view::TerminalViewState;
+use crate::TerminalView;
+use egui::{Align2, Area, Frame, Key, KeyboardShortcut, Modifiers, Order, Response, Vec2};
+
+/// A protected key held back until the user confirms it.
+#[derive(Clone, Copy, Debug)]
+pub struct PendingKey {
+    key: Key,
+    modifiers: Modifiers,
+    /// The protected shortcut it matched, shown in the prompt.
+    shortcut: KeyboardShortcut,
+}
+
+impl TerminalView<'_> {
+    /// Filter a pressed key through the protected keys, returns the key to
+    /// send to the terminal now, if any.
+    ///
+    /// A protected key opens a prompt, the next key press confirms it with
+    /// enter or cancels it with escape, any other key is swallowed.
+    pub(crate) fn guard_key(
+        &self,
+        state: &mut TerminalViewState,
+        key: Key,
+        modifiers: Modifiers,
+    ) -> Option<(Key, Modifiers)> {
+        if let Some(pending) = state.pending_key {
+            return match key {
+                Key::Enter => {
+                    state.pending_key = None;
+                    Some((pending.key, pending.modifiers))
+                }
+                Key::Escape => {
+                    state.pending_key = None;
+                    None
+                }
+                _ => None,
+            };
+        }
+
+        let protected = self.protected_keys.iter().find(|shortcut| {
+            shortcut.logical_key == key && modifiers.matches_exact(shortcut.modifiers)
+        });
+        match protected {
+            Some(shortcut) => {
+                state.pending_key = Some(PendingKey {
+                    key,
+                    modifiers,
+                    shortcut: *shortcut,
+                });
+                None
+            }
+            None => Some((key, modifiers)),
+        }
+    }
+
+    pub(crate) fn guard_prompt(&mut self, state: &mut TerminalViewState, layout: &Response) {
+        let Some(pending) = state.pending_key else {
+            return;
+        };
+
+        let labels = self.labels;
+        let mut send = false;
+        let mut cancel = false;
+        Area::new(self.id().with("guard_prompt"))
+            .order(Order::Foreground)
+            .pivot(Align2::CENTER_BOTTOM)
+            .fixed_pos(layout.rect.center_bottom() - Vec2::new(0., 16.))
+            .show(&layout.ctx, |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let shortcut = ui.ctx().format_shortcut(&pending.shortcut);
+                        ui.label(labels.send_key_prompt.replace("{shortcut}", &shortcut));
+                        send = ui.button(&labels.send_key).clicked();
+                        cancel = ui.button(&labels.cancel_key).clicked();
+                    });
+                });
+            });
+
+        if send {
+            state.pending_key = None;
+            if let Some(action) = self.keyboard_input(state, pending.key, pending.modifiers, true) {
+                match action {
+                    InputAction::BackendCall(cmd) => self.term_ctx.process_command(cmd),
+                    InputAction::WriteToClipboard(data) => layout.ctx.copy_text(data),
+                }
+            }
+        } else if cancel {
+            state.pending_key = None;
+        }
+    }
+}
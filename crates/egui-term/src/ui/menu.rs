@@ -1,20 +1,59 @@
+use crate::styled::{selection_ansi, selection_html};
+use crate::view::TerminalViewState;
 use crate::TerminalView;
 use copypasta::ClipboardProvider;
 use egui::{Button, Key, KeyboardShortcut, Modifiers, Response, WidgetText};
+use parking_lot::Mutex;
+use tracing::error;
+
+/// Clipboard of the rich text copies, kept open: on X11 the copied data is
+/// served by it until another app owns the clipboard.
+static HTML_CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+/// Put `html` on the clipboard as rich text, with `text` for the apps which
+/// only paste plain text.
+fn copy_html(html: String, text: String) -> Result<(), arboard::Error> {
+    let mut clipboard = HTML_CLIPBOARD.lock();
+    let clipboard = match &mut *clipboard {
+        Some(clipboard) => clipboard,
+        None => clipboard.insert(arboard::Clipboard::new()?),
+    };
+    clipboard.set_html(html, Some(text))
+}
 
 impl TerminalView<'_> {
-    pub fn context_menu(&mut self, layout: &Response) {
+    pub fn context_menu(&mut self, state: &mut TerminalViewState, layout: &Response) {
+        state.save_scrollback_requested = false;
         layout.context_menu(|ui| {
             let width = 200.;
             ui.set_width(width);
             // copy btn
             self.copy_btn(ui, layout, width);
+            // copy with the colors
+            self.copy_styled_btns(ui, layout, width);
             // paste btn
             self.paste_btn(ui, width);
 
             ui.separator();
             // select all btn
             self.select_all_btn(ui, width);
+            // find btn
+            self.find_btn(ui, width);
+            // wrap toggle
+            self.wrap_btn(ui);
+            // save the history
+            if ui
+                .add(context_btn(&self.labels.save_scrollback, width, None))
+                .clicked()
+            {
+                state.save_scrollback_requested = true;
+                ui.close();
+            }
+
+            if let Some(add_contents) = &mut self.context_menu_ext {
+                ui.separator();
+                add_contents(ui);
+            }
         });
     }
 
@@ -24,21 +63,42 @@ impl TerminalView<'_> {
         #[cfg(target_os = "macos")]
         let copy_shortcut = KeyboardShortcut::new(Modifiers::MAC_CMD, Key::C);
         let copy_shortcut = ui.ctx().format_shortcut(&copy_shortcut);
-        let copy_btn = context_btn("Copy", btn_width, Some(copy_shortcut));
+        let copy_btn = context_btn(&self.labels.copy, btn_width, Some(copy_shortcut));
         if ui.add(copy_btn).clicked() {
-            let data = self.term_ctx.selection_content();
+            let data = self.copied_selection();
             layout.ctx.copy_text(data);
             ui.close();
         }
     }
 
+    fn copy_styled_btns(&mut self, ui: &mut egui::Ui, layout: &Response, btn_width: f32) {
+        let has_selection = !self.term_ctx.selection_is_empty();
+        let html_btn = context_btn(&self.labels.copy_as_html, btn_width, None);
+        if ui.add_enabled(has_selection, html_btn).clicked() {
+            let html = selection_html(&*self.term_ctx.terminal, self.theme(), self.redaction);
+            let text = self.copied_selection();
+            if let Err(err) = copy_html(html, text.clone()) {
+                // pasted without the colors rather than as markup
+                error!("failed to copy as html: {err}");
+                layout.ctx.copy_text(text);
+            }
+            ui.close();
+        }
+        let ansi_btn = context_btn(&self.labels.copy_as_ansi, btn_width, None);
+        if ui.add_enabled(has_selection, ansi_btn).clicked() {
+            let ansi = selection_ansi(&*self.term_ctx.terminal, self.redaction);
+            layout.ctx.copy_text(ansi);
+            ui.close();
+        }
+    }
+
     fn paste_btn(&mut self, ui: &mut egui::Ui, btn_width: f32) {
         #[cfg(not(target_os = "macos"))]
         let paste_shortcut = KeyboardShortcut::new(Modifiers::CTRL | Modifiers::SHIFT, Key::V);
         #[cfg(target_os = "macos")]
         let paste_shortcut = KeyboardShortcut::new(Modifiers::MAC_CMD, Key::V);
         let paste_shortcut = ui.ctx().format_shortcut(&paste_shortcut);
-        let paste_btn = context_btn("Paste", btn_width, Some(paste_shortcut));
+        let paste_btn = context_btn(&self.labels.paste, btn_width, Some(paste_shortcut));
         if ui.add(paste_btn).clicked() {
             if let Ok(data) = self.term_ctx.clipboard.get_contents() {
                 self.term_ctx.write_data(data.into_bytes());
@@ -54,12 +114,40 @@ impl TerminalView<'_> {
         #[cfg(target_os = "macos")]
         let select_all_shortcut = KeyboardShortcut::new(Modifiers::MAC_CMD, Key::A);
         let select_all_shortcut = ui.ctx().format_shortcut(&select_all_shortcut);
-        let select_all_btn = context_btn("Select All", btn_width, Some(select_all_shortcut));
+        let select_all_btn = context_btn(
+            &self.labels.select_all,
+            btn_width,
+            Some(select_all_shortcut),
+        );
         if ui.add(select_all_btn).clicked() {
             self.term_ctx.select_all();
             ui.close();
         }
     }
+
+    fn wrap_btn(&mut self, ui: &mut egui::Ui) {
+        let mut wrap = !self.term_ctx.line_wrap.no_wrap;
+        let response = ui
+            .checkbox(&mut wrap, &self.labels.wrap_lines)
+            .on_hover_text(&self.labels.wrap_lines_hint);
+        if response.clicked() {
+            self.term_ctx.line_wrap.toggle();
+            ui.close();
+        }
+    }
+
+    fn find_btn(&mut self, ui: &mut egui::Ui, btn_width: f32) {
+        #[cfg(not(target_os = "macos"))]
+        let find_shortcut = KeyboardShortcut::new(Modifiers::CTRL | Modifiers::SHIFT, Key::F);
+        #[cfg(target_os = "macos")]
+        let find_shortcut = KeyboardShortcut::new(Modifiers::MAC_CMD, Key::F);
+        let find_shortcut = ui.ctx().format_shortcut(&find_shortcut);
+        let find_btn = context_btn(&self.labels.find, btn_width, Some(find_shortcut));
+        if ui.add(find_btn).clicked() {
+            self.open_search_bar();
+            ui.close();
+        }
+    }
 }
 
 fn context_btn<'a>(
@@ -1 +1,7 @@
+mod error;
+mod guard;
 mod menu;
+mod search;
+mod wrap;
+
+pub(crate) use guard::PendingKey;
@@ -0,0 +1,125 @@
+use crate::TerminalView;
+use egui::{
+    Align2, Area, Color32, Frame, Id, Key, Order, Response, RichText, TextEdit, Vec2, WidgetText,
+};
+
+const SEARCH_INPUT_WIDTH: f32 = 180.;
+
+impl TerminalView<'_> {
+    pub(crate) fn search_input_id(&self) -> Id {
+        self.id().with("search_input")
+    }
+
+    pub(crate) fn open_search_bar(&mut self) {
+        self.term_ctx.search.open();
+        self.term_ctx.terminal.selection = None;
+    }
+
+    /// Whether the search input currently owns the keyboard focus.
+    pub(crate) fn search_has_focus(&self, ctx: &egui::Context) -> bool {
+        self.term_ctx.search.active && ctx.memory(|m| m.has_focus(self.search_input_id()))
+    }
+
+    pub(crate) fn search_bar(&mut self, layout: &Response) {
+        if !self.term_ctx.search.active {
+            return;
+        }
+
+        let input_id = self.search_input_id();
+        let labels = self.labels;
+        let mut close = false;
+
+        Area::new(self.id().with("search_bar"))
+            .order(Order::Foreground)
+            .pivot(Align2::RIGHT_TOP)
+            .fixed_pos(layout.rect.right_top() + Vec2::new(-8., 8.))
+            .show(&layout.ctx, |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let input = TextEdit::singleline(&mut self.term_ctx.search.query)
+                            .id(input_id)
+                            .hint_text(&labels.find)
+                            .desired_width(SEARCH_INPUT_WIDTH);
+                        let response = ui.add(input);
+                        if self.term_ctx.search.take_focus_request() {
+                            response.request_focus();
+                        }
+
+                        // `TextEdit` surrenders the focus on enter and escape.
+                        if response.lost_focus() {
+                            let (enter, escape, shift) = ui.input(|i| {
+                                (
+                                    i.key_pressed(Key::Enter),
+                                    i.key_pressed(Key::Escape),
+                                    i.modifiers.shift,
+                                )
+                            });
+                            if escape {
+                                close = true;
+                            } else if enter {
+                                self.term_ctx.update_search();
+                                if shift {
+                                    self.term_ctx.search_previous();
+                                } else {
+                                    self.term_ctx.search_next();
+                                }
+                                response.request_focus();
+                            }
+                        }
+
+                        ui.label(self.search_status());
+
+                        let options = &mut self.term_ctx.search.options;
+                        ui.toggle_value(&mut options.case_sensitive, "Aa")
+                            .on_hover_text(&labels.match_case);
+                        ui.toggle_value(&mut options.regex, ".*")
+                            .on_hover_text(&labels.use_regex);
+
+                        ui.separator();
+
+                        if ui
+                            .button("↑")
+                            .on_hover_text(&labels.previous_match)
+                            .clicked()
+                        {
+                            self.term_ctx.search_previous();
+                        }
+                        if ui.button("↓").on_hover_text(&labels.next_match).clicked() {
+                            self.term_ctx.search_next();
+                        }
+                        if ui.button("🗙").on_hover_text(&labels.close_search).clicked() {
+                            close = true;
+                        }
+                    });
+                });
+            });
+
+        if close {
+            self.term_ctx.search.close();
+            layout.request_focus();
+        }
+    }
+
+    fn search_status(&self) -> WidgetText {
+        let search = &self.term_ctx.search;
+        if search.error.is_some() {
+            return RichText::new(&self.labels.invalid_regex)
+                .color(Color32::RED)
+                .into();
+        }
+        if search.query.is_empty() {
+            return WidgetText::default();
+        }
+        match search.match_count() {
+            0 => self.labels.no_matches.as_str().into(),
+            count => {
+                let current = search.focused_index().unwrap_or(0);
+                self.labels
+                    .matches
+                    .replace("{current}", &current.to_string())
+                    .replace("{count}", &count.to_string())
+                    .into()
+            }
+        }
+    }
+}
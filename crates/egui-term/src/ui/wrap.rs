@@ -0,0 +1,75 @@
+use crate::scroll_bar::InteractiveScrollbar;
+use crate::view::TerminalViewState;
+use crate::TerminalView;
+use alacritty_terminal::grid::Dimensions;
+use egui::{Color32, NumExt, Rect, Response, Sense, Vec2};
+
+const MIN_SLIDER_WIDTH: f32 = 64.;
+
+impl TerminalView<'_> {
+    /// Horizontal scrollbar below the terminal in the no-wrap mode, also keeps
+    /// the view over the grid and on the cursor while typing.
+    pub(crate) fn wrap_scrollbar(
+        &mut self,
+        ui: &mut egui::Ui,
+        state: &mut TerminalViewState,
+        layout: &Response,
+        background: Color32,
+    ) {
+        if !self
+            .term_ctx
+            .line_wrap
+            .is_applied(self.term_ctx.term_mode())
+        {
+            return;
+        }
+
+        let id = self.id().with("wrap_scrollbar");
+        let view_width = layout.rect.width();
+        let cell_width = self.term_ctx.size.cell_width as f32;
+        let grid_width = self.term_ctx.size.columns() as f32 * cell_width;
+        let line_wrap = &mut *self.term_ctx.line_wrap;
+
+        if state.follow_cursor {
+            let column = self.term_ctx.terminal.grid().cursor.point.column.0 as f32;
+            line_wrap.reveal(column * cell_width, cell_width, view_width);
+        }
+
+        let bar = Rect::from_min_size(
+            layout.rect.left_bottom(),
+            Vec2::new(view_width, InteractiveScrollbar::WIDTH),
+        );
+        let max_offset = (grid_width - view_width).max(0.);
+        let slider_width = (view_width * view_width / grid_width)
+            .at_least(MIN_SLIDER_WIDTH)
+            .at_most(view_width);
+        let track = view_width - slider_width;
+
+        let response = ui.interact(bar, id, Sense::click_and_drag());
+        if response.clicked() || response.dragged() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                // the slider is centered on the pointer
+                let x = (pos.x - bar.left() - slider_width / 2.).clamp(0., track);
+                line_wrap.offset = if track > 0. {
+                    x / track * max_offset
+                } else {
+                    0.
+                };
+                state.follow_cursor = false;
+            }
+        }
+        line_wrap.clamp(grid_width, view_width);
+
+        let slider_x = if max_offset > 0. {
+            line_wrap.offset / max_offset * track
+        } else {
+            0.
+        };
+        let slider = Rect::from_min_size(
+            bar.min + Vec2::new(slider_x, 0.),
+            Vec2::new(slider_width, bar.height()),
+        );
+        ui.painter().rect_filled(bar, 0., background);
+        ui.painter().rect_filled(slider, 0., Color32::DARK_GRAY);
+    }
+}
@@ -1,21 +1,35 @@
-use crate::alacritty::{BackendCommand, TerminalContext};
+use crate::alacritty::{
+    cell_position, selection_point, BackendCommand, BackendError, Terminal, TerminalConfig,
+    TerminalContext, XOFF, XON,
+};
 use crate::bindings::Binding;
-use crate::bindings::{BindingAction, Bindings, InputKind};
+use crate::bindings::{BindingAction, Bindings, InputKind, MetaKeys};
+use crate::display::{CursorAnimation, RenderSnapshot, EXIT_CODE_GUTTER_CELLS};
 use crate::font::TerminalFont;
-use crate::input::{is_in_terminal, InputAction};
+use crate::input::{is_in_terminal, ClickCount, ComposeState, InputAction};
+use crate::labels::{TerminalLabels, DEFAULT_LABELS};
+use crate::redact::Redaction;
 use crate::scroll_bar::{InteractiveScrollbar, ScrollbarState};
-use crate::theme::TerminalTheme;
+use crate::theme::{TerminalTheme, TerminalTint};
 use crate::types::Size;
+use crate::ui::PendingKey;
+use crate::wrap::NO_WRAP_COLUMNS;
+use crate::UnicodeWidth;
 use alacritty_terminal::grid::{Dimensions, Scroll};
 use alacritty_terminal::index::Point;
+use alacritty_terminal::term::TermMode;
 use alacritty_terminal::vte::ansi::{Color, NamedColor};
+use copypasta::ClipboardContext;
 use egui::output::IMEOutput;
 use egui::Widget;
 use egui::{Context, Event};
-use egui::{CursorIcon, Key};
-use egui::{Id, Pos2};
+use egui::{CursorIcon, Key, KeyboardShortcut};
+use egui::{Id, Modifiers, PointerButton, Pos2};
 use egui::{ImeEvent, Rect};
 use egui::{Response, Vec2};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::time::Duration;
 
 #[derive(Clone, Default)]
 pub struct TerminalViewState {
@@ -25,6 +39,29 @@ pub struct TerminalViewState {
     pub mouse_position: Option<Pos2>,
     pub cursor_position: Option<Pos2>,
     pub scrollbar_state: ScrollbarState,
+    /// Protected key waiting for a confirmation.
+    pub pending_key: Option<PendingKey>,
+    /// Keep the cursor in the view in the no-wrap mode, set when typing and
+    /// cleared when scrolling horizontally.
+    pub follow_cursor: bool,
+    /// Summaries of the command outputs on the screen, clicked to fold or
+    /// unfold the outputs.
+    pub output_summaries: Vec<(Rect, usize)>,
+    /// Shapes painted for the terminal on the last frame.
+    pub shapes: usize,
+    /// Set on the frame the tab is closed from the error banner.
+    pub close_requested: bool,
+    /// Set on the frame the terminal is restarted from the error banner, once
+    /// its backend stopped. The application restarts it.
+    pub restart_requested: bool,
+    /// Set on the frame the history is asked to be saved from the context
+    /// menu, the application saves it.
+    pub save_scrollback_requested: bool,
+    /// Last failure of the backend, until it is retried or dismissed.
+    pub(crate) backend_error: Option<BackendError>,
+    pub(crate) compose: ComposeState,
+    pub(crate) clicks: ClickCount,
+    pub(crate) cursor_animation: CursorAnimation,
 }
 
 impl TerminalViewState {
@@ -41,6 +78,8 @@ impl TerminalViewState {
     }
 }
 
+type ContextMenuExt<'a> = Box<dyn FnMut(&mut egui::Ui) + 'a>;
+
 pub struct TerminalView<'a> {
     widget_id: Id,
     has_focus: bool,
@@ -48,6 +87,44 @@ pub struct TerminalView<'a> {
     pub options: TerminalOptions<'a>,
     pub term_ctx: TerminalContext<'a>,
     pub bindings_layout: Bindings,
+    /// Extra entries appended to the context menu by the application.
+    pub(crate) context_menu_ext: Option<ContextMenuExt<'a>>,
+    pub(crate) tint: Option<TerminalTint>,
+    /// Keys which are only sent after a confirmation.
+    pub(crate) protected_keys: &'a [KeyboardShortcut],
+    pub(crate) labels: &'a TerminalLabels,
+    /// Commands known to exist, set when the command typed at the prompt is
+    /// highlighted.
+    pub(crate) known_commands: Option<&'a HashSet<String>>,
+    /// Set when the exit codes of the commands are shown in a gutter at the
+    /// left.
+    pub(crate) exit_codes: bool,
+    /// Set when the commands which took at least this long show it.
+    pub(crate) duration_threshold: Option<Duration>,
+    /// Secrets masked in the copied text.
+    pub(crate) redaction: Option<&'a Redaction>,
+    /// The secrets are masked on screen too.
+    pub(crate) mask_secrets: bool,
+    /// Blocks are painted in place of the glyphs.
+    pub(crate) blurred: bool,
+    /// The cursor glides to its cell rather than jumping to it.
+    pub(crate) smooth_cursor: bool,
+    /// Dimmed without the focus, framed with it.
+    pub(crate) dim_unfocused: bool,
+    /// Flashed when the terminal rings the bell.
+    pub(crate) visual_bell: bool,
+    /// The view takes the input as soon as the pointer is over it, rather
+    /// than once clicked.
+    pub(crate) focus_follows_mouse: bool,
+    /// Show the chars typed before the server echoes them.
+    pub(crate) local_echo: bool,
+    /// Lines scrolled by a wheel notch.
+    pub(crate) scroll_multiplier: f32,
+    /// The wheel scrolls the content, not the view.
+    pub(crate) natural_scroll: bool,
+    /// Ctrl+S and Ctrl+Q are sent, they pause and resume the output.
+    pub(crate) flow_control: bool,
+    pub(crate) meta_keys: MetaKeys,
 }
 
 pub struct TerminalOptions<'a> {
@@ -64,11 +141,32 @@ impl Widget for TerminalView<'_> {
         let mut state = TerminalViewState::load(ui.ctx(), widget_id);
 
         ui.horizontal(|ui| {
-            let size_p = Vec2::new(self.size.x - InteractiveScrollbar::WIDTH, self.size.y);
+            let scrollbar_height = if self
+                .term_ctx
+                .line_wrap
+                .is_applied(self.term_ctx.term_mode())
+            {
+                InteractiveScrollbar::WIDTH
+            } else {
+                0.
+            };
+            let mut size_p = Vec2::new(
+                self.size.x - InteractiveScrollbar::WIDTH,
+                self.size.y - scrollbar_height,
+            );
+            let gutter = self.exit_codes.then(|| {
+                let width = self.term_ctx.size.cell_width as f32 * EXIT_CODE_GUTTER_CELLS;
+                let gutter = ui.allocate_painter(Vec2::new(width, size_p.y), egui::Sense::hover());
+                size_p.x -= width + ui.spacing().item_spacing.x;
+                gutter
+            });
             let (layout, painter) = ui.allocate_painter(size_p, egui::Sense::click());
 
             if layout.contains_pointer() {
-                *self.options.active_tab_id = Some(self.widget_id);
+                let pressed = layout.ctx.input(|i| i.pointer.any_pressed());
+                if self.focus_follows_mouse || pressed {
+                    *self.options.active_tab_id = Some(self.widget_id);
+                }
                 layout.ctx.set_cursor_icon(CursorIcon::Text);
             } else {
                 layout.ctx.set_cursor_icon(CursorIcon::Default);
@@ -78,15 +176,21 @@ impl Widget for TerminalView<'_> {
                 self.has_focus = false;
             }
 
-            self.context_menu(&layout);
+            self.context_menu(&mut state, &layout);
 
             let background = self.theme().get_color(Color::Named(NamedColor::Background));
 
             let mut term = self
                 .focus(&layout)
-                .resize(&layout)
+                .resize(&mut state, &layout)
                 .process_input(&mut state, &layout);
 
+            term.term_ctx.update_search();
+            term.search_bar(&layout);
+            term.guard_prompt(&mut state, &layout);
+            term.error_banner(&mut state, &layout);
+            term.wrap_scrollbar(ui, &mut state, &layout, background);
+
             if let Some(pos) = state.mouse_position {
                 if is_in_terminal(pos, layout.rect) {
                     if let Some(cur_pos) = state.cursor_position {
@@ -101,6 +205,7 @@ impl Widget for TerminalView<'_> {
                 }
             }
 
+            let markers = term.scroll_markers();
             let grid = term.term_ctx.terminal.grid_mut();
             let total_lines = grid.total_lines() as f32;
             let display_offset = grid.display_offset() as f32;
@@ -110,6 +215,7 @@ impl Widget for TerminalView<'_> {
 
             let mut scrollbar = InteractiveScrollbar::new(background);
             scrollbar.set_first_row_pos(display_offset_pos);
+            scrollbar.set_markers(markers);
             scrollbar.ui(total_height, ui);
             if let Some(new_first_row_pos) = scrollbar.new_first_row_pos {
                 let total_row_pos = new_first_row_pos + state.scrollbar_state.scroll_pixels;
@@ -120,7 +226,41 @@ impl Widget for TerminalView<'_> {
                 grid.scroll_display(line_delta);
             }
 
-            term.show(&mut state, &layout, &painter);
+            // a fling goes on after the fingers are lifted
+            let dt = ui.input(|i| i.stable_dt);
+            let font_size = term.options.font.font_size();
+            let lines = state.scrollbar_state.momentum(dt, font_size);
+            if lines != 0 {
+                grid.scroll_display(Scroll::Delta(lines));
+            }
+            let scrollbar_state = &mut state.scrollbar_state;
+            let at_bottom = grid.display_offset() == 0 && scrollbar_state.scroll_pixels > 0.;
+            let at_top =
+                grid.display_offset() == grid.history_size() && scrollbar_state.scroll_pixels < 0.;
+            if at_bottom || at_top {
+                scrollbar_state.scroll_pixels = 0.;
+                scrollbar_state.stop();
+            }
+            if scrollbar_state.is_flinging() {
+                ui.ctx().request_repaint();
+            }
+
+            // copy what is painted and release the lock, the event loop parses
+            // the output while the frame is painted
+            let redaction = term.redaction.filter(|_| term.mask_secrets);
+            let mut snapshot = RenderSnapshot::new(&term.term_ctx.terminal, redaction);
+            if term.local_echo {
+                term.term_ctx.local_echo.confirm(&term.term_ctx.terminal);
+                snapshot.predictions = term.term_ctx.local_echo.predictions();
+            } else {
+                term.term_ctx.local_echo.reset();
+            }
+            term.term_ctx.terminal.unlock();
+
+            if let Some((gutter, gutter_painter)) = &gutter {
+                term.exit_code_badges(&snapshot, gutter.rect, gutter_painter);
+            }
+            term.show(&snapshot, &mut state, &layout, &painter);
 
             state.store(ui.ctx(), widget_id);
             layout
@@ -144,9 +284,61 @@ impl<'a> TerminalView<'a> {
             term_ctx,
             options,
             bindings_layout: Bindings::new(),
+            context_menu_ext: None,
+            tint: None,
+            protected_keys: &[],
+            labels: &DEFAULT_LABELS,
+            known_commands: None,
+            exit_codes: false,
+            duration_threshold: None,
+            redaction: None,
+            mask_secrets: false,
+            blurred: false,
+            smooth_cursor: false,
+            dim_unfocused: false,
+            visual_bell: false,
+            focus_follows_mouse: true,
+            local_echo: false,
+            scroll_multiplier: 1.,
+            natural_scroll: false,
+            flow_control: true,
+            meta_keys: MetaKeys::default(),
         }
     }
 
+    /// View drawn with the font, theme and behavior of the
+    /// [`TerminalConfig`] of `terminal`, without options to gather on every
+    /// frame. The builders still override them for the frame.
+    pub fn from_terminal(
+        ui: &mut egui::Ui,
+        terminal: &'a mut Terminal,
+        clipboard: &'a mut ClipboardContext,
+    ) -> Self {
+        let (term_ctx, config, focused_view) = TerminalContext::with_config(terminal, clipboard);
+        let TerminalConfig {
+            smooth_cursor,
+            focus_follows_mouse,
+            local_echo,
+            flow_control,
+            scroll_multiplier,
+            natural_scroll,
+            ..
+        } = *config;
+        let options = TerminalOptions {
+            default_font_size: config.default_font_size,
+            font: &mut config.font,
+            multi_exec: &mut config.multi_exec,
+            theme: &mut config.theme,
+            active_tab_id: focused_view,
+        };
+        Self::new(ui, term_ctx, options)
+            .smooth_cursor(smooth_cursor)
+            .focus_follows_mouse(focus_follows_mouse)
+            .local_echo(local_echo)
+            .scroll_speed(scroll_multiplier, natural_scroll)
+            .flow_control(flow_control)
+    }
+
     pub fn id(&self) -> Id {
         self.widget_id
     }
@@ -179,25 +371,243 @@ impl<'a> TerminalView<'a> {
         self
     }
 
+    /// Drag a block with `modifiers` held, e.g. where the default ones move
+    /// the windows.
+    #[inline]
+    pub fn block_selection(mut self, modifiers: Modifiers) -> Self {
+        self.bindings_layout.rebind(
+            InputKind::Mouse(PointerButton::Primary),
+            modifiers,
+            BindingAction::BlockSelection,
+        );
+        self
+    }
+
+    /// Tint the background or frame the terminal, on top of the theme.
+    #[inline]
+    pub fn set_tint(mut self, tint: Option<TerminalTint>) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Ask for a confirmation before sending any of `keys` to the terminal,
+    /// e.g. `Ctrl+C` on a critical session.
+    #[inline]
+    pub fn protect_keys(mut self, keys: &'a [KeyboardShortcut]) -> Self {
+        self.protected_keys = keys;
+        self
+    }
+
+    /// Translate the menus and prompts of the terminal.
+    #[inline]
+    pub fn set_labels(mut self, labels: &'a TerminalLabels) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Highlight the command typed at the shell prompt. Besides the shell
+    /// builtins, `known_commands` tells which commands exist on the host.
+    #[inline]
+    pub fn highlight_input(mut self, known_commands: &'a HashSet<String>) -> Self {
+        self.known_commands = Some(known_commands);
+        self
+    }
+
+    /// Show the exit code of each command at its line, in a gutter at the left
+    /// of the terminal. The shell reports them through OSC 133.
+    #[inline]
+    pub fn show_exit_codes(mut self, show: bool) -> Self {
+        self.exit_codes = show;
+        self
+    }
+
+    /// Show how long each command which took at least `threshold` ran, at the
+    /// end of its output. The shell marks the commands through OSC 133.
+    #[inline]
+    pub fn show_durations(mut self, threshold: Option<Duration>) -> Self {
+        self.duration_threshold = threshold;
+        self
+    }
+
+    /// Mask the secrets matched by `redaction` in the text copied out of the
+    /// terminal, and on screen too with `on_screen`.
+    #[inline]
+    pub fn redact(mut self, redaction: &'a Redaction, on_screen: bool) -> Self {
+        self.redaction = Some(redaction);
+        self.mask_secrets = on_screen;
+        self
+    }
+
+    /// Paint a block in place of each glyph, hiding the content of the
+    /// terminal but not its layout, e.g. while the screen is shared.
+    #[inline]
+    pub fn blur(mut self, blurred: bool) -> Self {
+        self.blurred = blurred;
+        self
+    }
+
+    /// Animate the moves of the cursor, with a trail, so that its jumps are
+    /// easy to follow on large screens.
+    #[inline]
+    pub fn smooth_cursor(mut self, enabled: bool) -> Self {
+        self.smooth_cursor = enabled;
+        self
+    }
+
+    /// Dim the view unless it has the focus, and frame it when it has, e.g.
+    /// when several terminals are shown side by side, to tell where the keys
+    /// go.
+    #[inline]
+    pub fn dim_unfocused(mut self, enabled: bool) -> Self {
+        self.dim_unfocused = enabled;
+        self
+    }
+
+    /// Flash the view when the terminal rings the bell, e.g. on a completion
+    /// without matches, rather than ignoring it.
+    #[inline]
+    pub fn visual_bell(mut self, enabled: bool) -> Self {
+        self.visual_bell = enabled;
+        self
+    }
+
+    /// Take the input when the pointer enters the view, or only when it is
+    /// clicked.
+    #[inline]
+    pub fn focus_follows_mouse(mut self, enabled: bool) -> Self {
+        self.focus_follows_mouse = enabled;
+        self
+    }
+
+    /// Scroll `multiplier` lines by wheel notch, in the other direction with
+    /// `natural`, since the defaults of mice and trackpads differ.
+    #[inline]
+    pub fn scroll_speed(mut self, multiplier: f32, natural: bool) -> Self {
+        self.scroll_multiplier = multiplier;
+        self.natural_scroll = natural;
+        self
+    }
+
+    /// Show the printable chars typed right away, before the server echoes
+    /// them back, e.g. over a connection with a high latency. They are
+    /// underlined until they are confirmed.
+    #[inline]
+    pub fn local_echo(mut self, enabled: bool) -> Self {
+        self.local_echo = enabled;
+        self
+    }
+
+    /// Send XOFF and XON, typed with Ctrl+S and Ctrl+Q, or drop them so the
+    /// output can't be paused by mistake.
+    #[inline]
+    pub fn flow_control(mut self, enabled: bool) -> Self {
+        self.flow_control = enabled;
+        self
+    }
+
+    /// How Alt, or Option on macOS, modifies the keys, see [`MetaKeys`].
+    #[inline]
+    pub fn meta_keys(mut self, meta_keys: MetaKeys) -> Self {
+        self.meta_keys = meta_keys;
+        self
+    }
+
+    /// Width of the ambiguous chars and of the emoji printed from now on, see
+    /// [`UnicodeWidth`].
+    #[inline]
+    pub fn unicode_width(mut self, unicode_width: UnicodeWidth) -> Self {
+        self.term_ctx.terminal.set_unicode_width(unicode_width);
+        self
+    }
+
+    /// Selected text, as it is copied.
+    pub(crate) fn copied_selection(&self) -> String {
+        let content = self.term_ctx.selection_content();
+        // the history selected whole isn't copied once more without secrets
+        let redacted = match self.redaction.map(|redaction| redaction.redact(&content)) {
+            Some(Cow::Owned(redacted)) => Some(redacted),
+            _ => None,
+        };
+        redacted.unwrap_or(content)
+    }
+
+    /// Append entries to the context menu of the terminal.
+    #[inline]
+    pub fn extend_context_menu(mut self, add_contents: impl FnMut(&mut egui::Ui) + 'a) -> Self {
+        self.context_menu_ext = Some(Box::new(add_contents));
+        self
+    }
+
     fn focus(self, layout: &Response) -> Self {
-        if self.has_focus {
-            layout.request_focus();
-        } else {
+        if !self.has_focus {
             layout.surrender_focus();
+        } else if !self.search_has_focus(&layout.ctx) {
+            // the search bar keeps the keyboard focus while it is being typed in
+            layout.request_focus();
         }
 
         self
     }
 
-    fn resize(mut self, layout: &Response) -> Self {
-        self.term_ctx.process_command(BackendCommand::Resize(
-            Size::from(layout.rect.size()),
-            self.options.font.font_measure(&layout.ctx),
-        ));
+    fn resize(mut self, state: &mut TerminalViewState, layout: &Response) -> Self {
+        let old_size = *self.term_ctx.size;
+        let font_size = self.options.font.font_measure(&layout.ctx);
+        let mut layout_size = Size::from(layout.rect.size());
+        let mode = self.term_ctx.term_mode();
+        if self.term_ctx.line_wrap.is_applied(mode) {
+            // the grid extends past the view, which scrolls over it
+            let width = NO_WRAP_COLUMNS as f32 * font_size.width.floor();
+            layout_size.width = layout_size.width.max(width);
+        } else {
+            self.term_ctx.line_wrap.offset = 0.;
+        }
+        self.term_ctx
+            .process_command(BackendCommand::Resize(layout_size, font_size));
+
+        let size = *self.term_ctx.size;
+        if (size.cell_width, size.cell_height) != (old_size.cell_width, old_size.cell_height) {
+            self.cell_size_changed(state, layout, old_size.cell_width);
+        }
 
         self
     }
 
+    /// Move what is kept in pixels onto the new cells right away, rather
+    /// than on the next pointer event.
+    fn cell_size_changed(
+        &mut self,
+        state: &mut TerminalViewState,
+        layout: &Response,
+        old_cell_width: u16,
+    ) {
+        let size = *self.term_ctx.size;
+        let line_wrap = &mut *self.term_ctx.line_wrap;
+        line_wrap.rescale(old_cell_width as f32, size.cell_width as f32);
+        let offset = line_wrap.offset;
+        state.scrollbar_state.scroll_pixels = 0.;
+
+        let display_offset = self.term_ctx.terminal.grid().display_offset();
+        let cursor = self.term_ctx.terminal.grid().cursor.point;
+        state.cursor_position = cell_position(cursor, &size, display_offset)
+            .map(|(x, y)| layout.rect.min + Vec2::new(x - offset, y));
+
+        let Some(position) = state.mouse_position else {
+            return;
+        };
+        let mouse_x = position.x - layout.rect.min.x + offset;
+        let mouse_y = position.y - layout.rect.min.y;
+        state.mouse_point = selection_point(mouse_x, mouse_y, &size, display_offset);
+        // the selection keeps its anchor cell and follows the pointer
+        let reports_mouse = self
+            .term_ctx
+            .term_mode()
+            .intersects(TermMode::MOUSE_MOTION | TermMode::MOUSE_DRAG);
+        if state.is_dragged && !reports_mouse {
+            self.term_ctx
+                .process_command(BackendCommand::SelectUpdate(mouse_x, mouse_y));
+        }
+    }
+
     fn process_input(mut self, state: &mut TerminalViewState, layout: &Response) -> Self {
         if !layout.has_focus() {
             return self;
@@ -208,16 +618,38 @@ impl<'a> TerminalView<'a> {
 
         let modifiers = layout.ctx.input(|i| i.modifiers);
         let events = layout.ctx.input(|i| i.events.clone());
+        // the text of a key already sent as a sequence, which follows its key
+        let mut text_replaced = false;
+        state.compose.begin_frame();
 
         for event in events {
             let mut input_actions = vec![];
             match event {
-                Event::Text(text) | Event::Paste(text) => {
+                Event::Text(_) if std::mem::take(&mut text_replaced) => {}
+                Event::Text(text) if !state.compose.text(&text) => {}
+                Event::Text(text)
+                    if state.pending_key.is_none() && self.meta_keys.is_meta(modifiers) =>
+                {
+                    state.follow_cursor = true;
+                    self.term_ctx.local_echo.reset();
+                    input_actions.push(self.text_input(&self.meta_keys.meta_text(&text)));
+                }
+                // typing answers the confirmation prompt, don't send it
+                Event::Text(text) | Event::Paste(text) if state.pending_key.is_none() => {
+                    state.follow_cursor = true;
+                    if self.local_echo {
+                        self.term_ctx
+                            .local_echo
+                            .predict(&self.term_ctx.terminal, &text);
+                    }
                     input_actions.push(self.text_input(&text));
                 }
                 Event::Copy => {
-                    if let Some(action) = self.keyboard_input(Key::C, modifiers, true) {
-                        input_actions.push(action);
+                    let key = self.guard_key(state, Key::C, modifiers);
+                    if let Some((key, modifiers)) = key {
+                        if let Some(action) = self.keyboard_input(state, key, modifiers, true) {
+                            input_actions.push(action);
+                        }
                     }
                 }
                 Event::Key {
@@ -226,8 +658,19 @@ impl<'a> TerminalView<'a> {
                     modifiers,
                     ..
                 } => {
-                    if let Some(action) = self.keyboard_input(key, modifiers, pressed) {
-                        input_actions.push(action);
+                    let key = if pressed {
+                        self.guard_key(state, key, modifiers)
+                    } else {
+                        Some((key, modifiers))
+                    };
+                    if let Some((key, modifiers)) = key {
+                        if let Some(action) = self.keyboard_input(state, key, modifiers, pressed) {
+                            text_replaced = pressed && self.replaces_text(key, modifiers);
+                            // e.g. Enter or Backspace, which aren't predicted
+                            self.term_ctx.local_echo.reset();
+                            state.follow_cursor = true;
+                            input_actions.push(action);
+                        }
                     }
                 }
                 Event::MouseWheel {
@@ -249,6 +692,9 @@ impl<'a> TerminalView<'a> {
                     if out_of && pressed {
                         continue;
                     }
+                    if pressed {
+                        state.scrollbar_state.stop();
+                    }
 
                     let new_pos = if out_of {
                         pos.clamp(layout.rect.min, layout.rect.max)
@@ -266,23 +712,32 @@ impl<'a> TerminalView<'a> {
                     input_actions = self.mouse_move(state, layout, pos, &modifiers)
                 }
                 Event::Ime(event) => match event {
-                    ImeEvent::Preedit(text_mark) => {
-                        if text_mark != "\n" && text_mark != "\r" {
-                            // TODO: handle preedit
-                        }
-                    }
+                    ImeEvent::Preedit(text_mark) => state.compose.preedit(text_mark),
                     ImeEvent::Commit(prediction) => {
-                        if prediction != "\n" && prediction != "\r" {
+                        if state.compose.commit(&prediction) {
                             input_actions.push(self.text_input(&prediction));
                         }
                     }
-                    _ => {}
+                    ImeEvent::Disabled => {
+                        if let Some(text) = state.compose.disable() {
+                            input_actions.push(self.text_input(&text));
+                        }
+                    }
+                    ImeEvent::Enabled => {}
                 },
                 _ => {}
             };
 
             for action in input_actions {
                 match action {
+                    InputAction::BackendCall(BackendCommand::Write(mut input))
+                        if !self.flow_control =>
+                    {
+                        input.retain(|byte| !matches!(*byte, XOFF | XON));
+                        if !input.is_empty() {
+                            self.term_ctx.write_data(input);
+                        }
+                    }
                     InputAction::BackendCall(cmd) => {
                         self.term_ctx.process_command(cmd);
                     }
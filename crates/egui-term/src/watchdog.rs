@@ -0,0 +1,93 @@
+//! Heartbeats sent to the event loop of the pty along with the input, which
+//! tell a loop stuck or stopped, e.g. after a panic, from a quiet shell.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A heartbeat is sent with the input at most this often.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+/// The event loop is unresponsive once a heartbeat is unanswered for this long.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub(crate) struct Watchdog {
+    /// Last heartbeat the event loop received.
+    received: Arc<AtomicU64>,
+    sent: u64,
+    sent_at: Option<Instant>,
+}
+
+impl Watchdog {
+    pub(crate) fn new(received: Arc<AtomicU64>) -> Self {
+        Self {
+            received,
+            sent: 0,
+            sent_at: None,
+        }
+    }
+
+    /// The heartbeat to send at `now`, if the last one was answered a while
+    /// ago.
+    pub(crate) fn beat(&mut self, now: Instant) -> Option<u64> {
+        if self.waiting() {
+            return None;
+        }
+        if self
+            .sent_at
+            .is_some_and(|sent_at| now.duration_since(sent_at) < HEARTBEAT_INTERVAL)
+        {
+            return None;
+        }
+        self.sent += 1;
+        self.sent_at = Some(now);
+        Some(self.sent)
+    }
+
+    /// How long the last heartbeat may still be answered at `now`, `None`
+    /// once answered or missed.
+    pub(crate) fn pending(&self, now: Instant) -> Option<Duration> {
+        let sent_at = self.sent_at.filter(|_| self.waiting())?;
+        HEARTBEAT_TIMEOUT
+            .checked_sub(now.duration_since(sent_at))
+            .filter(|left| !left.is_zero())
+    }
+
+    /// Whether the last heartbeat was missed at `now`.
+    pub(crate) fn missed(&self, now: Instant) -> bool {
+        self.waiting() && self.pending(now).is_none()
+    }
+
+    fn waiting(&self) -> bool {
+        self.received.load(Ordering::Relaxed) < self.sent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missed_heartbeat() {
+        let received = Arc::new(AtomicU64::new(0));
+        let mut watchdog = Watchdog::new(received.clone());
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        assert_eq!(watchdog.beat(start), Some(1));
+        // one at a time
+        assert_eq!(watchdog.beat(at(2)), None);
+        assert_eq!(watchdog.pending(at(2)), Some(Duration::from_secs(3)));
+        received.store(1, Ordering::Relaxed);
+        assert_eq!(watchdog.pending(at(2)), None);
+        assert!(!watchdog.missed(at(10)));
+
+        assert_eq!(watchdog.beat(at(10)), Some(2));
+        assert_eq!(watchdog.beat(at(10)), None);
+        assert!(!watchdog.missed(at(14)));
+        assert!(watchdog.missed(at(15)));
+        // answered late, the loop is running again
+        received.store(2, Ordering::Relaxed);
+        assert!(!watchdog.missed(at(15)));
+    }
+}
@@ -0,0 +1,91 @@
+use alacritty_terminal::term::TermMode;
+
+/// Columns of the grid in the no-wrap mode, lines wider than this still wrap.
+///
+/// The pty has this width too, the shell and what it runs lay their output
+/// out over it, e.g. the columns of `ls`. The full-screen programs on the
+/// alternate screen keep the width of the view, the mode doesn't apply to
+/// them.
+pub const NO_WRAP_COLUMNS: u16 = 512;
+
+/// Whether long lines wrap at the width of the view, or run past it with the
+/// view scrolled horizontally.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LineWrap {
+    pub no_wrap: bool,
+    /// Horizontal scroll of the grid in the no-wrap mode, in pixels.
+    pub offset: f32,
+}
+
+impl LineWrap {
+    /// Whether lines run past the view in `mode`, on the primary screen
+    /// only.
+    pub fn is_applied(&self, mode: TermMode) -> bool {
+        self.no_wrap && !mode.contains(TermMode::ALT_SCREEN)
+    }
+
+    pub fn toggle(&mut self) {
+        self.no_wrap = !self.no_wrap;
+        self.offset = 0.;
+    }
+
+    /// Keep the view within the grid.
+    pub(crate) fn clamp(&mut self, grid_width: f32, view_width: f32) {
+        let max = (grid_width - view_width).max(0.);
+        self.offset = self.offset.clamp(0., max);
+    }
+
+    /// Keep the same column at the left of the view when the cells are
+    /// resized, e.g. by a new font size.
+    pub(crate) fn rescale(&mut self, old_cell_width: f32, cell_width: f32) {
+        if old_cell_width > 0. {
+            self.offset *= cell_width / old_cell_width;
+        }
+    }
+
+    /// Scroll just enough for `x..x + width` of the grid to be in the view.
+    pub(crate) fn reveal(&mut self, x: f32, width: f32, view_width: f32) {
+        if x < self.offset {
+            self.offset = x;
+        } else if x + width > self.offset + view_width {
+            self.offset = x + width - view_width;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reveal_and_clamp() {
+        let mut wrap = LineWrap {
+            no_wrap: true,
+            offset: 0.,
+        };
+        wrap.reveal(950., 10., 400.);
+        assert_eq!(wrap.offset, 560.);
+        // already visible
+        wrap.reveal(600., 10., 400.);
+        assert_eq!(wrap.offset, 560.);
+        wrap.reveal(100., 10., 400.);
+        assert_eq!(wrap.offset, 100.);
+
+        wrap.offset = 2000.;
+        wrap.clamp(1000., 400.);
+        assert_eq!(wrap.offset, 600.);
+        wrap.clamp(300., 400.);
+        assert_eq!(wrap.offset, 0.);
+
+        wrap.offset = 80.;
+        wrap.rescale(8., 10.);
+        assert_eq!(wrap.offset, 100.);
+        wrap.rescale(0., 10.);
+        assert_eq!(wrap.offset, 100.);
+
+        assert!(wrap.is_applied(TermMode::default()));
+        assert!(!wrap.is_applied(TermMode::ALT_SCREEN));
+        wrap.toggle();
+        assert!(!wrap.no_wrap);
+    }
+}
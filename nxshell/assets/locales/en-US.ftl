@@ -0,0 +1,506 @@
+## Main window
+
+sessions = Sessions
+sessions-loading = Loading sessions…
+host-up = Host is up
+host-down = Host is unreachable
+host-unknown = Not probed yet
+open-terminal = Open Terminal
+open-sftp = Open SFTP
+open-sftp-here = Open SFTP Here
+cd-to-bookmark = cd to Bookmark
+term-theme = Theme
+tab-local = local
+tab-sessions = sessions
+tab-pin = Pin Tab
+tab-unpin = Unpin Tab
+tab-encoding = Encoding
+tab-split-right = Split Right
+tab-split-down = Split Down
+tab-exited = [process exited] — press Enter to close
+tab-exited-code = [process exited with code { $code }] — press Enter to close
+tab-exited-close = Close
+tab-tunnels = Tunnels:
+tunnel-failed = { $forward } failed: { $error }
+backend-unresponsive = The terminal backend is unresponsive
+backend-unresponsive-hint = Its output and input are stuck, restart it to get a new shell
+backend-restart = Restart
+memory-trimmed = Trimmed the scrollback of { $count } tabs to stay within the memory budget
+idle-warning = { $name } is idle, it will be disconnected in a minute
+idle-disconnected = Disconnected { $name }, it was idle for too long
+idle-banner = Idle for too long, this connection is closed in a minute
+idle-stay-connected = Stay connected
+paused-banner = Output paused — press Ctrl+Q
+paused-resume = Resume
+wake-no-mac = { $name } has no MAC address, set it in the session to wake the host up
+wake-sent = Waking { $name } up, it is connected to once it is up
+wake-failed = { $name } didn't wake up
+connect-resolving = resolving…
+connect-authenticating = authenticating…
+connect-touch-security-key = touch your security key…
+connect-opening-pty = opening pty…
+presentation-exit-hint = Press { $shortcut } to exit the presentation mode
+privacy-blur-hint = The terminals are hidden, press { $shortcut } to show them again
+
+## Menu bar
+
+menu-session = Session
+menu-new-session = New Session
+menu-new-terminal = New Terminal
+menu-import-sessions = Import Sessions…
+menu-find = Find in Terminal
+menu-split-right = Split Terminal Right
+menu-split-down = Split Terminal Down
+menu-quit = Quit
+menu-window = Window
+menu-new-window = New Window
+menu-presentation-mode = Presentation Mode
+menu-privacy-blur = Hide Terminal Content
+menu-tab-overview = Tab Overview
+menu-startup-window = At Startup
+startup-window-restore = Restore the last size and position
+startup-window-maximized = Maximized
+menu-tools = Tools
+menu-multi-exec = Multi Exec
+menu-exit-codes = Exit Codes
+menu-exit-codes-hint = Show the exit code of each command next to it, with the shell integration
+menu-flash-failed-tabs = Flash Failed Tabs
+menu-flash-failed-tabs-hint = Flash the tabs in the background when a command fails in them
+menu-keep-exited-tabs = Keep exited tabs open
+menu-keep-exited-tabs-hint = Keep the local tabs open when their shell exits, to read the last output
+menu-durations = Durations of at least
+menu-durations-hint = Show how long the slow commands took at the end of their output, with the shell integration
+menu-scroll-speed = Lines by wheel notch
+menu-scroll-speed-hint = How far the mouse wheel and the trackpad scroll the terminals
+menu-natural-scroll = Natural scrolling
+menu-natural-scroll-hint = Invert the scroll direction, the content follows the fingers
+menu-flow-control = Flow control (Ctrl+S / Ctrl+Q)
+menu-flow-control-hint = Send Ctrl+S and Ctrl+Q to the shell, they pause and resume the output
+menu-record-commands = Command History
+menu-record-commands-hint = Save the commands run at the prompt to complete them later, with their secrets masked. The commands starting with a space are never saved
+menu-smooth-cursor = Smooth Cursor
+menu-smooth-cursor-hint = The cursor glides to its cell with a trail, its jumps are easier to follow on large screens
+menu-dim-inactive = Dim Inactive Terminals
+menu-dim-inactive-hint = While several terminals are shown, the ones without the focus are dimmed and the focused one is framed
+menu-focus-follows-mouse = Focus Follows Mouse
+menu-focus-follows-mouse-hint = The terminal under the pointer takes the keys without a click, otherwise a terminal is clicked to type in it
+menu-block-selection = Block Selection
+menu-block-selection-hint = Held while dragging to select a rectangle, e.g. where Alt+drag moves the windows. Ctrl+double-click expands the selection
+menu-bell = Bell
+menu-bell-hint = What a terminal does when a program rings the bell, e.g. a completion without matches
+block-selection-alt = Alt+Drag
+block-selection-ctrl-alt = Ctrl+Alt+Drag
+block-selection-shift-alt = Shift+Alt+Drag
+bell-visual = Flash the terminal
+bell-audible = Play a sound
+bell-none = Nothing
+menu-profiler = Render profiler
+menu-profiler-hint = Show the frame time and the render cost of each terminal
+menu-scrollback-lines = Scrollback lines
+menu-scrollback-lines-hint = Lines of history kept by the terminals opened next
+menu-memory-budget = Scrollback memory budget
+menu-memory-budget-hint = Trim the scrollback of the tabs shown the least recently when the terminals take more memory than this
+menu-idle-timeout = Disconnect idle ssh tabs after
+menu-idle-timeout-hint = Close the ssh connections without input nor output for this long, after a warning a minute before
+menu-host-badges = Probe the hosts every
+menu-host-badges-hint = Show whether the hosts of the saved sessions are up, with a TCP handshake to their ssh port
+menu-host-probe-concurrency = Hosts probed at a time
+menu-presentation-font-scale = Presentation font scale
+menu-language = Language
+menu-terminal-font = Terminal Font
+menu-theme-editor = Theme Editor
+menu-color-filter = Color Vision
+menu-color-filter-hint = Filter of the terminal colors, to check a theme or to tell red and green apart
+menu-bold-as-bright = Bold as Bright
+menu-bold-as-bright-hint = Bold text in the first 8 colors is shown in the bright ones, like the legacy terminals
+menu-bright-as-bold = Bright as Bold
+menu-bright-as-bold-hint = Text in the bright colors is shown in bold
+menu-api = Automation API
+menu-output-log = Output Log
+menu-export = Export
+menu-redaction = Secret Redaction
+font-family = Family
+font-size = Size
+font-line-height = Line height
+font-cell-padding = Cell padding
+font-cell-padding-hint = Extra width of the cells, in points
+font-bundled = Maple Mono (bundled)
+font-default-size = Default
+fonts-loading = Loading fonts…
+menu-help = Help
+menu-about = About
+menu-logs = Logs
+menu-export-diagnostics = Export Diagnostics
+menu-export-diagnostics-hint = Zip the recent logs and crash reports for a bug report
+diagnostics-saved = Diagnostics saved to { $path }
+
+## Status bar
+
+status-latency-hint = Round trip of a command doing nothing on the open session, measured every 30 seconds
+status-grid-hint = Columns × rows
+status-cursor = Ln { $line }, Col { $column }
+status-scroll-bottom = Bottom
+status-scroll-hint = Lines scrolled back into the history
+status-multi-exec = Multi Exec
+status-multi-exec-hint = Input is sent to every terminal
+
+## Host key
+
+host-key-changed-title = Host key changed
+host-key-changed-message = The host key of { $host } isn't the one saved with the session. Someone could be intercepting the connection, or the host was reinstalled.
+host-key-expected = Expected
+host-key-found = Presented
+host-key-disconnect = Disconnect
+host-key-trust = Trust the new key
+host-key-new-title = Unknown host key
+host-key-new-message = This is the first connection to { $host }. Check that the fingerprint below is the one of the host before trusting it, it is then saved with the session.
+host-key-trust-new = Trust and connect
+
+## Quit
+
+quit-title = Quit NxShell?
+quit-message = These connections will be closed:
+quit-transfers = { $name }: { $count } transfers in progress
+quit-cancel = Cancel
+quit-force = Quit Anyway
+
+## Keyboard-interactive authentication
+
+auth-prompt-title = Log in to { $host }
+auth-prompt-user = as { $user }
+auth-prompt-cancel = Cancel
+auth-prompt-submit = Log in
+auth-prompt-otp-hint = This host asks for a verification code, have your OTP device ready
+
+## Tab overview
+
+overview-title = Terminals
+overview-empty = No terminal is open
+
+## Logs
+
+logs = Logs
+logs-search = Search
+logs-copy = Copy
+logs-clear = Clear
+profiler = Render profiler
+profiler-frame-time = Frame time
+profiler-frame-interval = Frame interval
+profiler-tab = Tab
+profiler-shapes = Shapes
+profiler-throughput = Output
+profiler-hint = Shapes are counted on the last frame a tab was shown.
+diagnose-title = Diagnose { $host }
+diagnose-last = Last
+diagnose-min = Min
+diagnose-avg = Avg
+diagnose-max = Max
+diagnose-loss = Loss
+diagnose-ping-hint = Pinged every second with a TCP handshake to { $address }
+diagnose-trace = Trace route
+diagnose-trace-failed = Failed to run traceroute: { $error }
+import-title = Import Sessions
+import-path-hint = Path of a CSV or Ansible inventory file
+import-format-csv = CSV
+import-format-inventory = Ansible Inventory
+import-load = Load
+import-columns-hint = CSV columns: name, host, port, user, group. Hosts without a user log in with the ssh config, the passwords are asked on connection.
+import-column-line = Line
+import-column-group = Group
+import-column-name = Name
+import-column-host = Host
+import-column-port = Port
+import-column-user = User
+import-column-status = Status
+import-status-ok = Ready
+import-summary = { $count } hosts, { $invalid } skipped
+import-save = Import { $count } Sessions
+import-done = { $count } sessions imported
+import-error-read = Failed to read the file: { $error }
+import-error-host = No host
+import-error-port = Invalid port { $port }
+import-error-duplicate = Listed twice in the group
+import-error-exists = Already saved
+api-enabled = Enable the local HTTP API
+api-port = Port:
+api-copy-token = Copy Token
+api-token-copied = Token copied
+api-new-token = New Token
+api-new-token-hint = Replace the token, the clients holding the old one are refused
+api-hint = Serves the programs of this computer holding the token, e.g.
+    curl -H "Authorization: Bearer $TOKEN" http://127.0.0.1:{ $port }/tabs
+api-error-start = Failed to start the API: { $error }
+
+output-log-start = Log the Active Terminal
+output-log-stop = Stop Logging
+output-log-no-terminal = The active tab has no terminal
+output-log-collapse-progress = Collapse progress bars
+output-log-collapse-progress-hint = Log only the last state of the lines redrawn by progress bars and spinners, rather than every update
+output-log-started = Logging the output to { $path }
+output-log-stopped = Output logged to { $path }
+output-log-error = Failed to log the output: { $error }
+export-view-image = Image of the Terminal (PNG)
+export-history-pdf = Print the History (PDF)
+export-saved = Exported to { $path }
+export-error = Failed to export: { $error }
+
+redaction-on-screen = Mask secrets on screen
+redaction-on-screen-hint = Also mask the secrets in the terminals, e.g. while sharing the screen
+redaction-patterns-hint = Secrets matching these regular expressions are always masked in the copied text and the output logs. When a pattern has a group, only the group is masked.
+redaction-add = Add a Pattern
+redaction-remove = Remove the pattern
+redaction-reset = Restore the Defaults
+staging-title = Proposed by { $source }, review it before running it
+staging-run = Run
+staging-run-hint = Press Enter to run it in the terminal, Escape to dismiss it
+staging-dismiss = Dismiss
+
+## Session form
+
+session-form-title = New Session
+session-form-submit = Submit
+session-template = Template:
+session-template-none = None
+session-template-hint = Fill the form with a template, its host is a prefix to complete
+session-template-delete = Delete the template
+session-save-template = Save as Template
+session-save-template-hint = Save the form as a template, under its name, to add similar sessions quickly
+session-template-saved = Template { $name } saved
+session-duplicate = Duplicate
+session-copy-name = { $name } (copy)
+session-group = Group:
+session-name = Name:
+session-host = Host:
+session-host-alias = Host Alias:
+session-auth-type = Auth Type:
+session-username = Username:
+session-password = Password:
+session-identity-file = Identity file:
+session-passphrase = Passphrase:
+session-passphrase-hint = Asked on connection when empty
+session-tint = Tint:
+session-tint-hint = Color the tabs of this session, e.g. to spot production hosts
+session-theme = Theme:
+session-theme-hint = Palette of the terminals, Auto turns light when the host sets a light background
+session-protected-keys = Protected Keys:
+session-protected-keys-hint = Ask for a confirmation before sending these keys
+session-font = Font:
+session-font-hint = Font of the terminals of this session, the default font is used otherwise
+session-highlight = Highlighting:
+session-highlight-prompt = Highlight the command line
+session-highlight-hint = Color the commands, flags and quoted strings typed at the prompt. Needs the shell integration (OSC 133)
+session-local-echo = Local echo:
+session-local-echo-predict = Show the typed text right away
+session-local-echo-hint = Over a slow connection, the printable characters typed at the end of a line are shown, underlined, before the server echoes them back. Full screen applications are left alone
+session-alt-key = Alt key:
+session-alt-esc-prefix = Send ESC before the key
+session-alt-esc-prefix-hint = Alt+key sends ESC then the key, as most shells and editors expect. Otherwise the key is sent with its eighth bit set
+session-altgr-as-alt = Treat AltGr as Alt
+session-altgr-as-alt-hint = AltGr, reported as Ctrl+Alt, is Alt too, rather than typing the characters of the keyboard layout
+session-option-as-meta = Use Option as Meta
+session-option-as-meta-hint = Option+key is Meta+key, rather than typing the special characters of the keyboard layout
+session-char-width = Char width:
+session-ambiguous-wide = Ambiguous chars are wide
+session-ambiguous-wide-hint = East Asian ambiguous chars, e.g. · or ○, take two cells, as the applications of a host in a CJK locale expect
+session-narrow-emoji = Emoji are narrow
+session-narrow-emoji-hint = Emoji take a single cell, as the applications of older hosts expect. The cursor lands where they expect it with the width they assume
+session-idle-timeout = Idle timeout (min):
+session-idle-timeout-default = App setting
+session-idle-timeout-hint = Minutes without input nor output before the connection is closed, the setting of the app when 0
+session-mac-address = MAC address:
+session-mac-address-hint = Optional, to wake the host up on the local network (Wake-on-LAN)
+session-nested-command = Nested command:
+session-nested-command-hint = Optional, run once connected to reach an inner host or device, e.g. ssh inner-host, telnet 10.0.0.5 or picocom /dev/ttyUSB0. The tab is named after its target
+session-wake = Wake host
+session-diagnose = Diagnose network
+session-advanced = Advanced
+session-compression = Compression:
+session-compression-hint = Compress the traffic, it helps on slow links
+session-ciphers = Ciphers:
+session-ciphers-hint = Comma separated ciphers by preference, the ssh defaults when empty
+session-kex = Key Exchange:
+session-kex-hint = Comma separated key exchange algorithms by preference, e.g. for legacy devices
+session-locale = Locale:
+session-locale-auto = Auto
+session-locale-hint = LANG of the shell, e.g. de_DE.UTF-8. When empty, the best UTF-8 locale of the host is picked once connected
+session-tunnels = Tunnels
+session-tunnels-hint = Ports forwarded once connected, one per line: L 8080:localhost:80 forwards a local port to an address reached from the host, with nc or bash run on the host for each connection. Remote (R) and SOCKS (D) forwards aren't supported
+auth-password = Password
+auth-ssh-config = SSH Config
+auth-public-key = Public Key
+tint-none = None
+tint-background = Background
+tint-border = Border
+theme-editor = Theme Editor
+theme-true-color = Truecolor:
+theme-true-color-hint = How the 24-bit colors of the programs are shown
+theme-true-color-exact = Exact
+theme-true-color-ansi256 = Nearest of the 256 colors
+theme-reset-ansi256 = Reset the 256 colors
+theme-reset = Reset the theme
+theme-foreground = Foreground
+theme-background = Background
+theme-selection = Selection
+theme-ansi256 = 256 colors
+theme-color = Color { $index }
+theme-presets = Presets
+theme-preset-default = Default
+theme-preset-light = Light
+theme-preset-color-blind = Color-blind safe
+theme-preset-color-blind-light = Color-blind safe, light
+color-filter-none = Normal
+color-filter-simulate-protanopia = Simulate protanopia
+color-filter-simulate-deuteranopia = Simulate deuteranopia
+color-filter-compensate-protanopia = Compensate protanopia
+color-filter-compensate-deuteranopia = Compensate deuteranopia
+theme-auto = Auto
+theme-dark = Dark
+theme-light = Light
+compression-default = Default
+compression-on = On
+compression-off = Off
+error-empty-credentials = `username` and `password` cannot be empty in `Password` mode
+error-empty-identity = `username` and `identity file` cannot be empty in `Public Key` mode
+error-invalid-mac = Invalid MAC address `{ $mac }`, expected e.g. aa:bb:cc:dd:ee:ff
+error-session-exists = `group` and `name` already exist, please choose another name.
+error-template-name = A template needs a name.
+error-invalid-shortcut = invalid shortcut `{ $shortcut }`
+error-invalid-forward = invalid port forward `{ $forward }`, e.g. L 8080:localhost:80
+error-unsupported-forward = port forward `{ $forward }` isn't supported, only L forwards are
+
+## Terminal
+
+term-copy = Copy
+term-copy-as-html = Copy as HTML
+term-copy-as-ansi = Copy as ANSI
+term-paste = Paste
+term-select-all = Select All
+term-find = Find
+term-save-scrollback = Save Scrollback to File
+term-wrap-lines = Wrap Lines
+term-wrap-lines-hint = Scroll long lines horizontally when off, the shell then gets 512 columns. Full-screen programs keep the width of the view
+term-match-case = Match case
+term-use-regex = Use regular expression
+term-previous-match = Previous match (Shift+Enter)
+term-next-match = Next match (Enter)
+term-close-search = Close (Esc)
+term-invalid-regex = Invalid regex
+term-no-matches = No matches
+# `{current}` and `{count}` are replaced by the terminal widget
+term-matches = {"{"}current{"}"}/{"{"}count{"}"} matches
+term-send-key-prompt = Send {"{"}shortcut{"}"} to the terminal?
+term-send-key = Send (Enter)
+term-cancel-key = Cancel (Esc)
+# `{count}` and `{code}` are replaced by the terminal widget
+term-output-lines = {"{"}count{"}"} lines
+term-exit-code = exit {"{"}code{"}"}
+term-backend-error = The terminal backend failed: {"{"}error{"}"}
+term-retry = Retry
+term-dismiss = Dismiss
+term-close-tab = Close
+
+## SFTP explorer
+
+sftp-preview-placeholder = Select a file to preview it
+sftp-delete-title = Delete permanently
+sftp-delete-file = { $path } will be deleted, this can't be undone.
+sftp-delete-dir = { $path } and everything it contains will be deleted, this can't be undone.
+sftp-delete = Delete
+sftp-cancel = Cancel
+sftp-trashed = { $name } moved to the trash
+sftp-undo = Undo
+sftp-transfers = Transfers
+sftp-clear-finished = Clear finished
+sftp-cancel-all = Cancel all
+sftp-parallel = Parallel
+sftp-parallel-hint = Files transferred in parallel for this host
+sftp-download-dir-hint = Download directory
+sftp-drop-hint = Drop files here to upload them to the current directory
+sftp-queued = Queued
+sftp-resumed = Resumed at { $size }
+sftp-done = Done
+sftp-cancelled = Cancelled
+sftp-parent-dir = Parent directory
+sftp-refresh = Refresh
+sftp-preview-hint = Preview the selected file
+sftp-open-terminal-hint = Open a terminal in this directory
+sftp-sync-hint = Synchronize with a local directory
+sftp-trash = Trash
+sftp-bookmarks = Bookmarks
+sftp-clear-search = Clear search
+sftp-search-hint = name or glob, e.g. *.log
+sftp-remove-bookmark = Remove Bookmark
+sftp-add-bookmark = Bookmark This Directory
+sftp-column-name = Name
+sftp-column-size = Size
+sftp-column-modified = Modified
+sftp-column-permissions = Permissions
+sftp-download = Download
+sftp-move-to-trash = Move to Trash
+sftp-delete-permanently = Delete Permanently…
+preview-directory = Directory
+preview-image-too-large = Image too large to preview
+preview-truncated = First { $size } shown
+
+## Synchronization
+
+sync-title = Synchronize
+sync-local-dir = Local directory
+sync-remote-dir = Remote directory
+sync-direction = Direction
+sync-upload = Local → Remote
+sync-download = Remote → Local
+sync-delete = Delete files missing in the source
+sync-dry-run = Dry run
+sync-apply = Synchronize
+sync-apply-hint = Apply the dry run, files are copied by the transfer queue
+sync-nothing = Already in sync, nothing to do.
+sync-summary = { $copies } files to copy ({ $size }), { $deletes } to delete
+
+## Trash
+
+trash-title = Trash
+trash-count = { $count } items in ~/.nxshell-trash
+trash-empty-button = Empty trash
+trash-empty-hint = Delete every item for good
+trash-empty = The trash is empty
+trash-empty-title = Empty trash
+trash-empty-confirm = The { $count } items in the trash will be deleted, this can't be undone.
+trash-purge-hint = Delete for good
+trash-restore-hint = Restore
+
+## Connection errors
+
+connect-error-dns = Host not found
+connect-error-dns-hint = { $host } could not be resolved. Check the spelling of the host and your DNS settings.
+connect-error-no-config-host = Host not in the ssh config
+connect-error-no-config-host-hint = { $host } is neither a known name nor a Host of ~/.ssh/config. Add it to the config, or use its address.
+connect-error-timeout = Connection timed out
+connect-error-timeout-hint = { $host } didn't answer. Check that it is up, and that no firewall or VPN drops the connection.
+connect-error-refused = Connection refused
+connect-error-refused-hint = { $host } is up, but nothing listens on this port. Check that sshd runs, and the port of the session.
+connect-error-auth = Authentication failed
+connect-error-auth-hint = { $host } rejected the credentials. Check the user and the password, or the keys of ~/.ssh/config.
+connect-error-host-key = Host key mismatch
+connect-error-host-key-hint = The key of { $host } is not the one trusted. Make sure the host was reinstalled before trusting its new key.
+connect-error-other = Connection failed
+connect-error-other-hint = See the details below.
+connect-error-details = Details
+connect-error-credentials = Other credentials
+connect-error-user = User
+connect-error-password = Password
+connect-error-passphrase = Passphrase
+connect-error-retry = Retry
+connect-error-retry-credentials = Retry with these credentials
+connect-error-close = Close
+
+## Workspaces
+
+workspaces = Workspaces
+workspace-suggestion-title = Save as workspace?
+workspace-suggestion = You often open { $sessions } together. Save them as a workspace to open them at once?
+workspace-name-hint = Name of the workspace
+workspace-save = Save
+workspace-dismiss = Don't ask again
+workspace-open = Open all sessions
+workspace-delete = Delete workspace
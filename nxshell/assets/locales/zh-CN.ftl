@@ -0,0 +1,506 @@
+## Main window
+
+sessions = 会话
+sessions-loading = 正在加载会话…
+host-up = 主机在线
+host-down = 主机不可达
+host-unknown = 尚未探测
+open-terminal = 打开终端
+open-sftp = 打开 SFTP
+open-sftp-here = 在此处打开 SFTP
+cd-to-bookmark = 切换到书签目录
+term-theme = 主题
+tab-local = 本地
+tab-sessions = 会话
+tab-pin = 固定标签页
+tab-unpin = 取消固定
+tab-encoding = 字符编码
+tab-split-right = 向右拆分
+tab-split-down = 向下拆分
+tab-exited = [进程已退出] — 按 Enter 关闭
+tab-exited-code = [进程已退出，退出码 { $code }] — 按 Enter 关闭
+tab-exited-close = 关闭
+tab-tunnels = 隧道：
+tunnel-failed = { $forward } 失败：{ $error }
+backend-unresponsive = 终端后端无响应
+backend-unresponsive-hint = 输出和输入已停滞，重启后将获得新的 shell
+backend-restart = 重启
+memory-trimmed = 已裁剪 { $count } 个标签页的回滚历史，以保持在内存上限内
+idle-warning = { $name } 处于空闲状态，将在一分钟后断开
+idle-disconnected = 已断开 { $name }，空闲时间过长
+idle-banner = 空闲时间过长，此连接将在一分钟后关闭
+idle-stay-connected = 保持连接
+paused-banner = 输出已暂停 — 按 Ctrl+Q 恢复
+paused-resume = 恢复
+wake-no-mac = { $name } 没有 MAC 地址，请在会话中设置以唤醒主机
+wake-sent = 正在唤醒 { $name }，主机启动后将自动连接
+wake-failed = { $name } 未能唤醒
+connect-resolving = 正在解析…
+connect-authenticating = 正在认证…
+connect-touch-security-key = 请触摸安全密钥…
+connect-opening-pty = 正在打开终端…
+presentation-exit-hint = 按 { $shortcut } 退出演示模式
+privacy-blur-hint = 终端内容已隐藏，按 { $shortcut } 重新显示
+
+## Menu bar
+
+menu-session = 会话
+menu-new-session = 新建会话
+menu-new-terminal = 新建终端
+menu-import-sessions = 导入会话…
+menu-find = 在终端中查找
+menu-split-right = 向右拆分终端
+menu-split-down = 向下拆分终端
+menu-quit = 退出
+menu-window = 窗口
+menu-new-window = 新建窗口
+menu-presentation-mode = 演示模式
+menu-privacy-blur = 隐藏终端内容
+menu-tab-overview = 标签页概览
+menu-startup-window = 启动时
+startup-window-restore = 恢复上次的大小和位置
+startup-window-maximized = 最大化
+menu-tools = 工具
+menu-multi-exec = 多终端同步输入
+menu-exit-codes = 退出码
+menu-exit-codes-hint = 在每条命令旁显示其退出码，需要 Shell 集成
+menu-flash-failed-tabs = 命令失败时闪烁标签页
+menu-flash-failed-tabs-hint = 后台标签页中的命令失败时闪烁该标签页
+menu-keep-exited-tabs = 保留已退出的标签页
+menu-keep-exited-tabs-hint = 本地标签页的 Shell 退出后保持打开，以便查看最后的输出
+menu-durations = 显示耗时，不少于
+menu-durations-hint = 在耗时较长的命令输出末尾显示其耗时，需要 Shell 集成
+menu-scroll-speed = 滚轮每格滚动行数
+menu-scroll-speed-hint = 鼠标滚轮和触控板在终端中滚动的距离
+menu-natural-scroll = 自然滚动
+menu-natural-scroll-hint = 反转滚动方向，内容跟随手指移动
+menu-flow-control = 流控制 (Ctrl+S / Ctrl+Q)
+menu-flow-control-hint = 将 Ctrl+S 和 Ctrl+Q 发送给 shell，用于暂停和恢复输出
+menu-record-commands = 命令历史
+menu-record-commands-hint = 保存在提示符下运行的命令以便之后补全，其中的密钥会被遮盖。以空格开头的命令不会被保存
+menu-smooth-cursor = 平滑光标
+menu-smooth-cursor-hint = 光标带拖尾平滑移动到所在单元格，在大屏幕上更易跟踪其跳动
+menu-dim-inactive = 淡化非活动终端
+menu-dim-inactive-hint = 同时显示多个终端时，淡化未获得焦点的终端，并为获得焦点的终端加边框
+menu-focus-follows-mouse = 焦点跟随鼠标
+menu-focus-follows-mouse-hint = 鼠标指针下的终端无需点击即可接收按键，关闭后需点击终端才能输入
+menu-block-selection = 块选择
+menu-block-selection-hint = 拖动时按住以选择矩形区域，例如在 Alt+拖动会移动窗口的系统上。Ctrl+双击可扩展选区
+menu-bell = 响铃
+menu-bell-hint = 程序响铃时终端的反应，例如补全没有匹配项时
+block-selection-alt = Alt+拖动
+block-selection-ctrl-alt = Ctrl+Alt+拖动
+block-selection-shift-alt = Shift+Alt+拖动
+bell-visual = 闪烁终端
+bell-audible = 播放声音
+bell-none = 无
+menu-profiler = 渲染性能分析
+menu-profiler-hint = 显示帧耗时以及每个终端的渲染开销
+menu-scrollback-lines = 回滚缓冲区行数
+menu-scrollback-lines-hint = 之后打开的终端保留的历史行数
+menu-memory-budget = 回滚缓冲区内存上限
+menu-memory-budget-hint = 终端占用的内存超过该值时，裁剪最久未显示的标签页的回滚历史
+menu-idle-timeout = 空闲 SSH 标签页断开时间
+menu-idle-timeout-hint = 关闭在此时长内没有输入和输出的 SSH 连接，断开前一分钟会发出警告
+menu-host-badges = 探测主机，间隔
+menu-host-badges-hint = 通过与 SSH 端口的 TCP 握手，显示已保存会话的主机是否在线
+menu-host-probe-concurrency = 同时探测的主机数
+menu-presentation-font-scale = 演示模式字体缩放
+menu-language = 语言
+menu-terminal-font = 终端字体
+menu-theme-editor = 主题编辑器
+menu-color-filter = 色觉
+menu-color-filter-hint = 终端颜色的滤镜，用于检查主题或区分红色和绿色
+menu-bold-as-bright = 粗体显示为亮色
+menu-bold-as-bright-hint = 前 8 种颜色的粗体文本以亮色显示，与传统终端一致
+menu-bright-as-bold = 亮色显示为粗体
+menu-bright-as-bold-hint = 亮色文本以粗体显示
+menu-api = 自动化 API
+menu-output-log = 输出日志
+menu-export = 导出
+menu-redaction = 敏感信息遮盖
+font-family = 字体
+font-size = 字号
+font-line-height = 行高
+font-cell-padding = 字符间距
+font-cell-padding-hint = 每个字符单元额外增加的宽度（点）
+font-bundled = Maple Mono（内置）
+font-default-size = 默认
+fonts-loading = 正在加载字体…
+menu-help = 帮助
+menu-about = 关于
+menu-logs = 日志
+menu-export-diagnostics = 导出诊断信息
+menu-export-diagnostics-hint = 将最近的日志和崩溃报告打包，用于提交问题
+diagnostics-saved = 诊断信息已保存到 { $path }
+
+## Status bar
+
+status-latency-hint = 在已打开的会话上执行空命令的往返耗时，每 30 秒测量一次
+status-grid-hint = 列 × 行
+status-cursor = 行 { $line }，列 { $column }
+status-scroll-bottom = 底部
+status-scroll-hint = 向上滚动到历史记录中的行数
+status-multi-exec = 同步输入
+status-multi-exec-hint = 输入会发送到所有终端
+
+## Host key
+
+host-key-changed-title = 主机密钥已变更
+host-key-changed-message = { $host } 的主机密钥与会话保存的不一致。连接可能被拦截，或者主机已重装。
+host-key-expected = 预期
+host-key-found = 实际
+host-key-disconnect = 断开连接
+host-key-trust = 信任新密钥
+host-key-new-title = 未知的主机密钥
+host-key-new-message = 这是首次连接 { $host }。信任之前请确认下方的指纹与主机的一致，信任后它会保存到会话中。
+host-key-trust-new = 信任并连接
+
+## Quit
+
+quit-title = 退出 NxShell？
+quit-message = 以下连接将被关闭：
+quit-transfers = { $name }：{ $count } 个传输正在进行
+quit-cancel = 取消
+quit-force = 仍然退出
+
+## Keyboard-interactive authentication
+
+auth-prompt-title = 登录 { $host }
+auth-prompt-user = 用户 { $user }
+auth-prompt-cancel = 取消
+auth-prompt-submit = 登录
+auth-prompt-otp-hint = 此主机需要验证码，请准备好您的 OTP 设备
+
+## Tab overview
+
+overview-title = 终端
+overview-empty = 没有打开的终端
+
+## Logs
+
+logs = 日志
+logs-search = 搜索
+logs-copy = 复制
+logs-clear = 清空
+profiler = 渲染性能分析
+profiler-frame-time = 帧耗时
+profiler-frame-interval = 帧间隔
+profiler-tab = 标签页
+profiler-shapes = 图形数
+profiler-throughput = 输出
+profiler-hint = 图形数统计自标签页最后一次显示的那一帧。
+diagnose-title = 诊断 { $host }
+diagnose-last = 最近
+diagnose-min = 最小
+diagnose-avg = 平均
+diagnose-max = 最大
+diagnose-loss = 丢包
+diagnose-ping-hint = 每秒通过与 { $address } 的 TCP 握手进行探测
+diagnose-trace = 路由追踪
+diagnose-trace-failed = 无法运行 traceroute：{ $error }
+import-title = 导入会话
+import-path-hint = CSV 或 Ansible inventory 文件的路径
+import-format-csv = CSV
+import-format-inventory = Ansible Inventory
+import-load = 读取
+import-columns-hint = CSV 列：name, host, port, user, group。没有用户的主机使用 ssh 配置登录，密码在连接时询问。
+import-column-line = 行
+import-column-group = 分组
+import-column-name = 名称
+import-column-host = 主机
+import-column-port = 端口
+import-column-user = 用户
+import-column-status = 状态
+import-status-ok = 就绪
+import-summary = { $count } 个主机，跳过 { $invalid } 个
+import-save = 导入 { $count } 个会话
+import-done = 已导入 { $count } 个会话
+import-error-read = 读取文件失败：{ $error }
+import-error-host = 缺少主机
+import-error-port = 无效的端口 { $port }
+import-error-duplicate = 在分组中重复
+import-error-exists = 已保存
+api-enabled = 启用本地 HTTP API
+api-port = 端口：
+api-copy-token = 复制令牌
+api-token-copied = 令牌已复制
+api-new-token = 新令牌
+api-new-token-hint = 替换令牌，持有旧令牌的客户端将被拒绝
+api-hint = 为本机持有令牌的程序提供服务，例如
+    curl -H "Authorization: Bearer $TOKEN" http://127.0.0.1:{ $port }/tabs
+api-error-start = 启动 API 失败：{ $error }
+
+output-log-start = 记录当前终端
+output-log-stop = 停止记录
+output-log-no-terminal = 当前标签页没有终端
+output-log-collapse-progress = 折叠进度条
+output-log-collapse-progress-hint = 对于进度条和加载动画反复重绘的行，只记录其最终状态，而不是每次更新
+output-log-started = 正在将输出记录到 { $path }
+output-log-stopped = 输出已记录到 { $path }
+output-log-error = 记录输出失败：{ $error }
+export-view-image = 终端截图 (PNG)
+export-history-pdf = 打印历史记录 (PDF)
+export-saved = 已导出到 { $path }
+export-error = 导出失败：{ $error }
+
+redaction-on-screen = 在屏幕上遮盖敏感信息
+redaction-on-screen-hint = 终端中显示的敏感信息也会被遮盖，适用于共享屏幕等场景
+redaction-patterns-hint = 匹配这些正则表达式的敏感信息在复制的文本和输出日志中总是被遮盖。若表达式包含分组，则只遮盖分组的内容。
+redaction-add = 添加表达式
+redaction-remove = 删除该表达式
+redaction-reset = 恢复默认
+staging-title = 由 { $source } 建议，运行前请检查
+staging-run = 运行
+staging-run-hint = 按 Enter 在终端中运行，按 Escape 忽略
+staging-dismiss = 忽略
+
+## Session form
+
+session-form-title = 新建会话
+session-form-submit = 提交
+session-template = 模板：
+session-template-none = 无
+session-template-hint = 使用模板填充表单，其主机为待补全的前缀
+session-template-delete = 删除模板
+session-save-template = 保存为模板
+session-save-template-hint = 以当前名称将表单保存为模板，便于快速添加相似会话
+session-template-saved = 模板 { $name } 已保存
+session-duplicate = 复制
+session-copy-name = { $name }（副本）
+session-group = 分组：
+session-name = 名称：
+session-host = 主机：
+session-host-alias = 主机别名：
+session-auth-type = 认证方式：
+session-username = 用户名：
+session-password = 密码：
+session-identity-file = 私钥文件：
+session-passphrase = 私钥密码：
+session-passphrase-hint = 留空则在连接时询问
+session-tint = 着色：
+session-tint-hint = 为此会话的标签页着色，例如用于区分生产环境主机
+session-theme = 主题：
+session-theme-hint = 终端的配色，自动模式在主机设置浅色背景时切换为浅色
+session-protected-keys = 受保护按键：
+session-protected-keys-hint = 发送这些按键前需要确认
+session-font = 字体：
+session-font-hint = 此会话终端使用的字体，未设置时使用默认字体
+session-highlight = 高亮：
+session-highlight-prompt = 高亮命令行
+session-highlight-hint = 为提示符处输入的命令、选项和引号字符串着色，需要 Shell 集成（OSC 133）
+session-local-echo = 本地回显：
+session-local-echo-predict = 立即显示输入的文本
+session-local-echo-hint = 在慢速连接下，行尾输入的可打印字符会在服务器回显前以下划线显示，全屏应用不受影响
+session-alt-key = Alt 键：
+session-alt-esc-prefix = 在按键前发送 ESC
+session-alt-esc-prefix-hint = Alt+键 先发送 ESC 再发送该键，这是大多数 shell 和编辑器所期望的。否则发送设置了第八位的按键
+session-altgr-as-alt = 将 AltGr 视为 Alt
+session-altgr-as-alt-hint = AltGr（报告为 Ctrl+Alt）也视为 Alt，而不是输入键盘布局中的字符
+session-option-as-meta = 将 Option 用作 Meta
+session-option-as-meta-hint = Option+键 即 Meta+键，而不是输入键盘布局中的特殊字符
+session-char-width = 字符宽度：
+session-ambiguous-wide = 歧义字符为宽字符
+session-ambiguous-wide-hint = 东亚歧义字符（如 · 或 ○）占两个单元格，这是 CJK 区域设置的主机上的应用所期望的
+session-narrow-emoji = Emoji 为窄字符
+session-narrow-emoji-hint = Emoji 占一个单元格，这是较旧主机上的应用所期望的。使用应用假定的宽度，光标才会落在它们期望的位置
+session-idle-timeout = 空闲超时（分钟）：
+session-idle-timeout-default = 使用应用设置
+session-idle-timeout-hint = 没有输入和输出多少分钟后关闭连接，为 0 时使用应用的设置
+session-mac-address = MAC 地址：
+session-mac-address-hint = 可选，用于在局域网内唤醒主机（网络唤醒）
+session-nested-command = 嵌套命令：
+session-nested-command-hint = 可选，连接后运行以进入内部主机或设备，例如 ssh inner-host、telnet 10.0.0.5 或 picocom /dev/ttyUSB0，标签页以其目标命名
+session-wake = 唤醒主机
+session-diagnose = 网络诊断
+session-advanced = 高级
+session-compression = 压缩：
+session-compression-hint = 压缩传输数据，适用于慢速网络
+session-ciphers = 加密算法：
+session-ciphers-hint = 以逗号分隔，按优先级排列，留空则使用 ssh 默认值
+session-kex = 密钥交换：
+session-kex-hint = 以逗号分隔的密钥交换算法，按优先级排列，例如用于旧设备
+session-locale = 区域设置：
+session-locale-auto = 自动
+session-locale-hint = shell 的 LANG，例如 zh_CN.UTF-8。留空时，连接后自动选择主机上最合适的 UTF-8 区域设置
+session-tunnels = 隧道
+session-tunnels-hint = 连接后转发的端口，每行一个：L 8080:localhost:80 将本地端口转发到从主机可达的地址，每个连接在主机上运行 nc 或 bash。不支持远程（R）和 SOCKS（D）转发
+auth-password = 密码
+auth-ssh-config = SSH 配置
+auth-public-key = 公钥
+tint-none = 无
+tint-background = 背景
+tint-border = 边框
+theme-editor = 主题编辑器
+theme-true-color = 真彩色：
+theme-true-color-hint = 程序输出的 24 位颜色的显示方式
+theme-true-color-exact = 原样显示
+theme-true-color-ansi256 = 最接近的 256 色
+theme-reset-ansi256 = 重置 256 色
+theme-reset = 重置主题
+theme-foreground = 前景色
+theme-background = 背景色
+theme-selection = 选中色
+theme-ansi256 = 256 色
+theme-color = 颜色 { $index }
+theme-presets = 预设
+theme-preset-default = 默认
+theme-preset-light = 浅色
+theme-preset-color-blind = 色盲友好
+theme-preset-color-blind-light = 色盲友好（浅色）
+color-filter-none = 正常
+color-filter-simulate-protanopia = 模拟红色盲
+color-filter-simulate-deuteranopia = 模拟绿色盲
+color-filter-compensate-protanopia = 补偿红色盲
+color-filter-compensate-deuteranopia = 补偿绿色盲
+theme-auto = 自动
+theme-dark = 深色
+theme-light = 浅色
+compression-default = 默认
+compression-on = 开启
+compression-off = 关闭
+error-empty-credentials = `密码` 模式下 `用户名` 和 `密码` 不能为空
+error-empty-identity = `公钥` 模式下 `用户名` 和 `私钥文件` 不能为空
+error-invalid-mac = 无效的 MAC 地址 `{ $mac }`，示例：aa:bb:cc:dd:ee:ff
+error-session-exists = `分组` 和 `名称` 已存在，请换一个名称。
+error-template-name = 模板需要名称。
+error-invalid-shortcut = 无效的快捷键 `{ $shortcut }`
+error-invalid-forward = 无效的端口转发 `{ $forward }`，例如 L 8080:localhost:80
+error-unsupported-forward = 不支持端口转发 `{ $forward }`，仅支持 L 转发
+
+## Terminal
+
+term-copy = 复制
+term-copy-as-html = 复制为 HTML
+term-copy-as-ansi = 复制为 ANSI
+term-paste = 粘贴
+term-select-all = 全选
+term-find = 查找
+term-save-scrollback = 保存回滚内容到文件
+term-wrap-lines = 自动换行
+term-wrap-lines-hint = 关闭后长行可横向滚动，shell 的宽度为 512 列。全屏程序保持视图的宽度
+term-match-case = 区分大小写
+term-use-regex = 使用正则表达式
+term-previous-match = 上一个匹配 (Shift+Enter)
+term-next-match = 下一个匹配 (Enter)
+term-close-search = 关闭 (Esc)
+term-invalid-regex = 无效的正则表达式
+term-no-matches = 无匹配
+# `{current}` and `{count}` are replaced by the terminal widget
+term-matches = 第 {"{"}current{"}"}/{"{"}count{"}"} 个匹配
+term-send-key-prompt = 发送 {"{"}shortcut{"}"} 到终端？
+term-send-key = 发送 (Enter)
+term-cancel-key = 取消 (Esc)
+# `{count}` and `{code}` are replaced by the terminal widget
+term-output-lines = {"{"}count{"}"} 行
+term-exit-code = 退出码 {"{"}code{"}"}
+term-backend-error = 终端后端出错：{"{"}error{"}"}
+term-retry = 重试
+term-dismiss = 忽略
+term-close-tab = 关闭
+
+## SFTP explorer
+
+sftp-preview-placeholder = 选择一个文件进行预览
+sftp-delete-title = 永久删除
+sftp-delete-file = { $path } 将被删除，此操作无法撤销。
+sftp-delete-dir = { $path } 及其所有内容将被删除，此操作无法撤销。
+sftp-delete = 删除
+sftp-cancel = 取消
+sftp-trashed = { $name } 已移到回收站
+sftp-undo = 撤销
+sftp-transfers = 传输
+sftp-clear-finished = 清除已完成
+sftp-cancel-all = 全部取消
+sftp-parallel = 并行数
+sftp-parallel-hint = 此主机同时传输的文件数
+sftp-download-dir-hint = 下载目录
+sftp-drop-hint = 将文件拖放到此处，上传到当前目录
+sftp-queued = 等待中
+sftp-resumed = 从 { $size } 处续传
+sftp-done = 完成
+sftp-cancelled = 已取消
+sftp-parent-dir = 上级目录
+sftp-refresh = 刷新
+sftp-preview-hint = 预览选中的文件
+sftp-open-terminal-hint = 在此目录打开终端
+sftp-sync-hint = 与本地目录同步
+sftp-trash = 回收站
+sftp-bookmarks = 书签
+sftp-clear-search = 清除搜索
+sftp-search-hint = 名称或通配符，例如 *.log
+sftp-remove-bookmark = 移除书签
+sftp-add-bookmark = 将此目录加入书签
+sftp-column-name = 名称
+sftp-column-size = 大小
+sftp-column-modified = 修改时间
+sftp-column-permissions = 权限
+sftp-download = 下载
+sftp-move-to-trash = 移到回收站
+sftp-delete-permanently = 永久删除…
+preview-directory = 目录
+preview-image-too-large = 图片过大，无法预览
+preview-truncated = 仅显示前 { $size }
+
+## Synchronization
+
+sync-title = 同步
+sync-local-dir = 本地目录
+sync-remote-dir = 远程目录
+sync-direction = 方向
+sync-upload = 本地 → 远程
+sync-download = 远程 → 本地
+sync-delete = 删除源目录中不存在的文件
+sync-dry-run = 预演
+sync-apply = 同步
+sync-apply-hint = 执行预演的结果，文件由传输队列复制
+sync-nothing = 已经同步，无需操作。
+sync-summary = 需复制 { $copies } 个文件（{ $size }），需删除 { $deletes } 个
+
+## Trash
+
+trash-title = 回收站
+trash-count = ~/.nxshell-trash 中有 { $count } 项
+trash-empty-button = 清空回收站
+trash-empty-hint = 永久删除所有项目
+trash-empty = 回收站是空的
+trash-empty-title = 清空回收站
+trash-empty-confirm = 回收站中的 { $count } 项将被删除，此操作无法撤销。
+trash-purge-hint = 永久删除
+trash-restore-hint = 恢复
+
+## Connection errors
+
+connect-error-dns = 找不到主机
+connect-error-dns-hint = 无法解析 { $host }。请检查主机拼写和 DNS 设置。
+connect-error-no-config-host = ssh 配置中没有该主机
+connect-error-no-config-host-hint = { $host } 既不是已知的主机名，也不是 ~/.ssh/config 中的 Host。请将其加入配置，或使用其地址。
+connect-error-timeout = 连接超时
+connect-error-timeout-hint = { $host } 没有响应。请检查主机是否在线，以及防火墙或 VPN 是否丢弃了连接。
+connect-error-refused = 连接被拒绝
+connect-error-refused-hint = { $host } 在线，但该端口没有服务监听。请检查 sshd 是否运行，以及会话的端口。
+connect-error-auth = 认证失败
+connect-error-auth-hint = { $host } 拒绝了凭据。请检查用户名和密码，或 ~/.ssh/config 中的密钥。
+connect-error-host-key = 主机密钥不匹配
+connect-error-host-key-hint = { $host } 的密钥不是已信任的密钥。请确认主机已重装后再信任其新密钥。
+connect-error-other = 连接失败
+connect-error-other-hint = 详见下方信息。
+connect-error-details = 详细信息
+connect-error-credentials = 其他凭据
+connect-error-user = 用户名
+connect-error-password = 密码
+connect-error-passphrase = 私钥密码
+connect-error-retry = 重试
+connect-error-retry-credentials = 使用这些凭据重试
+connect-error-close = 关闭
+
+## Workspaces
+
+workspaces = 工作区
+workspace-suggestion-title = 保存为工作区？
+workspace-suggestion = 你经常同时打开 { $sessions }。要将它们保存为工作区以便一次打开吗？
+workspace-name-hint = 工作区名称
+workspace-save = 保存
+workspace-dismiss = 不再询问
+workspace-open = 打开所有会话
+workspace-delete = 删除工作区
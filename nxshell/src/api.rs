@@ -0,0 +1,508 @@
+//! Opt-in HTTP API on localhost, to drive nxshell from scripts and launchers,
+//! e.g. Raycast or AutoHotkey.
+//!
+//! | Request                 | Body                                          |
+//! |-------------------------|-----------------------------------------------|
+//! | `GET /sessions`         |                                               |
+//! | `POST /sessions/open`   | `{"group": "", "name": "db"}`                 |
+//! | `GET /tabs`             |                                               |
+//! | `GET /tabs/{id}`        |                                               |
+//! | `POST /tabs/{id}/input` | `{"text": "uptime\n"}`                        |
+//! | `POST /tabs/{id}/stage` | `{"command": "df -h", "source": "assistant"}` |
+//!
+//! A staged command waits above the terminal until the user runs it, so that
+//! an assistant, e.g. behind an MCP server, only proposes commands.
+//!
+//! Every request carries the token of the settings, `Authorization: Bearer
+//! <token>`. Requests of web pages, which set an `Origin`, are refused.
+
+use serde_json::{json, Value};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// Keys of the API settings in the app storage.
+const API_ENABLED_KEY: &str = "api_enabled";
+const API_PORT_KEY: &str = "api_port";
+const API_TOKEN_KEY: &str = "api_token";
+
+pub const DEFAULT_API_PORT: u16 = 7870;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// The UI thread answers on its next frame, unless it is stuck.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_HEADER_LEN: usize = 8 * 1024;
+const MAX_BODY_LEN: usize = 64 * 1024;
+
+/// Settings of the API, saved with the app state. It is off by default.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: String,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_API_PORT,
+            token: String::new(),
+        }
+    }
+}
+
+impl ApiSettings {
+    pub fn restore(storage: &dyn eframe::Storage) -> Self {
+        let mut settings = Self {
+            enabled: storage.get_string(API_ENABLED_KEY).as_deref() == Some("true"),
+            token: storage.get_string(API_TOKEN_KEY).unwrap_or_default(),
+            ..Default::default()
+        };
+        if let Some(port) = storage
+            .get_string(API_PORT_KEY)
+            .and_then(|p| p.parse().ok())
+        {
+            settings.port = port;
+        }
+        settings
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        storage.set_string(API_ENABLED_KEY, self.enabled.to_string());
+        storage.set_string(API_PORT_KEY, self.port.to_string());
+        storage.set_string(API_TOKEN_KEY, self.token.clone());
+    }
+}
+
+/// Random token for the clients, in hex.
+pub fn generate_token() -> Result<String, orion::errors::UnknownCryptoError> {
+    let key = orion::aead::SecretKey::generate(24)?;
+    Ok(key
+        .unprotected_as_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+/// Operation asked by a client, carried out on the UI thread.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiCall {
+    ListSessions,
+    OpenSession {
+        group: String,
+        name: String,
+    },
+    ListTabs,
+    TabStatus(u64),
+    SendText {
+        tab: u64,
+        text: String,
+    },
+    StageCommand {
+        tab: u64,
+        command: String,
+        source: String,
+    },
+}
+
+pub struct ApiRequest {
+    pub call: ApiCall,
+    pub reply: Sender<ApiResponse>,
+}
+
+impl ApiRequest {
+    pub fn reply(self, response: ApiResponse) {
+        // the client may have given up
+        let _ = self.reply.send(response);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl ApiResponse {
+    pub fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    pub fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.into() }),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Listens on `127.0.0.1` on a background thread, which is stopped and joined
+/// once the server is dropped, freeing the port.
+pub struct ApiServer {
+    requests: Receiver<ApiRequest>,
+    address: SocketAddr,
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ApiServer {
+    pub fn spawn(ctx: egui::Context, port: u16, token: String) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let address = listener.local_addr()?;
+        let (sender, requests) = mpsc::channel();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let server = Endpoint {
+            ctx,
+            port: address.port(),
+            token,
+            sender,
+            stopped: stopped.clone(),
+        };
+        let thread = std::thread::Builder::new()
+            .name("api_server".to_string())
+            .spawn(move || server.serve(listener))?;
+        info!("api listening on {address}");
+        Ok(Self {
+            requests,
+            address,
+            stopped,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn try_recv(&self) -> Option<ApiRequest> {
+        self.requests.try_recv().ok()
+    }
+}
+
+impl Drop for ApiServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        // the client being served is answered right away, rather than once
+        // its call times out
+        let (_, closed) = mpsc::channel();
+        drop(std::mem::replace(&mut self.requests, closed));
+        // wake up the blocked `accept`
+        let _ = TcpStream::connect_timeout(&self.address, READ_TIMEOUT);
+        // the listener is closed with the thread
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                error!("the api thread panicked");
+            }
+        }
+    }
+}
+
+/// Listening side of [`ApiServer`].
+struct Endpoint {
+    ctx: egui::Context,
+    port: u16,
+    token: String,
+    sender: Sender<ApiRequest>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl Endpoint {
+    fn serve(self, listener: TcpListener) {
+        for stream in listener.incoming() {
+            if self.stopped.load(Ordering::Relaxed) {
+                break;
+            }
+            // a client at a time, the calls are quick
+            let result = stream.and_then(|stream| self.handle(stream));
+            if let Err(err) = result {
+                error!("api connection error: {err}");
+            }
+        }
+    }
+
+    fn handle(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut reader = BufReader::new(&stream);
+        let response = match read_request(&mut reader)? {
+            Ok(request) => self.respond(request),
+            Err(response) => response,
+        };
+        write_response(&stream, &response)
+    }
+
+    fn respond(&self, request: HttpRequest) -> ApiResponse {
+        if let Err(response) = authorize(&request, self.port, &self.token) {
+            return response;
+        }
+        let call = match route(&request.method, &request.path, &request.body) {
+            Ok(call) => call,
+            Err(response) => return response,
+        };
+        let (reply, response) = mpsc::channel();
+        if self.sender.send(ApiRequest { call, reply }).is_err() {
+            return ApiResponse::error(503, "nxshell is closing");
+        }
+        self.ctx.request_repaint();
+        response
+            .recv_timeout(REPLY_TIMEOUT)
+            .unwrap_or_else(|_| ApiResponse::error(503, "nxshell didn't answer"))
+    }
+}
+
+#[derive(Debug, Default)]
+struct HttpRequest {
+    method: String,
+    path: String,
+    /// Names in lowercase.
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// The request, or the response to a malformed one.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Result<HttpRequest, ApiResponse>> {
+    let mut request = HttpRequest::default();
+    let mut header_len = 0;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(Err(ApiResponse::error(400, "incomplete request")));
+        }
+        header_len += line.len();
+        if header_len > MAX_HEADER_LEN {
+            return Ok(Err(ApiResponse::error(413, "headers too long")));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if request.method.is_empty() {
+            let mut parts = line.split_whitespace();
+            request.method = parts.next().unwrap_or_default().to_string();
+            request.path = parts.next().unwrap_or_default().to_string();
+        } else if let Some((name, value)) = line.split_once(':') {
+            let header = (name.trim().to_ascii_lowercase(), value.trim().to_string());
+            request.headers.push(header);
+        }
+    }
+    let len = match request.header("content-length").map(str::parse::<usize>) {
+        None => 0,
+        Some(Ok(len)) if len <= MAX_BODY_LEN => len,
+        Some(Ok(_)) => return Ok(Err(ApiResponse::error(413, "body too long"))),
+        Some(Err(_)) => return Ok(Err(ApiResponse::error(400, "invalid content length"))),
+    };
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+    match String::from_utf8(body) {
+        Ok(body) => request.body = body,
+        Err(_) => return Ok(Err(ApiResponse::error(400, "the body isn't utf-8"))),
+    }
+    Ok(Ok(request))
+}
+
+fn write_response(mut stream: &TcpStream, response: &ApiResponse) -> io::Result<()> {
+    let body = response.body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        response.status,
+        response.reason(),
+        body.len()
+    )?;
+    stream.flush()
+}
+
+/// Only local programs holding the token are served. Web pages could reach
+/// the port too, through the browser or a domain resolving to `127.0.0.1`.
+fn authorize(request: &HttpRequest, port: u16, token: &str) -> Result<(), ApiResponse> {
+    if request.header("origin").is_some() {
+        return Err(ApiResponse::error(403, "requests of web pages are refused"));
+    }
+    let local_host = [format!("127.0.0.1:{port}"), format!("localhost:{port}")];
+    if !request
+        .header("host")
+        .is_some_and(|host| local_host.iter().any(|local| local == host))
+    {
+        return Err(ApiResponse::error(403, "unexpected host"));
+    }
+    let bearer = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match bearer {
+        Some(bearer) if !token.is_empty() && same_token(bearer.trim(), token) => Ok(()),
+        _ => Err(ApiResponse::error(401, "missing or wrong token")),
+    }
+}
+
+/// Compared in a constant time.
+fn same_token(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn route(method: &str, path: &str, body: &str) -> Result<ApiCall, ApiResponse> {
+    let path = path.split('?').next().unwrap_or_default();
+    let segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let tab_id = |id: &str| {
+        id.parse::<u64>()
+            .map_err(|_| ApiResponse::error(404, format!("no tab {id}")))
+    };
+    let call = match (method, segments.as_slice()) {
+        ("GET", ["sessions"]) => ApiCall::ListSessions,
+        ("POST", ["sessions", "open"]) => {
+            let body = json_body(body)?;
+            ApiCall::OpenSession {
+                group: string_field(&body, "group").unwrap_or_default(),
+                name: string_field(&body, "name")
+                    .ok_or_else(|| ApiResponse::error(400, "`name` is missing"))?,
+            }
+        }
+        ("GET", ["tabs"]) => ApiCall::ListTabs,
+        ("GET", ["tabs", id]) => ApiCall::TabStatus(tab_id(id)?),
+        ("POST", ["tabs", id, "input"]) => ApiCall::SendText {
+            tab: tab_id(id)?,
+            text: string_field(&json_body(body)?, "text")
+                .ok_or_else(|| ApiResponse::error(400, "`text` is missing"))?,
+        },
+        ("POST", ["tabs", id, "stage"]) => {
+            let body = json_body(body)?;
+            let command = string_field(&body, "command")
+                .ok_or_else(|| ApiResponse::error(400, "`command` is missing"))?;
+            // escape sequences and line breaks would run more than what is shown
+            if command.trim().is_empty() || command.chars().any(char::is_control) {
+                return Err(ApiResponse::error(
+                    400,
+                    "`command` must be a single line of text",
+                ));
+            }
+            ApiCall::StageCommand {
+                tab: tab_id(id)?,
+                command,
+                source: string_field(&body, "source").unwrap_or_else(|| "assistant".to_string()),
+            }
+        }
+        (
+            _,
+            ["sessions"]
+            | ["sessions", "open"]
+            | ["tabs"]
+            | ["tabs", _]
+            | ["tabs", _, "input" | "stage"],
+        ) => {
+            return Err(ApiResponse::error(405, format!("{method} isn't allowed")));
+        }
+        _ => return Err(ApiResponse::error(404, format!("no route {path}"))),
+    };
+    Ok(call)
+}
+
+fn json_body(body: &str) -> Result<Value, ApiResponse> {
+    serde_json::from_str(body).map_err(|err| ApiResponse::error(400, err.to_string()))
+}
+
+fn string_field(body: &Value, field: &str) -> Option<String> {
+    body.get(field).and_then(Value::as_str).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(text: &str) -> Result<HttpRequest, ApiResponse> {
+        read_request(&mut text.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn parse_and_authorize_requests() {
+        let text = "POST /tabs/3/input HTTP/1.1\r\nHost: 127.0.0.1:7870\r\n\
+                    Authorization: Bearer secret\r\nContent-Length: 20\r\n\r\n{\"text\": \"uptime\\n\"}";
+        let parsed = request(text).unwrap();
+        assert_eq!(parsed.header("content-length"), Some("20"));
+        assert_eq!(authorize(&parsed, 7870, "secret"), Ok(()));
+        assert_eq!(
+            route(&parsed.method, &parsed.path, &parsed.body),
+            Ok(ApiCall::SendText {
+                tab: 3,
+                text: "uptime\n".to_string()
+            })
+        );
+
+        let status = |result: Result<(), ApiResponse>| result.unwrap_err().status;
+        assert_eq!(status(authorize(&parsed, 7870, "other")), 401);
+        assert_eq!(status(authorize(&parsed, 7870, "")), 401);
+        // a domain rebound to the loopback address
+        let rebound = request("GET /tabs HTTP/1.1\r\nHost: evil.example:7870\r\n\r\n").unwrap();
+        assert_eq!(status(authorize(&rebound, 7870, "secret")), 403);
+        let browser = request(
+            "GET /tabs HTTP/1.1\r\nHost: localhost:7870\r\nOrigin: https://example.com\r\n\
+             Authorization: Bearer secret\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(status(authorize(&browser, 7870, "secret")), 403);
+
+        let too_long = request("POST /sessions/open HTTP/1.1\r\nContent-Length: 999999\r\n\r\n");
+        assert_eq!(too_long.unwrap_err().status, 413);
+    }
+
+    #[test]
+    fn route_requests() {
+        assert_eq!(route("GET", "/sessions", ""), Ok(ApiCall::ListSessions));
+        assert_eq!(route("GET", "/tabs/", ""), Ok(ApiCall::ListTabs));
+        assert_eq!(
+            route("GET", "/tabs/12?verbose", ""),
+            Ok(ApiCall::TabStatus(12))
+        );
+        assert_eq!(
+            route("POST", "/sessions/open", r#"{"name": "db"}"#),
+            Ok(ApiCall::OpenSession {
+                group: String::new(),
+                name: "db".to_string()
+            })
+        );
+        let status = |result: Result<ApiCall, ApiResponse>| result.unwrap_err().status;
+        assert_eq!(status(route("POST", "/sessions/open", "{}")), 400);
+        assert_eq!(status(route("POST", "/sessions/open", "not json")), 400);
+        assert_eq!(status(route("DELETE", "/tabs/1", "")), 405);
+        assert_eq!(status(route("GET", "/tabs/1/stage", "")), 405);
+        assert_eq!(
+            route("POST", "/tabs/1/stage", r#"{"command": "df -h"}"#),
+            Ok(ApiCall::StageCommand {
+                tab: 1,
+                command: "df -h".to_string(),
+                source: "assistant".to_string()
+            })
+        );
+        // only what is shown is run
+        let hidden = r#"{"command": "ls\nrm -rf ~"}"#;
+        assert_eq!(status(route("POST", "/tabs/1/stage", hidden)), 400);
+        assert_eq!(status(route("GET", "/tabs/first", "")), 404);
+        assert_eq!(status(route("GET", "/settings", "")), 404);
+    }
+}
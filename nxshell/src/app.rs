@@ -1,19 +1,90 @@
-use crate::db::DbConn;
-use crate::errors::{error_toast, NxError};
-use crate::ui::form::{AuthType, NxStateManager};
-use crate::ui::tab_view::Tab;
+use crate::api::{ApiServer, ApiSettings};
+use crate::availability::HostProber;
+use crate::bell::{self, BellMode};
+use crate::consts::{
+    OVERVIEW_SHORTCUT, PRESENTATION_SHORTCUT, PRIVACY_BLUR_SHORTCUT, SPLIT_DOWN_SHORTCUT,
+    SPLIT_RIGHT_SHORTCUT,
+};
+use crate::db::{Db, DbCallback, DbConn, PromptRecord, Session};
+use crate::diagnose::Diagnosis;
+use crate::errors::{error_toast, info_toast, NxError};
+use crate::fonts::FontManager;
+use crate::i18n::{self, t, Language, LANGUAGE_KEY};
+use crate::ui::connect_error::ConnectError;
+use crate::ui::export::ViewExport;
+use crate::ui::form::{NxStateManager, ThemeMode};
+use crate::ui::import::SessionImport;
+use crate::ui::logs::LogFilter;
+use crate::ui::overview::TabOverview;
+use crate::ui::quit::QuitState;
+use crate::ui::redaction::default_redaction_patterns;
+use crate::ui::sessions::SessionList;
+use crate::ui::tab_view::{FailedConnection, SplitDirection, Tab};
+use crate::ui::theme_editor::{self, ThemeEditor};
+use crate::ui::workspace::WorkspaceSuggestion;
+use crate::window::MainWindow;
+use crate::wol::WakeUp;
+use crate::workspace::Workspaces;
 use copypasta::ClipboardContext;
 use eframe::{egui, NativeOptions};
-use egui::{Align2, CollapsingHeader, FontData, FontId, Id, TextEdit};
+use egui::{Align2, FontId, Id, KeyboardShortcut, Modifiers};
 use egui_dock::{DockState, NodeIndex, SurfaceIndex, TabIndex};
-use egui_phosphor::regular::{DRONE, NUMPAD};
-use egui_term::{FontSettings, PtyEvent, TerminalFont};
-use egui_theme_switch::global_theme_switch;
+use egui_term::{
+    FontSettings, MetaKeys, PtyEvent, Redaction, TerminalConfig, TerminalFont, TerminalLabels,
+    TerminalTheme, TerminalTint, UnicodeWidth, BLOCK_SELECTION_MODIFIERS, DEFAULT_SCROLLBACK_LINES,
+};
 use egui_toast::Toasts;
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::mpsc::{Receiver, Sender};
-use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::error;
+
+/// Keys of the terminal font in the app storage.
+const FONT_FAMILY_KEY: &str = "term_font_family";
+const FONT_SIZE_KEY: &str = "term_font_size";
+const LINE_HEIGHT_KEY: &str = "term_line_height";
+const CELL_PADDING_KEY: &str = "term_cell_padding";
+const COLLAPSE_PROGRESS_KEY: &str = "collapse_progress";
+const EXIT_CODES_KEY: &str = "exit_codes";
+const FLASH_FAILED_TABS_KEY: &str = "flash_failed_tabs";
+const KEEP_EXITED_TABS_KEY: &str = "keep_exited_tabs";
+const MEMORY_BUDGET_KEY: &str = "memory_budget";
+const MEMORY_BUDGET_MB_KEY: &str = "memory_budget_mb";
+const SCROLLBACK_LINES_KEY: &str = "scrollback_lines";
+const IDLE_TIMEOUT_KEY: &str = "idle_timeout";
+const IDLE_TIMEOUT_MIN_KEY: &str = "idle_timeout_min";
+const SHOW_DURATIONS_KEY: &str = "show_durations";
+const DURATION_THRESHOLD_KEY: &str = "duration_threshold";
+const SCROLL_MULTIPLIER_KEY: &str = "scroll_multiplier";
+const NATURAL_SCROLL_KEY: &str = "natural_scroll";
+const FLOW_CONTROL_KEY: &str = "flow_control";
+const RECORD_COMMANDS_KEY: &str = "record_commands";
+/// One pattern by line.
+const REDACTION_PATTERNS_KEY: &str = "redaction_patterns";
+const MASK_SECRETS_KEY: &str = "mask_secrets";
+const HOST_BADGES_KEY: &str = "host_badges";
+const HOST_PROBE_INTERVAL_KEY: &str = "host_probe_interval";
+const HOST_PROBE_CONCURRENCY_KEY: &str = "host_probe_concurrency";
+const TERM_THEME_KEY: &str = "term_theme";
+const COLOR_FILTER_KEY: &str = "color_filter";
+const BOLD_AS_BRIGHT_KEY: &str = "bold_as_bright";
+const BRIGHT_AS_BOLD_KEY: &str = "bright_as_bold";
+const SMOOTH_CURSOR_KEY: &str = "smooth_cursor";
+const DIM_INACTIVE_KEY: &str = "dim_inactive";
+const FOCUS_FOLLOWS_MOUSE_KEY: &str = "focus_follows_mouse";
+const BLOCK_SELECTION_KEY: &str = "block_selection";
+const BELL_KEY: &str = "bell";
+
+/// Modifiers offered for the block selection, with their name in the app
+/// storage.
+pub const BLOCK_SELECTION_CHOICES: [(Modifiers, &str); 3] = [
+    (Modifiers::ALT, "alt"),
+    (Modifiers::CTRL.plus(Modifiers::ALT), "ctrl_alt"),
+    (Modifiers::SHIFT.plus(Modifiers::ALT), "shift_alt"),
+];
 
 #[derive(Debug, Clone)]
 pub struct NxShellOptions {
@@ -30,14 +101,159 @@ pub struct NxShellOptions {
     /// 2. When it is None, all tabs lose focus, and you can iteract with the other UI components.
     pub active_tab_id: Option<Id>,
     pub term_font: TerminalFont,
+    /// Installed font family of the terminals, the bundled font when empty.
+    pub term_font_family: String,
     pub term_font_size: f32,
+    /// Theme of the terminals, the sessions with a light theme swap its
+    /// palette.
+    pub term_theme: TerminalTheme,
+    /// Bumped on each edit of `term_theme`, for the tabs to pick it up.
+    pub term_theme_revision: u64,
+    /// Set while the theme editor is open.
+    pub theme_editor: Option<ThemeEditor>,
     pub session_filter: String,
+    /// Number of parallel sftp transfers, by host.
+    pub sftp_parallelism: HashMap<String, usize>,
+    /// Settings of the saved sessions, by group and name. Loaded from the db
+    /// the first time a session needs them.
+    pub session_settings: HashMap<(String, String), SessionSettings>,
+    /// Keyboard-interactive prompts last asked by each host, loaded on demand.
+    pub auth_prompts: HashMap<String, Vec<PromptRecord>>,
+    /// Commands run at the prompt of each host, the most recent first. Loaded
+    /// on demand.
+    pub command_history: HashMap<String, Vec<String>>,
+    /// The commands run are added to `command_history`, their secrets
+    /// masked.
+    pub record_commands: bool,
+    /// Factor applied to the terminal font size in presentation mode.
+    pub presentation_font_scale: f32,
+    /// Font size to restore when leaving the presentation mode, set while it is on.
+    pub presentation: Option<f32>,
+    pub show_logs: bool,
+    pub show_profiler: bool,
+    pub log_filter: LogFilter,
+    /// Set while the tab overview is shown.
+    pub overview: Option<TabOverview>,
+    /// Set while the import window is open.
+    pub session_import: Option<SessionImport>,
+    /// Labels of the terminals, in the language of the user interface.
+    pub term_labels: TerminalLabels,
+    pub api: ApiSettings,
+    /// Only the last state of the lines redrawn by progress bars is logged.
+    pub collapse_progress: bool,
+    /// The exit codes of the commands are shown at their line.
+    pub exit_codes: bool,
+    /// Tabs in the background flash when a command fails in them.
+    pub flash_failed_tabs: bool,
+    /// The history of the least recently shown terminals is trimmed when the
+    /// grids take more than `memory_budget_mb` megabytes.
+    pub memory_budget: bool,
+    pub memory_budget_mb: u32,
+    /// Lines of history of the terminals opened next.
+    pub scrollback_lines: usize,
+    /// Ssh tabs without input nor output for `idle_timeout_min` minutes are
+    /// disconnected, unless their session sets its own timeout.
+    pub idle_timeout: bool,
+    pub idle_timeout_min: u32,
+    /// Local tabs stay open when their shell exits.
+    pub keep_exited_tabs: bool,
+    /// The commands which took at least `duration_threshold` seconds show it.
+    pub show_durations: bool,
+    pub duration_threshold: f32,
+    /// Lines scrolled by a wheel notch.
+    pub scroll_multiplier: f32,
+    /// The wheel scrolls the content, not the view.
+    pub natural_scroll: bool,
+    /// Ctrl+S and Ctrl+Q are sent to the shell, they pause and resume the
+    /// output.
+    pub flow_control: bool,
+    /// Patterns of the secrets masked in the copied text and the output logs,
+    /// compiled into `redaction`.
+    pub redaction_patterns: Vec<String>,
+    pub redaction: Redaction,
+    /// Why the patterns couldn't be compiled, set until they are fixed.
+    pub redaction_error: Option<String>,
+    /// The secrets are masked on screen too, e.g. while it is shared.
+    pub mask_secrets: bool,
+    /// The content of the terminals is hidden, until it is toggled back.
+    pub privacy_blur: bool,
+    /// The cursor glides to its cell, with a trail.
+    pub smooth_cursor: bool,
+    /// While several terminals are shown, the ones without the focus are
+    /// dimmed and the focused one is framed.
+    pub dim_inactive: bool,
+    /// The terminal under the pointer takes the input, without a click.
+    pub focus_follows_mouse: bool,
+    /// Held while dragging to select a block rather than lines.
+    pub block_selection: Modifiers,
+    pub bell: BellMode,
+    /// The hosts of the saved sessions are probed every
+    /// `host_probe_interval` seconds, `host_probe_concurrency` at a time, and
+    /// the session list shows whether they are up.
+    pub host_badges: bool,
+    pub host_probe_interval: u32,
+    pub host_probe_concurrency: u32,
+}
+
+/// State of the app kept from a frame to the next, unlike
+/// [`NxShellOptions`] it is neither saved nor set by the user.
+pub struct RuntimeState {
+    pub quit: QuitState,
+    pub memory_checked_at: Instant,
+    /// When the sound of the bell last played, see [`bell::ring`].
+    pub bell_rang_at: Option<Instant>,
+    /// Terminals shown on the last frame, several when the dock is split.
+    pub visible_terminals: usize,
+}
+
+impl Default for RuntimeState {
+    fn default() -> Self {
+        Self {
+            quit: QuitState::default(),
+            memory_checked_at: Instant::now(),
+            bell_rang_at: None,
+            visible_terminals: 0,
+        }
+    }
+}
+
+/// Settings of a saved session shared by its tabs.
+#[derive(Debug, Clone, Default)]
+pub struct SessionSettings {
+    /// Bookmarked remote directories.
+    pub bookmarks: Vec<String>,
+    pub tint: Option<TerminalTint>,
+    /// Keys only sent to the terminal after a confirmation.
+    pub protected_keys: Vec<KeyboardShortcut>,
+    /// Font family of the terminals, the one of the app when empty.
+    pub font_family: String,
+    pub font_size: Option<f32>,
+    /// The command typed at the prompt isn't highlighted.
+    pub plain_prompt: bool,
+    /// The chars typed are shown before the server echoes them.
+    pub local_echo: bool,
+    /// Overrides the idle timeout of the app.
+    pub idle_timeout: Option<Duration>,
+    /// Run once connected, see [`crate::db::Session::nested_command`].
+    pub nested_command: String,
+    pub meta_keys: MetaKeys,
+    pub unicode_width: UnicodeWidth,
+    pub theme: ThemeMode,
 }
 
 impl NxShellOptions {
     pub fn surrender_focus(&mut self) {
         self.active_tab_id = None;
     }
+
+    pub fn terminal_config(&self) -> TerminalConfig {
+        // the views are drawn with the options of the app, not with the
+        // config of each terminal
+        TerminalConfig {
+            scrollback_lines: self.scrollback_lines,
+            ..Default::default()
+        }
+    }
 }
 
 impl Default for NxShellOptions {
@@ -45,6 +261,7 @@ impl Default for NxShellOptions {
         let term_font_size = 14.;
         let font_setting = FontSettings {
             font_type: FontId::monospace(term_font_size),
+            ..Default::default()
         };
         Self {
             show_add_session_modal: Rc::new(RefCell::new(false)),
@@ -52,8 +269,53 @@ impl Default for NxShellOptions {
             active_tab_id: None,
             multi_exec: false,
             term_font: TerminalFont::new(font_setting),
+            term_font_family: String::new(),
             term_font_size,
+            term_theme: TerminalTheme::default(),
+            term_theme_revision: 0,
+            theme_editor: None,
             session_filter: String::default(),
+            sftp_parallelism: HashMap::new(),
+            session_settings: HashMap::new(),
+            auth_prompts: HashMap::new(),
+            command_history: HashMap::new(),
+            record_commands: true,
+            presentation_font_scale: 1.5,
+            presentation: None,
+            show_logs: false,
+            show_profiler: false,
+            log_filter: LogFilter::default(),
+            overview: None,
+            session_import: None,
+            term_labels: i18n::terminal_labels(),
+            api: ApiSettings::default(),
+            collapse_progress: true,
+            exit_codes: true,
+            flash_failed_tabs: true,
+            keep_exited_tabs: false,
+            memory_budget: true,
+            memory_budget_mb: 1024,
+            scrollback_lines: DEFAULT_SCROLLBACK_LINES,
+            idle_timeout: false,
+            idle_timeout_min: 30,
+            show_durations: true,
+            duration_threshold: 5.,
+            scroll_multiplier: 1.,
+            natural_scroll: false,
+            flow_control: true,
+            redaction_patterns: default_redaction_patterns(),
+            redaction: Redaction::default(),
+            redaction_error: None,
+            mask_secrets: false,
+            privacy_blur: false,
+            smooth_cursor: false,
+            dim_inactive: true,
+            focus_follows_mouse: true,
+            block_selection: BLOCK_SELECTION_MODIFIERS,
+            bell: BellMode::default(),
+            host_badges: false,
+            host_probe_interval: 60,
+            host_probe_concurrency: 8,
         }
     }
 }
@@ -65,17 +327,55 @@ pub struct NxShell {
     pub command_receiver: Receiver<(u64, PtyEvent)>,
     pub clipboard: ClipboardContext,
     pub db: DbConn,
+    pub db_callbacks: Receiver<DbCallback>,
+    /// Where the database, crash reports and diagnostics are kept.
+    pub data_dir: PathBuf,
     pub opts: NxShellOptions,
+    pub runtime: RuntimeState,
+    pub fonts: FontManager,
+    pub window: MainWindow,
     pub toasts: Toasts,
+    /// Set while the automation API is enabled.
+    pub api: Option<ApiServer>,
+    /// Sessions whose host is woken up, connected to once it is.
+    pub waking: Vec<(Session, WakeUp)>,
+    /// Hosts pinged in the diagnose windows.
+    pub diagnoses: Vec<Diagnosis>,
+    /// Image of a terminal view to take on the next frame.
+    pub view_export: Option<ViewExport>,
+    /// Failed connections, shown one at a time until retried or dismissed.
+    pub connect_errors: Vec<ConnectError>,
+    /// Set while the hosts of the session list are probed.
+    pub host_prober: Option<HostProber>,
+    /// Sessions often opened together, offered to be saved as a workspace.
+    pub workspace_suggestion: Option<WorkspaceSuggestion>,
 }
 
 impl NxShell {
-    fn new() -> Result<Self, NxError> {
+    fn new(ctx: &egui::Context, data_dir: PathBuf, db_path: PathBuf) -> Result<Self, NxError> {
         let (command_sender, command_receiver) = std::sync::mpsc::channel();
         let dock_state = DockState::new(vec![]);
-        let db = DbConn::open()?;
+        let (db, db_callbacks) = DbConn::spawn(ctx, db_path)?;
+        db.run(Db::find_all_sessions, |shell, sessions| {
+            shell.state_manager.loading = false;
+            match sessions {
+                Ok(sessions) => shell.state_manager.set_sessions(sessions),
+                Err(err) => {
+                    error!("failed to load the sessions: {err}");
+                    shell.toasts.add(error_toast(err.to_string()));
+                }
+            }
+        });
+        db.run(Db::find_workspaces, |shell, workspaces| match workspaces {
+            Ok(workspaces) => shell.state_manager.workspaces = workspaces,
+            Err(err) => error!("failed to load the workspaces: {err}"),
+        });
         let state_manager = NxStateManager {
-            sessions: Some(db.find_all_sessions()?),
+            sessions: None,
+            templates: None,
+            loading: true,
+            session_list: SessionList::default(),
+            workspaces: Workspaces::default(),
         };
         Ok(Self {
             command_sender,
@@ -83,38 +383,287 @@ impl NxShell {
             dock_state,
             clipboard: ClipboardContext::new()?,
             db,
+            db_callbacks,
+            data_dir,
             opts: NxShellOptions {
                 term_font: TerminalFont::new(FontSettings {
                     font_type: FontId::monospace(14.),
+                    ..Default::default()
                 }),
                 ..Default::default()
             },
+            runtime: RuntimeState::default(),
             state_manager,
+            fonts: FontManager::new(ctx),
+            window: MainWindow::default(),
             toasts: Toasts::new()
                 .anchor(Align2::CENTER_CENTER, (10.0, 10.0))
                 .direction(egui::Direction::TopDown),
+            api: None,
+            waking: vec![],
+            diagnoses: vec![],
+            view_export: None,
+            connect_errors: vec![],
+            host_prober: None,
+            workspace_suggestion: None,
         })
     }
 
-    pub fn start(options: NativeOptions) -> eframe::Result<()> {
+    pub fn start(
+        mut options: NativeOptions,
+        data_dir: PathBuf,
+        db_path: PathBuf,
+    ) -> eframe::Result<()> {
+        // the geometry is restored by `MainWindow`, along with the startup setting
+        options.persist_window = false;
         eframe::run_native(
             "NxShell",
             options,
             Box::new(|cc| {
+                let language = cc
+                    .storage
+                    .and_then(|storage| storage.get_string(LANGUAGE_KEY))
+                    .and_then(|tag| Language::from_tag(&tag))
+                    .unwrap_or_else(Language::system);
+                i18n::set_language(language);
                 catppuccin_egui::set_theme(&cc.egui_ctx, catppuccin_egui::FRAPPE);
                 egui_extras::install_image_loaders(&cc.egui_ctx);
-                set_font(&cc.egui_ctx);
                 cc.egui_ctx
                     .options_mut(|opt| opt.zoom_with_keyboard = false);
-                Ok(Box::new(NxShell::new()?))
+                let mut shell = NxShell::new(&cc.egui_ctx, data_dir, db_path)?;
+                shell.window = MainWindow::restore(cc.storage);
+                if let Some(storage) = cc.storage {
+                    shell.restore_font(storage);
+                    shell.opts.api = ApiSettings::restore(storage);
+                    let enabled = |key| storage.get_string(key).as_deref() != Some("false");
+                    shell.opts.collapse_progress = enabled(COLLAPSE_PROGRESS_KEY);
+                    shell.opts.exit_codes = enabled(EXIT_CODES_KEY);
+                    shell.opts.flash_failed_tabs = enabled(FLASH_FAILED_TABS_KEY);
+                    shell.opts.show_durations = enabled(SHOW_DURATIONS_KEY);
+                    shell.opts.memory_budget = enabled(MEMORY_BUDGET_KEY);
+                    shell.opts.flow_control = enabled(FLOW_CONTROL_KEY);
+                    shell.opts.record_commands = enabled(RECORD_COMMANDS_KEY);
+                    shell.opts.dim_inactive = enabled(DIM_INACTIVE_KEY);
+                    shell.opts.focus_follows_mouse = enabled(FOCUS_FOLLOWS_MOUSE_KEY);
+                    if let Some((modifiers, _)) =
+                        storage.get_string(BLOCK_SELECTION_KEY).and_then(|name| {
+                            BLOCK_SELECTION_CHOICES
+                                .into_iter()
+                                .find(|(_, choice)| *choice == name)
+                        })
+                    {
+                        shell.opts.block_selection = modifiers;
+                    }
+                    if let Some(bell) = storage
+                        .get_string(BELL_KEY)
+                        .and_then(|name| BellMode::from_name(&name))
+                    {
+                        shell.opts.bell = bell;
+                    }
+                    if let Some(budget) = storage
+                        .get_string(MEMORY_BUDGET_MB_KEY)
+                        .and_then(|budget| budget.parse().ok())
+                    {
+                        shell.opts.memory_budget_mb = budget;
+                    }
+                    if let Some(lines) = storage
+                        .get_string(SCROLLBACK_LINES_KEY)
+                        .and_then(|lines| lines.parse().ok())
+                    {
+                        shell.opts.scrollback_lines = lines;
+                    }
+                    shell.opts.idle_timeout =
+                        storage.get_string(IDLE_TIMEOUT_KEY).as_deref() == Some("true");
+                    if let Some(minutes) = storage
+                        .get_string(IDLE_TIMEOUT_MIN_KEY)
+                        .and_then(|minutes| minutes.parse().ok())
+                    {
+                        shell.opts.idle_timeout_min = minutes;
+                    }
+                    shell.opts.keep_exited_tabs =
+                        storage.get_string(KEEP_EXITED_TABS_KEY).as_deref() == Some("true");
+                    if let Some(threshold) = storage
+                        .get_string(DURATION_THRESHOLD_KEY)
+                        .and_then(|threshold| threshold.parse().ok())
+                    {
+                        shell.opts.duration_threshold = threshold;
+                    }
+                    if let Some(multiplier) = storage
+                        .get_string(SCROLL_MULTIPLIER_KEY)
+                        .and_then(|multiplier| multiplier.parse().ok())
+                    {
+                        shell.opts.scroll_multiplier = multiplier;
+                    }
+                    shell.opts.natural_scroll =
+                        storage.get_string(NATURAL_SCROLL_KEY).as_deref() == Some("true");
+                    if let Some(patterns) = storage.get_string(REDACTION_PATTERNS_KEY) {
+                        shell.opts.redaction_patterns =
+                            patterns.lines().map(String::from).collect();
+                    }
+                    shell.opts.mask_secrets =
+                        storage.get_string(MASK_SECRETS_KEY).as_deref() == Some("true");
+                    shell.opts.smooth_cursor =
+                        storage.get_string(SMOOTH_CURSOR_KEY).as_deref() == Some("true");
+                    shell.opts.host_badges =
+                        storage.get_string(HOST_BADGES_KEY).as_deref() == Some("true");
+                    if let Some(interval) = storage
+                        .get_string(HOST_PROBE_INTERVAL_KEY)
+                        .and_then(|interval| interval.parse().ok())
+                    {
+                        shell.opts.host_probe_interval = interval;
+                    }
+                    if let Some(concurrency) = storage
+                        .get_string(HOST_PROBE_CONCURRENCY_KEY)
+                        .and_then(|concurrency| concurrency.parse().ok())
+                    {
+                        shell.opts.host_probe_concurrency = concurrency;
+                    }
+                    if let Some(theme) = storage.get_string(TERM_THEME_KEY) {
+                        shell.opts.term_theme = theme_editor::theme_from_string(&theme);
+                    }
+                    if let Some(color_filter) = storage.get_string(COLOR_FILTER_KEY) {
+                        let color_filter = theme_editor::color_filter_from_name(&color_filter);
+                        shell.opts.term_theme.set_color_filter(color_filter);
+                    }
+                    let theme = &mut shell.opts.term_theme;
+                    theme.set_bold_as_bright(
+                        storage.get_string(BOLD_AS_BRIGHT_KEY).as_deref() == Some("true"),
+                    );
+                    theme.set_bright_as_bold(
+                        storage.get_string(BRIGHT_AS_BOLD_KEY).as_deref() == Some("true"),
+                    );
+                }
+                shell.apply_redaction();
+                shell.apply_api_settings(&cc.egui_ctx);
+                Ok(Box::new(shell))
             }),
         )
     }
 }
 
 impl eframe::App for NxShell {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        storage.set_string(LANGUAGE_KEY, i18n::language().tag().to_string());
+        storage.set_string(FONT_FAMILY_KEY, self.opts.term_font_family.clone());
+        storage.set_string(FONT_SIZE_KEY, self.opts.term_font_size.to_string());
+        let font = &self.opts.term_font;
+        storage.set_string(LINE_HEIGHT_KEY, font.line_height().to_string());
+        storage.set_string(CELL_PADDING_KEY, font.cell_padding().to_string());
+        storage.set_string(
+            COLLAPSE_PROGRESS_KEY,
+            self.opts.collapse_progress.to_string(),
+        );
+        storage.set_string(EXIT_CODES_KEY, self.opts.exit_codes.to_string());
+        storage.set_string(
+            FLASH_FAILED_TABS_KEY,
+            self.opts.flash_failed_tabs.to_string(),
+        );
+        storage.set_string(SHOW_DURATIONS_KEY, self.opts.show_durations.to_string());
+        storage.set_string(MEMORY_BUDGET_KEY, self.opts.memory_budget.to_string());
+        storage.set_string(MEMORY_BUDGET_MB_KEY, self.opts.memory_budget_mb.to_string());
+        storage.set_string(SCROLLBACK_LINES_KEY, self.opts.scrollback_lines.to_string());
+        storage.set_string(IDLE_TIMEOUT_KEY, self.opts.idle_timeout.to_string());
+        storage.set_string(IDLE_TIMEOUT_MIN_KEY, self.opts.idle_timeout_min.to_string());
+        storage.set_string(KEEP_EXITED_TABS_KEY, self.opts.keep_exited_tabs.to_string());
+        storage.set_string(
+            DURATION_THRESHOLD_KEY,
+            self.opts.duration_threshold.to_string(),
+        );
+        storage.set_string(
+            SCROLL_MULTIPLIER_KEY,
+            self.opts.scroll_multiplier.to_string(),
+        );
+        storage.set_string(NATURAL_SCROLL_KEY, self.opts.natural_scroll.to_string());
+        storage.set_string(FLOW_CONTROL_KEY, self.opts.flow_control.to_string());
+        storage.set_string(RECORD_COMMANDS_KEY, self.opts.record_commands.to_string());
+        storage.set_string(
+            REDACTION_PATTERNS_KEY,
+            self.opts.redaction_patterns.join("\n"),
+        );
+        storage.set_string(MASK_SECRETS_KEY, self.opts.mask_secrets.to_string());
+        storage.set_string(SMOOTH_CURSOR_KEY, self.opts.smooth_cursor.to_string());
+        storage.set_string(DIM_INACTIVE_KEY, self.opts.dim_inactive.to_string());
+        storage.set_string(
+            FOCUS_FOLLOWS_MOUSE_KEY,
+            self.opts.focus_follows_mouse.to_string(),
+        );
+        if let Some((_, name)) = BLOCK_SELECTION_CHOICES
+            .into_iter()
+            .find(|(modifiers, _)| *modifiers == self.opts.block_selection)
+        {
+            storage.set_string(BLOCK_SELECTION_KEY, name.to_string());
+        }
+        storage.set_string(BELL_KEY, self.opts.bell.name().to_string());
+        storage.set_string(HOST_BADGES_KEY, self.opts.host_badges.to_string());
+        storage.set_string(
+            HOST_PROBE_INTERVAL_KEY,
+            self.opts.host_probe_interval.to_string(),
+        );
+        storage.set_string(
+            HOST_PROBE_CONCURRENCY_KEY,
+            self.opts.host_probe_concurrency.to_string(),
+        );
+        storage.set_string(
+            TERM_THEME_KEY,
+            theme_editor::theme_to_string(&self.opts.term_theme),
+        );
+        let color_filter = self.opts.term_theme.color_filter();
+        storage.set_string(
+            COLOR_FILTER_KEY,
+            theme_editor::color_filter_name(color_filter).to_string(),
+        );
+        let theme = &self.opts.term_theme;
+        storage.set_string(BOLD_AS_BRIGHT_KEY, theme.bold_as_bright().to_string());
+        storage.set_string(BRIGHT_AS_BOLD_KEY, theme.bright_as_bold().to_string());
+        self.window.save(storage);
+        self.opts.api.save(storage);
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.shutdown_tabs();
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        self.fonts.begin_frame();
+        self.window.update(ctx);
         self.recv_event();
+        self.enforce_memory_budget();
+        self.enforce_idle_timeout(ctx);
+        self.recv_db_callbacks();
+        self.poll_connections();
+        self.poll_wake_ups(ctx);
+        self.probe_hosts(ctx);
+        self.intercept_close(ctx);
+        self.quit_confirmation(ctx);
+        self.host_key_prompt(ctx);
+        self.keyboard_interactive_prompt(ctx);
+        self.connect_error_dialog(ctx);
+        self.workspace_suggestion_window(ctx);
+        self.serve_api(ctx);
+        self.export_view_images(ctx);
+
+        if ctx.input_mut(|i| i.consume_shortcut(&PRESENTATION_SHORTCUT)) {
+            self.toggle_presentation(ctx);
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&OVERVIEW_SHORTCUT)) {
+            self.open_overview(true);
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&PRIVACY_BLUR_SHORTCUT)) {
+            self.toggle_privacy_blur(ctx);
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&SPLIT_RIGHT_SHORTCUT)) {
+            self.split_active_tab(ctx, SplitDirection::Right);
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&SPLIT_DOWN_SHORTCUT)) {
+            self.split_active_tab(ctx, SplitDirection::Down);
+        }
+        if self.opts.presentation.is_some() {
+            egui::CentralPanel::default()
+                .frame(egui::Frame::central_panel(&ctx.style()).inner_margin(0.))
+                .show(ctx, |ui| self.presentation_view(ui));
+            self.toasts.show(ctx);
+            return;
+        }
 
         egui::TopBottomPanel::top("main_top_panel").show(ctx, |ui| {
             self.menubar(ui);
@@ -125,24 +674,33 @@ impl eframe::App for NxShell {
             .show(ctx, |ui| {
                 ui.horizontal(|ui| {
                     ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
-                        ui.label("Sessions");
+                        ui.label(t!("sessions"));
                     });
                 });
 
                 self.search_sessions(ui);
                 ui.separator();
+                self.list_workspaces(ctx, ui);
                 self.list_sessions(ctx, ui);
             });
         egui::TopBottomPanel::bottom("main_bottom_panel").show(ctx, |ui| {
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
-                global_theme_switch(ui);
-            });
+            self.status_bar(ui);
         });
 
         if *self.opts.show_add_session_modal.borrow() {
             self.opts.surrender_focus();
             self.show_add_session_window(ctx);
         }
+        if self.opts.show_logs {
+            self.logs_window(ctx);
+        }
+        if self.opts.show_profiler {
+            self.profiler_window(ctx, frame.info().cpu_usage);
+        }
+        self.import_window(ctx);
+        self.theme_editor_window(ctx);
+        self.diagnose_windows(ctx);
+        self.tab_overview(ctx);
 
         egui::CentralPanel::default().show(ctx, |_ui| {
             self.tab_view(ctx);
@@ -153,86 +711,185 @@ impl eframe::App for NxShell {
 }
 
 impl NxShell {
-    fn search_sessions(&mut self, ui: &mut egui::Ui) {
-        let text_edit = TextEdit::singleline(&mut self.opts.session_filter);
-        let response = ui.add(text_edit);
-        if response.clicked() {
-            self.opts.surrender_focus();
-        } else if response.changed() {
-            if let Ok(sessions) = self.db.find_sessions(&self.opts.session_filter) {
-                self.state_manager.sessions = Some(sessions);
+    /// Show only the active tab, fullscreen and with a larger font, or restore
+    /// the window as it was.
+    pub fn toggle_presentation(&mut self, ctx: &egui::Context) {
+        let font_size = self.opts.term_font.font_size_mut();
+        let scale = self.opts.presentation_font_scale;
+        match self.opts.presentation.take() {
+            Some(restored) => {
+                *font_size = restored;
+                for (_, tab) in self.dock_state.iter_all_tabs_mut() {
+                    tab.scale_font(1. / scale);
+                }
+            }
+            None => {
+                self.opts.presentation = Some(*font_size);
+                *font_size *= scale;
+                for (_, tab) in self.dock_state.iter_all_tabs_mut() {
+                    tab.scale_font(scale);
+                }
+                let shortcut = ctx.format_shortcut(&PRESENTATION_SHORTCUT);
+                self.toasts.add(info_toast(t!(
+                    "presentation-exit-hint",
+                    shortcut = shortcut
+                )));
             }
         }
+        ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(
+            self.opts.presentation.is_some(),
+        ));
     }
 
-    fn list_sessions(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
-        if let Some(sessions) = self.state_manager.sessions.take() {
-            for (group, sessions) in sessions.iter() {
-                CollapsingHeader::new(group)
-                    .default_open(true)
-                    .show(ui, |ui| {
-                        for session in sessions {
-                            let icon = match AuthType::from(session.auth_type) {
-                                AuthType::Password => NUMPAD,
-                                AuthType::Config => DRONE,
-                            };
-                            let response = ui.button(format!("{icon} {}", session.name));
-                            if response.double_clicked() {
-                                match self.db.find_session(&session.group, &session.name) {
-                                    Ok(Some(session)) => {
-                                        if let Err(err) =
-                                            self.add_shell_tab_with_secret(ctx, session)
-                                        {
-                                            self.toasts.add(error_toast(err.to_string()));
-                                        }
-                                    }
-                                    Ok(None) => {}
-                                    Err(err) => {
-                                        self.toasts.add(error_toast(err.to_string()));
-                                    }
-                                }
-                            } else if response.secondary_clicked() {
-                            }
-                        }
-                    });
-            }
-            self.state_manager.sessions = Some(sessions);
+    /// Hide the content of every terminal, or show it again.
+    pub fn toggle_privacy_blur(&mut self, ctx: &egui::Context) {
+        self.opts.privacy_blur = !self.opts.privacy_blur;
+        if self.opts.privacy_blur {
+            let shortcut = ctx.format_shortcut(&PRIVACY_BLUR_SHORTCUT);
+            self.toasts
+                .add(info_toast(t!("privacy-blur-hint", shortcut = shortcut)));
         }
     }
-}
 
-impl NxShell {
-    fn recv_event(&mut self) {
-        if let Ok((tab_id, PtyEvent::Exit)) = self.command_receiver.try_recv() {
-            let mut index: Option<(SurfaceIndex, NodeIndex, TabIndex)> = None;
-            for (_, tab) in self.dock_state.iter_all_tabs() {
-                if tab.id() == tab_id {
-                    index = self.dock_state.find_tab(tab);
-                    break;
+    /// Terminal font saved by a previous run.
+    fn restore_font(&mut self, storage: &dyn eframe::Storage) {
+        if let Some(family) = storage.get_string(FONT_FAMILY_KEY) {
+            self.opts.term_font_family = family;
+        }
+        let size = storage.get_string(FONT_SIZE_KEY);
+        if let Some(size) = size.and_then(|size| size.parse::<f32>().ok()) {
+            self.opts.term_font_size = size;
+            *self.opts.term_font.font_size_mut() = size;
+        }
+        let number = |key| storage.get_string(key).and_then(|n| n.parse::<f32>().ok());
+        if let Some(line_height) = number(LINE_HEIGHT_KEY) {
+            self.opts.term_font.set_line_height(line_height);
+        }
+        if let Some(cell_padding) = number(CELL_PADDING_KEY) {
+            self.opts.term_font.set_cell_padding(cell_padding);
+        }
+    }
+
+    /// Apply the results of the queries run on the db thread.
+    fn recv_db_callbacks(&mut self) {
+        while let Ok(callback) = self.db_callbacks.try_recv() {
+            callback(self);
+        }
+    }
+
+    /// Swap the connected ssh tabs for their terminals, close the failed ones.
+    fn poll_connections(&mut self) {
+        let mut failed = vec![];
+        let mut pinned = vec![];
+        for (_, tab) in self.dock_state.iter_all_tabs_mut() {
+            // a split tab only closes the pane that failed
+            let split = tab.is_split();
+            match tab.poll_connection() {
+                Ok(Some(fingerprint)) => pinned.extend(tab.session_key().zip(Some(fingerprint))),
+                Ok(None) => {}
+                Err(failure) => failed.push((failure, split)),
+            }
+        }
+        for ((group, name), fingerprint) in pinned {
+            self.db.run(
+                move |db| db.update_host_fingerprint(&group, &name, &fingerprint),
+                |_, result| {
+                    if let Err(err) = result {
+                        error!("failed to pin the host key: {err}");
+                    }
+                },
+            );
+        }
+        for (FailedConnection { id, term_type, err }, split) in failed {
+            error!("connecting tab {id} failed: {err}");
+            match ConnectError::new(id, err.as_ref(), term_type) {
+                Some(error) => self.connect_errors.push(error),
+                None => {
+                    self.toasts.add(error_toast(err.to_string()));
                 }
             }
-            if let Some(index) = index {
+            if split {
+                continue;
+            }
+            if let Some(index) = self.dock_state.find_tab_from(|tab| tab.id() == id) {
                 self.dock_state.remove_tab(index);
             }
         }
     }
-}
 
-fn set_font(ctx: &egui::Context) {
-    let name = "MapleMono";
-    let font = include_bytes!("../assets/fonts/MapleMono-NF-CN-Light.ttf");
-    let mut fonts = egui::FontDefinitions::default();
-    fonts
-        .font_data
-        .insert(name.to_owned(), Arc::new(FontData::from_static(font)));
-    fonts
-        .families
-        .entry(egui::FontFamily::Monospace)
-        .or_default()
-        .push(name.to_owned());
-
-    // add egui icon
-    egui_phosphor::add_to_fonts(&mut fonts, egui_phosphor::Variant::Regular);
-
-    ctx.set_fonts(fonts);
+    fn recv_event(&mut self) {
+        while let Ok((tab_id, event)) = self.command_receiver.try_recv() {
+            match event {
+                PtyEvent::ChildExit(code) => {
+                    let tab = self
+                        .dock_state
+                        .iter_all_tabs_mut()
+                        .find_map(|(_, tab)| tab.pane_mut(tab_id));
+                    if let Some(tab) = tab {
+                        tab.set_exit_code(code);
+                    }
+                }
+                PtyEvent::Exit => {
+                    // closed with Enter, once its last output is read
+                    if self.opts.keep_exited_tabs {
+                        let tab = self
+                            .dock_state
+                            .iter_all_tabs_mut()
+                            .find_map(|(_, tab)| tab.pane_mut(tab_id));
+                        if tab.is_some_and(|tab| tab.keep_exited()) {
+                            continue;
+                        }
+                    }
+                    // the other panes of a split tab are kept
+                    if self
+                        .dock_state
+                        .iter_all_tabs_mut()
+                        .any(|(_, tab)| tab.close_pane(tab_id))
+                    {
+                        continue;
+                    }
+                    let mut index: Option<(SurfaceIndex, NodeIndex, TabIndex)> = None;
+                    for (_, tab) in self.dock_state.iter_all_tabs() {
+                        if tab.id() == tab_id {
+                            index = self.dock_state.find_tab(tab);
+                            break;
+                        }
+                    }
+                    if let Some(index) = index {
+                        self.dock_state.remove_tab(index);
+                    }
+                }
+                PtyEvent::CommandFinished(code) => {
+                    let tab = self
+                        .dock_state
+                        .iter_all_tabs_mut()
+                        .find_map(|(_, tab)| tab.pane_mut(tab_id).is_some().then_some(tab));
+                    if let Some(tab) = tab {
+                        if let Some(pane) = tab.pane_mut(tab_id) {
+                            pane.nested_finished();
+                        }
+                        // cleared right away when the tab is shown
+                        if code.is_some_and(|code| code != 0) && self.opts.flash_failed_tabs {
+                            tab.command_failed();
+                        }
+                    }
+                }
+                PtyEvent::Bell => match self.opts.bell {
+                    // the view flashes by itself once shown
+                    BellMode::Visual => {
+                        let tab = self
+                            .dock_state
+                            .iter_all_tabs_mut()
+                            .find_map(|(_, tab)| tab.pane_mut(tab_id).is_some().then_some(tab));
+                        if let Some(tab) = tab {
+                            tab.bell_rang();
+                        }
+                    }
+                    BellMode::Audible => bell::ring(&mut self.runtime.bell_rang_at),
+                    BellMode::None => {}
+                },
+                _ => {}
+            }
+        }
+    }
 }
@@ -0,0 +1,173 @@
+//! Availability of the saved hosts, probed in the background with a TCP
+//! handshake to their ssh port like the latency of the tabs.
+
+use crate::latency::{config_address, probe};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
+use tracing::error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostStatus {
+    Up,
+    Down,
+}
+
+/// Host of a saved session to probe.
+#[derive(Debug, Clone)]
+pub struct ProbeTarget {
+    pub group: String,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    /// The host is an alias of the ssh config.
+    pub config: bool,
+}
+
+/// Statuses of the hosts, by group and name of their session.
+type Statuses = HashMap<(String, String), HostStatus>;
+
+/// Probes the hosts of the saved sessions on a background thread, which stops
+/// once the prober is dropped. Each round probes `concurrency` hosts at a
+/// time.
+pub struct HostProber {
+    targets: Sender<Vec<ProbeTarget>>,
+    statuses: Arc<Mutex<Statuses>>,
+    concurrency: Arc<AtomicUsize>,
+    /// When the last round was asked for.
+    pub probed_at: Option<Instant>,
+}
+
+impl HostProber {
+    pub fn spawn(ctx: &egui::Context, concurrency: usize) -> Self {
+        let (targets, receiver) = mpsc::channel();
+        let statuses = Arc::new(Mutex::new(Statuses::new()));
+        let concurrency = Arc::new(AtomicUsize::new(concurrency));
+
+        let ctx = ctx.clone();
+        let (shared, limit) = (statuses.clone(), concurrency.clone());
+        let spawned = std::thread::Builder::new()
+            .name("host_prober".to_string())
+            .spawn(move || probe_rounds(receiver, &shared, &limit, &ctx));
+        if let Err(err) = spawned {
+            error!("failed to spawn the host prober: {err}");
+        }
+        Self {
+            targets,
+            statuses,
+            concurrency,
+            probed_at: None,
+        }
+    }
+
+    /// Probe `targets` in the next round, the hosts of the other sessions are
+    /// forgotten.
+    pub fn probe(&self, targets: Vec<ProbeTarget>) {
+        let _ = self.targets.send(targets);
+    }
+
+    pub fn set_concurrency(&self, concurrency: usize) {
+        self.concurrency.store(concurrency, Ordering::Relaxed);
+    }
+
+    /// `None` until the host of the session is probed.
+    pub fn status(&self, group: &str, name: &str) -> Option<HostStatus> {
+        let statuses = self.statuses.lock().unwrap_or_else(PoisonError::into_inner);
+        statuses
+            .get(&(group.to_string(), name.to_string()))
+            .copied()
+    }
+}
+
+fn probe_rounds(
+    receiver: Receiver<Vec<ProbeTarget>>,
+    statuses: &Mutex<Statuses>,
+    concurrency: &AtomicUsize,
+    ctx: &egui::Context,
+) {
+    while let Ok(mut targets) = receiver.recv() {
+        // only the latest targets, the rounds asked meanwhile are skipped
+        while let Ok(newer) = receiver.try_recv() {
+            targets = newer;
+        }
+        probe_round(&targets, concurrency.load(Ordering::Relaxed), statuses);
+        ctx.request_repaint();
+    }
+}
+
+/// Probe `targets`, `concurrency` at a time, and keep only their statuses.
+fn probe_round(targets: &[ProbeTarget], concurrency: usize, statuses: &Mutex<Statuses>) {
+    let next = AtomicUsize::new(0);
+    std::thread::scope(|scope| {
+        for _ in 0..concurrency.clamp(1, targets.len().max(1)) {
+            scope.spawn(|| {
+                while let Some(target) = targets.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let (host, port) = match target.config {
+                        true => config_address(&target.host),
+                        false => (target.host.clone(), target.port),
+                    };
+                    let status = match probe(&host, port) {
+                        Some(_) => HostStatus::Up,
+                        None => HostStatus::Down,
+                    };
+                    statuses
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .insert((target.group.clone(), target.name.clone()), status);
+                }
+            });
+        }
+    });
+    statuses
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .retain(|(group, name), _| {
+            targets
+                .iter()
+                .any(|target| (&target.group, &target.name) == (group, name))
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn probe_the_hosts() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let up = listener.local_addr().unwrap().port();
+        // a port nothing listens on anymore
+        let down = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let target = |name: &str, port: u16| ProbeTarget {
+            group: "lab".to_string(),
+            name: name.to_string(),
+            host: "127.0.0.1".to_string(),
+            port,
+            config: false,
+        };
+
+        let statuses = Mutex::new(Statuses::new());
+        statuses
+            .lock()
+            .unwrap()
+            .insert(("lab".to_string(), "removed".to_string()), HostStatus::Up);
+        probe_round(&[target("up", up), target("down", down)], 4, &statuses);
+        let statuses = statuses.into_inner().unwrap();
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(
+            statuses[&("lab".to_string(), "up".to_string())],
+            HostStatus::Up
+        );
+        assert_eq!(
+            statuses[&("lab".to_string(), "down".to_string())],
+            HostStatus::Down
+        );
+    }
+}
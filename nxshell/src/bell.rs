@@ -0,0 +1,99 @@
+use crate::i18n::t;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Bells rung sooner after the last one are silent, e.g. while a key held
+/// down rings it on every repeat.
+const MIN_RING_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Commands playing the sound of the bell, the first one found is run.
+#[cfg(target_os = "macos")]
+const SOUND_COMMANDS: &[&[&str]] = &[&["osascript", "-e", "beep"]];
+#[cfg(windows)]
+const SOUND_COMMANDS: &[&[&str]] = &[&["rundll32", "user32.dll,MessageBeep"]];
+#[cfg(not(any(target_os = "macos", windows)))]
+const SOUND_COMMANDS: &[&[&str]] = &[
+    &["canberra-gtk-play", "-i", "bell"],
+    &["paplay", "/usr/share/sounds/freedesktop/stereo/bell.oga"],
+];
+
+/// What a terminal does when it rings the bell, e.g. on a completion without
+/// matches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BellMode {
+    /// The terminal flashes, and so does its tab in the background.
+    #[default]
+    Visual,
+    /// The sound of the system plays.
+    Audible,
+    None,
+}
+
+impl BellMode {
+    pub const ALL: [BellMode; 3] = [BellMode::Visual, BellMode::Audible, BellMode::None];
+
+    /// Name in the app storage.
+    pub fn name(self) -> &'static str {
+        match self {
+            BellMode::Visual => "visual",
+            BellMode::Audible => "audible",
+            BellMode::None => "none",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            BellMode::Visual => t!("bell-visual"),
+            BellMode::Audible => t!("bell-audible"),
+            BellMode::None => t!("bell-none"),
+        }
+    }
+}
+
+/// Play the sound of the bell in the background, unless it was played less
+/// than [`MIN_RING_INTERVAL`] ago. `rang_at` is when it last was.
+pub fn ring(rang_at: &mut Option<Instant>) {
+    let now = Instant::now();
+    if rang_at.is_some_and(|rang_at| now.duration_since(rang_at) < MIN_RING_INTERVAL) {
+        return;
+    }
+    *rang_at = Some(now);
+    let _ = std::thread::Builder::new()
+        .name("bell".to_string())
+        .spawn(|| {
+            for command in SOUND_COMMANDS {
+                let child = Command::new(command[0])
+                    .args(&command[1..])
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn();
+                match child {
+                    // waited for, so that it isn't left a zombie
+                    Ok(mut child) => {
+                        let _ = child.wait();
+                        return;
+                    }
+                    Err(err) => debug!("failed to play the bell with {}: {err}", command[0]),
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bell_mode_names() {
+        for mode in BellMode::ALL {
+            assert_eq!(BellMode::from_name(mode.name()), Some(mode));
+        }
+        assert_eq!(BellMode::from_name("loud"), None);
+    }
+}
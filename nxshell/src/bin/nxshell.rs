@@ -2,7 +2,9 @@
 
 use egui::ViewportBuilder;
 use nxshell::app::NxShell;
+use nxshell::cli::{Args, USAGE};
 use nxshell::consts::PKG_NAME;
+use nxshell::diagnostics::{install_panic_hook, LogBuffer};
 use std::io::stdout;
 use tracing::Level;
 use tracing_subscriber::fmt::writer::MakeWriterExt;
@@ -18,20 +20,44 @@ fn init_log() {
         .with_ansi(true)
         .with_line_number(true)
         .with_writer(stdout.with_max_level(Level::INFO));
+    // kept for crash reports and diagnostics
+    let buffer_layer = tracing_subscriber::fmt::layer()
+        .with_ansi(false)
+        .with_writer(LogBuffer::global().clone());
 
     Registry::default()
         .with(env_filter)
         .with(formatting_layer)
+        .with(buffer_layer)
         .init();
 }
 
 pub fn main() -> eframe::Result<()> {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{USAGE}");
+        return Ok(());
+    }
     init_log();
+    let (data_dir, db_path) = match args.data_dir().and_then(|dir| Ok((dir, args.db_path()?))) {
+        Ok(paths) => paths,
+        Err(err) => {
+            eprintln!("failed to locate the data directory: {err}");
+            std::process::exit(1);
+        }
+    };
+    install_panic_hook(data_dir.clone());
 
     let options = eframe::NativeOptions {
         centered: true,
         viewport: ViewportBuilder::default().with_min_inner_size((1000.0, 600.0)),
         ..Default::default()
     };
-    NxShell::start(options)
+    NxShell::start(options, data_dir, db_path)
 }
@@ -0,0 +1,124 @@
+use crate::consts::PKG_NAME;
+use homedir::my_home;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+pub const USAGE: &str = "Usage: nxshell [--portable] [--db <path>]
+
+Options:
+  --portable   Keep the data beside the executable
+  --db <path>  Use this database file
+  -h, --help   Print this help";
+
+const DB_FILE: &str = "db.sqlite";
+
+/// Command line options.
+///
+/// The data is kept in the platform data directory, e.g.
+/// `~/.local/share/nxshell` on Linux, `~/Library/Application Support/nxshell`
+/// on macOS and `%APPDATA%\nxshell` on Windows. `--portable` keeps it beside
+/// the executable instead, `--db` only moves the database.
+#[derive(Debug, Default, PartialEq)]
+pub struct Args {
+    pub portable: bool,
+    pub db: Option<PathBuf>,
+    pub help: bool,
+}
+
+impl Args {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--portable" => parsed.portable = true,
+                "--db" => match args.next() {
+                    Some(path) => parsed.db = Some(PathBuf::from(path)),
+                    None => return Err("`--db` expects a path".to_string()),
+                },
+                "-h" | "--help" => parsed.help = true,
+                _ => match arg.strip_prefix("--db=") {
+                    Some(path) => parsed.db = Some(PathBuf::from(path)),
+                    None => return Err(format!("unexpected argument `{arg}`")),
+                },
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Directory holding the data of nxshell, created if missing.
+    pub fn data_dir(&self) -> io::Result<PathBuf> {
+        let dir = if self.portable {
+            let exe = std::env::current_exe()?;
+            exe.parent().map(Path::to_path_buf).unwrap_or_default()
+        } else {
+            platform_data_dir()?.join(PKG_NAME)
+        };
+        std::fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    /// The database file. A database left in the working directory by older
+    /// releases is copied to the data directory the first time.
+    pub fn db_path(&self) -> io::Result<PathBuf> {
+        if let Some(path) = &self.db {
+            return Ok(path.clone());
+        }
+        let path = self.data_dir()?.join(DB_FILE);
+        let legacy = Path::new(DB_FILE);
+        if !path.exists() && legacy.is_file() {
+            info!("copy {} to {}", legacy.display(), path.display());
+            std::fs::copy(legacy, &path)?;
+        }
+        Ok(path)
+    }
+}
+
+fn platform_data_dir() -> io::Result<PathBuf> {
+    let env_dir = if cfg!(windows) {
+        std::env::var_os("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        None
+    } else {
+        std::env::var_os("XDG_DATA_HOME").filter(|dir| Path::new(dir).is_absolute())
+    };
+    if let Some(dir) = env_dir {
+        return Ok(PathBuf::from(dir));
+    }
+
+    let home = my_home()
+        .map_err(io::Error::other)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+    Ok(if cfg!(target_os = "macos") {
+        home.join("Library").join("Application Support")
+    } else if cfg!(windows) {
+        home.join("AppData").join("Roaming")
+    } else {
+        home.join(".local").join("share")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Args;
+    use std::path::PathBuf;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parse_args() {
+        assert_eq!(parse(&[]).unwrap(), Args::default());
+        let args = parse(&["--portable", "--db", "/tmp/a.sqlite"]).unwrap();
+        assert!(args.portable);
+        assert_eq!(args.db, Some(PathBuf::from("/tmp/a.sqlite")));
+        assert_eq!(
+            parse(&["--db=b.sqlite"]).unwrap().db,
+            Some(PathBuf::from("b.sqlite"))
+        );
+        assert!(parse(&["--help"]).unwrap().help);
+        assert!(parse(&["--db"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use egui::{Key, KeyboardShortcut, Modifiers};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Once;
 
@@ -7,6 +8,34 @@ pub use id::*;
 pub const PKG_NAME: &str = env!("CARGO_PKG_NAME");
 pub const REPOSITORY_URL: &str = env!("CARGO_PKG_REPOSITORY");
 pub static GLOBAL_COUNTER: Counter = Counter::new();
+pub const PRESENTATION_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::NONE, Key::F11);
+pub const PRIVACY_BLUR_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::B);
+/// Shown in the menu for the search bar of the focused terminal. The key is
+/// the default `SearchForward` binding of the terminals, which handle it.
+/// Ctrl+F alone belongs to the shell and to the full screen applications.
+#[cfg(target_os = "macos")]
+pub const FIND_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::MAC_CMD, Key::F);
+#[cfg(not(target_os = "macos"))]
+pub const FIND_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::CTRL.plus(Modifiers::SHIFT), Key::F);
+/// Split the focused terminal side by side, or one above the other, keeping
+/// clear of the keys of the shell.
+#[cfg(target_os = "macos")]
+pub const SPLIT_RIGHT_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::MAC_CMD, Key::D);
+#[cfg(not(target_os = "macos"))]
+pub const SPLIT_RIGHT_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::CTRL.plus(Modifiers::SHIFT), Key::E);
+#[cfg(target_os = "macos")]
+pub const SPLIT_DOWN_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::MAC_CMD.plus(Modifiers::SHIFT), Key::D);
+#[cfg(not(target_os = "macos"))]
+pub const SPLIT_DOWN_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::CTRL.plus(Modifiers::SHIFT), Key::O);
+pub const OVERVIEW_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Tab);
 
 pub struct Counter {
     value: AtomicU64,
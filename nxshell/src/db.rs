@@ -1,7 +1,18 @@
+use crate::app::NxShell;
 use crate::errors::NxError;
+use crate::workspace::{
+    members_key, suggest_workspace, SessionKey, SessionOpen, Workspaces, SESSION_OPEN_LIMIT,
+};
 use chrono::Local;
 use indexmap::IndexMap;
-use rusqlite::{Connection, Result};
+use rusqlite::{params, Connection, Result, Row, Rows};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use tracing::error;
+
+/// Saved sessions by group.
+pub type SessionGroups = IndexMap<String, Vec<Session>>;
 
 #[derive(Clone, Default)]
 pub struct Session {
@@ -15,15 +26,198 @@ pub struct Session {
     pub secret_data: Vec<u8>,
     pub secret_key: Vec<u8>,
     pub create_time: u64,
+    pub tint_mode: u16,
+    /// `0xRRGGBB`
+    pub tint_color: u32,
+    /// Comma separated shortcuts, e.g. `Ctrl+C, Ctrl+D`.
+    pub protected_keys: String,
+    /// Terminal font family, the default font when empty.
+    pub font_family: String,
+    /// Terminal font size, the default size when `0`.
+    pub font_size: f32,
+    /// Fingerprint of the host key, empty until the first connection.
+    pub host_fingerprint: String,
+    /// See `Compression`, the ssh default when `0`.
+    pub compression: u16,
+    /// Comma separated ciphers by preference, the ssh default when empty.
+    pub ciphers: String,
+    /// Comma separated key exchange algorithms by preference, the ssh default
+    /// when empty.
+    pub kex_algorithms: String,
+    /// Saved as a template for new sessions, not listed with the sessions.
+    pub template: bool,
+    /// The command typed at the prompt is left as the shell prints it,
+    /// without highlighting.
+    pub plain_prompt: bool,
+    /// The printable chars typed are shown before the server echoes them.
+    pub local_echo: bool,
+    /// Minutes without input nor output before disconnecting, the idle
+    /// timeout of the app when `0`.
+    pub idle_timeout: u32,
+    /// MAC address the host is woken up with, empty when it can't be.
+    pub mac_address: String,
+    /// Run once connected, e.g. `ssh inner-host`, empty when there is none.
+    pub nested_command: String,
+    /// `LANG` of the shell, e.g. `de_DE.UTF-8`, picked from the locales of
+    /// the host when empty.
+    pub locale: String,
+    /// Alt+key sends the key with its eighth bit set, rather than ESC then
+    /// the key.
+    pub alt_eight_bit: bool,
+    /// AltGr is Alt too, rather than typing the chars of the layout.
+    pub altgr_as_alt: bool,
+    /// Option is Meta on macOS.
+    pub option_as_meta: bool,
+    /// East Asian ambiguous chars take two cells, as the CJK locales of the
+    /// host expect.
+    pub ambiguous_wide: bool,
+    /// Emoji take a single cell, as older hosts expect.
+    pub narrow_emoji: bool,
+    /// See `ThemeMode`, the theme follows the background set by the host
+    /// when `0`.
+    pub theme: u16,
+    /// Private key of the public key authentication, e.g.
+    /// `~/.ssh/id_ed25519`, its passphrase is the secret data.
+    pub identity_file: String,
+    /// Ports forwarded once connected, one by line, e.g. `L 8080:localhost:80`.
+    pub forwards: String,
+}
+
+/// Columns read by [`session_from_row`].
+const SESSION_COLUMNS: &str = "id, group_name, name, host, port, auth_type, username, \
+    secret_data, secret_key, create_time, tint_mode, tint_color, protected_keys, font_family, \
+    font_size, host_fingerprint, compression, ciphers, kex_algorithms, is_template, plain_prompt, \
+    local_echo, idle_timeout, mac_address, nested_command, locale, alt_eight_bit, altgr_as_alt, \
+    option_as_meta, ambiguous_wide, narrow_emoji, theme, identity_file, \
+    forwards";
+
+/// Prepared statements kept by the connection, enough for every query of
+/// [`Db`].
+const STATEMENT_CACHE_CAPACITY: usize = 32;
+
+/// Commands kept in the history of each host, the oldest ones are dropped.
+pub const COMMAND_HISTORY_LIMIT: usize = 500;
+
+fn session_from_row(row: &Row) -> Result<Session> {
+    Ok(Session {
+        id: row.get(0)?,
+        group: row.get(1)?,
+        name: row.get(2)?,
+        host: row.get(3)?,
+        port: row.get(4)?,
+        auth_type: row.get(5)?,
+        username: row.get(6)?,
+        secret_data: row.get(7)?,
+        secret_key: row.get(8)?,
+        create_time: row.get(9)?,
+        tint_mode: row.get(10)?,
+        tint_color: row.get(11)?,
+        protected_keys: row.get(12)?,
+        font_family: row.get(13)?,
+        font_size: row.get(14)?,
+        host_fingerprint: row.get(15)?,
+        compression: row.get(16)?,
+        ciphers: row.get(17)?,
+        kex_algorithms: row.get(18)?,
+        template: row.get(19)?,
+        plain_prompt: row.get(20)?,
+        local_echo: row.get(21)?,
+        idle_timeout: row.get(22)?,
+        mac_address: row.get(23)?,
+        nested_command: row.get(24)?,
+        locale: row.get(25)?,
+        alt_eight_bit: row.get(26)?,
+        altgr_as_alt: row.get(27)?,
+        option_as_meta: row.get(28)?,
+        ambiguous_wide: row.get(29)?,
+        narrow_emoji: row.get(30)?,
+        theme: row.get(31)?,
+        identity_file: row.get(32)?,
+        forwards: row.get(33)?,
+    })
+}
+
+/// Prompt of a keyboard-interactive authentication, as last asked by a host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptRecord {
+    pub label: String,
+    /// Typed by the user, e.g. a verification code, rather than answered from
+    /// the session.
+    pub typed: bool,
 }
 
+/// Runs on the db thread and hands its result to the UI thread.
+type Job = Box<dyn FnOnce(Result<&Db, &str>) -> DbCallback + Send>;
+/// Applies the result of a query on the UI thread.
+pub type DbCallback = Box<dyn FnOnce(&mut NxShell) + Send>;
+
+/// Handle to the db thread, which owns the sqlite connection. Cheap to clone.
+///
+/// Queries are queued with [`DbConn::run`] and their results are handed back
+/// through the receiver returned by [`DbConn::spawn`], the UI thread never
+/// waits for sqlite.
+#[derive(Clone)]
 pub struct DbConn {
-    db: Connection,
+    jobs: Sender<Job>,
 }
 
 impl DbConn {
-    pub fn open() -> Result<Self> {
-        let db = Connection::open("db.sqlite")?;
+    /// Start the db thread, the database at `path` is opened on it.
+    pub fn spawn(
+        ctx: &egui::Context,
+        path: PathBuf,
+    ) -> Result<(Self, Receiver<DbCallback>), NxError> {
+        let (jobs, job_receiver) = mpsc::channel::<Job>();
+        let (callbacks, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+
+        std::thread::Builder::new()
+            .name("db".to_string())
+            .spawn(move || {
+                let db = Db::open(&path).map_err(|err| {
+                    error!("failed to open the database {}: {err}", path.display());
+                    err.to_string()
+                });
+                for job in job_receiver {
+                    let callback = job(db.as_ref().map_err(String::as_str));
+                    if callbacks.send(callback).is_err() {
+                        break;
+                    }
+                    ctx.request_repaint();
+                }
+            })
+            .map_err(|err| NxError::Plain(format!("failed to spawn the db thread: {err}")))?;
+        Ok((Self { jobs }, receiver))
+    }
+
+    /// Run `query` on the db thread, then `callback` with its result on the UI
+    /// thread.
+    pub fn run<T: Send + 'static>(
+        &self,
+        query: impl FnOnce(&Db) -> Result<T, NxError> + Send + 'static,
+        callback: impl FnOnce(&mut NxShell, Result<T, NxError>) + Send + 'static,
+    ) {
+        let job: Job = Box::new(move |db| {
+            let result = db
+                .map_err(|err| NxError::Plain(err.to_string()))
+                .and_then(query);
+            Box::new(move |shell| callback(shell, result))
+        });
+        if self.jobs.send(job).is_err() {
+            error!("the db thread has stopped");
+        }
+    }
+}
+
+/// Queries of the database, see [`DbConn::run`].
+pub struct Db {
+    conn: Connection,
+}
+
+impl Db {
+    /// Open the database file, creating or migrating its tables.
+    fn open(path: &Path) -> Result<Self> {
+        let db = Connection::open(path)?;
         db.execute(
             "CREATE TABLE IF NOT EXISTS session
                 (
@@ -37,76 +231,188 @@ impl DbConn {
                     secret_data    BLOB NOT NULL,
                     secret_key     BLOB NOT NULL,
                     create_time    DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    tint_mode      INTEGER NOT NULL DEFAULT 0,
+                    tint_color     INTEGER NOT NULL DEFAULT 0,
+                    protected_keys TEXT NOT NULL DEFAULT '',
 
                     UNIQUE (group_name, name)
                 );",
             (),
         )?;
-        Ok(Self { db })
+        // columns added after the table was first released
+        add_column(&db, "session", "tint_mode", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column(&db, "session", "tint_color", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column(&db, "session", "protected_keys", "TEXT NOT NULL DEFAULT ''")?;
+        add_column(&db, "session", "font_family", "TEXT NOT NULL DEFAULT ''")?;
+        add_column(&db, "session", "font_size", "REAL NOT NULL DEFAULT 0")?;
+        add_column(
+            &db,
+            "session",
+            "host_fingerprint",
+            "TEXT NOT NULL DEFAULT ''",
+        )?;
+        add_column(&db, "session", "compression", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column(&db, "session", "ciphers", "TEXT NOT NULL DEFAULT ''")?;
+        add_column(&db, "session", "kex_algorithms", "TEXT NOT NULL DEFAULT ''")?;
+        add_column(&db, "session", "is_template", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column(&db, "session", "plain_prompt", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column(&db, "session", "local_echo", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column(&db, "session", "idle_timeout", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column(&db, "session", "mac_address", "TEXT NOT NULL DEFAULT ''")?;
+        add_column(&db, "session", "nested_command", "TEXT NOT NULL DEFAULT ''")?;
+        add_column(&db, "session", "locale", "TEXT NOT NULL DEFAULT ''")?;
+        add_column(
+            &db,
+            "session",
+            "alt_eight_bit",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        add_column(&db, "session", "altgr_as_alt", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column(
+            &db,
+            "session",
+            "option_as_meta",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        add_column(
+            &db,
+            "session",
+            "ambiguous_wide",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        add_column(&db, "session", "narrow_emoji", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column(&db, "session", "theme", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column(&db, "session", "identity_file", "TEXT NOT NULL DEFAULT ''")?;
+        add_column(&db, "session", "forwards", "TEXT NOT NULL DEFAULT ''")?;
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS bookmark
+                (
+                    id             INTEGER PRIMARY KEY AUTOINCREMENT,
+                    group_name     TEXT NOT NULL,
+                    session_name   TEXT NOT NULL,
+                    path           TEXT NOT NULL,
+
+                    UNIQUE (group_name, session_name, path)
+                );",
+            (),
+        )?;
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS auth_prompt
+                (
+                    host           TEXT NOT NULL,
+                    position       INTEGER NOT NULL,
+                    label          TEXT NOT NULL,
+                    typed          INTEGER NOT NULL,
+
+                    PRIMARY KEY (host, position)
+                );",
+            (),
+        )?;
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS command_history
+                (
+                    host           TEXT NOT NULL,
+                    command        TEXT NOT NULL,
+                    last_used      INTEGER NOT NULL,
+
+                    PRIMARY KEY (host, command)
+                );",
+            (),
+        )?;
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS session_open
+                (
+                    group_name     TEXT NOT NULL,
+                    name           TEXT NOT NULL,
+                    opened_at      INTEGER NOT NULL
+                );",
+            (),
+        )?;
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS workspace
+                (
+                    name           TEXT NOT NULL,
+                    group_name     TEXT NOT NULL,
+                    session_name   TEXT NOT NULL,
+                    position       INTEGER NOT NULL,
+
+                    PRIMARY KEY (name, group_name, session_name)
+                );",
+            (),
+        )?;
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS dismissed_workspace
+                (
+                    members        TEXT PRIMARY KEY
+                );",
+            (),
+        )?;
+        // for the lookups and the search of the session list
+        db.execute_batch(
+            "CREATE INDEX IF NOT EXISTS session_group_name ON session (group_name);
+             CREATE INDEX IF NOT EXISTS session_name ON session (name);
+             CREATE INDEX IF NOT EXISTS session_host ON session (host);",
+        )?;
+        // the queries are prepared once and reused from the cache
+        db.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+        Ok(Self { conn: db })
     }
 
-    pub fn find_all_sessions(&self) -> Result<IndexMap<String, Vec<Session>>> {
-        let mut stmt = self
-            .db
-            .prepare("SELECT id, group_name, name, auth_type FROM session")?;
+    pub fn find_all_sessions(&self) -> Result<SessionGroups, NxError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, group_name, name, auth_type FROM session WHERE is_template = 0",
+        )?;
+        session_groups(stmt.query(())?)
+    }
+
+    /// Address of every saved session, to probe whether its host is up.
+    pub fn find_session_hosts(&self) -> Result<Vec<Session>, NxError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT group_name, name, host, port, auth_type FROM session WHERE is_template = 0",
+        )?;
         let mut rows = stmt.query(())?;
         let mut sessions = vec![];
         while let Some(row) = rows.next()? {
             sessions.push(Session {
-                id: row.get(0)?,
-                group: row.get(1)?,
-                name: row.get(2)?,
-                auth_type: row.get(3)?,
+                group: row.get(0)?,
+                name: row.get(1)?,
+                host: row.get(2)?,
+                port: row.get(3)?,
+                auth_type: row.get(4)?,
                 ..Default::default()
             });
         }
-        let mut session_groups: IndexMap<String, Vec<Session>> =
-            IndexMap::with_capacity(sessions.len());
-        for session in sessions {
-            session_groups
-                .entry(session.group.clone())
-                .or_default()
-                .push(session);
-        }
-        Ok(session_groups)
+        Ok(sessions)
     }
 
-    pub fn find_sessions(&self, key: &str) -> Result<IndexMap<String, Vec<Session>>> {
+    /// Sessions whose group, name, host or user contains `key`, ignoring the
+    /// case of ASCII letters.
+    pub fn find_sessions(&self, key: &str) -> Result<SessionGroups, NxError> {
         if key.is_empty() {
             return self.find_all_sessions();
         }
-        let mut stmt = self
-            .db
-            .prepare("SELECT id, group_name, name, auth_type FROM session where group_name like ?1 or name like ?1")?;
-        let mut rows = stmt.query((format!("%{key}%"),))?;
-        let mut sessions = vec![];
-        while let Some(row) = rows.next()? {
-            sessions.push(Session {
-                id: row.get(0)?,
-                group: row.get(1)?,
-                name: row.get(2)?,
-                auth_type: row.get(3)?,
-                ..Default::default()
-            });
-        }
-        let mut session_groups: IndexMap<String, Vec<Session>> =
-            IndexMap::with_capacity(sessions.len());
-        for session in sessions {
-            session_groups
-                .entry(session.group.clone())
-                .or_default()
-                .push(session);
-        }
-        Ok(session_groups)
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, group_name, name, auth_type FROM session WHERE is_template = 0 \
+                AND (group_name LIKE ?1 ESCAPE '\\' OR name LIKE ?1 ESCAPE '\\' \
+                    OR host LIKE ?1 ESCAPE '\\' OR username LIKE ?1 ESCAPE '\\')",
+        )?;
+        session_groups(stmt.query((like_pattern(key),))?)
     }
 
     pub fn insert_session(&self, session: Session) -> Result<(), NxError> {
         let time = Local::now().timestamp_millis() as u64;
-        self.db.execute(
+        self.conn.execute(
             "INSERT INTO session(group_name, name, host, port, auth_type, \
-                                     username, secret_data, secret_key, create_time) \
-                                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-            (
+                                     username, secret_data, secret_key, create_time, \
+                                     tint_mode, tint_color, protected_keys, font_family, font_size, \
+                                     host_fingerprint, compression, ciphers, kex_algorithms, is_template, \
+                                     plain_prompt, local_echo, idle_timeout, mac_address, \
+                                     nested_command, locale, alt_eight_bit, altgr_as_alt, option_as_meta, \
+                                     ambiguous_wide, narrow_emoji, theme, identity_file, forwards) \
+                                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, \
+                                     ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, \
+                                     ?29, ?30, ?31, ?32, ?33)",
+            params![
                 &session.group,
                 &session.name,
                 &session.host,
@@ -116,40 +422,536 @@ impl DbConn {
                 &session.secret_data,
                 &session.secret_key,
                 time,
-            ),
+                session.tint_mode,
+                session.tint_color,
+                &session.protected_keys,
+                &session.font_family,
+                session.font_size,
+                &session.host_fingerprint,
+                session.compression,
+                &session.ciphers,
+                &session.kex_algorithms,
+                session.template,
+                session.plain_prompt,
+                session.local_echo,
+                session.idle_timeout,
+                &session.mac_address,
+                &session.nested_command,
+                &session.locale,
+                session.alt_eight_bit,
+                session.altgr_as_alt,
+                session.option_as_meta,
+                session.ambiguous_wide,
+                session.narrow_emoji,
+                session.theme,
+                &session.identity_file,
+                &session.forwards,
+            ],
         )?;
         Ok(())
     }
 
-    pub fn find_session(&self, group_name: &str, name: &str) -> Result<Option<Session>> {
-        let mut stmt = self.db.prepare(
-            "SELECT id, group_name, name, host, port, auth_type, \
-                        username, secret_data, secret_key, create_time FROM session \
-                        WHERE group_name = ?1 AND name = ?2",
-        )?;
+    pub fn find_session(&self, group_name: &str, name: &str) -> Result<Option<Session>, NxError> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {SESSION_COLUMNS} FROM session WHERE group_name = ?1 AND name = ?2"
+        ))?;
         let mut rows = stmt.query((group_name, name))?;
-        if let Some(row) = rows.next()? {
-            return Ok(Some(Session {
-                id: row.get(0)?,
-                group: row.get(1)?,
-                name: row.get(2)?,
-                host: row.get(3)?,
-                port: row.get(4)?,
-                auth_type: row.get(5)?,
-                username: row.get(6)?,
-                secret_data: row.get(7)?,
-                secret_key: row.get(8)?,
-                create_time: row.get(9)?,
-            }));
-        }
-        Ok(None)
-    }
-
-    pub fn delete_session(&self, group_name: &str, name: &str) -> Result<()> {
-        self.db.execute(
+        match rows.next()? {
+            Some(row) => Ok(Some(session_from_row(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Sessions saved as templates for new ones, by name.
+    pub fn find_templates(&self) -> Result<Vec<Session>, NxError> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {SESSION_COLUMNS} FROM session WHERE is_template = 1 ORDER BY name"
+        ))?;
+        let mut rows = stmt.query(())?;
+        let mut templates = vec![];
+        while let Some(row) = rows.next()? {
+            templates.push(session_from_row(row)?);
+        }
+        Ok(templates)
+    }
+
+    /// Pin the host key of a session, see [`Session::host_fingerprint`].
+    pub fn update_host_fingerprint(
+        &self,
+        group_name: &str,
+        name: &str,
+        fingerprint: &str,
+    ) -> Result<(), NxError> {
+        self.conn.execute(
+            "UPDATE session SET host_fingerprint = ?3 WHERE group_name = ?1 AND name = ?2",
+            (group_name, name, fingerprint),
+        )?;
+        Ok(())
+    }
+
+    /// Remember the theme chosen for the terminals of a session.
+    pub fn update_session_theme(
+        &self,
+        group_name: &str,
+        name: &str,
+        theme: u16,
+    ) -> Result<(), NxError> {
+        self.conn.execute(
+            "UPDATE session SET theme = ?3 WHERE group_name = ?1 AND name = ?2",
+            (group_name, name, theme),
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_session(&self, group_name: &str, name: &str) -> Result<(), NxError> {
+        self.conn.execute(
             "DELETE FROM session WHERE group_name = ?1 AND name = ?2",
             (group_name, name),
         )?;
+        self.conn.execute(
+            "DELETE FROM bookmark WHERE group_name = ?1 AND session_name = ?2",
+            (group_name, name),
+        )?;
+        self.conn.execute(
+            "DELETE FROM session_open WHERE group_name = ?1 AND name = ?2",
+            (group_name, name),
+        )?;
+        self.conn.execute(
+            "DELETE FROM workspace WHERE group_name = ?1 AND session_name = ?2",
+            (group_name, name),
+        )?;
+        Ok(())
+    }
+
+    /// Count an open of a session in the usage statistics.
+    pub fn record_session_open(&self, group_name: &str, name: &str) -> Result<(), NxError> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO session_open(group_name, name, opened_at) VALUES (?1, ?2, ?3)",
+            (group_name, name, Local::now().timestamp_millis()),
+        )?;
+        tx.execute(
+            "DELETE FROM session_open WHERE rowid NOT IN \
+                (SELECT rowid FROM session_open ORDER BY opened_at DESC LIMIT ?1)",
+            (SESSION_OPEN_LIMIT,),
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Sessions often opened together and not saved as a workspace yet, nor
+    /// dismissed.
+    pub fn suggest_workspace(&self) -> Result<Option<Vec<SessionKey>>, NxError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT group_name, name, opened_at FROM session_open ORDER BY opened_at",
+        )?;
+        let mut rows = stmt.query(())?;
+        let mut opens = vec![];
+        while let Some(row) = rows.next()? {
+            opens.push(SessionOpen {
+                session: (row.get(0)?, row.get(1)?),
+                opened_at: row.get(2)?,
+            });
+        }
+        let mut known: HashSet<String> = self
+            .find_workspaces()?
+            .values()
+            .map(|members| members_key(members))
+            .collect();
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT members FROM dismissed_workspace")?;
+        let mut rows = stmt.query(())?;
+        while let Some(row) = rows.next()? {
+            known.insert(row.get(0)?);
+        }
+        Ok(suggest_workspace(&opens, &known))
+    }
+
+    /// Don't suggest `members` as a workspace anymore.
+    pub fn dismiss_workspace(&self, members: &[SessionKey]) -> Result<(), NxError> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO dismissed_workspace(members) VALUES (?1)",
+            (members_key(members),),
+        )?;
+        Ok(())
+    }
+
+    pub fn find_workspaces(&self) -> Result<Workspaces, NxError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT name, group_name, session_name FROM workspace ORDER BY name, position",
+        )?;
+        let mut rows = stmt.query(())?;
+        let mut workspaces = Workspaces::default();
+        while let Some(row) = rows.next()? {
+            workspaces
+                .entry(row.get(0)?)
+                .or_default()
+                .push((row.get(1)?, row.get(2)?));
+        }
+        Ok(workspaces)
+    }
+
+    /// Save `members` as the workspace `name`, in place of the one of that
+    /// name.
+    pub fn save_workspace(&self, name: &str, members: &[SessionKey]) -> Result<(), NxError> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM workspace WHERE name = ?1", (name,))?;
+        for (position, (group_name, session_name)) in members.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO workspace(name, group_name, session_name, position) \
+                    VALUES (?1, ?2, ?3, ?4)",
+                (name, group_name, session_name, position),
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn delete_workspace(&self, name: &str) -> Result<(), NxError> {
+        self.conn
+            .execute("DELETE FROM workspace WHERE name = ?1", (name,))?;
+        Ok(())
+    }
+
+    /// Prompts of the last keyboard-interactive authentication with `host`, in
+    /// the order they were asked.
+    pub fn find_auth_prompts(&self, host: &str) -> Result<Vec<PromptRecord>, NxError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT label, typed FROM auth_prompt WHERE host = ?1 ORDER BY position",
+        )?;
+        let mut rows = stmt.query((host,))?;
+        let mut prompts = vec![];
+        while let Some(row) = rows.next()? {
+            prompts.push(PromptRecord {
+                label: row.get(0)?,
+                typed: row.get(1)?,
+            });
+        }
+        Ok(prompts)
+    }
+
+    pub fn replace_auth_prompts(
+        &self,
+        host: &str,
+        prompts: &[PromptRecord],
+    ) -> Result<(), NxError> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM auth_prompt WHERE host = ?1", (host,))?;
+        for (position, prompt) in prompts.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO auth_prompt(host, position, label, typed) VALUES (?1, ?2, ?3, ?4)",
+                (host, position, &prompt.label, prompt.typed),
+            )?;
+        }
+        tx.commit()?;
         Ok(())
     }
+
+    /// Commands run at the prompt of `host`, the most recent first.
+    pub fn find_command_history(&self, host: &str) -> Result<Vec<String>, NxError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT command FROM command_history WHERE host = ?1 \
+                        ORDER BY last_used DESC LIMIT ?2",
+        )?;
+        let mut rows = stmt.query((host, COMMAND_HISTORY_LIMIT))?;
+        let mut commands = vec![];
+        while let Some(row) = rows.next()? {
+            commands.push(row.get(0)?);
+        }
+        Ok(commands)
+    }
+
+    /// Move `command` to the top of the history of `host`.
+    pub fn record_command(&self, host: &str, command: &str) -> Result<(), NxError> {
+        let tx = self.conn.unchecked_transaction()?;
+        // a sequence rather than a timestamp, commands may run in the same second
+        tx.execute(
+            "INSERT INTO command_history(host, command, last_used) \
+                VALUES (?1, ?2, (SELECT COALESCE(MAX(last_used), 0) + 1 FROM command_history)) \
+                ON CONFLICT (host, command) DO UPDATE SET last_used = excluded.last_used",
+            (host, command),
+        )?;
+        tx.execute(
+            "DELETE FROM command_history WHERE host = ?1 AND command NOT IN \
+                (SELECT command FROM command_history WHERE host = ?1 \
+                    ORDER BY last_used DESC LIMIT ?2)",
+            (host, COMMAND_HISTORY_LIMIT),
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Remote directories bookmarked for a session, sorted by path.
+    pub fn find_bookmarks(&self, group_name: &str, name: &str) -> Result<Vec<String>, NxError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT path FROM bookmark WHERE group_name = ?1 AND session_name = ?2 \
+                        ORDER BY path",
+        )?;
+        let mut rows = stmt.query((group_name, name))?;
+        let mut bookmarks = vec![];
+        while let Some(row) = rows.next()? {
+            bookmarks.push(row.get(0)?);
+        }
+        Ok(bookmarks)
+    }
+
+    pub fn insert_bookmark(&self, group_name: &str, name: &str, path: &str) -> Result<(), NxError> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO bookmark(group_name, session_name, path) VALUES (?1, ?2, ?3)",
+            (group_name, name, path),
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_bookmark(&self, group_name: &str, name: &str, path: &str) -> Result<(), NxError> {
+        self.conn.execute(
+            "DELETE FROM bookmark WHERE group_name = ?1 AND session_name = ?2 AND path = ?3",
+            (group_name, name, path),
+        )?;
+        Ok(())
+    }
+}
+
+/// Sessions of the rows of a listing, by group in the order of the rows.
+fn session_groups(mut rows: Rows<'_>) -> Result<SessionGroups, NxError> {
+    let mut session_groups = SessionGroups::default();
+    while let Some(row) = rows.next()? {
+        let session = Session {
+            id: row.get(0)?,
+            group: row.get(1)?,
+            name: row.get(2)?,
+            auth_type: row.get(3)?,
+            ..Default::default()
+        };
+        session_groups
+            .entry(session.group.clone())
+            .or_default()
+            .push(session);
+    }
+    Ok(session_groups)
+}
+
+/// Pattern of LIKE matching the values which contain `key`, its wildcards
+/// escaped with `\`.
+fn like_pattern(key: &str) -> String {
+    let mut pattern = String::with_capacity(key.len() + 2);
+    pattern.push('%');
+    for c in key.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// Add a column to a table created by an older release, if it is missing.
+fn add_column(db: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = db.prepare(&format!(
+        "SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1"
+    ))?;
+    if !stmt.exists((column,))? {
+        db.execute(
+            &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+            (),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add_column, like_pattern, Db, PromptRecord, Session, COMMAND_HISTORY_LIMIT};
+    use rusqlite::Connection;
+    use std::path::Path;
+
+    #[test]
+    fn session_and_bookmarks() {
+        let db = Db::open(Path::new(":memory:")).unwrap();
+        db.insert_session(Session {
+            group: "prod".to_string(),
+            name: "web".to_string(),
+            host: "10.0.0.1".to_string(),
+            port: 22,
+            font_family: "Fira Code".to_string(),
+            font_size: 16.,
+            compression: 1,
+            ciphers: "aes128-ctr,aes256-ctr".to_string(),
+            plain_prompt: true,
+            local_echo: true,
+            idle_timeout: 15,
+            nested_command: "ssh db-1".to_string(),
+            locale: "C.UTF-8".to_string(),
+            altgr_as_alt: true,
+            ambiguous_wide: true,
+            identity_file: "~/.ssh/id_ed25519".to_string(),
+            forwards: "L 8080:localhost:80\nL 9000:[::1]:3000".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        db.insert_bookmark("prod", "web", "/var/log").unwrap();
+        db.insert_bookmark("prod", "web", "/var/log").unwrap();
+
+        let sessions = db.find_all_sessions().unwrap();
+        assert_eq!(sessions["prod"].len(), 1);
+        let session = db.find_session("prod", "web").unwrap().unwrap();
+        assert_eq!(
+            (session.font_family.as_str(), session.font_size),
+            ("Fira Code", 16.)
+        );
+        assert_eq!(
+            (session.compression, session.ciphers.as_str()),
+            (1, "aes128-ctr,aes256-ctr")
+        );
+        assert!(session.kex_algorithms.is_empty());
+        assert!(session.plain_prompt);
+        assert!(session.local_echo);
+        assert_eq!(session.idle_timeout, 15);
+        assert_eq!(session.nested_command, "ssh db-1");
+        assert_eq!(session.locale, "C.UTF-8");
+        assert_eq!(session.identity_file, "~/.ssh/id_ed25519");
+        assert_eq!(session.forwards, "L 8080:localhost:80\nL 9000:[::1]:3000");
+        assert!(!session.alt_eight_bit && session.altgr_as_alt);
+        assert!(session.ambiguous_wide && !session.narrow_emoji);
+        assert!(session.host_fingerprint.is_empty());
+        db.update_host_fingerprint("prod", "web", "SHA256:q1w2e3r4")
+            .unwrap();
+        let session = db.find_session("prod", "web").unwrap().unwrap();
+        assert_eq!(session.host_fingerprint, "SHA256:q1w2e3r4");
+        assert_eq!(session.theme, 0);
+        db.update_session_theme("prod", "web", 2).unwrap();
+        let session = db.find_session("prod", "web").unwrap().unwrap();
+        assert_eq!(session.theme, 2);
+        assert_eq!(db.find_bookmarks("prod", "web").unwrap(), ["/var/log"]);
+
+        db.insert_session(Session {
+            group: "prod".to_string(),
+            name: "web template".to_string(),
+            port: 22,
+            template: true,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(db.find_all_sessions().unwrap()["prod"].len(), 1);
+        assert_eq!(db.find_sessions("web").unwrap()["prod"].len(), 1);
+        assert_eq!(db.find_sessions("0.0.1").unwrap()["prod"].len(), 1);
+        assert!(db.find_sessions("10%1").unwrap().is_empty());
+        let templates = db.find_templates().unwrap();
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].name, "web template");
+
+        db.delete_session("prod", "web").unwrap();
+        assert!(db.find_session("prod", "web").unwrap().is_none());
+        assert!(db.find_bookmarks("prod", "web").unwrap().is_empty());
+    }
+
+    #[test]
+    fn search_sessions() {
+        let db = Db::open(Path::new(":memory:")).unwrap();
+        for (group, name, host, username) in [
+            ("prod", "web", "10.0.0.1", "deploy"),
+            ("staging", "db_1", "db.staging", "postgres"),
+        ] {
+            db.insert_session(Session {
+                group: group.to_string(),
+                name: name.to_string(),
+                host: host.to_string(),
+                port: 22,
+                username: username.to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+        }
+        assert!(db
+            .find_sessions("POSTGRES")
+            .unwrap()
+            .contains_key("staging"));
+        assert!(db.find_sessions("staging").unwrap().contains_key("staging"));
+        assert_eq!(db.find_sessions("deploy").unwrap().len(), 1);
+        // the wildcards are matched as they are
+        assert!(db.find_sessions("0_0").unwrap().is_empty());
+        let hosts = db.find_session_hosts().unwrap();
+        assert_eq!((hosts[1].host.as_str(), hosts[1].port), ("db.staging", 22));
+        assert_eq!(db.find_sessions("b_").unwrap().len(), 1);
+        assert_eq!(like_pattern("50%_\\"), "%50\\%\\_\\\\%");
+    }
+
+    #[test]
+    fn remember_auth_prompts() {
+        let db = Db::open(Path::new(":memory:")).unwrap();
+        let prompt = |label: &str, typed| PromptRecord {
+            label: label.to_string(),
+            typed,
+        };
+        db.replace_auth_prompts("10.0.0.1", &[prompt("Code: ", true)])
+            .unwrap();
+        let prompts = [prompt("Password: ", false), prompt("Code: ", true)];
+        db.replace_auth_prompts("10.0.0.1", &prompts).unwrap();
+
+        assert_eq!(db.find_auth_prompts("10.0.0.1").unwrap(), prompts);
+        assert!(db.find_auth_prompts("10.0.0.2").unwrap().is_empty());
+    }
+
+    #[test]
+    fn record_command_history() {
+        let db = Db::open(Path::new(":memory:")).unwrap();
+        for command in ["ls", "git status", "ls", "top"] {
+            db.record_command("10.0.0.1", command).unwrap();
+        }
+        db.record_command("", "cargo build").unwrap();
+
+        assert_eq!(
+            db.find_command_history("10.0.0.1").unwrap(),
+            ["top", "ls", "git status"]
+        );
+        assert_eq!(db.find_command_history("").unwrap(), ["cargo build"]);
+
+        for n in 0..COMMAND_HISTORY_LIMIT {
+            db.record_command("10.0.0.1", &format!("echo {n}")).unwrap();
+        }
+        let history = db.find_command_history("10.0.0.1").unwrap();
+        assert_eq!(history.len(), COMMAND_HISTORY_LIMIT);
+        assert!(!history.iter().any(|command| command == "top"));
+    }
+
+    #[test]
+    fn workspaces() {
+        let db = Db::open(Path::new(":memory:")).unwrap();
+        let key = |name: &str| ("lab".to_string(), name.to_string());
+        for _ in 0..3 {
+            db.record_session_open("lab", "web-1").unwrap();
+            db.record_session_open("lab", "db-1").unwrap();
+        }
+        // opened within minutes, as a single burst
+        assert_eq!(db.suggest_workspace().unwrap(), None);
+
+        let members = [key("web-1"), key("db-1")];
+        db.save_workspace("lab", &members).unwrap();
+        assert_eq!(db.find_workspaces().unwrap()["lab"], members);
+        db.delete_session("lab", "db-1").unwrap();
+        assert_eq!(db.find_workspaces().unwrap()["lab"], [key("web-1")]);
+        db.delete_workspace("lab").unwrap();
+        assert!(db.find_workspaces().unwrap().is_empty());
+
+        db.dismiss_workspace(&members).unwrap();
+        db.dismiss_workspace(&members).unwrap();
+    }
+
+    #[test]
+    fn add_missing_column_once() {
+        let db = Connection::open_in_memory().unwrap();
+        db.execute("CREATE TABLE session (id INTEGER PRIMARY KEY)", ())
+            .unwrap();
+        db.execute("INSERT INTO session (id) VALUES (1)", ())
+            .unwrap();
+
+        add_column(&db, "session", "tint_mode", "INTEGER NOT NULL DEFAULT 0").unwrap();
+        add_column(&db, "session", "tint_mode", "INTEGER NOT NULL DEFAULT 0").unwrap();
+        let mode: u16 = db
+            .query_row("SELECT tint_mode FROM session", (), |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, 0);
+    }
 }
@@ -0,0 +1,177 @@
+use crate::latency::{address, probe};
+use egui_term::SshOptions;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tracing::error;
+
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+/// Pings kept for the chart and the statistics.
+pub const PING_HISTORY: usize = 120;
+
+/// Round trips of the pings, `None` for the lost ones.
+pub type Pings = VecDeque<Option<Duration>>;
+
+/// Pings a ssh host every second with a TCP handshake to its port, which
+/// needs no privileges unlike ICMP, and traces the route to it with the tool
+/// of the system. Both stop once the diagnosis is dropped.
+pub struct Diagnosis {
+    pub host: String,
+    pub port: u16,
+    pings: Arc<Mutex<Pings>>,
+    /// Output of the traceroute, line by line.
+    route: Arc<Mutex<Vec<String>>>,
+    tracer: Option<Child>,
+}
+
+impl Diagnosis {
+    pub fn spawn(options: &SshOptions) -> Self {
+        let (host, port) = address(options);
+        let pings = Arc::new(Mutex::new(VecDeque::with_capacity(PING_HISTORY)));
+        let weak = Arc::downgrade(&pings);
+        let target = host.clone();
+
+        let spawned = std::thread::Builder::new()
+            .name(format!("ping_{host}"))
+            .spawn(move || {
+                while let Some(pings) = weak.upgrade() {
+                    let rtt = probe(&target, port);
+                    let mut pings = pings.lock().unwrap_or_else(PoisonError::into_inner);
+                    if pings.len() == PING_HISTORY {
+                        pings.pop_front();
+                    }
+                    pings.push_back(rtt);
+                    drop(pings);
+                    std::thread::sleep(PING_INTERVAL);
+                }
+            });
+        if let Err(err) = spawned {
+            error!("failed to spawn the ping of {host}: {err}");
+        }
+        Self {
+            host,
+            port,
+            pings,
+            route: Arc::default(),
+            tracer: None,
+        }
+    }
+
+    pub fn pings(&self) -> Pings {
+        self.pings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn route(&self) -> Vec<String> {
+        self.route
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Whether the traceroute is still running.
+    pub fn is_tracing(&mut self) -> bool {
+        self.tracer
+            .as_mut()
+            .is_some_and(|tracer| matches!(tracer.try_wait(), Ok(None)))
+    }
+
+    /// Trace the route to the host again, with `traceroute`, or `tracert` on
+    /// Windows.
+    pub fn trace_route(&mut self) -> std::io::Result<()> {
+        self.stop_tracing();
+        let mut command = match cfg!(windows) {
+            true => Command::new("tracert"),
+            false => Command::new("traceroute"),
+        };
+        let mut tracer = command
+            .arg(&self.host)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdout = tracer.stdout.take();
+        self.tracer = Some(tracer);
+
+        let route = self.route.clone();
+        route.lock().unwrap_or_else(PoisonError::into_inner).clear();
+        std::thread::Builder::new()
+            .name(format!("traceroute_{}", self.host))
+            .spawn(move || {
+                let Some(stdout) = stdout else {
+                    return;
+                };
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    route
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push(line);
+                }
+            })?;
+        Ok(())
+    }
+
+    fn stop_tracing(&mut self) {
+        if let Some(mut tracer) = self.tracer.take() {
+            let _ = tracer.kill();
+            let _ = tracer.wait();
+        }
+    }
+}
+
+impl Drop for Diagnosis {
+    fn drop(&mut self) {
+        self.stop_tracing();
+    }
+}
+
+/// Last, minimum, average and maximum round trips, and the share of the pings
+/// lost.
+#[derive(Debug, Default, PartialEq)]
+pub struct PingStats {
+    pub last: Option<Duration>,
+    pub min: Option<Duration>,
+    pub avg: Option<Duration>,
+    pub max: Option<Duration>,
+    pub loss: f32,
+}
+
+pub fn ping_stats(pings: &Pings) -> PingStats {
+    if pings.is_empty() {
+        return PingStats::default();
+    }
+    let received: Vec<Duration> = pings.iter().flatten().copied().collect();
+    let avg = match received.len() {
+        0 => None,
+        count => Some(received.iter().sum::<Duration>() / count as u32),
+    };
+    PingStats {
+        last: pings.back().copied().flatten(),
+        min: received.iter().min().copied(),
+        avg,
+        max: received.iter().max().copied(),
+        loss: 1. - received.len() as f32 / pings.len() as f32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_of_the_pings() {
+        assert_eq!(ping_stats(&Pings::new()), PingStats::default());
+
+        let ms = Duration::from_millis;
+        let pings = Pings::from([Some(ms(10)), None, Some(ms(30)), Some(ms(20))]);
+        let stats = ping_stats(&pings);
+        assert_eq!(stats.last, Some(ms(20)));
+        assert_eq!((stats.min, stats.max), (Some(ms(10)), Some(ms(30))));
+        assert_eq!(stats.avg, Some(ms(20)));
+        assert_eq!(stats.loss, 0.25);
+    }
+}
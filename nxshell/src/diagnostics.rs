@@ -0,0 +1,301 @@
+use chrono::Local;
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard, PoisonError};
+use tracing::{Level, Metadata};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Number of log lines kept for the log viewer, crash reports and diagnostics.
+const LOG_CAPACITY: usize = 2000;
+const CRASH_DIR: &str = "crashes";
+
+static LOG_BUFFER: LazyLock<LogBuffer> = LazyLock::new(LogBuffer::default);
+
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub level: Level,
+    /// Formatted event, without the trailing newline.
+    pub text: String,
+}
+
+/// The most recent log lines, in memory. Used as a writer of the tracing
+/// subscriber.
+#[derive(Clone, Default)]
+pub struct LogBuffer {
+    lines: Arc<Mutex<VecDeque<LogLine>>>,
+}
+
+impl LogBuffer {
+    pub fn global() -> &'static LogBuffer {
+        &LOG_BUFFER
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<LogLine>> {
+        self.lines.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push(&self, line: LogLine) {
+        let mut lines = self.lock();
+        if lines.len() == LOG_CAPACITY {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    pub fn contents(&self) -> String {
+        self.lock()
+            .iter()
+            .fold(String::new(), |mut contents, line| {
+                contents.push_str(&line.text);
+                contents.push('\n');
+                contents
+            })
+    }
+
+    /// Lines at `level` or more severe, containing `query` ignoring the case.
+    pub fn filter(&self, level: Level, query: &str) -> Vec<LogLine> {
+        let query = query.to_lowercase();
+        self.lock()
+            .iter()
+            .filter(|line| line.level <= level && line.text.to_lowercase().contains(&query))
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn writer(&self, level: Level) -> LogWriter {
+        LogWriter {
+            buffer: self.clone(),
+            level,
+            event: vec![],
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogBuffer {
+    type Writer = LogWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.writer(Level::INFO)
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        self.writer(*meta.level())
+    }
+}
+
+/// Collects one formatted event, stored once dropped.
+pub struct LogWriter {
+    buffer: LogBuffer,
+    level: Level,
+    event: Vec<u8>,
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.event.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        if !self.event.is_empty() {
+            let text = String::from_utf8_lossy(&self.event);
+            self.buffer.push(LogLine {
+                level: self.level,
+                text: text.trim_end().to_string(),
+            });
+        }
+    }
+}
+
+fn system_info() -> String {
+    format!(
+        "nxshell {}\nos: {} {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )
+}
+
+/// Write a crash report to `data_dir` when a thread panics, before the
+/// default hook runs. The release profile aborts on panic.
+pub fn install_panic_hook(data_dir: PathBuf) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let thread = std::thread::current();
+        let report = format!(
+            "{}time: {}\nthread: {}\n\n{info}\n\n{}\n\nrecent logs:\n{}",
+            system_info(),
+            Local::now().to_rfc3339(),
+            thread.name().unwrap_or("<unnamed>"),
+            Backtrace::force_capture(),
+            LogBuffer::global().contents(),
+        );
+        let dir = data_dir.join(CRASH_DIR);
+        let path = dir.join(format!(
+            "crash-{}.txt",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let written = std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(&path, report));
+        match written {
+            Ok(_) => eprintln!("crash report written to {}", path.display()),
+            Err(err) => eprintln!("failed to write the crash report: {err}"),
+        }
+        default_hook(info);
+    }));
+}
+
+/// Zip the recent logs and the crash reports into `data_dir`, returns the
+/// path of the archive.
+pub fn export(data_dir: &Path) -> io::Result<PathBuf> {
+    let mut zip = ZipWriter::default();
+    zip.add("system.txt", system_info().as_bytes());
+    zip.add("logs.txt", LogBuffer::global().contents().as_bytes());
+    if let Ok(entries) = std::fs::read_dir(data_dir.join(CRASH_DIR)) {
+        for entry in entries.flatten() {
+            let name = format!("{CRASH_DIR}/{}", entry.file_name().to_string_lossy());
+            zip.add(&name, &std::fs::read(entry.path())?);
+        }
+    }
+
+    let name = format!("diagnostics-{}.zip", Local::now().format("%Y%m%d-%H%M%S"));
+    let path = data_dir.join(name);
+    std::fs::write(&path, zip.finish())?;
+    Ok(path)
+}
+
+/// Minimal zip archive with uncompressed entries.
+#[derive(Default)]
+struct ZipWriter {
+    data: Vec<u8>,
+    central_directory: Vec<u8>,
+    entries: u16,
+}
+
+impl ZipWriter {
+    const VERSION: u16 = 20;
+    /// File names are UTF-8.
+    const FLAGS: u16 = 1 << 11;
+    /// 1980-01-01 00:00, the earliest MS-DOS date.
+    const DOS_TIME: u16 = 0;
+    const DOS_DATE: u16 = (1 << 5) | 1;
+
+    fn add(&mut self, name: &str, content: &[u8]) {
+        let offset = self.data.len() as u32;
+        let crc = crc32fast::hash(content);
+        let size = content.len() as u32;
+
+        let mut header = vec![];
+        put_u16(&mut header, Self::VERSION);
+        put_u16(&mut header, Self::FLAGS);
+        put_u16(&mut header, 0); // stored
+        put_u16(&mut header, Self::DOS_TIME);
+        put_u16(&mut header, Self::DOS_DATE);
+        put_u32(&mut header, crc);
+        put_u32(&mut header, size);
+        put_u32(&mut header, size);
+        put_u16(&mut header, name.len() as u16);
+        put_u16(&mut header, 0); // extra field
+
+        put_u32(&mut self.data, 0x04034b50);
+        self.data.extend_from_slice(&header);
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(content);
+
+        let central = &mut self.central_directory;
+        put_u32(central, 0x02014b50);
+        put_u16(central, Self::VERSION);
+        central.extend_from_slice(&header);
+        put_u16(central, 0); // comment
+        put_u16(central, 0); // disk
+        put_u16(central, 0); // internal attributes
+        put_u32(central, 0); // external attributes
+        put_u32(central, offset);
+        central.extend_from_slice(name.as_bytes());
+        self.entries += 1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let offset = self.data.len() as u32;
+        let size = self.central_directory.len() as u32;
+        self.data.append(&mut self.central_directory);
+        put_u32(&mut self.data, 0x06054b50);
+        put_u16(&mut self.data, 0); // disk
+        put_u16(&mut self.data, 0); // disk of the central directory
+        put_u16(&mut self.data, self.entries);
+        put_u16(&mut self.data, self.entries);
+        put_u32(&mut self.data, size);
+        put_u32(&mut self.data, offset);
+        put_u16(&mut self.data, 0); // comment
+        self.data
+    }
+}
+
+fn put_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LogBuffer, ZipWriter, LOG_CAPACITY};
+    use std::io::Write;
+    use tracing::Level;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    #[test]
+    fn log_buffer_keeps_recent_lines() {
+        let buffer = LogBuffer::default();
+        for i in 0..LOG_CAPACITY + 1 {
+            writeln!(buffer.make_writer(), "{i}").unwrap();
+        }
+        let contents = buffer.contents();
+        assert!(contents.starts_with("1\n"));
+        assert!(contents.ends_with(&format!("{LOG_CAPACITY}\n")));
+    }
+
+    #[test]
+    fn filter_by_level_and_query() {
+        let buffer = LogBuffer::default();
+        writeln!(buffer.writer(Level::ERROR), "ERROR connect failed").unwrap();
+        writeln!(buffer.writer(Level::INFO), "INFO Connected").unwrap();
+        writeln!(buffer.writer(Level::DEBUG), "DEBUG connect packet").unwrap();
+
+        assert_eq!(buffer.filter(Level::TRACE, "").len(), 3);
+        assert_eq!(buffer.filter(Level::INFO, "CONNECT").len(), 2);
+        let errors = buffer.filter(Level::ERROR, "");
+        assert_eq!(errors[0].text, "ERROR connect failed");
+        buffer.clear();
+        assert!(buffer.filter(Level::TRACE, "").is_empty());
+    }
+
+    #[test]
+    fn zip_layout() {
+        let mut zip = ZipWriter::default();
+        zip.add("a.txt", b"hello");
+        zip.add("b.txt", b"");
+        let data = zip.finish();
+
+        assert_eq!(&data[..4], &0x04034b50u32.to_le_bytes());
+        assert_eq!(&data[14..18], &crc32fast::hash(b"hello").to_le_bytes());
+        let end = &data[data.len() - 22..];
+        assert_eq!(&end[..4], &0x06054b50u32.to_le_bytes());
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+        let offset = u32::from_le_bytes(end[16..20].try_into().unwrap()) as usize;
+        assert_eq!(&data[offset..offset + 4], &0x02014b50u32.to_le_bytes());
+    }
+}
@@ -17,6 +17,28 @@ pub enum NxError {
     FromUtf8(#[from] FromUtf8Error),
 }
 
+pub fn info_toast<E: Into<WidgetText>>(text: E) -> Toast {
+    Toast {
+        text: text.into(),
+        kind: ToastKind::Info,
+        options: ToastOptions::default()
+            .duration_in_seconds(3.0)
+            .show_progress(true),
+        ..Default::default()
+    }
+}
+
+pub fn warning_toast<E: Into<WidgetText>>(text: E) -> Toast {
+    Toast {
+        text: text.into(),
+        kind: ToastKind::Warning,
+        options: ToastOptions::default()
+            .duration_in_seconds(10.0)
+            .show_progress(true),
+        ..Default::default()
+    }
+}
+
 pub fn error_toast<E: Into<WidgetText>>(err: E) -> Toast {
     Toast {
         text: err.into(),
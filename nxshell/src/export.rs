@@ -0,0 +1,259 @@
+//! Images of the terminal views and prints of their history, written without
+//! compression like the diagnostics archive.
+
+use egui::ColorImage;
+use egui_term::TextRun;
+use std::fmt::Write;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Largest block of a deflate stream without compression.
+const STORED_BLOCK_LEN: usize = u16::MAX as usize;
+
+/// `image` as a PNG, in RGBA.
+pub fn png(image: &ColorImage) -> Vec<u8> {
+    let [width, height] = image.size;
+    // each row after its filter, none
+    let mut raw = Vec::with_capacity(height * (1 + width * 4));
+    if width > 0 {
+        for row in image.pixels.chunks(width) {
+            raw.push(0);
+            for pixel in row {
+                raw.extend_from_slice(&pixel.to_srgba_unmultiplied());
+            }
+        }
+    }
+
+    let mut header = vec![];
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, no filter nor interlace
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    put_chunk(&mut png, b"IHDR", &header);
+    put_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    put_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn put_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(data);
+    png.extend_from_slice(&crc.finalize().to_be_bytes());
+}
+
+/// `data` in a zlib stream of stored deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = data.chunks(STORED_BLOCK_LEN).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        zlib.push(last as u8);
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(data).to_be_bytes());
+    zlib
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // the sums can't overflow over this many bytes
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+/// Points of an A4 page, wider for the lines which don't fit.
+const PAGE_WIDTH: f32 = 595.;
+const PAGE_HEIGHT: f32 = 842.;
+const MARGIN: f32 = 36.;
+const FONT_SIZE: f32 = 7.;
+const LEADING: f32 = 8.5;
+/// Width of the chars of Courier, by point of the font size.
+const CHAR_WIDTH: f32 = 0.6;
+const LINES_PER_PAGE: usize = ((PAGE_HEIGHT - 2. * MARGIN) / LEADING) as usize;
+
+/// `lines` printed in Courier on as many pages as they take, in their colors
+/// on white. The chars out of Latin-1 are replaced, the standard fonts of PDF
+/// don't have them.
+pub fn pdf(lines: &[Vec<TextRun>]) -> Vec<u8> {
+    let columns = lines
+        .iter()
+        .map(|runs| runs.iter().map(|run| run.text.chars().count()).sum())
+        .max()
+        .unwrap_or(0);
+    let width = (2. * MARGIN + columns as f32 * FONT_SIZE * CHAR_WIDTH).max(PAGE_WIDTH);
+    let mut pages: Vec<&[Vec<TextRun>]> = lines.chunks(LINES_PER_PAGE).collect();
+    if pages.is_empty() {
+        pages.push(&[]);
+    }
+
+    // the catalog, the page tree and the fonts, then each page and its content
+    let page_id = |index: usize| 5 + 2 * index;
+    let kids: Vec<String> = (0..pages.len())
+        .map(|index| format!("{} 0 R", page_id(index)))
+        .collect();
+    let mut pdf = PdfWriter::default();
+    pdf.object(b"<< /Type /Catalog /Pages 2 0 R >>");
+    pdf.object(
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        )
+        .as_bytes(),
+    );
+    for font in ["Courier", "Courier-Bold"] {
+        let font = format!(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /{font} /Encoding /WinAnsiEncoding >>"
+        );
+        pdf.object(font.as_bytes());
+    }
+    for (index, page) in pages.into_iter().enumerate() {
+        pdf.object(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {width} {PAGE_HEIGHT}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                page_id(index) + 1
+            )
+            .as_bytes(),
+        );
+        let content = page_content(page);
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend_from_slice(&content);
+        stream.extend_from_slice(b"\nendstream");
+        pdf.object(&stream);
+    }
+    pdf.finish()
+}
+
+fn page_content(lines: &[Vec<TextRun>]) -> Vec<u8> {
+    let mut content = format!(
+        "BT\n{LEADING} TL\n{MARGIN} {} Td\n",
+        PAGE_HEIGHT - MARGIN - FONT_SIZE
+    )
+    .into_bytes();
+    for runs in lines {
+        for run in runs {
+            let font = if run.bold { 2 } else { 1 };
+            let [r, g, b, _] = run
+                .color
+                .map_or([0.; 4], |color| color.to_normalized_gamma_f32());
+            content.extend_from_slice(
+                format!("/F{font} {FONT_SIZE} Tf {r:.3} {g:.3} {b:.3} rg (").as_bytes(),
+            );
+            for c in run.text.chars() {
+                match c {
+                    '(' | ')' | '\\' => content.extend_from_slice(&[b'\\', c as u8]),
+                    ' '..='~' | '\u{a0}'..='\u{ff}' => content.push(c as u8),
+                    _ => content.push(b'?'),
+                }
+            }
+            content.extend_from_slice(b") Tj\n");
+        }
+        content.extend_from_slice(b"T*\n");
+    }
+    content.extend_from_slice(b"ET");
+    content
+}
+
+const PDF_HEADER: &[u8] = b"%PDF-1.4\n";
+
+/// PDF file of numbered objects, with their cross-reference table.
+#[derive(Default)]
+struct PdfWriter {
+    data: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl PdfWriter {
+    fn object(&mut self, body: &[u8]) {
+        if self.data.is_empty() {
+            self.data.extend_from_slice(PDF_HEADER);
+        }
+        self.offsets.push(self.data.len());
+        let id = self.offsets.len();
+        self.data
+            .extend_from_slice(format!("{id} 0 obj\n").as_bytes());
+        self.data.extend_from_slice(body);
+        self.data.extend_from_slice(b"\nendobj\n");
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let xref = self.data.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in &self.offsets {
+            let _ = writeln!(table, "{offset:010} 00000 n ");
+        }
+        let _ = write!(
+            table,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            self.offsets.len() + 1
+        );
+        self.data.extend_from_slice(table.as_bytes());
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::Color32;
+
+    #[test]
+    fn png_of_an_image() {
+        let image = ColorImage::new([2, 1], vec![Color32::RED, Color32::BLUE]);
+        let png = png(&image);
+        assert!(png.starts_with(PNG_SIGNATURE));
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 2, 0, 0, 0, 1]);
+        // IEND and its well-known CRC
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+
+        let raw = [0, 255, 0, 0, 255, 0, 0, 255, 255];
+        let zlib = zlib_stored(&raw);
+        assert_eq!(&zlib[2..7], &[1, 9, 0, 0xf6, 0xff]);
+        assert_eq!(&zlib[7..16], &raw);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn pdf_of_the_history() {
+        let line = |text: &str| {
+            vec![TextRun {
+                text: text.to_string(),
+                color: Some(Color32::RED),
+                bold: true,
+            }]
+        };
+        let mut lines = vec![line("f(x) \\ é 中")];
+        lines.extend((0..LINES_PER_PAGE).map(|i| line(&i.to_string())));
+        let pdf = pdf(&lines);
+        assert!(pdf.starts_with(b"%PDF-1.4\n"));
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("/F2 7 Tf 1.000 0.000 0.000 rg (f\\(x\\) \\\\ \u{fffd} ?) Tj"));
+
+        let startxref = text.rsplit("startxref\n").next().unwrap();
+        let xref: usize = startxref.lines().next().unwrap().parse().unwrap();
+        assert!(pdf[xref..].starts_with(b"xref\n0 9\n"));
+    }
+}
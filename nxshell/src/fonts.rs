@@ -0,0 +1,214 @@
+use crate::i18n::t;
+use egui::{ComboBox, FontData, FontDefinitions, FontFamily, FontId, Response};
+use fontdb::{Database, Family, Query};
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use tracing::{error, warn};
+
+/// Monospace font bundled with nxshell, it also covers the CJK characters.
+const BUNDLED_FONT: &str = "MapleMono";
+const BUNDLED_FONT_DATA: &[u8] = include_bytes!("../assets/fonts/MapleMono-NF-CN-Light.ttf");
+
+/// Fonts of the terminals.
+///
+/// The installed monospace fonts are enumerated on a background thread and
+/// loaded into egui the first time a terminal uses them. A family which is
+/// missing, or not loaded yet, falls back to the bundled font.
+pub struct FontManager {
+    definitions: FontDefinitions,
+    receiver: Receiver<Database>,
+    database: Option<Database>,
+    /// Installed monospace families, `None` until they are enumerated.
+    families: Option<Vec<String>>,
+    /// Families egui can render this frame.
+    ready: HashSet<String>,
+    /// Families handed to egui, it renders them from the next frame.
+    pending: Vec<String>,
+    /// Families which couldn't be loaded.
+    failed: HashSet<String>,
+}
+
+impl FontManager {
+    pub fn new(ctx: &egui::Context) -> Self {
+        let mut definitions = FontDefinitions::default();
+        definitions.font_data.insert(
+            BUNDLED_FONT.to_owned(),
+            Arc::new(FontData::from_static(BUNDLED_FONT_DATA)),
+        );
+        definitions
+            .families
+            .entry(FontFamily::Monospace)
+            .or_default()
+            .push(BUNDLED_FONT.to_owned());
+        // fallback of the user interface for the CJK characters
+        definitions
+            .families
+            .entry(FontFamily::Proportional)
+            .or_default()
+            .push(BUNDLED_FONT.to_owned());
+        // add egui icon
+        egui_phosphor::add_to_fonts(&mut definitions, egui_phosphor::Variant::Regular);
+        ctx.set_fonts(definitions.clone());
+
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+        let spawned = std::thread::Builder::new()
+            .name("fonts".to_string())
+            .spawn(move || {
+                let mut database = Database::new();
+                database.load_system_fonts();
+                if sender.send(database).is_ok() {
+                    ctx.request_repaint();
+                }
+            });
+        if let Err(err) = spawned {
+            error!("failed to enumerate the system fonts: {err}");
+        }
+
+        Self {
+            definitions,
+            receiver,
+            database: None,
+            families: None,
+            ready: HashSet::new(),
+            pending: vec![],
+            failed: HashSet::new(),
+        }
+    }
+
+    /// Called at the start of every frame.
+    pub fn begin_frame(&mut self) {
+        self.ready.extend(self.pending.drain(..));
+        if let Ok(database) = self.receiver.try_recv() {
+            self.set_database(database);
+        }
+    }
+
+    fn set_database(&mut self, database: Database) {
+        let mut families: Vec<String> = database
+            .faces()
+            .filter(|face| face.monospaced)
+            .filter_map(|face| face.families.first().map(|(name, _)| name.clone()))
+            .collect();
+        families.sort_unstable();
+        families.dedup();
+        self.families = Some(families);
+        self.database = Some(database);
+    }
+
+    /// Installed monospace families, `None` while they are enumerated.
+    pub fn families(&self) -> Option<&[String]> {
+        self.families.as_deref()
+    }
+
+    /// Font of `family`, or of the bundled font until it is loaded. An empty
+    /// family is the bundled font.
+    pub fn font_id(&mut self, ctx: &egui::Context, family: &str, size: f32) -> FontId {
+        if self.ready.contains(family) {
+            return FontId::new(size, FontFamily::Name(family.into()));
+        }
+        if !family.is_empty()
+            && !self.failed.contains(family)
+            && !self.pending.iter().any(|pending| pending == family)
+        {
+            self.load(ctx, family);
+        }
+        FontId::monospace(size)
+    }
+
+    /// Pick one of the installed monospace families, or the bundled font.
+    pub fn family_picker(
+        &self,
+        ui: &mut egui::Ui,
+        id_salt: impl Hash,
+        family: &mut String,
+    ) -> Response {
+        let selected = if family.is_empty() {
+            t!("font-bundled")
+        } else {
+            family.clone()
+        };
+        ComboBox::from_id_salt(id_salt)
+            .selected_text(selected)
+            .width(160.)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(family, String::new(), t!("font-bundled"));
+                match self.families() {
+                    Some(families) => {
+                        for name in families {
+                            ui.selectable_value(family, name.clone(), name);
+                        }
+                    }
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.weak(t!("fonts-loading"));
+                        });
+                    }
+                }
+            })
+            .response
+    }
+
+    fn load(&mut self, ctx: &egui::Context, family: &str) {
+        // retried once the fonts are enumerated
+        let Some(database) = &self.database else {
+            return;
+        };
+        let query = Query {
+            families: &[Family::Name(family)],
+            ..Default::default()
+        };
+        let data = database.query(&query).and_then(|id| {
+            database.with_face_data(id, |data, index| {
+                let mut font = FontData::from_owned(data.to_vec());
+                font.index = index;
+                font
+            })
+        });
+        let Some(data) = data else {
+            warn!("font `{family}` not found, using the bundled font");
+            self.failed.insert(family.to_string());
+            return;
+        };
+
+        self.definitions
+            .font_data
+            .insert(family.to_string(), Arc::new(data));
+        self.definitions.families.insert(
+            FontFamily::Name(family.into()),
+            vec![family.to_string(), BUNDLED_FONT.to_string()],
+        );
+        ctx.set_fonts(self.definitions.clone());
+        self.pending.push(family.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_on_demand_with_fallback() {
+        let ctx = egui::Context::default();
+        let mut fonts = FontManager::new(&ctx);
+        let mut database = Database::new();
+        database.load_font_data(BUNDLED_FONT_DATA.to_vec());
+        let family = database.faces().next().unwrap().families[0].0.clone();
+        fonts.set_database(database);
+
+        assert_eq!(fonts.font_id(&ctx, "", 14.), FontId::monospace(14.));
+        assert_eq!(fonts.font_id(&ctx, "Missing", 14.), FontId::monospace(14.));
+        assert!(fonts.failed.contains("Missing"));
+
+        // usable from the next frame
+        assert_eq!(fonts.font_id(&ctx, &family, 14.), FontId::monospace(14.));
+        fonts.ready.extend(fonts.pending.drain(..));
+        assert_eq!(
+            fonts.font_id(&ctx, &family, 14.),
+            FontId::new(14., FontFamily::Name(family.as_str().into()))
+        );
+    }
+}
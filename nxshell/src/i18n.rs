@@ -0,0 +1,185 @@
+use egui_term::TerminalLabels;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::LazyLock;
+use tracing::error;
+use unic_langid::LanguageIdentifier;
+
+/// Key of the chosen language in the app storage.
+pub const LANGUAGE_KEY: &str = "language";
+
+static LANGUAGE: AtomicU8 = AtomicU8::new(Language::English as u8);
+static BUNDLES: LazyLock<Vec<FluentBundle<FluentResource>>> =
+    LazyLock::new(|| Language::ALL.into_iter().map(bundle).collect());
+
+/// Languages the user interface is translated to.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English = 0,
+    Chinese = 1,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Chinese];
+
+    pub fn tag(self) -> &'static str {
+        match self {
+            Language::English => "en-US",
+            Language::Chinese => "zh-CN",
+        }
+    }
+
+    /// Name of the language, in that language.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Chinese => "简体中文",
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Language::English => include_str!("../assets/locales/en-US.ftl"),
+            Language::Chinese => include_str!("../assets/locales/zh-CN.ftl"),
+        }
+    }
+
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|language| language.tag() == tag)
+    }
+
+    /// Language of the system, English if it isn't translated.
+    pub fn system() -> Self {
+        match sys_locale::get_locale() {
+            Some(locale) if locale.starts_with("zh") => Language::Chinese,
+            _ => Language::English,
+        }
+    }
+}
+
+fn bundle(language: Language) -> FluentBundle<FluentResource> {
+    let id: LanguageIdentifier = language.tag().parse().expect("invalid language tag");
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // egui doesn't handle the unicode isolation marks around arguments
+    bundle.set_use_isolating(false);
+    let resource = FluentResource::try_new(language.source().to_string()).unwrap_or_else(
+        |(resource, errors)| {
+            error!("invalid {} translations: {errors:?}", language.tag());
+            resource
+        },
+    );
+    if let Err(errors) = bundle.add_resource(resource) {
+        error!("duplicated {} translations: {errors:?}", language.tag());
+    }
+    bundle
+}
+
+pub fn language() -> Language {
+    match LANGUAGE.load(Ordering::Relaxed) {
+        1 => Language::Chinese,
+        _ => Language::English,
+    }
+}
+
+/// Switch the language of the user interface, from the next frame.
+pub fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+/// Translate the message `id`, falling back to English, then to the id.
+pub fn translate(id: &str, args: Option<&FluentArgs>) -> String {
+    [language(), Language::English]
+        .into_iter()
+        .find_map(|language| {
+            let bundle = &BUNDLES[language as usize];
+            let pattern = bundle.get_message(id)?.value()?;
+            let mut errors = vec![];
+            let text = bundle.format_pattern(pattern, args, &mut errors);
+            if !errors.is_empty() {
+                error!("failed to format the message `{id}`: {errors:?}");
+            }
+            Some(text.into_owned())
+        })
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Translate a message of the current language, e.g.
+/// `t!("diagnostics-saved", path = path.display().to_string())`.
+macro_rules! t {
+    ($id:literal) => {
+        $crate::i18n::translate($id, None)
+    };
+    ($id:literal, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::translate($id, Some(&args))
+    }};
+}
+pub(crate) use t;
+
+/// Labels of the terminal widget in the current language.
+pub fn terminal_labels() -> TerminalLabels {
+    TerminalLabels {
+        copy: t!("term-copy"),
+        copy_as_html: t!("term-copy-as-html"),
+        copy_as_ansi: t!("term-copy-as-ansi"),
+        paste: t!("term-paste"),
+        select_all: t!("term-select-all"),
+        find: t!("term-find"),
+        save_scrollback: t!("term-save-scrollback"),
+        wrap_lines: t!("term-wrap-lines"),
+        wrap_lines_hint: t!("term-wrap-lines-hint"),
+        match_case: t!("term-match-case"),
+        use_regex: t!("term-use-regex"),
+        previous_match: t!("term-previous-match"),
+        next_match: t!("term-next-match"),
+        close_search: t!("term-close-search"),
+        invalid_regex: t!("term-invalid-regex"),
+        no_matches: t!("term-no-matches"),
+        matches: t!("term-matches"),
+        send_key_prompt: t!("term-send-key-prompt"),
+        send_key: t!("term-send-key"),
+        cancel_key: t!("term-cancel-key"),
+        output_lines: t!("term-output-lines"),
+        exit_code: t!("term-exit-code"),
+        backend_error: t!("term-backend-error"),
+        retry: t!("term-retry"),
+        restart: t!("backend-restart"),
+        dismiss: t!("term-dismiss"),
+        close_tab: t!("term-close-tab"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn message_ids(source: &str) -> BTreeSet<&str> {
+        source
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_lowercase()))
+            .filter_map(|line| line.split_once(" =").map(|(id, _)| id))
+            .collect()
+    }
+
+    #[test]
+    fn translations_are_complete() {
+        let english = message_ids(Language::English.source());
+        for language in Language::ALL {
+            assert!(FluentResource::try_new(language.source().to_string()).is_ok());
+            assert_eq!(message_ids(language.source()), english, "{language:?}");
+        }
+    }
+
+    #[test]
+    fn translate_messages() {
+        let labels = terminal_labels();
+        assert_eq!(labels, TerminalLabels::default());
+        assert_eq!(t!("status-cursor", line = 3, column = 14), "Ln 3, Col 14");
+        assert_eq!(translate("missing-message", None), "missing-message");
+    }
+}
@@ -0,0 +1,339 @@
+//! Hosts read from a CSV or an Ansible inventory file, to be saved as sessions.
+
+use crate::i18n::t;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Order of the columns of a CSV file without a header.
+const CSV_COLUMNS: [&str; 5] = ["name", "host", "port", "user", "group"];
+/// Groups every host of an Ansible inventory belongs to.
+const IMPLICIT_GROUPS: [&str; 2] = ["all", "ungrouped"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// `name,host,port,user,group`, the header is optional.
+    #[default]
+    Csv,
+    /// INI inventory of Ansible, its groups become the session groups.
+    Inventory,
+}
+
+impl ImportFormat {
+    pub const ALL: [ImportFormat; 2] = [ImportFormat::Csv, ImportFormat::Inventory];
+
+    /// Guessed from the extension, inventories rarely have one.
+    pub fn of_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => ImportFormat::Csv,
+            _ => ImportFormat::Inventory,
+        }
+    }
+}
+
+/// Host read from an import file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportRow {
+    /// Line in the file, starting at 1.
+    pub line: usize,
+    pub group: String,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    /// Empty when the ssh config tells it.
+    pub user: String,
+    /// Why the host can't be saved, it is skipped by the import.
+    pub error: Option<String>,
+}
+
+impl ImportRow {
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Read the hosts of a file, with an error on the rows that can't be saved.
+pub fn parse(text: &str, format: ImportFormat) -> Vec<ImportRow> {
+    let mut rows = match format {
+        ImportFormat::Csv => parse_csv(text),
+        ImportFormat::Inventory => parse_inventory(text),
+    };
+    let mut names = HashSet::new();
+    for row in &mut rows {
+        if row.error.is_none() && !names.insert((row.group.clone(), row.name.clone())) {
+            row.error = Some(t!("import-error-duplicate"));
+        }
+    }
+    rows
+}
+
+fn parse_csv(text: &str) -> Vec<ImportRow> {
+    // position of each field in `CSV_COLUMNS`
+    let mut columns: Vec<Option<usize>> = (0..CSV_COLUMNS.len()).map(Some).collect();
+    let mut rows = vec![];
+    let mut header = true;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields = split_csv_line(line);
+        if std::mem::take(&mut header) {
+            let header: Vec<_> = fields.iter().map(|field| csv_column(field)).collect();
+            if header.contains(&csv_column("host")) {
+                columns = header;
+                continue;
+            }
+        }
+        let mut values = [""; CSV_COLUMNS.len()];
+        for (column, field) in columns.iter().zip(&fields) {
+            if let Some(column) = column {
+                values[*column] = field.as_str();
+            }
+        }
+        let [name, host, port, user, group] = values;
+        rows.push(host_row(index + 1, group, name, host, port, user));
+    }
+    rows
+}
+
+/// Column of a header field, the others are ignored.
+fn csv_column(field: &str) -> Option<usize> {
+    let field = field.to_ascii_lowercase();
+    let field = match field.as_str() {
+        "username" | "login" => "user",
+        "hostname" | "address" => "host",
+        field => field,
+    };
+    CSV_COLUMNS.iter().position(|column| *column == field)
+}
+
+/// Fields of a line, which are quoted when they hold a comma.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+fn parse_inventory(text: &str) -> Vec<ImportRow> {
+    let mut group = String::new();
+    let mut hosts_section = true;
+    let mut rows = vec![];
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            // `[group:vars]` and `[group:children]` don't list hosts
+            hosts_section = !section.contains(':');
+            group = match IMPLICIT_GROUPS.contains(&section) {
+                true => String::new(),
+                false => section.to_string(),
+            };
+            continue;
+        }
+        if !hosts_section {
+            continue;
+        }
+
+        let mut tokens = line.split_whitespace();
+        let Some(alias) = tokens.next() else {
+            continue;
+        };
+        let (mut host, mut port, mut user) = (None, None, "");
+        for token in tokens {
+            if token.starts_with('#') {
+                break;
+            }
+            match token.split_once('=') {
+                Some(("ansible_host" | "ansible_ssh_host", value)) => host = Some(value),
+                Some(("ansible_port" | "ansible_ssh_port", value)) => port = Some(value),
+                Some(("ansible_user" | "ansible_ssh_user", value)) => user = value,
+                _ => {}
+            }
+        }
+        // `host:2222` is the same as `ansible_port=2222`
+        let (alias, alias_port) = match alias.rsplit_once(':') {
+            Some((alias, alias_port))
+                if !alias.contains(':') && alias_port.bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                (alias, Some(alias_port))
+            }
+            _ => (alias, None),
+        };
+        let port = port.or(alias_port).unwrap_or_default();
+        for alias in expand_range(alias) {
+            let host = host.map_or(alias.as_str(), |host| host);
+            rows.push(host_row(index + 1, &group, &alias, host, port, user));
+        }
+    }
+    rows
+}
+
+/// Hosts of a pattern like `web[01:03].example.com`, with the width of the
+/// first number.
+fn expand_range(alias: &str) -> Vec<String> {
+    let range = alias.split_once('[').and_then(|(prefix, rest)| {
+        let (range, suffix) = rest.split_once(']')?;
+        let (start, end) = range.split_once(':')?;
+        let width = start.len();
+        let (start, end) = (start.parse::<u32>().ok()?, end.parse::<u32>().ok()?);
+        Some((prefix, start..=end, width, suffix))
+    });
+    match range {
+        Some((prefix, range, width, suffix)) => range
+            .map(|n| format!("{prefix}{n:0width$}{suffix}"))
+            .collect(),
+        None => vec![alias.to_string()],
+    }
+}
+
+fn host_row(line: usize, group: &str, name: &str, host: &str, port: &str, user: &str) -> ImportRow {
+    let mut row = ImportRow {
+        line,
+        group: group.to_string(),
+        name: if name.is_empty() { host } else { name }.to_string(),
+        host: host.to_string(),
+        port: 22,
+        user: user.to_string(),
+        error: None,
+    };
+    if host.is_empty() {
+        row.error = Some(t!("import-error-host"));
+    } else if !port.is_empty() {
+        match port.parse::<u16>() {
+            Ok(port) if port > 0 => row.port = port,
+            _ => row.error = Some(t!("import-error-port", port = port.to_string())),
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_csv_file() {
+        let text = "\
+# exported hosts
+name,host,port,user,group
+web,10.0.0.1,,deploy,prod
+\"db, primary\",10.0.0.2,2222,,prod
+,10.0.0.3,22,root,
+bad,10.0.0.4,99999,root,
+missing,,22,root,
+web,10.0.0.5,22,root,prod
+";
+        let rows = parse(text, ImportFormat::Csv);
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|row| (row.line, row.name.as_str(), row.port, row.is_valid()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (3, "web", 22, true),
+                (4, "db, primary", 2222, true),
+                (5, "10.0.0.3", 22, true),
+                (6, "bad", 22, false),
+                (7, "missing", 22, false),
+                // already in the group
+                (8, "web", 22, false),
+            ]
+        );
+        assert_eq!(
+            (rows[0].user.as_str(), rows[0].group.as_str()),
+            ("deploy", "prod")
+        );
+
+        // the columns are in the default order without a header
+        let rows = parse("web,example.com,2200,admin,lab", ImportFormat::Csv);
+        assert_eq!(rows[0].host, "example.com");
+        assert_eq!(rows[0].user, "admin");
+        assert_eq!(rows[0].group, "lab");
+        // and in any order with one
+        let rows = parse("Hostname,Username\nexample.com,admin", ImportFormat::Csv);
+        assert_eq!(
+            (rows[0].name.as_str(), rows[0].user.as_str()),
+            ("example.com", "admin")
+        );
+    }
+
+    #[test]
+    fn parse_inventory_file() {
+        let text = "\
+mail.example.com
+
+[web]
+web[1:3].example.com ansible_user=deploy
+proxy ansible_host=10.0.0.9 ansible_port=2222 # front
+
+[db]
+db.example.com:5022
+
+[db:vars]
+ansible_user=postgres
+
+[prod:children]
+web
+";
+        let rows = parse(text, ImportFormat::Inventory);
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|row| {
+                let ImportRow {
+                    group,
+                    name,
+                    host,
+                    port,
+                    user,
+                    ..
+                } = row;
+                (
+                    group.as_str(),
+                    name.as_str(),
+                    host.as_str(),
+                    *port,
+                    user.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("", "mail.example.com", "mail.example.com", 22, ""),
+                ("web", "web1.example.com", "web1.example.com", 22, "deploy"),
+                ("web", "web2.example.com", "web2.example.com", 22, "deploy"),
+                ("web", "web3.example.com", "web3.example.com", 22, "deploy"),
+                ("web", "proxy", "10.0.0.9", 2222, ""),
+                ("db", "db.example.com", "db.example.com", 5022, ""),
+            ]
+        );
+        assert!(rows.iter().all(ImportRow::is_valid));
+
+        assert_eq!(expand_range("node[08:10]"), ["node08", "node09", "node10"]);
+        assert_eq!(
+            ImportFormat::of_path(Path::new("hosts.CSV")),
+            ImportFormat::Csv
+        );
+        assert_eq!(
+            ImportFormat::of_path(Path::new("/etc/ansible/hosts")),
+            ImportFormat::Inventory
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use egui_term::{Authentication, SshOptions};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use wezterm_ssh::Config;
+
+/// Probes give up on the host by then.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Host and port actually connected to, hosts of the ssh config are aliases.
+pub(crate) fn address(options: &SshOptions) -> (String, u16) {
+    match options.auth {
+        Authentication::Password(..) | Authentication::PublicKey { .. } => {
+            (options.host.clone(), options.port.unwrap_or(22))
+        }
+        Authentication::Config => config_address(&options.host),
+    }
+}
+
+/// Host name and port of `host` in the ssh config.
+pub(crate) fn config_address(host: &str) -> (String, u16) {
+    let mut config = Config::new();
+    config.add_default_config_files();
+    let config = config.for_host(host);
+    let hostname = config
+        .get("hostname")
+        .cloned()
+        .unwrap_or_else(|| host.to_string());
+    let port = config
+        .get("port")
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(22);
+    (hostname, port)
+}
+
+/// TCP handshake time to the host, for the hosts without an open session,
+/// e.g. to wake one up. The open sessions measure their latency on
+/// themselves, see [`SessionLatency`].
+///
+/// [`SessionLatency`]: egui_term::SessionLatency
+pub(crate) fn probe(host: &str, port: u16) -> Option<Duration> {
+    let addr = (host, port).to_socket_addrs().ok()?.next()?;
+    let start = Instant::now();
+    TcpStream::connect_timeout(&addr, PROBE_TIMEOUT).ok()?;
+    Some(start.elapsed())
+}
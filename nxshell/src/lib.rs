@@ -1,6 +1,21 @@
+mod api;
 pub mod app;
+mod availability;
+mod bell;
+pub mod cli;
 pub mod consts;
 mod db;
+mod diagnose;
+pub mod diagnostics;
 mod errors;
+mod export;
+mod fonts;
+mod i18n;
+mod import;
+mod latency;
 mod security;
+mod sftp;
 mod ui;
+mod window;
+mod wol;
+mod workspace;
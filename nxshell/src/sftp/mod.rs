@@ -0,0 +1,463 @@
+mod pattern;
+mod sync;
+mod transfer;
+mod trash;
+
+use egui_term::{
+    connect_ssh_with_observer, ConnectObserver, HostKeyChange, SessionLatency, SshOptions,
+};
+pub use pattern::NamePattern;
+use smol::channel::{unbounded, Sender as AsyncSender};
+use smol::io::AsyncReadExt;
+use smol::LocalExecutor;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+pub use sync::{SyncAction, SyncPlan};
+use tracing::{debug, error};
+use transfer::TransferPool;
+pub use transfer::{
+    TransferDirection, TransferEvent, TransferQueue, TransferStatus, DEFAULT_PARALLELISM,
+    MAX_PARALLELISM,
+};
+pub use trash::TrashItem;
+use wezterm_ssh::{Metadata, Sftp, Utf8PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct SftpEntry {
+    pub path: Utf8PathBuf,
+    pub name: String,
+    pub is_dir: bool,
+    pub size: Option<u64>,
+    /// Seconds since the unix epoch.
+    pub modified: Option<u64>,
+    pub permissions: Option<u32>,
+}
+
+impl SftpEntry {
+    fn new(path: Utf8PathBuf, metadata: Metadata) -> Self {
+        Self {
+            name: path.file_name().unwrap_or(path.as_str()).to_string(),
+            is_dir: metadata.is_dir(),
+            size: metadata.size,
+            modified: metadata.modified,
+            permissions: metadata.permissions.map(|p| p.to_unix_mode()),
+            path,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SftpRequest {
+    ReadDir(Utf8PathBuf),
+    /// Recursively walk `root`, reporting every entry whose name matches `pattern`.
+    Search {
+        id: u64,
+        root: Utf8PathBuf,
+        pattern: NamePattern,
+    },
+    /// Upload a local file or directory to `remote`.
+    Upload {
+        local: PathBuf,
+        remote: Utf8PathBuf,
+    },
+    /// Download a remote file or directory to `local`.
+    Download {
+        remote: Utf8PathBuf,
+        local: PathBuf,
+    },
+    /// Number of files transferred concurrently.
+    SetParallelism(usize),
+    /// Compare `local` and `remote` without changing anything.
+    SyncPlan {
+        direction: TransferDirection,
+        local: PathBuf,
+        remote: Utf8PathBuf,
+        delete: bool,
+    },
+    Sync(SyncPlan),
+    /// Read at most `limit` bytes from the start of a file.
+    Preview {
+        path: Utf8PathBuf,
+        limit: u64,
+    },
+    /// Delete a file or a directory recursively, or move it to the trash.
+    Delete {
+        path: Utf8PathBuf,
+        is_dir: bool,
+        trash: bool,
+    },
+    ListTrash,
+    Restore(TrashItem),
+    /// Delete trashed entries for good.
+    Purge(Vec<TrashItem>),
+}
+
+#[derive(Debug)]
+pub enum SftpEvent {
+    /// The host key is new or changed, the connection waits for the answer
+    /// of the user on the sender.
+    TrustHostKey(HostKeyChange, Sender<bool>),
+    /// Fingerprint of the host key to save with the session.
+    HostKeyPinned(String),
+    /// The session is ready, carries the remote home directory.
+    Connected(Utf8PathBuf),
+    ReadDir(Utf8PathBuf, Vec<SftpEntry>),
+    SearchHit(u64, SftpEntry),
+    SearchDone(u64),
+    Transfer(TransferEvent),
+    SyncPlan(SyncPlan),
+    Preview(Utf8PathBuf, Result<Vec<u8>, String>),
+    Deleted(Utf8PathBuf),
+    Trashed(TrashItem),
+    Trash(Vec<TrashItem>),
+    Restored(TrashItem),
+    Error(String),
+}
+
+/// Handle of a sftp session running on its own thread.
+///
+/// Requests are served concurrently, results are sent back as [`SftpEvent`]s and the
+/// egui context is repainted whenever a new event is available.
+pub struct SftpClient {
+    requests: AsyncSender<SftpRequest>,
+    events: Receiver<SftpEvent>,
+    /// Id of the running search, a search stops as soon as it is superseded.
+    search_id: Arc<AtomicU64>,
+    /// Bumped to cancel every queued and running transfer.
+    transfer_generation: Arc<AtomicU64>,
+    latency: SessionLatency,
+}
+
+impl SftpClient {
+    pub fn connect(id: u64, ctx: egui::Context, options: SshOptions) -> std::io::Result<Self> {
+        let (requests, request_receiver) = unbounded::<SftpRequest>();
+        let (event_sender, events) = channel();
+        let search_id = Arc::new(AtomicU64::new(0));
+        let current_search = search_id.clone();
+        let transfer_generation = Arc::new(AtomicU64::new(0));
+        let current_generation = transfer_generation.clone();
+        let latency = SessionLatency::default();
+        let session_latency = latency.clone();
+
+        std::thread::Builder::new()
+            .name(format!("sftp_{id}"))
+            .spawn(move || {
+                let proxy = EventProxy {
+                    sender: event_sender,
+                    ctx,
+                };
+                let session = match connect_ssh_with_observer(options, &proxy) {
+                    Ok(session) => session,
+                    Err(err) => {
+                        error!("sftp_{id}: failed to connect: {err}");
+                        proxy.send(SftpEvent::Error(err.to_string()));
+                        return;
+                    }
+                };
+                session_latency.watch(&session);
+                drop(session_latency);
+                let sftp = session.sftp();
+                let transfers = TransferPool::new(current_generation);
+
+                let executor = LocalExecutor::new();
+                smol::block_on(executor.run(async {
+                    match sftp.canonicalize(".").await {
+                        Ok(home) => proxy.send(SftpEvent::Connected(home)),
+                        Err(err) => {
+                            proxy.send(SftpEvent::Error(err.to_string()));
+                            return;
+                        }
+                    }
+
+                    while let Ok(request) = request_receiver.recv().await {
+                        if let SftpRequest::SetParallelism(parallelism) = request {
+                            transfers.set_parallelism(parallelism);
+                        } else {
+                            let task = handle_request(
+                                sftp.clone(),
+                                request,
+                                proxy.clone(),
+                                current_search.clone(),
+                                transfers.clone(),
+                            );
+                            executor.spawn(task).detach();
+                        }
+                        transfers.spawn_workers(&executor, &sftp, &proxy);
+                    }
+                }));
+                debug!("sftp_{id}: session closed");
+            })?;
+
+        Ok(Self {
+            requests,
+            events,
+            search_id,
+            transfer_generation,
+            latency,
+        })
+    }
+
+    /// Round trip of the session, see [`SessionLatency`].
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency.latency()
+    }
+
+    pub fn read_dir(&self, path: Utf8PathBuf) {
+        self.send(SftpRequest::ReadDir(path));
+    }
+
+    /// Start a new search, cancelling the previous one. Returns the id of the search.
+    pub fn search(&self, root: Utf8PathBuf, pattern: NamePattern) -> u64 {
+        let id = self.search_id.fetch_add(1, Ordering::SeqCst) + 1;
+        self.send(SftpRequest::Search { id, root, pattern });
+        id
+    }
+
+    pub fn cancel_search(&self) {
+        self.search_id.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn upload(&self, local: PathBuf, remote: Utf8PathBuf) {
+        self.send(SftpRequest::Upload { local, remote });
+    }
+
+    pub fn download(&self, remote: Utf8PathBuf, local: PathBuf) {
+        self.send(SftpRequest::Download { remote, local });
+    }
+
+    /// Dry-run of a synchronization, answered by [`SftpEvent::SyncPlan`].
+    pub fn sync_plan(
+        &self,
+        direction: TransferDirection,
+        local: PathBuf,
+        remote: Utf8PathBuf,
+        delete: bool,
+    ) {
+        self.send(SftpRequest::SyncPlan {
+            direction,
+            local,
+            remote,
+            delete,
+        });
+    }
+
+    pub fn sync(&self, plan: SyncPlan) {
+        self.send(SftpRequest::Sync(plan));
+    }
+
+    /// Fetch the head of a file, answered by [`SftpEvent::Preview`].
+    pub fn preview(&self, path: Utf8PathBuf, limit: u64) {
+        self.send(SftpRequest::Preview { path, limit });
+    }
+
+    /// Delete an entry, when `trash` is set it is moved to `~/.nxshell-trash` instead.
+    pub fn delete(&self, path: Utf8PathBuf, is_dir: bool, trash: bool) {
+        self.send(SftpRequest::Delete {
+            path,
+            is_dir,
+            trash,
+        });
+    }
+
+    /// List the trashed entries, answered by [`SftpEvent::Trash`].
+    pub fn list_trash(&self) {
+        self.send(SftpRequest::ListTrash);
+    }
+
+    pub fn restore(&self, item: TrashItem) {
+        self.send(SftpRequest::Restore(item));
+    }
+
+    pub fn purge(&self, items: Vec<TrashItem>) {
+        self.send(SftpRequest::Purge(items));
+    }
+
+    pub fn set_parallelism(&self, parallelism: usize) {
+        self.send(SftpRequest::SetParallelism(parallelism));
+    }
+
+    /// Cancel every queued and running transfer, partial files are kept so the
+    /// transfers can be resumed later.
+    pub fn cancel_transfers(&self) {
+        self.transfer_generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn try_recv(&self) -> Option<SftpEvent> {
+        self.events.try_recv().ok()
+    }
+
+    fn send(&self, request: SftpRequest) {
+        if let Err(err) = self.requests.try_send(request) {
+            error!("sftp session is closed: {err}");
+        }
+    }
+}
+
+impl Drop for SftpClient {
+    fn drop(&mut self) {
+        self.cancel_search();
+        self.cancel_transfers();
+        self.requests.close();
+    }
+}
+
+#[derive(Clone)]
+struct EventProxy {
+    sender: Sender<SftpEvent>,
+    ctx: egui::Context,
+}
+
+impl EventProxy {
+    fn send(&self, event: SftpEvent) {
+        if self.sender.send(event).is_ok() {
+            self.ctx.request_repaint();
+        }
+    }
+}
+
+/// The pinned host key is checked like for the terminals.
+impl ConnectObserver for EventProxy {
+    fn host_key_pinned(&self, fingerprint: &str) {
+        self.send(SftpEvent::HostKeyPinned(fingerprint.to_string()));
+    }
+
+    fn trust_host_key(&self, change: &HostKeyChange) -> bool {
+        let (answer, answer_receiver) = channel();
+        self.send(SftpEvent::TrustHostKey(change.clone(), answer));
+        // refused if the tab is closed before the user answers
+        answer_receiver.recv().unwrap_or(false)
+    }
+}
+
+async fn handle_request(
+    sftp: Sftp,
+    request: SftpRequest,
+    proxy: EventProxy,
+    current_search: Arc<AtomicU64>,
+    transfers: Rc<TransferPool>,
+) {
+    match request {
+        SftpRequest::ReadDir(path) => match read_dir(&sftp, &path).await {
+            Ok(entries) => proxy.send(SftpEvent::ReadDir(path, entries)),
+            Err(err) => proxy.send(SftpEvent::Error(format!("{path}: {err}"))),
+        },
+        SftpRequest::Search { id, root, pattern } => {
+            let is_current = || current_search.load(Ordering::SeqCst) == id;
+            let mut pending = VecDeque::from([root]);
+            while let Some(dir) = pending.pop_front() {
+                if !is_current() {
+                    return;
+                }
+                // Unreadable directories are skipped, like `find` does.
+                let Ok(entries) = read_dir(&sftp, &dir).await else {
+                    continue;
+                };
+                for entry in entries {
+                    if entry.is_dir {
+                        pending.push_back(entry.path.clone());
+                    }
+                    if pattern.matches(&entry.name) {
+                        proxy.send(SftpEvent::SearchHit(id, entry));
+                    }
+                }
+            }
+            if is_current() {
+                proxy.send(SftpEvent::SearchDone(id));
+            }
+        }
+        SftpRequest::Upload { local, remote } => {
+            if let Err(err) = transfers.upload(&sftp, &proxy, local, remote).await {
+                proxy.send(SftpEvent::Error(err));
+            }
+        }
+        SftpRequest::Download { remote, local } => {
+            if let Err(err) = transfers.download(&sftp, &proxy, remote, local).await {
+                proxy.send(SftpEvent::Error(err));
+            }
+        }
+        SftpRequest::SyncPlan {
+            direction,
+            local,
+            remote,
+            delete,
+        } => match sync::plan(&sftp, direction, local, remote, delete).await {
+            Ok(plan) => proxy.send(SftpEvent::SyncPlan(plan)),
+            Err(err) => proxy.send(SftpEvent::Error(err)),
+        },
+        SftpRequest::Sync(plan) => {
+            if let Err(err) = sync::execute(&sftp, &proxy, &transfers, plan).await {
+                proxy.send(SftpEvent::Error(err));
+            }
+        }
+        SftpRequest::Preview { path, limit } => {
+            let data = read_head(&sftp, &path, limit).await;
+            proxy.send(SftpEvent::Preview(path, data));
+        }
+        SftpRequest::Delete {
+            path,
+            is_dir,
+            trash: true,
+        } => match trash::move_to_trash(&sftp, path, is_dir).await {
+            Ok(item) => proxy.send(SftpEvent::Trashed(item)),
+            Err(err) => proxy.send(SftpEvent::Error(err)),
+        },
+        SftpRequest::Delete { path, .. } => match trash::remove_all(&sftp, &path).await {
+            Ok(()) => proxy.send(SftpEvent::Deleted(path)),
+            Err(err) => proxy.send(SftpEvent::Error(err)),
+        },
+        SftpRequest::ListTrash => match trash::list(&sftp).await {
+            Ok(items) => proxy.send(SftpEvent::Trash(items)),
+            Err(err) => proxy.send(SftpEvent::Error(err)),
+        },
+        SftpRequest::Restore(item) => match trash::restore(&sftp, &item).await {
+            Ok(()) => proxy.send(SftpEvent::Restored(item)),
+            Err(err) => proxy.send(SftpEvent::Error(err)),
+        },
+        SftpRequest::Purge(items) => {
+            for item in &items {
+                if let Err(err) = trash::purge(&sftp, item).await {
+                    proxy.send(SftpEvent::Error(err));
+                    break;
+                }
+            }
+            match trash::list(&sftp).await {
+                Ok(items) => proxy.send(SftpEvent::Trash(items)),
+                Err(err) => proxy.send(SftpEvent::Error(err)),
+            }
+        }
+        SftpRequest::SetParallelism(_) => {}
+    }
+}
+
+/// Ranged read, huge files are never fetched entirely.
+async fn read_head(sftp: &Sftp, path: &Utf8PathBuf, limit: u64) -> Result<Vec<u8>, String> {
+    let file = sftp
+        .open(path.clone())
+        .await
+        .map_err(|err| err.to_string())?;
+    let mut data = vec![];
+    file.take(limit)
+        .read_to_end(&mut data)
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(data)
+}
+
+async fn read_dir(sftp: &Sftp, path: &Utf8PathBuf) -> Result<Vec<SftpEntry>, String> {
+    let mut entries: Vec<SftpEntry> = sftp
+        .read_dir(path.clone())
+        .await
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .filter(|(path, _)| !matches!(path.file_name(), Some(".") | Some("..")))
+        .map(|(path, metadata)| SftpEntry::new(path, metadata))
+        .collect();
+    // directories first, then by name
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    Ok(entries)
+}
@@ -0,0 +1,94 @@
+/// File name matcher used by the remote search.
+///
+/// Queries containing `*` or `?` are treated as globs matching the whole name,
+/// anything else is a substring match. Both are case-insensitive.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NamePattern {
+    Substring(String),
+    Glob(Vec<char>),
+}
+
+impl NamePattern {
+    pub fn new(query: &str) -> Self {
+        let query = query.trim().to_lowercase();
+        if query.contains(['*', '?']) {
+            NamePattern::Glob(query.chars().collect())
+        } else {
+            NamePattern::Substring(query)
+        }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        match self {
+            NamePattern::Substring(needle) => name.contains(needle.as_str()),
+            NamePattern::Glob(glob) => {
+                let name: Vec<char> = name.chars().collect();
+                glob_match(glob, &name)
+            }
+        }
+    }
+}
+
+/// Iterative wildcard matching with single-star backtracking.
+fn glob_match(glob: &[char], name: &[char]) -> bool {
+    let (mut g, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match glob.get(g) {
+            Some('*') => {
+                star = Some((g, n));
+                g += 1;
+            }
+            Some('?') => {
+                g += 1;
+                n += 1;
+            }
+            Some(c) if *c == name[n] => {
+                g += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_g, star_n)) => {
+                    g = star_g + 1;
+                    n = star_n + 1;
+                    star = Some((star_g, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    glob[g..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NamePattern;
+
+    #[test]
+    fn substring_match() {
+        let pattern = NamePattern::new("Log");
+        assert!(pattern.matches("nginx.log"));
+        assert!(pattern.matches("LOGS"));
+        assert!(!pattern.matches("nginx.conf"));
+    }
+
+    #[test]
+    fn glob_match() {
+        let pattern = NamePattern::new("*.log");
+        assert!(pattern.matches("access.log"));
+        assert!(pattern.matches(".log"));
+        assert!(!pattern.matches("access.log.1"));
+
+        let pattern = NamePattern::new("access.log.?");
+        assert!(pattern.matches("access.log.1"));
+        assert!(!pattern.matches("access.log.10"));
+
+        let pattern = NamePattern::new("a*b*c");
+        assert!(pattern.matches("aXXbYYc"));
+        assert!(pattern.matches("abc"));
+        assert!(!pattern.matches("aXXbYY"));
+    }
+}
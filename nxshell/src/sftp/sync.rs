@@ -0,0 +1,414 @@
+use super::transfer::{FileCopy, TransferPool};
+use super::{read_dir, EventProxy, TransferDirection};
+use smol::stream::StreamExt;
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use wezterm_ssh::{Sftp, Utf8PathBuf};
+
+/// Files of a directory tree, keyed by their `/` separated path relative to the root.
+pub type FileTree = BTreeMap<String, FileState>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileState {
+    pub is_dir: bool,
+    pub size: u64,
+    /// Seconds since the unix epoch.
+    pub modified: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncAction {
+    /// Delete an entry which doesn't exist in the source anymore.
+    Delete {
+        path: String,
+        is_dir: bool,
+    },
+    CreateDir(String),
+    /// Copy a file missing in the destination.
+    Create {
+        path: String,
+        size: u64,
+    },
+    /// Copy a file whose size differs or which is newer in the source.
+    Update {
+        path: String,
+        size: u64,
+    },
+}
+
+impl SyncAction {
+    pub fn path(&self) -> &str {
+        match self {
+            SyncAction::Delete { path, .. }
+            | SyncAction::CreateDir(path)
+            | SyncAction::Create { path, .. }
+            | SyncAction::Update { path, .. } => path,
+        }
+    }
+}
+
+/// Result of a dry-run, executing it applies the actions in order.
+#[derive(Debug, Clone)]
+pub struct SyncPlan {
+    pub direction: TransferDirection,
+    pub local: PathBuf,
+    pub remote: Utf8PathBuf,
+    pub actions: Vec<SyncAction>,
+}
+
+impl SyncPlan {
+    /// Number of files to copy and their total size.
+    pub fn copy_stats(&self) -> (usize, u64) {
+        self.actions
+            .iter()
+            .fold((0, 0), |(count, size), action| match action {
+                SyncAction::Create { size: s, .. } | SyncAction::Update { size: s, .. } => {
+                    (count + 1, size + s)
+                }
+                _ => (count, size),
+            })
+    }
+
+    pub fn delete_count(&self) -> usize {
+        self.actions
+            .iter()
+            .filter(|action| matches!(action, SyncAction::Delete { .. }))
+            .count()
+    }
+}
+
+/// Compare two trees like `rsync -r --update`, entries only present in the
+/// destination are deleted when `delete` is set.
+///
+/// Deletions come first, deepest entries before their parent, then directories
+/// are created before the files they contain.
+pub fn diff(source: &FileTree, dest: &FileTree, delete: bool) -> Vec<SyncAction> {
+    let mut deletes = vec![];
+    let mut creates = vec![];
+
+    for (path, src) in source {
+        match dest.get(path) {
+            Some(dst) if dst.is_dir == src.is_dir => {
+                let newer = match (src.modified, dst.modified) {
+                    (Some(src), Some(dst)) => src > dst,
+                    _ => false,
+                };
+                if !src.is_dir && (src.size != dst.size || newer) {
+                    creates.push(SyncAction::Update {
+                        path: path.clone(),
+                        size: src.size,
+                    });
+                }
+                continue;
+            }
+            // a file replaced by a directory or the other way round
+            Some(dst) => {
+                if dst.is_dir {
+                    let prefix = format!("{path}/");
+                    let children = dest
+                        .range(prefix.clone()..)
+                        .take_while(|(child, _)| child.starts_with(&prefix))
+                        .map(|(child, state)| SyncAction::Delete {
+                            path: child.clone(),
+                            is_dir: state.is_dir,
+                        });
+                    deletes.extend(children);
+                }
+                deletes.push(SyncAction::Delete {
+                    path: path.clone(),
+                    is_dir: dst.is_dir,
+                });
+            }
+            None => {}
+        }
+        if src.is_dir {
+            creates.push(SyncAction::CreateDir(path.clone()));
+        } else {
+            creates.push(SyncAction::Create {
+                path: path.clone(),
+                size: src.size,
+            });
+        }
+    }
+
+    if delete {
+        for (path, dst) in dest {
+            if !source.contains_key(path) {
+                deletes.push(SyncAction::Delete {
+                    path: path.clone(),
+                    is_dir: dst.is_dir,
+                });
+            }
+        }
+    }
+    // children sort after their parent
+    deletes.sort_by(|a, b| b.path().cmp(a.path()));
+    deletes.dedup();
+
+    deletes.extend(creates);
+    deletes
+}
+
+fn local_path(root: &Path, relative: &str) -> PathBuf {
+    relative
+        .split('/')
+        .fold(root.to_path_buf(), |path, name| path.join(name))
+}
+
+async fn local_tree(root: &Path) -> Result<FileTree, String> {
+    let mut tree = FileTree::new();
+    if smol::fs::metadata(root).await.is_err() {
+        return Ok(tree);
+    }
+    let mut pending = VecDeque::from([(root.to_path_buf(), String::new())]);
+    while let Some((dir, prefix)) = pending.pop_front() {
+        let mut entries = smol::fs::read_dir(&dir)
+            .await
+            .map_err(|err| format!("{}: {err}", dir.display()))?;
+        while let Some(entry) = entries.try_next().await.map_err(|err| err.to_string())? {
+            let metadata = entry.metadata().await.map_err(|err| err.to_string())?;
+            let relative = format!("{prefix}{}", entry.file_name().to_string_lossy());
+            if metadata.is_dir() {
+                pending.push_back((entry.path(), format!("{relative}/")));
+            }
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs());
+            let state = FileState {
+                is_dir: metadata.is_dir(),
+                size: metadata.len(),
+                modified,
+            };
+            tree.insert(relative, state);
+        }
+    }
+    Ok(tree)
+}
+
+async fn remote_tree(sftp: &Sftp, root: &Utf8PathBuf) -> Result<FileTree, String> {
+    let mut tree = FileTree::new();
+    if sftp.metadata(root.clone()).await.is_err() {
+        return Ok(tree);
+    }
+    let mut pending = VecDeque::from([(root.clone(), String::new())]);
+    while let Some((dir, prefix)) = pending.pop_front() {
+        for entry in read_dir(sftp, &dir).await? {
+            let relative = format!("{prefix}{}", entry.name);
+            if entry.is_dir {
+                pending.push_back((entry.path.clone(), format!("{relative}/")));
+            }
+            let state = FileState {
+                is_dir: entry.is_dir,
+                size: entry.size.unwrap_or_default(),
+                modified: entry.modified,
+            };
+            tree.insert(relative, state);
+        }
+    }
+    Ok(tree)
+}
+
+/// Dry-run, compute what a synchronization in `direction` would do.
+pub(super) async fn plan(
+    sftp: &Sftp,
+    direction: TransferDirection,
+    local: PathBuf,
+    remote: Utf8PathBuf,
+    delete: bool,
+) -> Result<SyncPlan, String> {
+    let local_tree = local_tree(&local).await?;
+    let remote_tree = remote_tree(sftp, &remote).await?;
+    let actions = match direction {
+        TransferDirection::Upload => diff(&local_tree, &remote_tree, delete),
+        TransferDirection::Download => diff(&remote_tree, &local_tree, delete),
+    };
+    Ok(SyncPlan {
+        direction,
+        local,
+        remote,
+        actions,
+    })
+}
+
+/// Apply a plan, deletions and directories are handled right away while the
+/// files are copied by the transfer queue.
+pub(super) async fn execute(
+    sftp: &Sftp,
+    proxy: &EventProxy,
+    transfers: &TransferPool,
+    plan: SyncPlan,
+) -> Result<(), String> {
+    let SyncPlan {
+        direction,
+        local,
+        remote,
+        actions,
+    } = plan;
+    let generation = transfers.generation();
+
+    match direction {
+        TransferDirection::Upload => {
+            if sftp.metadata(remote.clone()).await.is_err() {
+                sftp.create_dir(remote.clone(), 0o755)
+                    .await
+                    .map_err(|err| format!("{remote}: {err}"))?;
+            }
+        }
+        TransferDirection::Download => smol::fs::create_dir_all(&local)
+            .await
+            .map_err(|err| format!("{}: {err}", local.display()))?,
+    }
+
+    for action in actions {
+        if transfers.is_cancelled(generation) {
+            break;
+        }
+        let local = local_path(&local, action.path());
+        let remote = remote.join(action.path());
+        let result = match (action, direction) {
+            (SyncAction::Delete { is_dir, .. }, TransferDirection::Upload) => {
+                let result = if is_dir {
+                    sftp.remove_dir(remote.clone()).await
+                } else {
+                    sftp.remove_file(remote.clone()).await
+                };
+                result.map_err(|err| format!("{remote}: {err}"))
+            }
+            (SyncAction::Delete { is_dir, .. }, TransferDirection::Download) => {
+                let result = if is_dir {
+                    smol::fs::remove_dir(&local).await
+                } else {
+                    smol::fs::remove_file(&local).await
+                };
+                result.map_err(|err| format!("{}: {err}", local.display()))
+            }
+            (SyncAction::CreateDir(_), TransferDirection::Upload) => sftp
+                .create_dir(remote.clone(), 0o755)
+                .await
+                .map_err(|err| format!("{remote}: {err}")),
+            (SyncAction::CreateDir(_), TransferDirection::Download) => {
+                smol::fs::create_dir_all(&local)
+                    .await
+                    .map_err(|err| format!("{}: {err}", local.display()))
+            }
+            (SyncAction::Create { size, .. } | SyncAction::Update { size, .. }, _) => {
+                let copy = FileCopy {
+                    direction,
+                    local,
+                    remote,
+                    size,
+                    overwrite: true,
+                };
+                transfers.enqueue(proxy, generation, copy);
+                Ok(())
+            }
+        };
+        result?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, FileState, FileTree, SyncAction};
+
+    fn file(size: u64, modified: u64) -> FileState {
+        FileState {
+            is_dir: false,
+            size,
+            modified: Some(modified),
+        }
+    }
+
+    fn dir() -> FileState {
+        FileState {
+            is_dir: true,
+            size: 0,
+            modified: None,
+        }
+    }
+
+    fn tree(entries: &[(&str, FileState)]) -> FileTree {
+        entries
+            .iter()
+            .map(|(path, state)| (path.to_string(), *state))
+            .collect()
+    }
+
+    #[test]
+    fn copy_new_and_changed_files() {
+        let source = tree(&[
+            ("a", dir()),
+            ("a/new", file(1, 10)),
+            ("bigger", file(2, 10)),
+            ("newer", file(1, 20)),
+            ("same", file(1, 10)),
+        ]);
+        let dest = tree(&[
+            ("bigger", file(1, 10)),
+            ("newer", file(1, 10)),
+            ("same", file(1, 10)),
+        ]);
+        assert_eq!(
+            diff(&source, &dest, false),
+            vec![
+                SyncAction::CreateDir("a".into()),
+                SyncAction::Create {
+                    path: "a/new".into(),
+                    size: 1
+                },
+                SyncAction::Update {
+                    path: "bigger".into(),
+                    size: 2
+                },
+                SyncAction::Update {
+                    path: "newer".into(),
+                    size: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn delete_extraneous_entries() {
+        let source = tree(&[("keep", file(1, 10))]);
+        let dest = tree(&[
+            ("keep", file(1, 10)),
+            ("old", dir()),
+            ("old/file", file(1, 10)),
+        ]);
+        assert!(diff(&source, &dest, false).is_empty());
+        assert_eq!(
+            diff(&source, &dest, true),
+            vec![
+                SyncAction::Delete {
+                    path: "old/file".into(),
+                    is_dir: false
+                },
+                SyncAction::Delete {
+                    path: "old".into(),
+                    is_dir: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn replace_file_by_directory() {
+        let source = tree(&[("x", dir())]);
+        let dest = tree(&[("x", file(1, 10))]);
+        assert_eq!(
+            diff(&source, &dest, false),
+            vec![
+                SyncAction::Delete {
+                    path: "x".into(),
+                    is_dir: false
+                },
+                SyncAction::CreateDir("x".into()),
+            ]
+        );
+    }
+}
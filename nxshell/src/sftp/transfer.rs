@@ -0,0 +1,650 @@
+use super::{read_dir, EventProxy, SftpEvent};
+use smol::channel::{unbounded, Receiver as AsyncReceiver, Sender as AsyncSender};
+use smol::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use smol::stream::StreamExt;
+use smol::LocalExecutor;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use wezterm_ssh::{OpenFileType, OpenOptions, RenameOptions, Sftp, Utf8PathBuf, WriteMode};
+
+pub const DEFAULT_PARALLELISM: usize = 4;
+pub const MAX_PARALLELISM: usize = 16;
+
+const CHUNK_SIZE: usize = 32 * 1024;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+/// Time window used to compute the aggregate throughput.
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(3);
+/// Suffix of the file a transfer writes to, renamed to the destination once
+/// complete. Only these files are resumed from, the destination may be an
+/// unrelated file.
+const PART_SUFFIX: &str = ".nxpart";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransferDirection {
+    Upload,
+    Download,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferStatus {
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    pub id: u64,
+    pub direction: TransferDirection,
+    pub local: PathBuf,
+    pub remote: Utf8PathBuf,
+    pub size: u64,
+    pub transferred: u64,
+    /// Offset the transfer has been resumed from, `0` for a fresh transfer.
+    pub resumed_from: u64,
+    pub status: TransferStatus,
+}
+
+impl Transfer {
+    pub fn progress(&self) -> f32 {
+        if self.size == 0 {
+            if self.status == TransferStatus::Done {
+                1.
+            } else {
+                0.
+            }
+        } else {
+            self.transferred as f32 / self.size as f32
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            TransferStatus::Done | TransferStatus::Failed(_) | TransferStatus::Cancelled
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum TransferEvent {
+    Queued(Transfer),
+    Started { id: u64, resumed_from: u64 },
+    Progress { id: u64, transferred: u64 },
+    Finished(u64),
+    Failed(u64, String),
+    Cancelled(u64),
+}
+
+/// Transfers shown in the queue of a sftp explorer.
+#[derive(Default)]
+pub struct TransferQueue {
+    pub transfers: Vec<Transfer>,
+    /// Bytes moved over the wire since the queue was created.
+    bytes: u64,
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl TransferQueue {
+    pub fn apply(&mut self, event: TransferEvent) {
+        match event {
+            TransferEvent::Queued(transfer) => self.transfers.push(transfer),
+            TransferEvent::Started { id, resumed_from } => {
+                if let Some(transfer) = self.get_mut(id) {
+                    transfer.status = TransferStatus::Running;
+                    transfer.resumed_from = resumed_from;
+                    transfer.transferred = resumed_from;
+                }
+            }
+            TransferEvent::Progress { id, transferred } => {
+                if let Some(transfer) = self.get_mut(id) {
+                    let delta = transferred.saturating_sub(transfer.transferred);
+                    transfer.transferred = transferred;
+                    self.bytes += delta;
+                }
+            }
+            TransferEvent::Finished(id) => {
+                if let Some(transfer) = self.get_mut(id) {
+                    transfer.status = TransferStatus::Done;
+                }
+            }
+            TransferEvent::Failed(id, err) => {
+                if let Some(transfer) = self.get_mut(id) {
+                    transfer.status = TransferStatus::Failed(err);
+                }
+            }
+            TransferEvent::Cancelled(id) => {
+                if let Some(transfer) = self.get_mut(id) {
+                    transfer.status = TransferStatus::Cancelled;
+                }
+            }
+        }
+    }
+
+    fn get_mut(&mut self, id: u64) -> Option<&mut Transfer> {
+        self.transfers.iter_mut().find(|t| t.id == id)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.transfers.iter().any(|t| !t.is_finished())
+    }
+
+    /// Number of the transfers queued or running.
+    pub fn pending(&self) -> usize {
+        self.transfers.iter().filter(|t| !t.is_finished()).count()
+    }
+
+    pub fn clear_finished(&mut self) {
+        self.transfers.retain(|t| !t.is_finished());
+    }
+
+    /// Record the transferred bytes, should be called once per frame.
+    pub fn sample(&mut self, now: Instant) {
+        self.samples.push_back((now, self.bytes));
+        while self
+            .samples
+            .front()
+            .is_some_and(|(time, _)| now.duration_since(*time) > THROUGHPUT_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Aggregate throughput of all transfers, in bytes per second.
+    pub fn throughput(&self) -> f64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some((start, start_bytes)), Some((end, end_bytes))) if end > start => {
+                (end_bytes - start_bytes) as f64 / end.duration_since(*start).as_secs_f64()
+            }
+            _ => 0.,
+        }
+    }
+
+    /// Transferred and total bytes of the queue.
+    pub fn totals(&self) -> (u64, u64) {
+        self.transfers.iter().fold((0, 0), |(done, total), t| {
+            (done + t.transferred, total + t.size)
+        })
+    }
+}
+
+/// Offset a transfer can resume from, given the size of the file it partially
+/// wrote. A file larger than the source can't be a prefix of it.
+pub fn resume_offset(existing: Option<u64>, size: u64) -> u64 {
+    match existing {
+        Some(existing) if existing <= size => existing,
+        _ => 0,
+    }
+}
+
+/// A file to be copied, see [`TransferPool::enqueue`].
+pub(super) struct FileCopy {
+    pub direction: TransferDirection,
+    pub local: PathBuf,
+    pub remote: Utf8PathBuf,
+    pub size: u64,
+    /// Start over instead of resuming from a previous attempt.
+    pub overwrite: bool,
+}
+
+/// A single file to be transferred.
+struct TransferJob {
+    transfer: Transfer,
+    overwrite: bool,
+    /// Generation of the queue the job belongs to, see [`super::SftpClient::cancel_transfers`].
+    generation: u64,
+}
+
+/// Worker pool executing file transfers of a sftp session.
+///
+/// Lives on the session thread, workers are tasks of its [`LocalExecutor`].
+pub(super) struct TransferPool {
+    jobs: AsyncSender<TransferJob>,
+    pending: AsyncReceiver<TransferJob>,
+    parallelism: Cell<usize>,
+    workers: Cell<usize>,
+    next_id: Cell<u64>,
+    generation: Arc<AtomicU64>,
+}
+
+impl TransferPool {
+    pub(super) fn new(generation: Arc<AtomicU64>) -> Rc<Self> {
+        let (jobs, pending) = unbounded();
+        Rc::new(Self {
+            jobs,
+            pending,
+            parallelism: Cell::new(DEFAULT_PARALLELISM),
+            workers: Cell::new(0),
+            next_id: Cell::new(0),
+            generation,
+        })
+    }
+
+    pub(super) fn set_parallelism(&self, parallelism: usize) {
+        self.parallelism.set(parallelism.clamp(1, MAX_PARALLELISM));
+    }
+
+    /// Spawn workers until the configured parallelism is reached. Extra workers
+    /// exit by themselves after their current job.
+    pub(super) fn spawn_workers(
+        self: &Rc<Self>,
+        executor: &LocalExecutor<'_>,
+        sftp: &Sftp,
+        proxy: &EventProxy,
+    ) {
+        while self.workers.get() < self.parallelism.get() {
+            self.workers.set(self.workers.get() + 1);
+            let worker = worker(self.clone(), sftp.clone(), proxy.clone());
+            executor.spawn(worker).detach();
+        }
+    }
+
+    pub(super) fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    pub(super) fn is_cancelled(&self, generation: u64) -> bool {
+        self.generation() != generation
+    }
+
+    pub(super) fn enqueue(&self, proxy: &EventProxy, generation: u64, copy: FileCopy) {
+        let id = self.next_id.get() + 1;
+        self.next_id.set(id);
+        let transfer = Transfer {
+            id,
+            direction: copy.direction,
+            local: copy.local,
+            remote: copy.remote,
+            size: copy.size,
+            transferred: 0,
+            resumed_from: 0,
+            status: TransferStatus::Queued,
+        };
+        proxy.send(SftpEvent::Transfer(TransferEvent::Queued(transfer.clone())));
+        let _ = self.jobs.try_send(TransferJob {
+            transfer,
+            overwrite: copy.overwrite,
+            generation,
+        });
+    }
+
+    /// Queue every file below `local`, creating the remote directories on the way.
+    pub(super) async fn upload(
+        &self,
+        sftp: &Sftp,
+        proxy: &EventProxy,
+        local: PathBuf,
+        remote: Utf8PathBuf,
+    ) -> Result<(), String> {
+        let generation = self.generation();
+        let mut pending = VecDeque::from([(local, remote)]);
+        while let Some((local, remote)) = pending.pop_front() {
+            if self.is_cancelled(generation) {
+                break;
+            }
+            let metadata = smol::fs::metadata(&local)
+                .await
+                .map_err(|err| format!("{}: {err}", local.display()))?;
+            if !metadata.is_dir() {
+                let copy = FileCopy {
+                    direction: TransferDirection::Upload,
+                    local,
+                    remote,
+                    size: metadata.len(),
+                    overwrite: false,
+                };
+                self.enqueue(proxy, generation, copy);
+                continue;
+            }
+
+            // The directory may already exist when resuming an upload.
+            if sftp.metadata(remote.clone()).await.is_err() {
+                sftp.create_dir(remote.clone(), 0o755)
+                    .await
+                    .map_err(|err| format!("{remote}: {err}"))?;
+            }
+            let mut entries = smol::fs::read_dir(&local)
+                .await
+                .map_err(|err| format!("{}: {err}", local.display()))?;
+            while let Some(entry) = entries.try_next().await.map_err(|err| err.to_string())? {
+                let name = entry.file_name().to_string_lossy().to_string();
+                pending.push_back((entry.path(), remote.join(&name)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Queue every file below `remote`, creating the local directories on the way.
+    pub(super) async fn download(
+        &self,
+        sftp: &Sftp,
+        proxy: &EventProxy,
+        remote: Utf8PathBuf,
+        local: PathBuf,
+    ) -> Result<(), String> {
+        let generation = self.generation();
+        let metadata = sftp
+            .metadata(remote.clone())
+            .await
+            .map_err(|err| format!("{remote}: {err}"))?;
+        if !metadata.is_dir() {
+            let copy = FileCopy {
+                direction: TransferDirection::Download,
+                local,
+                remote,
+                size: metadata.size.unwrap_or_default(),
+                overwrite: false,
+            };
+            self.enqueue(proxy, generation, copy);
+            return Ok(());
+        }
+
+        let mut pending = VecDeque::from([(remote, local)]);
+        while let Some((remote, local)) = pending.pop_front() {
+            if self.is_cancelled(generation) {
+                break;
+            }
+            smol::fs::create_dir_all(&local)
+                .await
+                .map_err(|err| format!("{}: {err}", local.display()))?;
+            for entry in read_dir(sftp, &remote).await? {
+                let local = local.join(&entry.name);
+                if entry.is_dir {
+                    pending.push_back((entry.path, local));
+                } else {
+                    let copy = FileCopy {
+                        direction: TransferDirection::Download,
+                        local,
+                        remote: entry.path,
+                        size: entry.size.unwrap_or_default(),
+                        overwrite: false,
+                    };
+                    self.enqueue(proxy, generation, copy);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn worker(pool: Rc<TransferPool>, sftp: Sftp, proxy: EventProxy) {
+    while pool.workers.get() <= pool.parallelism.get() {
+        let Ok(job) = pool.pending.recv().await else {
+            break;
+        };
+        let id = job.transfer.id;
+        if pool.is_cancelled(job.generation) {
+            proxy.send(SftpEvent::Transfer(TransferEvent::Cancelled(id)));
+            continue;
+        }
+        let event = match transfer(&pool, &sftp, &proxy, &job).await {
+            Ok(true) => TransferEvent::Finished(id),
+            Ok(false) => TransferEvent::Cancelled(id),
+            Err(err) => TransferEvent::Failed(id, err),
+        };
+        proxy.send(SftpEvent::Transfer(event));
+    }
+    pool.workers.set(pool.workers.get() - 1);
+}
+
+/// Copy a single file to a part file next to the destination, resuming from
+/// the part a previous attempt left, then replace the destination with it.
+/// Returns `false` if the transfer has been cancelled, the part is kept.
+async fn transfer(
+    pool: &TransferPool,
+    sftp: &Sftp,
+    proxy: &EventProxy,
+    job: &TransferJob,
+) -> Result<bool, String> {
+    let TransferJob {
+        transfer,
+        overwrite,
+        generation,
+    } = job;
+    let (local, remote) = (&transfer.local, &transfer.remote);
+    let local_err = |err: std::io::Error| format!("{}: {err}", local.display());
+    let remote_err = |err: wezterm_ssh::SftpChannelError| format!("{remote}: {err}");
+
+    match transfer.direction {
+        TransferDirection::Upload => {
+            let part = remote_part(remote);
+            let existing = if *overwrite {
+                None
+            } else {
+                sftp.metadata(part.clone()).await.ok().and_then(|m| m.size)
+            };
+            let offset = resume_offset(existing, transfer.size);
+            start(proxy, transfer.id, offset);
+            if offset > 0 && offset == transfer.size {
+                finish_remote(sftp, &part, remote).await?;
+                return Ok(true);
+            }
+
+            let mut reader = smol::fs::File::open(local).await.map_err(local_err)?;
+            reader
+                .seek(SeekFrom::Start(offset))
+                .await
+                .map_err(local_err)?;
+            let write_mode = if offset > 0 {
+                WriteMode::Append
+            } else {
+                WriteMode::Write
+            };
+            let options = OpenOptions {
+                read: false,
+                write: Some(write_mode),
+                mode: 0o644,
+                ty: OpenFileType::File,
+            };
+            let mut writer = sftp
+                .open_with_mode(part.clone(), options)
+                .await
+                .map_err(remote_err)?;
+            let copied = copy(pool, proxy, job, &mut reader, &mut writer, offset).await?;
+            if copied {
+                finish_remote(sftp, &part, remote).await?;
+            }
+            Ok(copied)
+        }
+        TransferDirection::Download => {
+            let (mut writer, offset) = open_local_part(local, transfer.size, *overwrite)
+                .await
+                .map_err(local_err)?;
+            start(proxy, transfer.id, offset);
+            if offset > 0 && offset == transfer.size {
+                finish_local(local).await.map_err(local_err)?;
+                return Ok(true);
+            }
+
+            let mut reader = sftp.open(remote.clone()).await.map_err(remote_err)?;
+            // The sftp file can't seek, so the already downloaded prefix is
+            // read again but not written a second time.
+            smol::io::copy((&mut reader).take(offset), &mut smol::io::sink())
+                .await
+                .map_err(|err| format!("{remote}: {err}"))?;
+            if pool.is_cancelled(*generation) {
+                return Ok(false);
+            }
+            let copied = copy(pool, proxy, job, &mut reader, &mut writer, offset).await?;
+            if copied {
+                finish_local(local).await.map_err(local_err)?;
+            }
+            Ok(copied)
+        }
+    }
+}
+
+fn remote_part(remote: &Utf8PathBuf) -> Utf8PathBuf {
+    Utf8PathBuf::from(format!("{remote}{PART_SUFFIX}"))
+}
+
+fn local_part(local: &Path) -> PathBuf {
+    let mut part = local.as_os_str().to_owned();
+    part.push(PART_SUFFIX);
+    PathBuf::from(part)
+}
+
+/// Replace `remote` with its complete part. Servers without the rename
+/// extension don't overwrite, the destination is removed first then.
+async fn finish_remote(
+    sftp: &Sftp,
+    part: &Utf8PathBuf,
+    remote: &Utf8PathBuf,
+) -> Result<(), String> {
+    let rename = || sftp.rename(part.clone(), remote.clone(), RenameOptions::default());
+    if rename().await.is_err() {
+        let _ = sftp.remove_file(remote.clone()).await;
+        rename().await.map_err(|err| format!("{remote}: {err}"))?;
+    }
+    Ok(())
+}
+
+/// Open the part file of a download to `local`, at the offset to resume
+/// from unless `overwrite`.
+async fn open_local_part(
+    local: &Path,
+    size: u64,
+    overwrite: bool,
+) -> std::io::Result<(smol::fs::File, u64)> {
+    let part = local_part(local);
+    let existing = if overwrite {
+        None
+    } else {
+        smol::fs::metadata(&part).await.ok().map(|m| m.len())
+    };
+    let offset = resume_offset(existing, size);
+    let file = smol::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(offset > 0)
+        .truncate(offset == 0)
+        .open(&part)
+        .await?;
+    Ok((file, offset))
+}
+
+/// Replace `local` with its complete part.
+async fn finish_local(local: &Path) -> std::io::Result<()> {
+    smol::fs::rename(local_part(local), local).await
+}
+
+fn start(proxy: &EventProxy, id: u64, resumed_from: u64) {
+    proxy.send(SftpEvent::Transfer(TransferEvent::Started {
+        id,
+        resumed_from,
+    }));
+}
+
+async fn copy<R, W>(
+    pool: &TransferPool,
+    proxy: &EventProxy,
+    job: &TransferJob,
+    reader: &mut R,
+    writer: &mut W,
+    offset: u64,
+) -> Result<bool, String>
+where
+    R: smol::io::AsyncRead + Unpin,
+    W: smol::io::AsyncWrite + Unpin,
+{
+    let id = job.transfer.id;
+    let mut buf = vec![0; CHUNK_SIZE];
+    let mut transferred = offset;
+    let mut last_progress = Instant::now();
+    loop {
+        if pool.is_cancelled(job.generation) {
+            let _ = writer.close().await;
+            return Ok(false);
+        }
+        let n = reader.read(&mut buf).await.map_err(|err| err.to_string())?;
+        if n == 0 {
+            break;
+        }
+        writer
+            .write_all(&buf[..n])
+            .await
+            .map_err(|err| err.to_string())?;
+        transferred += n as u64;
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            proxy.send(SftpEvent::Transfer(TransferEvent::Progress {
+                id,
+                transferred,
+            }));
+        }
+    }
+    writer.close().await.map_err(|err| err.to_string())?;
+    proxy.send(SftpEvent::Transfer(TransferEvent::Progress {
+        id,
+        transferred,
+    }));
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{finish_local, local_part, open_local_part, resume_offset, TransferQueue};
+    use smol::io::AsyncWriteExt;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn resume_from_partial_destination() {
+        assert_eq!(resume_offset(None, 100), 0);
+        assert_eq!(resume_offset(Some(40), 100), 40);
+        assert_eq!(resume_offset(Some(100), 100), 100);
+        // a larger destination is not a prefix, start over
+        assert_eq!(resume_offset(Some(120), 100), 0);
+    }
+
+    #[test]
+    fn unrelated_destination_replaced() {
+        let dir = std::env::temp_dir().join(format!("nxshell_transfer_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let local = dir.join("file");
+        let data = b"0123456789";
+        // a smaller file and a file of the same size aren't partial copies
+        for unrelated in [&b"old"[..], b"abcdefghij"] {
+            std::fs::write(&local, unrelated).unwrap();
+            smol::block_on(async {
+                let (mut part, offset) = open_local_part(&local, 10, false).await.unwrap();
+                assert_eq!(offset, 0);
+                part.write_all(data).await.unwrap();
+                part.close().await.unwrap();
+                finish_local(&local).await.unwrap();
+            });
+            assert_eq!(std::fs::read(&local).unwrap(), data);
+            assert!(!local_part(&local).exists());
+        }
+
+        // the part left by an interrupted attempt is resumed
+        std::fs::write(local_part(&local), &data[..4]).unwrap();
+        let offset = smol::block_on(async {
+            let (_, offset) = open_local_part(&local, 10, false).await.unwrap();
+            offset
+        });
+        assert_eq!(offset, 4);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn throughput_over_window() {
+        let mut queue = TransferQueue::default();
+        let start = Instant::now();
+        queue.sample(start);
+        queue.bytes = 2048;
+        queue.sample(start + Duration::from_secs(2));
+        assert_eq!(queue.throughput(), 1024.);
+
+        // samples older than the window are dropped
+        queue.sample(start + Duration::from_secs(6));
+        assert_eq!(queue.throughput(), 0.);
+    }
+}
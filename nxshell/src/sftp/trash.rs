@@ -0,0 +1,213 @@
+use super::{read_dir, read_head};
+use smol::io::AsyncWriteExt;
+use std::time::{SystemTime, UNIX_EPOCH};
+use wezterm_ssh::{RenameOptions, Sftp, Utf8PathBuf};
+
+/// Trash directory, relative to the remote home directory.
+const TRASH_DIR: &str = ".nxshell-trash";
+/// Written next to a trashed entry, holds the path it is restored to.
+const ORIGIN_FILE: &str = ".nxshell-origin";
+
+/// An entry moved to `~/.nxshell-trash/<timestamp>/`, where it keeps its name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrashItem {
+    pub dir: Utf8PathBuf,
+    /// Milliseconds since the unix epoch, also the name of `dir`.
+    pub deleted_at: u64,
+    pub original: Utf8PathBuf,
+    pub is_dir: bool,
+}
+
+impl TrashItem {
+    pub fn name(&self) -> &str {
+        self.original.file_name().unwrap_or(self.original.as_str())
+    }
+
+    fn trashed_path(&self) -> Utf8PathBuf {
+        self.dir.join(self.name())
+    }
+}
+
+async fn trash_root(sftp: &Sftp) -> Result<Utf8PathBuf, String> {
+    let home = sftp
+        .canonicalize(".")
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(home.join(TRASH_DIR))
+}
+
+/// Whether `path` is the trash directory or inside it.
+fn is_in_trash(root: &str, path: &str) -> bool {
+    path.strip_prefix(root)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Move an entry to the trash. The entry is renamed, so this fails if the
+/// trash is on another filesystem than the entry.
+pub(super) async fn move_to_trash(
+    sftp: &Sftp,
+    path: Utf8PathBuf,
+    is_dir: bool,
+) -> Result<TrashItem, String> {
+    let root = trash_root(sftp).await?;
+    if is_in_trash(root.as_str(), path.as_str()) {
+        return Err(format!("{path} is already in the trash"));
+    }
+    if sftp.metadata(root.clone()).await.is_err() {
+        sftp.create_dir(root.clone(), 0o700)
+            .await
+            .map_err(|err| format!("{root}: {err}"))?;
+    }
+
+    let mut deleted_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default();
+    // entries deleted within the same millisecond get their own directory
+    let dir = loop {
+        let dir = root.join(deleted_at.to_string());
+        match sftp.create_dir(dir.clone(), 0o700).await {
+            Ok(()) => break dir,
+            Err(_) if sftp.metadata(dir.clone()).await.is_ok() => deleted_at += 1,
+            Err(err) => return Err(format!("{dir}: {err}")),
+        }
+    };
+    let item = TrashItem {
+        dir,
+        deleted_at,
+        original: path,
+        is_dir,
+    };
+
+    let moved = async {
+        let mut origin = sftp
+            .create(item.dir.join(ORIGIN_FILE))
+            .await
+            .map_err(|err| err.to_string())?;
+        origin
+            .write_all(item.original.as_str().as_bytes())
+            .await
+            .map_err(|err| err.to_string())?;
+        origin.close().await.map_err(|err| err.to_string())?;
+        sftp.rename(
+            item.original.clone(),
+            item.trashed_path(),
+            RenameOptions::default(),
+        )
+        .await
+        .map_err(|err| err.to_string())
+    };
+    if let Err(err) = moved.await {
+        let _ = remove_all(sftp, &item.dir).await;
+        return Err(format!(
+            "failed to move {} to the trash: {err}",
+            item.original
+        ));
+    }
+    Ok(item)
+}
+
+/// Trashed entries, most recently deleted first.
+pub(super) async fn list(sftp: &Sftp) -> Result<Vec<TrashItem>, String> {
+    let root = trash_root(sftp).await?;
+    if sftp.metadata(root.clone()).await.is_err() {
+        return Ok(vec![]);
+    }
+    let mut items = vec![];
+    for entry in read_dir(sftp, &root).await? {
+        let Ok(deleted_at) = entry.name.parse() else {
+            continue;
+        };
+        // not created by us, or the entry has been moved out by hand
+        let Ok(origin) = read_head(sftp, &entry.path.join(ORIGIN_FILE), 4096).await else {
+            continue;
+        };
+        let mut item = TrashItem {
+            dir: entry.path,
+            deleted_at,
+            original: Utf8PathBuf::from(String::from_utf8_lossy(&origin).trim()),
+            is_dir: false,
+        };
+        let Ok(metadata) = sftp.symlink_metadata(item.trashed_path()).await else {
+            continue;
+        };
+        item.is_dir = metadata.is_dir();
+        items.push(item);
+    }
+    items.sort_by_key(|item| std::cmp::Reverse(item.deleted_at));
+    Ok(items)
+}
+
+/// Move a trashed entry back, an entry created at its path in the meantime is
+/// never overwritten.
+pub(super) async fn restore(sftp: &Sftp, item: &TrashItem) -> Result<(), String> {
+    if sftp.symlink_metadata(item.original.clone()).await.is_ok() {
+        return Err(format!("{} already exists", item.original));
+    }
+    sftp.rename(
+        item.trashed_path(),
+        item.original.clone(),
+        RenameOptions::default(),
+    )
+    .await
+    .map_err(|err| format!("failed to restore {}: {err}", item.original))?;
+    remove_all(sftp, &item.dir).await
+}
+
+pub(super) async fn purge(sftp: &Sftp, item: &TrashItem) -> Result<(), String> {
+    remove_all(sftp, &item.dir).await
+}
+
+/// Delete a file, or a directory with everything it contains. Symbolic links
+/// are removed, never followed.
+pub(super) async fn remove_all(sftp: &Sftp, path: &Utf8PathBuf) -> Result<(), String> {
+    let metadata = sftp
+        .symlink_metadata(path.clone())
+        .await
+        .map_err(|err| format!("{path}: {err}"))?;
+    if !metadata.is_dir() {
+        return sftp
+            .remove_file(path.clone())
+            .await
+            .map_err(|err| format!("{path}: {err}"));
+    }
+
+    let mut dirs = vec![path.clone()];
+    let mut i = 0;
+    while let Some(dir) = dirs.get(i).cloned() {
+        for entry in read_dir(sftp, &dir).await? {
+            if entry.is_dir {
+                dirs.push(entry.path);
+            } else {
+                sftp.remove_file(entry.path.clone())
+                    .await
+                    .map_err(|err| format!("{}: {err}", entry.path))?;
+            }
+        }
+        i += 1;
+    }
+    // children were discovered after their parent
+    for dir in dirs.into_iter().rev() {
+        sftp.remove_dir(dir.clone())
+            .await
+            .map_err(|err| format!("{dir}: {err}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_in_trash;
+
+    #[test]
+    fn entries_in_trash() {
+        let root = "/home/user/.nxshell-trash";
+        assert!(is_in_trash(root, root));
+        assert!(is_in_trash(
+            root,
+            "/home/user/.nxshell-trash/1700000000000/a"
+        ));
+        assert!(!is_in_trash(root, "/home/user/.nxshell-trash2"));
+        assert!(!is_in_trash(root, "/home/user/a"));
+    }
+}
@@ -0,0 +1,199 @@
+use crate::api::{generate_token, ApiCall, ApiRequest, ApiResponse, ApiServer};
+use crate::app::NxShell;
+use crate::errors::{error_toast, info_toast};
+use crate::i18n::t;
+use crate::ui::form::AuthType;
+use crate::ui::tab_view::{StagedCommand, Tab};
+use egui::{DragValue, RichText};
+use serde_json::{json, Value};
+use tracing::error;
+
+impl NxShell {
+    /// Start or stop the API to match its settings.
+    pub fn apply_api_settings(&mut self, ctx: &egui::Context) {
+        // joins the thread of the previous server, its port is free again
+        // for the new one
+        self.api = None;
+        let settings = &mut self.opts.api;
+        if !settings.enabled {
+            return;
+        }
+        if settings.token.is_empty() {
+            match generate_token() {
+                Ok(token) => settings.token = token,
+                Err(err) => error!("failed to generate the api token: {err}"),
+            }
+        }
+        match ApiServer::spawn(ctx.clone(), settings.port, settings.token.clone()) {
+            Ok(server) => self.api = Some(server),
+            Err(err) => {
+                error!("failed to start the api on port {}: {err}", settings.port);
+                settings.enabled = false;
+                self.toasts
+                    .add(error_toast(t!("api-error-start", error = err.to_string())));
+            }
+        }
+    }
+
+    /// Carry out the calls of the API clients.
+    pub fn serve_api(&mut self, ctx: &egui::Context) {
+        while let Some(request) = self.api.as_ref().and_then(ApiServer::try_recv) {
+            self.serve_api_request(ctx, request);
+        }
+    }
+
+    fn serve_api_request(&mut self, ctx: &egui::Context, request: ApiRequest) {
+        match request.call.clone() {
+            ApiCall::ListSessions => self.db.run(
+                |db| db.find_all_sessions(),
+                move |_, sessions| {
+                    let response = match sessions {
+                        Ok(groups) => {
+                            let sessions: Vec<_> = groups
+                                .values()
+                                .flatten()
+                                .map(|session| {
+                                    let auth = match AuthType::from(session.auth_type) {
+                                        AuthType::Password => "password",
+                                        AuthType::PublicKey => "public_key",
+                                        AuthType::Config => "config",
+                                    };
+                                    json!({
+                                        "group": session.group,
+                                        "name": session.name,
+                                        "auth": auth,
+                                    })
+                                })
+                                .collect();
+                            ApiResponse::ok(json!(sessions))
+                        }
+                        Err(err) => ApiResponse::error(500, err.to_string()),
+                    };
+                    request.reply(response);
+                },
+            ),
+            ApiCall::OpenSession { group, name } => {
+                let ctx = ctx.clone();
+                self.db.run(
+                    move |db| db.find_session(&group, &name),
+                    move |shell, session| {
+                        let response = match session {
+                            Ok(Some(session)) => {
+                                match shell.add_shell_tab_with_secret(&ctx, session) {
+                                    // the new tab is the active one
+                                    Ok(()) => match shell.dock_state.find_active_focused() {
+                                        Some((_, tab)) => ApiResponse::ok(tab_json(tab)),
+                                        None => ApiResponse::ok(Value::Null),
+                                    },
+                                    Err(err) => ApiResponse::error(500, err.to_string()),
+                                }
+                            }
+                            Ok(None) => ApiResponse::error(404, "no such session"),
+                            Err(err) => ApiResponse::error(500, err.to_string()),
+                        };
+                        request.reply(response);
+                    },
+                );
+            }
+            ApiCall::ListTabs => {
+                let tabs: Vec<_> = self
+                    .dock_state
+                    .iter_all_tabs()
+                    .map(|(_, tab)| tab_json(tab))
+                    .collect();
+                request.reply(ApiResponse::ok(json!(tabs)));
+            }
+            ApiCall::TabStatus(id) => {
+                let response = match self
+                    .dock_state
+                    .iter_all_tabs()
+                    .find(|(_, tab)| tab.id() == id)
+                {
+                    Some((_, tab)) => ApiResponse::ok(tab_json(tab)),
+                    None => ApiResponse::error(404, format!("no tab {id}")),
+                };
+                request.reply(response);
+            }
+            ApiCall::SendText { tab: id, text } => {
+                let tab = self
+                    .dock_state
+                    .iter_all_tabs_mut()
+                    .find(|(_, tab)| tab.id() == id);
+                let response = match tab {
+                    Some((_, tab)) => match tab.send_text(text, &mut self.clipboard) {
+                        true => ApiResponse::ok(tab_json(tab)),
+                        false => ApiResponse::error(400, format!("tab {id} has no terminal")),
+                    },
+                    None => ApiResponse::error(404, format!("no tab {id}")),
+                };
+                request.reply(response);
+            }
+            ApiCall::StageCommand {
+                tab: id,
+                command,
+                source,
+            } => {
+                let tab = self
+                    .dock_state
+                    .iter_all_tabs_mut()
+                    .find(|(_, tab)| tab.id() == id);
+                let staged = StagedCommand { command, source };
+                let response = match tab {
+                    Some((_, tab)) => match tab.stage_command(staged) {
+                        true => ApiResponse::ok(tab_json(tab)),
+                        false => ApiResponse::error(400, format!("tab {id} has no terminal")),
+                    },
+                    None => ApiResponse::error(404, format!("no tab {id}")),
+                };
+                request.reply(response);
+            }
+        }
+    }
+
+    pub fn api_menu(&mut self, ui: &mut egui::Ui) {
+        let settings = &mut self.opts.api;
+        let mut changed = ui
+            .checkbox(&mut settings.enabled, t!("api-enabled"))
+            .changed();
+        ui.horizontal(|ui| {
+            ui.label(t!("api-port"));
+            let port = ui.add(DragValue::new(&mut settings.port).range(1024..=65535));
+            changed |= settings.enabled && (port.drag_stopped() || port.lost_focus());
+        });
+        ui.horizontal(|ui| {
+            let copy = ui.add_enabled(
+                !settings.token.is_empty(),
+                egui::Button::new(t!("api-copy-token")),
+            );
+            if copy.clicked() {
+                ui.ctx().copy_text(settings.token.clone());
+                self.toasts.add(info_toast(t!("api-token-copied")));
+            }
+            if ui
+                .button(t!("api-new-token"))
+                .on_hover_text(t!("api-new-token-hint"))
+                .clicked()
+            {
+                settings.token.clear();
+                changed = true;
+            }
+        });
+        ui.label(RichText::new(t!("api-hint", port = settings.port)).weak());
+        if changed {
+            self.apply_api_settings(ui.ctx());
+        }
+    }
+}
+
+fn tab_json(tab: &Tab) -> Value {
+    let session = tab
+        .session_key()
+        .map(|(group, name)| json!({ "group": group, "name": name }));
+    json!({
+        "id": tab.id(),
+        "title": tab.title(),
+        "kind": tab.kind(),
+        "session": session,
+        "host": tab.status().host,
+    })
+}
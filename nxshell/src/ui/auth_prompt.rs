@@ -0,0 +1,195 @@
+use crate::app::NxShell;
+use crate::db::{DbConn, PromptRecord};
+use crate::i18n::t;
+use egui::{Grid, Id, Key, Modal, TextEdit};
+use egui_term::KeyboardInteractive;
+use std::collections::HashMap;
+use tracing::error;
+
+/// Keyboard-interactive prompts last asked by `host`, loaded from the db the
+/// first time they are needed.
+pub fn prompt_history<'a>(
+    cache: &'a mut HashMap<String, Vec<PromptRecord>>,
+    db: &DbConn,
+    host: &str,
+) -> &'a [PromptRecord] {
+    cache.entry(host.to_string()).or_insert_with(|| {
+        let host = host.to_string();
+        db.run(
+            move |db| Ok((db.find_auth_prompts(&host)?, host)),
+            |shell, loaded| match loaded {
+                Ok((prompts, host)) => {
+                    shell.opts.auth_prompts.insert(host, prompts);
+                }
+                Err(err) => error!("failed to load the auth prompts: {err}"),
+            },
+        );
+        vec![]
+    })
+}
+
+/// Whether the host asked for something the session can't answer last time,
+/// e.g. a verification code.
+pub fn expects_typed_answer(history: &[PromptRecord]) -> bool {
+    history.iter().any(|prompt| prompt.typed)
+}
+
+/// Field to focus: the first unanswered one the user typed last time, the
+/// first unanswered one otherwise.
+fn focused_prompt(interactive: &KeyboardInteractive, history: &[PromptRecord]) -> Option<usize> {
+    let unanswered = |index: &usize| interactive.prompts[*index].answer.is_none();
+    let typed_before = |index: &usize| {
+        let label = &interactive.prompts[*index].label;
+        history
+            .iter()
+            .any(|prompt| prompt.typed && prompt.label == *label)
+    };
+    let indexes = 0..interactive.prompts.len();
+    indexes
+        .clone()
+        .filter(unanswered)
+        .find(typed_before)
+        .or_else(|| indexes.clone().find(unanswered))
+}
+
+impl NxShell {
+    /// Ask for the answers a connecting session doesn't have, e.g. a
+    /// verification code after the password.
+    pub fn keyboard_interactive_prompt(&mut self, ctx: &egui::Context) {
+        let Some((tab_id, interactive)) = self
+            .dock_state
+            .iter_all_tabs()
+            .find_map(|(_, tab)| Some((tab.id(), tab.keyboard_interactive()?.clone())))
+        else {
+            return;
+        };
+        let history = prompt_history(&mut self.opts.auth_prompts, &self.db, &interactive.host);
+        let focused = focused_prompt(&interactive, history);
+
+        let answers_id = Id::new("keyboard_interactive").with(tab_id);
+        let stored: Option<Vec<String>> = ctx.data(|d| d.get_temp(answers_id));
+        let first_frame = stored.is_none();
+        let mut answers = stored.unwrap_or_else(|| interactive.answers());
+
+        let mut submitted = None;
+        Modal::new(Id::new("keyboard_interactive_modal").with(tab_id)).show(ctx, |ui| {
+            ui.set_max_width(420.);
+            ui.heading(t!("auth-prompt-title", host = interactive.host.clone()));
+            if !interactive.username.is_empty() {
+                ui.weak(t!("auth-prompt-user", user = interactive.username.clone()));
+            }
+            if !interactive.instructions.is_empty() {
+                ui.label(&interactive.instructions);
+            }
+            ui.add_space(8.);
+            let mut enter = false;
+            Grid::new("keyboard_interactive_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    for (index, (prompt, answer)) in
+                        interactive.prompts.iter().zip(&mut answers).enumerate()
+                    {
+                        ui.label(prompt.label.trim());
+                        let edit = TextEdit::singleline(answer).password(!prompt.echo);
+                        let response = ui.add(edit);
+                        if first_frame && focused == Some(index) {
+                            response.request_focus();
+                        }
+                        enter |= response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+                        ui.end_row();
+                    }
+                });
+            ui.add_space(8.);
+            ui.horizontal(|ui| {
+                if ui.button(t!("auth-prompt-cancel")).clicked() {
+                    submitted = Some(None);
+                }
+                if ui.button(t!("auth-prompt-submit")).clicked() || enter {
+                    submitted = Some(Some(answers.clone()));
+                }
+            });
+        });
+        // a click next to the dialog doesn't cancel the login
+        if submitted.is_none() && ctx.input(|i| i.key_pressed(Key::Escape)) {
+            submitted = Some(None);
+        }
+
+        let Some(answers) = submitted else {
+            ctx.data_mut(|d| d.insert_temp(answers_id, answers));
+            return;
+        };
+        ctx.data_mut(|d| d.remove_temp::<Vec<String>>(answers_id));
+        if answers.is_some() {
+            self.remember_prompts(&interactive);
+        }
+        if let Some((_, tab)) = self
+            .dock_state
+            .iter_all_tabs_mut()
+            .find(|(_, tab)| tab.id() == tab_id)
+        {
+            tab.answer_keyboard_interactive(answers);
+        }
+    }
+
+    /// Save the order and labels of the prompts of the host, for its next login.
+    fn remember_prompts(&mut self, interactive: &KeyboardInteractive) {
+        let prompts: Vec<PromptRecord> = interactive
+            .prompts
+            .iter()
+            .map(|prompt| PromptRecord {
+                label: prompt.label.clone(),
+                typed: prompt.answer.is_none(),
+            })
+            .collect();
+        let host = interactive.host.clone();
+        self.opts.auth_prompts.insert(host.clone(), prompts.clone());
+        self.db.run(
+            move |db| db.replace_auth_prompts(&host, &prompts),
+            |_, result| {
+                if let Err(err) = result {
+                    error!("failed to save the auth prompts: {err}");
+                }
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui_term::InteractivePrompt;
+
+    #[test]
+    fn focus_the_remembered_prompt() {
+        let prompt = |label: &str, answer: Option<&str>| InteractivePrompt {
+            label: label.to_string(),
+            echo: true,
+            answer: answer.map(str::to_string),
+        };
+        let interactive = KeyboardInteractive {
+            host: "example.com".to_string(),
+            username: "root".to_string(),
+            instructions: String::new(),
+            prompts: vec![
+                prompt("Password: ", Some("secret")),
+                prompt("Username: ", None),
+                prompt("Code: ", None),
+            ],
+        };
+        assert_eq!(focused_prompt(&interactive, &[]), Some(1));
+
+        let history = [
+            PromptRecord {
+                label: "Password: ".to_string(),
+                typed: false,
+            },
+            PromptRecord {
+                label: "Code: ".to_string(),
+                typed: true,
+            },
+        ];
+        assert_eq!(focused_prompt(&interactive, &history), Some(2));
+        assert!(expects_typed_answer(&history));
+        assert!(!expects_typed_answer(&history[..1]));
+    }
+}
@@ -0,0 +1,194 @@
+use crate::app::NxShell;
+use crate::errors::error_toast;
+use crate::i18n::t;
+use egui::{Button, CollapsingHeader, Grid, Id, Modal, RichText, TextEdit};
+use egui_phosphor::regular::WARNING;
+use egui_term::{Authentication, ConnectFailure, SshOptions, TermError, TermType};
+use std::error::Error;
+
+/// Failed ssh connection, with the credentials to retry it with.
+pub struct ConnectError {
+    tab_id: u64,
+    failure: ConnectFailure,
+    message: String,
+    options: SshOptions,
+    working_directory: Option<String>,
+    user: String,
+    /// Passphrase of the key of a session authenticated with a key, password
+    /// of the others.
+    password: String,
+}
+
+impl ConnectError {
+    /// `None` for a local terminal, which doesn't connect.
+    pub fn new(tab_id: u64, err: &(dyn Error + 'static), term_type: TermType) -> Option<Self> {
+        let TermType::Ssh {
+            options,
+            working_directory,
+        } = term_type
+        else {
+            return None;
+        };
+        let failure = err
+            .downcast_ref::<TermError>()
+            .map_or(ConnectFailure::Other, |err| {
+                err.connect_failure(&options.auth)
+            });
+        let user = match &options.auth {
+            Authentication::Password(user, _) | Authentication::PublicKey { user, .. } => {
+                user.clone()
+            }
+            Authentication::Config => String::new(),
+        };
+        Some(Self {
+            tab_id,
+            failure,
+            message: err.to_string(),
+            options: *options,
+            working_directory,
+            user,
+            password: String::new(),
+        })
+    }
+}
+
+enum ConnectErrorAction {
+    Close,
+    Retry,
+    RetryWithCredentials,
+    Diagnose,
+}
+
+fn failure_title(failure: ConnectFailure) -> String {
+    match failure {
+        ConnectFailure::Dns => t!("connect-error-dns"),
+        ConnectFailure::NoConfigHost => t!("connect-error-no-config-host"),
+        ConnectFailure::Timeout => t!("connect-error-timeout"),
+        ConnectFailure::Refused => t!("connect-error-refused"),
+        ConnectFailure::AuthRejected => t!("connect-error-auth"),
+        ConnectFailure::HostKeyMismatch => t!("connect-error-host-key"),
+        ConnectFailure::Other => t!("connect-error-other"),
+    }
+}
+
+/// What to check to fix the connection.
+fn failure_hint(failure: ConnectFailure, host: &str) -> String {
+    let host = host.to_string();
+    match failure {
+        ConnectFailure::Dns => t!("connect-error-dns-hint", host = host),
+        ConnectFailure::NoConfigHost => t!("connect-error-no-config-host-hint", host = host),
+        ConnectFailure::Timeout => t!("connect-error-timeout-hint", host = host),
+        ConnectFailure::Refused => t!("connect-error-refused-hint", host = host),
+        ConnectFailure::AuthRejected => t!("connect-error-auth-hint", host = host),
+        ConnectFailure::HostKeyMismatch => t!("connect-error-host-key-hint", host = host),
+        ConnectFailure::Other => t!("connect-error-other-hint"),
+    }
+}
+
+impl NxShell {
+    /// Tell why the first of the failed connections failed and what to check,
+    /// until it is dismissed or retried.
+    pub fn connect_error_dialog(&mut self, ctx: &egui::Context) {
+        let Some(error) = self.connect_errors.first_mut() else {
+            return;
+        };
+
+        let mut action = None;
+        let failure = error.failure;
+        let modal = Modal::new(Id::new("connect_error").with(error.tab_id)).show(ctx, |ui| {
+            ui.set_max_width(480.);
+            let title = RichText::new(format!("{WARNING} {}", failure_title(failure)))
+                .color(ui.visuals().error_fg_color);
+            ui.heading(title);
+            let port = error.options.port.unwrap_or(22);
+            ui.weak(format!("{}:{port}", error.options.host));
+            ui.add_space(8.);
+            ui.label(failure_hint(failure, &error.options.host));
+            ui.add_space(8.);
+            CollapsingHeader::new(t!("connect-error-details"))
+                .id_salt("connect_error_details")
+                .show(ui, |ui| {
+                    ui.monospace(&error.message);
+                });
+            // another user, or a password in place of the keys of the config.
+            // A session with a key keeps it, its passphrase is asked again
+            let public_key = matches!(error.options.auth, Authentication::PublicKey { .. });
+            CollapsingHeader::new(t!("connect-error-credentials"))
+                .id_salt("connect_error_credentials")
+                .default_open(failure == ConnectFailure::AuthRejected)
+                .show(ui, |ui| {
+                    Grid::new("connect_error_credentials_grid")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            ui.label(t!("connect-error-user"));
+                            ui.text_edit_singleline(&mut error.user);
+                            ui.end_row();
+                            ui.label(match public_key {
+                                true => t!("connect-error-passphrase"),
+                                false => t!("connect-error-password"),
+                            });
+                            ui.add(TextEdit::singleline(&mut error.password).password(true));
+                            ui.end_row();
+                        });
+                    let retry = Button::new(t!("connect-error-retry-credentials"));
+                    if ui
+                        .add_enabled(!error.user.trim().is_empty(), retry)
+                        .clicked()
+                    {
+                        action = Some(ConnectErrorAction::RetryWithCredentials);
+                    }
+                });
+            ui.add_space(8.);
+            ui.horizontal(|ui| {
+                if ui.button(t!("connect-error-close")).clicked() {
+                    action = Some(ConnectErrorAction::Close);
+                }
+                if ui.button(t!("connect-error-retry")).clicked() {
+                    action = Some(ConnectErrorAction::Retry);
+                }
+                if matches!(
+                    failure,
+                    ConnectFailure::Dns | ConnectFailure::Timeout | ConnectFailure::Refused
+                ) && ui.button(t!("session-diagnose")).clicked()
+                {
+                    action = Some(ConnectErrorAction::Diagnose);
+                }
+            });
+        });
+        if modal.should_close() {
+            action.get_or_insert(ConnectErrorAction::Close);
+        }
+
+        let Some(action) = action else {
+            return;
+        };
+        let error = self.connect_errors.remove(0);
+        let mut options = error.options;
+        match action {
+            ConnectErrorAction::Close => return,
+            ConnectErrorAction::Diagnose => {
+                self.diagnose_host(&options);
+                return;
+            }
+            ConnectErrorAction::Retry => {}
+            ConnectErrorAction::RetryWithCredentials => {
+                let user = error.user.trim().to_string();
+                options.auth = match options.auth {
+                    Authentication::PublicKey { path, .. } => Authentication::PublicKey {
+                        user,
+                        path,
+                        passphrase: error.password,
+                    },
+                    _ => Authentication::Password(user, error.password),
+                };
+            }
+        }
+        let typ = TermType::Ssh {
+            options: Box::new(options),
+            working_directory: error.working_directory,
+        };
+        if let Err(err) = self.add_shell_tab(ctx.clone(), typ) {
+            self.toasts.add(error_toast(err.to_string()));
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::app::NxShell;
+use crate::db::Session;
+use crate::diagnose::{ping_stats, Diagnosis, Pings, PING_HISTORY};
+use crate::errors::error_toast;
+use crate::i18n::t;
+use crate::ui::menubar::ssh_options;
+use egui::{
+    Color32, Grid, Id, Pos2, Rect, RichText, ScrollArea, Sense, Shape, Stroke, Vec2, Window,
+};
+use egui_term::SshOptions;
+use std::time::Duration;
+
+/// The windows refresh at least this often, a ping is sent every second.
+const DIAGNOSE_REFRESH: Duration = Duration::from_millis(500);
+const CHART_HEIGHT: f32 = 120.;
+/// The scale of the chart goes up by steps of this many milliseconds.
+const CHART_SCALE_STEP: f32 = 50.;
+const LOST_COLOR: Color32 = Color32::from_rgb(231, 130, 132);
+
+impl NxShell {
+    /// Ping the host of `session` and trace the route to it, in a window.
+    pub fn diagnose_session(&mut self, session: Session) {
+        match ssh_options(session) {
+            Ok(options) => self.diagnose_host(&options),
+            Err(err) => {
+                self.toasts.add(error_toast(err.to_string()));
+            }
+        }
+    }
+
+    /// Ping the host of `options` and trace the route to it, in a window.
+    pub fn diagnose_host(&mut self, options: &SshOptions) {
+        let diagnosis = Diagnosis::spawn(options);
+        // one window by host
+        let (host, port) = (&diagnosis.host, diagnosis.port);
+        if !self
+            .diagnoses
+            .iter()
+            .any(|open| (&open.host, open.port) == (host, port))
+        {
+            self.diagnoses.push(diagnosis);
+        }
+    }
+
+    pub fn diagnose_windows(&mut self, ctx: &egui::Context) {
+        if self.diagnoses.is_empty() {
+            return;
+        }
+        let mut errors = vec![];
+        self.diagnoses.retain_mut(|diagnosis| {
+            let mut open = true;
+            Window::new(t!("diagnose-title", host = diagnosis.host.clone()))
+                .id(Id::new((
+                    "diagnose_window",
+                    &diagnosis.host,
+                    diagnosis.port,
+                )))
+                .open(&mut open)
+                .default_width(480.)
+                .show(ctx, |ui| {
+                    let pings = diagnosis.pings();
+                    let stats = ping_stats(&pings);
+                    let ms = |rtt: Option<Duration>| match rtt {
+                        Some(rtt) => format!("{:.1} ms", rtt.as_secs_f64() * 1000.),
+                        None => "-".to_string(),
+                    };
+                    Grid::new("diagnose_stats").num_columns(5).show(ui, |ui| {
+                        ui.strong(t!("diagnose-last"));
+                        ui.strong(t!("diagnose-min"));
+                        ui.strong(t!("diagnose-avg"));
+                        ui.strong(t!("diagnose-max"));
+                        ui.strong(t!("diagnose-loss"));
+                        ui.end_row();
+                        ui.monospace(ms(stats.last));
+                        ui.monospace(ms(stats.min));
+                        ui.monospace(ms(stats.avg));
+                        ui.monospace(ms(stats.max));
+                        ui.monospace(format!("{:.0} %", stats.loss * 100.));
+                        ui.end_row();
+                    });
+                    ping_chart(ui, &pings);
+                    ui.label(
+                        RichText::new(t!(
+                            "diagnose-ping-hint",
+                            address = format!("{}:{}", diagnosis.host, diagnosis.port)
+                        ))
+                        .weak(),
+                    );
+                    ui.separator();
+
+                    ui.horizontal(|ui| {
+                        let tracing = diagnosis.is_tracing();
+                        let trace =
+                            ui.add_enabled(!tracing, egui::Button::new(t!("diagnose-trace")));
+                        if trace.clicked() {
+                            if let Err(err) = diagnosis.trace_route() {
+                                errors.push(t!("diagnose-trace-failed", error = err.to_string()));
+                            }
+                        }
+                        if tracing {
+                            ui.spinner();
+                        }
+                    });
+                    let route = diagnosis.route();
+                    if !route.is_empty() {
+                        ScrollArea::vertical()
+                            .max_height(200.)
+                            .stick_to_bottom(true)
+                            .show(ui, |ui| {
+                                for line in route {
+                                    ui.monospace(line);
+                                }
+                            });
+                    }
+                });
+            open
+        });
+        for error in errors {
+            self.toasts.add(error_toast(error));
+        }
+        ctx.request_repaint_after(DIAGNOSE_REFRESH);
+    }
+}
+
+/// Round trips of the pings over time, the lost ones as red ticks.
+fn ping_chart(ui: &mut egui::Ui, pings: &Pings) {
+    let size = Vec2::new(ui.available_width(), CHART_HEIGHT);
+    let (response, painter) = ui.allocate_painter(size, Sense::hover());
+    let rect = response.rect;
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 2., visuals.extreme_bg_color);
+
+    let max_ms = pings
+        .iter()
+        .flatten()
+        .map(|rtt| rtt.as_secs_f32() * 1000.)
+        .fold(0., f32::max);
+    let scale = (max_ms / CHART_SCALE_STEP).ceil().max(1.) * CHART_SCALE_STEP;
+    let step = rect.width() / (PING_HISTORY - 1) as f32;
+    // the latest ping at the right
+    let x = |i: usize| rect.right() - (pings.len() - 1 - i) as f32 * step;
+
+    let mut points = vec![];
+    for (i, rtt) in pings.iter().enumerate() {
+        match rtt {
+            Some(rtt) => {
+                let y = rect.bottom() - rtt.as_secs_f32() * 1000. / scale * rect.height();
+                points.push(Pos2::new(x(i), y));
+            }
+            None => {
+                let tick = Rect::from_min_max(
+                    Pos2::new(x(i) - 1., rect.top()),
+                    Pos2::new(x(i) + 1., rect.bottom()),
+                );
+                painter.rect_filled(tick, 0., LOST_COLOR.linear_multiply(0.5));
+                if points.len() > 1 {
+                    let stroke = Stroke::new(1.5, visuals.selection.bg_fill);
+                    painter.add(Shape::line(std::mem::take(&mut points), stroke));
+                }
+                points.clear();
+            }
+        }
+    }
+    if points.len() > 1 {
+        painter.add(Shape::line(
+            points,
+            Stroke::new(1.5, visuals.selection.bg_fill),
+        ));
+    }
+    painter.text(
+        rect.left_top() + Vec2::new(4., 2.),
+        egui::Align2::LEFT_TOP,
+        format!("{scale:.0} ms"),
+        egui::FontId::monospace(10.),
+        visuals.weak_text_color(),
+    );
+}
@@ -0,0 +1,142 @@
+use crate::app::NxShell;
+use crate::errors::{error_toast, info_toast};
+use crate::export::{pdf, png};
+use crate::i18n::t;
+use crate::ui::output_log::file_name;
+use egui::{Event, Rect, UserData, ViewportCommand};
+use egui_term::HistorySnapshot;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::error;
+
+/// Directory of the exported images and prints, in the data directory.
+const EXPORT_DIR: &str = "exports";
+
+/// Image of a terminal view, cut out of the screenshot of the window.
+pub struct ViewExport {
+    rect: Rect,
+    name: String,
+}
+
+impl NxShell {
+    /// Export the active terminal as an image of its view or a print of its
+    /// history.
+    pub fn export_menu(&mut self, ui: &mut egui::Ui) {
+        let active = self.dock_state.find_active_focused().and_then(|(_, tab)| {
+            let rect = tab.view_rect()?;
+            let name = tab
+                .session_key()
+                .map_or("local".to_string(), |(_, name)| name);
+            Some((rect, name))
+        });
+        let Some((rect, name)) = active else {
+            for label in [t!("export-view-image"), t!("export-history-pdf")] {
+                ui.add_enabled(false, egui::Button::new(label))
+                    .on_disabled_hover_text(t!("output-log-no-terminal"));
+            }
+            return;
+        };
+        if ui.button(t!("export-view-image")).clicked() {
+            // taken on the next frame, once the menu is closed
+            self.view_export = Some(ViewExport { rect, name });
+            ui.ctx().request_repaint();
+            ui.close();
+        } else if ui.button(t!("export-history-pdf")).clicked() {
+            self.export_history(&name);
+            ui.close();
+        }
+    }
+
+    /// Take the screenshot of an image to export, and save the images cut
+    /// out of the screenshots taken.
+    pub fn export_view_images(&mut self, ctx: &egui::Context) {
+        if let Some(export) = self.view_export.take() {
+            ctx.send_viewport_cmd(ViewportCommand::Screenshot(UserData::new(export)));
+        }
+        let screenshots: Vec<_> = ctx.input(|i| {
+            i.raw
+                .events
+                .iter()
+                .filter_map(|event| match event {
+                    Event::Screenshot {
+                        image, user_data, ..
+                    } => {
+                        let export = user_data.data.as_ref()?.downcast_ref::<ViewExport>()?;
+                        let image = image.region(&export.rect, Some(i.pixels_per_point));
+                        Some((export.name.clone(), png(&image)))
+                    }
+                    _ => None,
+                })
+                .collect()
+        });
+        for (name, png) in screenshots {
+            self.save_export(&file_name(&name, "png"), &png);
+        }
+    }
+
+    fn export_history(&mut self, name: &str) {
+        let redaction = self.opts.mask_secrets.then_some(&self.opts.redaction);
+        let lines = self
+            .dock_state
+            .find_active_focused()
+            .and_then(|(_, tab)| tab.styled_history(redaction));
+        if let Some(lines) = lines {
+            let pdf = pdf(&lines);
+            self.save_export(&file_name(name, "pdf"), &pdf);
+        }
+    }
+
+    pub(crate) fn save_export(&mut self, file_name: &str, data: &[u8]) {
+        let saved = write_export(&self.data_dir, file_name, data);
+        self.export_saved(saved);
+    }
+
+    /// Save the history of a terminal as text. The text can be a million
+    /// lines long, it is made and written on the db thread, off the frame.
+    pub(crate) fn save_scrollback(&mut self, name: &str, snapshot: HistorySnapshot) {
+        let data_dir = self.data_dir.clone();
+        let file_name = file_name(name, "txt");
+        let redaction = self.opts.mask_secrets.then(|| self.opts.redaction.clone());
+        self.db.run(
+            move |_| {
+                let text = snapshot.text(redaction.as_ref());
+                Ok(write_export(&data_dir, &file_name, text.as_bytes()))
+            },
+            |shell, saved| match saved {
+                Ok(saved) => shell.export_saved(saved),
+                Err(err) => {
+                    shell.toasts.add(error_toast(err.to_string()));
+                }
+            },
+        );
+    }
+
+    fn export_saved(&mut self, (path, saved): (PathBuf, io::Result<()>)) {
+        match saved {
+            Ok(()) => {
+                self.toasts.add(info_toast(t!(
+                    "export-saved",
+                    path = path.display().to_string()
+                )));
+                let dir = path.parent().unwrap_or(&path);
+                if let Err(err) = open::that(dir) {
+                    error!("failed to open {}: {err}", dir.display());
+                }
+            }
+            Err(err) => {
+                error!("failed to export to {}: {err}", path.display());
+                self.toasts
+                    .add(error_toast(t!("export-error", error = err.to_string())));
+            }
+        }
+    }
+}
+
+/// Write `data` to the export directory in `data_dir`, returns the path of
+/// the file along with whether it was written.
+fn write_export(data_dir: &Path, file_name: &str, data: &[u8]) -> (PathBuf, io::Result<()>) {
+    let dir = data_dir.join(EXPORT_DIR);
+    let path = dir.join(file_name);
+    let written = std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(&path, data));
+    (path, written)
+}
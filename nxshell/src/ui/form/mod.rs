@@ -1,11 +1,29 @@
-use crate::db::Session;
-use indexmap::IndexMap;
+use crate::db::{Session, SessionGroups};
+use crate::ui::sessions::SessionList;
+use crate::workspace::Workspaces;
 
 mod session;
-pub use session::AuthType;
+pub use session::{
+    open_secret, parse_forwards, parse_shortcuts, remote_locale, transport_options, AuthType,
+    Compression, ThemeMode, TintMode,
+};
 
 #[derive(Default)]
 pub struct NxStateManager {
     // db state
-    pub sessions: Option<IndexMap<String, Vec<Session>>>,
+    pub sessions: Option<SessionGroups>,
+    /// Sessions saved as templates, loaded with the session form.
+    pub templates: Option<Vec<Session>>,
+    /// Set until the sessions are loaded at startup.
+    pub loading: bool,
+    pub session_list: SessionList,
+    pub workspaces: Workspaces,
+}
+
+impl NxStateManager {
+    /// Show `sessions` in the session list.
+    pub fn set_sessions(&mut self, sessions: SessionGroups) {
+        self.sessions = Some(sessions);
+        self.session_list.invalidate();
+    }
 }
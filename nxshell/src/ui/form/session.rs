@@ -1,15 +1,21 @@
 use crate::app::NxShell;
-use crate::db::Session;
-use crate::errors::{error_toast, NxError};
+use crate::db::{Db, Session};
+use crate::errors::{error_toast, info_toast, NxError};
+use crate::i18n::t;
+use crate::wol::parse_mac;
 use egui::{
-    Align2, CentralPanel, ComboBox, Context, Grid, Id, Layout, Order, TextEdit, TopBottomPanel,
-    Window,
+    Align2, CentralPanel, CollapsingHeader, Color32, ComboBox, Context, Grid, Id, Key,
+    KeyboardShortcut, Layout, Modifiers, Order, ScrollArea, TextEdit, TopBottomPanel, Window,
 };
 use egui_form::garde::GardeReport;
 use egui_form::{Form, FormField};
-use egui_term::{Authentication, SshOptions, TermType};
+use egui_phosphor::regular::TRASH;
+use egui_term::{
+    Authentication, Forward, HostKeyCheck, RemoteLocale, SshOptions, TermError, TermType,
+    TerminalTint, TransportOptions,
+};
 use garde::Validate;
-use orion::aead::{seal, SecretKey};
+use orion::aead::{open, seal, SecretKey};
 use std::fmt::Display;
 use tracing::error;
 
@@ -27,8 +33,59 @@ pub struct SessionState {
     pub auth_type: AuthType,
     #[garde(skip)]
     pub username: String,
+    /// The password, or the passphrase of the identity file.
     #[garde(skip)]
     pub auth_data: String,
+    #[garde(skip)]
+    pub identity_file: String,
+    #[garde(skip)]
+    pub tint_mode: TintMode,
+    #[garde(skip)]
+    pub tint_color: Color32,
+    #[garde(skip)]
+    pub theme: ThemeMode,
+    #[garde(skip)]
+    pub protected_keys: String,
+    #[garde(skip)]
+    pub font_family: String,
+    /// The default size when `0`.
+    #[garde(range(min = 0., max = 72.))]
+    pub font_size: f32,
+    #[garde(skip)]
+    pub compression: Compression,
+    #[garde(skip)]
+    pub ciphers: String,
+    #[garde(skip)]
+    pub kex_algorithms: String,
+    /// Name of the template the form was filled with.
+    #[garde(skip)]
+    pub template: String,
+    #[garde(skip)]
+    pub plain_prompt: bool,
+    #[garde(skip)]
+    pub local_echo: bool,
+    /// Minutes, the idle timeout of the app when `0`.
+    #[garde(skip)]
+    pub idle_timeout: u32,
+    #[garde(skip)]
+    pub mac_address: String,
+    #[garde(skip)]
+    pub nested_command: String,
+    #[garde(skip)]
+    pub locale: String,
+    #[garde(skip)]
+    pub alt_eight_bit: bool,
+    #[garde(skip)]
+    pub altgr_as_alt: bool,
+    #[garde(skip)]
+    pub option_as_meta: bool,
+    #[garde(skip)]
+    pub ambiguous_wide: bool,
+    #[garde(skip)]
+    pub narrow_emoji: bool,
+    /// One forward by line, see [`parse_forwards`].
+    #[garde(skip)]
+    pub forwards: String,
 }
 
 #[repr(u16)]
@@ -37,13 +94,15 @@ pub enum AuthType {
     #[default]
     Password = 0,
     Config = 1,
+    PublicKey = 2,
 }
 
 impl Display for AuthType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            AuthType::Password => write!(f, "Password"),
-            AuthType::Config => write!(f, "SSH Config"),
+            AuthType::Password => write!(f, "{}", t!("auth-password")),
+            AuthType::Config => write!(f, "{}", t!("auth-ssh-config")),
+            AuthType::PublicKey => write!(f, "{}", t!("auth-public-key")),
         }
     }
 }
@@ -52,11 +111,216 @@ impl From<u16> for AuthType {
     fn from(value: u16) -> Self {
         match value {
             0 => AuthType::Password,
+            2 => AuthType::PublicKey,
             _ => AuthType::Config,
         }
     }
 }
 
+/// How the tabs of a session are tinted, e.g. to tell production hosts apart.
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq)]
+pub enum TintMode {
+    #[default]
+    None = 0,
+    Background = 1,
+    Border = 2,
+}
+
+impl Display for TintMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TintMode::None => write!(f, "{}", t!("tint-none")),
+            TintMode::Background => write!(f, "{}", t!("tint-background")),
+            TintMode::Border => write!(f, "{}", t!("tint-border")),
+        }
+    }
+}
+
+impl From<u16> for TintMode {
+    fn from(value: u16) -> Self {
+        match value {
+            1 => TintMode::Background,
+            2 => TintMode::Border,
+            _ => TintMode::None,
+        }
+    }
+}
+
+impl TintMode {
+    /// `color` is stored as `0xRRGGBB`.
+    pub fn tint(self, color: u32) -> Option<TerminalTint> {
+        let [_, r, g, b] = color.to_be_bytes();
+        let color = Color32::from_rgb(r, g, b);
+        match self {
+            TintMode::None => None,
+            TintMode::Background => Some(TerminalTint::Background(color)),
+            TintMode::Border => Some(TerminalTint::Border(color)),
+        }
+    }
+}
+
+/// Palette of the terminals of a session.
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq)]
+pub enum ThemeMode {
+    /// Light when the host sets a light background, dark otherwise.
+    #[default]
+    Auto = 0,
+    Dark = 1,
+    Light = 2,
+}
+
+impl Display for ThemeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThemeMode::Auto => write!(f, "{}", t!("theme-auto")),
+            ThemeMode::Dark => write!(f, "{}", t!("theme-dark")),
+            ThemeMode::Light => write!(f, "{}", t!("theme-light")),
+        }
+    }
+}
+
+impl From<u16> for ThemeMode {
+    fn from(value: u16) -> Self {
+        match value {
+            1 => ThemeMode::Dark,
+            2 => ThemeMode::Light,
+            _ => ThemeMode::Auto,
+        }
+    }
+}
+
+impl ThemeMode {
+    pub const ALL: [ThemeMode; 3] = [ThemeMode::Auto, ThemeMode::Dark, ThemeMode::Light];
+
+    /// Whether the light palette is shown, given the background set by the
+    /// host through OSC 11.
+    pub fn is_light(self, remote_background: Option<Color32>) -> bool {
+        match self {
+            ThemeMode::Dark => false,
+            ThemeMode::Light => true,
+            ThemeMode::Auto => remote_background.is_some_and(|background| {
+                let [r, g, b, _] = background.to_array();
+                // perceived brightness
+                0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32 > 127.5
+            }),
+        }
+    }
+}
+
+/// Compression of the ssh transport, it helps on slow links.
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq)]
+pub enum Compression {
+    /// As set by the ssh config, off otherwise.
+    #[default]
+    Default = 0,
+    On = 1,
+    Off = 2,
+}
+
+impl Display for Compression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Compression::Default => write!(f, "{}", t!("compression-default")),
+            Compression::On => write!(f, "{}", t!("compression-on")),
+            Compression::Off => write!(f, "{}", t!("compression-off")),
+        }
+    }
+}
+
+impl From<u16> for Compression {
+    fn from(value: u16) -> Self {
+        match value {
+            1 => Compression::On,
+            2 => Compression::Off,
+            _ => Compression::Default,
+        }
+    }
+}
+
+/// Transport options of a session, `ciphers` and `kex_algorithms` are comma
+/// separated lists, the ssh defaults are used when they are empty.
+pub fn transport_options(
+    compression: Compression,
+    ciphers: &str,
+    kex_algorithms: &str,
+) -> TransportOptions {
+    TransportOptions {
+        compression: match compression {
+            Compression::Default => None,
+            Compression::On => Some(true),
+            Compression::Off => Some(false),
+        },
+        ciphers: algorithm_list(ciphers),
+        kex_algorithms: algorithm_list(kex_algorithms),
+    }
+}
+
+/// `LANG` of the shell of a session, picked from the locales of the host
+/// when `locale` is empty.
+pub fn remote_locale(locale: &str) -> RemoteLocale {
+    match locale.trim() {
+        "" => RemoteLocale::Auto,
+        locale => RemoteLocale::Fixed(locale.to_string()),
+    }
+}
+
+/// Forwards of a session, one by line, e.g. `L 8080:localhost:80`. Blank
+/// lines are skipped.
+pub fn parse_forwards(text: &str) -> Result<Vec<Forward>, NxError> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.parse().map_err(|err| match err {
+                TermError::UnsupportedForward(_) => {
+                    NxError::Plain(t!("error-unsupported-forward", forward = line))
+                }
+                _ => NxError::Plain(t!("error-invalid-forward", forward = line)),
+            })
+        })
+        .collect()
+}
+
+/// `aes128-ctr, aes256-ctr` as ssh expects it, `aes128-ctr,aes256-ctr`.
+fn algorithm_list(text: &str) -> Option<String> {
+    let names: Vec<&str> = text
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect();
+    (!names.is_empty()).then(|| names.join(","))
+}
+
+/// Parse a comma separated list of shortcuts, e.g. `Ctrl+C, Ctrl+D`.
+pub fn parse_shortcuts(text: &str) -> Result<Vec<KeyboardShortcut>, NxError> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|shortcut| !shortcut.is_empty())
+        .map(parse_shortcut)
+        .collect()
+}
+
+fn parse_shortcut(text: &str) -> Result<KeyboardShortcut, NxError> {
+    let invalid = || NxError::Plain(t!("error-invalid-shortcut", shortcut = text));
+    let mut names: Vec<&str> = text.split('+').map(str::trim).collect();
+    let key = names.pop().and_then(Key::from_name).ok_or_else(invalid)?;
+
+    let mut modifiers = Modifiers::NONE;
+    for name in names {
+        modifiers |= match name.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => Modifiers::CTRL,
+            "shift" => Modifiers::SHIFT,
+            "alt" | "option" => Modifiers::ALT,
+            "cmd" | "command" => Modifiers::MAC_CMD,
+            _ => return Err(invalid()),
+        };
+    }
+    Ok(KeyboardShortcut::new(modifiers, key))
+}
+
 impl Default for SessionState {
     fn default() -> Self {
         Self {
@@ -67,6 +331,29 @@ impl Default for SessionState {
             auth_type: AuthType::Password,
             username: String::default(),
             auth_data: String::default(),
+            identity_file: String::default(),
+            tint_mode: TintMode::None,
+            tint_color: Color32::from_rgb(0xd2, 0x0f, 0x39),
+            theme: ThemeMode::Auto,
+            protected_keys: String::default(),
+            font_family: String::default(),
+            font_size: 0.,
+            compression: Compression::Default,
+            ciphers: String::default(),
+            kex_algorithms: String::default(),
+            template: String::default(),
+            plain_prompt: false,
+            local_echo: false,
+            idle_timeout: 0,
+            mac_address: String::default(),
+            nested_command: String::default(),
+            locale: String::default(),
+            alt_eight_bit: false,
+            altgr_as_alt: false,
+            option_as_meta: false,
+            ambiguous_wide: false,
+            narrow_emoji: false,
+            forwards: String::default(),
         }
     }
 }
@@ -87,6 +374,163 @@ impl SessionState {
     pub fn remove(self, ctx: &Context, id: Id) {
         ctx.data_mut(|d| d.remove_temp::<Self>(id));
     }
+
+    /// Form filled with a saved session, e.g. to duplicate it.
+    pub fn from_session(session: Session) -> Result<Self, NxError> {
+        let auth_type = AuthType::from(session.auth_type);
+        let auth_data = match auth_type {
+            AuthType::Password | AuthType::PublicKey if !session.secret_data.is_empty() => {
+                open_secret(&session.secret_key, &session.secret_data)?
+            }
+            _ => String::new(),
+        };
+        let [_, r, g, b] = session.tint_color.to_be_bytes();
+        Ok(Self {
+            group: session.group,
+            name: session.name,
+            host: session.host,
+            port: session.port,
+            auth_type,
+            username: session.username,
+            auth_data,
+            identity_file: session.identity_file,
+            tint_mode: TintMode::from(session.tint_mode),
+            tint_color: Color32::from_rgb(r, g, b),
+            theme: ThemeMode::from(session.theme),
+            protected_keys: session.protected_keys,
+            font_family: session.font_family,
+            font_size: session.font_size,
+            compression: Compression::from(session.compression),
+            ciphers: session.ciphers,
+            kex_algorithms: session.kex_algorithms,
+            template: String::new(),
+            plain_prompt: session.plain_prompt,
+            local_echo: session.local_echo,
+            idle_timeout: session.idle_timeout,
+            mac_address: session.mac_address,
+            nested_command: session.nested_command,
+            locale: session.locale,
+            alt_eight_bit: session.alt_eight_bit,
+            altgr_as_alt: session.altgr_as_alt,
+            option_as_meta: session.option_as_meta,
+            ambiguous_wide: session.ambiguous_wide,
+            narrow_emoji: session.narrow_emoji,
+            forwards: session.forwards,
+        })
+    }
+
+    /// Fill the form with a template, its name is left to the new session and
+    /// its host is a prefix to complete, e.g. `10.0.1.`.
+    fn apply_template(&mut self, template: Session) -> Result<(), NxError> {
+        let name = template.name.clone();
+        *self = Self {
+            name: String::new(),
+            template: name,
+            ..Self::from_session(template)?
+        };
+        Ok(())
+    }
+}
+
+/// Password, or passphrase of the identity file, of a saved session.
+pub fn open_secret(secret_key: &[u8], secret_data: &[u8]) -> Result<String, NxError> {
+    let key = SecretKey::from_slice(secret_key)?;
+    let auth_data = open(&key, secret_data)?;
+    Ok(String::from_utf8(auth_data)?)
+}
+
+/// Session to save from the form, with its password sealed, and how the
+/// terminal authenticates. The credentials of a template can be left empty.
+fn sealed_session(
+    session: &SessionState,
+    template: bool,
+) -> Result<(Session, Authentication), NxError> {
+    let auth = match session.auth_type {
+        AuthType::Password => {
+            let empty = session.username.trim().is_empty() || session.auth_data.trim().is_empty();
+            if empty && !template {
+                return Err(NxError::Plain(t!("error-empty-credentials")));
+            }
+            Authentication::Password(session.username.to_string(), session.auth_data.to_string())
+        }
+        // a key without a passphrase is fine
+        AuthType::PublicKey => {
+            let empty =
+                session.username.trim().is_empty() || session.identity_file.trim().is_empty();
+            if empty && !template {
+                return Err(NxError::Plain(t!("error-empty-identity")));
+            }
+            Authentication::PublicKey {
+                user: session.username.to_string(),
+                path: session.identity_file.trim().to_string(),
+                passphrase: session.auth_data.to_string(),
+            }
+        }
+        AuthType::Config => Authentication::Config,
+    };
+    let (secret_key, secret_data) = match session.auth_type {
+        AuthType::Password | AuthType::PublicKey if !session.auth_data.is_empty() => {
+            let secret_key = SecretKey::generate(32)?;
+            let secret_data = seal(&secret_key, session.auth_data.as_bytes())?;
+            (secret_key.unprotected_as_bytes().to_vec(), secret_data)
+        }
+        _ => (vec![], vec![]),
+    };
+
+    parse_shortcuts(&session.protected_keys)?;
+    let forwards = parse_forwards(&session.forwards)?;
+    if !session.mac_address.trim().is_empty() {
+        parse_mac(&session.mac_address)?;
+    }
+
+    let new_session = Session {
+        group: session.group.to_string(),
+        name: session.name.to_string(),
+        host: session.host.to_string(),
+        port: session.port,
+        auth_type: session.auth_type as u16,
+        username: session.username.to_string(),
+        secret_data,
+        secret_key,
+        tint_mode: session.tint_mode as u16,
+        tint_color: u32::from_be_bytes([
+            0,
+            session.tint_color.r(),
+            session.tint_color.g(),
+            session.tint_color.b(),
+        ]),
+        theme: session.theme as u16,
+        protected_keys: session.protected_keys.trim().to_string(),
+        font_family: session.font_family.clone(),
+        font_size: session.font_size,
+        compression: session.compression as u16,
+        ciphers: algorithm_list(&session.ciphers).unwrap_or_default(),
+        kex_algorithms: algorithm_list(&session.kex_algorithms).unwrap_or_default(),
+        template,
+        plain_prompt: session.plain_prompt,
+        local_echo: session.local_echo,
+        idle_timeout: session.idle_timeout,
+        mac_address: session.mac_address.trim().to_string(),
+        nested_command: session.nested_command.trim().to_string(),
+        locale: session.locale.trim().to_string(),
+        alt_eight_bit: session.alt_eight_bit,
+        altgr_as_alt: session.altgr_as_alt,
+        option_as_meta: session.option_as_meta,
+        ambiguous_wide: session.ambiguous_wide,
+        narrow_emoji: session.narrow_emoji,
+        identity_file: match session.auth_type {
+            AuthType::PublicKey => session.identity_file.trim().to_string(),
+            _ => String::new(),
+        },
+        // normalized, e.g. `l 8080:localhost:80` is saved as `L 8080:localhost:80`
+        forwards: forwards
+            .iter()
+            .map(Forward::to_string)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        ..Default::default()
+    };
+    Ok((new_session, auth))
 }
 
 impl NxShell {
@@ -95,9 +539,9 @@ impl NxShell {
         let mut session_state = SessionState::load(ctx, session_id);
 
         let show_add_session_modal = self.opts.show_add_session_modal.clone();
-        let mut should_close = false;
 
-        Window::new("New Session")
+        Window::new(t!("session-form-title"))
+            .id(session_id)
             .order(Order::Middle)
             .open(&mut show_add_session_modal.borrow_mut())
             .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
@@ -108,100 +552,242 @@ impl NxShell {
 
                 TopBottomPanel::bottom("session_modal_bottom_panel").show_inside(ui, |ui| {
                     ui.with_layout(Layout::right_to_left(egui::Align::TOP), |ui| {
-                        if let Some(Ok(())) = form.handle_submit(&ui.button("Submit"), ui) {
+                        if let Some(Ok(())) =
+                            form.handle_submit(&ui.button(t!("session-form-submit")), ui)
+                        {
+                            // the window is closed once the session is saved
                             match self.submit_session(ctx, &mut session_state) {
-                                Ok(_) => should_close = true,
+                                Ok(_) => {}
                                 Err(err) => {
                                     error!("failed to add session: {err}");
                                     self.toasts.add(error_toast(err.to_string()));
                                 }
                             }
                         }
+                        let template_btn = ui.button(t!("session-save-template"));
+                        if template_btn
+                            .on_hover_text(t!("session-save-template-hint"))
+                            .clicked()
+                        {
+                            if let Err(err) = self.save_template(&session_state) {
+                                self.toasts.add(error_toast(err.to_string()));
+                            }
+                        }
                     });
                 });
 
                 CentralPanel::default().show_inside(ui, |ui| {
-                    ui.vertical(|ui| {
-                        ui.add_space(20.);
-                    });
-                    ui.horizontal(|ui| {
-                        ui.add_space(20.);
+                    // the advanced options don't fit otherwise
+                    ScrollArea::vertical().show(ui, |ui| {
+                        ui.vertical(|ui| {
+                            ui.add_space(20.);
+                        });
+                        ui.horizontal(|ui| {
+                            ui.add_space(20.);
 
-                        self.ssh_form(ui, &mut form, &mut session_state);
+                            ui.vertical(|ui| self.ssh_form(ui, &mut form, &mut session_state));
+                        });
                     });
                 });
             });
 
-        if should_close {
-            *self.opts.show_add_session_modal.borrow_mut() = false;
-            session_state.remove(ctx, session_id);
-        } else {
-            session_state.store(ctx, session_id);
-        }
+        session_state.store(ctx, session_id);
     }
 
     fn submit_session(&mut self, ctx: &Context, session: &mut SessionState) -> Result<(), NxError> {
-        let (auth, secret_key, secret_data) = match session.auth_type {
-            AuthType::Password => {
-                if session.username.trim().is_empty() || session.auth_data.trim().is_empty() {
-                    return Err(NxError::Plain(
-                        "`username` and `password` cannot be empty in `Password` mode".to_string(),
-                    ));
-                }
-
-                let secret_key = SecretKey::generate(32)?;
-                let secret_data = seal(&secret_key, session.auth_data.as_bytes())?;
-                let secret_key = secret_key.unprotected_as_bytes().to_vec();
-
-                (
-                    Authentication::Password(
-                        session.username.to_string(),
-                        session.auth_data.to_string(),
-                    ),
-                    secret_key,
-                    secret_data,
-                )
-            }
-            AuthType::Config => (Authentication::Config, vec![], vec![]),
-        };
+        let (new_session, auth) = sealed_session(session, false)?;
         let typ = TermType::Ssh {
-            options: SshOptions {
+            options: Box::new(SshOptions {
                 group: session.group.to_string(),
                 name: session.name.to_string(),
                 host: session.host.to_string(),
                 port: Some(session.port),
                 auth,
-            },
+                // pinned once connected, the session is saved meanwhile
+                host_key: HostKeyCheck::Pinned(None),
+                transport: transport_options(
+                    session.compression,
+                    &session.ciphers,
+                    &session.kex_algorithms,
+                ),
+                locale: remote_locale(&session.locale),
+                forwards: parse_forwards(&session.forwards)?,
+            }),
+            working_directory: None,
         };
 
-        if self
-            .db
-            .find_session(&session.group, &session.name)?
-            .is_some()
-        {
-            return Err(NxError::Plain(
-                "`group` and `name` already exist, please choose another name.".to_string(),
-            ));
+        let ctx = ctx.clone();
+        self.db.run(
+            move |db| {
+                if db
+                    .find_session(&new_session.group, &new_session.name)?
+                    .is_some()
+                {
+                    return Err(NxError::Plain(t!("error-session-exists")));
+                }
+                db.insert_session(new_session)?;
+                db.find_all_sessions()
+            },
+            move |shell, sessions| match sessions {
+                Ok(sessions) => {
+                    shell.state_manager.set_sessions(sessions);
+                    *shell.opts.show_add_session_modal.borrow_mut() = false;
+                    SessionState::default().remove(&ctx, Id::new(SessionState::id()));
+                    if let Err(err) = shell.add_shell_tab(ctx, typ) {
+                        shell.toasts.add(error_toast(err.to_string()));
+                    }
+                }
+                Err(err) => {
+                    error!("failed to add session: {err}");
+                    shell.toasts.add(error_toast(err.to_string()));
+                }
+            },
+        );
+        Ok(())
+    }
+
+    /// Save the form as a template for new sessions, under its name.
+    fn save_template(&mut self, session: &SessionState) -> Result<(), NxError> {
+        if session.name.trim().is_empty() {
+            return Err(NxError::Plain(t!("error-template-name")));
         }
+        let (template, _) = sealed_session(session, true)?;
+        let name = template.name.clone();
+        self.db.run(
+            move |db| {
+                if db.find_session(&template.group, &template.name)?.is_some() {
+                    return Err(NxError::Plain(t!("error-session-exists")));
+                }
+                db.insert_session(template)?;
+                db.find_templates()
+            },
+            move |shell, templates| match templates {
+                Ok(templates) => {
+                    shell.state_manager.templates = Some(templates);
+                    shell
+                        .toasts
+                        .add(info_toast(t!("session-template-saved", name = name)));
+                }
+                Err(err) => {
+                    error!("failed to save the template: {err}");
+                    shell.toasts.add(error_toast(err.to_string()));
+                }
+            },
+        );
+        Ok(())
+    }
 
-        self.add_shell_tab(ctx.clone(), typ)?;
+    fn delete_template(&mut self, template: Session) {
+        self.db.run(
+            move |db| {
+                db.delete_session(&template.group, &template.name)?;
+                db.find_templates()
+            },
+            |shell, templates| match templates {
+                Ok(templates) => shell.state_manager.templates = Some(templates),
+                Err(err) => {
+                    error!("failed to delete the template: {err}");
+                    shell.toasts.add(error_toast(err.to_string()));
+                }
+            },
+        );
+    }
 
-        self.db.insert_session(Session {
-            group: session.group.to_string(),
-            name: session.name.to_string(),
-            host: session.host.to_string(),
-            port: session.port,
-            auth_type: session.auth_type as u16,
-            username: session.username.to_string(),
-            secret_data,
-            secret_key,
-            ..Default::default()
-        })?;
+    /// Templates of the form, loaded the first time it is shown.
+    fn templates(&mut self) -> &[Session] {
+        if self.state_manager.templates.is_none() {
+            self.db
+                .run(Db::find_templates, |shell, templates| match templates {
+                    Ok(templates) => shell.state_manager.templates = Some(templates),
+                    Err(err) => error!("failed to load the templates: {err}"),
+                });
+        }
+        self.state_manager.templates.get_or_insert_default()
+    }
 
-        if let Ok(sessions) = self.db.find_all_sessions() {
-            self.state_manager.sessions = Some(sessions);
+    /// Open the form filled with a saved session, to save a copy of it under
+    /// another name.
+    pub fn duplicate_session(&mut self, ctx: &Context, group: String, name: String) {
+        let ctx = ctx.clone();
+        self.db.run(
+            move |db| db.find_session(&group, &name),
+            move |shell, session| {
+                let state = match session
+                    .and_then(|session| session.map(SessionState::from_session).transpose())
+                {
+                    Ok(Some(state)) => state,
+                    Ok(None) => return,
+                    Err(err) => {
+                        error!("failed to duplicate the session: {err}");
+                        shell.toasts.add(error_toast(err.to_string()));
+                        return;
+                    }
+                };
+                let name = t!("session-copy-name", name = state.name.clone());
+                SessionState { name, ..state }.store(&ctx, Id::new(SessionState::id()));
+                *shell.opts.show_add_session_modal.borrow_mut() = true;
+            },
+        );
+    }
+
+    fn template_row(&mut self, ui: &mut egui::Ui, session: &mut SessionState) {
+        let templates = self.templates().to_vec();
+        if templates.is_empty() {
+            return;
+        }
+        ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.label(t!("session-template"));
+        });
+        let mut applied = None;
+        let mut deleted = None;
+        ui.horizontal(|ui| {
+            let selected = if session.template.is_empty() {
+                t!("session-template-none")
+            } else {
+                session.template.clone()
+            };
+            ComboBox::from_id_salt("session_template")
+                .selected_text(selected)
+                .width(160.)
+                .show_ui(ui, |ui| {
+                    for template in &templates {
+                        let label = if template.group.is_empty() {
+                            template.name.clone()
+                        } else {
+                            format!("{} / {}", template.group, template.name)
+                        };
+                        if ui.selectable_label(false, label).clicked() {
+                            applied = Some(template.clone());
+                        }
+                    }
+                })
+                .response
+                .on_hover_text(t!("session-template-hint"));
+            let current = templates
+                .iter()
+                .find(|template| template.name == session.template);
+            if let Some(template) = current {
+                if ui
+                    .button(TRASH)
+                    .on_hover_text(t!("session-template-delete"))
+                    .clicked()
+                {
+                    deleted = Some(template.clone());
+                }
+            }
+        });
+        ui.end_row();
+
+        if let Some(template) = applied {
+            if let Err(err) = session.apply_template(template) {
+                error!("failed to apply the template: {err}");
+                self.toasts.add(error_toast(err.to_string()));
+            }
+        }
+        if let Some(template) = deleted {
+            session.template.clear();
+            self.delete_template(template);
         }
-        Ok(())
     }
 
     fn ssh_form(
@@ -214,24 +800,27 @@ impl NxShell {
             .num_columns(2)
             .spacing([10.0, 15.0])
             .show(ui, |ui| {
+                // template
+                self.template_row(ui, session);
+
                 // group
                 ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
-                    ui.label("Group:");
+                    ui.label(t!("session-group"));
                 });
                 FormField::new(form, "group").ui(ui, TextEdit::singleline(&mut session.group));
                 ui.end_row();
 
                 // name
                 ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
-                    ui.label("Name:");
+                    ui.label(t!("session-name"));
                 });
                 FormField::new(form, "name").ui(ui, TextEdit::singleline(&mut session.name));
                 ui.end_row();
 
                 // host
                 let host_label = match session.auth_type {
-                    AuthType::Password => "Host:",
-                    AuthType::Config => "Host Alias:",
+                    AuthType::Password | AuthType::PublicKey => t!("session-host"),
+                    AuthType::Config => t!("session-host-alias"),
                 };
 
                 ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
@@ -242,7 +831,7 @@ impl NxShell {
                     ui.horizontal_centered(|ui| {
                         let host_edit = TextEdit::singleline(&mut session.host);
                         match session.auth_type {
-                            AuthType::Password => {
+                            AuthType::Password | AuthType::PublicKey => {
                                 FormField::new(form, "host")
                                     .ui(ui, host_edit.char_limit(15).desired_width(150.));
                             }
@@ -251,7 +840,7 @@ impl NxShell {
                             }
                         }
 
-                        if let AuthType::Password = session.auth_type {
+                        if session.auth_type != AuthType::Config {
                             FormField::new(form, "port").ui(
                                 ui,
                                 egui::DragValue::new(&mut session.port)
@@ -266,7 +855,7 @@ impl NxShell {
 
                 // auth type
                 ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
-                    ui.label("Auth Type:");
+                    ui.label(t!("session-auth-type"));
                 });
                 ComboBox::from_id_salt(session.auth_type)
                     .selected_text(session.auth_type.to_string())
@@ -277,6 +866,11 @@ impl NxShell {
                             AuthType::Password,
                             AuthType::Password.to_string(),
                         );
+                        ui.selectable_value(
+                            &mut session.auth_type,
+                            AuthType::PublicKey,
+                            AuthType::PublicKey.to_string(),
+                        );
                         ui.selectable_value(
                             &mut session.auth_type,
                             AuthType::Config,
@@ -286,25 +880,401 @@ impl NxShell {
                 ui.end_row();
 
                 // FIXME: Why is the line height smaller in this row?
-                if let AuthType::Password = session.auth_type {
+                if session.auth_type != AuthType::Config {
                     // username
                     ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
-                        ui.label("Username:");
+                        ui.label(t!("session-username"));
                     });
                     FormField::new(form, "username")
                         .ui(ui, TextEdit::singleline(&mut session.username));
                     ui.end_row();
+                }
 
-                    // password
+                if let AuthType::PublicKey = session.auth_type {
+                    // identity file
                     ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
-                        ui.label("Password:");
+                        ui.label(t!("session-identity-file"));
                     });
-                    FormField::new(form, "auth_data").ui(
+                    FormField::new(form, "identity_file").ui(
                         ui,
-                        TextEdit::singleline(&mut session.auth_data).password(true),
+                        TextEdit::singleline(&mut session.identity_file)
+                            .hint_text("~/.ssh/id_ed25519"),
                     );
                     ui.end_row();
                 }
+
+                if session.auth_type != AuthType::Config {
+                    // password, or passphrase of the key
+                    let label = match session.auth_type {
+                        AuthType::PublicKey => t!("session-passphrase"),
+                        _ => t!("session-password"),
+                    };
+                    ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(label);
+                    });
+                    let mut edit = TextEdit::singleline(&mut session.auth_data).password(true);
+                    if let AuthType::PublicKey = session.auth_type {
+                        edit = edit.hint_text(t!("session-passphrase-hint"));
+                    }
+                    FormField::new(form, "auth_data").ui(ui, edit);
+                    ui.end_row();
+                }
+
+                // tint
+                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(t!("session-tint"));
+                });
+                ui.horizontal(|ui| {
+                    ComboBox::from_id_salt("session_tint_mode")
+                        .selected_text(session.tint_mode.to_string())
+                        .width(120.)
+                        .show_ui(ui, |ui| {
+                            for mode in [TintMode::None, TintMode::Background, TintMode::Border] {
+                                ui.selectable_value(&mut session.tint_mode, mode, mode.to_string());
+                            }
+                        });
+                    if session.tint_mode != TintMode::None {
+                        ui.color_edit_button_srgba(&mut session.tint_color);
+                    }
+                })
+                .response
+                .on_hover_text(t!("session-tint-hint"));
+                ui.end_row();
+
+                // theme
+                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(t!("session-theme"));
+                });
+                ComboBox::from_id_salt("session_theme")
+                    .selected_text(session.theme.to_string())
+                    .width(120.)
+                    .show_ui(ui, |ui| {
+                        for mode in ThemeMode::ALL {
+                            ui.selectable_value(&mut session.theme, mode, mode.to_string());
+                        }
+                    })
+                    .response
+                    .on_hover_text(t!("session-theme-hint"));
+                ui.end_row();
+
+                // protected keys
+                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(t!("session-protected-keys"));
+                });
+                ui.add(
+                    TextEdit::singleline(&mut session.protected_keys).hint_text("Ctrl+C, Ctrl+D"),
+                )
+                .on_hover_text(t!("session-protected-keys-hint"));
+                ui.end_row();
+
+                // font
+                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(t!("session-font"));
+                });
+                ui.horizontal(|ui| {
+                    let family = &mut session.font_family;
+                    self.fonts.family_picker(ui, "session_font_family", family);
+                    let size = egui::DragValue::new(&mut session.font_size)
+                        .range(0.0..=72.0)
+                        .custom_formatter(|size, _| {
+                            if size == 0. {
+                                t!("font-default-size")
+                            } else {
+                                size.to_string()
+                            }
+                        });
+                    FormField::new(form, "font_size").ui(ui, size);
+                })
+                .response
+                .on_hover_text(t!("session-font-hint"));
+                ui.end_row();
+
+                // highlighting
+                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(t!("session-highlight"));
+                });
+                let mut highlight = !session.plain_prompt;
+                if ui
+                    .checkbox(&mut highlight, t!("session-highlight-prompt"))
+                    .on_hover_text(t!("session-highlight-hint"))
+                    .changed()
+                {
+                    session.plain_prompt = !highlight;
+                }
+                ui.end_row();
+
+                // local echo
+                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(t!("session-local-echo"));
+                });
+                ui.checkbox(&mut session.local_echo, t!("session-local-echo-predict"))
+                    .on_hover_text(t!("session-local-echo-hint"));
+                ui.end_row();
+
+                // meta keys
+                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(t!("session-alt-key"));
+                });
+                ui.vertical(|ui| {
+                    let mut esc_prefix = !session.alt_eight_bit;
+                    if ui
+                        .checkbox(&mut esc_prefix, t!("session-alt-esc-prefix"))
+                        .on_hover_text(t!("session-alt-esc-prefix-hint"))
+                        .changed()
+                    {
+                        session.alt_eight_bit = !esc_prefix;
+                    }
+                    ui.checkbox(&mut session.altgr_as_alt, t!("session-altgr-as-alt"))
+                        .on_hover_text(t!("session-altgr-as-alt-hint"));
+                    if cfg!(target_os = "macos") {
+                        ui.checkbox(&mut session.option_as_meta, t!("session-option-as-meta"))
+                            .on_hover_text(t!("session-option-as-meta-hint"));
+                    }
+                });
+                ui.end_row();
+
+                // unicode width
+                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(t!("session-char-width"));
+                });
+                ui.vertical(|ui| {
+                    ui.checkbox(&mut session.ambiguous_wide, t!("session-ambiguous-wide"))
+                        .on_hover_text(t!("session-ambiguous-wide-hint"));
+                    ui.checkbox(&mut session.narrow_emoji, t!("session-narrow-emoji"))
+                        .on_hover_text(t!("session-narrow-emoji-hint"));
+                });
+                ui.end_row();
+
+                // idle timeout
+                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(t!("session-idle-timeout"));
+                });
+                ui.add(
+                    egui::DragValue::new(&mut session.idle_timeout)
+                        .range(0..=1440)
+                        .custom_formatter(|minutes, _| {
+                            if minutes == 0. {
+                                t!("session-idle-timeout-default")
+                            } else {
+                                minutes.to_string()
+                            }
+                        }),
+                )
+                .on_hover_text(t!("session-idle-timeout-hint"));
+                ui.end_row();
+
+                // wake-on-lan
+                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(t!("session-mac-address"));
+                });
+                ui.add(
+                    TextEdit::singleline(&mut session.mac_address).hint_text("aa:bb:cc:dd:ee:ff"),
+                )
+                .on_hover_text(t!("session-mac-address-hint"));
+                ui.end_row();
+
+                // nested connection
+                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(t!("session-nested-command"));
+                });
+                ui.add(
+                    TextEdit::singleline(&mut session.nested_command)
+                        .hint_text("ssh inner-host")
+                        .code_editor(),
+                )
+                .on_hover_text(t!("session-nested-command-hint"));
+                ui.end_row();
+            });
+
+        ui.add_space(10.);
+        CollapsingHeader::new(t!("session-advanced"))
+            .id_salt("ssh_form_advanced")
+            .show(ui, |ui| {
+                Grid::new("ssh_form_advanced_grid")
+                    .num_columns(2)
+                    .spacing([10.0, 15.0])
+                    .show(ui, |ui| transport_form(ui, session));
+            });
+
+        CollapsingHeader::new(t!("session-tunnels"))
+            .id_salt("ssh_form_tunnels")
+            .show(ui, |ui| {
+                ui.add(
+                    TextEdit::multiline(&mut session.forwards)
+                        .hint_text("L 8080:localhost:80")
+                        .desired_rows(3)
+                        .code_editor(),
+                )
+                .on_hover_text(t!("session-tunnels-hint"));
             });
     }
 }
+
+/// Transport preferences and the locale, for constrained or legacy hosts.
+fn transport_form(ui: &mut egui::Ui, session: &mut SessionState) {
+    // compression
+    ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+        ui.label(t!("session-compression"));
+    });
+    ComboBox::from_id_salt("session_compression")
+        .selected_text(session.compression.to_string())
+        .width(120.)
+        .show_ui(ui, |ui| {
+            for compression in [Compression::Default, Compression::On, Compression::Off] {
+                let text = compression.to_string();
+                ui.selectable_value(&mut session.compression, compression, text);
+            }
+        })
+        .response
+        .on_hover_text(t!("session-compression-hint"));
+    ui.end_row();
+
+    // ciphers
+    ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+        ui.label(t!("session-ciphers"));
+    });
+    ui.add(TextEdit::singleline(&mut session.ciphers).hint_text("aes128-ctr, aes256-ctr"))
+        .on_hover_text(t!("session-ciphers-hint"));
+    ui.end_row();
+
+    // key exchange
+    ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+        ui.label(t!("session-kex"));
+    });
+    ui.add(
+        TextEdit::singleline(&mut session.kex_algorithms).hint_text("diffie-hellman-group14-sha1"),
+    )
+    .on_hover_text(t!("session-kex-hint"));
+    ui.end_row();
+
+    // locale
+    ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+        ui.label(t!("session-locale"));
+    });
+    ui.add(TextEdit::singleline(&mut session.locale).hint_text(t!("session-locale-auto")))
+        .on_hover_text(t!("session-locale-hint"));
+    ui.end_row();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_the_form_with_a_saved_session() {
+        let state = SessionState {
+            name: "web".to_string(),
+            host: "10.0.1.".to_string(),
+            username: "root".to_string(),
+            auth_data: "secret".to_string(),
+            compression: Compression::On,
+            ..Default::default()
+        };
+        let (session, _) = sealed_session(&state, true).unwrap();
+        assert!(session.template);
+        let filled = SessionState::from_session(session.clone()).unwrap();
+        assert_eq!(
+            (filled.auth_data.as_str(), filled.compression),
+            ("secret", Compression::On)
+        );
+
+        let mut form = SessionState::default();
+        form.apply_template(session).unwrap();
+        assert_eq!((form.name.as_str(), form.host.as_str()), ("", "10.0.1."));
+        assert_eq!(form.template, "web");
+
+        let anonymous = SessionState {
+            auth_data: String::new(),
+            ..state
+        };
+        assert!(sealed_session(&anonymous, false).is_err());
+        assert!(sealed_session(&anonymous, true)
+            .unwrap()
+            .0
+            .secret_key
+            .is_empty());
+
+        let key = SessionState {
+            auth_type: AuthType::PublicKey,
+            identity_file: "~/.ssh/id_ed25519".to_string(),
+            ..anonymous
+        };
+        let (session, auth) = sealed_session(&key, false).unwrap();
+        assert_eq!(AuthType::from(session.auth_type), AuthType::PublicKey);
+        assert!(session.secret_data.is_empty());
+        assert_eq!(
+            auth,
+            Authentication::PublicKey {
+                user: "root".to_string(),
+                path: "~/.ssh/id_ed25519".to_string(),
+                passphrase: String::new(),
+            }
+        );
+        let filled = SessionState::from_session(session).unwrap();
+        assert_eq!(filled.identity_file, "~/.ssh/id_ed25519");
+        let no_key = SessionState {
+            identity_file: " ".to_string(),
+            ..key
+        };
+        assert!(sealed_session(&no_key, false).is_err());
+    }
+
+    #[test]
+    fn transport_of_a_session() {
+        let transport = transport_options(Compression::On, " aes128-ctr ,, aes256-ctr", " ");
+        assert_eq!(transport.compression, Some(true));
+        assert_eq!(transport.ciphers.as_deref(), Some("aes128-ctr,aes256-ctr"));
+        assert_eq!(transport.kex_algorithms, None);
+        assert_eq!(
+            transport_options(Compression::Default, "", "").compression,
+            None
+        );
+        assert_eq!(Compression::from(Compression::Off as u16), Compression::Off);
+    }
+
+    #[test]
+    fn forwards_of_a_session() {
+        let state = SessionState {
+            host: "10.0.1.2".to_string(),
+            forwards: " l 8080:localhost:80\n\nL 9000:[::1]:3000 ".to_string(),
+            ..Default::default()
+        };
+        let (session, _) = sealed_session(&state, true).unwrap();
+        assert_eq!(session.forwards, "L 8080:localhost:80\nL 9000:[::1]:3000");
+        assert_eq!(parse_forwards(&session.forwards).unwrap().len(), 2);
+        assert!(parse_forwards("R 9000:localhost:3000").is_err());
+        assert!(parse_forwards("D 1080").is_err());
+
+        let invalid = SessionState {
+            forwards: "L 8080".to_string(),
+            ..state
+        };
+        assert!(sealed_session(&invalid, true).is_err());
+    }
+
+    #[test]
+    fn parse_protected_keys() {
+        let shortcuts = parse_shortcuts("Ctrl+C, ctrl + shift+d,,F1").unwrap();
+        assert_eq!(
+            shortcuts,
+            vec![
+                KeyboardShortcut::new(Modifiers::CTRL, Key::C),
+                KeyboardShortcut::new(Modifiers::CTRL | Modifiers::SHIFT, Key::D),
+                KeyboardShortcut::new(Modifiers::NONE, Key::F1),
+            ]
+        );
+        assert!(parse_shortcuts("").unwrap().is_empty());
+        assert!(parse_shortcuts("Ctrl+").is_err());
+        assert!(parse_shortcuts("Hyper+C").is_err());
+        assert!(parse_shortcuts("+C").is_err());
+    }
+
+    #[test]
+    fn theme_of_the_host_background() {
+        assert!(ThemeMode::Auto.is_light(Some(Color32::from_rgb(0xfd, 0xf6, 0xe3))));
+        assert!(!ThemeMode::Auto.is_light(Some(Color32::from_rgb(0x00, 0x2b, 0x36))));
+        assert!(!ThemeMode::Auto.is_light(None));
+        assert!(ThemeMode::Light.is_light(None));
+        assert_eq!(ThemeMode::from(ThemeMode::Dark as u16), ThemeMode::Dark);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::app::NxShell;
+use crate::i18n::t;
+use egui::{Grid, Id, Modal, RichText};
+use egui_phosphor::regular::WARNING;
+
+impl NxShell {
+    /// Ask to trust the host key of a pinned session, on its first connection
+    /// or when the key changed. Its connection waits until the key is trusted
+    /// or refused, the modal can't be dismissed otherwise.
+    pub fn host_key_prompt(&mut self, ctx: &egui::Context) {
+        let Some((tab_id, change)) = self
+            .dock_state
+            .iter_all_tabs()
+            .find_map(|(_, tab)| Some((tab.id(), tab.host_key_change()?.clone())))
+        else {
+            return;
+        };
+
+        let mut answer = None;
+        Modal::new(Id::new("host_key_changed").with(tab_id)).show(ctx, |ui| {
+            ui.set_max_width(480.);
+            match &change.expected {
+                Some(_) => {
+                    let warning =
+                        RichText::new(format!("{WARNING} {}", t!("host-key-changed-title")))
+                            .color(ui.visuals().error_fg_color);
+                    ui.heading(warning);
+                    ui.label(t!("host-key-changed-message", host = change.host.clone()));
+                }
+                None => {
+                    ui.heading(t!("host-key-new-title"));
+                    ui.label(t!("host-key-new-message", host = change.host.clone()));
+                }
+            }
+            ui.add_space(8.);
+            Grid::new("host_key_fingerprints")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    if let Some(expected) = &change.expected {
+                        ui.label(t!("host-key-expected"));
+                        ui.monospace(expected);
+                        ui.end_row();
+                    }
+                    ui.label(t!("host-key-found"));
+                    ui.monospace(&change.found);
+                    ui.end_row();
+                });
+            ui.add_space(8.);
+            ui.horizontal(|ui| {
+                if ui.button(t!("host-key-disconnect")).clicked() {
+                    answer = Some(false);
+                }
+                let trust = match change.expected {
+                    Some(_) => {
+                        RichText::new(t!("host-key-trust")).color(ui.visuals().error_fg_color)
+                    }
+                    None => RichText::new(t!("host-key-trust-new")),
+                };
+                if ui.button(trust).clicked() {
+                    answer = Some(true);
+                }
+            });
+        });
+
+        if let Some(trust) = answer {
+            if let Some((_, tab)) = self
+                .dock_state
+                .iter_all_tabs_mut()
+                .find(|(_, tab)| tab.id() == tab_id)
+            {
+                tab.answer_host_key(trust);
+            }
+        }
+    }
+}
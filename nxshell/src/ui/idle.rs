@@ -0,0 +1,111 @@
+use crate::app::NxShell;
+use crate::errors::{info_toast, warning_toast};
+use crate::i18n::t;
+use egui_term::PtyEvent;
+use std::time::{Duration, Instant};
+use tracing::error;
+
+/// The tabs are warned this long before they are disconnected.
+const IDLE_WARNING: Duration = Duration::from_secs(60);
+/// The tabs are checked at least this often while the app is idle.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// When a terminal last read or wrote something.
+#[derive(Debug, PartialEq)]
+pub struct IdleTimer {
+    bytes: u64,
+    active_at: Instant,
+    /// Set once the tab is warned, until something happens in it.
+    pub warned: bool,
+}
+
+impl Default for IdleTimer {
+    fn default() -> Self {
+        Self {
+            bytes: 0,
+            active_at: Instant::now(),
+            warned: false,
+        }
+    }
+}
+
+impl IdleTimer {
+    /// How long the terminal is idle at `now`, given the bytes it read and
+    /// wrote so far.
+    pub fn sample(&mut self, now: Instant, bytes: u64) -> Duration {
+        if bytes != self.bytes {
+            self.bytes = bytes;
+            self.reset(now);
+        }
+        now.saturating_duration_since(self.active_at)
+    }
+
+    /// Start over, e.g. the user chose to stay connected.
+    pub fn reset(&mut self, now: Instant) {
+        self.active_at = now;
+        self.warned = false;
+    }
+}
+
+impl NxShell {
+    /// Disconnect the ssh tabs without input nor output for longer than the
+    /// idle timeout of their session, or of the app. They are warned a minute
+    /// before.
+    pub fn enforce_idle_timeout(&mut self, ctx: &egui::Context) {
+        let global = self
+            .opts
+            .idle_timeout
+            .then(|| Duration::from_secs(self.opts.idle_timeout_min as u64 * 60));
+        let now = Instant::now();
+        let mut disconnected = vec![];
+        for (_, tab) in self.dock_state.iter_all_tabs_mut() {
+            let timeout = tab
+                .session_key()
+                .and_then(|key| self.opts.session_settings.get(&key))
+                .and_then(|settings| settings.idle_timeout)
+                .or(global);
+            let (Some(timeout), Some(idle)) = (timeout, tab.idle_for(now)) else {
+                continue;
+            };
+            ctx.request_repaint_after(IDLE_CHECK_INTERVAL);
+            if idle >= timeout {
+                disconnected.push((tab.id(), tab.title()));
+            } else if idle + IDLE_WARNING >= timeout && tab.warn_idle() {
+                self.toasts
+                    .add(warning_toast(t!("idle-warning", name = tab.title())));
+            }
+        }
+        for (id, name) in disconnected {
+            if let Err(err) = self.command_sender.send((id, PtyEvent::Exit)) {
+                error!("close idle tab {id} failed: {err}");
+                continue;
+            }
+            self.toasts
+                .add(info_toast(t!("idle-disconnected", name = name)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_until_activity() {
+        let mut timer = IdleTimer::default();
+        let start = Instant::now();
+        timer.sample(start, 0);
+        let later = start + Duration::from_secs(90);
+        assert!(timer.sample(later, 0) >= Duration::from_secs(90));
+
+        timer.warned = true;
+        assert_eq!(timer.sample(later, 12), Duration::ZERO);
+        assert!(!timer.warned);
+
+        timer.reset(later + Duration::from_secs(30));
+        assert_eq!(
+            timer.sample(later + Duration::from_secs(40), 12),
+            Duration::from_secs(10)
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use crate::app::NxShell;
+use crate::db::Session;
+use crate::errors::{error_toast, info_toast, NxError};
+use crate::i18n::t;
+use crate::import::{self, ImportFormat, ImportRow};
+use crate::ui::form::AuthType;
+use egui::{Align, Color32, ComboBox, Id, Layout, RichText, TextEdit, Window};
+use egui_extras::{Column, TableBuilder};
+use std::path::Path;
+use tracing::error;
+
+const ROW_HEIGHT: f32 = 20.;
+
+/// Sessions read from a file, previewed before they are saved.
+#[derive(Debug, Clone, Default)]
+pub struct SessionImport {
+    path: String,
+    format: ImportFormat,
+    rows: Vec<ImportRow>,
+    /// Set while the rows are checked against the saved sessions, or saved.
+    busy: bool,
+}
+
+impl ImportFormat {
+    fn label(self) -> String {
+        match self {
+            ImportFormat::Csv => t!("import-format-csv"),
+            ImportFormat::Inventory => t!("import-format-inventory"),
+        }
+    }
+}
+
+impl NxShell {
+    pub fn import_window(&mut self, ctx: &egui::Context) {
+        let Some(mut import) = self.opts.session_import.take() else {
+            return;
+        };
+        let mut open = true;
+        let mut load = false;
+        let mut save = false;
+
+        Window::new(t!("import-title"))
+            .id(Id::new("import_window"))
+            .open(&mut open)
+            .default_size([720., 420.])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let path = TextEdit::singleline(&mut import.path)
+                        .hint_text(t!("import-path-hint"))
+                        .desired_width(320.);
+                    if ui.add(path).changed() {
+                        import.format = ImportFormat::of_path(Path::new(&import.path));
+                    }
+                    ComboBox::from_id_salt("import_format")
+                        .selected_text(import.format.label())
+                        .show_ui(ui, |ui| {
+                            for format in ImportFormat::ALL {
+                                ui.selectable_value(&mut import.format, format, format.label());
+                            }
+                        });
+                    let can_load = !import.busy && !import.path.trim().is_empty();
+                    load = ui
+                        .add_enabled(can_load, egui::Button::new(t!("import-load")))
+                        .clicked();
+                });
+                ui.weak(t!("import-columns-hint"));
+                ui.separator();
+
+                let valid = import.rows.iter().filter(|row| row.is_valid()).count();
+                ui.with_layout(Layout::bottom_up(Align::Min), |ui| {
+                    ui.horizontal(|ui| {
+                        let label = t!("import-save", count = valid);
+                        save = ui
+                            .add_enabled(!import.busy && valid > 0, egui::Button::new(label))
+                            .clicked();
+                        if !import.rows.is_empty() {
+                            let invalid = import.rows.len() - valid;
+                            ui.label(t!(
+                                "import-summary",
+                                count = import.rows.len(),
+                                invalid = invalid
+                            ));
+                        }
+                    });
+                    ui.separator();
+                    ui.with_layout(Layout::top_down(Align::Min), |ui| {
+                        preview_table(ui, &import.rows);
+                    });
+                });
+            });
+
+        if load {
+            self.load_import(&mut import);
+        }
+        if save {
+            self.save_import(&mut import);
+        }
+        if open {
+            self.opts.session_import = Some(import);
+        }
+    }
+
+    /// Read the file, then mark the hosts that are already saved. Both are
+    /// done on the db thread, the file may be large or on a slow disk.
+    fn load_import(&mut self, import: &mut SessionImport) {
+        let path = import.path.trim().to_string();
+        let format = import.format;
+        import.rows.clear();
+        import.busy = true;
+        self.db.run(
+            move |db| {
+                let text = std::fs::read_to_string(&path).map_err(|err| {
+                    error!("failed to read {path}: {err}");
+                    NxError::Plain(t!("import-error-read", error = err.to_string()))
+                })?;
+                let mut rows = import::parse(&text, format);
+                for row in rows.iter_mut().filter(|row| row.is_valid()) {
+                    if db.find_session(&row.group, &row.name)?.is_some() {
+                        row.error = Some(t!("import-error-exists"));
+                    }
+                }
+                Ok(rows)
+            },
+            |shell, rows| {
+                let Some(import) = &mut shell.opts.session_import else {
+                    return;
+                };
+                import.busy = false;
+                match rows {
+                    Ok(rows) => import.rows = rows,
+                    Err(err) => {
+                        shell.toasts.add(error_toast(err.to_string()));
+                    }
+                }
+            },
+        );
+    }
+
+    /// Save the valid rows, the invalid ones are left out.
+    fn save_import(&mut self, import: &mut SessionImport) {
+        let sessions: Vec<_> = import
+            .rows
+            .iter()
+            .filter(|row| row.is_valid())
+            .map(imported_session)
+            .collect();
+        import.busy = true;
+        self.db.run(
+            move |db| {
+                let mut imported = 0;
+                for session in sessions {
+                    // saved from another window since the preview
+                    if db.find_session(&session.group, &session.name)?.is_none() {
+                        db.insert_session(session)?;
+                        imported += 1;
+                    }
+                }
+                Ok((imported, db.find_all_sessions()?))
+            },
+            |shell, result| match result {
+                Ok((imported, sessions)) => {
+                    shell.state_manager.set_sessions(sessions);
+                    shell.opts.session_import = None;
+                    shell
+                        .toasts
+                        .add(info_toast(t!("import-done", count = imported)));
+                }
+                Err(err) => {
+                    if let Some(import) = &mut shell.opts.session_import {
+                        import.busy = false;
+                    }
+                    shell.toasts.add(error_toast(err.to_string()));
+                }
+            },
+        );
+    }
+}
+
+/// Without a saved password, it is asked on connection. Without a user, the
+/// ssh config tells both, the port of the row still applies.
+fn imported_session(row: &ImportRow) -> Session {
+    let auth_type = match row.user.is_empty() {
+        true => AuthType::Config,
+        false => AuthType::Password,
+    };
+    Session {
+        group: row.group.clone(),
+        name: row.name.clone(),
+        host: row.host.clone(),
+        port: row.port,
+        auth_type: auth_type as u16,
+        username: row.user.clone(),
+        ..Default::default()
+    }
+}
+
+fn preview_table(ui: &mut egui::Ui, rows: &[ImportRow]) {
+    TableBuilder::new(ui)
+        .striped(true)
+        .resizable(true)
+        .cell_layout(Layout::left_to_right(Align::Center))
+        .column(Column::initial(50.))
+        .column(Column::initial(90.))
+        .column(Column::initial(140.))
+        .column(Column::initial(140.))
+        .column(Column::initial(50.))
+        .column(Column::initial(80.))
+        .column(Column::remainder().at_least(120.))
+        .header(ROW_HEIGHT, |mut header| {
+            let titles = [
+                t!("import-column-line"),
+                t!("import-column-group"),
+                t!("import-column-name"),
+                t!("import-column-host"),
+                t!("import-column-port"),
+                t!("import-column-user"),
+                t!("import-column-status"),
+            ];
+            for title in titles {
+                header.col(|ui| {
+                    ui.strong(title);
+                });
+            }
+        })
+        .body(|body| {
+            body.rows(ROW_HEIGHT, rows.len(), |mut table_row| {
+                let row = &rows[table_row.index()];
+                let cells = [
+                    row.line.to_string(),
+                    row.group.clone(),
+                    row.name.clone(),
+                    row.host.clone(),
+                    row.port.to_string(),
+                    row.user.clone(),
+                ];
+                for cell in cells {
+                    table_row.col(|ui| {
+                        ui.add(egui::Label::new(cell).truncate());
+                    });
+                }
+                table_row.col(|ui| match &row.error {
+                    Some(error) => {
+                        let error = RichText::new(error).color(Color32::from_rgb(231, 130, 132));
+                        ui.add(egui::Label::new(error).truncate());
+                    }
+                    None => {
+                        ui.label(t!("import-status-ok"));
+                    }
+                });
+            });
+        });
+}
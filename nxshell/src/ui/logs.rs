@@ -0,0 +1,87 @@
+use crate::app::NxShell;
+use crate::diagnostics::LogBuffer;
+use crate::i18n::t;
+use egui::{Color32, ComboBox, Id, Label, RichText, ScrollArea, TextEdit, TextStyle, Window};
+use tracing::Level;
+
+const LEVELS: [Level; 5] = [
+    Level::ERROR,
+    Level::WARN,
+    Level::INFO,
+    Level::DEBUG,
+    Level::TRACE,
+];
+
+/// Filter of the log viewer.
+#[derive(Debug, Clone)]
+pub struct LogFilter {
+    /// Least severe level shown.
+    pub level: Level,
+    pub query: String,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            level: Level::INFO,
+            query: String::new(),
+        }
+    }
+}
+
+impl NxShell {
+    /// Recent tracing output, the console is hidden in Windows release builds.
+    pub fn logs_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.opts.show_logs;
+        let mut surrender_focus = false;
+
+        Window::new(t!("logs"))
+            .id(Id::new("logs_window"))
+            .open(&mut open)
+            .default_size([720., 400.])
+            .show(ctx, |ui| {
+                let filter = &mut self.opts.log_filter;
+                ui.horizontal(|ui| {
+                    ComboBox::from_id_salt("log_level")
+                        .selected_text(filter.level.as_str())
+                        .show_ui(ui, |ui| {
+                            for level in LEVELS {
+                                ui.selectable_value(&mut filter.level, level, level.as_str());
+                            }
+                        });
+                    let search =
+                        TextEdit::singleline(&mut filter.query).hint_text(t!("logs-search"));
+                    surrender_focus = ui.add(search).clicked();
+                    if ui.button(t!("logs-copy")).clicked() {
+                        ui.ctx().copy_text(LogBuffer::global().contents());
+                    }
+                    if ui.button(t!("logs-clear")).clicked() {
+                        LogBuffer::global().clear();
+                    }
+                });
+                ui.separator();
+
+                let lines = LogBuffer::global().filter(filter.level, &filter.query);
+                let row_height = ui.text_style_height(&TextStyle::Monospace);
+                ScrollArea::both()
+                    .auto_shrink(false)
+                    .stick_to_bottom(true)
+                    .show_rows(ui, row_height, lines.len(), |ui, rows| {
+                        for line in &lines[rows] {
+                            let text = RichText::new(&line.text).monospace();
+                            let text = match line.level {
+                                Level::ERROR => text.color(Color32::from_rgb(231, 130, 132)),
+                                Level::WARN => text.color(Color32::from_rgb(229, 200, 144)),
+                                _ => text,
+                            };
+                            ui.add(Label::new(text).extend());
+                        }
+                    });
+            });
+
+        self.opts.show_logs = open;
+        if surrender_focus {
+            self.opts.surrender_focus();
+        }
+    }
+}
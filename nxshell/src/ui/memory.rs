@@ -0,0 +1,52 @@
+use crate::app::NxShell;
+use crate::errors::info_toast;
+use crate::i18n::t;
+use std::time::{Duration, Instant};
+
+const MEMORY_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Lines of history left to the trimmed terminals.
+const TRIMMED_HISTORY_LINES: usize = 1000;
+
+impl NxShell {
+    /// Keep the grids of the terminals within the memory budget, trimming the
+    /// history of the least recently shown tabs first.
+    pub fn enforce_memory_budget(&mut self) {
+        if !self.opts.memory_budget
+            || self.runtime.memory_checked_at.elapsed() < MEMORY_CHECK_INTERVAL
+        {
+            return;
+        }
+        self.runtime.memory_checked_at = Instant::now();
+
+        let budget = self.opts.memory_budget_mb as usize * 1024 * 1024;
+        let mut total: usize = self
+            .dock_state
+            .iter_all_tabs()
+            .map(|(_, tab)| tab.grid_memory())
+            .sum();
+        if total <= budget {
+            return;
+        }
+        let mut tabs: Vec<_> = self
+            .dock_state
+            .iter_all_tabs_mut()
+            .map(|(_, tab)| tab)
+            .collect();
+        tabs.sort_by_key(|tab| tab.last_active());
+        let mut trimmed = 0;
+        for tab in tabs {
+            if total <= budget {
+                break;
+            }
+            let memory = tab.grid_memory();
+            if tab.trim_history(TRIMMED_HISTORY_LINES) > 0 {
+                total -= memory.saturating_sub(tab.grid_memory());
+                trimmed += 1;
+            }
+        }
+        if trimmed > 0 {
+            self.toasts
+                .add(info_toast(t!("memory-trimmed", count = trimmed)));
+        }
+    }
+}
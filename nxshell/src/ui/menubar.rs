@@ -1,17 +1,31 @@
-use crate::app::NxShell;
-use crate::consts::{REPOSITORY_URL, SHOW_DOCK_PANEL_ONCE};
+use crate::app::{NxShell, BLOCK_SELECTION_CHOICES};
+use crate::bell::BellMode;
+use crate::consts::{
+    FIND_SHORTCUT, OVERVIEW_SHORTCUT, PRESENTATION_SHORTCUT, PRIVACY_BLUR_SHORTCUT, REPOSITORY_URL,
+    SHOW_DOCK_PANEL_ONCE, SPLIT_DOWN_SHORTCUT, SPLIT_RIGHT_SHORTCUT,
+};
 use crate::db::Session;
-use crate::errors::NxError;
-use crate::ui::tab_view::Tab;
-use egui::{Button, Checkbox, MenuBar, Modifiers};
+use crate::diagnostics;
+use crate::errors::{error_toast, info_toast, NxError};
+use crate::i18n::{self, t, Language};
+use crate::ui::import::SessionImport;
+use crate::ui::tab_view::{SplitDirection, Tab};
+use crate::ui::theme_editor::{color_filter_label, ThemeEditor};
+use crate::window::StartupWindow;
+use egui::{Button, Checkbox, DragValue, Grid, MenuBar, Modifiers};
 use egui_dock::DockState;
-use egui_term::{Authentication, SshOptions, TermType};
-use orion::aead::{open as orion_open, SecretKey};
+use egui_term::{
+    Authentication, BackendCommand, ColorFilter, HostKeyCheck, SshOptions, TermType,
+    CELL_PADDING_RANGE, LINE_HEIGHT_RANGE,
+};
 use std::env;
 use std::process::Command;
 use tracing::error;
+use wezterm_ssh::Utf8PathBuf;
 
-use super::form::AuthType;
+use super::form::{
+    open_secret, parse_forwards, remote_locale, transport_options, AuthType, Compression,
+};
 
 const BTN_WIDTH: f32 = 200.0;
 
@@ -21,11 +35,11 @@ impl NxShell {
             // Session
             self.session_menu(ui);
             // Window
-            window_menu(ui);
+            self.window_menu(ui);
             // Tools
             self.tools_menu(ui);
             // Help
-            help_menu(ui);
+            self.help_menu(ui);
         });
     }
 
@@ -39,14 +53,15 @@ impl NxShell {
                 },
             );
         }
-        ui.menu_button("Session", |ui| {
-            let new_session_btn = Button::new("New Session").min_size((BTN_WIDTH, 0.).into());
+        ui.menu_button(t!("menu-session"), |ui| {
+            let new_session_btn =
+                Button::new(t!("menu-new-session")).min_size((BTN_WIDTH, 0.).into());
             if ui.add(new_session_btn).clicked() {
                 *self.opts.show_add_session_modal.borrow_mut() = true;
                 ui.close();
             }
             let new_term_shortcut = ui.ctx().format_shortcut(&new_term_shortcut);
-            let new_term_btn = Button::new("New Terminal")
+            let new_term_btn = Button::new(t!("menu-new-terminal"))
                 .min_size((BTN_WIDTH, 0.).into())
                 .shortcut_text(new_term_shortcut);
             if ui.add(new_term_btn).clicked() {
@@ -58,16 +73,386 @@ impl NxShell {
                 );
                 ui.close();
             }
+            let import_btn =
+                Button::new(t!("menu-import-sessions")).min_size((BTN_WIDTH, 0.).into());
+            if ui.add(import_btn).clicked() {
+                self.opts
+                    .session_import
+                    .get_or_insert_with(SessionImport::default);
+                ui.close();
+            }
+            let find_btn = Button::new(t!("menu-find"))
+                .min_size((BTN_WIDTH, 0.).into())
+                .shortcut_text(ui.ctx().format_shortcut(&FIND_SHORTCUT));
+            if ui.add(find_btn).clicked() {
+                self.search_active_terminal(BackendCommand::SearchStart(None));
+                ui.close();
+            }
+            for (label, shortcut, direction) in [
+                (
+                    t!("menu-split-right"),
+                    SPLIT_RIGHT_SHORTCUT,
+                    SplitDirection::Right,
+                ),
+                (
+                    t!("menu-split-down"),
+                    SPLIT_DOWN_SHORTCUT,
+                    SplitDirection::Down,
+                ),
+            ] {
+                let split_btn = Button::new(label)
+                    .min_size((BTN_WIDTH, 0.).into())
+                    .shortcut_text(ui.ctx().format_shortcut(&shortcut));
+                if ui.add(split_btn).clicked() {
+                    self.split_active_tab(ui.ctx(), direction);
+                    ui.close();
+                }
+            }
             ui.separator();
-            if ui.button("Quit").clicked() {
-                std::process::exit(0);
+            if ui.button(t!("menu-quit")).clicked() {
+                self.request_quit(ui.ctx());
+                ui.close();
             }
         });
     }
 
     fn tools_menu(&mut self, ui: &mut egui::Ui) {
-        ui.menu_button("Tools", |ui| {
-            ui.add(Checkbox::new(&mut self.opts.multi_exec, "Multi Exec"));
+        ui.menu_button(t!("menu-tools"), |ui| {
+            ui.add(Checkbox::new(
+                &mut self.opts.multi_exec,
+                t!("menu-multi-exec"),
+            ));
+            ui.checkbox(&mut self.opts.exit_codes, t!("menu-exit-codes"))
+                .on_hover_text(t!("menu-exit-codes-hint"));
+            ui.checkbox(
+                &mut self.opts.flash_failed_tabs,
+                t!("menu-flash-failed-tabs"),
+            )
+            .on_hover_text(t!("menu-flash-failed-tabs-hint"));
+            ui.checkbox(&mut self.opts.keep_exited_tabs, t!("menu-keep-exited-tabs"))
+                .on_hover_text(t!("menu-keep-exited-tabs-hint"));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.opts.show_durations, t!("menu-durations"))
+                    .on_hover_text(t!("menu-durations-hint"));
+                ui.add_enabled(
+                    self.opts.show_durations,
+                    DragValue::new(&mut self.opts.duration_threshold)
+                        .range(0.0..=3600.0)
+                        .speed(0.5)
+                        .suffix(" s"),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label(t!("menu-scroll-speed"))
+                    .on_hover_text(t!("menu-scroll-speed-hint"));
+                ui.add(
+                    DragValue::new(&mut self.opts.scroll_multiplier)
+                        .range(0.25..=20.0)
+                        .speed(0.05),
+                );
+            });
+            ui.checkbox(&mut self.opts.natural_scroll, t!("menu-natural-scroll"))
+                .on_hover_text(t!("menu-natural-scroll-hint"));
+            ui.checkbox(&mut self.opts.flow_control, t!("menu-flow-control"))
+                .on_hover_text(t!("menu-flow-control-hint"));
+            ui.checkbox(&mut self.opts.record_commands, t!("menu-record-commands"))
+                .on_hover_text(t!("menu-record-commands-hint"));
+            ui.checkbox(&mut self.opts.smooth_cursor, t!("menu-smooth-cursor"))
+                .on_hover_text(t!("menu-smooth-cursor-hint"));
+            ui.checkbox(&mut self.opts.dim_inactive, t!("menu-dim-inactive"))
+                .on_hover_text(t!("menu-dim-inactive-hint"));
+            ui.checkbox(
+                &mut self.opts.focus_follows_mouse,
+                t!("menu-focus-follows-mouse"),
+            )
+            .on_hover_text(t!("menu-focus-follows-mouse-hint"));
+            ui.menu_button(t!("menu-block-selection"), |ui| {
+                for (modifiers, name) in BLOCK_SELECTION_CHOICES {
+                    let label = match name {
+                        "ctrl_alt" => t!("block-selection-ctrl-alt"),
+                        "shift_alt" => t!("block-selection-shift-alt"),
+                        _ => t!("block-selection-alt"),
+                    };
+                    let block_selection = &mut self.opts.block_selection;
+                    if ui.radio_value(block_selection, modifiers, label).clicked() {
+                        ui.close();
+                    }
+                }
+            })
+            .response
+            .on_hover_text(t!("menu-block-selection-hint"));
+            ui.menu_button(t!("menu-bell"), |ui| {
+                for mode in BellMode::ALL {
+                    if ui
+                        .radio_value(&mut self.opts.bell, mode, mode.label())
+                        .clicked()
+                    {
+                        ui.close();
+                    }
+                }
+            })
+            .response
+            .on_hover_text(t!("menu-bell-hint"));
+            ui.checkbox(&mut self.opts.show_profiler, t!("menu-profiler"))
+                .on_hover_text(t!("menu-profiler-hint"));
+            ui.horizontal(|ui| {
+                ui.label(t!("menu-scrollback-lines"))
+                    .on_hover_text(t!("menu-scrollback-lines-hint"));
+                ui.add(
+                    DragValue::new(&mut self.opts.scrollback_lines)
+                        .range(1000..=1_000_000)
+                        .speed(100),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.opts.memory_budget, t!("menu-memory-budget"))
+                    .on_hover_text(t!("menu-memory-budget-hint"));
+                ui.add_enabled(
+                    self.opts.memory_budget,
+                    DragValue::new(&mut self.opts.memory_budget_mb)
+                        .range(64..=65536)
+                        .speed(16)
+                        .suffix(" MB"),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.opts.idle_timeout, t!("menu-idle-timeout"))
+                    .on_hover_text(t!("menu-idle-timeout-hint"));
+                ui.add_enabled(
+                    self.opts.idle_timeout,
+                    DragValue::new(&mut self.opts.idle_timeout_min)
+                        .range(2..=1440)
+                        .suffix(" min"),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.opts.host_badges, t!("menu-host-badges"))
+                    .on_hover_text(t!("menu-host-badges-hint"));
+                ui.add_enabled(
+                    self.opts.host_badges,
+                    DragValue::new(&mut self.opts.host_probe_interval)
+                        .range(10..=3600)
+                        .suffix(" s"),
+                );
+                ui.add_enabled(
+                    self.opts.host_badges,
+                    DragValue::new(&mut self.opts.host_probe_concurrency).range(1..=64),
+                )
+                .on_hover_text(t!("menu-host-probe-concurrency"));
+            });
+            ui.horizontal(|ui| {
+                ui.label(t!("menu-presentation-font-scale"));
+                ui.add(
+                    DragValue::new(&mut self.opts.presentation_font_scale)
+                        .range(1.0..=4.0)
+                        .speed(0.05),
+                );
+            });
+            ui.menu_button(t!("menu-terminal-font"), |ui| {
+                self.terminal_font_menu(ui);
+            });
+            if ui.button(t!("menu-theme-editor")).clicked() {
+                self.opts
+                    .theme_editor
+                    .get_or_insert_with(ThemeEditor::default);
+                ui.close();
+            }
+            ui.menu_button(t!("menu-color-filter"), |ui| {
+                let theme = &mut self.opts.term_theme;
+                for color_filter in ColorFilter::ALL {
+                    let selected = theme.color_filter() == color_filter;
+                    if ui
+                        .radio(selected, color_filter_label(color_filter))
+                        .clicked()
+                    {
+                        theme.set_color_filter(color_filter);
+                        self.opts.term_theme_revision += 1;
+                        ui.close();
+                    }
+                }
+            })
+            .response
+            .on_hover_text(t!("menu-color-filter-hint"));
+            let theme = &mut self.opts.term_theme;
+            let mut bold_as_bright = theme.bold_as_bright();
+            if ui
+                .checkbox(&mut bold_as_bright, t!("menu-bold-as-bright"))
+                .on_hover_text(t!("menu-bold-as-bright-hint"))
+                .changed()
+            {
+                theme.set_bold_as_bright(bold_as_bright);
+                self.opts.term_theme_revision += 1;
+            }
+            let theme = &mut self.opts.term_theme;
+            let mut bright_as_bold = theme.bright_as_bold();
+            if ui
+                .checkbox(&mut bright_as_bold, t!("menu-bright-as-bold"))
+                .on_hover_text(t!("menu-bright-as-bold-hint"))
+                .changed()
+            {
+                theme.set_bright_as_bold(bright_as_bold);
+                self.opts.term_theme_revision += 1;
+            }
+            ui.menu_button(t!("menu-api"), |ui| {
+                self.api_menu(ui);
+            });
+            ui.menu_button(t!("menu-output-log"), |ui| {
+                self.output_log_menu(ui);
+            });
+            ui.menu_button(t!("menu-export"), |ui| {
+                self.export_menu(ui);
+            });
+            ui.menu_button(t!("menu-redaction"), |ui| {
+                self.redaction_menu(ui);
+            });
+            ui.separator();
+            ui.menu_button(t!("menu-language"), |ui| {
+                for language in Language::ALL {
+                    let selected = i18n::language() == language;
+                    if ui.radio(selected, language.name()).clicked() {
+                        self.set_language(language);
+                        ui.close();
+                    }
+                }
+            });
+        });
+    }
+
+    /// Default font of the terminals, sessions can override it.
+    fn terminal_font_menu(&mut self, ui: &mut egui::Ui) {
+        Grid::new("terminal_font_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label(t!("font-family"));
+                let family = &mut self.opts.term_font_family;
+                self.fonts.family_picker(ui, "terminal_font_family", family);
+                ui.end_row();
+
+                ui.label(t!("font-size"));
+                let size = &mut self.opts.term_font_size;
+                if ui.add(DragValue::new(size).range(6.0..=72.0)).changed() {
+                    *self.opts.term_font.font_size_mut() = *size;
+                }
+                ui.end_row();
+
+                let font = &mut self.opts.term_font;
+                ui.label(t!("font-line-height"));
+                let mut line_height = font.line_height();
+                let line_height_value = DragValue::new(&mut line_height)
+                    .range(LINE_HEIGHT_RANGE)
+                    .speed(0.01)
+                    .fixed_decimals(2);
+                if ui.add(line_height_value).changed() {
+                    font.set_line_height(line_height);
+                }
+                ui.end_row();
+
+                ui.label(t!("font-cell-padding"));
+                let mut cell_padding = font.cell_padding();
+                let cell_padding_value = DragValue::new(&mut cell_padding)
+                    .range(CELL_PADDING_RANGE)
+                    .speed(0.1)
+                    .fixed_decimals(1);
+                if ui
+                    .add(cell_padding_value)
+                    .on_hover_text(t!("font-cell-padding-hint"))
+                    .changed()
+                {
+                    font.set_cell_padding(cell_padding);
+                }
+                ui.end_row();
+            });
+    }
+
+    /// Relabel the user interface, the choice is saved with the app state.
+    fn set_language(&mut self, language: Language) {
+        i18n::set_language(language);
+        self.opts.term_labels = i18n::terminal_labels();
+    }
+
+    fn help_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button(t!("menu-help"), |ui| {
+            let about_btn = Button::new(t!("menu-about")).min_size((BTN_WIDTH, 0.).into());
+            if ui.add(about_btn).clicked() {
+                if let Err(err) = open::that(REPOSITORY_URL) {
+                    error!("opening page {REPOSITORY_URL} error: {err}");
+                }
+            }
+            let logs_btn = Button::new(t!("menu-logs")).min_size((BTN_WIDTH, 0.).into());
+            if ui.add(logs_btn).clicked() {
+                self.opts.show_logs = true;
+                ui.close();
+            }
+            let export_btn =
+                Button::new(t!("menu-export-diagnostics")).min_size((BTN_WIDTH, 0.).into());
+            if ui
+                .add(export_btn)
+                .on_hover_text(t!("menu-export-diagnostics-hint"))
+                .clicked()
+            {
+                self.export_diagnostics();
+                ui.close();
+            }
+        });
+    }
+
+    fn export_diagnostics(&mut self) {
+        match diagnostics::export(&self.data_dir) {
+            Ok(path) => {
+                let path = path.display().to_string();
+                self.toasts
+                    .add(info_toast(t!("diagnostics-saved", path = path)));
+                if let Err(err) = open::that(&self.data_dir) {
+                    error!("opening {} error: {err}", self.data_dir.display());
+                }
+            }
+            Err(err) => {
+                error!("failed to export the diagnostics: {err}");
+                self.toasts.add(error_toast(err.to_string()));
+            }
+        }
+    }
+
+    fn window_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button(t!("menu-window"), |ui| {
+            new_window_button(ui);
+            let shortcut = ui.ctx().format_shortcut(&PRESENTATION_SHORTCUT);
+            let presentation_btn = Button::new(t!("menu-presentation-mode"))
+                .min_size((BTN_WIDTH, 0.).into())
+                .shortcut_text(shortcut);
+            if ui.add(presentation_btn).clicked() {
+                self.toggle_presentation(ui.ctx());
+                ui.close();
+            }
+            let shortcut = ui.ctx().format_shortcut(&PRIVACY_BLUR_SHORTCUT);
+            let privacy_btn = Button::selectable(self.opts.privacy_blur, t!("menu-privacy-blur"))
+                .min_size((BTN_WIDTH, 0.).into())
+                .shortcut_text(shortcut);
+            if ui.add(privacy_btn).clicked() {
+                self.toggle_privacy_blur(ui.ctx());
+                ui.close();
+            }
+            let shortcut = ui.ctx().format_shortcut(&OVERVIEW_SHORTCUT);
+            let overview_btn = Button::new(t!("menu-tab-overview"))
+                .min_size((BTN_WIDTH, 0.).into())
+                .shortcut_text(shortcut);
+            if ui.add(overview_btn).clicked() {
+                self.open_overview(false);
+                ui.close();
+            }
+            ui.separator();
+            ui.menu_button(t!("menu-startup-window"), |ui| {
+                for startup in StartupWindow::ALL {
+                    let label = match startup {
+                        StartupWindow::Restore => t!("startup-window-restore"),
+                        StartupWindow::Maximized => t!("startup-window-maximized"),
+                    };
+                    let startup_window = &mut self.window.startup;
+                    if ui.radio_value(startup_window, startup, label).clicked() {
+                        ui.close();
+                    }
+                }
+            });
         });
     }
 }
@@ -80,7 +465,8 @@ impl NxShell {
         SHOW_DOCK_PANEL_ONCE.call_once(|| {
             self.opts.show_dock_panel = true;
         });
-        match Tab::term(ctx, typ, self.command_sender.clone()) {
+        let config = self.opts.terminal_config();
+        match Tab::term(ctx, typ, config, self.command_sender.clone()) {
             Ok(tab) => {
                 self.dock_state.push_to_focused_leaf(tab);
                 Ok(())
@@ -97,29 +483,48 @@ impl NxShell {
         ctx: &egui::Context,
         session: Session,
     ) -> Result<(), NxError> {
-        let auth = match AuthType::from(session.auth_type) {
-            AuthType::Password => {
-                let key = SecretKey::from_slice(&session.secret_key)?;
-                let auth_data = orion_open(&key, &session.secret_data)?;
-                let auth_data = String::from_utf8(auth_data)?;
-
-                Authentication::Password(session.username, auth_data)
-            }
-            AuthType::Config => Authentication::Config,
+        let key = (session.group.clone(), session.name.clone());
+        let options = ssh_options(session)?;
+        let typ = TermType::Ssh {
+            options: Box::new(options),
+            working_directory: None,
         };
+        self.add_shell_tab(ctx.clone(), typ)?;
+        self.record_session_open(key);
+        Ok(())
+    }
 
-        self.add_shell_tab(
-            ctx.clone(),
-            TermType::Ssh {
-                options: SshOptions {
-                    group: session.group,
-                    name: session.name,
-                    host: session.host,
-                    port: Some(session.port),
-                    auth,
-                },
-            },
-        )
+    pub fn add_sftp_tab_with_secret(
+        &mut self,
+        ctx: &egui::Context,
+        session: Session,
+    ) -> Result<(), NxError> {
+        self.add_sftp_tab(ctx.clone(), ssh_options(session)?, None)
+    }
+
+    /// Open a sftp explorer, at `start_dir` or at the remote home directory.
+    pub fn add_sftp_tab(
+        &mut self,
+        ctx: egui::Context,
+        options: SshOptions,
+        start_dir: Option<Utf8PathBuf>,
+    ) -> Result<(), NxError> {
+        if self.dock_state.surfaces_count() == 0 {
+            self.dock_state = DockState::new(vec![]);
+        }
+        SHOW_DOCK_PANEL_ONCE.call_once(|| {
+            self.opts.show_dock_panel = true;
+        });
+        match Tab::sftp(ctx, options, start_dir) {
+            Ok(tab) => {
+                self.dock_state.push_to_focused_leaf(tab);
+                Ok(())
+            }
+            Err(err) => {
+                error!("add sftp session error: {err}");
+                Err(NxError::Plain(err.to_string()))
+            }
+        }
     }
 
     pub fn add_sessions_tab(&mut self) {
@@ -133,51 +538,73 @@ impl NxShell {
     }
 }
 
-fn window_menu(ui: &mut egui::Ui) {
-    ui.menu_button("Window", |ui| {
-        let new_window_btn = Button::new("New Window").min_size((BTN_WIDTH, 0.).into());
-        if ui.add(new_window_btn).clicked() {
-            match env::current_exe() {
-                Ok(path) => {
-                    let mut child = Command::new(path);
+pub(crate) fn ssh_options(session: Session) -> Result<SshOptions, NxError> {
+    // imported sessions have no password, it is asked on connection
+    let auth_data = match session.secret_data.is_empty() {
+        true => String::new(),
+        false => open_secret(&session.secret_key, &session.secret_data)?,
+    };
+    let auth = match AuthType::from(session.auth_type) {
+        AuthType::Password => Authentication::Password(session.username, auth_data),
+        AuthType::PublicKey => Authentication::PublicKey {
+            user: session.username,
+            path: session.identity_file,
+            passphrase: auth_data,
+        },
+        AuthType::Config => Authentication::Config,
+    };
 
-                    #[cfg(windows)]
-                    {
-                        use std::os::windows::process::CommandExt;
-                        use windows::Win32::System::Threading::CREATE_NEW_PROCESS_GROUP;
+    let transport = transport_options(
+        Compression::from(session.compression),
+        &session.ciphers,
+        &session.kex_algorithms,
+    );
+    let fingerprint = Some(session.host_fingerprint).filter(|fingerprint| !fingerprint.is_empty());
+    Ok(SshOptions {
+        group: session.group,
+        name: session.name,
+        host: session.host,
+        port: Some(session.port),
+        auth,
+        host_key: HostKeyCheck::Pinned(fingerprint),
+        transport,
+        locale: remote_locale(&session.locale),
+        forwards: parse_forwards(&session.forwards)?,
+    })
+}
 
-                        child.creation_flags(CREATE_NEW_PROCESS_GROUP.0 as u32);
-                    }
+fn new_window_button(ui: &mut egui::Ui) {
+    let new_window_btn = Button::new(t!("menu-new-window")).min_size((BTN_WIDTH, 0.).into());
+    if ui.add(new_window_btn).clicked() {
+        match env::current_exe() {
+            Ok(path) => {
+                let mut child = Command::new(path);
 
-                    #[cfg(unix)]
-                    {
-                        use std::os::unix::prelude::CommandExt;
-                        unsafe {
-                            child.pre_exec(|| {
-                                let _ = rustix::process::setsid();
-                                Ok(())
-                            });
-                        }
-                    }
+                #[cfg(windows)]
+                {
+                    use std::os::windows::process::CommandExt;
+                    use windows::Win32::System::Threading::CREATE_NEW_PROCESS_GROUP;
 
-                    if let Err(err) = child.spawn() {
-                        error!("failed to launch new window: {err}");
+                    child.creation_flags(CREATE_NEW_PROCESS_GROUP.0 as u32);
+                }
+
+                #[cfg(unix)]
+                {
+                    use std::os::unix::prelude::CommandExt;
+                    unsafe {
+                        child.pre_exec(|| {
+                            let _ = rustix::process::setsid();
+                            Ok(())
+                        });
                     }
                 }
-                Err(err) => error!("failed to get current exe path: {err}"),
-            }
-            ui.close();
-        }
-    });
-}
 
-fn help_menu(ui: &mut egui::Ui) {
-    ui.menu_button("Help", |ui| {
-        let about_btn = Button::new("About").min_size((BTN_WIDTH, 0.).into());
-        if ui.add(about_btn).clicked() {
-            if let Err(err) = open::that(REPOSITORY_URL) {
-                error!("opening page {REPOSITORY_URL} error: {err}");
+                if let Err(err) = child.spawn() {
+                    error!("failed to launch new window: {err}");
+                }
             }
+            Err(err) => error!("failed to get current exe path: {err}"),
         }
-    });
+        ui.close();
+    }
 }
@@ -1,3 +1,23 @@
+pub mod api;
+pub mod auth_prompt;
+pub mod connect_error;
+pub mod diagnose;
+pub mod export;
 pub mod form;
+pub mod host_key;
+pub mod idle;
+pub mod import;
+pub mod logs;
+pub mod memory;
 pub mod menubar;
+pub mod output_log;
+pub mod overview;
+pub mod profiler;
+pub mod quit;
+pub mod redaction;
+pub mod sessions;
+pub mod status_bar;
 pub mod tab_view;
+pub mod theme_editor;
+pub mod wake;
+pub mod workspace;
@@ -0,0 +1,114 @@
+use crate::app::NxShell;
+use crate::errors::{error_toast, info_toast};
+use crate::i18n::t;
+use chrono::Local;
+use egui::RichText;
+use tracing::error;
+
+/// Directory of the output logs, in the data directory.
+const OUTPUT_LOG_DIR: &str = "session-logs";
+
+impl NxShell {
+    /// Log the output of the active terminal to a file.
+    pub fn output_log_menu(&mut self, ui: &mut egui::Ui) {
+        let active = self.dock_state.find_active_focused().map(|(_, tab)| {
+            let name = tab.session_key().map(|(_, name)| name);
+            let log_path = tab.terminal().map(|terminal| terminal.log_path());
+            (name, log_path)
+        });
+        match active {
+            Some((_, Some(Some(path)))) => {
+                ui.label(RichText::new(path.display().to_string()).weak());
+                if ui.button(t!("output-log-stop")).clicked() {
+                    self.stop_output_log();
+                    ui.close();
+                }
+            }
+            Some((name, Some(None))) => {
+                if ui.button(t!("output-log-start")).clicked() {
+                    self.start_output_log(name.as_deref().unwrap_or("local"));
+                    ui.close();
+                }
+            }
+            _ => {
+                ui.add_enabled(false, egui::Button::new(t!("output-log-start")))
+                    .on_disabled_hover_text(t!("output-log-no-terminal"));
+            }
+        }
+        ui.separator();
+        ui.checkbox(
+            &mut self.opts.collapse_progress,
+            t!("output-log-collapse-progress"),
+        )
+        .on_hover_text(t!("output-log-collapse-progress-hint"));
+    }
+
+    fn start_output_log(&mut self, name: &str) {
+        let dir = self.data_dir.join(OUTPUT_LOG_DIR);
+        let path = dir.join(file_name(name, "log"));
+        let collapse_progress = self.opts.collapse_progress;
+        let redaction = self.opts.redaction.clone();
+        let Some((_, tab)) = self.dock_state.find_active_focused() else {
+            return;
+        };
+        let Some(terminal) = tab.terminal() else {
+            return;
+        };
+        let started = std::fs::create_dir_all(&dir)
+            .and_then(|_| terminal.start_log(&path, collapse_progress, redaction));
+        match started {
+            Ok(()) => {
+                let path = path.display().to_string();
+                self.toasts
+                    .add(info_toast(t!("output-log-started", path = path)));
+            }
+            Err(err) => {
+                error!("failed to log to {}: {err}", path.display());
+                self.toasts
+                    .add(error_toast(t!("output-log-error", error = err.to_string())));
+            }
+        }
+    }
+
+    fn stop_output_log(&mut self) {
+        let stopped = self
+            .dock_state
+            .find_active_focused()
+            .and_then(|(_, tab)| tab.terminal()?.stop_log());
+        if let Some(path) = stopped {
+            let path = path.display().to_string();
+            self.toasts
+                .add(info_toast(t!("output-log-stopped", path = path)));
+        }
+    }
+}
+
+/// `{name}-{time}.{extension}`, with the characters which aren't allowed in
+/// file names replaced.
+pub(crate) fn file_name(name: &str, extension: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    format!(
+        "{}-{}.{extension}",
+        name.trim(),
+        Local::now().format("%Y%m%d-%H%M%S")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_name_is_safe() {
+        let name = file_name("web: prod/eu", "log");
+        assert!(name.starts_with("web_ prod_eu-"), "{name}");
+        assert!(name.ends_with(".log"));
+    }
+}
@@ -0,0 +1,140 @@
+use crate::app::NxShell;
+use crate::i18n::t;
+use egui::{Align2, Id, Modal, Sense, Stroke, StrokeKind, TextStyle, Vec2};
+use egui_term::TerminalThumbnail;
+use std::time::{Duration, Instant};
+
+/// Refresh rate of the miniatures, the terminals keep running under them.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+const THUMBNAIL_SIZE: Vec2 = Vec2::new(240., 140.);
+
+/// Grid of miniatures of the terminal tabs, to switch between them.
+#[derive(Debug, Clone, Default)]
+pub struct TabOverview {
+    /// Opened by the shortcut, the selected tab is activated once its
+    /// modifiers are released, like a window switcher.
+    held: bool,
+    selected: usize,
+    /// Id, title and miniature of the terminal tabs.
+    thumbnails: Vec<(u64, String, TerminalThumbnail)>,
+    refreshed: Option<Instant>,
+}
+
+impl NxShell {
+    /// Open the overview, or select the next tab if the shortcut is pressed
+    /// again while it is held.
+    pub fn open_overview(&mut self, held: bool) {
+        match &mut self.opts.overview {
+            Some(overview) if overview.held => {
+                overview.selected = (overview.selected + 1) % overview.thumbnails.len().max(1);
+            }
+            _ => {
+                let mut overview = TabOverview {
+                    held,
+                    ..Default::default()
+                };
+                self.refresh_thumbnails(&mut overview);
+                let active = self
+                    .dock_state
+                    .find_active_focused()
+                    .map(|(_, tab)| tab.id())
+                    .and_then(|id| overview.thumbnails.iter().position(|(tab, ..)| *tab == id));
+                overview.selected = match active {
+                    // the previous tab is one press away
+                    Some(active) if held => (active + 1) % overview.thumbnails.len(),
+                    active => active.unwrap_or_default(),
+                };
+                self.opts.overview = Some(overview);
+            }
+        }
+    }
+
+    fn refresh_thumbnails(&self, overview: &mut TabOverview) {
+        overview.thumbnails = self
+            .dock_state
+            .iter_all_tabs()
+            .filter_map(|(_, tab)| Some((tab.id(), tab.title(), tab.thumbnail()?)))
+            .collect();
+        overview.selected = overview
+            .selected
+            .min(overview.thumbnails.len().saturating_sub(1));
+        overview.refreshed = Some(Instant::now());
+    }
+
+    pub fn tab_overview(&mut self, ctx: &egui::Context) {
+        let Some(mut overview) = self.opts.overview.take() else {
+            return;
+        };
+        if overview
+            .refreshed
+            .is_none_or(|refreshed| refreshed.elapsed() >= REFRESH_INTERVAL)
+        {
+            self.refresh_thumbnails(&mut overview);
+        }
+        ctx.request_repaint_after(REFRESH_INTERVAL);
+
+        let mut activated = None;
+        let released = overview.held && !ctx.input(|i| i.modifiers.command);
+        if released {
+            activated = overview
+                .thumbnails
+                .get(overview.selected)
+                .map(|(id, ..)| *id);
+        }
+
+        let max_width = ctx.screen_rect().width() * 0.8;
+        let modal = Modal::new(Id::new("tab_overview")).show(ctx, |ui| {
+            ui.set_max_width(max_width);
+            ui.heading(t!("overview-title"));
+            if overview.thumbnails.is_empty() {
+                ui.weak(t!("overview-empty"));
+            }
+            ui.horizontal_wrapped(|ui| {
+                for (index, (id, title, thumbnail)) in overview.thumbnails.iter().enumerate() {
+                    let font_id = TextStyle::Body.resolve(ui.style());
+                    let title_height = ui.fonts(|f| f.row_height(&font_id)) + 4.;
+                    let size = THUMBNAIL_SIZE + Vec2::new(0., title_height);
+                    let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+                    let painter = ui.painter_at(rect);
+
+                    let preview = rect.with_max_y(rect.max.y - title_height);
+                    thumbnail.paint(&painter, preview);
+                    let visuals = ui.visuals();
+                    painter.text(
+                        rect.center_bottom(),
+                        Align2::CENTER_BOTTOM,
+                        title,
+                        font_id,
+                        visuals.text_color(),
+                    );
+                    let stroke = if index == overview.selected {
+                        visuals.selection.stroke
+                    } else if response.hovered() {
+                        visuals.widgets.hovered.bg_stroke
+                    } else {
+                        Stroke::new(1., visuals.weak_text_color())
+                    };
+                    painter.rect_stroke(preview, 2., stroke, StrokeKind::Inside);
+
+                    if response.hovered() {
+                        overview.selected = index;
+                    }
+                    if response.clicked() {
+                        activated = Some(*id);
+                    }
+                }
+            });
+        });
+
+        if let Some(id) = activated {
+            if let Some((surface, node, tab)) = self.dock_state.find_tab_from(|tab| tab.id() == id)
+            {
+                self.dock_state.set_active_tab((surface, node, tab));
+                self.dock_state
+                    .set_focused_node_and_surface((surface, node));
+            }
+        } else if !released && !modal.should_close() {
+            self.opts.overview = Some(overview);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::app::NxShell;
+use crate::i18n::t;
+use egui::{Grid, Id, RichText, Window};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Window the throughput of the terminals is averaged over.
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(2);
+/// The profiler refreshes at least this often while nothing else repaints.
+const PROFILER_REFRESH: Duration = Duration::from_millis(500);
+
+/// Bytes per second read from the pty of a terminal.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Throughput {
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl Throughput {
+    /// Record the bytes read so far, see [`egui_term::Terminal::read_bytes`].
+    pub fn sample(&mut self, now: Instant, bytes: u64) {
+        self.samples.push_back((now, bytes));
+        while self
+            .samples
+            .front()
+            .is_some_and(|(time, _)| now.duration_since(*time) > THROUGHPUT_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn bytes_per_sec(&self) -> f64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some((start, start_bytes)), Some((end, end_bytes))) if end > start => {
+                (end_bytes - start_bytes) as f64 / end.duration_since(*start).as_secs_f64()
+            }
+            _ => 0.,
+        }
+    }
+}
+
+impl NxShell {
+    /// Frame time and render cost of each terminal, to report performance
+    /// issues. `cpu_usage` is the time taken by the previous frame.
+    pub fn profiler_window(&mut self, ctx: &egui::Context, cpu_usage: Option<f32>) {
+        let mut open = self.opts.show_profiler;
+        let frame_interval = ctx.input(|i| i.unstable_dt);
+        let now = Instant::now();
+
+        Window::new(t!("profiler"))
+            .id(Id::new("profiler_window"))
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                Grid::new("profiler_frame").num_columns(2).show(ui, |ui| {
+                    ui.label(t!("profiler-frame-time"));
+                    let cpu_usage = cpu_usage.unwrap_or_default();
+                    ui.monospace(format!("{:.2} ms", cpu_usage * 1000.));
+                    ui.end_row();
+                    ui.label(t!("profiler-frame-interval"));
+                    ui.monospace(format!("{:.2} ms", frame_interval * 1000.));
+                    ui.end_row();
+                });
+                ui.separator();
+
+                Grid::new("profiler_tabs")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong(t!("profiler-tab"));
+                        ui.strong(t!("profiler-shapes"));
+                        ui.strong(t!("profiler-throughput"));
+                        ui.end_row();
+                        for (_, tab) in self.dock_state.iter_all_tabs_mut() {
+                            let title = tab.title();
+                            let Some((shapes, bytes_per_sec)) = tab.render_stats(now) else {
+                                continue;
+                            };
+                            ui.label(title);
+                            ui.monospace(shapes.to_string());
+                            ui.monospace(format!("{:.1} KB/s", bytes_per_sec / 1024.));
+                            ui.end_row();
+                        }
+                    });
+                ui.label(RichText::new(t!("profiler-hint")).weak());
+            });
+        self.opts.show_profiler = open;
+        ctx.request_repaint_after(PROFILER_REFRESH);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throughput_over_window() {
+        let mut throughput = Throughput::default();
+        let start = Instant::now();
+        throughput.sample(start, 100);
+        assert_eq!(throughput.bytes_per_sec(), 0.);
+        throughput.sample(start + Duration::from_secs(1), 4196);
+        assert_eq!(throughput.bytes_per_sec(), 4096.);
+
+        // idle terminals fall back to nothing
+        throughput.sample(start + Duration::from_secs(4), 4196);
+        throughput.sample(start + Duration::from_secs(5), 4196);
+        assert_eq!(throughput.bytes_per_sec(), 0.);
+    }
+}
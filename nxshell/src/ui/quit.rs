@@ -0,0 +1,108 @@
+use crate::app::NxShell;
+use crate::i18n::t;
+use egui::{Id, Modal, RichText, ViewportCommand};
+use egui_dock::DockState;
+use egui_phosphor::regular::WARNING;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Time the terminals are given to shut down on exit, all together.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Where the app is in quitting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuitState {
+    #[default]
+    Running,
+    /// Asking whether to quit while ssh sessions are open.
+    Confirming,
+    /// The window is closing, it isn't asked again.
+    Quitting,
+}
+
+impl NxShell {
+    /// Quit from the menu, or from the window being closed. It is confirmed
+    /// first while ssh sessions are open or files are transferred.
+    pub fn request_quit(&mut self, ctx: &egui::Context) {
+        if self.runtime.quit == QuitState::Quitting {
+            return;
+        }
+        if self.active_connections().is_empty() {
+            self.quit(ctx);
+        } else {
+            self.runtime.quit = QuitState::Confirming;
+        }
+    }
+
+    /// Catch the window being closed, to confirm it.
+    pub fn intercept_close(&mut self, ctx: &egui::Context) {
+        if self.runtime.quit != QuitState::Quitting && ctx.input(|i| i.viewport().close_requested())
+        {
+            ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+            self.request_quit(ctx);
+        }
+    }
+
+    pub fn quit_confirmation(&mut self, ctx: &egui::Context) {
+        if self.runtime.quit != QuitState::Confirming {
+            return;
+        }
+        let connections = self.active_connections();
+        let mut answer = None;
+        let modal = Modal::new(Id::new("quit_confirmation")).show(ctx, |ui| {
+            ui.set_max_width(480.);
+            let title = RichText::new(format!("{WARNING} {}", t!("quit-title")));
+            ui.heading(title);
+            ui.label(t!("quit-message"));
+            ui.add_space(8.);
+            for connection in &connections {
+                ui.label(format!("• {connection}"));
+            }
+            ui.add_space(8.);
+            ui.horizontal(|ui| {
+                if ui.button(t!("quit-cancel")).clicked() {
+                    answer = Some(false);
+                }
+                let quit = RichText::new(t!("quit-force")).color(ui.visuals().error_fg_color);
+                if ui.button(quit).clicked() {
+                    answer = Some(true);
+                }
+            });
+        });
+        if modal.should_close() {
+            answer.get_or_insert(false);
+        }
+        match answer {
+            Some(true) => self.quit(ctx),
+            Some(false) => self.runtime.quit = QuitState::Running,
+            None => {}
+        }
+    }
+
+    /// Ssh sessions and sftp transfers which quitting would cut, described
+    /// for the confirmation.
+    fn active_connections(&self) -> Vec<String> {
+        self.dock_state
+            .iter_all_tabs()
+            .filter_map(|(_, tab)| tab.connection())
+            .collect()
+    }
+
+    /// Close the window, the tabs are shut down on exit.
+    fn quit(&mut self, ctx: &egui::Context) {
+        self.runtime.quit = QuitState::Quitting;
+        ctx.send_viewport_cmd(ViewportCommand::Close);
+    }
+
+    /// Shut the terminals down in order, waiting for their threads for
+    /// [`SHUTDOWN_TIMEOUT`] at most, then drop the sftp sessions.
+    pub fn shutdown_tabs(&mut self) {
+        let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+        for (_, tab) in self.dock_state.iter_all_tabs_mut() {
+            if !tab.shutdown(deadline) {
+                warn!("the terminal of tab {} didn't shut down in time", tab.id());
+            }
+        }
+        self.dock_state = DockState::new(vec![]);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::app::NxShell;
+use crate::i18n::t;
+use egui::{RichText, TextEdit};
+use egui_phosphor::regular::TRASH;
+use egui_term::{Redaction, DEFAULT_REDACTION_PATTERNS};
+
+impl NxShell {
+    /// Patterns of the secrets masked in the terminals.
+    pub fn redaction_menu(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.opts.mask_secrets, t!("redaction-on-screen"))
+            .on_hover_text(t!("redaction-on-screen-hint"));
+        ui.separator();
+        ui.label(RichText::new(t!("redaction-patterns-hint")).weak());
+
+        let mut changed = false;
+        let mut removed = None;
+        for (index, pattern) in self.opts.redaction_patterns.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                let edit = TextEdit::singleline(pattern)
+                    .code_editor()
+                    .desired_width(360.);
+                changed |= ui.add(edit).changed();
+                if ui
+                    .button(TRASH)
+                    .on_hover_text(t!("redaction-remove"))
+                    .clicked()
+                {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            self.opts.redaction_patterns.remove(index);
+            changed = true;
+        }
+        ui.horizontal(|ui| {
+            if ui.button(t!("redaction-add")).clicked() {
+                self.opts.redaction_patterns.push(String::new());
+            }
+            if ui.button(t!("redaction-reset")).clicked() {
+                self.opts.redaction_patterns = default_redaction_patterns();
+                changed = true;
+            }
+        });
+        if changed {
+            self.apply_redaction();
+        }
+        if let Some(err) = &self.opts.redaction_error {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+    }
+
+    /// Compile the redaction patterns. While one of them is invalid, the
+    /// previous ones stay in use.
+    pub fn apply_redaction(&mut self) {
+        let patterns: Vec<_> = self
+            .opts
+            .redaction_patterns
+            .iter()
+            .filter(|pattern| !pattern.trim().is_empty())
+            .collect();
+        match Redaction::new(&patterns) {
+            Ok(redaction) => {
+                self.opts.redaction = redaction;
+                self.opts.redaction_error = None;
+            }
+            Err(err) => self.opts.redaction_error = Some(err.to_string()),
+        }
+    }
+}
+
+pub fn default_redaction_patterns() -> Vec<String> {
+    DEFAULT_REDACTION_PATTERNS
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .collect()
+}
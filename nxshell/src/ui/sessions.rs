@@ -0,0 +1,333 @@
+use crate::app::NxShell;
+use crate::availability::{HostProber, HostStatus, ProbeTarget};
+use crate::db::{Session, SessionGroups};
+use crate::errors::error_toast;
+use crate::i18n::t;
+use crate::ui::form::AuthType;
+use egui::{Button, Color32, ScrollArea, Sense, TextEdit, TextStyle, Vec2};
+use egui_phosphor::regular::{CARET_DOWN, CARET_RIGHT, DRONE, KEY, NUMPAD};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use tracing::error;
+
+/// The search runs once the typing pauses this long.
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Row of the session list, a group or a session of an unfolded group.
+#[derive(Debug, PartialEq)]
+enum SessionRow {
+    Group {
+        name: String,
+        /// Label with the caret and the count of the sessions, made once.
+        header: String,
+    },
+    Session {
+        group: String,
+        name: String,
+        auth_type: u16,
+    },
+}
+
+/// Rows of the session list, only the visible ones are laid out so that
+/// thousands of sessions don't slow the frames down.
+#[derive(Default)]
+pub struct SessionList {
+    /// `None` once the sessions or the folded groups change.
+    rows: Option<Vec<SessionRow>>,
+    /// Groups folded by the user, kept across the searches.
+    folded: HashSet<String>,
+    /// When the search was last edited, if it hasn't run since.
+    edited_at: Option<Instant>,
+}
+
+impl SessionList {
+    /// The rows are built again on the next frame.
+    pub fn invalidate(&mut self) {
+        self.rows = None;
+    }
+}
+
+fn session_rows(groups: &SessionGroups, folded: &HashSet<String>) -> Vec<SessionRow> {
+    let mut rows = Vec::with_capacity(groups.len() + groups.values().map(Vec::len).sum::<usize>());
+    for (group, sessions) in groups {
+        let unfolded = !folded.contains(group);
+        let caret = if unfolded { CARET_DOWN } else { CARET_RIGHT };
+        rows.push(SessionRow::Group {
+            name: group.clone(),
+            header: format!("{caret} {group} ({})", sessions.len()),
+        });
+        if unfolded {
+            rows.extend(sessions.iter().map(|session| SessionRow::Session {
+                group: group.clone(),
+                name: session.name.clone(),
+                auth_type: session.auth_type,
+            }));
+        }
+    }
+    rows
+}
+
+/// Diameter of the dot telling whether the host of a session is up.
+const HOST_BADGE_SIZE: f32 = 8.;
+
+impl NxShell {
+    /// Probe the hosts of the saved sessions every `host_probe_interval`
+    /// seconds while the badges are shown.
+    pub fn probe_hosts(&mut self, ctx: &egui::Context) {
+        if !self.opts.host_badges {
+            self.host_prober = None;
+            return;
+        }
+        let concurrency = self.opts.host_probe_concurrency as usize;
+        let prober = self
+            .host_prober
+            .get_or_insert_with(|| HostProber::spawn(ctx, concurrency));
+        prober.set_concurrency(concurrency);
+        let interval = Duration::from_secs(self.opts.host_probe_interval as u64);
+        if let Some(elapsed) = prober.probed_at.map(|probed_at| probed_at.elapsed()) {
+            if elapsed < interval {
+                ctx.request_repaint_after(interval - elapsed);
+                return;
+            }
+        }
+        prober.probed_at = Some(Instant::now());
+        ctx.request_repaint_after(interval);
+        self.db.run(
+            |db| db.find_session_hosts(),
+            |shell, sessions| match sessions {
+                Ok(sessions) => {
+                    let targets = sessions
+                        .into_iter()
+                        .map(|session| ProbeTarget {
+                            config: AuthType::from(session.auth_type) == AuthType::Config,
+                            group: session.group,
+                            name: session.name,
+                            host: session.host,
+                            port: session.port,
+                        })
+                        .collect();
+                    if let Some(prober) = &shell.host_prober {
+                        prober.probe(targets);
+                    }
+                }
+                Err(err) => error!("failed to load the hosts to probe: {err}"),
+            },
+        );
+    }
+
+    pub fn search_sessions(&mut self, ui: &mut egui::Ui) {
+        let text_edit = TextEdit::singleline(&mut self.opts.session_filter);
+        let response = ui.add(text_edit);
+        if response.clicked() {
+            self.opts.surrender_focus();
+        } else if response.changed() {
+            self.state_manager.session_list.edited_at = Some(Instant::now());
+        }
+
+        let Some(edited_at) = self.state_manager.session_list.edited_at else {
+            return;
+        };
+        let elapsed = edited_at.elapsed();
+        if elapsed < SEARCH_DEBOUNCE {
+            ui.ctx().request_repaint_after(SEARCH_DEBOUNCE - elapsed);
+            return;
+        }
+        self.state_manager.session_list.edited_at = None;
+        let filter = self.opts.session_filter.clone();
+        self.db.run(
+            move |db| db.find_sessions(&filter),
+            |shell, sessions| match sessions {
+                Ok(sessions) => shell.state_manager.set_sessions(sessions),
+                Err(err) => error!("failed to search the sessions: {err}"),
+            },
+        );
+    }
+
+    pub fn list_sessions(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
+        if self.state_manager.loading {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(t!("sessions-loading"));
+            });
+            return;
+        }
+        let list = &mut self.state_manager.session_list;
+        let Some(rows) = list.rows.take().or_else(|| {
+            let groups = self.state_manager.sessions.as_ref()?;
+            Some(session_rows(groups, &list.folded))
+        }) else {
+            return;
+        };
+
+        // every row as high, for the scroll area to tell the visible ones
+        let row_height = (ui.text_style_height(&TextStyle::Button)
+            + 2. * ui.spacing().button_padding.y)
+            .max(ui.spacing().interact_size.y);
+        let mut toggled = None;
+        ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, rows.len(), |ui, range| {
+                for row in &rows[range] {
+                    match row {
+                        SessionRow::Group { name, header } => {
+                            let header = Button::new(header.as_str())
+                                .frame(false)
+                                .min_size(Vec2::new(0., row_height));
+                            if ui.add(header).clicked() {
+                                toggled = Some(name.clone());
+                            }
+                        }
+                        SessionRow::Session {
+                            group,
+                            name,
+                            auth_type,
+                        } => {
+                            ui.horizontal(|ui| {
+                                ui.add_space(ui.spacing().indent);
+                                if let Some(prober) = &self.host_prober {
+                                    host_badge(ui, prober.status(group, name));
+                                }
+                                self.session_button(ctx, ui, row_height, group, name, *auth_type);
+                            });
+                        }
+                    }
+                }
+            });
+
+        let list = &mut self.state_manager.session_list;
+        match toggled {
+            Some(group) => {
+                if !list.folded.remove(&group) {
+                    list.folded.insert(group);
+                }
+            }
+            None => list.rows = Some(rows),
+        }
+    }
+
+    fn session_button(
+        &mut self,
+        ctx: &egui::Context,
+        ui: &mut egui::Ui,
+        height: f32,
+        group: &str,
+        name: &str,
+        auth_type: u16,
+    ) {
+        let icon = match AuthType::from(auth_type) {
+            AuthType::Password => NUMPAD,
+            AuthType::PublicKey => KEY,
+            AuthType::Config => DRONE,
+        };
+        let button = Button::new(format!("{icon} {name}")).min_size(Vec2::new(0., height));
+        let response = ui.add(button);
+        let mut open_sftp = None;
+        if response.double_clicked() {
+            open_sftp = Some(false);
+        }
+        response.context_menu(|ui| {
+            if ui.button(t!("open-terminal")).clicked() {
+                open_sftp = Some(false);
+                ui.close();
+            }
+            if ui.button(t!("open-sftp")).clicked() {
+                open_sftp = Some(true);
+                ui.close();
+            }
+            ui.separator();
+            if ui.button(t!("session-diagnose")).clicked() {
+                self.with_session(group, name, |shell, session| {
+                    shell.diagnose_session(session)
+                });
+                ui.close();
+            }
+            if ui.button(t!("session-wake")).clicked() {
+                self.with_session(group, name, |shell, session| shell.wake_session(session));
+                ui.close();
+            }
+            if ui.button(t!("session-duplicate")).clicked() {
+                self.duplicate_session(ctx, group.to_string(), name.to_string());
+                ui.close();
+            }
+        });
+        let Some(sftp) = open_sftp else {
+            return;
+        };
+        let ctx = ctx.clone();
+        self.with_session(group, name, move |shell, session| {
+            let result = match sftp {
+                true => shell.add_sftp_tab_with_secret(&ctx, session),
+                false => shell.add_shell_tab_with_secret(&ctx, session),
+            };
+            if let Err(err) = result {
+                shell.toasts.add(error_toast(err.to_string()));
+            }
+        });
+    }
+
+    /// Load the whole session of a row, then hand it to `f`.
+    pub fn with_session(
+        &mut self,
+        group: &str,
+        name: &str,
+        f: impl FnOnce(&mut NxShell, Session) + Send + 'static,
+    ) {
+        let (group, name) = (group.to_string(), name.to_string());
+        self.db.run(
+            move |db| db.find_session(&group, &name),
+            move |shell, session| match session {
+                Ok(Some(session)) => f(shell, session),
+                Ok(None) => {}
+                Err(err) => {
+                    shell.toasts.add(error_toast(err.to_string()));
+                }
+            },
+        );
+    }
+}
+
+/// Green dot when the host is up, red when it is down and grey until it is
+/// probed.
+fn host_badge(ui: &mut egui::Ui, status: Option<HostStatus>) {
+    let (rect, response) = ui.allocate_exact_size(Vec2::splat(HOST_BADGE_SIZE), Sense::hover());
+    let (color, hint) = match status {
+        Some(HostStatus::Up) => (Color32::from_rgb(0x4c, 0xaf, 0x50), t!("host-up")),
+        Some(HostStatus::Down) => (ui.visuals().error_fg_color, t!("host-down")),
+        None => (Color32::GRAY, t!("host-unknown")),
+    };
+    ui.painter()
+        .circle_filled(rect.center(), HOST_BADGE_SIZE / 2., color);
+    response.on_hover_text(hint);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folded_groups() {
+        let session = |group: &str, name: &str| Session {
+            group: group.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let mut groups = SessionGroups::default();
+        groups.insert(
+            "db".to_string(),
+            vec![session("db", "pg1"), session("db", "pg2")],
+        );
+        groups.insert("web".to_string(), vec![session("web", "nginx")]);
+
+        let rows = session_rows(&groups, &HashSet::from(["db".to_string()]));
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            rows[0],
+            SessionRow::Group {
+                name: "db".to_string(),
+                header: format!("{CARET_RIGHT} db (2)"),
+            }
+        );
+        assert!(matches!(&rows[2], SessionRow::Session { name, .. } if name == "nginx"));
+        assert_eq!(session_rows(&groups, &HashSet::new()).len(), 5);
+    }
+}
@@ -0,0 +1,56 @@
+use crate::app::NxShell;
+use crate::i18n::t;
+use egui::{Align, Layout, RichText};
+use egui_phosphor::regular::{BROADCAST, PLUGS_CONNECTED};
+use egui_theme_switch::global_theme_switch;
+
+impl NxShell {
+    /// Status of the active tab, refreshed every frame.
+    pub fn status_bar(&mut self, ui: &mut egui::Ui) {
+        let status = self
+            .dock_state
+            .find_active_focused()
+            .map(|(_, tab)| tab.status())
+            .unwrap_or_default();
+
+        ui.horizontal(|ui| {
+            if let Some(host) = &status.host {
+                ui.label(format!("{PLUGS_CONNECTED} {host}"));
+                let latency = match status.latency {
+                    Some(latency) => format!("{} ms", latency.as_millis()),
+                    None => "-- ms".to_string(),
+                };
+                ui.label(latency).on_hover_text(t!("status-latency-hint"));
+                ui.separator();
+            }
+            if let Some(grid) = status.grid {
+                ui.label(format!("{}×{}", grid.columns, grid.rows))
+                    .on_hover_text(t!("status-grid-hint"));
+                ui.label(t!(
+                    "status-cursor",
+                    line = grid.cursor_line,
+                    column = grid.cursor_column
+                ));
+                let scroll = if grid.display_offset == 0 {
+                    t!("status-scroll-bottom")
+                } else {
+                    format!("↑ {}/{}", grid.display_offset, grid.history_size)
+                };
+                ui.label(scroll).on_hover_text(t!("status-scroll-hint"));
+                ui.separator();
+                ui.label(status.encoding.to_string());
+            }
+            if self.opts.multi_exec {
+                ui.separator();
+                ui.label(
+                    RichText::new(format!("{BROADCAST} {}", t!("status-multi-exec"))).strong(),
+                )
+                .on_hover_text(t!("status-multi-exec-hint"));
+            }
+
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                global_theme_switch(ui);
+            });
+        });
+    }
+}
@@ -0,0 +1,208 @@
+use crate::db::{DbConn, COMMAND_HISTORY_LIMIT};
+use egui::{Area, Frame, Id, Key, Order, Pos2, RichText};
+use egui_term::Redaction;
+use std::collections::{HashMap, HashSet};
+use tracing::error;
+
+/// Shorter input isn't completed, most commands would match it.
+const MIN_INPUT_LEN: usize = 2;
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Popup completing what is typed at the shell prompt with the commands run
+/// before on the same host.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Completion {
+    /// Input the suggestions were last shown for.
+    input: String,
+    selected: usize,
+    /// Input the popup was closed at with Escape, it opens again once the
+    /// input changes.
+    dismissed: Option<String>,
+}
+
+/// Commands of the history starting with `input`, the most recent first.
+pub fn suggestions<'a>(history: &'a [String], input: &str) -> Vec<&'a str> {
+    if input.trim().len() < MIN_INPUT_LEN {
+        return vec![];
+    }
+    history
+        .iter()
+        .filter(|command| command.len() > input.len() && command.starts_with(input))
+        .take(MAX_SUGGESTIONS)
+        .map(String::as_str)
+        .collect()
+}
+
+impl Completion {
+    /// Whether the popup is shown for `input`, with its suggestions.
+    pub fn is_open(&mut self, input: &str, suggestions: &[&str]) -> bool {
+        if self.input != input {
+            self.input = input.to_string();
+            self.selected = 0;
+            self.dismissed = None;
+        }
+        self.selected = self.selected.min(suggestions.len().saturating_sub(1));
+        !suggestions.is_empty() && self.dismissed.is_none()
+    }
+
+    /// Take the keys of the open popup before the terminal gets them, returns
+    /// the rest of the command to type once one is picked with Tab.
+    pub fn handle_keys(&mut self, ui: &mut egui::Ui, suggestions: &[&str]) -> Option<String> {
+        let (tab, up, down, escape) = ui.input_mut(|i| {
+            let mut consume = |key| i.consume_key(egui::Modifiers::NONE, key);
+            (
+                consume(Key::Tab),
+                consume(Key::ArrowUp),
+                consume(Key::ArrowDown),
+                consume(Key::Escape),
+            )
+        });
+        if up {
+            self.selected = self
+                .selected
+                .checked_sub(1)
+                .unwrap_or(suggestions.len() - 1);
+        }
+        if down {
+            self.selected = (self.selected + 1) % suggestions.len();
+        }
+        if escape {
+            self.dismissed = Some(self.input.clone());
+        }
+        tab.then(|| suggestions[self.selected][self.input.len()..].to_string())
+    }
+
+    /// List the suggestions under the cursor of the terminal.
+    pub fn show(&self, ctx: &egui::Context, id: Id, pos: Pos2, suggestions: &[&str]) {
+        Area::new(id.with("completion"))
+            .order(Order::Foreground)
+            .fixed_pos(pos)
+            .interactable(false)
+            .show(ctx, |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    for (index, command) in suggestions.iter().enumerate() {
+                        let text = RichText::new(*command).monospace();
+                        ui.add(egui::Button::selectable(index == self.selected, text));
+                    }
+                });
+            });
+    }
+}
+
+/// Commands run before on the host, the first word of each line of its
+/// history.
+pub fn known_commands(history: &[String]) -> HashSet<String> {
+    history
+        .iter()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(String::from)
+        .collect()
+}
+
+/// Commands last run on `host`, loaded from the db the first time they are
+/// needed. Local shells are under an empty host.
+pub fn command_history<'a>(
+    cache: &'a mut HashMap<String, Vec<String>>,
+    db: &DbConn,
+    host: &str,
+) -> &'a mut Vec<String> {
+    cache.entry(host.to_string()).or_insert_with(|| {
+        let host = host.to_string();
+        db.run(
+            move |db| Ok((db.find_command_history(&host)?, host)),
+            |shell, loaded| match loaded {
+                Ok((mut commands, host)) => {
+                    let history = shell.opts.command_history.entry(host).or_default();
+                    // run while the history was loading
+                    commands.retain(|command| !history.contains(command));
+                    history.extend(commands);
+                }
+                Err(err) => error!("failed to load the command history: {err}"),
+            },
+        );
+        vec![]
+    })
+}
+
+/// `command` as saved in the history, its secrets masked. Like shells do,
+/// commands starting with a space are left out.
+fn recorded_command(command: &str, redaction: &Redaction) -> Option<String> {
+    if command.starts_with(' ') || command.trim().is_empty() {
+        return None;
+    }
+    Some(redaction.redact(command).into_owned())
+}
+
+/// Put `command` on top of the history of `host`, see [`recorded_command`].
+pub fn record_command(
+    history: &mut Vec<String>,
+    db: &DbConn,
+    host: &str,
+    command: &str,
+    redaction: &Redaction,
+) {
+    let Some(command) = recorded_command(command, redaction) else {
+        return;
+    };
+    history.retain(|c| *c != command);
+    history.insert(0, command.clone());
+    history.truncate(COMMAND_HISTORY_LIMIT);
+
+    let host = host.to_string();
+    db.run(
+        move |db| db.record_command(&host, &command),
+        |_, result| {
+            if let Err(err) = result {
+                error!("failed to save the command history: {err}");
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggest_commands_of_history() {
+        let history: Vec<_> = ["git push", "ls -la", "git status", "git", "gitk"]
+            .map(String::from)
+            .into();
+        assert_eq!(
+            suggestions(&history, "git"),
+            ["git push", "git status", "gitk"]
+        );
+        assert_eq!(suggestions(&history, "git s"), ["git status"]);
+        // the whole command is typed already
+        assert!(suggestions(&history, "git status").is_empty());
+        assert!(suggestions(&history, "g").is_empty());
+
+        let mut completion = Completion::default();
+        let matches = suggestions(&history, "git");
+        assert!(completion.is_open("git", &matches));
+        completion.dismissed = Some("git".to_string());
+        assert!(!completion.is_open("git", &matches));
+        // typing on opens it again
+        let matches = suggestions(&history, "git ");
+        assert!(completion.is_open("git ", &matches));
+
+        let commands = known_commands(&history);
+        assert!(commands.contains("git") && commands.contains("ls"));
+        assert!(!commands.contains("status"));
+    }
+
+    #[test]
+    fn commands_recorded() {
+        let redaction = Redaction::new(egui_term::DEFAULT_REDACTION_PATTERNS).unwrap();
+        assert_eq!(
+            recorded_command("ls -la", &redaction).as_deref(),
+            Some("ls -la")
+        );
+        assert_eq!(
+            recorded_command("mysql --password=hunter2", &redaction).as_deref(),
+            Some("mysql --password=*******")
+        );
+        assert_eq!(recorded_command(" export TOKEN=abc", &redaction), None);
+        assert_eq!(recorded_command("  ", &redaction), None);
+    }
+}
@@ -1,34 +1,104 @@
+mod completion;
 mod session;
+mod sftp;
+mod split;
 mod terminal;
 
-use crate::app::{NxShell, NxShellOptions};
+use crate::app::{NxShell, NxShellOptions, SessionSettings};
+use crate::bell::BellMode;
 use crate::consts::GLOBAL_COUNTER;
+use crate::db::DbConn;
+use crate::errors::{error_toast, NxError};
+use crate::fonts::FontManager;
+use crate::i18n::t;
+use crate::sftp::DEFAULT_PARALLELISM;
+use crate::ui::auth_prompt::{expects_typed_answer, prompt_history};
+use crate::ui::form::{parse_shortcuts, ThemeMode, TintMode};
+use crate::ui::idle::IdleTimer;
+use crate::ui::profiler::Throughput;
+use crate::ui::tab_view::completion::{
+    command_history, known_commands, record_command, suggestions, Completion,
+};
 use crate::ui::tab_view::session::SessionList;
+pub use crate::ui::tab_view::split::SplitDirection;
+use crate::ui::tab_view::split::SplitTerminalTab;
 use copypasta::ClipboardContext;
-use egui::{Label, Response, Sense, Ui};
+use egui::{
+    Color32, FontId, Id, Key, Label, LayerId, Modifiers, PointerButton, Pos2, Rect, Response,
+    RichText, Sense, Ui, UiBuilder, Vec2,
+};
 use egui_dock::tab_viewer::OnCloseResponse;
-use egui_dock::{DockArea, Style};
-use egui_phosphor::regular::{DRONE, NUMPAD};
+use egui_dock::{DockArea, NodeIndex, Style, SurfaceIndex};
+use egui_phosphor::regular::{BELL, CIRCLE_NOTCH, DRONE, FOLDER, KEY, NUMPAD, PUSH_PIN};
 use egui_term::{
-    Authentication, PtyEvent, TermType, Terminal, TerminalContext, TerminalOptions, TerminalTheme,
-    TerminalView,
+    cd_command, Authentication, BackendCommand, FontSettings, GridStatus, HistorySnapshot,
+    HostKeyChange, HostKeyCheck, KeyboardInteractive, MetaKeys, PtyEvent, Redaction, SshOptions,
+    TermEncoding, TermType, Terminal, TerminalConfig, TerminalContext, TerminalFont,
+    TerminalOptions, TerminalTheme, TerminalThumbnail, TerminalView, TerminalViewState, TextRun,
+    TunnelState, UnicodeWidth,
 };
 use homedir::my_home;
+use sftp::SftpExplorer;
+use std::collections::HashMap;
 use std::error::Error;
 use std::sync::mpsc::Sender;
-use terminal::TerminalTab;
+use std::time::{Duration, Instant};
+pub use terminal::StagedCommand;
+use terminal::{
+    exit_banner, idle_banner, paused_banner, staging_bar, unresponsive_overlay, BackendAction,
+    ConnectingTab, NestedCommand, StagingAction, TerminalTab, NESTED_SETTLE,
+};
 use tracing::error;
+use wezterm_ssh::Utf8PathBuf;
+
+/// How long the tab of a failed command blinks, see [`Tab::command_failed`].
+const FLASH_DURATION: Duration = Duration::from_secs(3);
+const FLASH_INTERVAL: Duration = Duration::from_millis(500);
 
 #[derive(PartialEq)]
 enum TabInner {
     Term(Box<TerminalTab>),
+    Connecting(Box<ConnectingTab>),
+    Sftp(Box<SftpExplorer>),
     SessionList(SessionList),
+    Split(Box<SplitTerminalTab>),
 }
 
 #[derive(PartialEq)]
 pub struct Tab {
     inner: TabInner,
     id: u64,
+    /// Closed by a middle-click on its button, removed on the next frame.
+    close_requested: bool,
+    /// When a command failed in the tab while it was in the background, until
+    /// the tab is shown.
+    failed_at: Option<Instant>,
+    /// Set when the bell rang in the tab while it was in the background,
+    /// until the tab is shown.
+    rang: bool,
+    /// Pinned tabs can't be closed until they are unpinned.
+    pinned: bool,
+    /// When the tab was last shown.
+    last_active: Instant,
+}
+
+/// Connection of a tab, or of a pane of a split tab, which failed.
+pub struct FailedConnection {
+    /// Id of the tab, or of the pane.
+    pub id: u64,
+    pub term_type: TermType,
+    pub err: Box<dyn Error>,
+}
+
+/// What the status bar shows about the active tab.
+#[derive(Default)]
+pub struct TabStatus {
+    pub grid: Option<GridStatus>,
+    /// `host:port` of ssh tabs.
+    pub host: Option<String>,
+    pub latency: Option<Duration>,
+    /// Of the output of terminal tabs.
+    pub encoding: TermEncoding,
 }
 
 impl Tab {
@@ -36,91 +106,1101 @@ impl Tab {
         self.id
     }
 
+    pub fn status(&self) -> TabStatus {
+        let mut encoding = TermEncoding::default();
+        let mut latency = None;
+        let (grid, options) = match &self.inner {
+            TabInner::Term(tab) => {
+                let options = match &tab.term_type {
+                    TermType::Ssh { options, .. } => Some(options.as_ref()),
+                    TermType::Regular { .. } => None,
+                };
+                encoding = tab.terminal.encoding();
+                latency = tab.terminal.latency();
+                (Some(tab.terminal.grid_status()), options)
+            }
+            TabInner::Connecting(tab) => match &tab.term_type {
+                TermType::Ssh { options, .. } => (None, Some(options.as_ref())),
+                TermType::Regular { .. } => (None, None),
+            },
+            TabInner::Sftp(explorer) => {
+                latency = explorer.latency();
+                (None, Some(&explorer.options))
+            }
+            TabInner::SessionList(_) => (None, None),
+            TabInner::Split(split) => return split.focused().status(),
+        };
+        TabStatus {
+            grid,
+            host: options.map(|options| format!("{}:{}", options.host, options.port.unwrap_or(22))),
+            latency,
+            encoding,
+        }
+    }
+
     pub fn term(
         ctx: egui::Context,
         typ: TermType,
+        config: TerminalConfig,
         command_sender: Sender<(u64, PtyEvent)>,
     ) -> Result<Self, Box<dyn Error>> {
         let id = GLOBAL_COUNTER.next();
-
-        let terminal = match typ {
-            TermType::Ssh { ref options } => {
-                Terminal::new_ssh(id, ctx, options.clone(), command_sender)?
-            }
-            _ => Terminal::new_regular(id, ctx, my_home()?, command_sender)?,
+        let inner = match typ {
+            // connected in the background, the tab shows the progress
+            TermType::Ssh {
+                ref options,
+                ref working_directory,
+            } => TabInner::Connecting(Box::new(ConnectingTab {
+                pending: Terminal::connect_ssh(
+                    id,
+                    ctx,
+                    options.as_ref().clone(),
+                    working_directory.clone(),
+                    config,
+                    command_sender,
+                )?,
+                term_type: typ,
+            })),
+            TermType::Regular { .. } => TabInner::Term(Box::new(TerminalTab {
+                terminal: Terminal::new_regular(id, ctx, my_home()?, config, command_sender)?,
+                terminal_theme: TerminalTheme::default(),
+                theme_of: None,
+                term_type: typ,
+                font: None,
+                staged: None,
+                completion: Completion::default(),
+                exited: false,
+                exit_code: None,
+                shapes: 0,
+                view_rect: Rect::NOTHING,
+                view_id: None,
+                throughput: Throughput::default(),
+                idle: IdleTimer::default(),
+                nested: NestedCommand::default(),
+            })),
         };
 
         Ok(Self {
             id,
-            inner: TabInner::Term(Box::new(TerminalTab {
-                terminal,
-                terminal_theme: TerminalTheme::default(),
-                term_type: typ,
-            })),
+            inner,
+            close_requested: false,
+            failed_at: None,
+            rang: false,
+            pinned: false,
+            last_active: Instant::now(),
         })
     }
 
-    pub fn session_list() -> Self {
+    pub fn sftp(
+        ctx: egui::Context,
+        options: SshOptions,
+        start_dir: Option<Utf8PathBuf>,
+    ) -> Result<Self, Box<dyn Error>> {
         let id = GLOBAL_COUNTER.next();
+        let explorer = SftpExplorer::new(id, ctx, options, start_dir)?;
 
-        Self {
+        Ok(Self {
             id,
-            inner: TabInner::SessionList(SessionList {}),
+            inner: TabInner::Sftp(Box::new(explorer)),
+            close_requested: false,
+            failed_at: None,
+            rang: false,
+            pinned: false,
+            last_active: Instant::now(),
+        })
+    }
+
+    /// Split the terminal of the tab in two, the focused pane of a split tab,
+    /// the new pane runs a terminal of the same type.
+    pub fn split(
+        &mut self,
+        ctx: egui::Context,
+        direction: SplitDirection,
+        config: TerminalConfig,
+        command_sender: Sender<(u64, PtyEvent)>,
+    ) -> Result<(), Box<dyn Error>> {
+        if let TabInner::Split(split) = &mut self.inner {
+            if split.direction != direction || matches!(split.focused().inner, TabInner::Split(_)) {
+                return split
+                    .focused_mut()
+                    .split(ctx, direction, config, command_sender);
+            }
+            let Some(typ) = split.focused().term_type().cloned() else {
+                return Ok(());
+            };
+            split.insert(Self::term(ctx, typ, config, command_sender)?);
+            return Ok(());
         }
+        let Some(typ) = self.term_type().cloned() else {
+            return Ok(());
+        };
+        let pane = Self::term(ctx, typ, config, command_sender)?;
+        // the first pane keeps the terminal, and its id for the events of it
+        let inner = std::mem::replace(&mut self.inner, TabInner::SessionList(SessionList {}));
+        let first = Self {
+            inner,
+            id: self.id,
+            close_requested: false,
+            failed_at: None,
+            rang: false,
+            pinned: false,
+            last_active: self.last_active,
+        };
+        self.inner = TabInner::Split(Box::new(SplitTerminalTab::new(first, pane, direction)));
+        Ok(())
     }
-}
 
-struct TabViewer<'a> {
-    command_sender: &'a Sender<(u64, PtyEvent)>,
-    options: &'a mut NxShellOptions,
-    clipboard: &'a mut ClipboardContext,
-}
+    pub fn is_split(&self) -> bool {
+        matches!(self.inner, TabInner::Split(_))
+    }
 
-impl egui_dock::TabViewer for TabViewer<'_> {
-    type Tab = Tab;
+    /// The tab, or the pane of a split tab, whose terminal sends the events
+    /// of `id`.
+    pub fn pane_mut(&mut self, id: u64) -> Option<&mut Tab> {
+        match self.inner {
+            TabInner::Split(ref mut split) => {
+                split.panes.iter_mut().find_map(|pane| pane.pane_mut(id))
+            }
+            _ if self.id == id => Some(self),
+            _ => None,
+        }
+    }
 
-    fn title(&mut self, tab: &mut Self::Tab) -> egui::WidgetText {
-        let tab_id = tab.id();
-        match &mut tab.inner {
+    /// Close the pane of `id` of a split tab. Returns whether the tab has
+    /// such a pane.
+    pub fn close_pane(&mut self, id: u64) -> bool {
+        let TabInner::Split(split) = &mut self.inner else {
+            return false;
+        };
+        let closed = match split
+            .panes
+            .iter()
+            .position(|pane| pane.id == id && !pane.is_split())
+        {
+            Some(index) => {
+                split.remove(index);
+                true
+            }
+            None => split.panes.iter_mut().any(|pane| pane.close_pane(id)),
+        };
+        self.collapse_split();
+        closed
+    }
+
+    /// A split tab left with a single pane shows it alone.
+    fn collapse_split(&mut self) {
+        let TabInner::Split(split) = &mut self.inner else {
+            return;
+        };
+        if split.panes.len() != 1 {
+            return;
+        }
+        let pane = split.remove(0);
+        self.id = pane.id;
+        self.inner = pane.inner;
+    }
+
+    /// Id of the view of the terminal with the keyboard, the one of the
+    /// focused pane of a split tab.
+    fn view_id(&self) -> Option<Id> {
+        match &self.inner {
+            TabInner::Term(tab) => tab.view_id,
+            TabInner::Split(split) => split.focused().view_id(),
+            _ => None,
+        }
+    }
+
+    fn shows_view(&self, view_id: Id) -> bool {
+        match &self.inner {
+            TabInner::Term(tab) => tab.view_id == Some(view_id),
+            TabInner::Split(split) => split.panes.iter().any(|pane| pane.shows_view(view_id)),
+            _ => false,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        let tab_id = self.id;
+        match &self.inner {
             TabInner::Term(term) => match term.term_type {
-                TermType::Ssh { ref options } => {
+                TermType::Ssh { ref options, .. } => {
                     let icon = match options.auth {
                         Authentication::Config => DRONE,
                         Authentication::Password(..) => NUMPAD,
+                        Authentication::PublicKey { .. } => KEY,
+                    };
+                    let name = match term.nested.target() {
+                        Some(target) => format!("{} › {target}", options.name),
+                        None => options.name.clone(),
                     };
                     if tab_id > 0 {
-                        format!("{icon} {} ({tab_id})", options.name).into()
+                        format!("{icon} {name} ({tab_id})")
                     } else {
-                        format!("{icon} {}", options.name).into()
+                        format!("{icon} {name}")
                     }
                 }
                 TermType::Regular { .. } => {
                     if tab_id > 0 {
-                        format!("local ({tab_id})").into()
+                        format!("{} ({tab_id})", t!("tab-local"))
                     } else {
-                        "local".into()
+                        t!("tab-local")
                     }
                 }
             },
-            TabInner::SessionList(_) => "sessions".into(),
+            TabInner::Connecting(tab) => match &tab.term_type {
+                TermType::Ssh { options, .. } => {
+                    format!(
+                        "{CIRCLE_NOTCH} {} ({tab_id}) {}",
+                        options.name,
+                        tab.status()
+                    )
+                }
+                TermType::Regular { .. } => format!("{CIRCLE_NOTCH} {}", tab.status()),
+            },
+            TabInner::Sftp(explorer) => {
+                format!("{FOLDER} {} ({tab_id})", explorer.options.name)
+            }
+            TabInner::SessionList(_) => t!("tab-sessions"),
+            TabInner::Split(split) => split.panes[0].title(),
+        }
+    }
+
+    /// Ssh connection of the tab, along with the transfers it runs. Described
+    /// for the confirmation before quitting.
+    pub fn connection(&self) -> Option<String> {
+        match &self.inner {
+            TabInner::Term(tab) if matches!(tab.term_type, TermType::Ssh { .. }) => {
+                Some(self.title())
+            }
+            TabInner::Connecting(_) => Some(self.title()),
+            TabInner::Sftp(explorer) => match explorer.pending_transfers() {
+                0 => Some(self.title()),
+                count => Some(t!("quit-transfers", name = self.title(), count = count)),
+            },
+            TabInner::Split(split) => {
+                let connections: Vec<_> = split.panes.iter().filter_map(Tab::connection).collect();
+                (!connections.is_empty()).then(|| connections.join(", "))
+            }
+            _ => None,
+        }
+    }
+
+    /// Start a new backend in place of the one of a terminal tab, e.g. after
+    /// it stopped answering. The tab gets a new id, the events of the former
+    /// backend are ignored.
+    pub fn restart(
+        &mut self,
+        ctx: egui::Context,
+        config: TerminalConfig,
+        command_sender: Sender<(u64, PtyEvent)>,
+    ) -> Result<(), Box<dyn Error>> {
+        let TabInner::Term(tab) = &self.inner else {
+            return Ok(());
+        };
+        let mut restarted = Self::term(ctx, tab.term_type.clone(), config, command_sender)?;
+        restarted.pinned = self.pinned;
+        *self = restarted;
+        Ok(())
+    }
+
+    /// Shut the terminal of the tab down, see [`Terminal::shutdown`]. Returns
+    /// whether it finished before `deadline`.
+    pub fn shutdown(&mut self, deadline: Instant) -> bool {
+        match &mut self.inner {
+            TabInner::Term(tab) => tab.terminal.shutdown(deadline),
+            TabInner::Split(split) => split
+                .panes
+                .iter_mut()
+                .fold(true, |done, pane| pane.shutdown(deadline) && done),
+            _ => true,
+        }
+    }
+
+    /// Shapes painted for the terminal on its last frame and the bytes per
+    /// second its shell outputs, sampled at `now`.
+    pub fn render_stats(&mut self, now: Instant) -> Option<(usize, f64)> {
+        if let TabInner::Split(split) = &mut self.inner {
+            return split
+                .panes
+                .iter_mut()
+                .filter_map(|pane| pane.render_stats(now))
+                .reduce(|(shapes, rate), (pane_shapes, pane_rate)| {
+                    (shapes + pane_shapes, rate + pane_rate)
+                });
+        }
+        let TabInner::Term(tab) = &mut self.inner else {
+            return None;
+        };
+        tab.throughput.sample(now, tab.terminal.read_bytes());
+        Some((tab.shapes, tab.throughput.bytes_per_sec()))
+    }
+
+    /// How long a ssh terminal tab had no input nor output at `now`.
+    pub fn idle_for(&mut self, now: Instant) -> Option<Duration> {
+        if let TabInner::Split(split) = &mut self.inner {
+            // idle once every pane is
+            return split
+                .panes
+                .iter_mut()
+                .filter_map(|pane| pane.idle_for(now))
+                .min();
+        }
+        let TabInner::Term(tab) = &mut self.inner else {
+            return None;
+        };
+        if !matches!(tab.term_type, TermType::Ssh { .. }) {
+            return None;
+        }
+        let bytes = tab.terminal.read_bytes() + tab.terminal.written_bytes();
+        Some(tab.idle.sample(now, bytes))
+    }
+
+    /// The nested command of the tab finished, it is named after its session
+    /// again.
+    pub fn nested_finished(&mut self) {
+        if let TabInner::Term(tab) = &mut self.inner {
+            if tab.nested.target().is_some() {
+                tab.nested = NestedCommand::Done;
+            }
+        }
+    }
+
+    /// Warn the tab that it is about to be disconnected for being idle.
+    /// Returns whether it wasn't warned yet.
+    pub fn warn_idle(&mut self) -> bool {
+        match &mut self.inner {
+            TabInner::Term(tab) => !std::mem::replace(&mut tab.idle.warned, true),
+            TabInner::Split(split) => split
+                .panes
+                .iter_mut()
+                .fold(false, |warned, pane| pane.warn_idle() || warned),
+            _ => false,
+        }
+    }
+
+    pub fn last_active(&self) -> Instant {
+        self.last_active
+    }
+
+    /// Approximate memory taken by the terminal of the tab, see
+    /// [`Terminal::grid_memory`].
+    pub fn grid_memory(&self) -> usize {
+        match &self.inner {
+            TabInner::Split(split) => split.panes.iter().map(Tab::grid_memory).sum(),
+            _ => self.terminal().map_or(0, Terminal::grid_memory),
+        }
+    }
+
+    /// Drop the oldest lines of the history of the terminal, see
+    /// [`Terminal::trim_history`].
+    pub fn trim_history(&mut self, max_lines: usize) -> usize {
+        match &mut self.inner {
+            TabInner::Term(tab) => tab.terminal.trim_history(max_lines),
+            TabInner::Split(split) => split
+                .panes
+                .iter_mut()
+                .map(|pane| pane.trim_history(max_lines))
+                .sum(),
+            _ => 0,
+        }
+    }
+
+    /// Exit code of the shell of a terminal tab, reported before it exits.
+    pub fn set_exit_code(&mut self, code: i32) {
+        if let TabInner::Term(tab) = &mut self.inner {
+            tab.exit_code = Some(code);
+        }
+    }
+
+    /// Keep a local terminal tab whose shell exited open, showing its last
+    /// screen until it is closed. Returns whether the tab is kept.
+    pub fn keep_exited(&mut self) -> bool {
+        match &mut self.inner {
+            TabInner::Term(tab) if matches!(tab.term_type, TermType::Regular { .. }) => {
+                tab.exited = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Flash the tab, a command failed in it. Cleared once the tab is shown.
+    pub fn command_failed(&mut self) {
+        self.failed_at = Some(Instant::now());
+    }
+
+    /// Mark the tab, the bell rang in it. Cleared once the tab is shown.
+    pub fn bell_rang(&mut self) {
+        self.rang = true;
+    }
+
+    /// Whether the title of the tab is flashing, see [`Tab::command_failed`].
+    fn is_flashing(&self) -> bool {
+        self.failed_at
+            .is_some_and(|failed_at| failed_at.elapsed() < FLASH_DURATION)
+    }
+
+    /// What the tab shows, as reported by the automation API.
+    pub fn kind(&self) -> &'static str {
+        match &self.inner {
+            TabInner::Term(_) => "terminal",
+            TabInner::Connecting(_) => "connecting",
+            TabInner::Sftp(_) => "sftp",
+            TabInner::SessionList(_) => "sessions",
+            TabInner::Split(_) => "split",
+        }
+    }
+
+    /// Terminal of a connected terminal tab, of the focused pane of a split
+    /// tab.
+    pub fn terminal(&self) -> Option<&Terminal> {
+        match &self.inner {
+            TabInner::Term(tab) => Some(&tab.terminal),
+            TabInner::Split(split) => split.focused().terminal(),
+            _ => None,
+        }
+    }
+
+    /// Type `text` into a terminal tab, returns whether the tab has a terminal.
+    pub fn send_text(&mut self, text: String, clipboard: &mut ClipboardContext) -> bool {
+        match &mut self.inner {
+            TabInner::Term(tab) => {
+                TerminalContext::new(&mut tab.terminal, clipboard).write_data(text.into_bytes());
+                true
+            }
+            TabInner::Split(split) => split.focused_mut().send_text(text, clipboard),
+            _ => false,
+        }
+    }
+
+    /// Hand `command` to the backend of a terminal tab, e.g. to search its
+    /// scrollback. Returns whether the tab has a terminal.
+    pub fn process_command(
+        &mut self,
+        command: BackendCommand,
+        clipboard: &mut ClipboardContext,
+    ) -> bool {
+        match &mut self.inner {
+            TabInner::Term(tab) => {
+                TerminalContext::new(&mut tab.terminal, clipboard).process_command(command);
+                true
+            }
+            TabInner::Split(split) => split.focused_mut().process_command(command, clipboard),
+            _ => false,
+        }
+    }
+
+    /// Propose a command to run in a terminal tab, it replaces the one proposed
+    /// before. Returns whether the tab has a terminal.
+    pub fn stage_command(&mut self, staged: StagedCommand) -> bool {
+        match &mut self.inner {
+            TabInner::Term(tab) => {
+                tab.staged = Some(staged);
+                true
+            }
+            TabInner::Split(split) => split.focused_mut().stage_command(staged),
+            _ => false,
+        }
+    }
+
+    /// Miniature of a terminal tab, for the tab overview.
+    pub fn thumbnail(&self) -> Option<TerminalThumbnail> {
+        match &self.inner {
+            TabInner::Term(tab) => Some(tab.terminal.thumbnail(&tab.terminal_theme)),
+            TabInner::Split(split) => split.focused().thumbnail(),
+            _ => None,
+        }
+    }
+
+    /// Where the terminal was drawn on the last frame.
+    pub fn view_rect(&self) -> Option<Rect> {
+        match &self.inner {
+            TabInner::Term(tab) => Some(tab.view_rect),
+            TabInner::Split(split) => split.focused().view_rect(),
+            _ => None,
+        }
+    }
+
+    /// Lines of the history of a terminal tab in the colors of its theme.
+    pub fn styled_history(&self, redaction: Option<&Redaction>) -> Option<Vec<Vec<TextRun>>> {
+        match &self.inner {
+            TabInner::Term(tab) => {
+                Some(tab.terminal.styled_history(&tab.terminal_theme, redaction))
+            }
+            TabInner::Split(split) => split.focused().styled_history(redaction),
+            _ => None,
+        }
+    }
+
+    /// Swap a connecting tab for its terminal once connected, the connection
+    /// of a sftp tab goes on in the background. Returns the fingerprint of the
+    /// host key to pin to the session, once it is known.
+    pub fn poll_connection(&mut self) -> Result<Option<String>, FailedConnection> {
+        if let TabInner::Split(split) = &mut self.inner {
+            // the pane that failed to connect is closed, not the whole tab
+            let mut pinned = None;
+            for index in 0..split.panes.len() {
+                match split.panes[index].poll_connection() {
+                    Ok(fingerprint) => pinned = pinned.or(fingerprint),
+                    Err(err) => {
+                        split.remove(index);
+                        self.collapse_split();
+                        return Err(err);
+                    }
+                }
+            }
+            return Ok(pinned);
+        }
+        if let TabInner::Sftp(explorer) = &mut self.inner {
+            explorer.poll_events();
+            return Ok(explorer.take_pinned_host_key());
+        }
+        let TabInner::Connecting(tab) = &mut self.inner else {
+            return Ok(None);
+        };
+        let terminal = tab.pending.poll();
+        let pinned = tab.pending.take_pinned_host_key();
+        if let (Some(fingerprint), TermType::Ssh { options, .. }) = (&pinned, &mut tab.term_type) {
+            // a duplicate of the tab expects the same key
+            options.host_key = HostKeyCheck::Pinned(Some(fingerprint.clone()));
+        }
+        let Some(terminal) = terminal else {
+            return Ok(pinned);
+        };
+        let term_type = tab.term_type.clone();
+        let terminal = match terminal {
+            Ok(terminal) => terminal,
+            Err(err) => {
+                return Err(FailedConnection {
+                    id: self.id,
+                    term_type,
+                    err: err.into(),
+                })
+            }
+        };
+        self.inner = TabInner::Term(Box::new(TerminalTab {
+            terminal,
+            terminal_theme: TerminalTheme::default(),
+            theme_of: None,
+            term_type,
+            font: None,
+            staged: None,
+            completion: Completion::default(),
+            exited: false,
+            exit_code: None,
+            shapes: 0,
+            view_rect: Rect::NOTHING,
+            view_id: None,
+            throughput: Throughput::default(),
+            idle: IdleTimer::default(),
+            nested: NestedCommand::default(),
+        }));
+        Ok(pinned)
+    }
+
+    /// Changed host key a connecting tab waits on, see [`Tab::answer_host_key`].
+    pub fn host_key_change(&self) -> Option<&HostKeyChange> {
+        match &self.inner {
+            TabInner::Connecting(tab) => tab.pending.host_key_change(),
+            TabInner::Sftp(explorer) => explorer.host_key_change(),
+            TabInner::Split(split) => split.panes.iter().find_map(Tab::host_key_change),
+            _ => None,
+        }
+    }
+
+    pub fn answer_host_key(&mut self, trust: bool) {
+        match &mut self.inner {
+            TabInner::Connecting(tab) => tab.pending.answer_host_key(trust),
+            TabInner::Sftp(explorer) => explorer.answer_host_key(trust),
+            TabInner::Split(split) => {
+                let pane = split
+                    .panes
+                    .iter_mut()
+                    .find(|pane| pane.host_key_change().is_some());
+                if let Some(pane) = pane {
+                    pane.answer_host_key(trust);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Prompts a connecting tab waits on, see [`Tab::answer_keyboard_interactive`].
+    pub fn keyboard_interactive(&self) -> Option<&KeyboardInteractive> {
+        match &self.inner {
+            TabInner::Connecting(tab) => tab.pending.keyboard_interactive(),
+            TabInner::Split(split) => split.panes.iter().find_map(Tab::keyboard_interactive),
+            _ => None,
+        }
+    }
+
+    pub fn answer_keyboard_interactive(&mut self, answers: Option<Vec<String>>) {
+        match &mut self.inner {
+            TabInner::Connecting(tab) => tab.pending.answer_keyboard_interactive(answers),
+            TabInner::Split(split) => {
+                let pane = split
+                    .panes
+                    .iter_mut()
+                    .find(|pane| pane.keyboard_interactive().is_some());
+                if let Some(pane) = pane {
+                    pane.answer_keyboard_interactive(answers);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// How the terminal of the tab is started, `None` for the other tabs.
+    pub fn term_type(&self) -> Option<&TermType> {
+        match &self.inner {
+            TabInner::Term(tab) => Some(&tab.term_type),
+            TabInner::Connecting(tab) => Some(&tab.term_type),
+            TabInner::Split(split) => split.panes[0].term_type(),
+            _ => None,
+        }
+    }
+
+    /// Group and name of the saved session of a ssh tab.
+    pub fn session_key(&self) -> Option<(String, String)> {
+        let options = match &self.inner {
+            TabInner::Term(tab) => match &tab.term_type {
+                TermType::Ssh { options, .. } => options,
+                TermType::Regular { .. } => return None,
+            },
+            TabInner::Connecting(tab) => match &tab.term_type {
+                TermType::Ssh { options, .. } => options,
+                TermType::Regular { .. } => return None,
+            },
+            TabInner::Sftp(explorer) => &explorer.options,
+            TabInner::SessionList(_) => return None,
+            TabInner::Split(split) => return split.panes[0].session_key(),
+        };
+        Some((options.group.clone(), options.name.clone()))
+    }
+
+    /// Scale the own font of a terminal tab, see [`TerminalTab::font`].
+    pub fn scale_font(&mut self, scale: f32) {
+        match &mut self.inner {
+            TabInner::Term(tab) => {
+                if let Some(font) = &mut tab.font {
+                    *font.font_size_mut() *= scale;
+                }
+            }
+            TabInner::Split(split) => {
+                for pane in &mut split.panes {
+                    pane.scale_font(scale);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn session_list() -> Self {
+        let id = GLOBAL_COUNTER.next();
+
+        Self {
+            id,
+            inner: TabInner::SessionList(SessionList {}),
+            close_requested: false,
+            failed_at: None,
+            rang: false,
+            pinned: false,
+            last_active: Instant::now(),
+        }
+    }
+}
+
+/// Tabs opened from another tab and files saved from it, handled once the
+/// dock area is shown.
+pub enum TabRequest {
+    Terminal {
+        options: SshOptions,
+        working_directory: Option<String>,
+    },
+    Sftp {
+        options: SshOptions,
+        start_dir: Option<Utf8PathBuf>,
+    },
+    /// The history of a terminal, saved to a text file named after `name`.
+    SaveScrollback {
+        name: String,
+        snapshot: HistorySnapshot,
+    },
+}
+
+struct TabViewer<'a> {
+    command_sender: &'a Sender<(u64, PtyEvent)>,
+    requests: &'a mut Vec<TabRequest>,
+    db: &'a DbConn,
+    options: &'a mut NxShellOptions,
+    fonts: &'a mut FontManager,
+    clipboard: &'a mut ClipboardContext,
+    /// Ids, rects and layers of the tab buttons shown this frame.
+    tab_buttons: Vec<(u64, Rect, LayerId)>,
+    /// Terminals shown on the last frame, see [`crate::app::RuntimeState`].
+    visible_terminals: usize,
+    /// Terminals shown this frame.
+    terminals_shown: usize,
+}
+
+impl egui_dock::TabViewer for TabViewer<'_> {
+    type Tab = Tab;
+
+    fn title(&mut self, tab: &mut Self::Tab) -> egui::WidgetText {
+        let mut title = if tab.pinned {
+            format!("{PUSH_PIN} {}", tab.title())
+        } else {
+            tab.title()
+        };
+        if tab.rang {
+            title = format!("{BELL} {title}");
+        }
+        let Some(failed_at) = tab.failed_at else {
+            return title.into();
+        };
+        // blinks for a while, then stays highlighted
+        let blink = (failed_at.elapsed().as_millis() / FLASH_INTERVAL.as_millis()) % 2 == 1;
+        if tab.is_flashing() && blink {
+            title.into()
+        } else {
+            RichText::new(title).color(Color32::LIGHT_RED).into()
         }
     }
 
     fn ui(&mut self, ui: &mut Ui, tab: &mut Self::Tab) {
+        tab.failed_at = None;
+        tab.rang = false;
+        tab.last_active = Instant::now();
+        let mut close = false;
+        let mut restart = false;
         match &mut tab.inner {
             TabInner::Term(tab) => {
+                self.terminals_shown += 1;
+                let current_dir = tab.terminal.current_dir();
+                let mut cd_to = None;
+                let session = match &tab.term_type {
+                    TermType::Ssh { options, .. } => Some((
+                        options,
+                        session_settings(&mut self.options.session_settings, self.db, options),
+                    )),
+                    TermType::Regular { .. } => None,
+                };
+
+                let mut font_family = &self.options.term_font_family;
+                let mut default_font_size = self.options.term_font_size;
+                if let Some((_, settings)) = &session {
+                    if !settings.font_family.is_empty() {
+                        font_family = &settings.font_family;
+                    }
+                    if let Some(size) = settings.font_size {
+                        default_font_size = size;
+                        tab.font.get_or_insert_with(|| {
+                            TerminalFont::new(FontSettings {
+                                font_type: FontId::monospace(size),
+                                ..Default::default()
+                            })
+                        });
+                    }
+                }
+                // the spacing is the same for every terminal
+                if let Some(font) = &mut tab.font {
+                    font.set_line_height(self.options.term_font.line_height());
+                    font.set_cell_padding(self.options.term_font.cell_padding());
+                }
+                let font = tab.font.as_mut().unwrap_or(&mut self.options.term_font);
+                let font_id = self.fonts.font_id(ui.ctx(), font_family, font.font_size());
+                font.set_font_family(font_id.family);
+
+                // commands are completed from the ones run on the same host
+                let host = match &tab.term_type {
+                    TermType::Ssh { options, .. } => options.host.as_str(),
+                    TermType::Regular { .. } => "",
+                };
+                let history = command_history(&mut self.options.command_history, self.db, host);
+                for command in tab.terminal.take_submitted_commands() {
+                    if self.options.record_commands {
+                        let redaction = &self.options.redaction;
+                        record_command(history, self.db, host, &command, redaction);
+                    }
+                }
+                let prompt_input = tab.terminal.prompt_input();
+                // the history would show through the blur
+                let matches = match &prompt_input {
+                    Some(input) if !self.options.privacy_blur => suggestions(history, input),
+                    _ => vec![],
+                };
+                let plain_prompt = session
+                    .as_ref()
+                    .is_some_and(|(_, settings)| settings.plain_prompt);
+                let known_commands = (!plain_prompt).then(|| known_commands(history));
+
+                if let Some((_, settings)) = &session {
+                    let read_bytes = tab.terminal.read_bytes();
+                    let command = &settings.nested_command;
+                    if tab.nested.ready(command, read_bytes, Instant::now()) {
+                        TerminalContext::new(&mut tab.terminal, self.clipboard)
+                            .write_data(format!("{command}\r").into_bytes());
+                    } else if matches!(tab.nested, NestedCommand::Pending { .. }) {
+                        ui.ctx().request_repaint_after(NESTED_SETTLE);
+                    }
+                }
+                if let Some(staged) = &mut tab.staged {
+                    let active_tab_id = &mut self.options.active_tab_id;
+                    match staging_bar(ui, staged, active_tab_id) {
+                        Some(StagingAction::Run(command)) => {
+                            tab.staged = None;
+                            TerminalContext::new(&mut tab.terminal, self.clipboard)
+                                .write_data(format!("{command}\r").into_bytes());
+                        }
+                        Some(StagingAction::Dismiss) => tab.staged = None,
+                        None => {}
+                    }
+                }
+                if tab.exited {
+                    close = exit_banner(ui, tab.exit_code);
+                }
+                if tab.idle.warned && idle_banner(ui) {
+                    tab.idle.reset(Instant::now());
+                }
+                if tab.terminal.output_paused() && paused_banner(ui) {
+                    TerminalContext::new(&mut tab.terminal, self.clipboard).resume_output();
+                }
+
+                // the host may set a light background the theme clashes with
+                let theme = session
+                    .as_ref()
+                    .map_or(ThemeMode::Auto, |(_, settings)| settings.theme);
+                let light = theme.is_light(tab.terminal.remote_background());
+                let theme_of = (light, self.options.term_theme_revision);
+                if tab.theme_of != Some(theme_of) {
+                    tab.theme_of = Some(theme_of);
+                    tab.terminal_theme = self.options.term_theme.clone();
+                    if light {
+                        let palette = tab.terminal_theme.palette_mut();
+                        *palette = palette.on_light_background();
+                    }
+                }
+
+                let focused_id = self.options.active_tab_id;
+                tab.view_rect = ui.available_rect_before_wrap();
                 let term_ctx = TerminalContext::new(&mut tab.terminal, self.clipboard);
                 let term_opt = TerminalOptions {
-                    font: &mut self.options.term_font,
+                    font,
                     multi_exec: &mut self.options.multi_exec,
                     theme: &mut tab.terminal_theme,
-                    default_font_size: self.options.term_font_size,
+                    default_font_size,
                     active_tab_id: &mut self.options.active_tab_id,
                 };
 
-                let terminal =
-                    TerminalView::new(ui, term_ctx, term_opt).set_size(ui.available_size());
-                ui.add(terminal);
+                let mut terminal = TerminalView::new(ui, term_ctx, term_opt)
+                    .set_size(ui.available_size())
+                    .set_labels(&self.options.term_labels)
+                    .show_exit_codes(self.options.exit_codes)
+                    .redact(&self.options.redaction, self.options.mask_secrets)
+                    .blur(self.options.privacy_blur)
+                    .smooth_cursor(self.options.smooth_cursor)
+                    .focus_follows_mouse(self.options.focus_follows_mouse)
+                    .block_selection(self.options.block_selection)
+                    .dim_unfocused(self.options.dim_inactive && self.visible_terminals > 1)
+                    .visual_bell(self.options.bell == BellMode::Visual)
+                    .scroll_speed(self.options.scroll_multiplier, self.options.natural_scroll)
+                    .flow_control(self.options.flow_control)
+                    .show_durations(
+                        self.options
+                            .show_durations
+                            .then(|| Duration::from_secs_f32(self.options.duration_threshold)),
+                    );
+                if let Some(commands) = &known_commands {
+                    terminal = terminal.highlight_input(commands);
+                }
+                if let Some((options, settings)) = session {
+                    let requests = &mut *self.requests;
+                    let bookmarks = &settings.bookmarks;
+                    let theme = &mut settings.theme;
+                    let db = self.db;
+                    let cd_to = &mut cd_to;
+                    terminal = terminal
+                        .set_tint(settings.tint)
+                        .protect_keys(&settings.protected_keys)
+                        .local_echo(settings.local_echo)
+                        .meta_keys(settings.meta_keys)
+                        .unicode_width(settings.unicode_width);
+                    terminal = terminal.extend_context_menu(move |ui| {
+                        let btn =
+                            egui::Button::new(t!("open-sftp-here")).min_size((200., 0.).into());
+                        if ui.add(btn).clicked() {
+                            requests.push(TabRequest::Sftp {
+                                options: options.as_ref().clone(),
+                                start_dir: current_dir.as_deref().map(Utf8PathBuf::from),
+                            });
+                            ui.close();
+                        }
+                        ui.add_enabled_ui(!bookmarks.is_empty(), |ui| {
+                            ui.menu_button(t!("cd-to-bookmark"), |ui| {
+                                for path in bookmarks.iter() {
+                                    if ui.button(path).clicked() {
+                                        *cd_to = Some(path.clone());
+                                        ui.close();
+                                    }
+                                }
+                            });
+                        });
+                        // remembered for the next tabs of the session
+                        ui.menu_button(t!("term-theme"), |ui| {
+                            for mode in ThemeMode::ALL {
+                                if ui.radio_value(theme, mode, mode.to_string()).clicked() {
+                                    save_theme(db, options, mode);
+                                    ui.close();
+                                }
+                            }
+                        });
+                    });
+                }
+                // only at the prompt, full screen applications get every key
+                let view_id = terminal.id();
+                tab.view_id = Some(view_id);
+                let completing = focused_id == Some(view_id)
+                    && prompt_input
+                        .as_deref()
+                        .is_some_and(|input| tab.completion.is_open(input, &matches));
+                let completed = completing
+                    .then(|| tab.completion.handle_keys(ui, &matches))
+                    .flatten();
+                // the shell is gone, Enter closes the tab
+                if tab.exited && focused_id == Some(view_id) {
+                    close |= ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Enter));
+                }
+                let rect = ui.add(terminal).rect;
+                let now = Instant::now();
+                if tab.terminal.backend_unresponsive(now) {
+                    match unresponsive_overlay(ui, view_id, rect) {
+                        Some(BackendAction::Restart) => restart = true,
+                        Some(BackendAction::Close) => close = true,
+                        None => {}
+                    }
+                } else if let Some(wait) = tab.terminal.heartbeat_pending(now) {
+                    ui.ctx().request_repaint_after(wait);
+                }
+                let view_state = TerminalViewState::load(ui.ctx(), view_id);
+                tab.shapes = view_state.shapes;
+                close |= view_state.close_requested;
+                restart |= view_state.restart_requested;
+                if view_state.save_scrollback_requested {
+                    let name = match host {
+                        "" => "local",
+                        host => host,
+                    };
+                    self.requests.push(TabRequest::SaveScrollback {
+                        name: name.to_string(),
+                        snapshot: tab.terminal.history_snapshot(),
+                    });
+                }
+                if let Some(rest) = completed {
+                    TerminalContext::new(&mut tab.terminal, self.clipboard)
+                        .write_data(rest.into_bytes());
+                } else if completing {
+                    let state = TerminalViewState::load(ui.ctx(), view_id);
+                    if let Some(cursor) = state.cursor_position {
+                        let below = Vec2::new(0., tab.terminal.size.cell_height as f32);
+                        tab.completion
+                            .show(ui.ctx(), view_id, cursor + below, &matches);
+                    }
+                }
+                if let Some(dir) = cd_to {
+                    TerminalContext::new(&mut tab.terminal, self.clipboard)
+                        .write_data(cd_command(&dir).into_bytes());
+                }
+            }
+            TabInner::Connecting(tab) => {
+                let otp_hint = match &tab.term_type {
+                    TermType::Ssh { options, .. } => {
+                        let history =
+                            prompt_history(&mut self.options.auth_prompts, self.db, &options.host);
+                        expects_typed_answer(history)
+                    }
+                    TermType::Regular { .. } => false,
+                };
+                ui.centered_and_justified(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(tab.status());
+                        });
+                        if otp_hint {
+                            ui.weak(t!("auth-prompt-otp-hint"));
+                        }
+                    });
+                });
+            }
+            TabInner::Sftp(explorer) => {
+                let parallelism = self
+                    .options
+                    .sftp_parallelism
+                    .entry(explorer.options.host.clone())
+                    .or_insert(DEFAULT_PARALLELISM);
+                let settings = session_settings(
+                    &mut self.options.session_settings,
+                    self.db,
+                    &explorer.options,
+                );
+                explorer.ui(ui, parallelism, &settings.bookmarks);
+                if let Some(dir) = explorer.take_bookmark_toggle() {
+                    let bookmarks = &mut settings.bookmarks;
+                    toggle_bookmark(bookmarks, self.db, &explorer.options, dir.into_string());
+                }
+                if let Some(dir) = explorer.take_terminal_request() {
+                    self.requests.push(TabRequest::Terminal {
+                        options: explorer.options.clone(),
+                        working_directory: Some(dir.to_string()),
+                    });
+                }
+            }
+            TabInner::Split(split) => {
+                let rect = ui.available_rect_before_wrap();
+                let (panes, dividers) = split.layout(rect);
+                for (index, divider) in dividers.into_iter().enumerate() {
+                    let response = ui.interact(divider, ui.id().with(index), Sense::drag());
+                    if response.hovered() || response.dragged() {
+                        ui.ctx().set_cursor_icon(split.direction.resize_cursor());
+                    }
+                    let color = if response.dragged() {
+                        ui.visuals().widgets.active.bg_fill
+                    } else {
+                        ui.visuals().widgets.noninteractive.bg_stroke.color
+                    };
+                    ui.painter().rect_filled(divider, 0., color);
+                    split.drag_divider(index, response.drag_delta(), rect.size());
+                }
+                for (pane, pane_rect) in split.panes.iter_mut().zip(panes) {
+                    let builder = UiBuilder::new().max_rect(pane_rect).id_salt(pane.id);
+                    self.ui(&mut ui.new_child(builder), pane);
+                }
+                ui.allocate_rect(rect, Sense::hover());
+
+                // the terminals take the keyboard when clicked, or hovered
+                let active = self.options.active_tab_id;
+                match split.focused().view_id() {
+                    Some(view_id) if split.claim_focus => {
+                        split.claim_focus = false;
+                        self.options.active_tab_id = Some(view_id);
+                    }
+                    // the new pane is still connecting, the keyboard stays
+                    // with the pane it was split from until then
+                    None if split.claim_focus => {}
+                    _ => {
+                        let focused = active.and_then(|active| {
+                            split.panes.iter().position(|pane| pane.shows_view(active))
+                        });
+                        if let Some(index) = focused {
+                            split.focused = index;
+                        }
+                    }
+                }
+                // the last pane closes the tab along with it
+                if split.panes.iter().all(|pane| pane.close_requested) {
+                    close = true;
+                } else {
+                    split.remove_closed();
+                }
             }
             TabInner::SessionList(_list) => {
                 ui.collapsing("Tab body", |ui| {
@@ -136,29 +1216,129 @@ impl egui_dock::TabViewer for TabViewer<'_> {
                 });
             }
         }
+        tab.collapse_split();
+        if close {
+            tab.close_requested = true;
+        } else if restart {
+            let command_sender = self.command_sender.clone();
+            let config = self.options.terminal_config();
+            if let Err(err) = tab.restart(ui.ctx().clone(), config, command_sender) {
+                error!("restart tab {} failed: {err}", tab.id);
+            }
+        }
     }
 
     fn on_tab_button(&mut self, tab: &mut Self::Tab, response: &Response) {
+        self.tab_buttons
+            .push((tab.id, response.rect, response.layer_id));
+        // like in browsers, going through the same path as the close button
+        if response.middle_clicked()
+            && self.closeable(tab)
+            && matches!(self.on_close(tab), OnCloseResponse::Close)
+        {
+            tab.close_requested = true;
+        }
         if response.hovered() {
             if let TabInner::Term(term) = &mut tab.inner {
-                if let TermType::Ssh { options } = &term.term_type {
-                    if let Authentication::Password(..) = options.auth {
-                        response.show_tooltip_text(format!(
-                            "{}:{}",
-                            options.host,
-                            options.port.unwrap_or(22)
-                        ));
+                if let TermType::Ssh { options, .. } = &term.term_type {
+                    let mut lines = vec![];
+                    if !matches!(options.auth, Authentication::Config) {
+                        lines.push(format!("{}:{}", options.host, options.port.unwrap_or(22)));
+                    }
+                    let tunnels = term.terminal.tunnels();
+                    if !tunnels.is_empty() {
+                        lines.push(t!("tab-tunnels"));
+                    }
+                    for tunnel in tunnels {
+                        lines.push(match tunnel.state {
+                            TunnelState::Listening => tunnel.forward.to_string(),
+                            TunnelState::Failed(err) => {
+                                t!(
+                                    "tunnel-failed",
+                                    forward = tunnel.forward.to_string(),
+                                    error = err
+                                )
+                            }
+                        });
+                    }
+                    if !lines.is_empty() {
+                        response.show_tooltip_text(lines.join("\n"));
                     }
                 }
             }
         }
     }
 
+    fn context_menu(
+        &mut self,
+        ui: &mut Ui,
+        tab: &mut Self::Tab,
+        _surface: SurfaceIndex,
+        _node: NodeIndex,
+    ) {
+        if !matches!(tab.inner, TabInner::SessionList(_)) {
+            let label = if tab.pinned {
+                t!("tab-unpin")
+            } else {
+                t!("tab-pin")
+            };
+            if ui.button(label).clicked() {
+                tab.pinned = !tab.pinned;
+                ui.close();
+            }
+        }
+        if tab.term_type().is_some() {
+            for (label, direction) in [
+                (t!("tab-split-right"), SplitDirection::Right),
+                (t!("tab-split-down"), SplitDirection::Down),
+            ] {
+                if ui.button(label).clicked() {
+                    let command_sender = self.command_sender.clone();
+                    let config = self.options.terminal_config();
+                    if let Err(err) = tab.split(ui.ctx().clone(), direction, config, command_sender)
+                    {
+                        error!("split tab {} failed: {err}", tab.id);
+                    }
+                    ui.close();
+                }
+            }
+        }
+        // e.g. for a legacy host in GBK
+        if let Some(terminal) = tab.terminal() {
+            ui.menu_button(t!("tab-encoding"), |ui| {
+                let current = terminal.encoding();
+                for encoding in TermEncoding::ALL {
+                    if ui
+                        .radio(current == encoding, encoding.to_string())
+                        .clicked()
+                    {
+                        terminal.set_encoding(encoding);
+                        ui.close();
+                    }
+                }
+            });
+        }
+    }
+
     fn closeable(&mut self, tab: &mut Self::Tab) -> bool {
-        matches!(&mut tab.inner, TabInner::Term(_))
+        // the close button is hidden, and middle-clicks are ignored
+        !tab.pinned
+            && matches!(
+                &mut tab.inner,
+                TabInner::Term(_)
+                    | TabInner::Connecting(_)
+                    | TabInner::Sftp(_)
+                    | TabInner::Split(_)
+            )
     }
 
     fn on_close(&mut self, tab: &mut Self::Tab) -> OnCloseResponse {
+        // The sftp session is closed when the explorer is dropped, the
+        // connection of a connecting tab once it is established, and the
+        // terminals of a split tab when they are dropped.
+        if let TabInner::Sftp(_) | TabInner::Connecting(_) | TabInner::Split(_) = tab.inner {
+            return OnCloseResponse::Close;
+        }
         match self.command_sender.send((tab.id, PtyEvent::Exit)) {
             Err(err) => {
                 error!("close tab {} failed: {err}", tab.id);
@@ -168,26 +1348,288 @@ impl egui_dock::TabViewer for TabViewer<'_> {
         }
     }
 
+    fn force_close(&mut self, tab: &mut Self::Tab) -> bool {
+        tab.close_requested
+    }
+
     fn scroll_bars(&self, _tab: &Self::Tab) -> [bool; 2] {
         [false, false]
     }
 }
 
+/// Last tab of the tab bar whose empty space, right of the tabs, is at `pos`
+/// on `layer`, the topmost one there. A window over the tab bar hides it.
+fn empty_tab_bar_at(
+    tab_buttons: &[(u64, Rect, LayerId)],
+    pos: Pos2,
+    layer: LayerId,
+) -> Option<u64> {
+    let tab_buttons = tab_buttons
+        .iter()
+        .filter(|(.., tab_layer)| *tab_layer == layer);
+    if tab_buttons.clone().any(|(_, rect, _)| rect.contains(pos)) {
+        return None;
+    }
+    tab_buttons
+        .filter(|(_, rect, _)| rect.y_range().contains(pos.y) && rect.max.x <= pos.x)
+        .max_by(|(_, a, _), (_, b, _)| a.max.x.total_cmp(&b.max.x))
+        .map(|(id, ..)| *id)
+}
+
+/// Settings of a saved session, loaded from the db on first use. They are
+/// the defaults until the query completes.
+fn session_settings<'b>(
+    cache: &'b mut HashMap<(String, String), SessionSettings>,
+    db: &DbConn,
+    session: &SshOptions,
+) -> &'b mut SessionSettings {
+    let key = (session.group.clone(), session.name.clone());
+    cache.entry(key.clone()).or_insert_with(|| {
+        let (group, name) = key.clone();
+        db.run(
+            move |db| {
+                let bookmarks = db.find_bookmarks(&group, &name)?;
+                Ok((bookmarks, db.find_session(&group, &name)?))
+            },
+            move |shell, loaded| {
+                let (bookmarks, session) = match loaded {
+                    Ok(loaded) => loaded,
+                    Err(err) => {
+                        error!("failed to load the settings of {}: {err}", key.1);
+                        return;
+                    }
+                };
+                let mut settings = SessionSettings {
+                    bookmarks,
+                    ..Default::default()
+                };
+                if let Some(s) = session {
+                    settings.tint = TintMode::from(s.tint_mode).tint(s.tint_color);
+                    settings.protected_keys =
+                        parse_shortcuts(&s.protected_keys).unwrap_or_else(|err| {
+                            error!("ignored the protected keys of {}: {err}", key.1);
+                            vec![]
+                        });
+                    settings.font_family = s.font_family;
+                    settings.font_size = (s.font_size > 0.).then_some(s.font_size);
+                    settings.plain_prompt = s.plain_prompt;
+                    settings.local_echo = s.local_echo;
+                    settings.nested_command = s.nested_command;
+                    settings.meta_keys = MetaKeys {
+                        esc_prefix: !s.alt_eight_bit,
+                        altgr_as_alt: s.altgr_as_alt,
+                        option_as_meta: s.option_as_meta,
+                    };
+                    settings.unicode_width = UnicodeWidth {
+                        ambiguous_wide: s.ambiguous_wide,
+                        narrow_emoji: s.narrow_emoji,
+                    };
+                    settings.idle_timeout = (s.idle_timeout > 0)
+                        .then(|| Duration::from_secs(s.idle_timeout as u64 * 60));
+                    settings.theme = ThemeMode::from(s.theme);
+                }
+                shell.opts.session_settings.insert(key, settings);
+            },
+        );
+        SessionSettings::default()
+    })
+}
+
+fn save_theme(db: &DbConn, session: &SshOptions, theme: ThemeMode) {
+    let (group, name) = (session.group.clone(), session.name.clone());
+    db.run(
+        move |db| db.update_session_theme(&group, &name, theme as u16),
+        |_, result| {
+            if let Err(err) = result {
+                error!("failed to save the theme: {err}");
+            }
+        },
+    );
+}
+
+/// Bookmark `path`, or remove the bookmark if it already exists.
+fn toggle_bookmark(bookmarks: &mut Vec<String>, db: &DbConn, session: &SshOptions, path: String) {
+    let (group, name) = (session.group.clone(), session.name.clone());
+    let callback = |_: &mut NxShell, result: Result<(), NxError>| {
+        if let Err(err) = result {
+            error!("failed to update the bookmarks: {err}");
+        }
+    };
+    match bookmarks.iter().position(|bookmark| *bookmark == path) {
+        Some(index) => {
+            bookmarks.remove(index);
+            db.run(move |db| db.delete_bookmark(&group, &name, &path), callback);
+        }
+        None => {
+            bookmarks.push(path.clone());
+            bookmarks.sort();
+            db.run(move |db| db.insert_bookmark(&group, &name, &path), callback);
+        }
+    }
+}
+
 impl NxShell {
     pub fn tab_view(&mut self, ctx: &egui::Context) {
         if self.opts.show_dock_panel {
+            let mut requests = vec![];
+            let mut viewer = TabViewer {
+                command_sender: &self.command_sender,
+                requests: &mut requests,
+                db: &self.db,
+                options: &mut self.opts,
+                fonts: &mut self.fonts,
+                clipboard: &mut self.clipboard,
+                tab_buttons: vec![],
+                visible_terminals: self.runtime.visible_terminals,
+                terminals_shown: 0,
+            };
             DockArea::new(&mut self.dock_state)
                 .show_add_buttons(false)
                 .show_leaf_collapse_buttons(false)
                 .style(Style::from_egui(ctx.style().as_ref()))
-                .show(
-                    ctx,
-                    &mut TabViewer {
-                        command_sender: &self.command_sender,
-                        options: &mut self.opts,
-                        clipboard: &mut self.clipboard,
-                    },
-                );
+                .show(ctx, &mut viewer);
+            let tab_buttons = viewer.tab_buttons;
+            self.runtime.visible_terminals = viewer.terminals_shown;
+            if self
+                .dock_state
+                .iter_all_tabs()
+                .any(|(_, tab)| tab.is_flashing())
+            {
+                ctx.request_repaint_after(FLASH_INTERVAL);
+            }
+            self.handle_tab_requests(ctx, requests);
+            self.tab_bar_double_click(ctx, &tab_buttons);
         }
     }
+
+    /// A double-click on the empty space of a tab bar opens a local terminal
+    /// next to its tabs, the new session window with the command modifier.
+    fn tab_bar_double_click(&mut self, ctx: &egui::Context, tab_buttons: &[(u64, Rect, LayerId)]) {
+        let click = ctx.input(|i| {
+            let pos = i.pointer.interact_pos()?;
+            i.pointer
+                .button_double_clicked(PointerButton::Primary)
+                .then_some((pos, i.modifiers.command))
+        });
+        let Some((pos, command)) = click else {
+            return;
+        };
+        // no area is there over the panels of the dock
+        let layer = ctx.layer_id_at(pos).unwrap_or_else(LayerId::background);
+        let Some(tab_id) = empty_tab_bar_at(tab_buttons, pos, layer) else {
+            return;
+        };
+        if command {
+            *self.opts.show_add_session_modal.borrow_mut() = true;
+            return;
+        }
+        if let Some((surface, node, _)) = self.dock_state.find_tab_from(|tab| tab.id == tab_id) {
+            self.dock_state
+                .set_focused_node_and_surface((surface, node));
+        }
+        let typ = TermType::Regular {
+            working_directory: None,
+        };
+        if let Err(err) = self.add_shell_tab(ctx.clone(), typ) {
+            self.toasts.add(error_toast(err.to_string()));
+        }
+    }
+
+    /// Search the scrollback of the focused terminal.
+    pub fn search_active_terminal(&mut self, command: BackendCommand) {
+        if let Some((_, tab)) = self.dock_state.find_active_focused() {
+            tab.process_command(command, &mut self.clipboard);
+        }
+    }
+
+    /// Split the focused terminal, see [`Tab::split`].
+    pub fn split_active_tab(&mut self, ctx: &egui::Context, direction: SplitDirection) {
+        let config = self.opts.terminal_config();
+        let Some((_, tab)) = self.dock_state.find_active_focused() else {
+            return;
+        };
+        if let Err(err) = tab.split(ctx.clone(), direction, config, self.command_sender.clone()) {
+            self.toasts.add(error_toast(err.to_string()));
+        }
+    }
+
+    /// Only the active tab is shown in presentation mode, without the dock.
+    pub fn presentation_view(&mut self, ui: &mut Ui) {
+        let mut requests = vec![];
+        if let Some((_, tab)) = self.dock_state.find_active_focused() {
+            let mut viewer = TabViewer {
+                command_sender: &self.command_sender,
+                requests: &mut requests,
+                db: &self.db,
+                options: &mut self.opts,
+                fonts: &mut self.fonts,
+                clipboard: &mut self.clipboard,
+                tab_buttons: vec![],
+                visible_terminals: self.runtime.visible_terminals,
+                terminals_shown: 0,
+            };
+            egui_dock::TabViewer::ui(&mut viewer, ui, tab);
+        }
+        self.runtime.visible_terminals = 1;
+        self.handle_tab_requests(ui.ctx(), requests);
+    }
+
+    fn handle_tab_requests(&mut self, ctx: &egui::Context, requests: Vec<TabRequest>) {
+        for request in requests {
+            let result = match request {
+                TabRequest::Terminal {
+                    options,
+                    working_directory,
+                } => {
+                    let typ = TermType::Ssh {
+                        options: Box::new(options),
+                        working_directory,
+                    };
+                    self.add_shell_tab(ctx.clone(), typ)
+                }
+                TabRequest::Sftp { options, start_dir } => {
+                    self.add_sftp_tab(ctx.clone(), options, start_dir)
+                }
+                TabRequest::SaveScrollback { name, snapshot } => {
+                    self.save_scrollback(&name, snapshot);
+                    Ok(())
+                }
+            };
+            if let Err(err) = result {
+                self.toasts.add(error_toast(err.to_string()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn double_click_right_of_the_tabs() {
+        let dock = LayerId::background();
+        let window = LayerId::new(egui::Order::Middle, Id::new("window"));
+        let tab = |x: f32, y: f32| Rect::from_min_size(Pos2::new(x, y), egui::vec2(100., 24.));
+        // two leaves side by side, and one below
+        let tab_buttons = [
+            (1, tab(0., 0.), dock),
+            (2, tab(100., 0.), dock),
+            (3, tab(500., 0.), dock),
+            (4, tab(0., 300.), dock),
+        ];
+        let at = |x: f32, y: f32| empty_tab_bar_at(&tab_buttons, Pos2::new(x, y), dock);
+
+        assert_eq!(at(300., 10.), Some(2));
+        assert_eq!(at(700., 10.), Some(3));
+        assert_eq!(at(200., 310.), Some(4));
+        // on a tab, or in the body of a tab
+        assert_eq!(at(150., 10.), None);
+        assert_eq!(at(300., 100.), None);
+        // on a window over the tab bar
+        assert_eq!(
+            empty_tab_bar_at(&tab_buttons, Pos2::new(300., 10.), window),
+            None
+        );
+    }
 }
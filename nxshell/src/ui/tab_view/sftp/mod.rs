@@ -0,0 +1,817 @@
+mod preview;
+mod sync;
+mod trash;
+
+use crate::i18n::t;
+use crate::sftp::{
+    NamePattern, SftpClient, SftpEntry, SftpEvent, TransferDirection, TransferQueue,
+    TransferStatus, TrashItem, DEFAULT_PARALLELISM, MAX_PARALLELISM,
+};
+use chrono::DateTime;
+use egui::{
+    Align, Align2, DragValue, Frame, Layout, ProgressBar, Rect, RichText, ScrollArea, Sense,
+    TextEdit, Ui, Window,
+};
+use egui_extras::{Column, TableBuilder};
+use egui_phosphor::regular::{
+    ARROWS_CLOCKWISE, ARROW_CLOCKWISE, ARROW_UP, BOOKMARK, DOWNLOAD_SIMPLE, EYE, FILE, FOLDER,
+    MAGNIFYING_GLASS, SPINNER, TERMINAL_WINDOW, TRASH, UPLOAD_SIMPLE, X,
+};
+use egui_term::{HostKeyChange, HostKeyCheck, SshOptions};
+use homedir::my_home;
+use preview::FilePreview;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+use sync::SyncDialog;
+use trash::TrashManager;
+use wezterm_ssh::Utf8PathBuf;
+
+const ROW_HEIGHT: f32 = 20.;
+/// How long a deletion can be undone from the notification.
+const UNDO_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+struct RemoteSearch {
+    query: String,
+    /// Id of the search the results belong to.
+    id: Option<u64>,
+    running: bool,
+    results: Vec<SftpEntry>,
+}
+
+pub struct SftpExplorer {
+    id: u64,
+    pub options: SshOptions,
+    ctx: egui::Context,
+    client: SftpClient,
+    connected: bool,
+    current_dir: Option<Utf8PathBuf>,
+    path_input: String,
+    entries: Vec<SftpEntry>,
+    loading: bool,
+    search: RemoteSearch,
+    transfers: TransferQueue,
+    /// Parallelism last sent to the session.
+    parallelism: usize,
+    download_dir: String,
+    sync: SyncDialog,
+    trash: TrashManager,
+    /// Last entry moved to the trash, and when.
+    undo: Option<(TrashItem, Instant)>,
+    /// Entry waiting for the permanent deletion to be confirmed.
+    confirm_delete: Option<SftpEntry>,
+    selected: Option<Utf8PathBuf>,
+    show_preview: bool,
+    preview: Option<FilePreview>,
+    /// Directory to open once connected, instead of the home directory.
+    start_dir: Option<Utf8PathBuf>,
+    /// Directory a terminal has been requested for.
+    terminal_request: Option<Utf8PathBuf>,
+    /// Directory to bookmark, or whose bookmark should be removed.
+    bookmark_toggle: Option<Utf8PathBuf>,
+    /// New or changed host key waiting for the user, see
+    /// [`SftpExplorer::answer_host_key`].
+    host_key_change: Option<(HostKeyChange, Sender<bool>)>,
+    pinned_host_key: Option<String>,
+    error: Option<String>,
+}
+
+enum TableAction {
+    Select(SftpEntry),
+    Open(SftpEntry),
+    Download(SftpEntry),
+    Delete { entry: SftpEntry, trash: bool },
+}
+
+impl PartialEq for SftpExplorer {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Drop for SftpExplorer {
+    fn drop(&mut self) {
+        self.set_preview(None);
+    }
+}
+
+impl SftpExplorer {
+    /// Round trip of the sftp session, `None` until it is measured.
+    pub fn latency(&self) -> Option<Duration> {
+        self.client.latency()
+    }
+
+    pub fn new(
+        id: u64,
+        ctx: egui::Context,
+        options: SshOptions,
+        start_dir: Option<Utf8PathBuf>,
+    ) -> std::io::Result<Self> {
+        let client = SftpClient::connect(id, ctx.clone(), options.clone())?;
+        Ok(Self {
+            id,
+            ctx,
+            options,
+            client,
+            connected: false,
+            current_dir: None,
+            path_input: String::new(),
+            entries: vec![],
+            loading: true,
+            search: RemoteSearch::default(),
+            transfers: TransferQueue::default(),
+            parallelism: DEFAULT_PARALLELISM,
+            download_dir: default_download_dir().display().to_string(),
+            sync: SyncDialog::new(id, default_download_dir().display().to_string()),
+            trash: TrashManager::new(id),
+            undo: None,
+            confirm_delete: None,
+            selected: None,
+            show_preview: false,
+            preview: None,
+            start_dir,
+            terminal_request: None,
+            bookmark_toggle: None,
+            host_key_change: None,
+            pinned_host_key: None,
+            error: None,
+        })
+    }
+
+    /// The host key is new or changed, the connection waits until
+    /// [`SftpExplorer::answer_host_key`] is called.
+    pub fn host_key_change(&self) -> Option<&HostKeyChange> {
+        self.host_key_change.as_ref().map(|(change, _)| change)
+    }
+
+    /// Trust the host key and go on connecting, or refuse it.
+    pub fn answer_host_key(&mut self, trust: bool) {
+        if let Some((_, answer)) = self.host_key_change.take() {
+            let _ = answer.send(trust);
+        }
+    }
+
+    /// Fingerprint of the host key to save with the session, once trusted.
+    pub fn take_pinned_host_key(&mut self) -> Option<String> {
+        let pinned = self.pinned_host_key.take();
+        if let Some(fingerprint) = &pinned {
+            // a duplicate of the tab expects the same key
+            self.options.host_key = HostKeyCheck::Pinned(Some(fingerprint.clone()));
+        }
+        pinned
+    }
+
+    fn open_dir(&mut self, path: Utf8PathBuf) {
+        self.selected = None;
+        self.loading = true;
+        self.error = None;
+        self.client.read_dir(path);
+    }
+
+    fn start_search(&mut self) {
+        let Some(root) = self.current_dir.clone() else {
+            return;
+        };
+        if self.search.query.trim().is_empty() {
+            self.stop_search();
+            return;
+        }
+        let id = self
+            .client
+            .search(root, NamePattern::new(&self.search.query));
+        self.search.id = Some(id);
+        self.search.running = true;
+        self.search.results.clear();
+    }
+
+    fn stop_search(&mut self) {
+        self.client.cancel_search();
+        self.search.id = None;
+        self.search.running = false;
+        self.search.results.clear();
+    }
+
+    /// Apply the events of the session, also polled while the tab is hidden
+    /// for its connection to go on.
+    pub fn poll_events(&mut self) {
+        while let Some(event) = self.client.try_recv() {
+            match event {
+                SftpEvent::TrustHostKey(change, answer) => {
+                    self.host_key_change = Some((change, answer));
+                }
+                SftpEvent::HostKeyPinned(fingerprint) => self.pinned_host_key = Some(fingerprint),
+                SftpEvent::Connected(home) => {
+                    self.connected = true;
+                    let dir = self.start_dir.take().unwrap_or(home);
+                    self.open_dir(dir);
+                }
+                SftpEvent::ReadDir(path, entries) => {
+                    self.loading = false;
+                    self.path_input = path.to_string();
+                    self.current_dir = Some(path);
+                    self.entries = entries;
+                }
+                SftpEvent::SearchHit(id, entry) => {
+                    if self.search.id == Some(id) {
+                        self.search.results.push(entry);
+                    }
+                }
+                SftpEvent::SearchDone(id) => {
+                    if self.search.id == Some(id) {
+                        self.search.running = false;
+                    }
+                }
+                SftpEvent::Transfer(event) => self.transfers.apply(event),
+                SftpEvent::SyncPlan(plan) => self.sync.set_plan(plan),
+                SftpEvent::Preview(path, data) => {
+                    if let Some(preview) = &mut self.preview {
+                        if preview.path() == &path {
+                            preview.loaded(&self.ctx, data);
+                        }
+                    }
+                }
+                SftpEvent::Deleted(path) => self.removed(&path),
+                SftpEvent::Trashed(item) => {
+                    self.removed(&item.original);
+                    self.trash.refresh(&self.client);
+                    self.undo = Some((item, Instant::now()));
+                }
+                SftpEvent::Trash(items) => self.trash.set_items(items),
+                SftpEvent::Restored(item) => {
+                    self.trash.restored(&item);
+                    if self
+                        .undo
+                        .as_ref()
+                        .is_some_and(|(undo, _)| undo.dir == item.dir)
+                    {
+                        self.undo = None;
+                    }
+                    self.refresh();
+                }
+                SftpEvent::Error(err) => {
+                    self.sync.failed();
+                    self.trash.failed();
+                    self.loading = false;
+                    self.error = Some(err);
+                }
+            }
+        }
+    }
+
+    fn refresh(&mut self) {
+        if let Some(dir) = self.current_dir.clone() {
+            self.open_dir(dir);
+        }
+    }
+
+    /// An entry has been deleted or moved to the trash.
+    fn removed(&mut self, path: &Utf8PathBuf) {
+        self.search.results.retain(|entry| &entry.path != path);
+        if self
+            .preview
+            .as_ref()
+            .is_some_and(|preview| preview.path() == path)
+        {
+            self.set_preview(None);
+        }
+        self.refresh();
+    }
+
+    /// `parallelism` is shared by all explorers of the same host, `bookmarks` by
+    /// those of the same session.
+    pub fn ui(&mut self, ui: &mut Ui, parallelism: &mut usize, bookmarks: &[String]) {
+        self.poll_events();
+        let rect = ui.max_rect();
+        if self.parallelism != *parallelism {
+            self.parallelism = *parallelism;
+            self.client.set_parallelism(*parallelism);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(self.connected, |ui| self.toolbar(ui, bookmarks));
+        });
+        if let Some(err) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+        ui.separator();
+
+        if self.connected {
+            if self.sync.open {
+                self.sync.ui(ui.ctx(), &self.client);
+            }
+            if self.trash.open {
+                self.trash.ui(ui.ctx(), &self.client);
+            }
+            self.confirm_delete(ui.ctx());
+            self.undo_notification(ui.ctx(), rect);
+            self.upload_dropped_files(ui);
+            egui::TopBottomPanel::bottom(ui.id().with("transfers"))
+                .resizable(true)
+                .show_inside(ui, |ui| self.transfer_queue(ui, parallelism));
+            if self.show_preview {
+                egui::SidePanel::right(ui.id().with("preview"))
+                    .resizable(true)
+                    .default_width(320.)
+                    .show_inside(ui, |ui| match &self.preview {
+                        Some(preview) => preview.ui(ui),
+                        None => {
+                            ui.weak(t!("sftp-preview-placeholder"));
+                        }
+                    });
+            }
+        }
+
+        if !self.connected && self.error.is_none() {
+            ui.centered_and_justified(|ui| ui.spinner());
+            return;
+        }
+
+        let selected = self.selected.as_ref();
+        let (action, list_focused) = if self.search.id.is_some() {
+            entries_table(ui, &self.search.results, true, selected)
+        } else {
+            entries_table(ui, &self.entries, false, selected)
+        };
+
+        match action {
+            Some(TableAction::Select(entry)) => {
+                self.selected = Some(entry.path.clone());
+                if self.show_preview {
+                    self.set_preview(Some(entry));
+                }
+            }
+            Some(TableAction::Open(entry)) => self.open_entry(entry),
+            Some(TableAction::Download(entry)) => {
+                let local = PathBuf::from(&self.download_dir).join(&entry.name);
+                self.client.download(entry.path, local);
+            }
+            Some(TableAction::Delete { entry, trash: true }) => {
+                self.client.delete(entry.path, entry.is_dir, true);
+            }
+            Some(TableAction::Delete { entry, .. }) => self.confirm_delete = Some(entry),
+            None => {}
+        }
+
+        // the Delete key moves the selected entry to the trash, once a row of
+        // this list has the focus rather than a text input or another list
+        if !list_focused || !ui.input(|i| i.key_pressed(egui::Key::Delete)) {
+            return;
+        }
+        if let Some(path) = self.selected.clone() {
+            let mut entries = self.entries.iter().chain(&self.search.results);
+            if let Some(entry) = entries.find(|entry| entry.path == path) {
+                self.client.delete(path, entry.is_dir, true);
+            }
+        }
+    }
+
+    fn confirm_delete(&mut self, ctx: &egui::Context) {
+        let Some(entry) = &self.confirm_delete else {
+            return;
+        };
+        let mut open = true;
+        let mut close = false;
+        Window::new(t!("sftp-delete-title"))
+            .id(egui::Id::new("sftp_delete").with(self.id))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .show(ctx, |ui| {
+                let path = entry.path.to_string();
+                ui.label(if entry.is_dir {
+                    t!("sftp-delete-dir", path = path)
+                } else {
+                    t!("sftp-delete-file", path = path)
+                });
+                ui.horizontal(|ui| {
+                    if ui.button(t!("sftp-delete")).clicked() {
+                        self.client.delete(entry.path.clone(), entry.is_dir, false);
+                        close = true;
+                    }
+                    if ui.button(t!("sftp-cancel")).clicked() {
+                        close = true;
+                    }
+                });
+            });
+        if close || !open {
+            self.confirm_delete = None;
+        }
+    }
+
+    /// Toast-like notification offering to restore the last trashed entry.
+    fn undo_notification(&mut self, ctx: &egui::Context, rect: Rect) {
+        let Some((item, trashed_at)) = &self.undo else {
+            return;
+        };
+        let elapsed = trashed_at.elapsed();
+        if elapsed >= UNDO_TIMEOUT {
+            self.undo = None;
+            return;
+        }
+        ctx.request_repaint_after(UNDO_TIMEOUT - elapsed);
+
+        let mut undo = false;
+        egui::Area::new(egui::Id::new("sftp_undo").with(self.id))
+            .order(egui::Order::Foreground)
+            .pivot(Align2::RIGHT_BOTTOM)
+            .fixed_pos(rect.right_bottom() - egui::vec2(12., 12.))
+            .show(ctx, |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let trashed = t!("sftp-trashed", name = item.name());
+                        ui.label(format!("{TRASH} {trashed}"));
+                        undo = ui.button(t!("sftp-undo")).clicked();
+                    });
+                });
+            });
+        if undo {
+            self.client.restore(item.clone());
+            self.undo = None;
+        }
+    }
+
+    fn set_preview(&mut self, entry: Option<SftpEntry>) {
+        if let Some(preview) = self.preview.take() {
+            preview.close(&self.ctx);
+        }
+        self.preview = entry.map(|entry| FilePreview::new(entry, &self.client));
+    }
+
+    /// Number of the transfers queued or running.
+    pub fn pending_transfers(&self) -> usize {
+        self.transfers.pending()
+    }
+
+    /// Directory a new terminal should be opened at, requested from the toolbar.
+    pub fn take_terminal_request(&mut self) -> Option<Utf8PathBuf> {
+        self.terminal_request.take()
+    }
+
+    /// Directory whose bookmark has been toggled from the toolbar.
+    pub fn take_bookmark_toggle(&mut self) -> Option<Utf8PathBuf> {
+        self.bookmark_toggle.take()
+    }
+
+    fn open_entry(&mut self, entry: SftpEntry) {
+        let dir = if entry.is_dir {
+            Some(entry.path)
+        } else {
+            // reveal the file in its parent directory
+            entry.path.parent().map(|p| p.to_path_buf())
+        };
+        if let Some(dir) = dir {
+            self.stop_search();
+            self.open_dir(dir);
+        }
+    }
+
+    /// Files dropped on the explorer are uploaded to the current directory.
+    fn upload_dropped_files(&mut self, ui: &mut Ui) {
+        let Some(dir) = self.current_dir.clone() else {
+            return;
+        };
+        if !ui.ui_contains_pointer() {
+            return;
+        }
+        let dropped = ui.input(|i| i.raw.dropped_files.clone());
+        for path in dropped.into_iter().filter_map(|file| file.path) {
+            let Some(name) = path.file_name() else {
+                continue;
+            };
+            let remote = dir.join(name.to_string_lossy());
+            self.client.upload(path, remote);
+        }
+    }
+
+    fn transfer_queue(&mut self, ui: &mut Ui, parallelism: &mut usize) {
+        if self.transfers.is_running() {
+            self.transfers.sample(Instant::now());
+            ui.ctx().request_repaint();
+        }
+
+        ui.horizontal(|ui| {
+            ui.strong(t!("sftp-transfers"));
+            let (transferred, total) = self.transfers.totals();
+            if total > 0 {
+                ui.label(format!(
+                    "{} / {}",
+                    format_size(transferred),
+                    format_size(total)
+                ));
+            }
+            if self.transfers.is_running() {
+                ui.label(format!(
+                    "{}/s",
+                    format_size(self.transfers.throughput() as u64)
+                ));
+            }
+
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                if ui.button(t!("sftp-clear-finished")).clicked() {
+                    self.transfers.clear_finished();
+                }
+                if ui
+                    .add_enabled(
+                        self.transfers.is_running(),
+                        egui::Button::new(t!("sftp-cancel-all")),
+                    )
+                    .clicked()
+                {
+                    self.client.cancel_transfers();
+                }
+                ui.add(DragValue::new(parallelism).range(1..=MAX_PARALLELISM))
+                    .on_hover_text(t!("sftp-parallel-hint"));
+                ui.label(t!("sftp-parallel"));
+                ui.add(TextEdit::singleline(&mut self.download_dir).desired_width(200.))
+                    .on_hover_text(t!("sftp-download-dir-hint"));
+                ui.label(DOWNLOAD_SIMPLE);
+            });
+        });
+        ui.separator();
+
+        if self.transfers.transfers.is_empty() {
+            ui.weak(t!("sftp-drop-hint"));
+            return;
+        }
+        ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+            for transfer in &self.transfers.transfers {
+                ui.horizontal(|ui| {
+                    let (icon, path) = match transfer.direction {
+                        TransferDirection::Upload => (UPLOAD_SIMPLE, transfer.remote.to_string()),
+                        TransferDirection::Download => {
+                            (DOWNLOAD_SIMPLE, transfer.local.display().to_string())
+                        }
+                    };
+                    ui.label(icon);
+                    let status = match &transfer.status {
+                        TransferStatus::Queued => t!("sftp-queued"),
+                        TransferStatus::Running if transfer.resumed_from > 0 => {
+                            t!("sftp-resumed", size = format_size(transfer.resumed_from))
+                        }
+                        TransferStatus::Running => format_size(transfer.transferred),
+                        TransferStatus::Done => t!("sftp-done"),
+                        TransferStatus::Failed(err) => err.clone(),
+                        TransferStatus::Cancelled => t!("sftp-cancelled"),
+                    };
+                    ui.add(
+                        ProgressBar::new(transfer.progress())
+                            .desired_width(160.)
+                            .show_percentage(),
+                    )
+                    .on_hover_text(status);
+                    ui.add(egui::Label::new(path).truncate());
+                });
+            }
+        });
+    }
+
+    fn toolbar(&mut self, ui: &mut Ui, bookmarks: &[String]) {
+        let parent = self
+            .current_dir
+            .as_ref()
+            .and_then(|dir| dir.parent())
+            .map(|p| p.to_path_buf());
+        if ui
+            .add_enabled(parent.is_some(), egui::Button::new(ARROW_UP))
+            .on_hover_text(t!("sftp-parent-dir"))
+            .clicked()
+        {
+            if let Some(parent) = parent {
+                self.open_dir(parent);
+            }
+        }
+        if ui
+            .button(ARROW_CLOCKWISE)
+            .on_hover_text(t!("sftp-refresh"))
+            .clicked()
+        {
+            self.refresh();
+        }
+
+        if ui
+            .toggle_value(&mut self.show_preview, EYE)
+            .on_hover_text(t!("sftp-preview-hint"))
+            .changed()
+            && !self.show_preview
+        {
+            self.set_preview(None);
+        }
+        if ui
+            .button(TERMINAL_WINDOW)
+            .on_hover_text(t!("sftp-open-terminal-hint"))
+            .clicked()
+        {
+            self.terminal_request = self.current_dir.clone();
+        }
+        if ui
+            .button(ARROWS_CLOCKWISE)
+            .on_hover_text(t!("sftp-sync-hint"))
+            .clicked()
+        {
+            if let Some(dir) = &self.current_dir {
+                self.sync.show_for(dir);
+            }
+        }
+        if ui.button(TRASH).on_hover_text(t!("sftp-trash")).clicked() {
+            self.trash.show(&self.client);
+        }
+        ui.menu_button(BOOKMARK, |ui| self.bookmarks_menu(ui, bookmarks))
+            .response
+            .on_hover_text(t!("sftp-bookmarks"));
+
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            if self.search.id.is_some()
+                && ui
+                    .button(X)
+                    .on_hover_text(t!("sftp-clear-search"))
+                    .clicked()
+            {
+                self.stop_search();
+            }
+            if self.search.running {
+                ui.label(SPINNER);
+            }
+            let search = ui.add(
+                TextEdit::singleline(&mut self.search.query)
+                    .hint_text(format!("{MAGNIFYING_GLASS} {}", t!("sftp-search-hint")))
+                    .desired_width(200.),
+            );
+            if search.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                self.start_search();
+            }
+
+            let path = ui.add(
+                TextEdit::singleline(&mut self.path_input).desired_width(ui.available_width()),
+            );
+            if path.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                let path = Utf8PathBuf::from(self.path_input.trim());
+                self.stop_search();
+                self.open_dir(path);
+            }
+        });
+
+        if self.loading {
+            ui.spinner();
+        }
+    }
+
+    fn bookmarks_menu(&mut self, ui: &mut Ui, bookmarks: &[String]) {
+        let Some(dir) = self.current_dir.clone() else {
+            return;
+        };
+        let label = if bookmarks.iter().any(|path| path == dir.as_str()) {
+            t!("sftp-remove-bookmark")
+        } else {
+            t!("sftp-add-bookmark")
+        };
+        if ui.button(label).clicked() {
+            self.bookmark_toggle = Some(dir);
+            ui.close();
+        }
+        if !bookmarks.is_empty() {
+            ui.separator();
+        }
+        for path in bookmarks {
+            if ui.button(path).clicked() {
+                self.stop_search();
+                self.open_dir(Utf8PathBuf::from(path));
+                ui.close();
+            }
+        }
+    }
+}
+
+/// Show the entries, returns the action requested on one of them and
+/// whether one of the rows has the keyboard focus.
+fn entries_table(
+    ui: &mut Ui,
+    entries: &[SftpEntry],
+    full_path: bool,
+    selected: Option<&Utf8PathBuf>,
+) -> (Option<TableAction>, bool) {
+    let mut action = None;
+    let mut focused = false;
+    TableBuilder::new(ui)
+        .striped(true)
+        .resizable(true)
+        .sense(Sense::click())
+        .cell_layout(Layout::left_to_right(Align::Center))
+        .column(Column::remainder().at_least(200.))
+        .column(Column::initial(90.))
+        .column(Column::initial(150.))
+        .column(Column::initial(90.))
+        .header(ROW_HEIGHT, |mut header| {
+            let titles = [
+                t!("sftp-column-name"),
+                t!("sftp-column-size"),
+                t!("sftp-column-modified"),
+                t!("sftp-column-permissions"),
+            ];
+            for title in titles {
+                header.col(|ui| {
+                    ui.strong(title);
+                });
+            }
+        })
+        .body(|body| {
+            body.rows(ROW_HEIGHT, entries.len(), |mut row| {
+                let entry = &entries[row.index()];
+                row.set_selected(selected == Some(&entry.path));
+                row.col(|ui| {
+                    let icon = if entry.is_dir { FOLDER } else { FILE };
+                    let name = if full_path {
+                        entry.path.as_str()
+                    } else {
+                        entry.name.as_str()
+                    };
+                    ui.add(egui::Label::new(format!("{icon} {name}")).truncate());
+                });
+                row.col(|ui| {
+                    if !entry.is_dir {
+                        ui.label(entry.size.map(format_size).unwrap_or_default());
+                    }
+                });
+                row.col(|ui| {
+                    ui.label(entry.modified.map(format_time).unwrap_or_default());
+                });
+                row.col(|ui| {
+                    let mode = entry.permissions.map(format_mode).unwrap_or_default();
+                    ui.label(RichText::new(mode).monospace());
+                });
+                let response = row.response();
+                if response.clicked() {
+                    response.request_focus();
+                }
+                focused |= response.has_focus();
+                if response.double_clicked() {
+                    action = Some(TableAction::Open(entry.clone()));
+                } else if response.clicked() {
+                    action = Some(TableAction::Select(entry.clone()));
+                }
+                response.context_menu(|ui| {
+                    if ui.button(t!("sftp-download")).clicked() {
+                        action = Some(TableAction::Download(entry.clone()));
+                        ui.close();
+                    }
+                    ui.separator();
+                    let labels = [
+                        (t!("sftp-move-to-trash"), true),
+                        (t!("sftp-delete-permanently"), false),
+                    ];
+                    for (label, trash) in labels {
+                        if ui.button(label).clicked() {
+                            let entry = entry.clone();
+                            action = Some(TableAction::Delete { entry, trash });
+                            ui.close();
+                        }
+                    }
+                });
+            });
+        });
+    (action, focused)
+}
+
+fn default_download_dir() -> PathBuf {
+    let home = my_home().ok().flatten().unwrap_or_default();
+    let downloads = home.join("Downloads");
+    if downloads.is_dir() {
+        downloads
+    } else {
+        home
+    }
+}
+
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = size as f64;
+    let mut unit = 0;
+    while size >= 1024. && unit < UNITS.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{size} {}", UNITS[unit])
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+fn format_time(secs: u64) -> String {
+    DateTime::from_timestamp(secs as i64, 0)
+        .map(|time| time.with_timezone(&chrono::Local))
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+fn format_mode(mode: u32) -> String {
+    let mut s = String::with_capacity(9);
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        s.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        s.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        s.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+    }
+    s
+}
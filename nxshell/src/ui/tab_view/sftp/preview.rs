@@ -0,0 +1,198 @@
+use super::format_size;
+use crate::i18n::t;
+use crate::sftp::{SftpClient, SftpEntry};
+use egui::{Image, RichText, ScrollArea, TextStyle, Ui};
+use egui_extras::syntax_highlighting::{highlight, CodeTheme};
+use wezterm_ssh::Utf8PathBuf;
+
+/// Bytes fetched to preview a text or binary file.
+const TEXT_PREVIEW_LIMIT: u64 = 64 * 1024;
+/// Bytes shown in the hex dump of a binary file.
+const HEX_PREVIEW_LIMIT: usize = 4 * 1024;
+/// Images larger than this are not previewed, they can't be decoded partially.
+const IMAGE_PREVIEW_LIMIT: u64 = 8 * 1024 * 1024;
+
+const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "gif", "bmp", "webp", "ico"];
+
+enum PreviewContent {
+    Loading,
+    Text(String),
+    Image(String),
+    Binary(String),
+    Message(String),
+}
+
+/// Preview of the file selected in the sftp explorer.
+pub struct FilePreview {
+    entry: SftpEntry,
+    content: PreviewContent,
+}
+
+impl FilePreview {
+    pub fn new(entry: SftpEntry, client: &SftpClient) -> Self {
+        let size = entry.size.unwrap_or_default();
+        let content = if entry.is_dir {
+            PreviewContent::Message(t!("preview-directory"))
+        } else if is_image(&entry.name) && size > IMAGE_PREVIEW_LIMIT {
+            PreviewContent::Message(t!("preview-image-too-large"))
+        } else {
+            let limit = if is_image(&entry.name) {
+                IMAGE_PREVIEW_LIMIT
+            } else {
+                TEXT_PREVIEW_LIMIT
+            };
+            client.preview(entry.path.clone(), limit);
+            PreviewContent::Loading
+        };
+        Self { entry, content }
+    }
+
+    pub fn path(&self) -> &Utf8PathBuf {
+        &self.entry.path
+    }
+
+    pub fn loaded(&mut self, ctx: &egui::Context, data: Result<Vec<u8>, String>) {
+        self.content = match data {
+            Err(err) => PreviewContent::Message(err),
+            Ok(data) if is_image(&self.entry.name) => {
+                let uri = format!("bytes://sftp/{}", self.entry.path);
+                ctx.include_bytes(uri.clone(), data);
+                PreviewContent::Image(uri)
+            }
+            Ok(data) => match text(&data) {
+                Some(text) => PreviewContent::Text(text),
+                None => {
+                    PreviewContent::Binary(hex_dump(&data[..data.len().min(HEX_PREVIEW_LIMIT)]))
+                }
+            },
+        };
+    }
+
+    /// Free the decoded image, if any.
+    pub fn close(&self, ctx: &egui::Context) {
+        if let PreviewContent::Image(uri) = &self.content {
+            ctx.forget_image(uri);
+        }
+    }
+
+    pub fn ui(&self, ui: &mut Ui) {
+        let size = self.entry.size.unwrap_or_default();
+        ui.strong(&self.entry.name);
+        if !self.entry.is_dir {
+            ui.weak(format_size(size));
+        }
+        ui.separator();
+
+        let truncated = size > TEXT_PREVIEW_LIMIT;
+        match &self.content {
+            PreviewContent::Loading => {
+                ui.spinner();
+            }
+            PreviewContent::Message(message) => {
+                ui.weak(message);
+            }
+            PreviewContent::Image(uri) => {
+                ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+                    ui.add(Image::new(uri.as_str()).shrink_to_fit());
+                });
+            }
+            PreviewContent::Text(text) => {
+                if truncated {
+                    ui.weak(t!(
+                        "preview-truncated",
+                        size = format_size(TEXT_PREVIEW_LIMIT)
+                    ));
+                }
+                let language = self.entry.name.rsplit('.').next().unwrap_or_default();
+                let theme = CodeTheme::from_memory(ui.ctx(), ui.style());
+                let job = highlight(ui.ctx(), ui.style(), &theme, text, language);
+                ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+                    ui.label(job);
+                });
+            }
+            PreviewContent::Binary(hex) => {
+                if size > HEX_PREVIEW_LIMIT as u64 {
+                    let size = format_size(HEX_PREVIEW_LIMIT as u64);
+                    ui.weak(t!("preview-truncated", size = size));
+                }
+                ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+                    ui.label(RichText::new(hex).text_style(TextStyle::Monospace));
+                });
+            }
+        }
+    }
+}
+
+fn is_image(name: &str) -> bool {
+    let name = name.to_lowercase();
+    IMAGE_EXTENSIONS
+        .iter()
+        .any(|ext| name.rsplit_once('.').is_some_and(|(_, e)| e == *ext))
+}
+
+/// Decode the data as text, binary data contains NUL bytes or invalid UTF-8.
+fn text(data: &[u8]) -> Option<String> {
+    if data.contains(&0) {
+        return None;
+    }
+    match std::str::from_utf8(data) {
+        Ok(text) => Some(text.to_string()),
+        // the ranged read may have cut a multi-byte character
+        Err(err) if err.error_len().is_none() => {
+            Some(String::from_utf8_lossy(&data[..err.valid_up_to()]).into_owned())
+        }
+        Err(_) => None,
+    }
+}
+
+/// Classic `hexdump -C` layout: offset, 16 bytes in hex and their ASCII form.
+fn hex_dump(data: &[u8]) -> String {
+    let mut dump = String::with_capacity(data.len() * 4);
+    for (i, chunk) in data.chunks(16).enumerate() {
+        dump.push_str(&format!("{:08x}  ", i * 16));
+        for j in 0..16 {
+            match chunk.get(j) {
+                Some(byte) => dump.push_str(&format!("{byte:02x} ")),
+                None => dump.push_str("   "),
+            }
+            if j == 7 {
+                dump.push(' ');
+            }
+        }
+        dump.push_str(" |");
+        dump.extend(chunk.iter().map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            }
+        }));
+        dump.push_str("|\n");
+    }
+    dump
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hex_dump, text};
+
+    #[test]
+    fn hex_dump_layout() {
+        assert_eq!(
+            hex_dump(b"hello, world!\n\x00\x01\xff"),
+            "00000000  68 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a 00 01  |hello, world!...|\n\
+             00000010  ff                                                |.|\n"
+        );
+    }
+
+    #[test]
+    fn detect_text() {
+        assert_eq!(text(b"fn main() {}"), Some("fn main() {}".to_string()));
+        assert_eq!(text(b"\x7fELF\x02\x00"), None);
+        // a character cut by the ranged read
+        assert_eq!(
+            text("é".as_bytes().split_last().unwrap().1),
+            Some(String::new())
+        );
+    }
+}
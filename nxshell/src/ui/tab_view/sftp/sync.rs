@@ -0,0 +1,182 @@
+use super::format_size;
+use crate::i18n::t;
+use crate::sftp::{SftpClient, SyncAction, SyncPlan, TransferDirection};
+use egui::{Color32, Grid, RichText, ScrollArea, TextEdit, Ui, Window};
+use std::path::PathBuf;
+use wezterm_ssh::Utf8PathBuf;
+
+const ADDED_COLOR: Color32 = Color32::from_rgb(0x40, 0xa0, 0x2b);
+const UPDATED_COLOR: Color32 = Color32::from_rgb(0xdf, 0x8e, 0x1d);
+const DELETED_COLOR: Color32 = Color32::from_rgb(0xd2, 0x0f, 0x39);
+
+/// Window comparing a local and a remote directory, then synchronizing them.
+pub struct SyncDialog {
+    id: u64,
+    pub open: bool,
+    direction: TransferDirection,
+    local: String,
+    remote: String,
+    delete: bool,
+    planning: bool,
+    plan: Option<SyncPlan>,
+}
+
+impl SyncDialog {
+    pub fn new(id: u64, local: String) -> Self {
+        Self {
+            id,
+            open: false,
+            direction: TransferDirection::Upload,
+            local,
+            remote: String::new(),
+            delete: false,
+            planning: false,
+            plan: None,
+        }
+    }
+
+    pub fn show_for(&mut self, remote: &Utf8PathBuf) {
+        self.open = true;
+        self.remote = remote.to_string();
+        self.plan = None;
+    }
+
+    pub fn set_plan(&mut self, plan: SyncPlan) {
+        self.planning = false;
+        self.plan = Some(plan);
+    }
+
+    /// Called when the session reported an error.
+    pub fn failed(&mut self) {
+        self.planning = false;
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context, client: &SftpClient) {
+        let mut open = self.open;
+        Window::new(t!("sync-title"))
+            .id(egui::Id::new("sftp_sync").with(self.id))
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(480.)
+            .show(ctx, |ui| self.form(ui, client));
+        self.open &= open;
+    }
+
+    fn form(&mut self, ui: &mut Ui, client: &SftpClient) {
+        let mut changed = false;
+        Grid::new("sync_form").num_columns(2).show(ui, |ui| {
+            ui.label(t!("sync-local-dir"));
+            changed |= ui
+                .add(TextEdit::singleline(&mut self.local).desired_width(f32::INFINITY))
+                .changed();
+            ui.end_row();
+
+            ui.label(t!("sync-remote-dir"));
+            changed |= ui
+                .add(TextEdit::singleline(&mut self.remote).desired_width(f32::INFINITY))
+                .changed();
+            ui.end_row();
+
+            ui.label(t!("sync-direction"));
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .radio_value(
+                        &mut self.direction,
+                        TransferDirection::Upload,
+                        t!("sync-upload"),
+                    )
+                    .changed();
+                changed |= ui
+                    .radio_value(
+                        &mut self.direction,
+                        TransferDirection::Download,
+                        t!("sync-download"),
+                    )
+                    .changed();
+            });
+            ui.end_row();
+
+            ui.label("");
+            changed |= ui.checkbox(&mut self.delete, t!("sync-delete")).changed();
+            ui.end_row();
+        });
+        // the plan no longer matches the form
+        if changed {
+            self.plan = None;
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            let ready = !self.local.trim().is_empty() && !self.remote.trim().is_empty();
+            if ui
+                .add_enabled(
+                    ready && !self.planning,
+                    egui::Button::new(t!("sync-dry-run")),
+                )
+                .clicked()
+            {
+                self.planning = true;
+                self.plan = None;
+                client.sync_plan(
+                    self.direction,
+                    PathBuf::from(self.local.trim()),
+                    Utf8PathBuf::from(self.remote.trim()),
+                    self.delete,
+                );
+            }
+            let can_sync = self
+                .plan
+                .as_ref()
+                .is_some_and(|plan| !plan.actions.is_empty());
+            if ui
+                .add_enabled(can_sync, egui::Button::new(t!("sync-apply")))
+                .on_hover_text(t!("sync-apply-hint"))
+                .clicked()
+            {
+                if let Some(plan) = self.plan.take() {
+                    client.sync(plan);
+                    self.open = false;
+                }
+            }
+            if self.planning {
+                ui.spinner();
+            }
+        });
+
+        if let Some(plan) = &self.plan {
+            plan_summary(ui, plan);
+        }
+    }
+}
+
+fn plan_summary(ui: &mut Ui, plan: &SyncPlan) {
+    if plan.actions.is_empty() {
+        ui.label(t!("sync-nothing"));
+        return;
+    }
+    let (copies, size) = plan.copy_stats();
+    ui.label(t!(
+        "sync-summary",
+        copies = copies,
+        size = format_size(size),
+        deletes = plan.delete_count()
+    ));
+
+    ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+        for action in &plan.actions {
+            let (sign, color, size) = match action {
+                SyncAction::Delete { .. } => ("-", DELETED_COLOR, None),
+                SyncAction::CreateDir(_) => ("+", ADDED_COLOR, None),
+                SyncAction::Create { size, .. } => ("+", ADDED_COLOR, Some(*size)),
+                SyncAction::Update { size, .. } => ("~", UPDATED_COLOR, Some(*size)),
+            };
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(sign).color(color).monospace());
+                ui.label(RichText::new(action.path()).color(color));
+                if let Some(size) = size {
+                    ui.weak(format_size(size));
+                }
+            });
+        }
+    });
+}
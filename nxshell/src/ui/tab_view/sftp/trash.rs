@@ -0,0 +1,149 @@
+use super::format_time;
+use crate::i18n::t;
+use crate::sftp::{SftpClient, TrashItem};
+use egui::{Align, Align2, Layout, ScrollArea, Ui, Window};
+use egui_phosphor::regular::{ARROW_COUNTER_CLOCKWISE, FILE, FOLDER, TRASH};
+
+/// Window listing the remote trash, entries can be restored or purged.
+pub struct TrashManager {
+    id: u64,
+    pub open: bool,
+    loading: bool,
+    items: Vec<TrashItem>,
+    /// Set when emptying the trash waits to be confirmed.
+    confirm_empty: bool,
+}
+
+impl TrashManager {
+    pub fn new(id: u64) -> Self {
+        Self {
+            id,
+            open: false,
+            loading: false,
+            items: vec![],
+            confirm_empty: false,
+        }
+    }
+
+    pub fn show(&mut self, client: &SftpClient) {
+        self.open = true;
+        self.refresh(client);
+    }
+
+    /// Reload the listing if the window is open.
+    pub fn refresh(&mut self, client: &SftpClient) {
+        if self.open {
+            self.loading = true;
+            client.list_trash();
+        }
+    }
+
+    pub fn set_items(&mut self, items: Vec<TrashItem>) {
+        self.loading = false;
+        self.items = items;
+    }
+
+    pub fn restored(&mut self, item: &TrashItem) {
+        self.items.retain(|i| i.dir != item.dir);
+    }
+
+    /// Called when the session reported an error.
+    pub fn failed(&mut self) {
+        self.loading = false;
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context, client: &SftpClient) {
+        let mut open = self.open;
+        Window::new(t!("trash-title"))
+            .id(egui::Id::new("sftp_trash").with(self.id))
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(520.)
+            .show(ctx, |ui| self.list(ui, client));
+        self.open &= open;
+        self.confirm_empty(ctx, client);
+    }
+
+    fn confirm_empty(&mut self, ctx: &egui::Context, client: &SftpClient) {
+        if !self.confirm_empty {
+            return;
+        }
+        let mut open = true;
+        let mut close = false;
+        Window::new(t!("trash-empty-title"))
+            .id(egui::Id::new("sftp_trash_empty").with(self.id))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .show(ctx, |ui| {
+                ui.label(t!("trash-empty-confirm", count = self.items.len()));
+                ui.horizontal(|ui| {
+                    if ui.button(t!("sftp-delete")).clicked() {
+                        self.loading = true;
+                        client.purge(self.items.clone());
+                        close = true;
+                    }
+                    if ui.button(t!("sftp-cancel")).clicked() {
+                        close = true;
+                    }
+                });
+            });
+        if close || !open || !self.open {
+            self.confirm_empty = false;
+        }
+    }
+
+    fn list(&mut self, ui: &mut Ui, client: &SftpClient) {
+        ui.horizontal(|ui| {
+            ui.label(t!("trash-count", count = self.items.len()));
+            if self.loading {
+                ui.spinner();
+            }
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                if ui
+                    .add_enabled(
+                        !self.items.is_empty(),
+                        egui::Button::new(t!("trash-empty-button")),
+                    )
+                    .on_hover_text(t!("trash-empty-hint"))
+                    .clicked()
+                {
+                    self.confirm_empty = true;
+                }
+            });
+        });
+        ui.separator();
+
+        if self.items.is_empty() && !self.loading {
+            ui.weak(t!("trash-empty"));
+            return;
+        }
+        ScrollArea::vertical().max_height(360.).show(ui, |ui| {
+            for item in &self.items {
+                ui.horizontal(|ui| {
+                    let icon = if item.is_dir { FOLDER } else { FILE };
+                    ui.add(egui::Label::new(format!("{icon} {}", item.original)).truncate());
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .button(TRASH)
+                            .on_hover_text(t!("trash-purge-hint"))
+                            .clicked()
+                        {
+                            self.loading = true;
+                            client.purge(vec![item.clone()]);
+                        }
+                        if ui
+                            .button(ARROW_COUNTER_CLOCKWISE)
+                            .on_hover_text(t!("trash-restore-hint"))
+                            .clicked()
+                        {
+                            client.restore(item.clone());
+                        }
+                        ui.weak(format_time(item.deleted_at / 1000));
+                    });
+                });
+            }
+        });
+    }
+}
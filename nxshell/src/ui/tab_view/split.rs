@@ -0,0 +1,188 @@
+use crate::ui::tab_view::Tab;
+use egui::{CursorIcon, Rect, Vec2};
+
+/// Width of the bar between two panes, dragged to resize them.
+pub const DIVIDER_WIDTH: f32 = 4.;
+/// Smallest share of the tab a pane is resized to.
+const MIN_SHARE: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitDirection {
+    /// The panes side by side.
+    Right,
+    /// The panes one above the other.
+    Down,
+}
+
+impl SplitDirection {
+    /// Length of `size` along the panes.
+    fn along(self, size: Vec2) -> f32 {
+        match self {
+            SplitDirection::Right => size.x,
+            SplitDirection::Down => size.y,
+        }
+    }
+
+    pub fn resize_cursor(self) -> CursorIcon {
+        match self {
+            SplitDirection::Right => CursorIcon::ResizeHorizontal,
+            SplitDirection::Down => CursorIcon::ResizeVertical,
+        }
+    }
+}
+
+/// Terminals sharing a tab, its title and its lifetime, like the panes of
+/// tmux. A pane is a tab of its own, split again to nest the panes.
+#[derive(PartialEq)]
+pub struct SplitTerminalTab {
+    pub panes: Vec<Tab>,
+    pub direction: SplitDirection,
+    /// Share of the tab of each pane, they add up to 1.
+    shares: Vec<f32>,
+    /// Pane with the keys, the menus and the shortcuts act on it.
+    pub focused: usize,
+    /// Set when a pane is split, the new pane takes the keyboard once its
+    /// terminal is shown.
+    pub claim_focus: bool,
+}
+
+impl SplitTerminalTab {
+    /// The tab of `first` split in two halves, `second` gets the focus.
+    pub fn new(first: Tab, second: Tab, direction: SplitDirection) -> Self {
+        Self {
+            panes: vec![first, second],
+            direction,
+            shares: vec![0.5, 0.5],
+            focused: 1,
+            claim_focus: true,
+        }
+    }
+
+    pub fn focused(&self) -> &Tab {
+        &self.panes[self.focused]
+    }
+
+    pub fn focused_mut(&mut self) -> &mut Tab {
+        &mut self.panes[self.focused]
+    }
+
+    /// Split the focused pane in two halves, the new one after it gets the
+    /// focus.
+    pub fn insert(&mut self, pane: Tab) {
+        let index = self.focused + 1;
+        self.shares[self.focused] /= 2.;
+        self.shares.insert(index, self.shares[self.focused]);
+        self.panes.insert(index, pane);
+        self.focused = index;
+        self.claim_focus = true;
+    }
+
+    /// Remove a pane, the one before it, or after the first one, takes its
+    /// place.
+    pub fn remove(&mut self, index: usize) -> Tab {
+        let share = self.shares.remove(index);
+        let pane = self.panes.remove(index);
+        if let Some(neighbour) = self.shares.get_mut(index.saturating_sub(1)) {
+            *neighbour += share;
+        }
+        if self.focused >= index {
+            self.focused = self.focused.saturating_sub(1);
+        }
+        pane
+    }
+
+    /// Drop the panes closed on the last frame.
+    pub fn remove_closed(&mut self) {
+        while let Some(index) = self.panes.iter().position(|pane| pane.close_requested) {
+            self.remove(index);
+        }
+    }
+
+    /// Rects of the panes in `rect`, and of the dividers between them.
+    pub fn layout(&self, rect: Rect) -> (Vec<Rect>, Vec<Rect>) {
+        let dividers = (self.panes.len() - 1) as f32 * DIVIDER_WIDTH;
+        let length = (self.direction.along(rect.size()) - dividers).max(0.);
+        let mut panes = Vec::with_capacity(self.panes.len());
+        let mut bars = Vec::with_capacity(self.panes.len() - 1);
+        let mut start = 0.;
+        for (index, share) in self.shares.iter().enumerate() {
+            if index > 0 {
+                bars.push(self.span(rect, start, DIVIDER_WIDTH));
+                start += DIVIDER_WIDTH;
+            }
+            panes.push(self.span(rect, start, share * length));
+            start += share * length;
+        }
+        (panes, bars)
+    }
+
+    /// Part of `rect` from `start` along the panes, `length` long.
+    fn span(&self, rect: Rect, start: f32, length: f32) -> Rect {
+        match self.direction {
+            SplitDirection::Right => Rect::from_min_size(
+                rect.min + Vec2::new(start, 0.),
+                Vec2::new(length, rect.height()),
+            ),
+            SplitDirection::Down => Rect::from_min_size(
+                rect.min + Vec2::new(0., start),
+                Vec2::new(rect.width(), length),
+            ),
+        }
+    }
+
+    /// Move the divider after the pane `index` by `delta` pixels, in a tab
+    /// of `size`.
+    pub fn drag_divider(&mut self, index: usize, delta: Vec2, size: Vec2) {
+        let dividers = (self.panes.len() - 1) as f32 * DIVIDER_WIDTH;
+        let length = self.direction.along(size) - dividers;
+        if length <= 0. {
+            return;
+        }
+        let pair = self.shares[index] + self.shares[index + 1];
+        let share = (self.shares[index] + self.direction.along(delta) / length)
+            .clamp(MIN_SHARE.min(pair / 2.), (pair - MIN_SHARE).max(pair / 2.));
+        self.shares[index] = share;
+        self.shares[index + 1] = pair - share;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::Pos2;
+
+    fn split() -> SplitTerminalTab {
+        SplitTerminalTab::new(
+            Tab::session_list(),
+            Tab::session_list(),
+            SplitDirection::Right,
+        )
+    }
+
+    #[test]
+    fn panes_share_the_tab() {
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(404., 100.));
+        let mut split = split();
+        let (panes, dividers) = split.layout(rect);
+        assert_eq!(
+            panes[0],
+            Rect::from_min_size(Pos2::ZERO, Vec2::new(200., 100.))
+        );
+        assert_eq!(dividers[0].min, Pos2::new(200., 0.));
+        assert_eq!(panes[1].min, Pos2::new(204., 0.));
+
+        // the pane keeps a tenth of the tab
+        split.drag_divider(0, Vec2::new(1000., 0.), rect.size());
+        let (panes, _) = split.layout(rect);
+        assert!((panes[1].width() - 40.).abs() < 0.01);
+
+        split.focused = 0;
+        split.insert(Tab::session_list());
+        assert_eq!((split.panes.len(), split.focused), (3, 1));
+        assert!((split.shares[1] - 0.45).abs() < 0.001);
+        split.remove(1);
+        assert_eq!(split.panes.len(), 2);
+        assert!((split.shares[0] - 0.9).abs() < 0.001);
+        assert_eq!(split.focused, 0);
+    }
+}
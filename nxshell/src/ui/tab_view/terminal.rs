@@ -1,8 +1,291 @@
-use egui_term::{TermType, Terminal, TerminalTheme};
+use crate::i18n::t;
+use crate::ui::idle::IdleTimer;
+use crate::ui::profiler::Throughput;
+use crate::ui::tab_view::completion::Completion;
+use egui::{Align2, Color32, Frame, Id, Key, Order, Rect, RichText, TextEdit};
+use egui_term::{ConnectStage, PendingTerminal, TermType, Terminal, TerminalFont, TerminalTheme};
+use std::time::{Duration, Instant};
 
 #[derive(PartialEq)]
 pub struct TerminalTab {
     pub terminal_theme: TerminalTheme,
+    /// Whether `terminal_theme` has the light palette, see [`ThemeMode`], and
+    /// the revision of the theme of the app it was made of. `None` until the
+    /// tab is shown.
+    ///
+    /// [`ThemeMode`]: crate::ui::form::ThemeMode
+    pub theme_of: Option<(bool, u64)>,
     pub terminal: Terminal,
     pub term_type: TermType,
+    /// Own font of the tab when its session sets a font size, the tabs share
+    /// the font of the app otherwise.
+    pub font: Option<TerminalFont>,
+    /// Command proposed by an assistant, waiting for the user to run it.
+    pub staged: Option<StagedCommand>,
+    pub completion: Completion,
+    /// Set once the shell of a local tab exited, the tab is kept open to read
+    /// its last output.
+    pub exited: bool,
+    /// Reported by the shell as it exits.
+    pub exit_code: Option<i32>,
+    /// Shapes painted for the terminal on its last frame, see the profiler.
+    pub shapes: usize,
+    /// Where the terminal was drawn on its last frame, e.g. to export an image
+    /// of it.
+    pub view_rect: Rect,
+    /// Id of the view of the terminal, once shown.
+    pub view_id: Option<Id>,
+    pub throughput: Throughput,
+    /// Ssh tabs are disconnected once idle for too long.
+    pub idle: IdleTimer,
+    pub nested: NestedCommand,
+}
+
+/// The shell is ready for the nested command once its output stopped for this
+/// long, e.g. after the banner and the prompt.
+pub const NESTED_SETTLE: Duration = Duration::from_millis(500);
+
+/// Command run in a ssh tab once connected, to reach an inner host, see
+/// [`crate::db::Session::nested_command`].
+#[derive(Debug, PartialEq)]
+pub enum NestedCommand {
+    /// Waiting for the output of the shell to settle.
+    Pending {
+        read_bytes: u64,
+        changed_at: Instant,
+    },
+    /// Running, the tab is named after its target.
+    Launched { target: String },
+    /// Finished, or never run.
+    Done,
+}
+
+impl Default for NestedCommand {
+    fn default() -> Self {
+        Self::Pending {
+            read_bytes: 0,
+            changed_at: Instant::now(),
+        }
+    }
+}
+
+impl NestedCommand {
+    /// Whether `command` can be run now, given the bytes the terminal read so
+    /// far. It is then launched.
+    pub fn ready(&mut self, command: &str, read_bytes: u64, now: Instant) -> bool {
+        let Self::Pending {
+            read_bytes: read,
+            changed_at,
+        } = self
+        else {
+            return false;
+        };
+        if *read != read_bytes {
+            *read = read_bytes;
+            *changed_at = now;
+            return false;
+        }
+        if read_bytes == 0 || now.duration_since(*changed_at) < NESTED_SETTLE {
+            return false;
+        }
+        // the settings of the session are loaded by then
+        if command.is_empty() {
+            *self = Self::Done;
+            return false;
+        }
+        *self = Self::Launched {
+            target: nested_target(command).to_string(),
+        };
+        true
+    }
+
+    pub fn target(&self) -> Option<&str> {
+        match self {
+            Self::Launched { target } => Some(target),
+            _ => None,
+        }
+    }
+}
+
+/// What a nested command connects to, its last argument which isn't an
+/// option, e.g. the host of `ssh -p 2222 inner-host`.
+pub fn nested_target(command: &str) -> &str {
+    command
+        .split_whitespace()
+        .rev()
+        .find(|arg| !arg.starts_with('-'))
+        .unwrap_or(command)
+}
+
+/// Command proposed from outside, e.g. by an assistant through the API. It is
+/// only typed into the terminal once the user runs it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StagedCommand {
+    pub command: String,
+    /// Who proposed it.
+    pub source: String,
+}
+
+pub enum StagingAction {
+    Run(String),
+    Dismiss,
+}
+
+/// Bar above the terminal where the staged command is reviewed, and edited.
+/// The terminal gives up the keyboard while the command is typed in. The bar
+/// doesn't take the focus by itself, an Enter typed in the terminal meanwhile
+/// would run the command.
+pub fn staging_bar(
+    ui: &mut egui::Ui,
+    staged: &mut StagedCommand,
+    active_tab_id: &mut Option<Id>,
+) -> Option<StagingAction> {
+    let mut action = None;
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.label(RichText::new(t!("staging-title", source = staged.source.clone())).weak());
+        ui.horizontal(|ui| {
+            // read before the text edit, which gives up the focus on Escape
+            let (enter, escape) =
+                ui.input(|i| (i.key_pressed(Key::Enter), i.key_pressed(Key::Escape)));
+            let edit = TextEdit::singleline(&mut staged.command)
+                .code_editor()
+                .desired_width(ui.available_width() - 160.);
+            let response = ui.add(edit).on_hover_text(t!("staging-run-hint"));
+            let edited = response.has_focus() || response.lost_focus();
+            if ui.button(t!("staging-run")).clicked() || (response.lost_focus() && enter) {
+                action = Some(StagingAction::Run(staged.command.clone()));
+            }
+            if ui.button(t!("staging-dismiss")).clicked() || (edited && escape) {
+                action = Some(StagingAction::Dismiss);
+            }
+            if response.has_focus() {
+                *active_tab_id = None;
+            }
+        });
+    });
+    action
+}
+
+/// Bar above the terminal whose shell exited, returns whether the tab is
+/// closed.
+pub fn exit_banner(ui: &mut egui::Ui, exit_code: Option<i32>) -> bool {
+    let mut close = false;
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.horizontal(|ui| {
+            let text = match exit_code {
+                Some(code) => t!("tab-exited-code", code = code),
+                None => t!("tab-exited"),
+            };
+            ui.label(RichText::new(text).weak());
+            close = ui.button(t!("tab-exited-close")).clicked();
+        });
+    });
+    close
+}
+
+/// Bar above the terminal about to be disconnected for being idle, returns
+/// whether it stays connected.
+pub fn idle_banner(ui: &mut egui::Ui) -> bool {
+    let mut stay = false;
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(t!("idle-banner")).weak());
+            stay = ui.button(t!("idle-stay-connected")).clicked();
+        });
+    });
+    stay
+}
+
+/// Bar above the terminal sent Ctrl+S, which looks frozen until Ctrl+Q.
+/// Returns whether the output is resumed.
+pub fn paused_banner(ui: &mut egui::Ui) -> bool {
+    let mut resume = false;
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(t!("paused-banner")).weak());
+            resume = ui.button(t!("paused-resume")).clicked();
+        });
+    });
+    resume
+}
+
+pub enum BackendAction {
+    Restart,
+    Close,
+}
+
+/// Shown over a terminal whose backend stopped answering, see
+/// [`Terminal::backend_unresponsive`]. Returns what the user chose.
+pub fn unresponsive_overlay(ui: &mut egui::Ui, id: Id, rect: Rect) -> Option<BackendAction> {
+    ui.painter()
+        .rect_filled(rect, 0., Color32::from_black_alpha(160));
+    let mut action = None;
+    egui::Area::new(id.with("unresponsive"))
+        .order(Order::Foreground)
+        .pivot(Align2::CENTER_CENTER)
+        .fixed_pos(rect.center())
+        .show(ui.ctx(), |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(RichText::new(t!("backend-unresponsive")).strong());
+                ui.weak(t!("backend-unresponsive-hint"));
+                ui.horizontal(|ui| {
+                    if ui.button(t!("backend-restart")).clicked() {
+                        action = Some(BackendAction::Restart);
+                    }
+                    if ui.button(t!("tab-exited-close")).clicked() {
+                        action = Some(BackendAction::Close);
+                    }
+                });
+            });
+        });
+    action
+}
+
+/// Ssh terminal tab while its connection is established.
+#[derive(PartialEq)]
+pub struct ConnectingTab {
+    pub pending: PendingTerminal,
+    pub term_type: TermType,
+}
+
+impl ConnectingTab {
+    /// What the connection is doing, e.g. in the title of the tab.
+    pub fn status(&self) -> String {
+        match self.pending.stage() {
+            ConnectStage::Resolving => t!("connect-resolving"),
+            ConnectStage::Authenticating => t!("connect-authenticating"),
+            ConnectStage::TouchSecurityKey => t!("connect-touch-security-key"),
+            ConnectStage::OpeningPty => t!("connect-opening-pty"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_command_once_settled() {
+        assert_eq!(nested_target("ssh -p 2222 inner-host"), "inner-host");
+        assert_eq!(
+            nested_target("picocom -b 115200 /dev/ttyUSB0"),
+            "/dev/ttyUSB0"
+        );
+
+        let start = Instant::now();
+        let mut nested = NestedCommand::default();
+        assert!(!nested.ready("telnet 10.0.0.5", 0, start));
+        assert!(!nested.ready("telnet 10.0.0.5", 120, start));
+        // still printing the banner
+        let settled = start + NESTED_SETTLE;
+        assert!(!nested.ready("telnet 10.0.0.5", 240, settled));
+        assert!(nested.ready("telnet 10.0.0.5", 240, settled + NESTED_SETTLE));
+        assert_eq!(nested.target(), Some("10.0.0.5"));
+        assert!(!nested.ready("telnet 10.0.0.5", 240, settled + NESTED_SETTLE));
+
+        let mut none = NestedCommand::default();
+        assert!(!none.ready("", 240, start));
+        assert!(!none.ready("", 240, settled));
+        assert_eq!(none, NestedCommand::Done);
+    }
 }
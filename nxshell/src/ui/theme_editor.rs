@@ -0,0 +1,316 @@
+use crate::app::NxShell;
+use crate::i18n::t;
+use egui::color_picker::{color_edit_button_srgba, Alpha};
+use egui::{Color32, ComboBox, Grid, Id, Sense, Stroke, StrokeKind, Vec2, Window};
+use egui_term::{
+    color_to_hex, ColorDeficiency, ColorFilter, ColorPalette, ColorTest, TerminalTheme, TrueColor,
+};
+
+/// Side of a color of the 256 color palette.
+const SWATCH_SIZE: f32 = 14.;
+
+/// Palette of the terminals edited while the output of a color test shows it.
+#[derive(Debug, Clone)]
+pub struct ThemeEditor {
+    /// Index of the color of the 256 color palette being edited.
+    selected: u8,
+    color_test: ColorTest,
+}
+
+impl Default for ThemeEditor {
+    fn default() -> Self {
+        Self {
+            selected: 16,
+            color_test: ColorTest::new(),
+        }
+    }
+}
+
+/// Built-in palettes, the color-blind ones tell red and green apart.
+fn palette_presets() -> [(String, ColorPalette); 4] {
+    [
+        (t!("theme-preset-default"), ColorPalette::default()),
+        (t!("theme-preset-light"), ColorPalette::light()),
+        (t!("theme-preset-color-blind"), ColorPalette::color_blind()),
+        (
+            t!("theme-preset-color-blind-light"),
+            ColorPalette::color_blind_light(),
+        ),
+    ]
+}
+
+pub fn color_filter_label(color_filter: ColorFilter) -> String {
+    match color_filter {
+        ColorFilter::None => t!("color-filter-none"),
+        ColorFilter::Simulate(ColorDeficiency::Protanopia) => {
+            t!("color-filter-simulate-protanopia")
+        }
+        ColorFilter::Simulate(ColorDeficiency::Deuteranopia) => {
+            t!("color-filter-simulate-deuteranopia")
+        }
+        ColorFilter::Compensate(ColorDeficiency::Protanopia) => {
+            t!("color-filter-compensate-protanopia")
+        }
+        ColorFilter::Compensate(ColorDeficiency::Deuteranopia) => {
+            t!("color-filter-compensate-deuteranopia")
+        }
+    }
+}
+
+/// Name of the color filter in the app storage.
+pub fn color_filter_name(color_filter: ColorFilter) -> &'static str {
+    match color_filter {
+        ColorFilter::None => "none",
+        ColorFilter::Simulate(ColorDeficiency::Protanopia) => "simulate_protanopia",
+        ColorFilter::Simulate(ColorDeficiency::Deuteranopia) => "simulate_deuteranopia",
+        ColorFilter::Compensate(ColorDeficiency::Protanopia) => "compensate_protanopia",
+        ColorFilter::Compensate(ColorDeficiency::Deuteranopia) => "compensate_deuteranopia",
+    }
+}
+
+pub fn color_filter_from_name(name: &str) -> ColorFilter {
+    ColorFilter::ALL
+        .into_iter()
+        .find(|color_filter| color_filter_name(*color_filter) == name)
+        .unwrap_or_default()
+}
+
+fn true_color_label(true_color: TrueColor) -> String {
+    match true_color {
+        TrueColor::Exact => t!("theme-true-color-exact"),
+        TrueColor::Ansi256 => t!("theme-true-color-ansi256"),
+    }
+}
+
+impl NxShell {
+    /// The edits show at once in the preview and in the terminals.
+    pub fn theme_editor_window(&mut self, ctx: &egui::Context) {
+        let Some(editor) = &mut self.opts.theme_editor else {
+            return;
+        };
+        let theme = &mut self.opts.term_theme;
+        let mut open = true;
+        let mut changed = false;
+
+        Window::new(t!("theme-editor"))
+            .id(Id::new("theme_editor_window"))
+            .open(&mut open)
+            .default_size([960., 480.])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.menu_button(t!("theme-presets"), |ui| {
+                        for (label, palette) in palette_presets() {
+                            if ui.button(label).clicked() {
+                                *theme.palette_mut() = palette;
+                                changed = true;
+                                ui.close();
+                            }
+                        }
+                    });
+                    ui.label(t!("theme-true-color"));
+                    let mut true_color = theme.true_color();
+                    ComboBox::from_id_salt("theme_true_color")
+                        .selected_text(true_color_label(true_color))
+                        .show_ui(ui, |ui| {
+                            for mode in [TrueColor::Exact, TrueColor::Ansi256] {
+                                let label = true_color_label(mode);
+                                ui.selectable_value(&mut true_color, mode, label);
+                            }
+                        })
+                        .response
+                        .on_hover_text(t!("theme-true-color-hint"));
+                    if true_color != theme.true_color() {
+                        theme.set_true_color(true_color);
+                        changed = true;
+                    }
+                    if ui.button(t!("theme-reset-ansi256")).clicked() {
+                        theme.reset_ansi256_colors();
+                        changed = true;
+                    }
+                    if ui.button(t!("theme-reset")).clicked() {
+                        // the color filter and the bold options are settings
+                        // of their own
+                        let mut default = TerminalTheme::default();
+                        default.set_color_filter(theme.color_filter());
+                        default.set_bold_as_bright(theme.bold_as_bright());
+                        default.set_bright_as_bold(theme.bright_as_bold());
+                        *theme = default;
+                        changed = true;
+                    }
+                });
+                ui.separator();
+
+                ui.horizontal_top(|ui| {
+                    ui.vertical(|ui| {
+                        changed |= palette_grid(ui, theme.palette_mut());
+                        ui.add_space(8.);
+                        changed |= ansi256_grid(ui, theme, &mut editor.selected);
+                    });
+                    ui.separator();
+                    editor.color_test.show(ui, theme);
+                });
+            });
+
+        if changed {
+            self.opts.term_theme_revision += 1;
+        }
+        if !open {
+            self.opts.theme_editor = None;
+        }
+    }
+}
+
+/// Foreground, background and selection colors.
+fn palette_grid(ui: &mut egui::Ui, palette: &mut ColorPalette) -> bool {
+    let mut changed = false;
+    Grid::new("theme_palette_grid")
+        .num_columns(2)
+        .show(ui, |ui| {
+            for (label, hex) in [
+                (t!("theme-foreground"), &mut palette.foreground),
+                (t!("theme-background"), &mut palette.background),
+                (t!("theme-selection"), &mut palette.selection),
+            ] {
+                ui.label(label);
+                let mut color = Color32::from_hex(hex).unwrap_or_default();
+                if color_edit_button_srgba(ui, &mut color, Alpha::Opaque).changed() {
+                    *hex = color_to_hex(color);
+                    changed = true;
+                }
+                ui.end_row();
+            }
+        });
+    changed
+}
+
+/// The 16 colors of the palette, the 6x6x6 cube and the grays, the selected
+/// one is edited below them.
+fn ansi256_grid(ui: &mut egui::Ui, theme: &mut TerminalTheme, selected: &mut u8) -> bool {
+    ui.label(t!("theme-ansi256"));
+    let rows = [(0..=7).collect::<Vec<u8>>(), (8..=15).collect()]
+        .into_iter()
+        // a red level per row
+        .chain((0..6).map(|r| (0..36).map(|i| 16 + 36 * r + i).collect()))
+        .chain([(232..=255).collect()]);
+
+    ui.scope(|ui| {
+        ui.spacing_mut().item_spacing = Vec2::splat(1.);
+        for row in rows {
+            ui.horizontal(|ui| {
+                for index in row {
+                    if swatch(ui, theme.indexed_color(index), index == *selected).clicked() {
+                        *selected = index;
+                    }
+                }
+            });
+        }
+    });
+
+    ui.add_space(8.);
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label(t!("theme-color", index = *selected));
+        let mut color = theme.indexed_color(*selected);
+        if color_edit_button_srgba(ui, &mut color, Alpha::Opaque).changed() {
+            theme.set_indexed_color(*selected, color);
+            changed = true;
+        }
+        ui.weak(color_to_hex(color));
+    });
+    changed
+}
+
+fn swatch(ui: &mut egui::Ui, color: Color32, selected: bool) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(Vec2::splat(SWATCH_SIZE), Sense::click());
+    ui.painter().rect_filled(rect, 0., color);
+    if selected {
+        let stroke = Stroke::new(2., ui.visuals().strong_text_color());
+        ui.painter()
+            .rect_stroke(rect, 0., stroke, StrokeKind::Inside);
+    }
+    response
+}
+
+/// The theme as `name=#rrggbb` lines, to keep it in the app storage: the
+/// colors of the palette and the ones of the 256 color palette changed.
+pub fn theme_to_string(theme: &TerminalTheme) -> String {
+    let palette = theme.palette();
+    let mut lines = vec![
+        format!("foreground={}", palette.foreground),
+        format!("background={}", palette.background),
+        format!("selection={}", palette.selection),
+    ];
+    for index in 0..16 {
+        lines.push(format!(
+            "color{index}={}",
+            color_to_hex(theme.indexed_color(index))
+        ));
+    }
+    for (index, color) in theme.custom_ansi256_colors() {
+        lines.push(format!("color{index}={}", color_to_hex(color)));
+    }
+    if theme.true_color() == TrueColor::Ansi256 {
+        lines.push("true_color=ansi256".to_string());
+    }
+    lines.join("\n")
+}
+
+/// Theme of the lines of [`theme_to_string`], the invalid ones are skipped.
+/// The colors are stored as `#rrggbb`, whatever hex notation they are in.
+pub fn theme_from_string(text: &str) -> TerminalTheme {
+    let mut theme = TerminalTheme::default();
+    for (name, value) in text.lines().filter_map(|line| line.split_once('=')) {
+        if name == "true_color" {
+            if value == "ansi256" {
+                theme.set_true_color(TrueColor::Ansi256);
+            }
+            continue;
+        }
+        let Ok(color) = Color32::from_hex(value) else {
+            continue;
+        };
+        let palette = theme.palette_mut();
+        match name {
+            "foreground" => palette.foreground = color_to_hex(color),
+            "background" => palette.background = color_to_hex(color),
+            "selection" => palette.selection = color_to_hex(color),
+            _ => {
+                if let Some(Ok(index)) = name.strip_prefix("color").map(str::parse) {
+                    theme.set_indexed_color(index, color);
+                }
+            }
+        }
+    }
+    theme
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theme_round_trip() {
+        let mut theme = TerminalTheme::default();
+        theme.palette_mut().background = "#102030".to_string();
+        theme.set_indexed_color(3, Color32::from_rgb(0xaa, 0xbb, 0xcc));
+        theme.set_indexed_color(208, Color32::from_rgb(1, 2, 3));
+        theme.set_true_color(TrueColor::Ansi256);
+
+        let text = theme_to_string(&theme);
+        assert!(text.contains("\ncolor208=#010203"));
+        assert!(!text.contains("color209="));
+        assert_eq!(theme_from_string(&text), theme);
+        assert_eq!(
+            theme_from_string("color300=#ffffff\nbroken"),
+            TerminalTheme::default()
+        );
+        let short = theme_from_string("foreground=#abc\nselection=#102030ff");
+        assert_eq!(short.palette().foreground, "#aabbcc");
+        assert_eq!(short.palette().selection, "#102030");
+
+        for color_filter in ColorFilter::ALL {
+            let name = color_filter_name(color_filter);
+            assert_eq!(color_filter_from_name(name), color_filter);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use crate::app::NxShell;
+use crate::db::Session;
+use crate::errors::{error_toast, info_toast};
+use crate::i18n::t;
+use crate::ui::menubar::ssh_options;
+use crate::wol::{parse_mac, WakeState, WakeUp};
+use std::time::Duration;
+
+/// Hosts being woken up are checked at least this often.
+const WAKE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+impl NxShell {
+    /// Wake the host of `session` up with its MAC address, it is connected to
+    /// once its ssh port is open.
+    pub fn wake_session(&mut self, session: Session) {
+        if session.mac_address.is_empty() {
+            let error = t!("wake-no-mac", name = session.name.clone());
+            self.toasts.add(error_toast(error));
+            return;
+        }
+        let wake_up = parse_mac(&session.mac_address).and_then(|mac| {
+            let options = ssh_options(session.clone())?;
+            Ok(WakeUp::spawn(mac, &options))
+        });
+        match wake_up {
+            Ok(wake_up) => {
+                self.toasts
+                    .add(info_toast(t!("wake-sent", name = session.name.clone())));
+                self.waking.push((session, wake_up));
+            }
+            Err(err) => {
+                self.toasts.add(error_toast(err.to_string()));
+            }
+        }
+    }
+
+    /// Connect to the hosts which woke up, give up on the ones which didn't.
+    pub fn poll_wake_ups(&mut self, ctx: &egui::Context) {
+        if self.waking.is_empty() {
+            return;
+        }
+        ctx.request_repaint_after(WAKE_CHECK_INTERVAL);
+        let (done, waking): (Vec<_>, Vec<_>) = std::mem::take(&mut self.waking)
+            .into_iter()
+            .partition(|(_, wake_up)| wake_up.state() != WakeState::Waiting);
+        self.waking = waking;
+        for (session, wake_up) in done {
+            if wake_up.state() == WakeState::Failed {
+                self.toasts
+                    .add(error_toast(t!("wake-failed", name = session.name)));
+                continue;
+            }
+            if let Err(err) = self.add_shell_tab_with_secret(ctx, session) {
+                self.toasts.add(error_toast(err.to_string()));
+            }
+        }
+    }
+}
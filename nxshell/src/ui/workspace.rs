@@ -0,0 +1,167 @@
+use crate::app::NxShell;
+use crate::errors::error_toast;
+use crate::i18n::t;
+use crate::workspace::SessionKey;
+use egui::{Align2, Button, CollapsingHeader, TextEdit, Window};
+use egui_phosphor::regular::STACK;
+use tracing::error;
+
+/// Sessions often opened together, until saved as a workspace or dismissed.
+pub struct WorkspaceSuggestion {
+    members: Vec<SessionKey>,
+    name: String,
+}
+
+impl WorkspaceSuggestion {
+    fn new(members: Vec<SessionKey>) -> Self {
+        // the group of the sessions when they share it
+        let group = &members[0].0;
+        let name = match members.iter().all(|(other, _)| other == group) {
+            true => group.clone(),
+            false => String::new(),
+        };
+        Self { members, name }
+    }
+}
+
+impl NxShell {
+    /// Count an open of a saved session, then suggest the sessions it is
+    /// often opened with as a workspace.
+    pub fn record_session_open(&mut self, (group, name): SessionKey) {
+        self.db.run(
+            move |db| {
+                db.record_session_open(&group, &name)?;
+                db.suggest_workspace()
+            },
+            |shell, members| match members {
+                Ok(Some(members)) if shell.workspace_suggestion.is_none() => {
+                    shell.workspace_suggestion = Some(WorkspaceSuggestion::new(members));
+                }
+                Ok(_) => {}
+                Err(err) => error!("failed to record the session open: {err}"),
+            },
+        );
+    }
+
+    /// Offer to save the sessions often opened together as a workspace, in a
+    /// corner until answered.
+    pub fn workspace_suggestion_window(&mut self, ctx: &egui::Context) {
+        let Some(suggestion) = &mut self.workspace_suggestion else {
+            return;
+        };
+
+        let mut answer = None;
+        Window::new(t!("workspace-suggestion-title"))
+            .anchor(Align2::RIGHT_BOTTOM, [-8., -32.])
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.set_max_width(320.);
+                let sessions: Vec<&str> = suggestion
+                    .members
+                    .iter()
+                    .map(|(_, name)| name.as_str())
+                    .collect();
+                ui.label(t!("workspace-suggestion", sessions = sessions.join(", ")));
+                ui.add(
+                    TextEdit::singleline(&mut suggestion.name).hint_text(t!("workspace-name-hint")),
+                );
+                ui.horizontal(|ui| {
+                    let name = suggestion.name.trim();
+                    let save = Button::new(t!("workspace-save"));
+                    if ui.add_enabled(!name.is_empty(), save).clicked() {
+                        answer = Some(Some(name.to_string()));
+                    }
+                    if ui.button(t!("workspace-dismiss")).clicked() {
+                        answer = Some(None);
+                    }
+                });
+            });
+
+        let Some(answer) = answer else {
+            return;
+        };
+        let Some(suggestion) = self.workspace_suggestion.take() else {
+            return;
+        };
+        let members = suggestion.members;
+        match answer {
+            Some(name) => self.db.run(
+                move |db| {
+                    db.save_workspace(&name, &members)?;
+                    db.find_workspaces()
+                },
+                |shell, workspaces| match workspaces {
+                    Ok(workspaces) => shell.state_manager.workspaces = workspaces,
+                    Err(err) => {
+                        shell.toasts.add(error_toast(err.to_string()));
+                    }
+                },
+            ),
+            None => self.db.run(
+                move |db| db.dismiss_workspace(&members),
+                |_, result| {
+                    if let Err(err) = result {
+                        error!("failed to dismiss the workspace: {err}");
+                    }
+                },
+            ),
+        }
+    }
+
+    /// The saved workspaces, each opens all its sessions.
+    pub fn list_workspaces(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
+        if self.state_manager.workspaces.is_empty() {
+            return;
+        }
+        let mut opened = None;
+        let mut deleted = None;
+        CollapsingHeader::new(t!("workspaces"))
+            .default_open(true)
+            .show(ui, |ui| {
+                for (name, members) in &self.state_manager.workspaces {
+                    let response = ui.button(format!("{STACK} {name}")).on_hover_text(
+                        members
+                            .iter()
+                            .map(|(group, name)| format!("{group}/{name}"))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    );
+                    if response.double_clicked() {
+                        opened = Some(members.clone());
+                    }
+                    response.context_menu(|ui| {
+                        if ui.button(t!("workspace-open")).clicked() {
+                            opened = Some(members.clone());
+                            ui.close();
+                        }
+                        if ui.button(t!("workspace-delete")).clicked() {
+                            deleted = Some(name.clone());
+                            ui.close();
+                        }
+                    });
+                }
+            });
+        ui.separator();
+
+        for (group, name) in opened.into_iter().flatten() {
+            let ctx = ctx.clone();
+            self.with_session(&group, &name, move |shell, session| {
+                if let Err(err) = shell.add_shell_tab_with_secret(&ctx, session) {
+                    shell.toasts.add(error_toast(err.to_string()));
+                }
+            });
+        }
+        if let Some(name) = deleted {
+            self.state_manager.workspaces.shift_remove(&name);
+            self.db.run(
+                move |db| db.delete_workspace(&name),
+                |shell, result| {
+                    if let Err(err) = result {
+                        shell.toasts.add(error_toast(err.to_string()));
+                    }
+                },
+            );
+        }
+    }
+}
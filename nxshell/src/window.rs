@@ -0,0 +1,215 @@
+use eframe::egui;
+use egui::{Pos2, Rect, Vec2, ViewportCommand};
+
+/// Keys of the main window in the app storage.
+const STARTUP_WINDOW_KEY: &str = "startup_window";
+const WINDOW_GEOMETRY_KEY: &str = "window_geometry";
+
+/// How the main window opens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StartupWindow {
+    /// With the size and position it had when nxshell was closed.
+    #[default]
+    Restore,
+    Maximized,
+}
+
+impl StartupWindow {
+    pub const ALL: [StartupWindow; 2] = [StartupWindow::Restore, StartupWindow::Maximized];
+
+    fn key(self) -> &'static str {
+        match self {
+            StartupWindow::Restore => "restore",
+            StartupWindow::Maximized => "maximized",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|startup| startup.key() == key)
+    }
+}
+
+/// Size and position of the main window, in points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct WindowGeometry {
+    /// Outer position of the window.
+    position: Pos2,
+    /// Inner size of the window.
+    size: Vec2,
+    /// Size of the monitor the window was on.
+    monitor: Vec2,
+    maximized: bool,
+}
+
+impl WindowGeometry {
+    fn parse(value: &str) -> Option<Self> {
+        let values: Vec<f32> = value
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .ok()?;
+        match values[..] {
+            [x, y, width, height, monitor_width, monitor_height, maximized] => Some(Self {
+                position: Pos2::new(x, y),
+                size: Vec2::new(width, height),
+                monitor: Vec2::new(monitor_width, monitor_height),
+                maximized: maximized != 0.,
+            }),
+            _ => None,
+        }
+    }
+
+    fn to_storage_string(self) -> String {
+        let Self {
+            position,
+            size,
+            monitor,
+            maximized,
+        } = self;
+        format!(
+            "{} {} {} {} {} {} {}",
+            position.x,
+            position.y,
+            size.x,
+            size.y,
+            monitor.x,
+            monitor.y,
+            u8::from(maximized)
+        )
+    }
+
+    /// Outer position and inner size of the window on a `monitor`: the same
+    /// pixels on the same monitor, the same share of the screen on another.
+    fn fit(&self, monitor: Vec2) -> Rect {
+        if self.monitor == monitor || self.monitor.min_elem() <= 0. {
+            return Rect::from_min_size(self.position, self.size.min(monitor));
+        }
+        let scale = monitor / self.monitor;
+        let size = (self.size * scale).min(monitor);
+        let position = (self.position.to_vec2() * scale).to_pos2();
+        let position = position.clamp(Pos2::ZERO, (monitor - size).to_pos2());
+        Rect::from_min_size(position, size)
+    }
+}
+
+/// Restores the main window and keeps track of its geometry for the next run.
+#[derive(Debug, Default)]
+pub struct MainWindow {
+    pub startup: StartupWindow,
+    /// Last geometry of the window, its size and position are the ones it had
+    /// before being maximized.
+    geometry: Option<WindowGeometry>,
+    /// Whether the window still has to be restored, on the first frame.
+    restoring: bool,
+}
+
+impl MainWindow {
+    pub fn restore(storage: Option<&dyn eframe::Storage>) -> Self {
+        let load = |key| storage.and_then(|storage| storage.get_string(key));
+        Self {
+            startup: load(STARTUP_WINDOW_KEY)
+                .and_then(|key| StartupWindow::from_key(&key))
+                .unwrap_or_default(),
+            geometry: load(WINDOW_GEOMETRY_KEY).and_then(|value| WindowGeometry::parse(&value)),
+            restoring: true,
+        }
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        storage.set_string(STARTUP_WINDOW_KEY, self.startup.key().to_string());
+        if let Some(geometry) = self.geometry {
+            storage.set_string(WINDOW_GEOMETRY_KEY, geometry.to_storage_string());
+        }
+    }
+
+    /// Called every frame, the monitor is only known once the window is open.
+    pub fn update(&mut self, ctx: &egui::Context) {
+        let viewport = ctx.input(|i| i.viewport().clone());
+        let Some(monitor) = viewport.monitor_size else {
+            return;
+        };
+        if std::mem::take(&mut self.restoring) {
+            match (self.startup, self.geometry) {
+                (StartupWindow::Maximized, _) => {
+                    ctx.send_viewport_cmd(ViewportCommand::Maximized(true));
+                }
+                (StartupWindow::Restore, Some(geometry)) => {
+                    let rect = geometry.fit(monitor);
+                    ctx.send_viewport_cmd(ViewportCommand::OuterPosition(rect.min));
+                    ctx.send_viewport_cmd(ViewportCommand::InnerSize(rect.size()));
+                    if geometry.maximized {
+                        ctx.send_viewport_cmd(ViewportCommand::Maximized(true));
+                    }
+                }
+                (StartupWindow::Restore, None) => {}
+            }
+            // the commands apply on the next frame
+            return;
+        }
+        if viewport.fullscreen == Some(true) || viewport.minimized == Some(true) {
+            return;
+        }
+
+        let maximized = viewport.maximized == Some(true);
+        let geometry = match (self.geometry, maximized) {
+            // keep the size to go back to
+            (Some(geometry), true) => WindowGeometry {
+                maximized,
+                ..geometry
+            },
+            _ => {
+                let (Some(outer), Some(inner)) = (viewport.outer_rect, viewport.inner_rect) else {
+                    return;
+                };
+                WindowGeometry {
+                    position: outer.min,
+                    size: inner.size(),
+                    monitor,
+                    maximized,
+                }
+            }
+        };
+        self.geometry = Some(geometry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_geometry() {
+        let geometry = WindowGeometry {
+            position: Pos2::new(100., 50.),
+            size: Vec2::new(1200., 800.),
+            monitor: Vec2::new(1920., 1080.),
+            maximized: true,
+        };
+        let value = geometry.to_storage_string();
+        assert_eq!(WindowGeometry::parse(&value), Some(geometry));
+        assert_eq!(WindowGeometry::parse("1 2 3"), None);
+        assert_eq!(WindowGeometry::parse("a b c d e f g"), None);
+
+        // same monitor, same pixels
+        let rect = geometry.fit(Vec2::new(1920., 1080.));
+        assert_eq!(rect, Rect::from_min_size(geometry.position, geometry.size));
+        // larger monitor, same share of the screen
+        let rect = geometry.fit(Vec2::new(3840., 2160.));
+        assert_eq!(rect.min, Pos2::new(200., 100.));
+        assert_eq!(rect.size(), Vec2::new(2400., 1600.));
+        // kept on the screen
+        let moved = WindowGeometry {
+            position: Pos2::new(1800., 1000.),
+            ..geometry
+        };
+        let rect = moved.fit(Vec2::new(960., 540.));
+        assert_eq!(rect.size(), Vec2::new(600., 400.));
+        assert_eq!(rect.max, Pos2::new(960., 540.));
+
+        assert_eq!(
+            StartupWindow::from_key("maximized"),
+            Some(StartupWindow::Maximized)
+        );
+        assert_eq!(StartupWindow::from_key("centered"), None);
+    }
+}
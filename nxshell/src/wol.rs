@@ -0,0 +1,133 @@
+use crate::errors::NxError;
+use crate::i18n::t;
+use crate::latency::{address, probe};
+use egui_term::SshOptions;
+use std::net::{Ipv4Addr, UdpSocket};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::error;
+
+/// The host is given up on when its ssh port isn't open by then.
+const WAKE_TIMEOUT: Duration = Duration::from_secs(180);
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// The packet is sent again this often, the first one may be lost.
+const RESEND_INTERVAL: Duration = Duration::from_secs(30);
+/// Port of the magic packets, the discard port.
+const WOL_PORT: u16 = 9;
+
+const WAITING: u8 = 0;
+const AWAKE: u8 = 1;
+const FAILED: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WakeState {
+    Waiting,
+    Awake,
+    Failed,
+}
+
+/// Parse a MAC address like `aa:bb:cc:dd:ee:ff`, or with dashes.
+pub fn parse_mac(mac: &str) -> Result<[u8; 6], NxError> {
+    let invalid = || NxError::Plain(t!("error-invalid-mac", mac = mac.to_string()));
+    let mut bytes = [0; 6];
+    let mut parts = mac.trim().split([':', '-']);
+    for byte in &mut bytes {
+        let part = parts
+            .next()
+            .filter(|part| part.len() == 2)
+            .ok_or_else(invalid)?;
+        *byte = u8::from_str_radix(part, 16).map_err(|_| invalid())?;
+    }
+    match parts.next() {
+        Some(_) => Err(invalid()),
+        None => Ok(bytes),
+    }
+}
+
+/// Six `0xff` then the MAC address sixteen times.
+fn magic_packet(mac: [u8; 6]) -> [u8; 102] {
+    let mut packet = [0xff; 102];
+    for chunk in packet[6..].chunks_mut(6) {
+        chunk.copy_from_slice(&mac);
+    }
+    packet
+}
+
+/// Broadcast the magic packet waking the host with `mac` on the local network.
+fn send_magic_packet(mac: [u8; 6]) -> std::io::Result<()> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.set_broadcast(true)?;
+    socket.send_to(&magic_packet(mac), (Ipv4Addr::BROADCAST, WOL_PORT))?;
+    Ok(())
+}
+
+/// Wakes a host up, then waits on a background thread until its ssh port
+/// accepts connections.
+pub struct WakeUp {
+    state: Arc<AtomicU8>,
+}
+
+impl WakeUp {
+    pub fn spawn(mac: [u8; 6], options: &SshOptions) -> Self {
+        let state = Arc::new(AtomicU8::new(WAITING));
+        let shared = state.clone();
+        let (host, port) = address(options);
+
+        let spawned = std::thread::Builder::new()
+            .name(format!("wake_{host}"))
+            .spawn(move || {
+                let start = Instant::now();
+                let mut sent_at = None;
+                while start.elapsed() < WAKE_TIMEOUT {
+                    if sent_at.is_none_or(|at: Instant| at.elapsed() >= RESEND_INTERVAL) {
+                        if let Err(err) = send_magic_packet(mac) {
+                            error!("failed to send the magic packet to {host}: {err}");
+                            break;
+                        }
+                        sent_at = Some(Instant::now());
+                    }
+                    if probe(&host, port).is_some() {
+                        shared.store(AWAKE, Ordering::Relaxed);
+                        return;
+                    }
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+                shared.store(FAILED, Ordering::Relaxed);
+            });
+        if let Err(err) = spawned {
+            error!("failed to spawn the wake up thread: {err}");
+            state.store(FAILED, Ordering::Relaxed);
+        }
+        Self { state }
+    }
+
+    pub fn state(&self) -> WakeState {
+        match self.state.load(Ordering::Relaxed) {
+            WAITING => WakeState::Waiting,
+            AWAKE => WakeState::Awake,
+            _ => WakeState::Failed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mac_and_packet() {
+        let mac = parse_mac("00:1A:2b:3c:4d:5e").unwrap();
+        assert_eq!(mac, [0x00, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e]);
+        assert_eq!(parse_mac(" 00-1a-2b-3c-4d-5e ").unwrap(), mac);
+        assert!(parse_mac("00:1a:2b:3c:4d").is_err());
+        assert!(parse_mac("00:1a:2b:3c:4d:5e:6f").is_err());
+        assert!(parse_mac("00:1a:2b:3c:4d:zz").is_err());
+        assert!(parse_mac("001a2b3c4d5e").is_err());
+
+        let packet = magic_packet(mac);
+        assert_eq!(packet[..6], [0xff; 6]);
+        assert_eq!(packet[6..12], mac);
+        assert_eq!(packet[96..], mac);
+    }
+}
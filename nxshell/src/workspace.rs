@@ -0,0 +1,113 @@
+//! Sessions often opened together, suggested to be saved as a workspace and
+//! opened at once.
+
+use indexmap::IndexMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Group and name of a saved session.
+pub type SessionKey = (String, String);
+
+/// Sessions of the saved workspaces, by name.
+pub type Workspaces = IndexMap<String, Vec<SessionKey>>;
+
+/// Opens of the sessions kept in the usage statistics, the oldest ones are
+/// dropped.
+pub const SESSION_OPEN_LIMIT: usize = 1000;
+/// Sessions opened at most this long after the previous one were opened
+/// together, in milliseconds.
+const BURST_GAP: i64 = 10 * 60 * 1000;
+/// Sessions are suggested once opened together this many times.
+const MIN_OCCURRENCES: usize = 3;
+/// Larger sets aren't suggested, e.g. when all the hosts were checked.
+const MAX_MEMBERS: usize = 8;
+
+/// A saved session opened at `opened_at`, in milliseconds since the epoch.
+#[derive(Debug, Clone)]
+pub struct SessionOpen {
+    pub session: SessionKey,
+    pub opened_at: i64,
+}
+
+/// Sessions opened together most often, at least a few times, except the
+/// sets already saved or dismissed. `opens` are sorted by time.
+pub fn suggest_workspace(
+    opens: &[SessionOpen],
+    known: &HashSet<String>,
+) -> Option<Vec<SessionKey>> {
+    let mut counts: HashMap<BTreeSet<&SessionKey>, usize> = HashMap::new();
+    let mut burst = BTreeSet::new();
+    let mut last = None;
+    for open in opens {
+        if last.is_some_and(|last| open.opened_at - last > BURST_GAP) {
+            count_burst(&mut counts, std::mem::take(&mut burst));
+        }
+        burst.insert(&open.session);
+        last = Some(open.opened_at);
+    }
+    count_burst(&mut counts, burst);
+
+    counts
+        .into_iter()
+        .filter(|(_, count)| *count >= MIN_OCCURRENCES)
+        .map(|(members, count)| (members.into_iter().cloned().collect::<Vec<_>>(), count))
+        .filter(|(members, _)| !known.contains(&members_key(members)))
+        // the most frequent, then the largest, then the first by name
+        .max_by(|(a, a_count), (b, b_count)| {
+            (a_count, a.len())
+                .cmp(&(b_count, b.len()))
+                .then_with(|| b.cmp(a))
+        })
+        .map(|(members, _)| members)
+}
+
+fn count_burst<'a>(
+    counts: &mut HashMap<BTreeSet<&'a SessionKey>, usize>,
+    burst: BTreeSet<&'a SessionKey>,
+) {
+    if (2..=MAX_MEMBERS).contains(&burst.len()) {
+        *counts.entry(burst).or_default() += 1;
+    }
+}
+
+/// Key of a set of sessions, whatever their order, to remember it.
+pub fn members_key(members: &[SessionKey]) -> String {
+    let mut keys: Vec<String> = members
+        .iter()
+        .map(|(group, name)| format!("{group}\u{1f}{name}"))
+        .collect();
+    keys.sort();
+    keys.join("\u{1e}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_opened_together() {
+        let key = |name: &str| ("lab".to_string(), name.to_string());
+        let minute = 60 * 1000;
+        let mut opens = vec![];
+        for day in 0..3 {
+            let start = day * 24 * 60 * minute;
+            for (offset, name) in [(0, "web-1"), (1, "web-2"), (5, "db-1")] {
+                opens.push(SessionOpen {
+                    session: key(name),
+                    opened_at: start + offset * minute,
+                });
+            }
+            // later that day, on its own
+            opens.push(SessionOpen {
+                session: key("backup"),
+                opened_at: start + 60 * minute,
+            });
+        }
+
+        let members = suggest_workspace(&opens, &HashSet::new()).unwrap();
+        assert_eq!(members, [key("db-1"), key("web-1"), key("web-2")]);
+
+        let known = HashSet::from([members_key(&[key("web-2"), key("db-1"), key("web-1")])]);
+        assert_eq!(suggest_workspace(&opens, &known), None);
+        assert_eq!(suggest_workspace(&opens[..8], &HashSet::new()), None);
+    }
+}